@@ -0,0 +1,142 @@
+use tauri::command;
+use std::fs;
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use crate::db::{get_db_connection, run_blocking};
+use crate::errors::AppError;
+use crate::export::escape_csv;
+use crate::ledger::{load_entries, post_ledger};
+use crate::models::JournalEntry;
+use crate::money::Money;
+use crate::validation::{validate_date_format, INVALID_DATE_FORMAT};
+
+/// The accounting packages the journal can be handed to. Each gets the
+/// column layout and date format of its own journal import template.
+#[derive(Clone, Copy)]
+enum AccountingFormat {
+    QuickBooks,
+    Xero,
+    Tally,
+}
+
+impl AccountingFormat {
+    fn parse(format: &str) -> Result<Self, AppError> {
+        match format.trim().to_lowercase().as_str() {
+            "quickbooks" => Ok(AccountingFormat::QuickBooks),
+            "xero" => Ok(AccountingFormat::Xero),
+            "tally" => Ok(AccountingFormat::Tally),
+            other => Err(format!("Unknown accounting format '{}'; use quickbooks, xero or tally", other).into()),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AccountingFormat::QuickBooks => "quickbooks",
+            AccountingFormat::Xero => "xero",
+            AccountingFormat::Tally => "tally",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            AccountingFormat::QuickBooks => "JournalNo,JournalDate,Currency,Memo,Account,Debits,Credits,Description",
+            AccountingFormat::Xero => "*Narration,*Date,Description,*AccountCode,*TaxRate,*Amount",
+            AccountingFormat::Tally => "Voucher Date,Voucher Type,Voucher Number,Ledger Name,Debit,Credit,Narration",
+        }
+    }
+
+    fn date(self, date: &NaiveDate) -> String {
+        match self {
+            AccountingFormat::QuickBooks => date.format("%m/%d/%Y").to_string(),
+            AccountingFormat::Xero => date.format("%d/%m/%Y").to_string(),
+            AccountingFormat::Tally => date.format("%d-%m-%Y").to_string(),
+        }
+    }
+}
+
+fn amount(value: f64) -> String {
+    if value == 0.0 {
+        String::new()
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+/// One row per journal line. Xero wants a single signed amount (debits
+/// positive) and a tax rate on every line; ledger postings are tax exempt.
+fn accounting_csv(format: AccountingFormat, entries: &[JournalEntry], currency: &str) -> Result<String, AppError> {
+    let mut out = String::new();
+    out.push_str(format.header());
+    out.push('\n');
+
+    for entry in entries {
+        let date = NaiveDate::parse_from_str(&entry.entry_date, "%Y-%m-%d")
+            .map_err(|_| format!("Journal entry #{} has an invalid date '{}'", entry.id, entry.entry_date))?;
+        let date = format.date(&date);
+        let memo = escape_csv(&entry.memo);
+        for line in &entry.lines {
+            let row = match format {
+                AccountingFormat::QuickBooks => format!(
+                    "{},{},{},{},{},{},{},{}",
+                    entry.id,
+                    date,
+                    currency,
+                    memo,
+                    escape_csv(&line.account_name),
+                    amount(line.debit),
+                    amount(line.credit),
+                    memo
+                ),
+                AccountingFormat::Xero => format!(
+                    "{},{},{},{},Tax Exempt,{:.2}",
+                    memo,
+                    date,
+                    escape_csv(&line.account_name),
+                    escape_csv(&line.account_code),
+                    line.debit - line.credit
+                ),
+                AccountingFormat::Tally => format!(
+                    "{},Journal,{},{},{},{},{}",
+                    date,
+                    entry.id,
+                    escape_csv(&line.account_name),
+                    amount(line.debit),
+                    amount(line.credit),
+                    memo
+                ),
+            };
+            out.push_str(&row);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+pub fn build_accounting_csv(conn: &Connection, format: &str, start_date: &str, end_date: &str) -> Result<String, AppError> {
+    let format = AccountingFormat::parse(format)?;
+    post_ledger(conn)?;
+    let entries = load_entries(conn, "entry_date BETWEEN ?1 AND ?2", &[&start_date, &end_date])?;
+    accounting_csv(format, &entries, Money::load(conn)?.code())
+}
+
+/// Save the journal between two dates (inclusive) as a CSV ready to import
+/// into QuickBooks, Xero or Tally, and return the file's path. Payments,
+/// checkouts and expenses are posted to the ledger first.
+#[command]
+pub async fn export_accounting_csv(format: String, start_date: String, end_date: String) -> Result<String, AppError> {
+    validate_date_format(&start_date)?;
+    validate_date_format(&end_date)?;
+    if start_date > end_date {
+        return Err(AppError::invalid(INVALID_DATE_FORMAT, "end_date", "End date is before the start date"));
+    }
+    let name = AccountingFormat::parse(&format)?.name();
+
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        let csv = build_accounting_csv(&conn, &format, &start_date, &end_date)?;
+        let path = crate::export_jobs::default_export_path(&format!("journal_{}_{}_{}", name, start_date, end_date), "csv")?;
+        fs::write(&path, csv).map_err(|e| format!("Failed to write journal export: {}", e))?;
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+}
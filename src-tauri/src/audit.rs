@@ -0,0 +1,213 @@
+use tauri::command;
+use rusqlite::{params, params_from_iter, types::ValueRef, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{AuditLogEntry, AuditLogFilter, AuditLogPage};
+use crate::offline_auth::require_session;
+
+// Data-entry commands work without a sign-in; those changes are recorded under this name
+pub const FRONT_DESK_USER: &str = "front_desk";
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+// Audited entities and the table each one lives in
+const ENTITY_TABLES: &[(&str, &str)] = &[
+    ("guest", "customers"),
+    ("guest_profile", "guest_profiles"),
+    ("room", "resources"),
+    ("order", "sales"),
+    ("expense", "expenses"),
+];
+
+fn entity_table(entity: &str) -> Result<&'static str, String> {
+    ENTITY_TABLES
+        .iter()
+        .find(|(name, _)| *name == entity)
+        .map(|(_, table)| *table)
+        .ok_or(format!("'{}' is not an audited entity", entity))
+}
+
+/// Who a change is recorded against: the signed-in user when the frontend
+/// passes a live session token, otherwise the front desk. An expired token
+/// never blocks the change itself.
+pub fn audit_actor(session_token: Option<&str>) -> String {
+    session_token
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .and_then(|token| require_session(token).ok())
+        .map(|session| session.username)
+        .unwrap_or_else(|| FRONT_DESK_USER.to_string())
+}
+
+/// The entity's row as a JSON object keyed by column, or None if it doesn't exist.
+pub fn snapshot(conn: &Connection, entity: &str, id: i64) -> Result<Option<Value>, String> {
+    let table = entity_table(entity)?;
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {} WHERE id = ?1", table))
+        .map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+    stmt.query_row(params![id], |row| row_json(row, &columns))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// A result row as a JSON object keyed by the given column names.
+pub(crate) fn row_json(row: &rusqlite::Row, columns: &[String]) -> rusqlite::Result<Value> {
+    let mut object = Map::new();
+    for (i, column) in columns.iter().enumerate() {
+        let value = match row.get_ref(i)? {
+            ValueRef::Null => Value::Null,
+            ValueRef::Integer(n) => Value::from(n),
+            ValueRef::Real(f) => Value::from(f),
+            ValueRef::Text(t) => Value::from(String::from_utf8_lossy(t).to_string()),
+            ValueRef::Blob(b) => Value::from(format!("<{} bytes>", b.len())),
+        };
+        object.insert(column.clone(), value);
+    }
+    Ok(Value::Object(object))
+}
+
+/// For an update, keep only the columns whose value changed (updated_at aside).
+fn changed_columns(old: Option<Value>, new: Option<Value>) -> (Option<Value>, Option<Value>) {
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let changed: Vec<&String> = new
+                .keys()
+                .filter(|k| k.as_str() != "updated_at" && old.get(k.as_str()) != new.get(k.as_str()))
+                .collect();
+            let pick = |row: &Map<String, Value>| {
+                Value::Object(changed.iter().map(|k| (k.to_string(), row.get(k.as_str()).cloned().unwrap_or(Value::Null))).collect())
+            };
+            (Some(pick(&old)), Some(pick(&new)))
+        }
+        other => other,
+    }
+}
+
+/// Record a create, update or delete of a guest, room, order or expense with
+/// the row before and after. Pass the connection or transaction that made the
+/// change so the entry is only kept if the change is.
+pub fn record_change(
+    conn: &Connection,
+    username: &str,
+    entity: &str,
+    entity_id: i64,
+    action: &str,
+    old: Option<Value>,
+    new: Option<Value>,
+) -> Result<(), String> {
+    let (old, new) = if action == "update" { changed_columns(old, new) } else { (old, new) };
+    let details = match (action, &new) {
+        ("update", Some(Value::Object(changed))) if !changed.is_empty() => {
+            format!("{} #{}: {} changed", entity, entity_id, changed.keys().cloned().collect::<Vec<_>>().join(", "))
+        }
+        ("update", _) => format!("{} #{}: nothing changed", entity, entity_id),
+        ("create", _) => format!("{} #{} created", entity, entity_id),
+        ("delete", _) => format!("{} #{} deleted", entity, entity_id),
+        ("restore", _) => format!("{} #{} restored from the trash", entity, entity_id),
+        _ => format!("{} #{} {}", entity, entity_id, action),
+    };
+
+    conn.execute(
+        "INSERT INTO audit_log (timestamp, username, event_type, ip_address, user_agent, details,
+                                entity, entity_id, action, old_values, new_values)
+         VALUES (?1, ?2, ?3, 'localhost', 'Tauri App', ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            get_current_timestamp(),
+            username,
+            format!("{}_{}", entity, action),
+            details,
+            entity,
+            entity_id,
+            action,
+            old.map(|v| v.to_string()),
+            new.map(|v| v.to_string()),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Audit log entries, newest first, a page at a time. Every filter is
+/// optional; dates are inclusive YYYY-MM-DD. Login events have no entity, so
+/// filtering by entity shows data changes only.
+#[command]
+pub fn get_audit_log(filter: Option<AuditLogFilter>) -> Result<AuditLogPage, AppError> {
+    let filter = filter.unwrap_or_default();
+    let page = filter.page.unwrap_or(1).max(1);
+    let page_size = filter.page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let mut conditions = Vec::new();
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
+    if let Some(username) = filter.username.filter(|u| !u.trim().is_empty()) {
+        conditions.push("username = ?");
+        values.push(username.trim().to_string().into());
+    }
+    if let Some(entity) = filter.entity.filter(|e| !e.trim().is_empty()) {
+        entity_table(entity.trim())?;
+        conditions.push("entity = ?");
+        values.push(entity.trim().to_string().into());
+    }
+    if let Some(entity_id) = filter.entity_id {
+        conditions.push("entity_id = ?");
+        values.push(entity_id.into());
+    }
+    if let Some(action) = filter.action.filter(|a| !a.trim().is_empty()) {
+        conditions.push("action = ?");
+        values.push(action.trim().to_string().into());
+    }
+    if let Some(start_date) = filter.start_date {
+        crate::validation::validate_date_format(&start_date)?;
+        conditions.push("date(timestamp) >= ?");
+        values.push(start_date.into());
+    }
+    if let Some(end_date) = filter.end_date {
+        crate::validation::validate_date_format(&end_date)?;
+        conditions.push("date(timestamp) <= ?");
+        values.push(end_date.into());
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+
+    let conn = get_db_connection()?;
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM audit_log{}", where_clause),
+        params_from_iter(values.iter()),
+        |row| row.get(0),
+    )?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, timestamp, username, event_type, entity, entity_id, action, old_values, new_values, details
+         FROM audit_log{}
+         ORDER BY id DESC
+         LIMIT {} OFFSET {}",
+        where_clause,
+        page_size,
+        (page - 1) * page_size
+    ))?;
+    let json = |text: Option<String>| text.and_then(|t| serde_json::from_str::<Value>(&t).ok());
+    let entries = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok(AuditLogEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                username: row.get(2)?,
+                event_type: row.get(3)?,
+                entity: row.get(4)?,
+                entity_id: row.get(5)?,
+                action: row.get(6)?,
+                old_values: json(row.get(7)?),
+                new_values: json(row.get(8)?),
+                details: row.get(9)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(AuditLogPage { entries, total, page, page_size })
+}
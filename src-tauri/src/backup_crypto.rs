@@ -0,0 +1,144 @@
+use aes_gcm::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::repository::get_setting;
+
+// Encrypted files are MAGIC, a 16-byte salt, a 12-byte nonce, then the
+// AES-256-GCM ciphertext with its tag. The key comes from the passphrase via Argon2id.
+const MAGIC: &[u8] = b"HMENCBK1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key<Aes256Gcm>, String> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive backup key: {}", e))?;
+    Ok(key)
+}
+
+pub(crate) fn encrypt(plain: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt)?);
+    let sealed = cipher.encrypt(&nonce, plain).map_err(|_| "Failed to encrypt backup".to_string())?;
+
+    let mut out = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + sealed.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+pub(crate) fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let body = data.strip_prefix(MAGIC).ok_or("File is not an encrypted backup".to_string())?;
+    if body.len() < SALT_LEN + NONCE_LEN {
+        return Err("Encrypted backup is truncated".to_string());
+    }
+    let (salt, rest) = body.split_at(SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), sealed)
+        .map_err(|_| "Wrong passphrase, or the backup is damaged".to_string())
+}
+
+/// Whether `path` was written by `encrypt_backup`.
+pub fn is_encrypted(path: &Path) -> bool {
+    use std::io::Read;
+    let mut head = [0u8; 8];
+    fs::File::open(path).and_then(|mut f| f.read_exact(&mut head)).is_ok() && head == MAGIC
+}
+
+fn encrypt_file(path: &Path, passphrase: &str) -> Result<(), String> {
+    let plain = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    // Write beside the original and swap, so a failure never leaves half a file
+    let tmp = path.with_extension("enc_tmp");
+    fs::write(&tmp, encrypt(&plain, passphrase)?).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+fn files_under(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            files.extend(files_under(&path)?);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// The JSON export written with a backup database, if there is one.
+fn json_export_for(db_file: &Path) -> Option<PathBuf> {
+    let stamp = db_file.file_stem()?.to_str()?.strip_prefix("business_backup_")?;
+    Some(db_file.with_file_name(format!("business_data_{}.json", stamp))).filter(|p| p.is_file())
+}
+
+/// Encrypt a backup written by `write_backup` in place: the database, its
+/// JSON export and every file in its media folder. Names stay the same so
+/// listing, pruning and uploading don't change.
+pub fn encrypt_backup(db_file: &Path, passphrase: &str) -> Result<(), String> {
+    let mut files = vec![db_file.to_path_buf()];
+    files.extend(json_export_for(db_file));
+    let media = crate::media::media_backup_dir(db_file);
+    if media.is_dir() {
+        files.extend(files_under(&media)?);
+    }
+    for file in files {
+        encrypt_file(&file, passphrase)?;
+    }
+    Ok(())
+}
+
+/// A decrypted copy of a backup, deleted again when this is dropped.
+pub struct DecryptedBackup {
+    pub db_file: PathBuf,
+    dir: PathBuf,
+}
+
+impl Drop for DecryptedBackup {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Decrypt an encrypted backup and its media folder into `dest_dir`, under
+/// the same names.
+pub fn decrypt_backup(db_file: &Path, passphrase: &str, dest_dir: &Path) -> Result<DecryptedBackup, String> {
+    let name = db_file.file_name().ok_or("Backup path has no file name".to_string())?;
+    let data = fs::read(db_file).map_err(|e| format!("Failed to read backup: {}", e))?;
+    let plain = decrypt(&data, passphrase)?;
+    fs::create_dir_all(dest_dir).map_err(|e| format!("Failed to create {}: {}", dest_dir.display(), e))?;
+    let decrypted = DecryptedBackup { db_file: dest_dir.join(name), dir: dest_dir.to_path_buf() };
+    let plain_db = &decrypted.db_file;
+    fs::write(plain_db, plain).map_err(|e| format!("Failed to write decrypted backup: {}", e))?;
+
+    let media = crate::media::media_backup_dir(db_file);
+    if media.is_dir() {
+        let plain_media = crate::media::media_backup_dir(plain_db);
+        for file in files_under(&media)? {
+            let relative = file.strip_prefix(&media).map_err(|e| e.to_string())?;
+            let target = plain_media.join(relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let data = fs::read(&file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+            fs::write(&target, decrypt(&data, passphrase)?).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+        }
+    }
+    Ok(decrypted)
+}
+
+/// The `backup_passphrase` setting; backups are encrypted while it is set.
+pub fn saved_passphrase(conn: &Connection) -> Result<Option<String>, String> {
+    Ok(get_setting(conn, "backup_passphrase")?.filter(|v| !v.is_empty()))
+}
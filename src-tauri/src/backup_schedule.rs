@@ -0,0 +1,175 @@
+use tauri::command;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::BackupSchedule;
+use crate::repository::{get_non_empty_setting, set_setting};
+
+const FREQUENCIES: &[&str] = &["off", "daily", "weekly"];
+const DEFAULT_RETENTION: i64 = 7;
+const MAX_RETENTION: i64 = 365;
+// How often the scheduler wakes up to see whether a backup is due
+const CHECK_INTERVAL_SECS: u64 = 15 * 60;
+// Scheduled backups are named business_backup_scheduled_<timestamp>.db so the
+// restore screen still finds them and pruning never touches manual backups
+const SCHEDULED_STAMP_PREFIX: &str = "scheduled_";
+
+fn period(frequency: &str) -> Option<Duration> {
+    match frequency {
+        "daily" => Some(Duration::days(1)),
+        "weekly" => Some(Duration::weeks(1)),
+        _ => None,
+    }
+}
+
+fn load_schedule(conn: &Connection) -> Result<BackupSchedule, String> {
+    let frequency = get_non_empty_setting(conn, "backup_schedule_frequency")?.unwrap_or_else(|| "off".to_string());
+    let last_run = get_non_empty_setting(conn, "backup_schedule_last_run")?;
+    let next_run = match (period(&frequency), &last_run) {
+        (Some(period), Some(last)) => DateTime::parse_from_rfc3339(last)
+            .ok()
+            .map(|last| (last.with_timezone(&Utc) + period).to_rfc3339()),
+        (Some(_), None) => Some(get_current_timestamp()),
+        (None, _) => None,
+    };
+    Ok(BackupSchedule {
+        frequency,
+        directory: get_non_empty_setting(conn, "backup_schedule_directory")?,
+        retention: get_non_empty_setting(conn, "backup_schedule_retention")?
+            .and_then(|r| r.parse().ok())
+            .unwrap_or(DEFAULT_RETENTION),
+        last_run,
+        last_backup_path: get_non_empty_setting(conn, "backup_schedule_last_path")?,
+        last_error: get_non_empty_setting(conn, "backup_schedule_last_error")?,
+        next_run,
+    })
+}
+
+fn run_backup(conn: &Connection, schedule: &BackupSchedule) -> Result<PathBuf, String> {
+    let dir = schedule.directory.as_deref().ok_or("No backup directory is set".to_string())?;
+    let dir = Path::new(dir);
+    if !dir.is_dir() {
+        return Err(format!("Backup directory {} does not exist", dir.display()));
+    }
+    let stamp = format!("{}{}", SCHEDULED_STAMP_PREFIX, chrono::Local::now().format("%Y%m%d_%H%M%S"));
+    let path = crate::settings::write_backup(dir, &stamp)?;
+    crate::settings::prune_backups(dir, crate::settings::BackupKind::Scheduled, schedule.retention)?;
+    // Already off the UI thread, so upload here; a failed upload doesn't fail the backup
+    if let Err(e) = crate::cloud_backup::upload_backup(&path) {
+        println!("⚠️ Cloud backup upload failed: {}", e);
+    }
+    set_setting(conn, "backup_schedule_last_path", &path.to_string_lossy())?;
+    Ok(path)
+}
+
+/// Take a scheduled backup if one is due. Returns the backup written, if any.
+/// A failed backup is recorded and retried at the next check rather than
+/// waiting a whole period.
+pub fn run_due_backup() -> Result<Option<PathBuf>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let schedule = load_schedule(&conn)?;
+    let due = schedule
+        .next_run
+        .as_deref()
+        .and_then(|next| DateTime::parse_from_rfc3339(next).ok())
+        .is_some_and(|next| next.with_timezone(&Utc) <= Utc::now());
+    if !due {
+        return Ok(None);
+    }
+
+    let outcome = run_backup(&conn, &schedule);
+    // The backup closed the pooled connections, so record the outcome on a fresh one
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    match outcome {
+        Ok(path) => {
+            set_setting(&conn, "backup_schedule_last_run", &get_current_timestamp())?;
+            set_setting(&conn, "backup_schedule_last_error", "")?;
+            Ok(Some(path))
+        }
+        Err(e) => {
+            set_setting(&conn, "backup_schedule_last_error", &format!("{}: {}", get_current_timestamp(), e))?;
+            Err(e)
+        }
+    }
+}
+
+/// Check for due backups in the background for as long as the app runs. The
+/// first check happens at launch, so a backup missed while the PC was off is
+/// taken straight away.
+pub fn start_backup_scheduler() {
+    std::thread::spawn(|| loop {
+        match run_due_backup() {
+            Ok(Some(path)) => println!("Scheduled backup written to {}", path.display()),
+            Ok(None) => {}
+            Err(e) => eprintln!("Scheduled backup failed: {}", e),
+        }
+        std::thread::sleep(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+    });
+}
+
+/// Back up automatically every day or week into `directory`, keeping the
+/// newest `retention` scheduled backups. "off" stops scheduled backups.
+#[command]
+pub fn set_backup_schedule(frequency: String, directory: Option<String>, retention: Option<i64>) -> Result<BackupSchedule, AppError> {
+    let frequency = frequency.trim().to_lowercase();
+    if !FREQUENCIES.contains(&frequency.as_str()) {
+        return Err(format!("Frequency must be one of: {}", FREQUENCIES.join(", ")).into());
+    }
+    let retention = retention.unwrap_or(DEFAULT_RETENTION);
+    if !(1..=MAX_RETENTION).contains(&retention) {
+        return Err(format!("Keep between 1 and {} backups", MAX_RETENTION).into());
+    }
+    let directory = directory.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if let Some(dir) = &directory {
+        if !Path::new(dir).is_dir() {
+            return Err(format!("Backup directory {} does not exist", dir).into());
+        }
+    } else if frequency != "off" {
+        return Err(AppError::invalid(crate::validation::EMPTY_FIELD, "directory", "Choose a folder for scheduled backups"));
+    }
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    set_setting(&tx, "backup_schedule_frequency", &frequency)?;
+    set_setting(&tx, "backup_schedule_directory", directory.as_deref().unwrap_or(""))?;
+    set_setting(&tx, "backup_schedule_retention", &retention.to_string())?;
+    tx.commit()?;
+    Ok(load_schedule(&conn)?)
+}
+
+#[command]
+pub fn get_backup_schedule() -> Result<BackupSchedule, AppError> {
+    let conn = get_db_connection()?;
+    Ok(load_schedule(&conn)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+
+    fn schedule_into(db: &TestDb, frequency: &str) -> PathBuf {
+        let dir = db.dir().join("scheduled");
+        std::fs::create_dir_all(&dir).unwrap();
+        set_backup_schedule(frequency.to_string(), Some(dir.to_string_lossy().to_string()), Some(1)).unwrap();
+        dir
+    }
+
+    #[test]
+    fn a_due_backup_runs_once_per_period() {
+        let db = TestDb::new();
+        let dir = schedule_into(&db, "daily");
+        let first = run_due_backup().unwrap();
+        assert!(first.as_ref().is_some_and(|p| p.is_file() && p.starts_with(&dir)), "a due scheduled backup was not written: {:?}", first);
+        assert!(run_due_backup().unwrap().is_none(), "a second backup ran before the next one was due");
+    }
+
+    #[test]
+    fn nothing_runs_while_the_schedule_is_off() {
+        let db = TestDb::new();
+        schedule_into(&db, "off");
+        assert!(run_due_backup().unwrap().is_none(), "a backup ran with the schedule off");
+    }
+}
@@ -0,0 +1,122 @@
+use tauri::command;
+use std::collections::HashMap;
+use chrono::{Datelike, NaiveDate};
+use rusqlite::{params, Connection};
+use crate::db::{get_current_timestamp, get_db_connection};
+use crate::errors::AppError;
+use crate::models::{BudgetLine, BudgetVsActualReport, ExpenseBudget};
+use crate::simple_commands::to_cents;
+use crate::validation::{INVALID_DATE_FORMAT, NEGATIVE_AMOUNT};
+
+fn load_budgets(conn: &Connection) -> Result<Vec<ExpenseBudget>, AppError> {
+    let mut stmt = conn.prepare("SELECT category, monthly_amount, updated_at FROM expense_budgets ORDER BY category")?;
+    let budgets = stmt
+        .query_map([], |row| {
+            Ok(ExpenseBudget { category: row.get(0)?, monthly_amount: row.get(1)?, updated_at: row.get(2)? })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(budgets)
+}
+
+#[command]
+pub fn get_expense_budgets() -> Result<Vec<ExpenseBudget>, AppError> {
+    let conn = get_db_connection()?;
+    load_budgets(&conn)
+}
+
+/// Set the monthly budget for one of the managed expense categories.
+#[command]
+pub fn set_expense_budget(category: String, monthly_amount: f64) -> Result<Vec<ExpenseBudget>, AppError> {
+    if !monthly_amount.is_finite() || monthly_amount < 0.0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "monthly_amount", "Budget must be >= 0"));
+    }
+
+    let conn = get_db_connection()?;
+    let category = crate::expense_categories::resolve_category(&conn, &category)?;
+    conn.execute(
+        "INSERT INTO expense_budgets (category, monthly_amount, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(category) DO UPDATE SET monthly_amount = excluded.monthly_amount, updated_at = excluded.updated_at",
+        params![category, to_cents(monthly_amount) as f64 / 100.0, get_current_timestamp()],
+    )?;
+    load_budgets(&conn)
+}
+
+#[command]
+pub fn delete_expense_budget(category: String) -> Result<Vec<ExpenseBudget>, AppError> {
+    let conn = get_db_connection()?;
+    conn.execute("DELETE FROM expense_budgets WHERE category = ?1", params![category.trim()])?;
+    load_budgets(&conn)
+}
+
+/// Business expenses for the month against each category's budget. Owner
+/// drawings are left out; categories spent on without a budget are listed
+/// with no variance so nothing goes unseen.
+pub fn build_budget_vs_actual(conn: &Connection, year: i32, month: u32) -> Result<BudgetVsActualReport, AppError> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| AppError::invalid(INVALID_DATE_FORMAT, "month", format!("{}-{:02} is not a valid month", year, month)))?;
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .ok_or("Month is out of range")?;
+
+    // Spend in cents per lower-cased category, keeping the first spelling seen
+    let mut spent: HashMap<String, (String, i64)> = HashMap::new();
+    let mut stmt = conn.prepare(
+        "SELECT TRIM(category), SUM(amount) FROM expenses
+         WHERE is_drawing = 0 AND deleted_at IS NULL AND date >= ?1 AND date < ?2
+         GROUP BY TRIM(category) ORDER BY TRIM(category)",
+    )?;
+    let rows = stmt.query_map(params![first.to_string(), next.to_string()], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+    for row in rows {
+        let (category, amount) = row?;
+        spent.entry(category.to_lowercase()).or_insert_with(|| (category, 0)).1 += to_cents(amount);
+    }
+
+    let mut lines = Vec::new();
+    let mut total_budget = 0i64;
+    for budget in load_budgets(conn)? {
+        let budget_cents = to_cents(budget.monthly_amount);
+        let actual = spent.remove(&budget.category.to_lowercase()).map(|(_, cents)| cents).unwrap_or(0);
+        total_budget += budget_cents;
+        lines.push(BudgetLine {
+            category: budget.category,
+            budget: Some(budget_cents as f64 / 100.0),
+            actual: actual as f64 / 100.0,
+            variance: Some((actual - budget_cents) as f64 / 100.0),
+            percent_used: (budget_cents > 0).then(|| actual as f64 / budget_cents as f64 * 100.0),
+            over_budget: actual > budget_cents,
+        });
+    }
+    lines.sort_by(|a, b| b.variance.partial_cmp(&a.variance).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut unbudgeted: Vec<(String, i64)> = spent.into_values().collect();
+    unbudgeted.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    for (category, actual) in unbudgeted {
+        lines.push(BudgetLine {
+            category,
+            budget: None,
+            actual: actual as f64 / 100.0,
+            variance: None,
+            percent_used: None,
+            over_budget: false,
+        });
+    }
+
+    let total_actual = lines.iter().map(|l| to_cents(l.actual)).sum::<i64>();
+    Ok(BudgetVsActualReport {
+        year: first.year(),
+        month: first.month(),
+        lines,
+        total_budget: total_budget as f64 / 100.0,
+        total_actual: total_actual as f64 / 100.0,
+        total_variance: (total_actual - total_budget) as f64 / 100.0,
+    })
+}
+
+#[command]
+pub fn budget_vs_actual(year: i32, month: u32) -> Result<BudgetVsActualReport, AppError> {
+    let conn = get_db_connection()?;
+    build_budget_vs_actual(&conn, year, month)
+}
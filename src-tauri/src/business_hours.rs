@@ -0,0 +1,340 @@
+use tauri::command;
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime};
+use rusqlite::{params, Connection};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::models::{AfterHoursRow, AfterHoursSummary, BusinessHours};
+use crate::offline_auth::require_session;
+use crate::validation::AFTER_HOURS_RESTRICTED;
+use crate::repository::{get_setting, set_setting};
+
+const WEEKDAYS: &[&str] = &["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"];
+const AFTER_HOURS_OVERRIDE_ROLES: &[&str] = &["manager", "admin"];
+
+fn parse_weekday(day: &str) -> Result<u32, String> {
+    let day = day.trim().to_lowercase();
+    WEEKDAYS
+        .iter()
+        .position(|d| *d == day || d[..3] == day)
+        .map(|i| i as u32)
+        .ok_or(format!("Unknown weekday '{}'", day))
+}
+
+pub(crate) fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{}'. Expected HH:MM", value))
+}
+
+fn load_hours(conn: &Connection) -> Result<Vec<(u32, NaiveTime, NaiveTime)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT weekday, open_time, close_time FROM business_hours ORDER BY weekday")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut hours = Vec::new();
+    for row in rows {
+        let (weekday, open, close) = row.map_err(|e| e.to_string())?;
+        hours.push((weekday, parse_time(&open)?, parse_time(&close)?));
+    }
+    Ok(hours)
+}
+
+/// Whether `at` (local time) falls outside the configured hours.
+///
+/// Each weekday's window starts on that day; when close <= open it runs past
+/// midnight into the next calendar day, so 01:30 on Saturday belongs to
+/// Friday's 17:00-02:00 business day. A weekday with no row is closed. With no
+/// hours configured at all nothing is after hours.
+pub fn is_after_hours(conn: &Connection, at: NaiveDateTime) -> Result<bool, String> {
+    let hours = load_hours(conn)?;
+    if hours.is_empty() {
+        return Ok(false);
+    }
+
+    let open_now = hours.iter().any(|&(weekday, open, close)| window_covers(weekday, open, close, at));
+    Ok(!open_now)
+}
+
+/// Whether the window opening at `open` on `weekday` (0 = Monday) covers `at`.
+/// When close <= open the window runs past midnight into the next day.
+pub(crate) fn window_covers(weekday: u32, open: NaiveTime, close: NaiveTime, at: NaiveDateTime) -> bool {
+    let time = at.time();
+    let today = at.date().weekday().num_days_from_monday();
+    let yesterday = (at.date() - Duration::days(1)).weekday().num_days_from_monday();
+    let crosses_midnight = close <= open;
+    if weekday == today {
+        // Same-day part of today's window
+        time >= open && (crosses_midnight || time < close)
+    } else if weekday == yesterday {
+        // After-midnight tail of yesterday's window
+        crosses_midnight && time < close
+    } else {
+        false
+    }
+}
+
+fn strict_mode(conn: &Connection) -> Result<bool, String> {
+    Ok(get_setting(conn, "after_hours_strict")?.as_deref() == Some("1"))
+}
+
+/// After-hours flag for a transaction being entered now. In strict mode an
+/// after-hours entry needs a manager or admin session, otherwise it fails
+/// with AFTER_HOURS_RESTRICTED.
+pub fn check_after_hours(conn: &Connection, session_token: Option<&str>) -> Result<bool, String> {
+    let after_hours = is_after_hours(conn, chrono::Local::now().naive_local())?;
+    if !after_hours || !strict_mode(conn)? {
+        return Ok(after_hours);
+    }
+
+    let session = match session_token {
+        Some(token) => require_session(token)?,
+        None => return Err(format!("{}: a manager must sign in to record transactions outside business hours", AFTER_HOURS_RESTRICTED)),
+    };
+    if !AFTER_HOURS_OVERRIDE_ROLES.contains(&session.role.as_str()) {
+        return Err(format!("{}: a manager must sign in to record transactions outside business hours", AFTER_HOURS_RESTRICTED));
+    }
+    Ok(true)
+}
+
+/// Set one weekday's hours as HH:MM. Passing neither time marks the day closed.
+#[command]
+pub fn set_business_hours(day: String, open: Option<String>, close: Option<String>) -> Result<String, String> {
+    let weekday = parse_weekday(&day)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+
+    match (open, close) {
+        (Some(open), Some(close)) => {
+            let (open, close) = (parse_time(&open)?, parse_time(&close)?);
+            if open == close {
+                return Err("Opening and closing time cannot be the same".to_string());
+            }
+            conn.execute(
+                "INSERT OR REPLACE INTO business_hours (weekday, open_time, close_time, updated_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![weekday, open.format("%H:%M").to_string(), close.format("%H:%M").to_string(), get_current_timestamp()],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(format!("Business hours for {} saved", WEEKDAYS[weekday as usize]))
+        }
+        (None, None) => {
+            conn.execute("DELETE FROM business_hours WHERE weekday = ?1", params![weekday])
+                .map_err(|e| e.to_string())?;
+            Ok(format!("{} marked as closed", WEEKDAYS[weekday as usize]))
+        }
+        _ => Err("Both opening and closing time are required".to_string()),
+    }
+}
+
+#[command]
+pub fn get_business_hours() -> Result<Vec<BusinessHours>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let hours = load_hours(&conn)?;
+
+    Ok(WEEKDAYS
+        .iter()
+        .enumerate()
+        .map(|(i, day)| {
+            let window = hours.iter().find(|(weekday, _, _)| *weekday as usize == i);
+            BusinessHours {
+                day: day.to_string(),
+                open: window.map(|(_, open, _)| open.format("%H:%M").to_string()),
+                close: window.map(|(_, _, close)| close.format("%H:%M").to_string()),
+                crosses_midnight: window.is_some_and(|(_, open, close)| close <= open),
+            }
+        })
+        .collect())
+}
+
+#[command]
+pub fn set_after_hours_strict(enabled: bool) -> Result<String, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    set_setting(&conn, "after_hours_strict", if enabled { "1" } else { "0" })?;
+    Ok(if enabled {
+        "After-hours entries now need a manager".to_string()
+    } else {
+        "After-hours entries are flagged only".to_string()
+    })
+}
+
+#[command]
+pub fn get_after_hours_strict() -> Result<bool, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    strict_mode(&conn)
+}
+
+/// Number of orders and check-ins flagged after hours between two timestamps.
+/// Orders in the trash don't count.
+pub fn count_after_hours(conn: &Connection, from: &str, to: &str) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT (SELECT COUNT(*) FROM sales WHERE after_hours = 1 AND deleted_at IS NULL AND created_at >= ?1 AND created_at <= ?2)
+              + (SELECT COUNT(*) FROM customers WHERE after_hours = 1 AND created_at >= ?1 AND created_at <= ?2)",
+        params![from, to],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// After-hours orders and check-ins in a date range, for the security review.
+#[command]
+pub fn after_hours_summary(start_date: String, end_date: String) -> Result<AfterHoursSummary, String> {
+    crate::validation::validate_date_format(&start_date)?;
+    crate::validation::validate_date_format(&end_date)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT 'order', id, created_at, COALESCE(customer_name, customer_type), total_amount
+             FROM sales WHERE after_hours = 1 AND deleted_at IS NULL AND date(created_at) BETWEEN ?1 AND ?2
+             UNION ALL
+             SELECT 'check_in', id, created_at, name, NULL
+             FROM customers WHERE after_hours = 1 AND date(created_at) BETWEEN ?1 AND ?2
+             ORDER BY 3",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![start_date, end_date], |row| {
+            Ok(AfterHoursRow {
+                kind: row.get(0)?,
+                id: row.get(1)?,
+                created_at: row.get(2)?,
+                description: row.get(3)?,
+                amount: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(AfterHoursSummary {
+        start_date,
+        end_date,
+        orders: rows.iter().filter(|r| r.kind == "order").count() as i64,
+        check_ins: rows.iter().filter(|r| r.kind == "check_in").count() as i64,
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Weekday};
+    use crate::simple_commands::insert_food_order;
+    use crate::test_support::{admin_session, check_in, count, order_item, TestDb};
+
+    fn at(day: NaiveDate, time: &str) -> NaiveDateTime {
+        day.and_time(parse_time(time).expect("a valid time"))
+    }
+
+    fn late_order(session_token: Option<String>) -> Result<i64, crate::errors::AppError> {
+        let items = vec![order_item("Nightcap", 4.0, 1)];
+        insert_food_order(None, "walkin".to_string(), Some("Late order".to_string()), items, session_token, None, None)
+            .map(|(order_id, _)| order_id)
+    }
+
+    fn flagged(table: &str, id: i64) -> bool {
+        count(&format!("SELECT COUNT(*) FROM {} WHERE id = ?1 AND after_hours = 1", table), id) == 1
+    }
+
+    // Every day open from `open` to `close` (times of day only)
+    fn open_every_day(open: NaiveDateTime, close: NaiveDateTime) {
+        for day in WEEKDAYS {
+            set_business_hours(day.to_string(), Some(open.format("%H:%M").to_string()), Some(close.format("%H:%M").to_string()))
+                .expect("set the day's hours");
+        }
+    }
+
+    #[test]
+    fn hours_past_midnight_belong_to_the_day_that_opened() {
+        let _db = TestDb::new();
+        let conn = get_db_connection().unwrap();
+        // Friday's business day runs from 17:00 to 02:00 on Saturday; the other days are closed
+        set_business_hours("friday".to_string(), Some("17:00".to_string()), Some("02:00".to_string())).unwrap();
+        let friday = NaiveDate::from_isoywd_opt(2026, 42, Weekday::Fri).unwrap();
+        let saturday = friday + Duration::days(1);
+        for (when, expected) in [
+            (at(friday, "01:00"), true),
+            (at(friday, "16:59"), true),
+            (at(friday, "17:00"), false),
+            (at(friday, "23:59"), false),
+            (at(saturday, "00:00"), false),
+            (at(saturday, "01:59"), false),
+            (at(saturday, "02:00"), true),
+            (at(saturday, "17:30"), true),
+        ] {
+            assert_eq!(is_after_hours(&conn, when).unwrap(), expected, "after hours at {}", when);
+        }
+        let hours = get_business_hours().unwrap();
+        assert!(hours.iter().any(|h| h.day == "friday" && h.crosses_midnight), "business hours listed as {:?}", hours);
+        assert_eq!(hours.iter().filter(|h| h.open.is_some()).count(), 1, "business hours listed as {:?}", hours);
+
+        // The small hours go with the evening before, not the calendar day
+        set_business_hours("friday".to_string(), None, None).unwrap();
+        set_business_hours("saturday".to_string(), Some("17:00".to_string()), Some("02:00".to_string())).unwrap();
+        assert!(is_after_hours(&conn, at(saturday, "01:30")).unwrap(), "01:30 on Saturday counted in Saturday evening's hours");
+        assert!(!is_after_hours(&conn, at(friday + Duration::days(2), "01:30")).unwrap(), "01:30 on Sunday not counted in Saturday's hours");
+    }
+
+    #[test]
+    fn entries_outside_business_hours_are_flagged_and_summarised() {
+        let _db = TestDb::new();
+        let now = chrono::Local::now().naive_local();
+        open_every_day(now + Duration::hours(1), now + Duration::hours(2));
+        let order = late_order(None).unwrap();
+        let walk_in = check_in("Late arrival", None);
+        assert!(flagged("sales", order) && flagged("customers", walk_in), "entries outside business hours not flagged");
+
+        let day = chrono::Local::now().date_naive();
+        let (yesterday, tomorrow) = ((day - Duration::days(1)).to_string(), (day + Duration::days(1)).to_string());
+        let summary = after_hours_summary(yesterday, tomorrow).unwrap();
+        assert!(summary.rows.iter().any(|r| r.kind == "order" && r.id == order), "summary misses the after-hours order");
+        assert!(summary.rows.iter().any(|r| r.kind == "check_in" && r.id == walk_in), "summary misses the after-hours check-in");
+        assert_eq!((summary.orders, summary.check_ins), (1, 1));
+    }
+
+    #[test]
+    fn trashed_orders_drop_out_of_the_after_hours_count() {
+        let _db = TestDb::new();
+        let now = chrono::Local::now().naive_local();
+        open_every_day(now + Duration::hours(1), now + Duration::hours(2));
+        let order = late_order(None).unwrap();
+        check_in("Late arrival", None);
+        let conn = get_db_connection().unwrap();
+        let (from, to) = ("2000-01-01 00:00:00", "2999-12-31 23:59:59");
+        assert_eq!(count_after_hours(&conn, from, to).unwrap(), 2, "after-hours order and check-in not both counted");
+
+        crate::simple_commands::delete_food_order(order, None).unwrap();
+        assert_eq!(count_after_hours(&conn, from, to).unwrap(), 1, "trashed order still counted after hours");
+        let day = chrono::Local::now().date_naive();
+        let summary = after_hours_summary((day - Duration::days(1)).to_string(), (day + Duration::days(1)).to_string()).unwrap();
+        assert!(!summary.rows.iter().any(|r| r.kind == "order" && r.id == order), "summary lists the trashed order");
+    }
+
+    #[test]
+    fn strict_mode_needs_a_manager_after_hours() {
+        let _db = TestDb::new();
+        let token = admin_session();
+        let now = chrono::Local::now().naive_local();
+        open_every_day(now + Duration::hours(1), now + Duration::hours(2));
+        set_after_hours_strict(true).unwrap();
+        let refused = late_order(None);
+        assert!(
+            refused.is_err_and(|e| e.code() == AFTER_HOURS_RESTRICTED),
+            "strict mode took an after-hours order without a manager"
+        );
+        let approved = late_order(Some(token)).unwrap();
+        assert!(flagged("sales", approved), "after-hours order approved by a manager not flagged");
+    }
+
+    #[test]
+    fn entries_inside_business_hours_are_not_flagged() {
+        let _db = TestDb::new();
+        // Open since an hour ago, whether or not that window crosses midnight
+        let now = chrono::Local::now().naive_local();
+        open_every_day(now - Duration::hours(1), now + Duration::hours(1));
+        let order = late_order(None).unwrap();
+        assert!(!flagged("sales", order), "order inside business hours flagged as after hours");
+        let guest = check_in("Evening arrival", None);
+        assert!(!flagged("customers", guest), "check-in inside business hours flagged as after hours");
+    }
+}
@@ -0,0 +1,351 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::{get_db_connection, get_current_timestamp, log_audit_event};
+use crate::models::{CheckoutHold, GuestAdjustment};
+use crate::offline_auth::require_session;
+use crate::validation::{CHECKOUT_HOLD_ACTIVE, UNAUTHORIZED};
+
+const PLACE_HOLD_ROLES: &[&str] = &["housekeeping", "manager", "admin"];
+const RESOLVE_HOLD_ROLES: &[&str] = &["manager", "admin"];
+
+const HOLD_COLUMNS: &str = "id, guest_id, reason, amount_estimate, status, placed_by, placed_at,
+                            resolved_by, resolved_at, adjustment_id";
+
+fn map_hold(row: &rusqlite::Row) -> rusqlite::Result<CheckoutHold> {
+    Ok(CheckoutHold {
+        id: row.get(0)?,
+        guest_id: row.get(1)?,
+        reason: row.get(2)?,
+        amount_estimate: row.get(3)?,
+        status: row.get(4)?,
+        placed_by: row.get(5)?,
+        placed_at: row.get(6)?,
+        resolved_by: row.get(7)?,
+        resolved_at: row.get(8)?,
+        adjustment_id: row.get(9)?,
+    })
+}
+
+/// Open holds, for one guest or (with None) for every guest.
+pub fn open_holds(conn: &Connection, guest_id: Option<i64>) -> Result<Vec<CheckoutHold>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM checkout_holds
+             WHERE status = 'open' AND (?1 IS NULL OR guest_id = ?1)
+             ORDER BY placed_at, id",
+            HOLD_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map(params![guest_id], map_hold).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Fail with CHECKOUT_HOLD_ACTIVE, listing the open holds, if the guest has any.
+pub fn ensure_no_open_holds(conn: &Connection, guest_id: i64) -> Result<(), String> {
+    let holds = open_holds(conn, Some(guest_id))?;
+    if holds.is_empty() {
+        return Ok(());
+    }
+
+    let money = crate::money::Money::load(conn)?;
+    let listed = holds
+        .iter()
+        .map(|h| match h.amount_estimate {
+            Some(estimate) => format!("#{} {} (est. {})", h.id, h.reason, money.format(estimate, 2)),
+            None => format!("#{} {}", h.id, h.reason),
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(format!("{}: {}", CHECKOUT_HOLD_ACTIVE, listed))
+}
+
+/// Sum of folio adjustments posted to a guest.
+pub fn guest_adjustments_total(conn: &Connection, guest_id: i64) -> Result<f64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(amount), 0) FROM guest_adjustments WHERE guest_id = ?1",
+        params![guest_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub fn guest_adjustments(conn: &Connection, guest_id: i64) -> Result<Vec<GuestAdjustment>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, guest_id, description, amount, source, created_by, created_at
+             FROM guest_adjustments WHERE guest_id = ?1 ORDER BY created_at, id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![guest_id], |row| {
+            Ok(GuestAdjustment {
+                id: row.get(0)?,
+                guest_id: row.get(1)?,
+                description: row.get(2)?,
+                amount: row.get(3)?,
+                source: row.get(4)?,
+                created_by: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Load an open hold, or explain why it can't be resolved.
+fn load_open_hold(conn: &Connection, hold_id: i64) -> Result<CheckoutHold, String> {
+    let hold = conn
+        .query_row(
+            &format!("SELECT {} FROM checkout_holds WHERE id = ?1", HOLD_COLUMNS),
+            params![hold_id],
+            map_hold,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Checkout hold not found".to_string())?;
+
+    if hold.status != "open" {
+        return Err(format!("Checkout hold is already {}", hold.status));
+    }
+    Ok(hold)
+}
+
+#[command]
+pub fn place_checkout_hold(
+    session_token: String,
+    guest_id: i64,
+    reason: String,
+    amount_estimate: Option<f64>,
+) -> Result<CheckoutHold, String> {
+    let session = require_session(&session_token)?;
+    if !PLACE_HOLD_ROLES.contains(&session.role.as_str()) {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err("Hold reason cannot be empty".to_string());
+    }
+    if let Some(estimate) = amount_estimate {
+        if !estimate.is_finite() || estimate < 0.0 {
+            return Err("Estimated amount must be >= 0".to_string());
+        }
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let is_active: bool = conn
+        .query_row(
+            "SELECT 1 FROM customers WHERE id = ?1 AND status = 'active'",
+            params![guest_id],
+            |_| Ok(true),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+    if !is_active {
+        return Err("Active guest not found".to_string());
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO checkout_holds (guest_id, reason, amount_estimate, placed_by, placed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![guest_id, reason, amount_estimate, session.username, get_current_timestamp()],
+    )
+    .map_err(|e| e.to_string())?;
+    let hold_id = tx.last_insert_rowid();
+
+    log_audit_event(
+        &tx,
+        &session.username,
+        "checkout_hold_placed",
+        &format!("hold #{} on guest {}: {}", hold_id, guest_id, reason),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let hold = tx
+        .query_row(
+            &format!("SELECT {} FROM checkout_holds WHERE id = ?1", HOLD_COLUMNS),
+            params![hold_id],
+            map_hold,
+        )
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(hold)
+}
+
+/// Lift a hold without charging the guest.
+#[command]
+pub fn release_checkout_hold(session_token: String, hold_id: i64) -> Result<String, String> {
+    let session = require_session(&session_token)?;
+    if !RESOLVE_HOLD_ROLES.contains(&session.role.as_str()) {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let hold = load_open_hold(&conn, hold_id)?;
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE checkout_holds SET status = 'released', resolved_by = ?1, resolved_at = ?2 WHERE id = ?3",
+        params![session.username, get_current_timestamp(), hold_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    log_audit_event(
+        &tx,
+        &session.username,
+        "checkout_hold_released",
+        &format!("hold #{} on guest {} placed by {}: {}", hold.id, hold.guest_id, hold.placed_by, hold.reason),
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok("Checkout hold released".to_string())
+}
+
+/// Resolve a hold by posting the final amount to the guest's folio as an adjustment.
+#[command]
+pub fn convert_hold_to_charge(session_token: String, hold_id: i64, final_amount: f64) -> Result<GuestAdjustment, String> {
+    let session = require_session(&session_token)?;
+    if !RESOLVE_HOLD_ROLES.contains(&session.role.as_str()) {
+        return Err(UNAUTHORIZED.to_string());
+    }
+    if !final_amount.is_finite() || final_amount < 0.0 {
+        return Err("Charge amount must be >= 0".to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let hold = load_open_hold(&conn, hold_id)?;
+    let now = get_current_timestamp();
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO guest_adjustments (guest_id, description, amount, source, created_by, created_at)
+         VALUES (?1, ?2, ?3, 'checkout_hold', ?4, ?5)",
+        params![hold.guest_id, format!("Damage: {}", hold.reason), final_amount, session.username, now],
+    )
+    .map_err(|e| e.to_string())?;
+    let adjustment_id = tx.last_insert_rowid();
+
+    tx.execute(
+        "UPDATE checkout_holds SET status = 'converted', resolved_by = ?1, resolved_at = ?2, adjustment_id = ?3
+         WHERE id = ?4",
+        params![session.username, now, adjustment_id, hold_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    log_audit_event(
+        &tx,
+        &session.username,
+        "checkout_hold_converted",
+        &format!(
+            "hold #{} on guest {} placed by {} charged {:.2}: {}",
+            hold.id, hold.guest_id, hold.placed_by, final_amount, hold.reason
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(GuestAdjustment {
+        id: adjustment_id,
+        guest_id: hold.guest_id,
+        description: format!("Damage: {}", hold.reason),
+        amount: final_amount,
+        source: "checkout_hold".to_string(),
+        created_by: Some(session.username),
+        created_at: now,
+    })
+}
+
+#[command]
+pub fn get_checkout_holds(guest_id: i64, include_resolved: bool) -> Result<Vec<CheckoutHold>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    if !include_resolved {
+        return open_holds(&conn, Some(guest_id));
+    }
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM checkout_holds WHERE guest_id = ?1 ORDER BY placed_at, id",
+            HOLD_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![guest_id], map_hold).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_guest_adjustments(guest_id: i64) -> Result<Vec<GuestAdjustment>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    guest_adjustments(&conn, guest_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::checkout_guest;
+    use crate::test_support::{add_test_room, admin_session, check_in, same_amount, TestDb, ADMIN};
+
+    fn hold_events() -> i64 {
+        let conn = get_db_connection().unwrap();
+        conn.query_row(
+            "SELECT COUNT(*) FROM audit_log WHERE username = ?1
+             AND event_type IN ('checkout_hold_placed', 'checkout_hold_converted', 'checkout_hold_released')",
+            params![ADMIN],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn a_hold_blocks_checkout_until_it_is_charged() {
+        let _db = TestDb::new();
+        let token = admin_session();
+        let guest = check_in("Damage guest", Some(add_test_room("101")));
+        let hold = place_checkout_hold(token.clone(), guest, "Broken lamp".to_string(), Some(45.0)).unwrap();
+        let blocked = checkout_guest(guest, None, None, Some("cash".to_string()), None, None);
+        assert!(
+            blocked.is_err_and(|e| {
+                let e = e.to_string();
+                e.contains(CHECKOUT_HOLD_ACTIVE) && e.contains(&format!("#{} Broken lamp", hold.id))
+            }),
+            "checkout with an open hold was not refused with the hold listed"
+        );
+
+        let charge = convert_hold_to_charge(token, hold.id, 40.0).unwrap();
+        assert!(charge.description == "Damage: Broken lamp" && same_amount(charge.amount, 40.0), "hold converted to {:?}", charge);
+        let resolved = get_checkout_holds(guest, true).unwrap();
+        assert!(
+            resolved.len() == 1 && resolved[0].status == "converted" && resolved[0].adjustment_id == Some(charge.id)
+                && resolved[0].resolved_by.as_deref() == Some(ADMIN),
+            "converted hold recorded as {:?}", resolved
+        );
+
+        let invoice = crate::print_templates::build_final_invoice_html(guest, None).unwrap();
+        let conn = get_db_connection().unwrap();
+        let amount = crate::money::Money::load(&conn).unwrap().format(40.0, 0);
+        let line = invoice.split("Damage: Broken lamp").nth(1).and_then(|rest| rest.split("table-row").next());
+        assert!(line.is_some_and(|line| line.contains(&format!(">{}</div>", amount))), "invoice has no {} line for the damage charge", amount);
+        let totals = checkout_guest(guest, None, None, Some("cash".to_string()), None, None).unwrap();
+        assert!(same_amount(totals.adjustments, 40.0), "checkout billed {:.2} in adjustments, expected 40.00", totals.adjustments);
+        assert_eq!(hold_events(), 2, "placing and converting the hold weren't both audit-logged");
+    }
+
+    #[test]
+    fn a_released_hold_lets_the_guest_leave_without_a_charge() {
+        let _db = TestDb::new();
+        let token = admin_session();
+        let guest = check_in("Cleared guest", Some(add_test_room("102")));
+        let hold = place_checkout_hold(token.clone(), guest, "Stained towel".to_string(), None).unwrap();
+        assert!(checkout_guest(guest, None, None, Some("cash".to_string()), None, None).is_err(), "checkout went through an open hold");
+        release_checkout_hold(token.clone(), hold.id).unwrap();
+        assert!(release_checkout_hold(token, hold.id).is_err_and(|e| e.contains("already released")), "a hold was released twice");
+        let totals = checkout_guest(guest, None, None, Some("cash".to_string()), None, None).unwrap();
+        assert!(same_amount(totals.adjustments, 0.0), "released hold still charged {:.2}", totals.adjustments);
+        assert_eq!(hold_events(), 2, "placing and releasing the hold weren't both audit-logged");
+    }
+}
@@ -0,0 +1,287 @@
+use tauri::command;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, get_current_timestamp, log_audit_event};
+use crate::errors::AppError;
+use crate::events;
+use crate::models::ReopenedStay;
+use crate::repository::{get_parsed_setting, set_setting};
+use crate::simple_commands::{business_date_today, to_cents};
+use crate::validation::{GUEST_ANONYMIZED, GUEST_NOT_FOUND};
+
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+
+fn window_hours(conn: &rusqlite::Connection) -> Result<i64, String> {
+    Ok(get_parsed_setting::<i64>(conn, "checkout_reopen_window_hours")?.unwrap_or(DEFAULT_WINDOW_HOURS))
+}
+
+/// Hours after a checkout during which it can still be reopened; 0 turns reopening off.
+#[command]
+pub fn get_checkout_reopen_window() -> Result<i64, AppError> {
+    let conn = get_db_connection()?;
+    Ok(window_hours(&conn)?)
+}
+
+#[command]
+pub fn set_checkout_reopen_window(hours: i64) -> Result<i64, AppError> {
+    if hours < 0 {
+        return Err("The reopen window can't be negative".into());
+    }
+    let conn = get_db_connection()?;
+    set_setting(&conn, "checkout_reopen_window_hours", &hours.to_string())?;
+    Ok(hours)
+}
+
+/// Refund what is left of each checkout payment of the stay with a negative
+/// payment pointing at it. Returns the total refunded.
+fn reverse_checkout_payments(conn: &Connection, guest_id: i64) -> Result<f64, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT p.id, p.method, p.amount + COALESCE((SELECT SUM(r.amount) FROM payments r WHERE r.refund_of = p.id), 0)
+         FROM payments p
+         WHERE p.guest_id = ?1 AND p.is_deposit = 0 AND p.note = 'Checkout' AND p.refund_of IS NULL",
+    )?;
+    let payments = stmt
+        .query_map(params![guest_id], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let now = get_current_timestamp();
+    let mut reversed = 0;
+    for (payment_id, method, left) in payments {
+        let left = to_cents(left);
+        if left <= 0 {
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO payments (guest_id, amount, method, paid_at, note, is_deposit, refund_of, created_at)
+             VALUES (?1, ?2, ?3, ?4, 'Checkout reopened', 0, ?5, ?4)",
+            params![guest_id, -(left as f64 / 100.0), method, now, payment_id],
+        )?;
+        reversed += left;
+    }
+    Ok(reversed as f64 / 100.0)
+}
+
+/// Post a negative charge against each company charge of the stay that isn't
+/// reversed yet. Returns the total taken off company accounts.
+fn reverse_company_charges(conn: &Connection, guest_id: i64, actor: &str) -> Result<f64, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT ch.id, ch.company_id, ch.amount + COALESCE((SELECT SUM(r.amount) FROM company_charges r WHERE r.reversal_of = ch.id), 0)
+         FROM company_charges ch
+         WHERE ch.guest_id = ?1 AND ch.reversal_of IS NULL",
+    )?;
+    let charges = stmt
+        .query_map(params![guest_id], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, f64>(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let now = get_current_timestamp();
+    let mut reversed = 0;
+    for (charge_id, company_id, left) in charges {
+        let left = to_cents(left);
+        if left <= 0 {
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO company_charges (company_id, guest_id, amount, description, posted_on, created_by, created_at, reversal_of)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                company_id,
+                guest_id,
+                -(left as f64 / 100.0),
+                format!("Checkout reopened: stay #{}", guest_id),
+                business_date_today(),
+                actor,
+                now,
+                charge_id
+            ],
+        )?;
+        reversed += left;
+    }
+    Ok(reversed as f64 / 100.0)
+}
+
+/// Undo a checkout made by mistake, within the reopen window: the stay is
+/// active again, back in its room if nobody has taken it since, and the
+/// checkout's payment (or company account posting) is reversed and its taxes,
+/// voucher and late check-out fee taken off, so the bill is worked out afresh
+/// at the next checkout. Receipts
+/// already sent or fiscalised can't be recalled.
+#[command]
+pub fn reopen_checkout(guest_id: i64, session_token: Option<String>) -> Result<ReopenedStay, AppError> {
+    let conn = get_db_connection()?;
+    type StayRow = (String, Option<i64>, Option<String>, Option<String>, bool);
+    let (status, room_id, checked_out_at, previous_check_out, anonymized): StayRow = conn
+        .query_row(
+            "SELECT c.status, c.room_id, c.checked_out_at, c.planned_check_out, p.anonymized_at IS NOT NULL
+             FROM customers c LEFT JOIN guest_profiles p ON p.id = c.profile_id
+             WHERE c.id = ?1",
+            params![guest_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::coded(GUEST_NOT_FOUND, format!("Guest #{} not found", guest_id)))?;
+    if status != "checked_out" {
+        return Err("Only a checked-out stay can be reopened".into());
+    }
+    if anonymized {
+        return Err(AppError::coded(GUEST_ANONYMIZED, "This guest's details have been erased; the stay can't be reopened"));
+    }
+
+    // Checkouts from before checked_out_at was recorded can't be reopened
+    let Some(checked_out_at) = checked_out_at else {
+        return Err("No checkout is recorded for this stay, so it can't be reopened".into());
+    };
+    let window = window_hours(&conn)?;
+    let checked_out_at = DateTime::parse_from_rfc3339(&checked_out_at).map_err(|e| format!("Invalid checkout time: {}", e))?;
+    if window == 0 || Utc::now().signed_duration_since(checked_out_at).num_minutes() > window * 60 {
+        return Err(format!("Checkouts can only be reopened within {} hours", window).into());
+    }
+
+    let actor = audit_actor(session_token.as_deref());
+    let tx = conn.unchecked_transaction()?;
+    let before = snapshot(&tx, "guest", guest_id)?;
+
+    // Money taken or posted at checkout is reversed, not deleted, so takings
+    // and company statements for the day still show it
+    let payments_reversed = reverse_checkout_payments(&tx, guest_id)?;
+    let company_charges_reversed = reverse_company_charges(&tx, guest_id, &actor)?;
+    let late_fee_removed = crate::stay_fees::remove_late_check_out_fee(&tx, guest_id)?;
+    let taxes_reversed: f64 =
+        tx.query_row("SELECT COALESCE(SUM(amount), 0) FROM tax_charges WHERE guest_id = ?1", params![guest_id], |row| row.get(0))?;
+    tx.execute("DELETE FROM tax_charges WHERE guest_id = ?1", params![guest_id])?;
+    // A voucher taken off the bill (not off an order) can be used again
+    tx.execute(
+        "UPDATE vouchers SET uses = uses - 1
+         WHERE id IN (SELECT voucher_id FROM voucher_redemptions WHERE guest_id = ?1 AND order_id IS NULL) AND uses > 0",
+        params![guest_id],
+    )?;
+    tx.execute("DELETE FROM voucher_redemptions WHERE guest_id = ?1 AND order_id IS NULL", params![guest_id])?;
+
+    let room_free = match room_id {
+        Some(room_id) => tx
+            .query_row("SELECT is_occupied = 0 AND is_active = 1 FROM resources WHERE id = ?1", params![room_id], |row| row.get(0))
+            .optional()?
+            .unwrap_or(false),
+        None => false,
+    };
+    let kept_room = if room_free { room_id } else { None };
+    tx.execute(
+        "UPDATE customers
+         SET status = 'active', check_out = ?1, room_id = ?2, checkout_total = NULL,
+             checkout_food = NULL, checkout_service_charge = NULL, checked_out_at = NULL, planned_check_out = NULL,
+             billed_amount = CASE WHEN nightly_pricing = 1 THEN NULL ELSE billed_amount END, updated_at = ?3
+         WHERE id = ?4",
+        params![previous_check_out, kept_room, get_current_timestamp(), guest_id],
+    )?;
+    if let Some(room_id) = kept_room {
+        tx.execute("UPDATE resources SET is_occupied = 1, guest_id = ?1 WHERE id = ?2", params![guest_id, room_id])?;
+    }
+
+    log_audit_event(
+        &tx,
+        &actor,
+        "checkout_reopened",
+        &format!(
+            "guest #{}: checkout reopened, reversing {:.2} paid, {:.2} posted to company accounts, {:.2} late check-out fee and {:.2} tax{}",
+            guest_id,
+            payments_reversed,
+            company_charges_reversed,
+            late_fee_removed,
+            taxes_reversed,
+            if room_id.is_some() && kept_room.is_none() { "; their room was taken, so they have none" } else { "" }
+        ),
+    )?;
+    record_change(&tx, &actor, "guest", guest_id, "update", before, snapshot(&tx, "guest", guest_id)?)?;
+    tx.commit()?;
+
+    events::guest_changed(Some(guest_id), "checkout_reopened");
+    if kept_room.is_some() {
+        events::room_status_changed(kept_room, "occupied");
+    }
+
+    Ok(ReopenedStay {
+        guest_id,
+        check_out: previous_check_out,
+        room_restored: kept_room.is_some(),
+        payments_reversed,
+        company_charges_reversed,
+        taxes_reversed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{StayFeeRule, StayFeeRules};
+    use crate::simple_commands::{checkout_guest, get_rooms};
+    use crate::test_support::{add_test_room, check_in, count, same_amount, TestDb};
+
+    /// A 10.00 late check-out fee on any checkout when `late` is set.
+    fn late_fee_rules(late: bool) -> StayFeeRules {
+        StayFeeRules {
+            check_in_time: "14:00".to_string(),
+            check_out_time: if late { "00:00" } else { "12:00" }.to_string(),
+            early_check_in: None,
+            late_check_out: late.then(|| StayFeeRule { fee_type: "flat".to_string(), amount: 10.0 }),
+        }
+    }
+
+    /// Check a guest into ST-601 and out again, paying by card; returns the
+    /// guest and room ids.
+    fn checked_out_stay(late_fee: bool) -> (i64, i64) {
+        let room_id = add_test_room("ST-601");
+        let guest_id = check_in("Reopened Guest", Some(room_id));
+        crate::stay_fees::set_stay_fee_rules(late_fee_rules(late_fee)).unwrap();
+        checkout_guest(guest_id, None, None, Some("card".to_string()), None, None).unwrap();
+        crate::stay_fees::set_stay_fee_rules(late_fee_rules(false)).unwrap();
+        (guest_id, room_id)
+    }
+
+    #[test]
+    fn a_reopened_stay_is_active_again_in_its_room() {
+        let _db = TestDb::new();
+        let (guest_id, room_id) = checked_out_stay(false);
+        let reopened = reopen_checkout(guest_id, None).unwrap();
+        assert!(reopened.room_restored, "the stay did not get its room back");
+        // The stay had no check-out date before the checkout set one
+        assert_eq!(reopened.check_out, None, "check-out date not restored");
+        let active = count(
+            "SELECT COUNT(*) FROM customers
+             WHERE id = ?1 AND status = 'active' AND checkout_total IS NULL AND checked_out_at IS NULL AND planned_check_out IS NULL",
+            guest_id,
+        );
+        assert_eq!(active, 1, "the reopened stay still carries its checkout");
+        let occupied = get_rooms(None, None).unwrap().into_iter().any(|r| r.id == room_id && r.is_occupied);
+        assert!(occupied, "the room was not re-occupied");
+    }
+
+    #[test]
+    fn the_checkout_payment_is_reversed_not_deleted() {
+        let _db = TestDb::new();
+        let (guest_id, _) = checked_out_stay(false);
+        let reopened = reopen_checkout(guest_id, None).unwrap();
+        assert!(!same_amount(reopened.payments_reversed, 0.0), "no checkout payment was reversed");
+        let reversals = count("SELECT COUNT(*) FROM payments WHERE guest_id = ?1 AND refund_of IS NOT NULL AND amount < 0", guest_id);
+        assert_eq!(reversals, 1, "expected one reversing payment");
+
+        let totals = checkout_guest(guest_id, None, None, Some("cash".to_string()), None, None).unwrap();
+        let conn = get_db_connection().unwrap();
+        let paid: f64 = conn
+            .query_row("SELECT COALESCE(SUM(amount), 0) FROM payments WHERE guest_id = ?1", params![guest_id], |row| row.get(0))
+            .unwrap();
+        assert!(same_amount(paid, totals.balance_due), "guest paid {:.2} in all, expected {:.2}", paid, totals.balance_due);
+    }
+
+    #[test]
+    fn the_late_check_out_fee_comes_off_with_the_checkout() {
+        let _db = TestDb::new();
+        let (guest_id, _) = checked_out_stay(true);
+        let posted = count("SELECT COUNT(*) FROM guest_charges WHERE guest_id = ?1 AND category = 'late_checkout'", guest_id);
+        assert_eq!(posted, 1, "no late check-out fee at the first checkout");
+        reopen_checkout(guest_id, None).unwrap();
+        let fees = count("SELECT COUNT(*) FROM guest_charges WHERE guest_id = ?1 AND category = 'late_checkout'", guest_id);
+        assert_eq!(fees, 0, "the late check-out fee stayed on the reopened stay");
+        let totals = checkout_guest(guest_id, None, None, Some("cash".to_string()), None, None).unwrap();
+        assert!(same_amount(totals.extra_charges, 0.0), "the removed late check-out fee came back");
+    }
+}
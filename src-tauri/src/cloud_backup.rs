@@ -0,0 +1,432 @@
+use tauri::command;
+use base64::Engine;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::db::{get_db_connection, get_db_path, get_current_timestamp, with_db_path_override};
+use crate::errors::AppError;
+use crate::models::{CloudBackupSettings, CloudBackupSettingsInput, CloudBackupStatus};
+use crate::repository::{get_non_empty_setting, set_or_clear_setting};
+
+const PROVIDERS: &[&str] = &["none", "s3", "google_drive", "webdav"];
+// Backups can be tens of megabytes on a slow line
+const UPLOAD_TIMEOUT_SECS: u64 = 300;
+
+/// Somewhere a backup file can be copied to off this PC.
+trait CloudProvider {
+    /// Upload `bytes` as `name` and return where it ended up.
+    fn upload(&self, name: &str, content_type: &str, bytes: &[u8]) -> Result<String, String>;
+}
+
+/// Amazon S3 or any S3-compatible store (Backblaze B2, Cloudflare R2, MinIO, ...).
+struct S3 {
+    endpoint: String,
+    region: String,
+    bucket: String,
+    prefix: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+struct GoogleDrive {
+    client_id: String,
+    client_secret: String,
+    refresh_token: String,
+    folder_id: Option<String>,
+}
+
+struct WebDav {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(Duration::from_secs(UPLOAD_TIMEOUT_SECS)).build()
+}
+
+fn http_error(provider: &str, e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, resp) => {
+            format!("{} returned HTTP {}: {}", provider, code, resp.into_string().unwrap_or_default())
+        }
+        ureq::Error::Transport(t) => format!("Could not reach {}: {}", provider, t),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut block = [0u8; 64];
+    if key.len() > block.len() {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().to_vec()
+}
+
+/// A path segment as SigV4 wants it: everything but unreserved characters escaped.
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+impl CloudProvider for S3 {
+    /// A path-style PUT signed with AWS Signature Version 4.
+    fn upload(&self, name: &str, content_type: &str, bytes: &[u8]) -> Result<String, String> {
+        let endpoint = self.endpoint.trim_end_matches('/');
+        let host = endpoint.split("://").nth(1).unwrap_or(endpoint).split('/').next().unwrap_or_default();
+        let key = format!("{}{}", self.prefix, name);
+        let path = std::iter::once(self.bucket.as_str())
+            .chain(key.split('/'))
+            .map(uri_encode)
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex(&Sha256::digest(bytes));
+        let canonical_request = format!(
+            "PUT\n/{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            path, host, payload_hash, amz_date, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = ["s3", "aws4_request"].iter().fold(
+            hmac_sha256(
+                &hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), date.as_bytes()),
+                self.region.as_bytes(),
+            ),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+            self.access_key_id,
+            scope,
+            hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()))
+        );
+
+        let url = format!("{}/{}", endpoint, path);
+        agent()
+            .put(&url)
+            .set("Authorization", &authorization)
+            .set("x-amz-content-sha256", &payload_hash)
+            .set("x-amz-date", &amz_date)
+            .set("Content-Type", content_type)
+            .send_bytes(bytes)
+            .map_err(|e| http_error("S3", e))?;
+        Ok(format!("s3://{}/{}", self.bucket, key))
+    }
+}
+
+impl GoogleDrive {
+    /// Trade the saved refresh token for a short-lived access token.
+    fn access_token(&self) -> Result<String, String> {
+        let response: serde_json::Value = agent()
+            .post("https://oauth2.googleapis.com/token")
+            .send_form(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("refresh_token", self.refresh_token.as_str()),
+            ])
+            .map_err(|e| http_error("Google", e))?
+            .into_json()
+            .map_err(|e| format!("Unexpected reply from Google: {}", e))?;
+        response["access_token"]
+            .as_str()
+            .map(|t| t.to_string())
+            .ok_or("Google did not return an access token".to_string())
+    }
+}
+
+impl CloudProvider for GoogleDrive {
+    fn upload(&self, name: &str, content_type: &str, bytes: &[u8]) -> Result<String, String> {
+        let token = self.access_token()?;
+        let mut metadata = serde_json::json!({ "name": name });
+        if let Some(folder) = &self.folder_id {
+            metadata["parents"] = serde_json::json!([folder]);
+        }
+
+        let boundary = format!("backup_{}", uuid::Uuid::new_v4().simple());
+        let mut body = format!(
+            "--{b}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{}\r\n--{b}\r\nContent-Type: {}\r\n\r\n",
+            metadata,
+            content_type,
+            b = boundary
+        )
+        .into_bytes();
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(format!("\r\n--{}--", boundary).as_bytes());
+
+        let response: serde_json::Value = agent()
+            .post("https://www.googleapis.com/upload/drive/v3/files?uploadType=multipart")
+            .set("Authorization", &format!("Bearer {}", token))
+            .set("Content-Type", &format!("multipart/related; boundary={}", boundary))
+            .send_bytes(&body)
+            .map_err(|e| http_error("Google Drive", e))?
+            .into_json()
+            .map_err(|e| format!("Unexpected reply from Google Drive: {}", e))?;
+        Ok(format!("gdrive://{}", response["id"].as_str().unwrap_or(name)))
+    }
+}
+
+impl CloudProvider for WebDav {
+    fn upload(&self, name: &str, content_type: &str, bytes: &[u8]) -> Result<String, String> {
+        let url = format!("{}/{}", self.url.trim_end_matches('/'), uri_encode(name));
+        let mut request = agent().put(&url).set("Content-Type", content_type);
+        if let Some(username) = &self.username {
+            let credentials = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", username, self.password.as_deref().unwrap_or("")));
+            request = request.set("Authorization", &format!("Basic {}", credentials));
+        }
+        request.send_bytes(bytes).map_err(|e| http_error("WebDAV server", e))?;
+        Ok(url)
+    }
+}
+
+fn provider_name(conn: &Connection) -> Result<String, String> {
+    Ok(get_non_empty_setting(conn, "cloud_backup_provider")?.unwrap_or_else(|| "none".to_string()))
+}
+
+fn load_provider(conn: &Connection) -> Result<Box<dyn CloudProvider>, String> {
+    let require = |key: &str, what: &str| -> Result<String, String> {
+        get_non_empty_setting(conn, key)?.ok_or(format!("{} is not set", what))
+    };
+    match provider_name(conn)?.as_str() {
+        "s3" => Ok(Box::new(S3 {
+            endpoint: get_non_empty_setting(conn, "cloud_backup_s3_endpoint")?
+                .unwrap_or_else(|| "https://s3.amazonaws.com".to_string()),
+            region: get_non_empty_setting(conn, "cloud_backup_s3_region")?.unwrap_or_else(|| "us-east-1".to_string()),
+            bucket: require("cloud_backup_s3_bucket", "S3 bucket")?,
+            prefix: get_non_empty_setting(conn, "cloud_backup_s3_prefix")?.unwrap_or_default(),
+            access_key_id: require("cloud_backup_s3_access_key_id", "S3 access key ID")?,
+            secret_access_key: require("cloud_backup_s3_secret_access_key", "S3 secret access key")?,
+        })),
+        "google_drive" => Ok(Box::new(GoogleDrive {
+            client_id: require("cloud_backup_gdrive_client_id", "Google client ID")?,
+            client_secret: require("cloud_backup_gdrive_client_secret", "Google client secret")?,
+            refresh_token: require("cloud_backup_gdrive_refresh_token", "Google refresh token")?,
+            folder_id: get_non_empty_setting(conn, "cloud_backup_gdrive_folder_id")?,
+        })),
+        "webdav" => Ok(Box::new(WebDav {
+            url: require("cloud_backup_webdav_url", "WebDAV folder URL")?,
+            username: get_non_empty_setting(conn, "cloud_backup_webdav_username")?,
+            password: get_non_empty_setting(conn, "cloud_backup_webdav_password")?,
+        })),
+        _ => Err("No cloud backup destination is set up".to_string()),
+    }
+}
+
+/// The backup database and, when it was written, its JSON export.
+fn backup_files(db_file: &Path) -> Vec<(PathBuf, &'static str)> {
+    let encrypted = crate::backup_crypto::is_encrypted(db_file);
+    let content_type = |plain: &'static str| if encrypted { "application/octet-stream" } else { plain };
+    let mut files = vec![(db_file.to_path_buf(), content_type("application/vnd.sqlite3"))];
+    let stamp = db_file
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .and_then(|s| s.strip_prefix("business_backup_"));
+    if let Some(stamp) = stamp {
+        let json = db_file.with_file_name(format!("business_data_{}.json", stamp));
+        if json.is_file() {
+            files.push((json, content_type("application/json")));
+        }
+    }
+    files
+}
+
+/// Copy a backup written by `write_backup` to the cloud destination and
+/// record how it went for `get_cloud_backup_status`. Ok(false) when no
+/// destination is set up.
+pub fn upload_backup(db_file: &Path) -> Result<bool, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    if provider_name(&conn)? == "none" {
+        return Ok(false);
+    }
+    set_or_clear_setting(&conn, "cloud_backup_last_status", Some("uploading"))?;
+    set_or_clear_setting(&conn, "cloud_backup_last_attempt", Some(&get_current_timestamp()))?;
+
+    let outcome = load_provider(&conn).and_then(|provider| {
+        backup_files(db_file)
+            .into_iter()
+            .map(|(path, content_type)| {
+                let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                provider.upload(&name, content_type, &bytes)
+            })
+            .collect::<Result<Vec<String>, String>>()
+    });
+    match outcome {
+        Ok(locations) => {
+            set_or_clear_setting(&conn, "cloud_backup_last_status", Some("uploaded"))?;
+            set_or_clear_setting(&conn, "cloud_backup_last_sync", Some(&get_current_timestamp()))?;
+            set_or_clear_setting(&conn, "cloud_backup_last_error", None)?;
+            set_or_clear_setting(&conn, "cloud_backup_last_files", Some(&locations.join("\n")))?;
+            Ok(true)
+        }
+        Err(e) => {
+            set_or_clear_setting(&conn, "cloud_backup_last_status", Some("failed"))?;
+            set_or_clear_setting(&conn, "cloud_backup_last_error", Some(&e))?;
+            Err(e)
+        }
+    }
+}
+
+/// `upload_backup` off the calling thread so taking a backup never waits on
+/// the network.
+pub fn upload_in_background(db_file: PathBuf) {
+    let db_path = get_db_path();
+    std::thread::spawn(move || {
+        if let Err(e) = with_db_path_override(db_path, || upload_backup(&db_file)) {
+            println!("⚠️ Cloud backup upload failed: {}", e);
+        }
+    });
+}
+
+/// Choose where backups are uploaded and the credentials for it. Secrets
+/// left out keep the saved ones, so the form can be resubmitted without them.
+#[command]
+pub fn set_cloud_backup_provider(settings: CloudBackupSettingsInput) -> Result<CloudBackupSettings, AppError> {
+    let provider = settings.provider.trim().to_lowercase();
+    if !PROVIDERS.contains(&provider.as_str()) {
+        return Err(format!("Provider must be one of: {}", PROVIDERS.join(", ")).into());
+    }
+    let trimmed = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let s3_endpoint = trimmed(settings.s3_endpoint).map(|e| e.trim_end_matches('/').to_string());
+    let webdav_url = trimmed(settings.webdav_url);
+    for url in [&s3_endpoint, &webdav_url].into_iter().flatten() {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(format!("'{}' must be an http:// or https:// address", url).into());
+        }
+    }
+    // Keys are stored as given; a prefix always ends in one '/'
+    let s3_prefix = trimmed(settings.s3_prefix).map(|p| format!("{}/", p.trim_matches('/'))).filter(|p| p != "/");
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    set_or_clear_setting(&tx, "cloud_backup_provider", Some(&provider))?;
+    set_or_clear_setting(&tx, "cloud_backup_s3_endpoint", s3_endpoint.as_deref())?;
+    set_or_clear_setting(&tx, "cloud_backup_s3_region", trimmed(settings.s3_region).as_deref())?;
+    set_or_clear_setting(&tx, "cloud_backup_s3_bucket", trimmed(settings.s3_bucket).as_deref())?;
+    set_or_clear_setting(&tx, "cloud_backup_s3_prefix", s3_prefix.as_deref())?;
+    set_or_clear_setting(&tx, "cloud_backup_s3_access_key_id", trimmed(settings.s3_access_key_id).as_deref())?;
+    set_or_clear_setting(&tx, "cloud_backup_gdrive_client_id", trimmed(settings.gdrive_client_id).as_deref())?;
+    set_or_clear_setting(&tx, "cloud_backup_gdrive_folder_id", trimmed(settings.gdrive_folder_id).as_deref())?;
+    set_or_clear_setting(&tx, "cloud_backup_webdav_url", webdav_url.as_deref())?;
+    set_or_clear_setting(&tx, "cloud_backup_webdav_username", trimmed(settings.webdav_username).as_deref())?;
+    let secrets = [
+        ("cloud_backup_s3_secret_access_key", settings.s3_secret_access_key),
+        ("cloud_backup_gdrive_client_secret", settings.gdrive_client_secret),
+        ("cloud_backup_gdrive_refresh_token", settings.gdrive_refresh_token),
+        ("cloud_backup_webdav_password", settings.webdav_password),
+    ];
+    for (key, value) in secrets {
+        if let Some(value) = trimmed(value) {
+            set_or_clear_setting(&tx, key, Some(&value))?;
+        }
+    }
+    tx.commit()?;
+    get_cloud_backup_provider()
+}
+
+#[command]
+pub fn get_cloud_backup_provider() -> Result<CloudBackupSettings, AppError> {
+    let conn = get_db_connection()?;
+    let provider = provider_name(&conn)?;
+    let secret_keys: &[&str] = match provider.as_str() {
+        "s3" => &["cloud_backup_s3_secret_access_key"],
+        "google_drive" => &["cloud_backup_gdrive_client_secret", "cloud_backup_gdrive_refresh_token"],
+        "webdav" => &["cloud_backup_webdav_password"],
+        _ => &[],
+    };
+    let mut has_secret = !secret_keys.is_empty();
+    for key in secret_keys {
+        has_secret &= get_non_empty_setting(&conn, key)?.is_some();
+    }
+    Ok(CloudBackupSettings {
+        provider,
+        s3_endpoint: get_non_empty_setting(&conn, "cloud_backup_s3_endpoint")?,
+        s3_region: get_non_empty_setting(&conn, "cloud_backup_s3_region")?,
+        s3_bucket: get_non_empty_setting(&conn, "cloud_backup_s3_bucket")?,
+        s3_prefix: get_non_empty_setting(&conn, "cloud_backup_s3_prefix")?,
+        s3_access_key_id: get_non_empty_setting(&conn, "cloud_backup_s3_access_key_id")?,
+        gdrive_client_id: get_non_empty_setting(&conn, "cloud_backup_gdrive_client_id")?,
+        gdrive_folder_id: get_non_empty_setting(&conn, "cloud_backup_gdrive_folder_id")?,
+        webdav_url: get_non_empty_setting(&conn, "cloud_backup_webdav_url")?,
+        webdav_username: get_non_empty_setting(&conn, "cloud_backup_webdav_username")?,
+        has_secret,
+    })
+}
+
+/// How the last upload went and when backups last reached the cloud.
+#[command]
+pub fn get_cloud_backup_status() -> Result<CloudBackupStatus, AppError> {
+    let conn = get_db_connection()?;
+    Ok(CloudBackupStatus {
+        provider: provider_name(&conn)?,
+        status: get_non_empty_setting(&conn, "cloud_backup_last_status")?,
+        last_attempt: get_non_empty_setting(&conn, "cloud_backup_last_attempt")?,
+        last_sync: get_non_empty_setting(&conn, "cloud_backup_last_sync")?,
+        last_error: get_non_empty_setting(&conn, "cloud_backup_last_error")?,
+        last_files: get_non_empty_setting(&conn, "cloud_backup_last_files")?
+            .map(|files| files.lines().map(|f| f.to_string()).collect())
+            .unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+
+    fn backup_file(db: &TestDb) -> PathBuf {
+        let path = db.dir().join("business_backup_20000101_000000.db");
+        fs::write(&path, b"backup").unwrap();
+        path
+    }
+
+    #[test]
+    fn nothing_is_uploaded_without_a_destination() {
+        let db = TestDb::new();
+        assert_eq!(upload_backup(&backup_file(&db)), Ok(false), "a backup was uploaded with no destination set up");
+        assert!(get_cloud_backup_status().unwrap().status.is_none(), "an upload was recorded with no destination set up");
+    }
+
+    #[test]
+    fn a_failed_upload_is_recorded_in_the_status() {
+        let db = TestDb::new();
+        // Nothing listens on the discard port, so the upload fails without leaving this PC
+        let settings: CloudBackupSettingsInput = serde_json::from_value(serde_json::json!({
+            "provider": "webdav",
+            "webdav_url": "http://127.0.0.1:9/backups",
+        }))
+        .unwrap();
+        set_cloud_backup_provider(settings).unwrap();
+        assert!(upload_backup(&backup_file(&db)).is_err(), "upload to a closed port succeeded");
+        let status = get_cloud_backup_status().unwrap();
+        assert!(status.status.as_deref() == Some("failed") && status.last_error.is_some(), "failed upload not recorded: {:?}", status.status);
+    }
+}
@@ -0,0 +1,230 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::{get_current_timestamp, get_db_connection};
+use crate::errors::AppError;
+use crate::models::{Combo, ComboInput, ComboItem, OrderItemComponent};
+use crate::validation::{COMBO_EXISTS, COMBO_NOT_FOUND, EMPTY_FIELD, MENU_ITEM_NOT_FOUND, MENU_ITEM_UNAVAILABLE, NEGATIVE_AMOUNT};
+
+const COMBO_NAME_MAX_CHARS: usize = 80;
+
+fn combo_items(conn: &Connection, combo_id: i64) -> Result<Vec<(ComboItem, bool)>, AppError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT m.id, m.name, ci.quantity, m.price, m.is_active = 1 AND m.is_available = 1
+         FROM combo_items ci JOIN menu_items m ON m.id = ci.menu_item_id
+         WHERE ci.combo_id = ?1 ORDER BY m.name",
+    )?;
+    let items = stmt
+        .query_map(params![combo_id], |row| {
+            Ok((
+                ComboItem { menu_item_id: row.get(0)?, item_name: row.get(1)?, quantity: row.get(2)?, unit_price: row.get(3)? },
+                row.get(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(items)
+}
+
+fn build_combo(conn: &Connection, id: i64, name: String, price: f64) -> Result<Combo, AppError> {
+    let items = combo_items(conn, id)?;
+    let available = !items.is_empty() && items.iter().all(|(_, on_menu)| *on_menu);
+    let items: Vec<ComboItem> = items.into_iter().map(|(item, _)| item).collect();
+    let items_price = items.iter().map(|item| item.unit_price * item.quantity as f64).sum();
+    Ok(Combo { id, name, price, items, items_price, available })
+}
+
+fn load_combo(conn: &Connection, combo_id: i64) -> Result<Combo, AppError> {
+    let (name, price): (String, f64) = conn
+        .query_row("SELECT name, price FROM combos WHERE id = ?1", params![combo_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()?
+        .ok_or_else(|| AppError::coded(COMBO_NOT_FOUND, format!("Combo #{} not found", combo_id)))?;
+    build_combo(conn, combo_id, name, price)
+}
+
+fn find_by_name(conn: &Connection, name: &str) -> Result<Option<(i64, String)>, AppError> {
+    Ok(conn
+        .query_row(
+            "SELECT id, name FROM combos WHERE name = ?1 COLLATE NOCASE",
+            params![name.trim()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?)
+}
+
+/// Trimmed name and the items with repeats of the same item added together.
+fn validate_input(conn: &Connection, input: &ComboInput) -> Result<(String, Vec<(i64, i64)>), AppError> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Combo name cannot be empty"));
+    }
+    if name.chars().count() > COMBO_NAME_MAX_CHARS {
+        return Err(format!("Combo name must be at most {} characters", COMBO_NAME_MAX_CHARS).into());
+    }
+    if !input.price.is_finite() || input.price < 0.0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "price", "Combo price must be >= 0"));
+    }
+    let mut items: Vec<(i64, i64)> = Vec::new();
+    for item in &input.items {
+        if item.quantity < 1 {
+            return Err("Each item in a combo needs a quantity of at least 1".into());
+        }
+        let active: Option<bool> = conn
+            .query_row("SELECT is_active = 1 FROM menu_items WHERE id = ?1", params![item.menu_item_id], |row| row.get(0))
+            .optional()?;
+        if active != Some(true) {
+            return Err(AppError::coded(MENU_ITEM_NOT_FOUND, format!("Menu item #{} not found", item.menu_item_id)));
+        }
+        match items.iter_mut().find(|(id, _)| *id == item.menu_item_id) {
+            Some((_, quantity)) => *quantity += item.quantity,
+            None => items.push((item.menu_item_id, item.quantity)),
+        }
+    }
+    if items.iter().map(|(_, quantity)| quantity).sum::<i64>() < 2 {
+        return Err(AppError::invalid(EMPTY_FIELD, "items", "A combo needs at least two items"));
+    }
+    Ok((name.to_string(), items))
+}
+
+pub(crate) fn write_items(conn: &Connection, combo_id: i64, items: &[(i64, i64)]) -> Result<(), AppError> {
+    conn.execute("DELETE FROM combo_items WHERE combo_id = ?1", params![combo_id])?;
+    for (menu_item_id, quantity) in items {
+        conn.execute(
+            "INSERT INTO combo_items (combo_id, menu_item_id, quantity) VALUES (?1, ?2, ?3)",
+            params![combo_id, menu_item_id, quantity],
+        )?;
+    }
+    Ok(())
+}
+
+/// What one portion of `combo_id` is made of, for an order line. Refused when
+/// any of its items is off the menu.
+pub fn combo_components(conn: &Connection, combo_id: i64) -> Result<Vec<OrderItemComponent>, AppError> {
+    let combo = load_combo(conn, combo_id)?;
+    if !combo.available {
+        return Err(AppError::coded(
+            MENU_ITEM_UNAVAILABLE,
+            format!("'{}' can't be ordered: one of its items is not available", combo.name),
+        ));
+    }
+    Ok(combo
+        .items
+        .into_iter()
+        .map(|item| OrderItemComponent { menu_item_id: Some(item.menu_item_id), item_name: item.item_name, quantity: item.quantity })
+        .collect())
+}
+
+/// Combos with their items and what those would cost bought separately.
+#[command]
+pub fn get_combos() -> Result<Vec<Combo>, AppError> {
+    let conn = get_db_connection()?;
+    let combos: Vec<(i64, String, f64)> = conn
+        .prepare("SELECT id, name, price FROM combos ORDER BY name COLLATE NOCASE")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    combos.into_iter().map(|(id, name, price)| build_combo(&conn, id, name, price)).collect()
+}
+
+#[command]
+pub fn add_combo(combo: ComboInput) -> Result<Combo, AppError> {
+    let conn = get_db_connection()?;
+    let (name, items) = validate_input(&conn, &combo)?;
+    if let Some((_, existing)) = find_by_name(&conn, &name)? {
+        return Err(AppError::invalid(COMBO_EXISTS, "name", format!("Combo '{}' already exists", existing)));
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("INSERT INTO combos (name, price) VALUES (?1, ?2)", params![name, combo.price])?;
+    let combo_id = tx.last_insert_rowid();
+    write_items(&tx, combo_id, &items)?;
+    tx.commit()?;
+    load_combo(&conn, combo_id)
+}
+
+/// Change a combo's name, price or items. Orders already taken keep what
+/// they were made of.
+#[command]
+pub fn update_combo(combo_id: i64, combo: ComboInput) -> Result<Combo, AppError> {
+    let conn = get_db_connection()?;
+    load_combo(&conn, combo_id)?;
+    let (name, items) = validate_input(&conn, &combo)?;
+    if let Some((other_id, existing)) = find_by_name(&conn, &name)? {
+        if other_id != combo_id {
+            return Err(AppError::invalid(COMBO_EXISTS, "name", format!("Combo '{}' already exists", existing)));
+        }
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE combos SET name = ?1, price = ?2, updated_at = ?3 WHERE id = ?4",
+        params![name, combo.price, get_current_timestamp(), combo_id],
+    )?;
+    write_items(&tx, combo_id, &items)?;
+    tx.commit()?;
+    load_combo(&conn, combo_id)
+}
+
+/// Delete a combo. Orders that included it keep their lines and components.
+#[command]
+pub fn delete_combo(combo_id: i64) -> Result<(), AppError> {
+    let conn = get_db_connection()?;
+    load_combo(&conn, combo_id)?;
+    conn.execute("DELETE FROM combos WHERE id = ?1", params![combo_id])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ComboItemInput, OrderItemInput};
+    use crate::simple_commands::{add_menu_item, insert_food_order};
+    use crate::test_support::{order_item, same_amount, TestDb};
+
+    /// A burger (10 in stock) and two fries for 9.00, with both fries
+    /// entered as separate lines.
+    fn burger_meal() -> (i64, Combo) {
+        let burger = add_menu_item("Burger".to_string(), 6.0, "Meals".to_string(), None, Some(1), Some(10), Some(2)).unwrap();
+        let fries = add_menu_item("Fries".to_string(), 2.5, "Meals".to_string(), None, None, None, None).unwrap();
+        let meal = add_combo(ComboInput {
+            name: "Burger meal".to_string(),
+            price: 9.0,
+            items: vec![
+                ComboItemInput { menu_item_id: burger, quantity: 1 },
+                ComboItemInput { menu_item_id: fries, quantity: 1 },
+                ComboItemInput { menu_item_id: fries, quantity: 1 },
+            ],
+        })
+        .unwrap();
+        (burger, meal)
+    }
+
+    fn meals(combo_id: i64, quantity: i32) -> OrderItemInput {
+        OrderItemInput { combo_id: Some(combo_id), ..order_item("Burger meal", 9.0, quantity) }
+    }
+
+    #[test]
+    fn repeated_combo_items_are_merged_and_priced() {
+        let _db = TestDb::new();
+        let (_, meal) = burger_meal();
+        assert!(meal.items.len() == 2 && same_amount(meal.items_price, 11.0), "combo items not merged or priced: {:?}", meal.items);
+        assert!(get_combos().unwrap().iter().any(|c| c.id == meal.id && c.available), "new combo not listed as available");
+    }
+
+    #[test]
+    fn combo_orders_draw_on_each_items_stock() {
+        let _db = TestDb::new();
+        let (burger, meal) = burger_meal();
+        crate::simple_commands::set_tax_enabled(false).unwrap();
+        let too_many = insert_food_order(None, "walk_in".to_string(), None, vec![meals(meal.id, 11)], None, None, None);
+        assert!(too_many.is_err(), "combo ordered beyond its burger stock");
+        let (order_id, _) = insert_food_order(None, "walk_in".to_string(), None, vec![meals(meal.id, 2)], None, None, None).unwrap();
+        let conn = get_db_connection().unwrap();
+        let (total, stock): (f64, i64) = conn
+            .query_row(
+                "SELECT (SELECT total_amount FROM sales WHERE id = ?1), (SELECT stock_quantity FROM menu_items WHERE id = ?2)",
+                params![order_id, burger],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(same_amount(total, 18.0), "combo order total is {:.2}, expected 18.00", total);
+        assert_eq!(stock, 8, "burger stock after two meals");
+        let ticket = crate::print_templates::build_kitchen_ticket_html(order_id).unwrap();
+        assert!(ticket.contains("Burger meal") && ticket.contains("4&times; Fries"), "kitchen ticket does not break the combo into its items");
+    }
+}
@@ -0,0 +1,259 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::audit_actor;
+use crate::db::{get_db_connection, get_current_timestamp, log_audit_event, run_blocking};
+use crate::errors::AppError;
+use crate::models::{CheckoutTotals, Company, CompanyPayment, CompanyStatement, CompanyStatementEntry};
+use crate::simple_commands::{business_date_today, checkout_active_guest, parse_payment_method, refund_checkout_overpayment, to_cents};
+use crate::validation::{validate_date_format, EMPTY_FIELD, NEGATIVE_AMOUNT};
+
+const COMPANY_COLUMNS: &str = "c.id, c.name, c.contact_name, c.phone, c.email, c.billing_address, c.credit_limit, c.notes,
+    (SELECT COALESCE(SUM(ch.amount), 0.0) FROM company_charges ch WHERE ch.company_id = c.id),
+    (SELECT COALESCE(SUM(p.amount), 0.0) FROM company_payments p WHERE p.company_id = c.id)";
+
+fn trimmed(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn map_company(row: &rusqlite::Row) -> rusqlite::Result<Company> {
+    let charged: f64 = row.get(8)?;
+    let paid: f64 = row.get(9)?;
+    Ok(Company {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        contact_name: row.get(2)?,
+        phone: row.get(3)?,
+        email: row.get(4)?,
+        billing_address: row.get(5)?,
+        credit_limit: row.get(6)?,
+        notes: row.get(7)?,
+        charged,
+        paid,
+        balance: charged - paid,
+    })
+}
+
+fn load_company(conn: &Connection, company_id: i64) -> Result<Company, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM companies c WHERE c.id = ?1", COMPANY_COLUMNS),
+        params![company_id],
+        map_company,
+    )
+    .optional()?
+    .ok_or_else(|| format!("Company #{} not found", company_id).into())
+}
+
+#[command]
+pub fn add_company(
+    name: String,
+    contact_name: Option<String>,
+    phone: Option<String>,
+    email: Option<String>,
+    billing_address: Option<String>,
+    credit_limit: Option<f64>,
+    notes: Option<String>,
+) -> Result<Company, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Company name cannot be empty"));
+    }
+    if let Some(limit) = credit_limit {
+        if !limit.is_finite() || limit < 0.0 {
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "credit_limit", "Credit limit must be >= 0"));
+        }
+    }
+
+    let conn = get_db_connection()?;
+    conn.execute(
+        "INSERT INTO companies (name, contact_name, phone, email, billing_address, credit_limit, notes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            name,
+            trimmed(contact_name),
+            trimmed(phone),
+            trimmed(email),
+            trimmed(billing_address),
+            credit_limit,
+            trimmed(notes),
+            get_current_timestamp()
+        ],
+    )?;
+    load_company(&conn, conn.last_insert_rowid())
+}
+
+/// Companies by name, with what each still owes.
+#[command]
+pub fn get_companies() -> Result<Vec<Company>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM companies c ORDER BY c.name COLLATE NOCASE", COMPANY_COLUMNS))?;
+    let companies = stmt.query_map([], map_company)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(companies)
+}
+
+/// Check a guest out with the bill charged to a company rather than paid at
+/// the desk. Deposits are still netted off first; the posting fails, and the
+/// guest stays checked in, if it would take the company over its credit limit.
+#[command]
+pub fn post_to_company_account(
+    guest_id: i64,
+    company_id: i64,
+    discount_flat: Option<f64>,
+    discount_pct: Option<f64>,
+    session_token: Option<String>,
+) -> Result<CheckoutTotals, AppError> {
+    let actor = audit_actor(session_token.as_deref());
+    checkout_active_guest(guest_id, None, discount_flat, discount_pct, None, session_token, |tx, balance_due| {
+        let company = load_company(tx, company_id)?;
+        let amount = to_cents(balance_due);
+        if amount < 0 {
+            return Ok(refund_checkout_overpayment(tx, guest_id, -amount)?);
+        }
+        if amount == 0 {
+            return Ok(());
+        }
+        if let Some(limit) = company.credit_limit {
+            if to_cents(company.balance) + amount > to_cents(limit) {
+                return Err(format!(
+                    "Posting {:.2} would take {} over its credit limit of {:.2} (owes {:.2})",
+                    amount as f64 / 100.0,
+                    company.name,
+                    limit,
+                    company.balance
+                )
+                .into());
+            }
+        }
+
+        let guest_name: String = tx.query_row("SELECT name FROM customers WHERE id = ?1", params![guest_id], |row| row.get(0))?;
+        tx.execute(
+            "INSERT INTO company_charges (company_id, guest_id, amount, description, posted_on, created_by, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                company_id,
+                guest_id,
+                amount as f64 / 100.0,
+                format!("Stay: {}", guest_name),
+                business_date_today(),
+                actor,
+                get_current_timestamp()
+            ],
+        )?;
+        log_audit_event(
+            tx,
+            &actor,
+            "company_charge_posted",
+            &format!("{:.2} for guest #{} posted to {}", amount as f64 / 100.0, guest_id, company.name),
+        )?;
+        Ok(())
+    })
+}
+
+/// Money received from a company against its account. Can't exceed what the
+/// company still owes.
+#[command]
+pub fn record_company_payment(
+    company_id: i64,
+    amount: f64,
+    method: String,
+    paid_on: String,
+    note: Option<String>,
+    session_token: Option<String>,
+) -> Result<CompanyPayment, AppError> {
+    if !amount.is_finite() || to_cents(amount) <= 0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "amount", "Amount must be positive"));
+    }
+    let method = parse_payment_method(&method)?;
+    validate_date_format(&paid_on)?;
+    let note = trimmed(note);
+    let amount = to_cents(amount) as f64 / 100.0;
+
+    let conn = get_db_connection()?;
+    let company = load_company(&conn, company_id)?;
+    if to_cents(amount) > to_cents(company.balance) {
+        let money = crate::money::Money::load(&conn)?;
+        return Err(format!("{} only owes {}", company.name, money.format(company.balance.max(0.0), 2)).into());
+    }
+
+    let actor = audit_actor(session_token.as_deref());
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO company_payments (company_id, amount, method, paid_on, note, created_by, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![company_id, amount, method, paid_on, note, actor, get_current_timestamp()],
+    )?;
+    let payment_id = tx.last_insert_rowid();
+    log_audit_event(
+        &tx,
+        &actor,
+        "company_payment",
+        &format!("{:.2} ({}) received from {}", amount, method, company.name),
+    )?;
+    tx.commit()?;
+
+    Ok(CompanyPayment {
+        id: payment_id,
+        company_id,
+        amount,
+        method,
+        paid_on,
+        note,
+        created_by: Some(actor),
+    })
+}
+
+/// Charges and payments on a company account between two dates (inclusive),
+/// with the balance brought forward and a running balance per line.
+#[command]
+pub async fn company_statement(company_id: i64, start_date: String, end_date: String) -> Result<CompanyStatement, AppError> {
+    run_blocking(move || {
+        validate_date_format(&start_date)?;
+        validate_date_format(&end_date)?;
+        if end_date < start_date {
+            return Err("End date cannot be before start date".into());
+        }
+        let conn = get_db_connection()?;
+        let company = load_company(&conn, company_id)?;
+
+        let opening_balance: f64 = conn.query_row(
+            "SELECT (SELECT COALESCE(SUM(amount), 0.0) FROM company_charges WHERE company_id = ?1 AND posted_on < ?2)
+                  - (SELECT COALESCE(SUM(amount), 0.0) FROM company_payments WHERE company_id = ?1 AND paid_on < ?2)",
+            params![company_id, start_date],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT posted_on, 'charge', COALESCE('Guest #' || guest_id, 'Charge #' || id), description, amount, 0 AS sort, id
+             FROM company_charges WHERE company_id = ?1 AND posted_on >= ?2 AND posted_on <= ?3
+             UNION ALL
+             SELECT paid_on, 'payment', 'Payment #' || id, COALESCE(note, 'Payment (' || method || ')'), -amount, 1, id
+             FROM company_payments WHERE company_id = ?1 AND paid_on >= ?2 AND paid_on <= ?3
+             ORDER BY 1, 6, 7",
+        )?;
+        let mut balance = opening_balance;
+        let entries = stmt
+            .query_map(params![company_id, start_date, end_date], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?, row.get::<_, f64>(4)?))
+            })?
+            .map(|row| {
+                row.map(|(date, kind, reference, description, amount)| {
+                    balance += amount;
+                    CompanyStatementEntry { date, kind, reference, description, amount, balance }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let total_charges: f64 = entries.iter().filter(|e| e.kind == "charge").map(|e| e.amount).sum();
+        let total_payments: f64 = -entries.iter().filter(|e| e.kind == "payment").map(|e| e.amount).sum::<f64>();
+        Ok(CompanyStatement {
+            company,
+            start_date,
+            end_date,
+            opening_balance,
+            closing_balance: opening_balance + total_charges - total_payments,
+            entries,
+            total_charges,
+            total_payments,
+        })
+    })
+    .await
+}
@@ -1,20 +1,25 @@
-use crate::db::{get_db_path, initialize_database};
-use rusqlite::{Connection, Result};
+use crate::db::{get_db_connection, get_db_path, initialize_database, run_blocking};
+use crate::models::{DatabaseStats, DataIntegrityReport};
+use rusqlite::Result;
 use std::fs;
 
 /// Reset database with comprehensive seed data for testing and development
 #[tauri::command]
-pub fn reset_database() -> Result<String, String> {
-    let db_path = get_db_path();
+pub async fn reset_database() -> Result<String, String> {
+    run_blocking(move || {
+        let db_path = get_db_path();
 
-    // Remove the database file so initialize_database() recreates schema/migrations cleanly.
-    if db_path.exists() {
-        fs::remove_file(&db_path)
-            .map_err(|e| format!("Failed to remove database: {}", e))?;
-    }
+        // Remove the database file so initialize_database() recreates schema/migrations cleanly.
+        if db_path.exists() {
+            crate::db::close_pooled_connections();
+            fs::remove_file(&db_path)
+                .map_err(|e| format!("Failed to remove database: {}", e))?;
+        }
 
-    initialize_database().map_err(|e| format!("Failed to initialize database: {}", e))?;
-    Ok("Database reset successfully".to_string())
+        initialize_database().map_err(|e| format!("Failed to initialize database: {}", e))?;
+        Ok("Database reset successfully".to_string())
+    })
+    .await
 }
 
 /// Get the database file path
@@ -29,62 +34,80 @@ pub fn get_database_path() -> Result<String, String> {
 
 /// Get current database statistics for verification
 #[tauri::command]
-pub fn get_database_stats() -> Result<DatabaseStats, String> {
-    let db_path = get_db_path();
-    let conn = Connection::open(&db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+pub async fn get_database_stats() -> Result<DatabaseStats, String> {
+    run_blocking(move || {
+        let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
     
-    let total_rooms: i32 = conn.query_row("SELECT COUNT(*) FROM resources", [], |row| row.get(0))
-        .map_err(|e| format!("Failed to count resources: {}", e))?;
+        let total_rooms: i32 = conn.query_row("SELECT COUNT(*) FROM resources", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count resources: {}", e))?;
     
-    let occupied_rooms: i32 = conn.query_row("SELECT COUNT(*) FROM resources WHERE is_occupied = 1", [], |row| row.get(0))
-        .map_err(|e| format!("Failed to count occupied resources: {}", e))?;
+        let occupied_rooms: i32 = conn.query_row("SELECT COUNT(*) FROM resources WHERE is_occupied = 1", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count occupied resources: {}", e))?;
     
-    let active_guests: i32 = conn.query_row("SELECT COUNT(*) FROM customers WHERE status = 'active'", [], |row| row.get(0))
-        .map_err(|e| format!("Failed to count active customers: {}", e))?;
+        let active_guests: i32 = conn.query_row("SELECT COUNT(*) FROM customers WHERE status = 'active'", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count active customers: {}", e))?;
     
-    let total_guests: i32 = conn.query_row("SELECT COUNT(*) FROM customers", [], |row| row.get(0))
-        .map_err(|e| format!("Failed to count total customers: {}", e))?;
+        let total_guests: i32 = conn.query_row("SELECT COUNT(*) FROM customers", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count total customers: {}", e))?;
     
-    let menu_items: i32 = conn.query_row("SELECT COUNT(*) FROM menu_items", [], |row| row.get(0))
-        .map_err(|e| format!("Failed to count menu items: {}", e))?;
+        let menu_items: i32 = conn.query_row("SELECT COUNT(*) FROM menu_items", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count menu items: {}", e))?;
     
-    let food_orders: i32 = conn.query_row("SELECT COUNT(*) FROM sales", [], |row| row.get(0))
-        .map_err(|e| format!("Failed to count sales: {}", e))?;
+        let food_orders: i32 = conn.query_row("SELECT COUNT(*) FROM sales", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count sales: {}", e))?;
     
-    // Current schema uses paid=0/1 (migrations may also keep is_paid in older DBs)
-    let unpaid_orders: i32 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM sales WHERE COALESCE(paid, is_paid, 0) = 0",
-            [],
-            |row| row.get(0),
-        )
-        .map_err(|e| format!("Failed to count unpaid sales: {}", e))?;
+        // Current schema uses paid=0/1 (migrations may also keep is_paid in older DBs)
+        let unpaid_orders: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sales WHERE COALESCE(paid, is_paid, 0) = 0",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to count unpaid sales: {}", e))?;
     
-    let expenses: i32 = conn.query_row("SELECT COUNT(*) FROM expenses", [], |row| row.get(0))
-        .map_err(|e| format!("Failed to count expenses: {}", e))?;
+        let expenses: i32 = conn.query_row("SELECT COUNT(*) FROM expenses", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count expenses: {}", e))?;
     
-    Ok(DatabaseStats {
-        total_rooms,
-        occupied_rooms,
-        available_rooms: total_rooms - occupied_rooms,
-        active_guests,
-        total_guests,
-        menu_items,
-        food_orders,
-        unpaid_orders,
-        expenses,
+        Ok(DatabaseStats {
+            total_rooms,
+            occupied_rooms,
+            available_rooms: total_rooms - occupied_rooms,
+            active_guests,
+            total_guests,
+            menu_items,
+            food_orders,
+            unpaid_orders,
+            expenses,
+        })
     })
+    .await
 }
 
-#[derive(serde::Serialize)]
-pub struct DatabaseStats {
-    pub total_rooms: i32,
-    pub occupied_rooms: i32,
-    pub available_rooms: i32,
-    pub active_guests: i32,
-    pub total_guests: i32,
-    pub menu_items: i32,
-    pub food_orders: i32,
-    pub unpaid_orders: i32,
-    pub expenses: i32,
+/// SQLite's own consistency check plus a pass over referenced media files,
+/// so a backup restored onto a new PC shows what didn't come across.
+#[tauri::command]
+pub async fn check_data_integrity() -> Result<DataIntegrityReport, String> {
+    run_blocking(move || {
+        let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+
+        let mut stmt = conn
+            .prepare("PRAGMA integrity_check")
+            .map_err(|e| format!("Failed to run integrity check: {}", e))?;
+        let database_messages = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to run integrity check: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to run integrity check: {}", e))?;
+        let database_ok = database_messages.len() == 1 && database_messages[0] == "ok";
+
+        let media = crate::media::media_integrity_report(&conn)?;
+
+        Ok(DataIntegrityReport {
+            ok: database_ok && media.missing_total == 0,
+            database_ok,
+            database_messages,
+            media,
+        })
+    })
+    .await
 }
@@ -0,0 +1,248 @@
+use tauri::command;
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use crate::db::{get_db_connection, log_audit_event};
+use crate::models::{DatePlausibilitySettings, OutlierDateRow};
+use crate::validation::{DATE_BEFORE_EPOCH, INVALID_DATE_FORMAT, UNUSUAL_DATE};
+use crate::repository::{get_parsed_setting, get_setting, set_setting};
+
+const DEFAULT_PAST_DAYS: i64 = 365;
+const DEFAULT_FUTURE_DAYS: i64 = 30;
+const DEFAULT_EARLIEST_DATE: &str = "2000-01-01";
+
+// Data-entry commands aren't session-scoped, so acknowledgements are logged under this name
+const AUDIT_USER: &str = "front_desk";
+
+pub fn load_date_plausibility(conn: &Connection) -> Result<DatePlausibilitySettings, String> {
+    let days = |key: &str, default: i64| -> Result<i64, String> {
+        Ok(get_parsed_setting::<i64>(conn, key)?.filter(|d| *d >= 0).unwrap_or(default))
+    };
+
+    Ok(DatePlausibilitySettings {
+        past_days: days("date_window_past_days", DEFAULT_PAST_DAYS)?,
+        future_days: days("date_window_future_days", DEFAULT_FUTURE_DAYS)?,
+        earliest_date: get_setting(conn, "date_earliest_allowed")?
+            .filter(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok())
+            .unwrap_or_else(|| DEFAULT_EARLIEST_DATE.to_string()),
+    })
+}
+
+/// Check a user-entered date against the plausibility window around today.
+///
+/// Malformed dates and dates before the earliest allowed date are rejected
+/// outright. Dates outside the window fail with UNUSUAL_DATE, echoing the
+/// parsed date, unless `acknowledged` is set; in that case the returned note
+/// should be passed to `log_acknowledged_dates` once the write succeeds.
+pub fn check_date_plausibility(
+    conn: &Connection,
+    field: &str,
+    date: &str,
+    acknowledged: bool,
+) -> Result<Option<String>, String> {
+    check_date(conn, field, date, acknowledged, true)
+}
+
+/// Same checks for imported history, where any past date is expected and
+/// only future dates and dates before the earliest allowed one are suspicious.
+pub fn check_historical_date_plausibility(
+    conn: &Connection,
+    field: &str,
+    date: &str,
+    acknowledged: bool,
+) -> Result<Option<String>, String> {
+    check_date(conn, field, date, acknowledged, false)
+}
+
+fn check_date(
+    conn: &Connection,
+    field: &str,
+    date: &str,
+    acknowledged: bool,
+    check_past: bool,
+) -> Result<Option<String>, String> {
+    let parsed = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("{}: {} '{}' is not a YYYY-MM-DD date", INVALID_DATE_FORMAT, field, date))?;
+    let settings = load_date_plausibility(conn)?;
+
+    let earliest = NaiveDate::parse_from_str(&settings.earliest_date, "%Y-%m-%d")
+        .map_err(|e| e.to_string())?;
+    if parsed < earliest {
+        return Err(format!(
+            "{}: {} {} is before {}",
+            DATE_BEFORE_EPOCH, field, parsed.format("%d %b %Y"), settings.earliest_date
+        ));
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let offset = (parsed - today).num_days();
+    let description = if check_past && offset < -settings.past_days {
+        format!("{} days in the past", -offset)
+    } else if offset > settings.future_days {
+        format!("{} days in the future", offset)
+    } else {
+        return Ok(None);
+    };
+
+    let detail = format!("{} {} ({}) is {}", field, parsed, parsed.format("%d %b %Y"), description);
+    if acknowledged {
+        Ok(Some(detail))
+    } else {
+        Err(format!("{}: {}", UNUSUAL_DATE, detail))
+    }
+}
+
+/// Audit-log unusual dates the user confirmed.
+pub fn log_acknowledged_dates(conn: &Connection, context: &str, notes: &[Option<String>]) -> Result<(), String> {
+    for note in notes.iter().flatten() {
+        log_audit_event(conn, AUDIT_USER, "unusual_date_acknowledged", &format!("{}: {}", context, note))
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[command]
+pub fn get_date_plausibility_settings() -> Result<DatePlausibilitySettings, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    load_date_plausibility(&conn)
+}
+
+#[command]
+pub fn set_date_plausibility_settings(past_days: i64, future_days: i64, earliest_date: String) -> Result<String, String> {
+    if !(1..=3650).contains(&past_days) || !(0..=3650).contains(&future_days) {
+        return Err("Date window must be 1-3650 days in the past and 0-3650 days in the future".to_string());
+    }
+    let earliest_date = earliest_date.trim().to_string();
+    NaiveDate::parse_from_str(&earliest_date, "%Y-%m-%d")
+        .map_err(|_| format!("{}: earliest date must be YYYY-MM-DD", INVALID_DATE_FORMAT))?;
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    for (key, value) in [
+        ("date_window_past_days", past_days.to_string()),
+        ("date_window_future_days", future_days.to_string()),
+        ("date_earliest_allowed", earliest_date),
+    ] {
+        set_setting(&conn, key, &value)?;
+    }
+
+    Ok("Date checks saved".to_string())
+}
+
+/// Existing rows whose business date is outside the plausibility window
+/// relative to when the row was entered, i.e. likely typos. Imported history
+/// is left out since its entry time says nothing about the stay dates.
+#[command]
+pub fn find_outlier_dates() -> Result<Vec<OutlierDateRow>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let settings = load_date_plausibility(&conn)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT 'expenses', id, 'date', date, category || COALESCE(' - ' || description, ''), created_at
+             FROM expenses
+             WHERE created_at IS NOT NULL AND deleted_at IS NULL
+               AND (date < ?3
+                    OR julianday(date) - julianday(date(created_at)) < -?1
+                    OR julianday(date) - julianday(date(created_at)) > ?2)
+             UNION ALL
+             SELECT 'customers', id, 'check_in', check_in, name, created_at
+             FROM customers
+             WHERE created_at IS NOT NULL AND imported_from IS NULL
+               AND (check_in < ?3
+                    OR julianday(check_in) - julianday(date(created_at)) < -?1
+                    OR julianday(check_in) - julianday(date(created_at)) > ?2)
+             UNION ALL
+             SELECT 'customers', id, 'check_out', check_out, name, created_at
+             FROM customers
+             WHERE created_at IS NOT NULL AND imported_from IS NULL AND check_out IS NOT NULL
+               AND (check_out < ?3
+                    OR julianday(check_out) - julianday(date(created_at)) < -?1
+                    OR julianday(check_out) - julianday(date(created_at)) > ?2)
+             ORDER BY 1, 2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(
+            params![settings.past_days, settings.future_days, settings.earliest_date],
+            |row| {
+                Ok(OutlierDateRow {
+                    table: row.get(0)?,
+                    row_id: row.get(1)?,
+                    field: row.get(2)?,
+                    date: row.get(3)?,
+                    description: row.get(4)?,
+                    entered_at: row.get(5)?,
+                })
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{add_expense, update_guest};
+    use crate::test_support::{add_test_expense, check_in, days_from_today, TestDb};
+
+    /// A 3.00 supplies expense dated `date`, as typed in at the front desk.
+    fn expense(date: &str, acknowledged: Option<bool>) -> Result<i64, crate::errors::AppError> {
+        add_expense(date.to_string(), "Supplies".to_string(), Some("Year typo".to_string()), 3.0, None, acknowledged, None, None)
+    }
+
+    #[test]
+    fn unusual_dates_need_acknowledging() {
+        let _db = TestDb::new();
+        add_test_expense("Supplies", "Cleaning", 1.0);
+        let backdated = days_from_today(-400);
+        let shown = NaiveDate::parse_from_str(&backdated, "%Y-%m-%d").unwrap().format("%d %b %Y").to_string();
+        let warned = expense(&backdated, None);
+        assert!(
+            warned.as_ref().is_err_and(|e| e.code() == UNUSUAL_DATE && e.message().contains(&shown)),
+            "a date 400 days back gave {:?}", warned
+        );
+        let guest_id = check_in("Unusual Guest", None);
+        let warned = update_guest(guest_id, None, None, None, None, Some(days_from_today(45)), None, None, None);
+        assert!(warned.is_err_and(|e| e.code() == UNUSUAL_DATE), "a check-out 45 days ahead was saved without acknowledgement");
+
+        let old = expense(&backdated, Some(true)).unwrap();
+        let conn = get_db_connection().unwrap();
+        let logged: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM audit_log WHERE event_type = 'unusual_date_acknowledged' AND details LIKE '%' || ?1 || '%'",
+                params![backdated],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(logged, 1, "acknowledged backdated expense {} not audited once", old);
+    }
+
+    #[test]
+    fn acknowledging_does_not_let_impossible_dates_through() {
+        let _db = TestDb::new();
+        add_test_expense("Supplies", "Cleaning", 1.0);
+        assert!(
+            expense("1999-12-31", Some(true)).is_err_and(|e| e.code() == DATE_BEFORE_EPOCH),
+            "an acknowledged date before 2000 was accepted"
+        );
+        assert!(expense("2025-13-01", Some(true)).is_err_and(|e| e.code() == INVALID_DATE_FORMAT), "an acknowledged malformed date was accepted");
+    }
+
+    #[test]
+    fn outlier_dates_follow_the_plausibility_window() {
+        let _db = TestDb::new();
+        add_test_expense("Supplies", "Cleaning", 1.0);
+        let usual = expense(&days_from_today(0), None).unwrap();
+        let old = expense(&days_from_today(-400), Some(true)).unwrap();
+        let ahead = expense(&days_from_today(45), Some(true)).unwrap();
+        let outliers: Vec<i64> = find_outlier_dates().unwrap().into_iter().filter(|r| r.table == "expenses").map(|r| r.row_id).collect();
+        assert!(
+            outliers.contains(&old) && outliers.contains(&ahead) && !outliers.contains(&usual),
+            "outlier finder listed expenses {:?}; expected {} and {} but not {}", outliers, old, ahead, usual
+        );
+        set_date_plausibility_settings(500, 60, "2000-01-01".to_string()).unwrap();
+        let widened = find_outlier_dates().unwrap();
+        assert!(widened.iter().all(|r| r.row_id != old && r.row_id != ahead), "outlier finder ignores a wider window");
+        assert!(expense(&days_from_today(-400), None).is_ok(), "a date inside the wider window still needs acknowledging");
+    }
+}
@@ -1,22 +1,164 @@
 use rusqlite::{Connection, Result as SqliteResult, Transaction};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, OnceLock};
 use chrono::Utc;
+use crate::errors::AppError;
+use crate::validation::{INVALID_DATE_FORMAT, NEGATIVE_AMOUNT};
 use std::collections::HashSet;
 
-pub fn get_db_connection() -> SqliteResult<Connection> {
-    let db_path = get_db_path();
-    let conn = Connection::open(&db_path)?;
+// Idle connections kept open per database file; extra ones are closed when returned
+const MAX_IDLE_CONNECTIONS: usize = 8;
+
+struct ConnectionPool {
+    // Bumped by close_pooled_connections so connections borrowed before it aren't pooled again
+    generation: u64,
+    idle: HashMap<PathBuf, Vec<Connection>>,
+}
+
+// Process-wide rather than Tauri managed state: get_db_connection is called
+// from background jobs, the sync listener, the self-test and unit tests, none
+// of which have an AppHandle to look the pool up through
+fn connection_pool() -> &'static Mutex<ConnectionPool> {
+    static POOL: OnceLock<Mutex<ConnectionPool>> = OnceLock::new();
+    POOL.get_or_init(|| Mutex::new(ConnectionPool { generation: 0, idle: HashMap::new() }))
+}
+
+/// A connection borrowed from the pool. Derefs to `Connection` and goes back
+/// to the pool when dropped.
+pub struct PooledConnection {
+    conn: Option<Connection>,
+    path: PathBuf,
+    generation: u64,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("pooled connection used after drop")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().expect("pooled connection used after drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(conn) = self.conn.take() else { return };
+        // A connection still inside a transaction would hand it to the next borrower
+        if !conn.is_autocommit() {
+            return;
+        }
+        if let Ok(mut pool) = connection_pool().lock() {
+            if pool.generation != self.generation {
+                return;
+            }
+            let idle = pool.idle.entry(self.path.clone()).or_default();
+            if idle.len() < MAX_IDLE_CONNECTIONS {
+                idle.push(conn);
+            }
+        }
+    }
+}
+
+fn open_connection(path: &Path) -> SqliteResult<Connection> {
+    let conn = Connection::open(path)?;
+    // An encrypted database must get its key before anything else reads it
+    crate::db_encryption::apply_key(&conn, path)?;
     
     // Set pragmas for performance and data integrity
     // PRAGMA journal_mode returns the previous mode, so we need to handle it properly
     let _: String = conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
     conn.execute("PRAGMA synchronous=NORMAL", [])?;
     conn.execute("PRAGMA foreign_keys=ON", [])?;
+    // Wait for a competing writer instead of failing immediately with SQLITE_BUSY
+    conn.busy_timeout(std::time::Duration::from_secs(5))?;
     
     Ok(conn)
 }
 
+/// Borrow a connection to the database at `path`, reusing an idle one when
+/// available so the open and pragma setup only happen once per connection.
+pub fn get_pooled_connection(path: &Path) -> SqliteResult<PooledConnection> {
+    let (idle, generation) = {
+        let mut pool = connection_pool().lock().unwrap_or_else(|e| e.into_inner());
+        let generation = pool.generation;
+        (pool.idle.get_mut(path).and_then(|conns| conns.pop()), generation)
+    };
+    let conn = match idle {
+        Some(conn) => conn,
+        None => open_connection(path)?,
+    };
+    Ok(PooledConnection { conn: Some(conn), path: path.to_path_buf(), generation })
+}
+
+pub fn get_db_connection() -> SqliteResult<PooledConnection> {
+    get_pooled_connection(&get_db_path())
+}
+
+/// Close every idle pooled connection; connections borrowed right now are
+/// closed when returned. Call before copying, replacing or deleting a database
+/// file: closing the last connection checkpoints the WAL into the main file,
+/// and no stale handle keeps the old file open afterwards.
+pub fn close_pooled_connections() {
+    let idle = {
+        let mut pool = connection_pool().lock().unwrap_or_else(|e| e.into_inner());
+        pool.generation += 1;
+        std::mem::take(&mut pool.idle)
+    };
+    drop(idle);
+}
+
+thread_local! {
+    // Set while the self-test runs so the commands it drives hit a throwaway database
+    static DB_PATH_OVERRIDE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with every database access on this thread redirected to `path`.
+/// Other threads (the UI's own commands) keep using the live database.
+pub fn with_db_path_override<T>(path: PathBuf, f: impl FnOnce() -> T) -> T {
+    // Restores the previous path on drop, so a panicking step can't leave a pooled thread redirected
+    struct Restore(Option<PathBuf>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            DB_PATH_OVERRIDE.with(|cell| *cell.borrow_mut() = previous);
+        }
+    }
+
+    let _restore = Restore(DB_PATH_OVERRIDE.with(|cell| cell.replace(Some(path))));
+    f()
+}
+
+/// Run a command's database work on the blocking thread pool, so long exports,
+/// reports and backups don't hold up IPC. The database path of the calling
+/// thread (e.g. the self-test override) carries over.
+pub async fn run_blocking<T, E>(work: impl FnOnce() -> Result<T, E> + Send + 'static) -> Result<T, E>
+where
+    T: Send + 'static,
+    E: From<String> + Send + 'static,
+{
+    let db_path = get_db_path();
+    tauri::async_runtime::spawn_blocking(move || with_db_path_override(db_path, work))
+        .await
+        .map_err(|e| E::from(format!("Background task did not finish: {}", e)))?
+}
+
 pub fn get_db_path() -> PathBuf {
+    if let Some(path) = DB_PATH_OVERRIDE.with(|cell| cell.borrow().clone()) {
+        return path;
+    }
+    #[cfg(test)]
+    if let Some(path) = crate::test_support::database_path() {
+        return path;
+    }
+    
     // For now, use the current project structure during development
     let mut path = std::env::current_dir().unwrap();
     if path.ends_with("src-tauri") {
@@ -42,6 +184,9 @@ pub fn initialize_database() -> SqliteResult<()> {
     // Verify and fix database schema
     verify_and_fix_schema(&conn)?;
     
+    // Apply versioned migrations and record them for the post-update report
+    crate::migrations::run_pending_migrations(&conn)?;
+    
     // Seed initial data
     seed_initial_data(&conn)?;
     
@@ -73,13 +218,15 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             session_token TEXT PRIMARY KEY,
             admin_id INTEGER NOT NULL,
             expires_at TEXT NOT NULL,
+            last_activity_at TEXT,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             FOREIGN KEY (admin_id) REFERENCES admin_auth(id) ON DELETE CASCADE
         )",
         [],
     )?;
 
-    // Minimal audit log (required by offline_auth.rs)
+    // Audit log: logins (offline_auth.rs) and data changes, which also record
+    // the entity touched and its values before/after as JSON
     conn.execute(
         "CREATE TABLE IF NOT EXISTS audit_log (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -87,7 +234,13 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             username TEXT,
             event_type TEXT NOT NULL,
             ip_address TEXT,
-            user_agent TEXT
+            user_agent TEXT,
+            details TEXT,
+            entity TEXT,
+            entity_id INTEGER,
+            action TEXT,
+            old_values TEXT,
+            new_values TEXT
         )",
         [],
     )?;
@@ -116,6 +269,8 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             guest_id INTEGER,
             is_active INTEGER NOT NULL DEFAULT 1,
             resource_type TEXT NOT NULL DEFAULT 'Room',
+            floor INTEGER,
+            building TEXT,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             FOREIGN KEY (guest_id) REFERENCES customers(id)
@@ -123,7 +278,56 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
         [],
     )?;
     
-    // Customers table (renamed from guests)
+    // Guest profiles: who a guest is, kept across stays. phone_key is the
+    // phone's last ten digits, so local and international forms match
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS guest_profiles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            phone TEXT,
+            phone_key TEXT,
+            email TEXT,
+            id_number TEXT,
+            nationality TEXT,
+            preferences TEXT,
+            notes TEXT,
+            anonymized_at TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    
+    // Group bookings: several rooms booked under one party. With a shared
+    // folio the group settles every room's bill together at group checkout
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS guest_groups (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            contact_name TEXT,
+            contact_phone TEXT,
+            shared_folio INTEGER NOT NULL DEFAULT 1,
+            notes TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    
+    // The kinds of room on offer; resources.room_type holds the type's name
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS room_types (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            base_rate REAL NOT NULL DEFAULT 0,
+            max_occupancy INTEGER,
+            amenities TEXT NOT NULL DEFAULT '[]',
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    
+    // Customers table (renamed from guests); each row is one stay
     conn.execute(
         "CREATE TABLE IF NOT EXISTS customers (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -135,6 +339,22 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             daily_rate REAL NOT NULL,
             status TEXT NOT NULL DEFAULT 'active',
             loyalty_points INTEGER NOT NULL DEFAULT 0,
+            adults INTEGER NOT NULL DEFAULT 1,
+            children INTEGER NOT NULL DEFAULT 0,
+            terms_accepted_at TEXT,
+            signature_path TEXT,
+            billed_amount REAL,
+            imported_from TEXT,
+            import_row_hash TEXT,
+            after_hours INTEGER NOT NULL DEFAULT 0,
+            nightly_pricing INTEGER NOT NULL DEFAULT 0,
+            checkout_total REAL,
+            checkout_food REAL,
+            checkout_service_charge REAL,
+            checked_out_at TEXT,
+            planned_check_out TEXT,
+            profile_id INTEGER REFERENCES guest_profiles(id) ON DELETE SET NULL,
+            group_id INTEGER REFERENCES guest_groups(id) ON DELETE SET NULL,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             FOREIGN KEY (room_id) REFERENCES resources(id) ON DELETE RESTRICT
@@ -152,12 +372,74 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             stock_quantity INTEGER DEFAULT 0,
             track_stock INTEGER DEFAULT 0,
             low_stock_limit INTEGER DEFAULT 5,
+            attributes TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    
+    // The sections of the menu, in the order they're shown; menu_items.category
+    // holds the category's name
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS menu_categories (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            sort_order INTEGER NOT NULL DEFAULT 0,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    
+    // Time-based prices (happy hours) for one menu item or a whole category:
+    // a fixed price or a percentage off, on the weekdays in weekday_mask
+    // (bit 0 = Monday) between start_time and end_time (HH:MM, past midnight
+    // when end_time <= start_time)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS menu_price_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            menu_item_id INTEGER REFERENCES menu_items(id) ON DELETE CASCADE,
+            category TEXT COLLATE NOCASE,
+            weekday_mask INTEGER NOT NULL,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            price REAL,
+            discount_percent REAL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            CHECK ((menu_item_id IS NULL) != (category IS NULL)),
+            CHECK ((price IS NULL) != (discount_percent IS NULL))
+        )",
+        [],
+    )?;
+    
+    // Bundles of menu items sold together at one price
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS combos (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            price REAL NOT NULL,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
         )",
         [],
     )?;
     
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS combo_items (
+            combo_id INTEGER NOT NULL,
+            menu_item_id INTEGER NOT NULL,
+            quantity INTEGER NOT NULL CHECK (quantity > 0),
+            PRIMARY KEY (combo_id, menu_item_id),
+            FOREIGN KEY (combo_id) REFERENCES combos(id) ON DELETE CASCADE,
+            FOREIGN KEY (menu_item_id) REFERENCES menu_items(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    
     // Sales table (renamed from food_orders)
     conn.execute(
         "CREATE TABLE IF NOT EXISTS sales (
@@ -169,7 +451,23 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             paid INTEGER NOT NULL DEFAULT 0,
             paid_at DATETIME,
             total_amount REAL NOT NULL,
-            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL
+            amount_paid REAL NOT NULL DEFAULT 0,
+            after_hours INTEGER NOT NULL DEFAULT 0,
+            notes TEXT,
+            status TEXT NOT NULL DEFAULT 'pending',
+            preparing_at DATETIME,
+            served_at DATETIME,
+            closed_at DATETIME,
+            table_id INTEGER,
+            service_charge_percent REAL NOT NULL DEFAULT 0,
+            service_charge REAL NOT NULL DEFAULT 0,
+            import_ref TEXT,
+            deleted_at DATETIME,
+            deleted_by TEXT,
+            rounding REAL NOT NULL DEFAULT 0,
+            tax_amount REAL NOT NULL DEFAULT 0,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL,
+            FOREIGN KEY (table_id) REFERENCES dining_tables(id) ON DELETE SET NULL
         )",
         [],
     )?;
@@ -184,11 +482,123 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             unit_price REAL NOT NULL,
             quantity INTEGER NOT NULL,
             line_total REAL NOT NULL,
+            attributes TEXT,
+            notes TEXT,
+            combo_id INTEGER REFERENCES combos(id) ON DELETE SET NULL,
+            original_unit_price REAL,
+            price_rule_id INTEGER REFERENCES menu_price_rules(id) ON DELETE SET NULL,
             FOREIGN KEY (order_id) REFERENCES sales(id) ON DELETE CASCADE,
             FOREIGN KEY (menu_item_id) REFERENCES menu_items(id) ON DELETE SET NULL
         )",
         [],
     )?;
+
+    // What one portion of a combo line was made of when it was ordered, so the
+    // kitchen ticket and stock follow the components rather than the combo
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sale_item_components (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sale_item_id INTEGER NOT NULL,
+            menu_item_id INTEGER,
+            item_name TEXT NOT NULL,
+            quantity INTEGER NOT NULL,
+            FOREIGN KEY (sale_item_id) REFERENCES sale_items(id) ON DELETE CASCADE,
+            FOREIGN KEY (menu_item_id) REFERENCES menu_items(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Free-text changes to a line ("extra cheese"), each adding to or taking off its unit price
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sale_item_modifiers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sale_item_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            price_delta REAL NOT NULL DEFAULT 0,
+            FOREIGN KEY (sale_item_id) REFERENCES sale_items(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    
+    // Payments against a sale (order_id) or a guest's bill at checkout (guest_id);
+    // sales.paid and amount_paid are derived from the order payments
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_id INTEGER,
+            guest_id INTEGER,
+            amount REAL NOT NULL CHECK (amount > 0 OR refund_of IS NOT NULL),
+            method TEXT NOT NULL CHECK (method IN ('cash', 'card', 'bank_transfer')),
+            paid_at DATETIME NOT NULL,
+            note TEXT,
+            is_deposit INTEGER NOT NULL DEFAULT 0,
+            refund_of INTEGER,
+            shift_id INTEGER,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (order_id) REFERENCES sales(id) ON DELETE CASCADE,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL,
+            FOREIGN KEY (refund_of) REFERENCES payments(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+    
+    // Card payments taken online through a payment gateway, for an order or
+    // an in-house guest's folio; payment_id is the payment recorded once it succeeds
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS payment_intents (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_type TEXT NOT NULL CHECK (source_type IN ('order', 'folio')),
+            source_id INTEGER NOT NULL,
+            provider TEXT NOT NULL,
+            intent_id TEXT NOT NULL UNIQUE,
+            client_secret TEXT,
+            amount REAL NOT NULL,
+            currency TEXT NOT NULL,
+            status TEXT NOT NULL,
+            payment_id INTEGER,
+            note TEXT,
+            paid_at TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (payment_id) REFERENCES payments(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+    
+    // Gift vouchers and promo codes: a fixed amount or a percent off the bill,
+    // usable max_uses times (NULL for no limit) until expires_on
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS vouchers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            code TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            kind TEXT NOT NULL CHECK (kind IN ('amount', 'percent')),
+            value REAL NOT NULL CHECK (value > 0),
+            expires_on TEXT,
+            max_uses INTEGER CHECK (max_uses IS NULL OR max_uses > 0),
+            uses INTEGER NOT NULL DEFAULT 0,
+            is_active INTEGER NOT NULL DEFAULT 1,
+            note TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+    
+    // Each time a voucher came off an order or a checkout bill, and by how much
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS voucher_redemptions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            voucher_id INTEGER NOT NULL,
+            order_id INTEGER,
+            guest_id INTEGER,
+            amount REAL NOT NULL,
+            redeemed_by TEXT,
+            redeemed_at TEXT NOT NULL,
+            FOREIGN KEY (voucher_id) REFERENCES vouchers(id) ON DELETE CASCADE,
+            FOREIGN KEY (order_id) REFERENCES sales(id) ON DELETE CASCADE,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
     
     // Expenses table with created_at
     conn.execute(
@@ -198,11 +608,80 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             category TEXT NOT NULL,
             description TEXT,
             amount REAL NOT NULL,
+            is_drawing INTEGER NOT NULL DEFAULT 0,
+            tax_amount REAL NOT NULL DEFAULT 0,
+            deleted_at DATETIME,
+            deleted_by TEXT,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP
         )",
         [],
     )?;
 
+    // The categories expenses can be booked under
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS expense_categories (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Planned spend per expense category, the same every month
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS expense_budgets (
+            category TEXT PRIMARY KEY COLLATE NOCASE,
+            monthly_amount REAL NOT NULL CHECK (monthly_amount >= 0),
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Chart of accounts for the ledger; system_key marks the accounts postings
+    // are made to automatically
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS ledger_accounts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            code TEXT NOT NULL UNIQUE,
+            name TEXT NOT NULL,
+            account_type TEXT NOT NULL CHECK (account_type IN ('asset', 'liability', 'equity', 'revenue', 'expense')),
+            system_key TEXT UNIQUE,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Journal entries are never edited: a changed source gets a reversal and a
+    // fresh entry. source_type/source_id are NULL for manual entries.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS journal_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_date TEXT NOT NULL,
+            memo TEXT NOT NULL,
+            source_type TEXT,
+            source_id INTEGER,
+            fingerprint TEXT,
+            reversal_of INTEGER,
+            reversed_by INTEGER,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (reversal_of) REFERENCES journal_entries(id)
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS journal_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entry_id INTEGER NOT NULL,
+            account_id INTEGER NOT NULL,
+            debit REAL NOT NULL DEFAULT 0 CHECK (debit >= 0),
+            credit REAL NOT NULL DEFAULT 0 CHECK (credit >= 0),
+            FOREIGN KEY (entry_id) REFERENCES journal_entries(id) ON DELETE CASCADE,
+            FOREIGN KEY (account_id) REFERENCES ledger_accounts(id) ON DELETE RESTRICT
+        )",
+        [],
+    )?;
+
     // Shifts table for Z-reports (end-of-day closing)
     conn.execute(
         "CREATE TABLE IF NOT EXISTS shifts (
@@ -218,11 +697,574 @@ fn create_initial_schema(conn: &Connection) -> SqliteResult<()> {
             total_sales REAL DEFAULT 0.0,
             total_expenses REAL DEFAULT 0.0,
             status TEXT DEFAULT 'open',
-            notes TEXT
+            notes TEXT,
+            denominations TEXT,
+            owner_drawings REAL DEFAULT 0.0,
+            after_hours_count INTEGER DEFAULT 0,
+            cash_sales REAL DEFAULT 0.0,
+            reconciliation TEXT
         )",
         [],
     )?;
     
+    // Guest feedback captured at checkout (one row per stay)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS guest_feedback (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guest_id INTEGER NOT NULL UNIQUE,
+            room_id INTEGER,
+            shift_id INTEGER,
+            staff_admin_id INTEGER,
+            rating INTEGER NOT NULL CHECK (rating BETWEEN 1 AND 5),
+            comment TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE CASCADE,
+            FOREIGN KEY (room_id) REFERENCES resources(id) ON DELETE SET NULL,
+            FOREIGN KEY (shift_id) REFERENCES shifts(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Daily specials board: portions prepared per day, decremented as they are ordered
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS daily_specials (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            special_date TEXT NOT NULL,
+            menu_item_id INTEGER,
+            name TEXT NOT NULL,
+            price REAL NOT NULL,
+            prepared_quantity INTEGER NOT NULL,
+            sold_quantity INTEGER NOT NULL DEFAULT 0,
+            rolled_off INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (special_date, name),
+            CHECK (sold_quantity <= prepared_quantity),
+            FOREIGN KEY (menu_item_id) REFERENCES menu_items(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Free-form labels (VIP, Corporate, ...) attachable to guests and orders
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS entity_tags (
+            tag_id INTEGER NOT NULL,
+            entity_type TEXT NOT NULL CHECK (entity_type IN ('guest', 'order')),
+            entity_id INTEGER NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY (tag_id, entity_type, entity_id),
+            FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Extra charges and credits posted to a guest's folio outside food orders
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS guest_adjustments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guest_id INTEGER NOT NULL,
+            description TEXT NOT NULL,
+            amount REAL NOT NULL,
+            source TEXT NOT NULL DEFAULT 'manual',
+            created_by TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Miscellaneous items billed to a room (minibar, laundry, late checkout, ...)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS guest_charges (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guest_id INTEGER NOT NULL,
+            category TEXT NOT NULL,
+            description TEXT NOT NULL,
+            quantity INTEGER NOT NULL DEFAULT 1 CHECK (quantity > 0),
+            unit_price REAL NOT NULL CHECK (unit_price >= 0),
+            amount REAL NOT NULL,
+            charged_on TEXT NOT NULL,
+            created_by TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Room moves during a stay. Each row closes the stay's time in from_room_id at
+    // from_rate; the stay's own room_id and daily_rate bill from the last move on.
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS room_transfers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guest_id INTEGER NOT NULL,
+            from_room_id INTEGER,
+            to_room_id INTEGER,
+            from_rate REAL NOT NULL,
+            to_rate REAL NOT NULL,
+            effective_date TEXT NOT NULL,
+            created_by TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE CASCADE,
+            FOREIGN KEY (from_room_id) REFERENCES resources(id) ON DELETE SET NULL,
+            FOREIGN KEY (to_room_id) REFERENCES resources(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Holds that block checkout until resolved (e.g. damage reported by housekeeping)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS checkout_holds (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guest_id INTEGER NOT NULL,
+            reason TEXT NOT NULL,
+            amount_estimate REAL,
+            status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'released', 'converted')),
+            placed_by TEXT NOT NULL,
+            placed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            resolved_by TEXT,
+            resolved_at DATETIME,
+            adjustment_id INTEGER,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE CASCADE,
+            FOREIGN KEY (adjustment_id) REFERENCES guest_adjustments(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Repair tickets; an unresolved out-of-service ticket keeps the room from being assigned
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS maintenance_tickets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            room_id INTEGER NOT NULL,
+            issue TEXT NOT NULL,
+            priority TEXT NOT NULL DEFAULT 'normal' CHECK (priority IN ('low', 'normal', 'high', 'urgent')),
+            status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'in_progress', 'resolved')),
+            out_of_service INTEGER NOT NULL DEFAULT 0,
+            cost REAL,
+            reported_by TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            resolved_at DATETIME,
+            FOREIGN KEY (room_id) REFERENCES resources(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Ingredient stock, in whatever unit the kitchen counts it in
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS inventory_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            unit TEXT NOT NULL DEFAULT 'pcs',
+            quantity REAL NOT NULL DEFAULT 0,
+            low_stock_limit REAL NOT NULL DEFAULT 0,
+            unit_cost REAL NOT NULL DEFAULT 0,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // How much of each ingredient one portion of a menu item uses
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS menu_item_recipes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            menu_item_id INTEGER NOT NULL,
+            inventory_item_id INTEGER NOT NULL,
+            quantity REAL NOT NULL CHECK (quantity > 0),
+            UNIQUE (menu_item_id, inventory_item_id),
+            FOREIGN KEY (menu_item_id) REFERENCES menu_items(id) ON DELETE CASCADE,
+            FOREIGN KEY (inventory_item_id) REFERENCES inventory_items(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Every change to an ingredient's stock: orders, deliveries, waste and recounts
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS stock_movements (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            inventory_item_id INTEGER NOT NULL,
+            change REAL NOT NULL,
+            reason TEXT NOT NULL CHECK (reason IN ('order', 'restock', 'waste', 'correction')),
+            order_id INTEGER,
+            note TEXT,
+            created_by TEXT,
+            created_at DATETIME NOT NULL,
+            FOREIGN KEY (inventory_item_id) REFERENCES inventory_items(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Who the business buys stock from
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS suppliers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            contact_name TEXT,
+            phone TEXT,
+            email TEXT,
+            notes TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS purchase_orders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            supplier_id INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'ordered' CHECK (status IN ('ordered', 'partially_received', 'received', 'cancelled')),
+            order_date TEXT NOT NULL,
+            expected_date TEXT,
+            notes TEXT,
+            total_amount REAL NOT NULL DEFAULT 0,
+            created_by TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            received_at DATETIME,
+            FOREIGN KEY (supplier_id) REFERENCES suppliers(id)
+        )",
+        [],
+    )?;
+
+    // Lines without an inventory item (e.g. cleaning supplies) are paid for but not stocked
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS purchase_order_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            purchase_order_id INTEGER NOT NULL,
+            inventory_item_id INTEGER,
+            description TEXT NOT NULL,
+            quantity REAL NOT NULL CHECK (quantity > 0),
+            unit_cost REAL NOT NULL CHECK (unit_cost >= 0),
+            received_quantity REAL NOT NULL DEFAULT 0,
+            FOREIGN KEY (purchase_order_id) REFERENCES purchase_orders(id) ON DELETE CASCADE,
+            FOREIGN KEY (inventory_item_id) REFERENCES inventory_items(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Each payment is also an expense row, so purchases count in the expense totals
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS supplier_payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            supplier_id INTEGER NOT NULL,
+            purchase_order_id INTEGER,
+            amount REAL NOT NULL CHECK (amount > 0),
+            method TEXT NOT NULL,
+            paid_on TEXT NOT NULL,
+            note TEXT,
+            expense_id INTEGER,
+            created_by TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (supplier_id) REFERENCES suppliers(id),
+            FOREIGN KEY (purchase_order_id) REFERENCES purchase_orders(id) ON DELETE SET NULL,
+            FOREIGN KEY (expense_id) REFERENCES expenses(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Named taxes; 'rooms' taxes also cover extras and adjustments billed to the room
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS taxes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            percent REAL NOT NULL CHECK (percent >= 0 AND percent <= 100),
+            applies_to TEXT NOT NULL DEFAULT 'all' CHECK (applies_to IN ('rooms', 'food', 'all')),
+            is_active INTEGER NOT NULL DEFAULT 1,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Each tax charged on a checkout bill or walk-in order, as it was charged, for the tax return
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tax_charges (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            guest_id INTEGER,
+            order_id INTEGER REFERENCES sales(id) ON DELETE CASCADE,
+            tax_id INTEGER,
+            name TEXT NOT NULL,
+            percent REAL NOT NULL,
+            applies_to TEXT NOT NULL,
+            taxable REAL NOT NULL,
+            amount REAL NOT NULL,
+            included INTEGER NOT NULL DEFAULT 0,
+            charged_on TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Each paid order or checkout bill submitted to the tax authority, with the
+    // fiscal invoice number it came back with
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS fiscal_invoices (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_type TEXT NOT NULL CHECK (source_type IN ('order', 'checkout')),
+            source_id INTEGER NOT NULL,
+            usin TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'submitted', 'failed')),
+            fiscal_number TEXT,
+            qr_payload TEXT,
+            error TEXT,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            attempted_at DATETIME,
+            submitted_at TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (source_type, source_id)
+        )",
+        [],
+    )?;
+
+    // Companies that guests can be billed to on credit (the city ledger)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS companies (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            contact_name TEXT,
+            phone TEXT,
+            email TEXT,
+            billing_address TEXT,
+            credit_limit REAL CHECK (credit_limit >= 0),
+            notes TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Checkout balances posted to a company instead of being paid by the guest
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS company_charges (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            company_id INTEGER NOT NULL,
+            guest_id INTEGER,
+            amount REAL NOT NULL CHECK (amount > 0 OR reversal_of IS NOT NULL),
+            description TEXT NOT NULL,
+            posted_on TEXT NOT NULL,
+            created_by TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            reversal_of INTEGER,
+            FOREIGN KEY (company_id) REFERENCES companies(id),
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL,
+            FOREIGN KEY (reversal_of) REFERENCES company_charges(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS company_payments (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            company_id INTEGER NOT NULL,
+            amount REAL NOT NULL CHECK (amount > 0),
+            method TEXT NOT NULL,
+            paid_on TEXT NOT NULL,
+            note TEXT,
+            created_by TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (company_id) REFERENCES companies(id)
+        )",
+        [],
+    )?;
+
+    // Order lines taken off the bill, kept for management review even if the order is deleted
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS voided_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            sale_item_id INTEGER NOT NULL UNIQUE,
+            order_id INTEGER NOT NULL,
+            item_name TEXT NOT NULL,
+            quantity INTEGER NOT NULL,
+            amount REAL NOT NULL,
+            reason TEXT NOT NULL,
+            note TEXT,
+            voided_by TEXT,
+            voided_at DATETIME NOT NULL
+        )",
+        [],
+    )?;
+
+    // Restaurant tables; a table is open from its first order until it's closed
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS dining_tables (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            number TEXT NOT NULL UNIQUE,
+            capacity INTEGER NOT NULL DEFAULT 4 CHECK (capacity > 0),
+            status TEXT NOT NULL DEFAULT 'free' CHECK (status IN ('free', 'open')),
+            opened_at DATETIME,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Per-date rate overrides by room type
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rate_overrides (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            room_type TEXT NOT NULL,
+            date TEXT NOT NULL,
+            daily_rate REAL NOT NULL,
+            source TEXT NOT NULL DEFAULT 'manual',
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (room_type, date)
+        )",
+        [],
+    )?;
+
+    // Seasonal/weekend rates by room type; a plan needs a date range, a weekday mask
+    // (bit 0 = Monday .. bit 6 = Sunday) or both
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rate_plans (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            room_type TEXT NOT NULL,
+            start_date TEXT,
+            end_date TEXT,
+            weekday_mask INTEGER CHECK (weekday_mask BETWEEN 1 AND 127),
+            daily_rate REAL NOT NULL CHECK (daily_rate >= 0),
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Floor/ceiling the pricing suggestions must stay within, per room type
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS room_type_rate_bounds (
+            room_type TEXT PRIMARY KEY,
+            floor_rate REAL NOT NULL,
+            ceiling_rate REAL NOT NULL
+        )",
+        [],
+    )?;
+
+    // Dates the property is closed (no pricing suggestions are made for them)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS closure_periods (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            start_date TEXT NOT NULL,
+            end_date TEXT NOT NULL,
+            reason TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Price change history written by bulk price updates
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS menu_price_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            menu_item_id INTEGER NOT NULL,
+            old_price REAL NOT NULL,
+            new_price REAL NOT NULL,
+            changed_at TEXT NOT NULL,
+            FOREIGN KEY (menu_item_id) REFERENCES menu_items(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS room_rate_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            room_id INTEGER NOT NULL,
+            old_rate REAL NOT NULL,
+            new_rate REAL NOT NULL,
+            changed_at TEXT NOT NULL,
+            FOREIGN KEY (room_id) REFERENCES resources(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    // Opening hours per weekday (0 = Monday); close < open means the night runs past midnight
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS business_hours (
+            weekday INTEGER PRIMARY KEY CHECK (weekday BETWEEN 0 AND 6),
+            open_time TEXT NOT NULL,
+            close_time TEXT NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Future-dated bookings; converted to a customers row on arrival
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS reservations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            room_id INTEGER NOT NULL,
+            guest_name TEXT NOT NULL,
+            phone TEXT,
+            check_in TEXT NOT NULL,
+            check_out TEXT NOT NULL,
+            daily_rate REAL NOT NULL,
+            adults INTEGER NOT NULL DEFAULT 1,
+            children INTEGER NOT NULL DEFAULT 0,
+            notes TEXT,
+            status TEXT NOT NULL DEFAULT 'booked' CHECK (status IN ('booked', 'cancelled', 'checked_in')),
+            guest_id INTEGER,
+            group_id INTEGER REFERENCES guest_groups(id) ON DELETE SET NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (room_id) REFERENCES resources(id),
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Which guest messages go out automatically, over which channel, with what text
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS notification_events (
+            event_type TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL DEFAULT 0,
+            channel TEXT NOT NULL DEFAULT 'sms' CHECK (channel IN ('sms', 'whatsapp')),
+            template TEXT,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    // Every SMS/WhatsApp message sent or attempted
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS notification_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event_type TEXT NOT NULL,
+            channel TEXT NOT NULL,
+            recipient TEXT NOT NULL,
+            message TEXT NOT NULL,
+            status TEXT NOT NULL CHECK (status IN ('sent', 'failed')),
+            error TEXT,
+            provider_message_id TEXT,
+            guest_id INTEGER,
+            reservation_id INTEGER,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL,
+            FOREIGN KEY (reservation_id) REFERENCES reservations(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    // Versioned migrations applied by migrations::run_pending_migrations
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            app_version TEXT NOT NULL,
+            applied_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            rows_affected INTEGER NOT NULL DEFAULT 0,
+            schema_changes TEXT NOT NULL DEFAULT '[]',
+            seeded_settings TEXT NOT NULL DEFAULT '[]'
+        )",
+        [],
+    )?;
+
     // Admin settings table for password storage with timestamps
     // Phase 3 (White-labeling): add optional branding fields
     conn.execute(
@@ -320,9 +1362,39 @@ fn create_indexes(conn: &Connection) -> SqliteResult<()> {
     // Primary operational indexes
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_customers_status ON customers(status)", []);
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_customers_room_id ON customers(room_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_customers_profile_id ON customers(profile_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_customers_group_id ON customers(group_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_reservations_group_id ON reservations(group_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_guest_profiles_phone_key ON guest_profiles(phone_key)", []);
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_sales_guest_id ON sales(guest_id)", []);
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_sale_items_order_id ON sale_items(order_id)", []);
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_sale_items_menu_item_id ON sale_items(menu_item_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_sale_item_modifiers_item ON sale_item_modifiers(sale_item_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_sale_item_components_item ON sale_item_components(sale_item_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_voided_items_voided_at ON voided_items(voided_at)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_menu_item_recipes_menu_item ON menu_item_recipes(menu_item_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_stock_movements_item ON stock_movements(inventory_item_id, created_at)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_rate_plans_room_type ON rate_plans(room_type)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_purchase_orders_supplier ON purchase_orders(supplier_id, status)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_purchase_order_lines_po ON purchase_order_lines(purchase_order_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_supplier_payments_supplier ON supplier_payments(supplier_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_company_charges_company ON company_charges(company_id, posted_on)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_company_payments_company ON company_payments(company_id, paid_on)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_payments_order_id ON payments(order_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_payments_paid_at ON payments(paid_at)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_payments_guest_id ON payments(guest_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_payments_refund_of ON payments(refund_of)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_payment_intents_source ON payment_intents(source_type, source_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_voucher_redemptions_voucher ON voucher_redemptions(voucher_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_voucher_redemptions_order ON voucher_redemptions(order_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_voucher_redemptions_guest ON voucher_redemptions(guest_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON audit_log(entity, entity_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp ON audit_log(timestamp)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_reservations_room_dates ON reservations(room_id, check_in, check_out)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_journal_entries_source ON journal_entries(source_type, source_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_journal_entries_date ON journal_entries(entry_date)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_journal_lines_entry ON journal_lines(entry_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_tax_charges_charged_on ON tax_charges(charged_on)", []);
     
     // Timestamp indexes for analytics and filtering (safe with error handling)
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_sales_created_at ON sales(created_at)", []);
@@ -333,6 +1405,16 @@ fn create_indexes(conn: &Connection) -> SqliteResult<()> {
     
     // Payment status index for financial reports
     let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_sales_paid ON sales(paid)", []);
+    let _ = conn.execute("CREATE UNIQUE INDEX IF NOT EXISTS idx_customers_import_row_hash ON customers(import_row_hash)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_daily_specials_date ON daily_specials(special_date)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_guest_feedback_created_at ON guest_feedback(created_at)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_guest_feedback_room ON guest_feedback(room_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_entity_tags_entity ON entity_tags(entity_type, entity_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_guest_adjustments_guest ON guest_adjustments(guest_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_guest_charges_guest ON guest_charges(guest_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_room_transfers_guest ON room_transfers(guest_id)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_checkout_holds_guest_status ON checkout_holds(guest_id, status)", []);
+    let _ = conn.execute("CREATE INDEX IF NOT EXISTS idx_maintenance_tickets_room_status ON maintenance_tickets(room_id, status)", []);
     
     println!("Database indexes created successfully");
     Ok(())
@@ -352,15 +1434,18 @@ pub fn start_transaction(conn: &Connection) -> SqliteResult<Transaction<'_>> {
     conn.unchecked_transaction()
 }
 
-pub fn validate_date_format(date: &str) -> Result<(), String> {
-    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
-        .map_err(|_| format!("Invalid date format: {}. Expected YYYY-MM-DD", date))?;
+pub fn validate_date_format(date: &str) -> Result<(), AppError> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| AppError::Validation {
+        code: INVALID_DATE_FORMAT.to_string(),
+        message: format!("Invalid date format: {}. Expected YYYY-MM-DD", date),
+        field: None,
+    })?;
     Ok(())
 }
 
-pub fn validate_positive_amount(amount: f64, field_name: &str) -> Result<(), String> {
+pub fn validate_positive_amount(amount: f64, field_name: &str) -> Result<(), AppError> {
     if amount < 0.0 {
-        return Err(format!("{} must be >= 0", field_name));
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, field_name, format!("{} must be >= 0", field_name)));
     }
     Ok(())
 }
@@ -369,6 +1454,16 @@ pub fn get_current_timestamp() -> String {
     Utc::now().to_rfc3339()
 }
 
+/// Record a business action (not a login event) in the audit log.
+pub fn log_audit_event(conn: &Connection, username: &str, event_type: &str, details: &str) -> SqliteResult<()> {
+    conn.execute(
+        "INSERT INTO audit_log (timestamp, username, event_type, ip_address, user_agent, details)
+         VALUES (?1, ?2, ?3, 'localhost', 'Tauri App', ?4)",
+        rusqlite::params![get_current_timestamp(), username, event_type, details],
+    )?;
+    Ok(())
+}
+
 #[allow(dead_code)]
 pub fn is_room_available(room_id: i64) -> SqliteResult<bool> {
     let conn = get_db_connection()?;
@@ -386,6 +1481,42 @@ fn migrate_database(conn: &Connection) -> SqliteResult<()> {
     // 2025-12: Rename core tables to generic business names (one-time migration)
     ensure_business_table_renames(conn)?;
 
+    // Registration card terms acceptance and signature capture
+    let _ = conn.execute(
+        "ALTER TABLE customers ADD COLUMN terms_accepted_at TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE customers ADD COLUMN signature_path TEXT",
+        [],
+    );
+
+    // Stays imported from the pre-app register
+    let _ = conn.execute(
+        "ALTER TABLE customers ADD COLUMN billed_amount REAL",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE customers ADD COLUMN imported_from TEXT",
+        [],
+    );
+    let _ = conn.execute(
+        "ALTER TABLE customers ADD COLUMN import_row_hash TEXT",
+        [],
+    );
+
+    // Counted cash breakdown recorded when a shift is closed
+    let _ = conn.execute(
+        "ALTER TABLE shifts ADD COLUMN denominations TEXT",
+        [],
+    );
+
+    // Idle-timeout tracking for admin sessions
+    let _ = conn.execute(
+        "ALTER TABLE admin_sessions ADD COLUMN last_activity_at TEXT",
+        [],
+    );
+
     // Add room_type column if it doesn't exist
     let _ = conn.execute(
         "ALTER TABLE resources ADD COLUMN room_type TEXT NOT NULL DEFAULT 'Standard'",
@@ -561,6 +1692,16 @@ fn migrate_database(conn: &Connection) -> SqliteResult<()> {
         "ALTER TABLE menu_items ADD COLUMN low_stock_limit INTEGER DEFAULT 5",
         [],
     );
+    // Selectable per-item attributes (spice level, doneness, ...) as JSON
+    let _ = conn.execute(
+        "ALTER TABLE menu_items ADD COLUMN attributes TEXT",
+        [],
+    );
+    // Attribute selections per order line, stored as text so history survives definition edits
+    let _ = conn.execute(
+        "ALTER TABLE sale_items ADD COLUMN attributes TEXT",
+        [],
+    );
 
     println!("Database migration completed successfully");
     Ok(())
@@ -580,7 +1721,8 @@ fn ensure_audit_log_schema(conn: &Connection) -> SqliteResult<()> {
                 username TEXT,
                 event_type TEXT NOT NULL,
                 ip_address TEXT,
-                user_agent TEXT
+                user_agent TEXT,
+                details TEXT
             )",
             [],
         )?;
@@ -603,6 +1745,9 @@ fn ensure_audit_log_schema(conn: &Connection) -> SqliteResult<()> {
     if !existing.contains("user_agent") {
         let _ = conn.execute("ALTER TABLE audit_log ADD COLUMN user_agent TEXT", []);
     }
+    if !existing.contains("details") {
+        let _ = conn.execute("ALTER TABLE audit_log ADD COLUMN details TEXT", []);
+    }
 
     Ok(())
 }
@@ -0,0 +1,509 @@
+use tauri::command;
+use base64::Engine;
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use crate::db::{close_pooled_connections, get_db_path, get_pooled_connection};
+use crate::errors::AppError;
+use crate::models::DatabaseEncryptionStatus;
+use crate::offline_auth::require_session;
+use crate::validation::UNAUTHORIZED;
+
+const MODES: &[&str] = &["keyring", "password"];
+const ENCRYPTION_ROLES: &[&str] = &["admin"];
+const KEYRING_SERVICE: &str = "hotel-manager";
+// Every plaintext SQLite file starts with this; SQLCipher files look random
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// How the database key is kept, saved beside the database as `<db>.keyinfo`.
+/// Without this file the database is plaintext.
+#[derive(Serialize, Deserialize)]
+struct KeyInfo {
+    mode: String, // "keyring" or "password"
+    // Password mode: whose admin password unlocks it, and the database key
+    // sealed with that password (see backup_crypto)
+    username: Option<String>,
+    wrapped_key: Option<String>,
+}
+
+// Keys of databases unlocked in this process, as hex, by database path
+fn unlocked_keys() -> &'static Mutex<HashMap<PathBuf, String>> {
+    static KEYS: OnceLock<Mutex<HashMap<PathBuf, String>>> = OnceLock::new();
+    KEYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cached_key(path: &Path) -> Option<String> {
+    unlocked_keys().lock().unwrap_or_else(|e| e.into_inner()).get(path).cloned()
+}
+
+fn cache_key(path: &Path, key: Option<String>) {
+    let mut keys = unlocked_keys().lock().unwrap_or_else(|e| e.into_inner());
+    match key {
+        Some(key) => keys.insert(path.to_path_buf(), key),
+        None => keys.remove(path),
+    };
+}
+
+fn keyinfo_path(db_path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.keyinfo", db_path.display()))
+}
+
+fn read_keyinfo(db_path: &Path) -> Result<Option<KeyInfo>, String> {
+    let path = keyinfo_path(db_path);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map(Some).map_err(|e| format!("{} is damaged: {}", path.display(), e))
+}
+
+fn write_keyinfo(db_path: &Path, info: &KeyInfo) -> Result<(), String> {
+    let path = keyinfo_path(db_path);
+    let text = serde_json::to_string_pretty(info).map_err(|e| e.to_string())?;
+    fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn keyring_entry(db_path: &Path) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("database-key:{}", db_path.display()))
+        .map_err(|e| format!("The OS keyring is not available: {}", e))
+}
+
+/// A file that is already a plaintext SQLite database. A database switched
+/// halfway through encryption is left plaintext, so it still opens.
+fn is_plaintext(path: &Path) -> bool {
+    let mut head = [0u8; 16];
+    fs::File::open(path).and_then(|mut f| f.read_exact(&mut head)).is_ok() && head == SQLITE_HEADER
+}
+
+/// The key the database at `path` is encrypted with. None for plaintext
+/// databases; an error while a password-protected database is still locked.
+fn key_for(path: &Path) -> Result<Option<String>, String> {
+    if is_plaintext(path) {
+        return Ok(None);
+    }
+    if let Some(key) = cached_key(path) {
+        return Ok(Some(key));
+    }
+    let Some(info) = read_keyinfo(path)? else {
+        return Ok(None);
+    };
+    match info.mode.as_str() {
+        "keyring" => {
+            let key = keyring_entry(path)?
+                .get_password()
+                .map_err(|e| format!("The database key is missing from the OS keyring: {}", e))?;
+            cache_key(path, Some(key.clone()));
+            Ok(Some(key))
+        }
+        _ => Err("The database is encrypted with the admin password. Unlock it to continue.".to_string()),
+    }
+}
+
+fn pragma_key(key: Option<&str>) -> String {
+    key.map(|k| format!("x'{}'", k)).unwrap_or_default()
+}
+
+/// Give a freshly opened connection the database key. Must run before
+/// anything else touches the database.
+pub fn apply_key(conn: &Connection, path: &Path) -> SqliteResult<()> {
+    let key = key_for(path).map_err(|msg| {
+        rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_AUTH), Some(msg))
+    })?;
+    if let Some(key) = key {
+        conn.execute_batch(&format!("PRAGMA key = \"{}\";", pragma_key(Some(&key))))?;
+    }
+    Ok(())
+}
+
+/// Whether the live database is waiting for the admin password.
+pub fn is_locked() -> bool {
+    let path = get_db_path();
+    !is_plaintext(&path) && cached_key(&path).is_none() && read_keyinfo(&path).ok().flatten().is_some_and(|i| i.mode == "password")
+}
+
+/// Copy the database at `from` to `to`, re-encrypted with `to_key` (None
+/// for plaintext), using SQLCipher's export.
+fn export(from: &Path, from_key: Option<&str>, to: &Path, to_key: Option<&str>) -> Result<(), String> {
+    if to.exists() {
+        fs::remove_file(to).map_err(|e| format!("Failed to replace {}: {}", to.display(), e))?;
+    }
+    let conn = Connection::open(from).map_err(|e| e.to_string())?;
+    if let Some(key) = from_key {
+        conn.execute_batch(&format!("PRAGMA key = \"{}\";", pragma_key(Some(key))))
+            .map_err(|e| e.to_string())?;
+    }
+    let user_version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    conn.execute(
+        "ATTACH DATABASE ?1 AS target KEY ?2",
+        params![to.to_string_lossy().to_string(), pragma_key(to_key)],
+    )
+    .map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+    conn.query_row("SELECT sqlcipher_export('target')", [], |_| Ok(()))
+        .map_err(|e| format!("Failed to copy the database: {}", e))?;
+    conn.execute_batch(&format!("PRAGMA target.user_version = {}; DETACH DATABASE target;", user_version))
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Rewrite the live database with `to_key` (None for plaintext) and swap it
+/// in. `before_swap` runs once the new file is complete, to save its key.
+fn convert_live_database(
+    db_path: &Path,
+    from_key: Option<&str>,
+    to_key: Option<&str>,
+    before_swap: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String> {
+    let converted = db_path.with_extension("db.converting");
+    // Closing pooled connections checkpoints the WAL into the file being exported
+    close_pooled_connections();
+    export(db_path, from_key, &converted, to_key)?;
+    if let Err(e) = before_swap() {
+        let _ = fs::remove_file(&converted);
+        return Err(e);
+    }
+    close_pooled_connections();
+    fs::rename(&converted, db_path).map_err(|e| format!("Failed to replace the database: {}", e))
+}
+
+/// Copy the live database to `dest` as plaintext, so backups restore on any
+/// PC. Backup encryption (`set_backup_encryption`) is what protects them.
+/// Read through a live connection in one transaction, so the copy holds
+/// every committed write, including those still in the WAL, and nothing
+/// half-written by a command running meanwhile.
+pub fn copy_plaintext(db_path: &Path, dest: &Path) -> Result<(), String> {
+    if dest.exists() {
+        fs::remove_file(dest).map_err(|e| format!("Failed to replace {}: {}", dest.display(), e))?;
+    }
+    let conn = get_pooled_connection(db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+    if key_for(db_path)?.is_none() {
+        conn.execute("VACUUM INTO ?1", params![dest.to_string_lossy().to_string()])
+            .map_err(|e| format!("Failed to copy database: {}", e))?;
+        return Ok(());
+    }
+
+    conn.execute("ATTACH DATABASE ?1 AS target KEY ''", params![dest.to_string_lossy().to_string()])
+        .map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let copied = (|| {
+        let tx = conn.unchecked_transaction()?;
+        let user_version: i64 = tx.query_row("PRAGMA main.user_version", [], |row| row.get(0))?;
+        tx.query_row("SELECT sqlcipher_export('target')", [], |_| Ok(()))?;
+        tx.execute_batch(&format!("PRAGMA target.user_version = {};", user_version))?;
+        tx.commit()
+    })();
+    // Detach even after a failure, or the connection goes back to the pool with the copy attached
+    let detached = conn.execute_batch("DETACH DATABASE target;");
+    copied.map_err(|e| format!("Failed to copy database: {}", e))?;
+    detached.map_err(|e| e.to_string())
+}
+
+/// Put the plaintext database `source` in place of the live one, encrypted
+/// with the live database's key when encryption is on. Copied into a live
+/// connection with SQLite's backup API rather than swapping the file: it
+/// holds the write lock while it copies, so a command writing meanwhile
+/// either finishes first or waits and then sees the restored data.
+pub fn restore_plaintext(source: &Path, db_path: &Path) -> Result<(), String> {
+    let key = key_for(db_path)?;
+    // The backup API only copies between databases with the same key
+    let incoming = db_path.with_extension("db.restoring");
+    if let Some(key) = &key {
+        export(source, None, &incoming, Some(key))?;
+    }
+    let from = if key.is_some() { incoming.as_path() } else { source };
+
+    let restored = (|| {
+        let backup = Connection::open_with_flags(from, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        if let Some(key) = &key {
+            backup.execute_batch(&format!("PRAGMA key = \"{}\";", pragma_key(Some(key))))?;
+        }
+        let mut live = get_pooled_connection(db_path)?;
+        let copy = rusqlite::backup::Backup::new(&backup, &mut *live)?;
+        copy.run_to_completion(-1, std::time::Duration::from_millis(250), None)
+    })();
+    if key.is_some() {
+        let _ = fs::remove_file(&incoming);
+    }
+    restored.map_err(|e| format!("Failed to restore database: {}", e))
+}
+
+fn random_key() -> String {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+    key.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn wrap_key(key: &str, password: &str) -> Result<String, String> {
+    Ok(base64::engine::general_purpose::STANDARD.encode(crate::backup_crypto::encrypt(key.as_bytes(), password)?))
+}
+
+fn unwrap_key(wrapped: &str, password: &str) -> Result<String, String> {
+    let sealed = base64::engine::general_purpose::STANDARD
+        .decode(wrapped)
+        .map_err(|e| format!("The stored database key is damaged: {}", e))?;
+    let key = crate::backup_crypto::decrypt(&sealed, password).map_err(|_| "Wrong password".to_string())?;
+    String::from_utf8(key).map_err(|_| "The stored database key is damaged".to_string())
+}
+
+fn check_admin_password(username: &str, password: &str) -> Result<(), String> {
+    let valid = crate::offline_auth::AuthManager::new()
+        .check_password(username, password)
+        .map_err(|e| format!("Database error: {}", e))?;
+    if valid {
+        Ok(())
+    } else {
+        Err("Wrong username or password".to_string())
+    }
+}
+
+// The signed-in admin's username, or an error when the session isn't an admin's
+fn require_encryption_admin(session_token: &str) -> Result<String, AppError> {
+    let session = require_session(session_token)?;
+    if !ENCRYPTION_ROLES.contains(&session.role.as_str()) {
+        return Err(AppError::coded(UNAUTHORIZED, "Only an admin can change database encryption"));
+    }
+    Ok(session.username)
+}
+
+fn status(db_path: &Path) -> Result<DatabaseEncryptionStatus, String> {
+    let info = read_keyinfo(db_path)?;
+    let encrypted = info.is_some() && !is_plaintext(db_path);
+    Ok(DatabaseEncryptionStatus {
+        encrypted,
+        mode: info.as_ref().filter(|_| encrypted).map(|i| i.mode.clone()),
+        username: info.as_ref().filter(|_| encrypted).and_then(|i| i.username.clone()),
+        locked: encrypted && cached_key(db_path).is_none() && info.is_some_and(|i| i.mode == "password"),
+    })
+}
+
+/// Unlock a database encrypted with the admin password. Only the key is
+/// checked here; signing in is still a separate step.
+pub fn unlock_with_password(password: &str) -> Result<(), String> {
+    let db_path = get_db_path();
+    let info = read_keyinfo(&db_path)?.ok_or("The database is not encrypted".to_string())?;
+    let wrapped = info.wrapped_key.as_deref().ok_or("The database is not encrypted with the admin password".to_string())?;
+    let key = unwrap_key(wrapped, password)?;
+    cache_key(&db_path, Some(key));
+    Ok(())
+}
+
+/// Forget the key of the live database, as if the app had just started.
+#[cfg(test)]
+fn forget_key() {
+    close_pooled_connections();
+    cache_key(&get_db_path(), None);
+}
+
+/// Re-seal the database key after `username` changes their password, so the
+/// new password unlocks it from then on.
+pub fn admin_password_changed(username: &str, new_password: &str) -> Result<(), String> {
+    let db_path = get_db_path();
+    let Some(mut info) = read_keyinfo(&db_path)? else {
+        return Ok(());
+    };
+    let owner = info.username.as_deref().unwrap_or("");
+    if info.mode != "password" || !owner.eq_ignore_ascii_case(username.trim()) {
+        return Ok(());
+    }
+    let key = key_for(&db_path)?.ok_or("The database is not encrypted".to_string())?;
+    info.wrapped_key = Some(wrap_key(&key, new_password)?);
+    write_keyinfo(&db_path, &info)
+}
+
+/// Encrypt the database file with SQLCipher. "keyring" keeps a random key
+/// in the OS keyring and unlocks at startup without asking; "password"
+/// seals the key with `username`'s admin password, which must then be
+/// entered before the app can open the database. A forgotten password can't
+/// be reset while the database is locked, so keep a backup. Admins only.
+#[command]
+pub fn enable_database_encryption(
+    mode: String,
+    username: Option<String>,
+    password: Option<String>,
+    session_token: String,
+) -> Result<DatabaseEncryptionStatus, AppError> {
+    require_encryption_admin(&session_token)?;
+    let mode = mode.trim().to_lowercase();
+    if !MODES.contains(&mode.as_str()) {
+        return Err(format!("Mode must be one of: {}", MODES.join(", ")).into());
+    }
+    let db_path = get_db_path();
+    if key_for(&db_path)?.is_some() {
+        return Err("The database is already encrypted".into());
+    }
+
+    let key = random_key();
+    let info = if mode == "password" {
+        let username = username.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).ok_or_else(|| {
+            AppError::invalid(crate::validation::EMPTY_FIELD, "username", "Choose the admin whose password unlocks the database")
+        })?;
+        let password = password.filter(|p| !p.is_empty()).ok_or_else(|| {
+            AppError::invalid(crate::validation::EMPTY_FIELD, "password", "Enter the admin password")
+        })?;
+        check_admin_password(&username, &password)?;
+        KeyInfo { mode, wrapped_key: Some(wrap_key(&key, &password)?), username: Some(username) }
+    } else {
+        KeyInfo { mode, username: None, wrapped_key: None }
+    };
+
+    convert_live_database(&db_path, None, Some(&key), || {
+        if info.mode == "keyring" {
+            keyring_entry(&db_path)?
+                .set_password(&key)
+                .map_err(|e| format!("Failed to save the key in the OS keyring: {}", e))?;
+        }
+        write_keyinfo(&db_path, &info)
+    })?;
+    cache_key(&db_path, Some(key));
+    Ok(status(&db_path)?)
+}
+
+/// Go back to a plaintext database file. Admins only, and the signed-in
+/// admin's password is asked for again first.
+#[command]
+pub fn disable_database_encryption(password: String, session_token: String) -> Result<DatabaseEncryptionStatus, AppError> {
+    let username = require_encryption_admin(&session_token)?;
+    if password.is_empty() {
+        return Err(AppError::invalid(crate::validation::EMPTY_FIELD, "password", "Enter your admin password"));
+    }
+    check_admin_password(&username, &password)?;
+    let db_path = get_db_path();
+    let key = key_for(&db_path)?.ok_or("The database is not encrypted")?;
+    let info = read_keyinfo(&db_path)?;
+    convert_live_database(&db_path, Some(&key), None, || Ok(()))?;
+    cache_key(&db_path, None);
+    let _ = fs::remove_file(keyinfo_path(&db_path));
+    if info.is_some_and(|i| i.mode == "keyring") {
+        if let Ok(entry) = keyring_entry(&db_path) {
+            let _ = entry.delete_credential();
+        }
+    }
+    Ok(status(&db_path)?)
+}
+
+#[command]
+pub fn get_database_encryption() -> Result<DatabaseEncryptionStatus, AppError> {
+    Ok(status(&get_db_path())?)
+}
+
+/// Unlock a database encrypted with the admin password, then finish the
+/// startup that was held back while it was locked.
+#[command]
+pub fn unlock_database(password: String) -> Result<DatabaseEncryptionStatus, AppError> {
+    let db_path = get_db_path();
+    if !status(&db_path)?.locked {
+        return Ok(status(&db_path)?);
+    }
+    unlock_with_password(&password)?;
+    crate::db::initialize_database()?;
+    crate::start_database_services();
+    Ok(status(&db_path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::get_db_connection;
+    use crate::offline_auth::{AuthManager, PasswordResetRequest};
+    use crate::test_support::{admin_session, check_in, count, TestDb, ADMIN, ADMIN_PASSWORD};
+
+    fn guest_is_there(guest_id: i64) -> bool {
+        count("SELECT COUNT(*) FROM customers WHERE id = ?1", guest_id) == 1
+    }
+
+    /// Encrypt the test database under the admin password.
+    fn encrypt() -> String {
+        let token = admin_session();
+        let status = enable_database_encryption("password".to_string(), Some(ADMIN.to_string()), Some(ADMIN_PASSWORD.to_string()), token.clone())
+            .unwrap();
+        assert!(status.encrypted && !status.locked, "database not encrypted: {:?}", status);
+        close_pooled_connections();
+        token
+    }
+
+    fn reset_password(new_password: &str) -> bool {
+        let request = PasswordResetRequest {
+            username: ADMIN.to_string(),
+            security_answer: "yes".to_string(),
+            new_password: new_password.to_string(),
+        };
+        AuthManager::new().reset_password(request).unwrap().success
+    }
+
+    #[test]
+    fn encrypting_needs_an_admin_session_and_password() {
+        let _db = TestDb::new();
+        let guest_id = check_in("Sealed guest", None);
+        let anonymous = enable_database_encryption("keyring".to_string(), None, None, "not-a-session".to_string());
+        assert!(anonymous.is_err(), "encryption enabled without a session");
+        let wrong = enable_database_encryption("password".to_string(), Some(ADMIN.to_string()), Some("not it".to_string()), admin_session());
+        assert!(wrong.is_err(), "encryption enabled with the wrong admin password");
+        encrypt();
+        assert!(!is_plaintext(&get_db_path()), "database file is still plaintext");
+        assert!(guest_is_there(guest_id), "guest missing after encrypting the database");
+    }
+
+    #[test]
+    fn backups_of_an_encrypted_database_are_plaintext() {
+        let db = TestDb::new();
+        encrypt();
+        let dir = db.dir().join("backups").to_string_lossy().to_string();
+        fs::create_dir_all(&dir).unwrap();
+        tauri::async_runtime::block_on(crate::settings::backup_database(dir.clone())).unwrap();
+        let backups = tauri::async_runtime::block_on(crate::settings::list_backups(dir)).unwrap();
+        let head = fs::read(&backups.first().expect("no backup written").path).unwrap();
+        assert!(head.starts_with(SQLITE_HEADER), "backup of the encrypted database is not plaintext");
+    }
+
+    #[test]
+    fn a_locked_database_opens_only_with_the_admin_password() {
+        let _db = TestDb::new();
+        let guest_id = check_in("Sealed guest", None);
+        encrypt();
+        // As after a restart. unlock_database would also start the app's
+        // background services, so unlock with the password alone
+        forget_key();
+        assert!(get_database_encryption().unwrap().locked, "database not locked after forgetting its key");
+        assert!(get_db_connection().is_err(), "locked database opened without its key");
+        assert!(unlock_with_password("not it").is_err(), "database unlocked with the wrong password");
+        unlock_with_password(ADMIN_PASSWORD).unwrap();
+        assert!(!get_database_encryption().unwrap().locked, "database still locked after unlocking");
+        assert!(guest_is_there(guest_id), "guest missing after unlocking the database");
+    }
+
+    #[test]
+    fn a_password_reset_reseals_the_key() {
+        let _db = TestDb::new();
+        encrypt();
+        let keyinfo = keyinfo_path(&get_db_path());
+        let sealed = fs::read(&keyinfo).unwrap();
+        fs::write(&keyinfo, b"damaged").unwrap();
+        let refused = !reset_password("new-pass");
+        fs::write(&keyinfo, &sealed).unwrap();
+        assert!(refused, "password reset went through without re-sealing the database key");
+        assert!(AuthManager::new().check_password(ADMIN, ADMIN_PASSWORD).unwrap(), "refused password reset still changed the password");
+
+        assert!(reset_password("new-pass"), "password reset failed");
+        forget_key();
+        assert!(unlock_with_password(ADMIN_PASSWORD).is_err(), "database still unlocks with the old password");
+        unlock_with_password("new-pass").unwrap();
+    }
+
+    #[test]
+    fn decrypting_needs_the_admin_password() {
+        let _db = TestDb::new();
+        let guest_id = check_in("Sealed guest", None);
+        let token = encrypt();
+        let wrong = disable_database_encryption("not it".to_string(), token.clone());
+        assert!(wrong.is_err() && get_database_encryption().unwrap().encrypted, "encryption disabled with the wrong admin password");
+        let status = disable_database_encryption(ADMIN_PASSWORD.to_string(), token).unwrap();
+        close_pooled_connections();
+        assert!(!status.encrypted && is_plaintext(&get_db_path()), "database still encrypted after disabling");
+        assert!(guest_is_there(guest_id), "guest missing after decrypting the database");
+    }
+}
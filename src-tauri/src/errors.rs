@@ -0,0 +1,148 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+use crate::validation::{COMMAND_FAILED, CONSTRAINT_VIOLATION, DATABASE_ERROR, INVALID_DATE_FORMAT};
+
+/// Error returned by commands. The frontend receives `{code, message, field}`
+/// and can branch on `code` instead of matching message text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// Bad input; `field` names the argument at fault.
+    Validation { code: String, message: String, field: Option<String> },
+    /// Refused with one of the codes in validation.rs, optionally with detail
+    /// after the code ("UNUSUAL_DATE: check_in 2019-01-01 ...").
+    Coded { code: String, detail: Option<String> },
+    /// SQLite failed; constraint failures are reported as CONSTRAINT_VIOLATION.
+    Database { constraint: bool, message: String },
+    /// A message with no code of its own.
+    Other(String),
+}
+
+impl AppError {
+    pub fn invalid(code: &str, field: &str, message: impl Into<String>) -> Self {
+        AppError::Validation { code: code.to_string(), message: message.into(), field: Some(field.to_string()) }
+    }
+
+    pub fn coded(code: &str, detail: impl Into<String>) -> Self {
+        AppError::Coded { code: code.to_string(), detail: Some(detail.into()) }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            AppError::Validation { code, .. } | AppError::Coded { code, .. } => code,
+            AppError::Database { constraint: true, .. } => CONSTRAINT_VIOLATION,
+            AppError::Database { constraint: false, .. } => DATABASE_ERROR,
+            AppError::Other(_) => COMMAND_FAILED,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            AppError::Validation { message, .. } | AppError::Database { message, .. } | AppError::Other(message) => message.clone(),
+            AppError::Coded { detail: Some(detail), .. } => detail.clone(),
+            AppError::Coded { code, detail: None } => humanize_code(code),
+        }
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            AppError::Validation { field, .. } => field.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// "ROOM_OCCUPIED" -> "Room occupied", for codes returned without detail.
+fn humanize_code(code: &str) -> String {
+    let words = code.to_lowercase().replace('_', " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => words,
+    }
+}
+
+/// The leading code of a legacy error string such as "ORDER_NOT_FOUND" or
+/// "UNUSUAL_DATE: ...", and the detail after it.
+fn split_code(message: &str) -> Option<(&str, Option<&str>)> {
+    let (head, detail) = match message.split_once(':') {
+        Some((head, detail)) => (head.trim(), Some(detail.trim()).filter(|d| !d.is_empty())),
+        None => (message.trim(), None),
+    };
+    let is_code = head.len() >= 3
+        && head.contains('_')
+        && head.starts_with(|c: char| c.is_ascii_uppercase())
+        && head.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    is_code.then_some((head, detail))
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Same text the command returned before errors were structured
+            AppError::Coded { code, detail: Some(detail) } => write!(f, "{}: {}", code, detail),
+            AppError::Coded { code, detail: None } => write!(f, "{}", code),
+            _ => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.message())?;
+        state.serialize_field("field", &self.field())?;
+        state.end()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        match split_code(&message) {
+            Some((code, detail)) => AppError::Coded { code: code.to_string(), detail: detail.map(str::to_string) },
+            None => AppError::Other(message),
+        }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::from(message.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::Database {
+            constraint: e.sqlite_error_code() == Some(rusqlite::ErrorCode::ConstraintViolation),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<chrono::ParseError> for AppError {
+    fn from(e: chrono::ParseError) -> Self {
+        AppError::Validation { code: INVALID_DATE_FORMAT.to_string(), message: e.to_string(), field: None }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        AppError::Other(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        AppError::Other(e.to_string())
+    }
+}
+
+// Lets helpers and commands that still return String use `?` on AppError
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.to_string()
+    }
+}
@@ -0,0 +1,40 @@
+use serde::Serialize;
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+use crate::models::{GuestChangedEvent, OrderCreatedEvent, RoomStatusChangedEvent};
+
+// Events sent to every open window after a change is committed, so views can
+// refresh themselves instead of polling the get_* commands.
+pub const GUEST_CHANGED: &str = "guest-changed";
+pub const ORDER_CREATED: &str = "order-created";
+pub const ROOM_STATUS_CHANGED: &str = "room-status-changed";
+
+fn app_handle() -> &'static OnceLock<AppHandle> {
+    static APP: OnceLock<AppHandle> = OnceLock::new();
+    &APP
+}
+
+/// Called once from setup; until then (and in the self-test) events are dropped.
+pub fn init(app: AppHandle) {
+    let _ = app_handle().set(app);
+}
+
+fn emit<P: Serialize + Clone>(event: &str, payload: P) {
+    if let Some(app) = app_handle().get() {
+        let _ = app.emit(event, payload);
+    }
+}
+
+/// `guest_id` is None when many guests changed at once (import, restore).
+pub fn guest_changed(guest_id: Option<i64>, change: &str) {
+    emit(GUEST_CHANGED, GuestChangedEvent { guest_id, change: change.to_string() });
+}
+
+/// `room_id` is None when many rooms changed at once.
+pub fn room_status_changed(room_id: Option<i64>, change: &str) {
+    emit(ROOM_STATUS_CHANGED, RoomStatusChangedEvent { room_id, change: change.to_string() });
+}
+
+pub fn order_created(order_id: i64, guest_id: Option<i64>, table_id: Option<i64>, total_amount: f64) {
+    emit(ORDER_CREATED, OrderCreatedEvent { order_id, guest_id, table_id, total_amount });
+}
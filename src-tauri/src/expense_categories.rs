@@ -0,0 +1,180 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::audit_actor;
+use crate::db::{get_db_connection, log_audit_event};
+use crate::errors::AppError;
+use crate::models::ExpenseCategory;
+use crate::validation::{EMPTY_FIELD, EXPENSE_CATEGORY_EXISTS, EXPENSE_CATEGORY_IN_USE, EXPENSE_CATEGORY_NOT_FOUND};
+
+const CATEGORY_NAME_MAX_CHARS: usize = 50;
+
+// Offered on a fresh install; the supplier category is where purchasing books payments
+pub const DEFAULT_CATEGORIES: &[&str] = &[
+    "Groceries",
+    "Maintenance",
+    "Salaries",
+    "Utility Bills",
+    crate::purchasing::SUPPLIER_EXPENSE_CATEGORY,
+];
+
+const CATEGORY_COLUMNS: &str = "c.id, c.name, (SELECT COUNT(*) FROM expenses e WHERE e.category = c.name COLLATE NOCASE)";
+
+fn validate_category_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Category name cannot be empty"));
+    }
+    if name.chars().count() > CATEGORY_NAME_MAX_CHARS {
+        return Err(format!("Category name must be at most {} characters", CATEGORY_NAME_MAX_CHARS).into());
+    }
+    Ok(name.to_string())
+}
+
+fn map_category(row: &rusqlite::Row) -> rusqlite::Result<ExpenseCategory> {
+    Ok(ExpenseCategory { id: row.get(0)?, name: row.get(1)?, expense_count: row.get(2)? })
+}
+
+fn load_category(conn: &Connection, category_id: i64) -> Result<ExpenseCategory, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM expense_categories c WHERE c.id = ?1", CATEGORY_COLUMNS),
+        params![category_id],
+        map_category,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::coded(EXPENSE_CATEGORY_NOT_FOUND, format!("Expense category #{} not found", category_id)))
+}
+
+fn find_by_name(conn: &Connection, name: &str) -> Result<Option<(i64, String)>, AppError> {
+    Ok(conn
+        .query_row(
+            "SELECT id, name FROM expense_categories WHERE name = ?1 COLLATE NOCASE",
+            params![name.trim()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?)
+}
+
+/// The managed spelling of `name`, matched regardless of case. Expenses and
+/// budgets can only be booked under a category that exists.
+pub fn resolve_category(conn: &Connection, name: &str) -> Result<String, AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "category", "Category cannot be empty"));
+    }
+    find_by_name(conn, name)?.map(|(_, stored)| stored).ok_or_else(|| {
+        AppError::invalid(
+            EXPENSE_CATEGORY_NOT_FOUND,
+            "category",
+            format!("Unknown expense category '{}'. Add it under expense categories first", name.trim()),
+        )
+    })
+}
+
+/// Point expenses and the budget filed under `from` at `to`. A budget already
+/// set for `to` absorbs the one being moved.
+fn rewrite_category(conn: &Connection, from: &str, to: &str) -> Result<usize, AppError> {
+    let moved = conn.execute("UPDATE expenses SET category = ?1 WHERE category = ?2 COLLATE NOCASE", params![to, from])?;
+    let from_budget: Option<f64> = conn
+        .query_row("SELECT monthly_amount FROM expense_budgets WHERE category = ?1", params![from], |row| row.get(0))
+        .optional()?;
+    if let Some(amount) = from_budget {
+        conn.execute("DELETE FROM expense_budgets WHERE category = ?1", params![from])?;
+        conn.execute(
+            "INSERT INTO expense_budgets (category, monthly_amount, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(category) DO UPDATE SET monthly_amount = monthly_amount + excluded.monthly_amount, updated_at = excluded.updated_at",
+            params![to, amount, crate::db::get_current_timestamp()],
+        )?;
+    }
+    Ok(moved)
+}
+
+#[command]
+pub fn get_expense_categories() -> Result<Vec<ExpenseCategory>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM expense_categories c ORDER BY c.name COLLATE NOCASE", CATEGORY_COLUMNS))?;
+    let categories = stmt.query_map([], map_category)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(categories)
+}
+
+#[command]
+pub fn add_expense_category(name: String) -> Result<ExpenseCategory, AppError> {
+    let name = validate_category_name(&name)?;
+    let conn = get_db_connection()?;
+    if let Some((_, existing)) = find_by_name(&conn, &name)? {
+        return Err(AppError::invalid(EXPENSE_CATEGORY_EXISTS, "name", format!("Category '{}' already exists", existing)));
+    }
+    conn.execute("INSERT INTO expense_categories (name) VALUES (?1)", params![name])?;
+    load_category(&conn, conn.last_insert_rowid())
+}
+
+/// Rename a category and every expense booked under it. Renaming onto another
+/// category's name is refused; merge the two instead.
+#[command]
+pub fn rename_expense_category(category_id: i64, name: String, session_token: Option<String>) -> Result<ExpenseCategory, AppError> {
+    let name = validate_category_name(&name)?;
+    let conn = get_db_connection()?;
+    let current = load_category(&conn, category_id)?;
+    if let Some((other_id, existing)) = find_by_name(&conn, &name)? {
+        if other_id != category_id {
+            return Err(AppError::invalid(
+                EXPENSE_CATEGORY_EXISTS,
+                "name",
+                format!("Category '{}' already exists; merge into it instead", existing),
+            ));
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("UPDATE expense_categories SET name = ?1 WHERE id = ?2", params![name, category_id])?;
+    let moved = rewrite_category(&tx, &current.name, &name)?;
+    log_audit_event(
+        &tx,
+        &audit_actor(session_token.as_deref()),
+        "expense_category_renamed",
+        &format!("'{}' renamed to '{}' ({} expenses updated)", current.name, name, moved),
+    )?;
+    tx.commit()?;
+    load_category(&conn, category_id)
+}
+
+/// Fold `source_id` into `target_id`: its expenses and budget move across and
+/// the source category is removed. Used to clean up typos.
+#[command]
+pub fn merge_expense_categories(source_id: i64, target_id: i64, session_token: Option<String>) -> Result<ExpenseCategory, AppError> {
+    if source_id == target_id {
+        return Err(AppError::invalid(EXPENSE_CATEGORY_EXISTS, "target_id", "Pick a different category to merge into"));
+    }
+    let conn = get_db_connection()?;
+    let source = load_category(&conn, source_id)?;
+    let target = load_category(&conn, target_id)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let moved = rewrite_category(&tx, &source.name, &target.name)?;
+    tx.execute("DELETE FROM expense_categories WHERE id = ?1", params![source_id])?;
+    log_audit_event(
+        &tx,
+        &audit_actor(session_token.as_deref()),
+        "expense_category_merged",
+        &format!("'{}' merged into '{}' ({} expenses moved)", source.name, target.name, moved),
+    )?;
+    tx.commit()?;
+    load_category(&conn, target_id)
+}
+
+/// Delete an unused category along with its budget. Categories with expenses
+/// have to be merged into another one instead.
+#[command]
+pub fn delete_expense_category(category_id: i64) -> Result<(), AppError> {
+    let conn = get_db_connection()?;
+    let category = load_category(&conn, category_id)?;
+    if category.expense_count > 0 {
+        return Err(AppError::coded(
+            EXPENSE_CATEGORY_IN_USE,
+            format!("'{}' has {} expenses; merge it into another category instead", category.name, category.expense_count),
+        ));
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM expense_budgets WHERE category = ?1", params![category.name])?;
+    tx.execute("DELETE FROM expense_categories WHERE id = ?1", params![category_id])?;
+    tx.commit()?;
+    Ok(())
+}
@@ -0,0 +1,266 @@
+use tauri::command;
+use std::collections::HashMap;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::audit_actor;
+use crate::date_checks::{check_historical_date_plausibility, log_acknowledged_dates};
+use crate::db::{get_db_connection, log_audit_event};
+use crate::errors::AppError;
+use crate::expense_categories::resolve_category;
+use crate::models::{ExpenseImportMapping, ExpenseImportReport, ExpenseImportRow};
+use crate::stay_import::{column_letter_index, parse_date_text};
+
+/// How many rows from the top are searched for the header row; bank statements
+/// usually start with a few lines of account details.
+const HEADER_SEARCH_ROWS: usize = 15;
+
+struct ExpenseColumns {
+    date: usize,
+    amount: usize,
+    description: Option<usize>,
+    category: Option<usize>,
+}
+
+enum RowOutcome {
+    Created { expense_id: i64, amount: f64 },
+    Skipped(String),
+}
+
+struct ExpenseRow {
+    date: String,
+    amount: f64,
+    description: Option<String>,
+    category: Option<String>,
+}
+
+/// Find the header row (the one holding the date caption) and resolve each
+/// mapped column. Returns the index of the first data row.
+fn resolve_columns(rows: &[(usize, Vec<String>)], mapping: &ExpenseImportMapping) -> Result<(usize, ExpenseColumns), String> {
+    let header_row = rows
+        .iter()
+        .take(HEADER_SEARCH_ROWS)
+        .position(|(_, cells)| cells.iter().any(|cell| cell.eq_ignore_ascii_case(mapping.date.trim())));
+    let find = |caption: &str| -> Option<usize> {
+        let caption = caption.trim();
+        header_row
+            .and_then(|h| rows[h].1.iter().position(|cell| cell.eq_ignore_ascii_case(caption)))
+            .or_else(|| column_letter_index(caption))
+    };
+    let require = |caption: &str| find(caption).ok_or_else(|| format!("Column '{}' not found in the file", caption.trim()));
+
+    Ok((
+        header_row.map(|h| h + 1).unwrap_or(0),
+        ExpenseColumns {
+            date: require(&mapping.date)?,
+            amount: require(&mapping.amount)?,
+            description: mapping.description.as_deref().map(require).transpose()?,
+            category: mapping.category.as_deref().map(require).transpose()?,
+        },
+    ))
+}
+
+/// A signed amount; statements write money out as "-12.50" or "(12.50)".
+fn parse_amount(text: &str) -> Result<f64, String> {
+    let negative = text.starts_with('-') || (text.starts_with('(') && text.ends_with(')'));
+    let cleaned: String = text.chars().filter(|c| c.is_ascii_digit() || *c == '.').collect();
+    if cleaned.is_empty() {
+        return Err("amount is empty".to_string());
+    }
+    let amount = cleaned.parse::<f64>().map_err(|_| format!("unrecognised amount '{}'", text))?;
+    Ok(if negative { -amount } else { amount })
+}
+
+/// The row as an expense, or None for money coming in on a bank statement.
+fn parse_expense_row(cells: &[String], cols: &ExpenseColumns, bank_statement: bool) -> Result<Option<ExpenseRow>, String> {
+    let cell = |idx: Option<usize>| idx.and_then(|i| cells.get(i)).map(String::as_str).unwrap_or("");
+    let date = parse_date_text(cell(Some(cols.date))).map_err(|e| format!("date: {}", e))?;
+    let mut amount = parse_amount(cell(Some(cols.amount)))?;
+    if bank_statement {
+        if amount >= 0.0 {
+            return Ok(None);
+        }
+        amount = -amount;
+    } else if amount <= 0.0 {
+        return Err("amount must be positive".to_string());
+    }
+    let text = |idx: Option<usize>| Some(cell(idx)).filter(|c| !c.is_empty()).map(str::to_string);
+    Ok(Some(ExpenseRow {
+        date: date.format("%Y-%m-%d").to_string(),
+        amount,
+        description: text(cols.description),
+        category: text(cols.category),
+    }))
+}
+
+/// Date, amount in cents and description: what makes two expenses the same.
+pub(crate) fn duplicate_key(date: &str, amount: f64, description: Option<&str>) -> (String, i64, String) {
+    (date.to_string(), (amount * 100.0).round() as i64, description.unwrap_or("").trim().to_lowercase())
+}
+
+/// An expense already recorded with the same `duplicate_key`.
+pub(crate) fn existing_expense(conn: &Connection, key: &(String, i64, String)) -> Result<Option<i64>, String> {
+    conn.query_row(
+        "SELECT id FROM expenses
+         WHERE deleted_at IS NULL AND date = ?1 AND CAST(ROUND(amount * 100) AS INTEGER) = ?2 AND LOWER(TRIM(COALESCE(description, ''))) = ?3
+         LIMIT 1",
+        params![key.0, key.1, key.2],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Import historical expenses from a CSV file, spreadsheet or bank statement
+/// export. `mapping` says which column holds each field. An expense with the
+/// same date, amount and description as one already recorded (or an earlier
+/// row) is skipped, so the same file can be imported again safely. Categories
+/// must already exist. With `dry_run` nothing is saved.
+#[command]
+pub fn import_expenses_csv(
+    path: String,
+    mapping: ExpenseImportMapping,
+    session_token: Option<String>,
+) -> Result<ExpenseImportReport, AppError> {
+    let acknowledged = mapping.acknowledge_unusual_dates.unwrap_or(false);
+    let default_category = mapping.default_category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    let rows = crate::menu_import::read_rows(path.trim(), mapping.sheet_name.as_deref())?;
+    let (first_data_row, cols) = resolve_columns(&rows, &mapping)?;
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    let mut report = ExpenseImportReport {
+        dry_run: mapping.dry_run,
+        created: 0,
+        skipped: 0,
+        failed: 0,
+        total_amount: 0.0,
+        rows: Vec::new(),
+    };
+    // Duplicate key -> the row that first listed it
+    let mut seen: HashMap<(String, i64, String), usize> = HashMap::new();
+
+    for (row_number, cells) in rows.iter().skip(first_data_row) {
+        let result = parse_expense_row(cells, &cols, mapping.bank_statement).and_then(|expense| {
+            let Some(expense) = expense else {
+                return Ok(RowOutcome::Skipped("money in, not an expense".to_string()));
+            };
+            let key = duplicate_key(&expense.date, expense.amount, expense.description.as_deref());
+            if let Some(first) = seen.insert(key.clone(), *row_number) {
+                return Ok(RowOutcome::Skipped(format!("same as row {}", first)));
+            }
+            if let Some(expense_id) = existing_expense(&tx, &key)? {
+                return Ok(RowOutcome::Skipped(format!("already recorded as expense #{}", expense_id)));
+            }
+
+            let category = expense
+                .category
+                .as_deref()
+                .or(default_category)
+                .ok_or("category is empty and no default category was given".to_string())?;
+            let category = resolve_category(&tx, category)?;
+            let unusual_date = check_historical_date_plausibility(&tx, "date", &expense.date, acknowledged)?;
+            tx.execute(
+                "INSERT INTO expenses (date, category, description, amount) VALUES (?1, ?2, ?3, ?4)",
+                params![expense.date, category, expense.description, expense.amount],
+            )
+            .map_err(|e| e.to_string())?;
+            let expense_id = tx.last_insert_rowid();
+            log_acknowledged_dates(&tx, &format!("imported expense {} (row {})", expense_id, row_number), &[unusual_date])?;
+            Ok(RowOutcome::Created { expense_id, amount: expense.amount })
+        });
+
+        let row_result = match result {
+            Ok(RowOutcome::Created { expense_id, amount }) => {
+                report.created += 1;
+                report.total_amount += amount;
+                ExpenseImportRow { row: *row_number, status: "created".to_string(), reason: None, expense_id: Some(expense_id) }
+            }
+            Ok(RowOutcome::Skipped(reason)) => {
+                report.skipped += 1;
+                ExpenseImportRow { row: *row_number, status: "skipped".to_string(), reason: Some(reason), expense_id: None }
+            }
+            Err(reason) => {
+                report.failed += 1;
+                ExpenseImportRow { row: *row_number, status: "failed".to_string(), reason: Some(reason), expense_id: None }
+            }
+        };
+        report.rows.push(row_result);
+    }
+    report.total_amount = (report.total_amount * 100.0).round() / 100.0;
+
+    if mapping.dry_run {
+        tx.rollback()?;
+    } else {
+        if report.created > 0 {
+            log_audit_event(
+                &tx,
+                &audit_actor(session_token.as_deref()),
+                "expenses_imported",
+                &format!("{} expenses totalling {:.2} imported from {}", report.created, report.total_amount, path.trim()),
+            )?;
+        }
+        tx.commit()?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{count, same_amount, TestDb};
+
+    /// A bank statement with an account line above the headers, money in as
+    /// well as out, a bill entered twice and an unreadable amount, imported
+    /// into Supplies.
+    fn import_statement(db: &TestDb) -> ExpenseImportReport {
+        let path = db.dir().join("statement.csv");
+        if !path.exists() {
+            crate::expense_categories::add_expense_category("Supplies".to_string()).unwrap();
+            std::fs::write(
+                &path,
+                "Account,12345678\n\
+                 Date,Details,Amount\n\
+                 03/01/2024,Laundry service,-40.00\n\
+                 03/01/2024,Room payment,150.00\n\
+                 04/01/2024,Plumber,(75.50)\n\
+                 04/01/2024,Plumber,(75.50)\n\
+                 05/01/2024,Unreadable,abc\n",
+            )
+            .unwrap();
+        }
+        let mapping = ExpenseImportMapping {
+            date: "date".to_string(),
+            amount: "Amount".to_string(),
+            description: Some("Details".to_string()),
+            category: None,
+            default_category: Some("supplies".to_string()),
+            bank_statement: true,
+            dry_run: false,
+            sheet_name: None,
+            acknowledge_unusual_dates: None,
+        };
+        import_expenses_csv(path.to_string_lossy().to_string(), mapping, None).unwrap()
+    }
+
+    #[test]
+    fn a_bank_statement_imports_only_money_paid_out() {
+        let db = TestDb::new();
+        let report = import_statement(&db);
+        assert!(
+            report.created == 2 && report.skipped == 2 && report.failed == 1,
+            "statement import created {}, skipped {}, failed {}", report.created, report.skipped, report.failed
+        );
+        assert!(same_amount(report.total_amount, 115.5), "imported expenses total {:.2}", report.total_amount);
+        assert_eq!(report.rows.first().map(|r| r.row), Some(3), "rows not numbered as in the file");
+        let plumber = report.rows.iter().find(|r| r.row == 5).and_then(|r| r.expense_id).expect("plumber's bill was not imported");
+        let stored = count("SELECT COUNT(*) FROM expenses WHERE id = ?1 AND date = '2024-01-04' AND category = 'Supplies' AND amount = 75.5", plumber);
+        assert_eq!(stored, 1, "plumber's bill was stored with the wrong date, category or amount");
+    }
+
+    #[test]
+    fn importing_a_statement_again_skips_what_is_already_there() {
+        let db = TestDb::new();
+        import_statement(&db);
+        let again = import_statement(&db);
+        assert!(again.created == 0 && again.skipped == 4, "re-importing created {} and skipped {}", again.created, again.skipped);
+    }
+}
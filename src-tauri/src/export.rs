@@ -2,10 +2,12 @@ use serde_json::Value;
 use std::fs;
 use std::io::Write;
 use tauri::{AppHandle, Wry};
+use crate::errors::AppError;
+use crate::db::run_blocking;
 
 /// Export data to CSV file with user-selected location
 #[tauri::command]
-pub async fn export_history_csv_with_dialog(_app: AppHandle<Wry>, tab: String, filters: Value) -> Result<String, String> {
+pub async fn export_history_csv_with_dialog(_app: AppHandle<Wry>, tab: String, filters: Value) -> Result<String, AppError> {
     use rfd::AsyncFileDialog;
     
     // Generate timestamped filename
@@ -22,220 +24,301 @@ pub async fn export_history_csv_with_dialog(_app: AppHandle<Wry>, tab: String, f
     
     match file_path {
         Some(handle) => {
-            let path = handle.path();
+            let path = handle.path().to_path_buf();
             
-            // Create CSV file at selected location
-            let mut file = fs::File::create(path).map_err(|e| format!("Failed to create CSV file: {}", e))?;
-            
-            // Export based on tab type
-            match tab.as_str() {
-                "guests" => export_guests_csv(&mut file, &filters)?,
-                "orders" => export_orders_csv(&mut file, &filters)?,
-                "expenses" => export_expenses_csv(&mut file, &filters)?,
-                "rooms" => export_rooms_csv(&mut file, &filters)?,
-                _ => return Err(format!("Unknown export type: {}", tab)),
-            }
-            
-            Ok(path.to_string_lossy().to_string())
+            // Writing the rows is the slow part, so it runs off the IPC thread
+            run_blocking(move || {
+                // Create CSV file at selected location
+                let mut file = fs::File::create(&path).map_err(|e| format!("Failed to create CSV file: {}", e))?;
+                
+                // Export based on tab type
+                match tab.as_str() {
+                    "guests" => export_guests_csv(&mut file, &filters)?,
+                    "orders" => export_orders_csv(&mut file, &filters)?,
+                    "order_items" => export_order_items_csv(&mut file, &filters)?,
+                    "expenses" => export_expenses_csv(&mut file, &filters)?,
+                    "rooms" => export_rooms_csv(&mut file, &filters)?,
+                    _ => return Err(format!("Unknown export type: {}", tab).into()),
+                }
+                
+                Ok(path.to_string_lossy().to_string())
+            })
+            .await
         },
-        None => Err("Export cancelled by user".to_string())
+        None => Err("Export cancelled by user".into())
     }
 }
 
 /// Export data to CSV file with filters
 #[tauri::command]
-pub fn export_history_csv(tab: String, filters: Value) -> Result<String, String> {
-    // Get app data directory for exports
-    let app_data_dir = dirs::data_local_dir()
-        .ok_or("Failed to get app data directory".to_string())?
-        .join("hotel-app")
-        .join("exports");
-    
-    fs::create_dir_all(&app_data_dir).map_err(|e| format!("Failed to create exports directory: {}", e))?;
-    
-    // Generate timestamped filename
-    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
-    let filename = format!("{}_{}.csv", tab, timestamp);
-    let file_path = app_data_dir.join(&filename);
-    
-    // Create CSV file
-    let mut file = fs::File::create(&file_path).map_err(|e| format!("Failed to create CSV file: {}", e))?;
-    
-    // Export based on tab type
-    match tab.as_str() {
-        "guests" => export_guests_csv(&mut file, &filters)?,
-        "orders" => export_orders_csv(&mut file, &filters)?,
-        "expenses" => export_expenses_csv(&mut file, &filters)?,
-        "rooms" => export_rooms_csv(&mut file, &filters)?,
-        _ => return Err(format!("Unknown export type: {}", tab)),
-    }
+pub async fn export_history_csv(tab: String, filters: Value) -> Result<String, AppError> {
+    run_blocking(move || {
+        // Get app data directory for exports
+        let app_data_dir = dirs::data_local_dir()
+            .ok_or("Failed to get app data directory".to_string())?
+            .join("hotel-app")
+            .join("exports");
+    
+        fs::create_dir_all(&app_data_dir).map_err(|e| format!("Failed to create exports directory: {}", e))?;
+    
+        // Generate timestamped filename
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let filename = format!("{}_{}.csv", tab, timestamp);
+        let file_path = app_data_dir.join(&filename);
+    
+        // Create CSV file
+        let mut file = fs::File::create(&file_path).map_err(|e| format!("Failed to create CSV file: {}", e))?;
+    
+        // Export based on tab type
+        match tab.as_str() {
+            "guests" => export_guests_csv(&mut file, &filters)?,
+            "orders" => export_orders_csv(&mut file, &filters)?,
+            "order_items" => export_order_items_csv(&mut file, &filters)?,
+            "expenses" => export_expenses_csv(&mut file, &filters)?,
+            "rooms" => export_rooms_csv(&mut file, &filters)?,
+            _ => return Err(format!("Unknown export type: {}", tab).into()),
+        }
     
-    Ok(file_path.to_string_lossy().to_string())
+        Ok(file_path.to_string_lossy().to_string())
+    })
+    .await
 }
 
-fn export_guests_csv(file: &mut fs::File, filters: &Value) -> Result<(), String> {
-    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    
-    // Write CSV header
-    writeln!(file, "Guest ID,Name,Phone,Room Number,Check In,Check Out,Daily Rate,Total Bill,Status")
-        .map_err(|e| format!("Failed to write CSV header: {}", e))?;
-    
-    // Build query with filters
-        let mut query = "SELECT g.id, g.name, g.phone, r.number as room_number, g.check_in, g.check_out, g.daily_rate, 
-                    COALESCE((julianday(COALESCE(g.check_out, date('now'))) - julianday(g.check_in)) * g.daily_rate, 0) + 
-                    COALESCE((SELECT SUM(total_amount) FROM sales WHERE guest_id = g.id), 0) as total_bill,
-                    g.status
+/// Optional `tag_ids` array in the export filters.
+fn tag_ids_filter(filters: &Value) -> Option<Vec<i64>> {
+    filters
+        .get("tag_ids")
+        .and_then(|v| v.as_array())
+        .map(|ids| ids.iter().filter_map(|id| id.as_i64()).collect())
+}
+
+/// A filtered export query shared by the synchronous exports and background
+/// export jobs. `select` ends in its WHERE clause so callers can append
+/// pagination before `group_by`.
+pub(crate) struct ExportQuery {
+    pub header: &'static str,
+    pub select: String,
+    pub group_by: &'static str,
+    pub id_column: &'static str,
+    pub params: Vec<rusqlite::types::Value>,
+}
+
+// Columns holding amounts; their headers name the currency the amounts are in
+const MONEY_COLUMNS: &[&str] = &["Daily Rate", "Total Bill", "Total Amount", "Unit Price", "Line Total", "Amount"];
+
+/// `header` with the configured currency after each money column, e.g.
+/// "Daily Rate (PKR)". Values stay plain numbers so spreadsheets can sum them.
+pub(crate) fn header_with_currency(conn: &rusqlite::Connection, header: &str) -> Result<String, String> {
+    let money = crate::money::Money::load(conn)?;
+    Ok(header
+        .split(',')
+        .map(|column| {
+            if MONEY_COLUMNS.contains(&column) {
+                format!("{} ({})", column, money.code())
+            } else {
+                column.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+/// Non-empty string filter, e.g. `start_date`.
+fn text_filter(filters: &Value, key: &str) -> Option<String> {
+    filters
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+}
+
+pub(crate) fn guests_export_query(filters: &Value) -> ExportQuery {
+    // Checked-out guests export the rounded total they were billed at checkout
+    let mut select = "SELECT g.id, g.name, g.phone, r.number as room_number, g.check_in, g.check_out, g.daily_rate, 
+                    COALESCE(g.checkout_total,
+                        COALESCE(g.billed_amount, (julianday(COALESCE(g.check_out, date('now'))) - julianday(g.check_in)) * g.daily_rate, 0) + 
+                        COALESCE((SELECT SUM(total_amount) FROM sales WHERE guest_id = g.id AND deleted_at IS NULL), 0)) as total_bill,
+                    g.status,
+                    (SELECT GROUP_CONCAT(t.name, ', ') FROM entity_tags et JOIN tags t ON t.id = et.tag_id
+                      WHERE et.entity_type = 'guest' AND et.entity_id = g.id) as tags
                 FROM customers g 
                 JOIN resources r ON g.room_id = r.id 
                      WHERE 1=1".to_string();
-    
-    let mut params: Vec<&dyn rusqlite::ToSql> = vec![];
-    
-    // Apply filters - collect owned values first
-    let start_date_str = filters.get("start_date").and_then(|v| v.as_str()).map(|s| s.to_string());
-    let end_date_str = filters.get("end_date").and_then(|v| v.as_str()).map(|s| s.to_string());
-    let room_id_val = filters.get("room_id").and_then(|v| v.as_i64());
-    
-    if let Some(ref start_date) = start_date_str {
-        if !start_date.is_empty() {
-            query.push_str(" AND g.check_in >= ?");
-            params.push(start_date);
-        }
+    let mut params = Vec::new();
+
+    if let Some(start_date) = text_filter(filters, "start_date") {
+        select.push_str(" AND g.check_in >= ?");
+        params.push(start_date.into());
     }
-    
-    if let Some(ref end_date) = end_date_str {
-        if !end_date.is_empty() {
-            query.push_str(" AND g.check_in <= ?");
-            params.push(end_date);
-        }
+    if let Some(end_date) = text_filter(filters, "end_date") {
+        select.push_str(" AND g.check_in <= ?");
+        params.push(end_date.into());
     }
-    
-    if let Some(ref room_id) = room_id_val {
-        query.push_str(" AND g.room_id = ?");
-        params.push(room_id);
+    if let Some(room_id) = filters.get("room_id").and_then(|v| v.as_i64()) {
+        select.push_str(" AND g.room_id = ?");
+        params.push(room_id.into());
     }
-    
-    query.push_str(" ORDER BY g.check_in DESC");
-    
-    // Execute query and write rows
-    let mut stmt = conn.prepare(&query).map_err(|e| format!("Failed to prepare query: {}", e))?;
-    let rows = stmt.query_map(&*params, |row| {
-        Ok((
-            row.get::<_, i64>(0)?,      // id
-            row.get::<_, String>(1)?,   // name
-            row.get::<_, Option<String>>(2)?,  // phone
-            row.get::<_, String>(3)?,   // room_number
-            row.get::<_, String>(4)?,   // check_in
-            row.get::<_, Option<String>>(5)?,  // check_out
-            row.get::<_, f64>(6)?,      // daily_rate
-            row.get::<_, f64>(7)?,      // total_bill
-            row.get::<_, String>(8)?,   // status
-        ))
-    }).map_err(|e| format!("Failed to execute query: {}", e))?;
-    
-    for row in rows {
-        let (id, name, phone, room_number, check_in, check_out, daily_rate, total_bill, status) = 
-            row.map_err(|e| format!("Failed to read row: {}", e))?;
-        
-        writeln!(file, "{},{},{},{},{},{},{:.2},{:.2},{}",
-            id,
-            escape_csv(&name),
-            escape_csv(&phone.unwrap_or_default()),
-            escape_csv(&room_number),
-            check_in,
-            check_out.unwrap_or_default(),
-            daily_rate,
-            total_bill,
-            status
-        ).map_err(|e| format!("Failed to write row: {}", e))?;
+    select.push_str(&crate::tags::tag_filter_clause("guest", "g.id", tag_ids_filter(filters).as_deref()));
+
+    ExportQuery {
+        header: "Guest ID,Name,Phone,Room Number,Check In,Check Out,Daily Rate,Total Bill,Status,Tags",
+        select,
+        group_by: "",
+        id_column: "g.id",
+        params,
     }
-    
-    Ok(())
 }
 
-fn export_orders_csv(file: &mut fs::File, filters: &Value) -> Result<(), String> {
-    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    
-    // Write CSV header
-    writeln!(file, "Order ID,Guest Name,Room,Order Date,Total Amount,Payment Status,Items")
-        .map_err(|e| format!("Failed to write CSV header: {}", e))?;
-    
-        let mut query = "SELECT fo.id, COALESCE(g.name, 'Walk-in'), COALESCE(r.number, 'N/A'), fo.created_at, fo.total_amount, 
-                    CASE WHEN fo.paid = 1 THEN 'Paid' ELSE 'Unpaid' END as payment_status,
-                    GROUP_CONCAT(oi.item_name || ' x' || oi.quantity, ', ') as items
+pub(crate) fn write_guest_row(out: &mut dyn Write, row: &rusqlite::Row) -> Result<(), String> {
+    let read = |e: rusqlite::Error| format!("Failed to read row: {}", e);
+    writeln!(out, "{},{},{},{},{},{},{:.2},{:.2},{},{}",
+        row.get::<_, i64>(0).map_err(read)?,
+        escape_csv(&row.get::<_, String>(1).map_err(read)?),
+        escape_csv(&row.get::<_, Option<String>>(2).map_err(read)?.unwrap_or_default()),
+        escape_csv(&row.get::<_, String>(3).map_err(read)?),
+        row.get::<_, String>(4).map_err(read)?,
+        row.get::<_, Option<String>>(5).map_err(read)?.unwrap_or_default(),
+        row.get::<_, f64>(6).map_err(read)?,
+        row.get::<_, f64>(7).map_err(read)?,
+        row.get::<_, String>(8).map_err(read)?,
+        escape_csv(&row.get::<_, Option<String>>(9).map_err(read)?.unwrap_or_default())
+    ).map_err(|e| format!("Failed to write row: {}", e))
+}
+
+/// Date/guest filters on `fo` (sales), shared by the orders and order items exports.
+fn push_sale_filters(select: &mut String, params: &mut Vec<rusqlite::types::Value>, filters: &Value) {
+    if let Some(start_date) = text_filter(filters, "start_date") {
+        select.push_str(" AND fo.created_at >= ?");
+        params.push(start_date.into());
+    }
+    if let Some(end_date) = text_filter(filters, "end_date") {
+        select.push_str(" AND fo.created_at <= ?");
+        params.push(end_date.into());
+    }
+    if let Some(guest_id) = filters.get("guest_id").and_then(|v| v.as_i64()) {
+        select.push_str(" AND fo.guest_id = ?");
+        params.push(guest_id.into());
+    }
+    select.push_str(&crate::tags::tag_filter_clause("order", "fo.id", tag_ids_filter(filters).as_deref()));
+}
+
+pub(crate) fn orders_export_query(filters: &Value) -> ExportQuery {
+    let mut select = "SELECT fo.id, COALESCE(g.name, 'Walk-in'), COALESCE(r.number, 'N/A'), fo.created_at, fo.total_amount, 
+                    CASE WHEN fo.paid = 1 THEN 'Paid' WHEN fo.amount_paid > 0 THEN 'Partially paid' ELSE 'Unpaid' END as payment_status,
+                    GROUP_CONCAT(oi.item_name || ' x' || oi.quantity, ', ') as items,
+                    (SELECT GROUP_CONCAT(t.name, ', ') FROM entity_tags et JOIN tags t ON t.id = et.tag_id
+                      WHERE et.entity_type = 'order' AND et.entity_id = fo.id) as tags
                 FROM sales fo
                 LEFT JOIN customers g ON fo.guest_id = g.id
                 LEFT JOIN resources r ON g.room_id = r.id
                 LEFT JOIN sale_items oi ON fo.id = oi.order_id
-                     WHERE 1=1".to_string();
-    
-    let mut params: Vec<&dyn rusqlite::ToSql> = vec![];
-    
-    // Apply filters - collect owned values first
-    let start_date_str = filters.get("start_date").and_then(|v| v.as_str()).map(|s| s.to_string());
-    let end_date_str = filters.get("end_date").and_then(|v| v.as_str()).map(|s| s.to_string());
-    let guest_id_val = filters.get("guest_id").and_then(|v| v.as_i64());
-    
-    if let Some(ref start_date) = start_date_str {
-        if !start_date.is_empty() {
-            query.push_str(" AND fo.created_at >= ?");
-            params.push(start_date);
-        }
-    }
-    
-    if let Some(ref end_date) = end_date_str {
-        if !end_date.is_empty() {
-            query.push_str(" AND fo.created_at <= ?");
-            params.push(end_date);
-        }
+                     WHERE fo.deleted_at IS NULL".to_string();
+    let mut params = Vec::new();
+    push_sale_filters(&mut select, &mut params, filters);
+
+    ExportQuery {
+        header: "Order ID,Guest Name,Room,Order Date,Total Amount,Payment Status,Items,Tags",
+        select,
+        group_by: " GROUP BY fo.id",
+        id_column: "fo.id",
+        params,
     }
-    
-    if let Some(ref guest_id) = guest_id_val {
-        query.push_str(" AND fo.guest_id = ?");
-        params.push(guest_id);
+}
+
+pub(crate) fn write_order_row(out: &mut dyn Write, row: &rusqlite::Row) -> Result<(), String> {
+    let read = |e: rusqlite::Error| format!("Failed to read row: {}", e);
+    writeln!(out, "{},{},{},{},{:.2},{},\"{}\",{}",
+        row.get::<_, i64>(0).map_err(read)?,
+        escape_csv(&row.get::<_, String>(1).map_err(read)?),
+        escape_csv(&row.get::<_, String>(2).map_err(read)?),
+        row.get::<_, String>(3).map_err(read)?,
+        row.get::<_, f64>(4).map_err(read)?,
+        row.get::<_, String>(5).map_err(read)?,
+        row.get::<_, Option<String>>(6).map_err(read)?.unwrap_or_default(),
+        escape_csv(&row.get::<_, Option<String>>(7).map_err(read)?.unwrap_or_default())
+    ).map_err(|e| format!("Failed to write row: {}", e))
+}
+
+/// One line per order item, for the same filters as the orders export.
+pub(crate) fn order_items_export_query(filters: &Value) -> ExportQuery {
+    let mut select = "SELECT oi.id, fo.id, fo.created_at, COALESCE(g.name, 'Walk-in'), oi.item_name,
+                    oi.quantity, oi.unit_price, oi.line_total
+                FROM sale_items oi
+                JOIN sales fo ON fo.id = oi.order_id
+                LEFT JOIN customers g ON fo.guest_id = g.id
+                     WHERE fo.deleted_at IS NULL".to_string();
+    let mut params = Vec::new();
+    push_sale_filters(&mut select, &mut params, filters);
+
+    ExportQuery {
+        header: "Item ID,Order ID,Order Date,Guest Name,Item,Quantity,Unit Price,Line Total",
+        select,
+        group_by: "",
+        id_column: "oi.id",
+        params,
     }
+}
+
+pub(crate) fn write_order_item_row(out: &mut dyn Write, row: &rusqlite::Row) -> Result<(), String> {
+    let read = |e: rusqlite::Error| format!("Failed to read row: {}", e);
+    writeln!(out, "{},{},{},{},{},{},{:.2},{:.2}",
+        row.get::<_, i64>(0).map_err(read)?,
+        row.get::<_, i64>(1).map_err(read)?,
+        row.get::<_, String>(2).map_err(read)?,
+        escape_csv(&row.get::<_, String>(3).map_err(read)?),
+        escape_csv(&row.get::<_, String>(4).map_err(read)?),
+        row.get::<_, i64>(5).map_err(read)?,
+        row.get::<_, f64>(6).map_err(read)?,
+        row.get::<_, f64>(7).map_err(read)?
+    ).map_err(|e| format!("Failed to write row: {}", e))
+}
+
+/// Run an export query to completion, in `order_by` order.
+fn write_export_query(
+    file: &mut fs::File,
+    query: ExportQuery,
+    order_by: &str,
+    write_row: fn(&mut dyn Write, &rusqlite::Row) -> Result<(), String>,
+) -> Result<(), String> {
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
     
-    query.push_str(" GROUP BY fo.id ORDER BY fo.created_at DESC");
+    // Write CSV header
+    writeln!(file, "{}", header_with_currency(&conn, query.header)?)
+        .map_err(|e| format!("Failed to write CSV header: {}", e))?;
     
-    let mut stmt = conn.prepare(&query).map_err(|e| format!("Failed to prepare query: {}", e))?;
-    let rows = stmt.query_map(&*params, |row| {
-        Ok((
-            row.get::<_, i64>(0)?,      // id
-            row.get::<_, String>(1)?,   // guest_name
-            row.get::<_, String>(2)?,   // room_number
-            row.get::<_, String>(3)?,   // order_date
-            row.get::<_, f64>(4)?,      // total_amount
-            row.get::<_, String>(5)?,   // payment_status
-            row.get::<_, Option<String>>(6)?,  // items
-        ))
-    }).map_err(|e| format!("Failed to execute query: {}", e))?;
+    let sql = format!("{}{} ORDER BY {}", query.select, query.group_by, order_by);
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let mut rows = stmt
+        .query(rusqlite::params_from_iter(query.params.iter()))
+        .map_err(|e| format!("Failed to execute query: {}", e))?;
     
-    for row in rows {
-        let (id, guest_name, room_number, order_date, total_amount, payment_status, items) = 
-            row.map_err(|e| format!("Failed to read row: {}", e))?;
-        
-        writeln!(file, "{},{},{},{},{:.2},{},\"{}\"",
-            id,
-            escape_csv(&guest_name),
-            escape_csv(&room_number),
-            order_date,
-            total_amount,
-            payment_status,
-            items.unwrap_or_default()
-        ).map_err(|e| format!("Failed to write row: {}", e))?;
+    while let Some(row) = rows.next().map_err(|e| format!("Failed to read row: {}", e))? {
+        write_row(file, row)?;
     }
     
     Ok(())
 }
 
+fn export_guests_csv(file: &mut fs::File, filters: &Value) -> Result<(), String> {
+    write_export_query(file, guests_export_query(filters), "g.check_in DESC", write_guest_row)
+}
+
+fn export_orders_csv(file: &mut fs::File, filters: &Value) -> Result<(), String> {
+    write_export_query(file, orders_export_query(filters), "fo.created_at DESC", write_order_row)
+}
+
+fn export_order_items_csv(file: &mut fs::File, filters: &Value) -> Result<(), String> {
+    write_export_query(file, order_items_export_query(filters), "fo.created_at DESC, oi.id", write_order_item_row)
+}
+
 fn export_expenses_csv(file: &mut fs::File, filters: &Value) -> Result<(), String> {
     let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
     
     // Write CSV header
-    writeln!(file, "Date,Category,Description,Amount")
+    writeln!(file, "{}", header_with_currency(&conn, "Date,Category,Description,Amount,Owner Drawing")?)
         .map_err(|e| format!("Failed to write CSV header: {}", e))?;
     
-    let mut query = "SELECT date, category, description, amount FROM expenses WHERE 1=1".to_string();
+    let mut query = "SELECT date, category, description, amount, is_drawing FROM expenses WHERE deleted_at IS NULL".to_string();
     let mut params: Vec<&dyn rusqlite::ToSql> = vec![];
     
     // Apply filters - collect owned values first  
@@ -273,18 +356,20 @@ fn export_expenses_csv(file: &mut fs::File, filters: &Value) -> Result<(), Strin
             row.get::<_, String>(1)?,   // category
             row.get::<_, String>(2)?,   // description
             row.get::<_, f64>(3)?,      // amount
+            row.get::<_, bool>(4)?,     // is_drawing
         ))
     }).map_err(|e| format!("Failed to execute query: {}", e))?;
     
     for row in rows {
-        let (date, category, description, amount) = 
+        let (date, category, description, amount, is_drawing) = 
             row.map_err(|e| format!("Failed to read row: {}", e))?;
         
-        writeln!(file, "{},{},{},{:.2}",
+        writeln!(file, "{},{},{},{:.2},{}",
             date,
             escape_csv(&category),
             escape_csv(&description),
-            amount
+            amount,
+            if is_drawing { "Yes" } else { "No" }
         ).map_err(|e| format!("Failed to write row: {}", e))?;
     }
     
@@ -295,7 +380,7 @@ fn export_rooms_csv(file: &mut fs::File, _filters: &Value) -> Result<(), String>
     let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
     
     // Write CSV header
-    writeln!(file, "Room Number,Daily Rate,Status,Current Guest")
+    writeln!(file, "{}", header_with_currency(&conn, "Room Number,Daily Rate,Status,Current Guest")?)
         .map_err(|e| format!("Failed to write CSV header: {}", e))?;
     
     let query = "SELECT r.number, r.daily_rate, 
@@ -331,7 +416,7 @@ fn export_rooms_csv(file: &mut fs::File, _filters: &Value) -> Result<(), String>
 }
 
 /// Escape CSV values that contain commas, quotes, or newlines
-fn escape_csv(value: &str) -> String {
+pub(crate) fn escape_csv(value: &str) -> String {
     if value.contains(',') || value.contains('"') || value.contains('\n') {
         format!("\"{}\"", value.replace('"', "\"\""))
     } else {
@@ -341,21 +426,179 @@ fn escape_csv(value: &str) -> String {
 
 /// Create a backup of the current database
 #[tauri::command]
-pub fn create_database_backup() -> Result<String, String> {
-    let db_path = crate::db::get_db_path();
+pub async fn create_database_backup() -> Result<String, AppError> {
+    run_blocking(move || {
+        let db_path = crate::db::get_db_path();
     
-    let app_data_dir = dirs::data_local_dir()
-        .ok_or("Failed to get app data directory".to_string())?
-        .join("hotel-app")
-        .join("backups");
+        let app_data_dir = dirs::data_local_dir()
+            .ok_or("Failed to get app data directory".to_string())?
+            .join("hotel-app")
+            .join("backups");
     
-    fs::create_dir_all(&app_data_dir).map_err(|e| format!("Failed to create backups directory: {}", e))?;
+        fs::create_dir_all(&app_data_dir).map_err(|e| format!("Failed to create backups directory: {}", e))?;
     
-    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
-    let backup_filename = format!("business_backup_{}.db", timestamp);
-    let backup_path = app_data_dir.join(&backup_filename);
+        let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let backup_filename = format!("business_backup_{}.db", timestamp);
+        let backup_path = app_data_dir.join(&backup_filename);
     
-    fs::copy(&db_path, &backup_path).map_err(|e| format!("Failed to create backup: {}", e))?;
+        crate::db_encryption::copy_plaintext(&db_path, &backup_path).map_err(|e| format!("Failed to create backup: {}", e))?;
+        crate::media::backup_media_alongside(&backup_path)?;
     
-    Ok(backup_path.to_string_lossy().to_string())
+        Ok(backup_path.to_string_lossy().to_string())
+    })
+    .await
+}
+
+/// `name` as a quoted SQL identifier.
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// A column value as an SQL literal that reads back as the same value and type.
+fn sql_literal(value: rusqlite::types::ValueRef) -> String {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(n) => n.to_string(),
+        ValueRef::Real(f) if f.is_nan() => "NULL".to_string(),
+        ValueRef::Real(f) if f.is_infinite() => if f > 0.0 { "1e999" } else { "-1e999" }.to_string(),
+        // Debug formatting keeps the ".0" on whole numbers, so they stay REAL
+        ValueRef::Real(f) => format!("{:?}", f),
+        ValueRef::Text(t) => format!("'{}'", String::from_utf8_lossy(t).replace('\'', "''")),
+        ValueRef::Blob(b) => format!("X'{}'", b.iter().map(|byte| format!("{:02X}", byte)).collect::<String>()),
+    }
+}
+
+/// Write every row of `table` as INSERT statements. Virtual tables keep their
+/// rowids: the search index's rowids are derived from the ids of what they
+/// point at.
+fn dump_table_rows(conn: &rusqlite::Connection, out: &mut dyn Write, table: &str, with_rowid: bool) -> Result<usize, String> {
+    let select = if with_rowid {
+        format!("SELECT rowid, * FROM {}", quote_identifier(table))
+    } else {
+        format!("SELECT * FROM {}", quote_identifier(table))
+    };
+    let mut stmt = conn.prepare(&select).map_err(|e| format!("Failed to read table {}: {}", table, e))?;
+    let columns = stmt.column_count();
+    let target = if with_rowid {
+        let names: Vec<String> = stmt.column_names().into_iter().map(quote_identifier).collect();
+        format!("{}({})", quote_identifier(table), names.join(","))
+    } else {
+        quote_identifier(table)
+    };
+    let mut rows = stmt.query([]).map_err(|e| format!("Failed to read table {}: {}", table, e))?;
+    let mut written = 0;
+    while let Some(row) = rows.next().map_err(|e| format!("Failed to read table {}: {}", table, e))? {
+        let mut values = Vec::with_capacity(columns);
+        for i in 0..columns {
+            values.push(sql_literal(row.get_ref(i).map_err(|e| format!("Failed to read table {}: {}", table, e))?));
+        }
+        writeln!(out, "INSERT INTO {} VALUES({});", target, values.join(",")).map_err(|e| format!("Failed to write dump: {}", e))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Write the whole database as SQL text, the way `sqlite3 .dump` does: each
+/// table's CREATE statement and rows, then indexes, triggers and views, so
+/// loading it doesn't run the triggers over the rows a second time. Read from
+/// one snapshot. Returns the number of rows written.
+pub(crate) fn write_sql_dump(conn: &rusqlite::Connection, out: &mut dyn Write) -> Result<usize, String> {
+    let tx = conn.unchecked_transaction().map_err(|e| format!("Failed to read database: {}", e))?;
+    let read = |e: rusqlite::Error| format!("Failed to read schema: {}", e);
+    let schema: Vec<(String, String, Option<String>)> = {
+        let mut stmt = tx
+            .prepare("SELECT type, name, sql FROM sqlite_master ORDER BY type != 'table', name = 'sqlite_sequence', rowid")
+            .map_err(read)?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))).map_err(read)?;
+        rows.collect::<Result<_, _>>().map_err(read)?
+    };
+    // FTS5 keeps its data in shadow tables named after the virtual table; they
+    // are filled in again when the virtual table's rows are inserted
+    let virtual_tables: Vec<&str> = schema
+        .iter()
+        .filter(|(_, _, sql)| sql.as_deref().is_some_and(|s| s.starts_with("CREATE VIRTUAL TABLE")))
+        .map(|(_, name, _)| name.as_str())
+        .collect();
+    let is_shadow = |name: &str| virtual_tables.iter().any(|v| name.starts_with(&format!("{}_", v)));
+    let write = |out: &mut dyn Write, line: &str| writeln!(out, "{}", line).map_err(|e| format!("Failed to write dump: {}", e));
+
+    write(out, "PRAGMA foreign_keys=OFF;")?;
+    write(out, "BEGIN TRANSACTION;")?;
+    let mut rows = 0;
+    for (kind, name, sql) in &schema {
+        // Indexes SQLite makes for UNIQUE constraints have no SQL of their own
+        let Some(sql) = sql else { continue };
+        if kind != "table" {
+            write(out, &format!("{};", sql))?;
+        } else if name == "sqlite_sequence" {
+            write(out, "DELETE FROM sqlite_sequence;")?;
+            rows += dump_table_rows(&tx, out, name, false)?;
+        } else if !name.starts_with("sqlite_") && !is_shadow(name) {
+            write(out, &format!("{};", sql))?;
+            rows += dump_table_rows(&tx, out, name, virtual_tables.contains(&name.as_str()))?;
+        }
+    }
+    write(out, "COMMIT;")?;
+    Ok(rows)
+}
+
+/// Export the whole database as a plain-text SQL dump of its schema and data,
+/// for archiving or inspecting outside the app. The dump is never encrypted,
+/// even when the database is. Returns where it was written.
+#[tauri::command]
+pub async fn export_sql_dump(path: String, session_token: Option<String>) -> Result<String, AppError> {
+    run_blocking(move || {
+        let path = std::path::PathBuf::from(path.trim());
+        if path.as_os_str().is_empty() || path.is_dir() {
+            return Err("Choose a file to write the SQL dump to".into());
+        }
+        let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+        let file = fs::File::create(&path).map_err(|e| format!("Failed to create dump file: {}", e))?;
+        let mut out = std::io::BufWriter::new(file);
+        let rows = write_sql_dump(&conn, &mut out)?;
+        out.flush().map_err(|e| format!("Failed to write dump: {}", e))?;
+        crate::db::log_audit_event(
+            &conn,
+            &crate::audit::audit_actor(session_token.as_deref()),
+            "sql_dump_exported",
+            &format!("{} rows written to {}", rows, path.display()),
+        )?;
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::get_db_connection;
+    use crate::simple_commands::insert_food_order;
+    use crate::test_support::{add_test_expense, check_in, order_item, TestDb};
+
+    #[test]
+    fn an_sql_dump_loads_into_an_empty_database_unchanged() {
+        let db = TestDb::new();
+        check_in("Dumped guest", None);
+        insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Tea", 2.5, 2)], None, None, None).unwrap();
+        add_test_expense("Supplies", "Soap", 4.0);
+        let path = db.dir().join("dump.sql");
+        tauri::async_runtime::block_on(export_sql_dump(path.to_string_lossy().to_string(), None)).unwrap();
+        let copy = rusqlite::Connection::open_in_memory().unwrap();
+        copy.execute_batch(&fs::read_to_string(&path).unwrap()).expect("the dump did not load");
+
+        let conn = get_db_connection().unwrap();
+        let checks = [
+            "SELECT COUNT(*) || '/' || COALESCE(SUM(LENGTH(name)), 0) FROM customers",
+            "SELECT COUNT(*) || '/' || COALESCE(SUM(total_amount), 0) FROM sales",
+            "SELECT COUNT(*) || '/' || COALESCE(SUM(amount), 0) FROM expenses",
+            "SELECT COUNT(*) || '/' || COALESCE(SUM(rowid), 0) FROM search_index",
+            "SELECT COALESCE(MAX(seq), 0) || '' FROM sqlite_sequence WHERE name = 'customers'",
+            "SELECT COUNT(*) || '' FROM sqlite_master WHERE type IN ('index', 'trigger')",
+        ];
+        for sql in checks {
+            let read = |c: &rusqlite::Connection| c.query_row(sql, [], |row| row.get::<_, String>(0)).unwrap();
+            assert_eq!(read(&conn), read(&copy), "dump differs for {}", sql);
+        }
+    }
 }
@@ -0,0 +1,337 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Wry};
+use crate::db::{get_db_connection, get_db_path, get_current_timestamp, with_db_path_override};
+use crate::export::{
+    guests_export_query, orders_export_query, order_items_export_query,
+    write_guest_row, write_order_row, write_order_item_row, ExportQuery,
+};
+use crate::models::ExportJobStatus;
+
+// Rows read per query; each batch re-seeks by id so memory stays flat
+const EXPORT_BATCH_SIZE: i64 = 5000;
+// Pause between batches so the UI's own queries aren't starved
+const EXPORT_BATCH_PAUSE: Duration = Duration::from_millis(25);
+const MAX_RUNNING_EXPORT_JOBS: usize = 2;
+
+type RowWriter = fn(&mut dyn Write, &rusqlite::Row) -> Result<(), String>;
+
+struct ExportJob {
+    status: Mutex<ExportJobStatus>,
+    cancel: AtomicBool,
+}
+
+impl ExportJob {
+    fn update(&self, apply: impl FnOnce(&mut ExportJobStatus)) {
+        if let Ok(mut status) = self.status.lock() {
+            apply(&mut status);
+        }
+    }
+
+    fn snapshot(&self) -> Result<ExportJobStatus, String> {
+        self.status.lock().map(|s| s.clone()).map_err(|e| e.to_string())
+    }
+}
+
+fn export_jobs() -> &'static Mutex<HashMap<String, Arc<ExportJob>>> {
+    static JOBS: OnceLock<Mutex<HashMap<String, Arc<ExportJob>>>> = OnceLock::new();
+    JOBS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn find_job(job_id: &str) -> Result<Arc<ExportJob>, String> {
+    export_jobs()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(job_id)
+        .cloned()
+        .ok_or("Export job not found".to_string())
+}
+
+fn query_for_tab(tab: &str, filters: &Value) -> Result<(ExportQuery, RowWriter), String> {
+    match tab {
+        "guests" => Ok((guests_export_query(filters), write_guest_row)),
+        "orders" => Ok((orders_export_query(filters), write_order_row)),
+        "order_items" => Ok((order_items_export_query(filters), write_order_item_row)),
+        _ => Err(format!(
+            "'{}' has no background export; use export_history_csv for small exports",
+            tab
+        )),
+    }
+}
+
+/// Stream the query to `path` in id-descending batches. Everything is read
+/// inside one transaction, so under WAL the export sees a single snapshot even
+/// while orders keep being written. Returns false if the job was cancelled.
+fn run_export(job: &ExportJob, query: &ExportQuery, write_row: RowWriter, path: &PathBuf) -> Result<bool, String> {
+    let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    let estimated_total: i64 = tx
+        .query_row(
+            &format!("SELECT COUNT(*) FROM ({}{})", query.select, query.group_by),
+            rusqlite::params_from_iter(query.params.iter()),
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count rows: {}", e))?;
+    job.update(|s| s.estimated_total = estimated_total);
+
+    let file = fs::File::create(path).map_err(|e| format!("Failed to create CSV file: {}", e))?;
+    let mut out = BufWriter::new(file);
+    let header = crate::export::header_with_currency(&tx, query.header)?;
+    writeln!(out, "{}", header).map_err(|e| format!("Failed to write CSV header: {}", e))?;
+
+    let first_batch = format!(
+        "{}{} ORDER BY {} DESC LIMIT {}",
+        query.select, query.group_by, query.id_column, EXPORT_BATCH_SIZE
+    );
+    let next_batch = format!(
+        "{} AND {} < ?{} ORDER BY {} DESC LIMIT {}",
+        query.select, query.id_column, query.group_by, query.id_column, EXPORT_BATCH_SIZE
+    );
+
+    let mut last_id: Option<i64> = None;
+    let mut rows_written = 0i64;
+    loop {
+        if job.cancel.load(Ordering::SeqCst) {
+            return Ok(false);
+        }
+
+        let mut params = query.params.clone();
+        let sql = match last_id {
+            Some(id) => {
+                params.push(id.into());
+                &next_batch
+            }
+            None => &first_batch,
+        };
+
+        let mut stmt = tx.prepare_cached(sql).map_err(|e| format!("Failed to prepare query: {}", e))?;
+        let mut rows = stmt
+            .query(rusqlite::params_from_iter(params.iter()))
+            .map_err(|e| format!("Failed to execute query: {}", e))?;
+
+        let mut batch_rows = 0;
+        while let Some(row) = rows.next().map_err(|e| format!("Failed to read row: {}", e))? {
+            write_row(&mut out, row)?;
+            last_id = Some(row.get(0).map_err(|e| format!("Failed to read row: {}", e))?);
+            batch_rows += 1;
+        }
+
+        out.flush().map_err(|e| format!("Failed to write CSV file: {}", e))?;
+        rows_written += batch_rows;
+        job.update(|s| s.rows_written = rows_written);
+
+        if batch_rows < EXPORT_BATCH_SIZE {
+            return Ok(true);
+        }
+        std::thread::sleep(EXPORT_BATCH_PAUSE);
+    }
+}
+
+/// A timestamped `<stem>_<time>.<extension>` file in the app's exports folder.
+pub(crate) fn default_export_path(stem: &str, extension: &str) -> Result<PathBuf, String> {
+    let dir = dirs::data_local_dir()
+        .ok_or("Failed to get app data directory".to_string())?
+        .join("hotel-app")
+        .join("exports");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create exports directory: {}", e))?;
+
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    Ok(dir.join(format!("{}_{}.{}", stem, timestamp, extension)))
+}
+
+/// Start a background export of a large history tab (guests, orders or
+/// order_items) and return its job id. Progress is polled with
+/// get_export_job_status; `export-job-finished` is emitted when it ends.
+#[tauri::command]
+pub fn start_export_job(
+    app: AppHandle<Wry>,
+    tab: String,
+    filters: Value,
+    format: Option<String>,
+    target_path: Option<String>,
+) -> Result<String, String> {
+    spawn_export_job(tab, filters, format, target_path, move |status| {
+        let _ = app.emit("export-job-finished", status);
+    })
+}
+
+/// Queue the export behind start_export_job on a worker thread. `on_finished`
+/// gets the job's final status once it completes, is cancelled or fails.
+pub fn spawn_export_job(
+    tab: String,
+    filters: Value,
+    format: Option<String>,
+    target_path: Option<String>,
+    on_finished: impl FnOnce(ExportJobStatus) + Send + 'static,
+) -> Result<String, String> {
+    let format = format.unwrap_or_else(|| "csv".to_string()).to_lowercase();
+    if format != "csv" {
+        return Err(format!("Unsupported export format: {}", format));
+    }
+    let (query, write_row) = query_for_tab(&tab, &filters)?;
+
+    let path = match target_path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+        Some(p) => PathBuf::from(p),
+        None => default_export_path(&tab, "csv")?,
+    };
+
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let job = Arc::new(ExportJob {
+        status: Mutex::new(ExportJobStatus {
+            job_id: job_id.clone(),
+            tab: tab.clone(),
+            format,
+            status: "running".to_string(),
+            rows_written: 0,
+            estimated_total: 0,
+            file_path: path.to_string_lossy().to_string(),
+            error: None,
+            started_at: get_current_timestamp(),
+            finished_at: None,
+        }),
+        cancel: AtomicBool::new(false),
+    });
+
+    {
+        let mut jobs = export_jobs().lock().map_err(|e| e.to_string())?;
+        let running = jobs
+            .values()
+            .filter(|j| j.status.lock().map(|s| s.status == "running").unwrap_or(false))
+            .count();
+        if running >= MAX_RUNNING_EXPORT_JOBS {
+            return Err("Too many exports are already running; wait for one to finish".to_string());
+        }
+        jobs.insert(job_id.clone(), job.clone());
+    }
+
+    // The worker reads the caller's database, which is not the live one during the self-test
+    let db_path = get_db_path();
+    std::thread::spawn(move || {
+        let result = with_db_path_override(db_path, || run_export(&job, &query, write_row, &path));
+        let outcome = match result {
+            Ok(true) => ("completed", None),
+            Ok(false) => ("cancelled", None),
+            Err(e) => ("failed", Some(e)),
+        };
+        if outcome.0 != "completed" {
+            let _ = fs::remove_file(&path);
+        }
+
+        job.update(|s| {
+            s.status = outcome.0.to_string();
+            s.error = outcome.1;
+            s.finished_at = Some(get_current_timestamp());
+        });
+        if let Ok(status) = job.snapshot() {
+            on_finished(status);
+        }
+    });
+
+    Ok(job_id)
+}
+
+#[tauri::command]
+pub fn get_export_job_status(job_id: String) -> Result<ExportJobStatus, String> {
+    find_job(&job_id)?.snapshot()
+}
+
+/// Stop a running export; the worker removes the partial file when it sees the flag.
+#[tauri::command]
+pub fn cancel_export_job(job_id: String) -> Result<ExportJobStatus, String> {
+    let job = find_job(&job_id)?;
+    let status = job.snapshot()?;
+    if status.status != "running" {
+        return Err(format!("Export job is already {}", status.status));
+    }
+    job.cancel.store(true, Ordering::SeqCst);
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::mpsc;
+    use crate::test_support::TestDb;
+
+    /// Four batches of orders over 2024, a few of them in the trash.
+    fn a_year_of_orders() {
+        let conn = get_db_connection().unwrap();
+        conn.execute_batch(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 20000)
+             INSERT INTO sales (customer_type, total_amount, created_at, deleted_at)
+             SELECT 'walkin', i % 50, printf('2024-%02d-%02d 12:00:00', 1 + i % 12, 1 + i % 28),
+                    CASE WHEN i % 97 = 0 THEN '2024-12-31T00:00:00+00:00' END
+             FROM n;",
+        )
+        .unwrap();
+    }
+
+    fn start(db: &TestDb, filters: Value, file: &str) -> (String, mpsc::Receiver<ExportJobStatus>) {
+        let (finished, receiver) = mpsc::channel();
+        let target = db.dir().join(file).to_string_lossy().to_string();
+        let job_id = spawn_export_job("orders".to_string(), filters, None, Some(target), move |status| {
+            let _ = finished.send(status);
+        })
+        .unwrap();
+        (job_id, receiver)
+    }
+
+    fn wait(receiver: &mpsc::Receiver<ExportJobStatus>) -> ExportJobStatus {
+        receiver.recv_timeout(Duration::from_secs(60)).expect("export job did not finish")
+    }
+
+    #[test]
+    fn an_export_job_writes_every_matching_row() {
+        let db = TestDb::new();
+        a_year_of_orders();
+        let (job_id, receiver) = start(&db, serde_json::json!({ "start_date": "2024-07-01" }), "orders_h2.csv");
+        let mut progress = Vec::new();
+        loop {
+            let status = get_export_job_status(job_id.clone()).unwrap();
+            progress.push(status.rows_written);
+            if status.status != "running" {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        let finished = wait(&receiver);
+        assert_eq!(finished.status, "completed", "export failed: {:?}", finished.error);
+        assert!(progress.windows(2).all(|w| w[0] <= w[1]), "export progress went backwards: {:?}", progress);
+        let conn = get_db_connection().unwrap();
+        let expected: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sales WHERE deleted_at IS NULL AND created_at >= '2024-07-01'", [], |row| row.get(0))
+            .unwrap();
+        let lines = fs::read_to_string(&finished.file_path).unwrap().lines().count() as i64 - 1;
+        assert!(
+            finished.rows_written == expected && finished.estimated_total == expected && lines == expected,
+            "export wrote {} rows ({} in the file, {} estimated), COUNT gives {}",
+            finished.rows_written, lines, finished.estimated_total, expected
+        );
+    }
+
+    #[test]
+    fn a_cancelled_export_job_leaves_no_file_behind() {
+        let db = TestDb::new();
+        a_year_of_orders();
+        let (job_id, receiver) = start(&db, serde_json::json!({}), "orders_all.csv");
+        while get_export_job_status(job_id.clone()).unwrap().rows_written == 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        cancel_export_job(job_id.clone()).unwrap();
+        let cancelled = wait(&receiver);
+        assert!(
+            cancelled.status == "cancelled" && cancelled.rows_written < cancelled.estimated_total,
+            "export cancelled mid-way ended {} after {} of {} rows", cancelled.status, cancelled.rows_written, cancelled.estimated_total
+        );
+        assert!(!Path::new(&cancelled.file_path).exists(), "cancelled export left its partial file behind");
+        assert!(cancel_export_job(job_id).is_err(), "a finished export job was cancelled again");
+    }
+}
@@ -0,0 +1,590 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::time::Duration;
+use crate::db::{get_db_connection, get_db_path, get_current_timestamp, run_blocking, with_db_path_override};
+use crate::errors::AppError;
+use crate::models::{FiscalRecord, FiscalSettings, FiscalSettingsInput};
+use crate::repository::{get_non_empty_setting, get_setting_or, set_or_clear_setting, set_setting};
+use crate::simple_commands::to_cents;
+
+const PROVIDERS: &[&str] = &["none", "fbr", "webhook"];
+const SOURCE_TYPES: &[&str] = &["order", "checkout"];
+const STATUSES: &[&str] = &["pending", "submitted", "failed"];
+// The receipt may be waiting on this
+const SUBMIT_TIMEOUT_SECS: u64 = 20;
+// A submission still pending after this long was cut off and may be retried
+const STALE_PENDING: &str = "-10 minutes";
+
+const FBR_LIVE_URL: &str = "https://gw.fbr.gov.pk/imsp/v1/api/Live/PostData";
+const FBR_SANDBOX_URL: &str = "https://esp.fbr.gov.pk:8244/FBR/v1/api/Live/PostData";
+
+/// One line of a fiscal invoice. `sale_value` is before tax and `total` after.
+#[derive(Serialize)]
+struct FiscalItem {
+    code: String,
+    name: String,
+    quantity: f64,
+    sale_value: f64,
+    tax_rate: f64,
+    tax: f64,
+    total: f64,
+}
+
+/// A paid bill as the tax authority sees it. This is also the body the
+/// webhook provider posts.
+#[derive(Serialize)]
+struct FiscalInvoice {
+    usin: String,
+    source_type: String,
+    source_id: i64,
+    date_time: String,
+    buyer_name: String,
+    buyer_phone: Option<String>,
+    payment_method: String,
+    items: Vec<FiscalItem>,
+    total_sale_value: f64,
+    total_tax: f64,
+    discount: f64, // Vouchers taken off the items' total
+    total_amount: f64,
+}
+
+/// What the tax authority gave back for an invoice.
+struct FiscalReceipt {
+    fiscal_number: String,
+    qr_payload: Option<String>,
+}
+
+/// Somewhere finished invoices are reported for a fiscal invoice number.
+trait FiscalProvider {
+    fn submit(&self, invoice: &FiscalInvoice) -> Result<FiscalReceipt, String>;
+}
+
+/// Pakistan FBR's POS integration (real-time invoice reporting).
+struct Fbr {
+    url: &'static str,
+    pos_id: String,
+    token: String,
+    pct_code: String,
+}
+
+/// Any other fiscal service: the invoice is posted as JSON and the reply
+/// must carry `fiscal_number` and, optionally, `qr`.
+struct Webhook {
+    url: String,
+    token: Option<String>,
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(Duration::from_secs(SUBMIT_TIMEOUT_SECS)).build()
+}
+
+fn http_error(provider: &str, e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, resp) => {
+            format!("{} returned HTTP {}: {}", provider, code, resp.into_string().unwrap_or_default())
+        }
+        ureq::Error::Transport(t) => format!("Could not reach {}: {}", provider, t),
+    }
+}
+
+fn cents(value: i64) -> f64 {
+    value as f64 / 100.0
+}
+
+impl FiscalProvider for Fbr {
+    fn submit(&self, invoice: &FiscalInvoice) -> Result<FiscalReceipt, String> {
+        // FBR payment modes: 1 cash, 2 card, 5 mixed/other
+        let payment_mode = match invoice.payment_method.as_str() {
+            "cash" => 1,
+            "card" => 2,
+            _ => 5,
+        };
+        let items: Vec<serde_json::Value> = invoice
+            .items
+            .iter()
+            .map(|item| {
+                serde_json::json!({
+                    "ItemCode": item.code,
+                    "ItemName": item.name,
+                    "Quantity": item.quantity,
+                    "PCTCode": self.pct_code,
+                    "TaxRate": item.tax_rate,
+                    "SaleValue": item.sale_value,
+                    "TotalAmount": item.total,
+                    "TaxCharged": item.tax,
+                    "Discount": 0.0,
+                    "FurtherTax": 0.0,
+                    "InvoiceType": 1,
+                })
+            })
+            .collect();
+        let body = serde_json::json!({
+            "InvoiceNumber": "",
+            "POSID": self.pos_id,
+            "USIN": invoice.usin,
+            "DateTime": invoice.date_time,
+            "BuyerName": invoice.buyer_name,
+            "BuyerPhoneNumber": invoice.buyer_phone.clone().unwrap_or_default(),
+            "TotalBillAmount": invoice.total_amount,
+            "TotalQuantity": invoice.items.iter().map(|i| i.quantity).sum::<f64>(),
+            "TotalSaleValue": invoice.total_sale_value,
+            "TotalTaxCharged": invoice.total_tax,
+            "Discount": invoice.discount,
+            "FurtherTax": 0.0,
+            "PaymentMode": payment_mode,
+            "RefUSIN": null,
+            "InvoiceType": 1,
+            "Items": items,
+        });
+
+        let response: serde_json::Value = agent()
+            .post(self.url)
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .send_json(body)
+            .map_err(|e| http_error("FBR", e))?
+            .into_json()
+            .map_err(|e| format!("Unexpected reply from FBR: {}", e))?;
+        let code = match &response["Code"] {
+            serde_json::Value::String(code) => code.clone(),
+            other => other.to_string(),
+        };
+        let number = response["InvoiceNumber"].as_str().map(|n| n.trim()).filter(|n| !n.is_empty());
+        match number {
+            Some(number) if code == "100" => Ok(FiscalReceipt {
+                fiscal_number: number.to_string(),
+                // FBR's receipt QR carries the fiscal invoice number itself
+                qr_payload: Some(number.to_string()),
+            }),
+            _ => Err(format!(
+                "FBR rejected the invoice (code {}): {} {}",
+                code,
+                response["Response"].as_str().unwrap_or_default(),
+                response["Errors"].as_str().unwrap_or_default()
+            )
+            .trim()
+            .to_string()),
+        }
+    }
+}
+
+impl FiscalProvider for Webhook {
+    fn submit(&self, invoice: &FiscalInvoice) -> Result<FiscalReceipt, String> {
+        let mut request = agent().post(&self.url);
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        let response: serde_json::Value = request
+            .send_json(invoice)
+            .map_err(|e| http_error("The fiscal service", e))?
+            .into_json()
+            .map_err(|e| format!("Unexpected reply from the fiscal service: {}", e))?;
+        let fiscal_number = response["fiscal_number"]
+            .as_str()
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .ok_or("The fiscal service did not return a fiscal_number".to_string())?;
+        Ok(FiscalReceipt {
+            fiscal_number,
+            qr_payload: response["qr"].as_str().map(|q| q.to_string()).filter(|q| !q.is_empty()),
+        })
+    }
+}
+
+fn provider_name(conn: &Connection) -> Result<String, String> {
+    Ok(get_non_empty_setting(conn, "fiscal_provider")?.unwrap_or_else(|| "none".to_string()))
+}
+
+fn load_provider(conn: &Connection) -> Result<Box<dyn FiscalProvider>, String> {
+    let require = |key: &str, what: &str| -> Result<String, String> {
+        get_non_empty_setting(conn, key)?.ok_or(format!("{} is not set", what))
+    };
+    match provider_name(conn)?.as_str() {
+        "fbr" => Ok(Box::new(Fbr {
+            url: if get_setting_or(conn, "fiscal_fbr_environment", "sandbox")? == "live" {
+                FBR_LIVE_URL
+            } else {
+                FBR_SANDBOX_URL
+            },
+            pos_id: require("fiscal_fbr_pos_id", "FBR POS ID")?,
+            token: require("fiscal_fbr_token", "FBR access token")?,
+            pct_code: get_non_empty_setting(conn, "fiscal_fbr_pct_code")?.unwrap_or_default(),
+        })),
+        "webhook" => Ok(Box::new(Webhook {
+            url: require("fiscal_webhook_url", "Fiscal service URL")?,
+            token: get_non_empty_setting(conn, "fiscal_webhook_token")?,
+        })),
+        _ => Err("No fiscal provider is set up".to_string()),
+    }
+}
+
+fn parse_source_type(source_type: &str) -> Result<String, String> {
+    let source_type = source_type.trim().to_lowercase();
+    if !SOURCE_TYPES.contains(&source_type.as_str()) {
+        return Err(format!("Invoice type must be one of: {}", SOURCE_TYPES.join(", ")));
+    }
+    Ok(source_type)
+}
+
+/// Our own invoice number, matching the references the receipts' QR codes use.
+fn usin(source_type: &str, source_id: i64) -> String {
+    match source_type {
+        "order" => format!("ORDER-{}", source_id),
+        _ => format!("INVOICE-{}", source_id),
+    }
+}
+
+/// A paid order, item by item. Only taxes already inside the prices are
+/// reported; with tax-exclusive prices food tax is billed at checkout.
+fn order_invoice(conn: &Connection, order_id: i64) -> Result<FiscalInvoice, String> {
+    let (paid, total_amount, service_charge, buyer_name, buyer_phone): (bool, f64, f64, String, Option<String>) = conn
+        .query_row(
+            "SELECT s.paid, s.total_amount - s.rounding, s.service_charge,
+                    COALESCE(NULLIF(TRIM(s.customer_name), ''), c.name, 'Walk-in'), c.phone
+             FROM sales s LEFT JOIN customers c ON c.id = s.guest_id
+             WHERE s.id = ?1 AND s.deleted_at IS NULL",
+            params![order_id],
+            |row| Ok((row.get::<_, i32>(0)? == 1, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or(crate::validation::ORDER_NOT_FOUND.to_string())?;
+    if !paid {
+        return Err(format!("Order #{} is not paid yet", order_id));
+    }
+
+    let mut stmt = conn
+        .prepare("SELECT COALESCE(menu_item_id, 0), item_name, quantity, line_total FROM sale_items WHERE order_id = ?1 ORDER BY id")
+        .map_err(|e| e.to_string())?;
+    let mut lines = stmt
+        .query_map(params![order_id], |row| {
+            Ok((row.get::<_, i64>(0)?.to_string(), row.get::<_, String>(1)?, row.get::<_, f64>(2)?, to_cents(row.get(3)?)))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if to_cents(service_charge) > 0 {
+        lines.push(("SERVICE".to_string(), "Service charge".to_string(), 1.0, to_cents(service_charge)));
+    }
+
+    let taxes = crate::taxes::bill_taxes(conn, 0.0, total_amount, total_amount)?;
+    let included: Vec<_> = taxes.iter().filter(|t| t.included && t.amount > 0.0).collect();
+    let tax_total: i64 = included.iter().map(|t| to_cents(t.amount)).sum();
+    let tax_rate: f64 = included.iter().map(|t| t.percent).sum();
+    let gross: i64 = lines.iter().map(|(_, _, _, total)| total).sum();
+    let discount: i64 = crate::vouchers::redemptions_for(conn, Some(order_id), None)?
+        .iter()
+        .map(|(_, amount)| to_cents(*amount))
+        .sum();
+
+    let items: Vec<FiscalItem> = lines
+        .into_iter()
+        .map(|(code, name, quantity, total)| {
+            let tax = if gross > 0 { (total as f64 * tax_total as f64 / gross as f64).round() as i64 } else { 0 };
+            FiscalItem {
+                code,
+                name,
+                quantity,
+                sale_value: cents(total - tax),
+                tax_rate,
+                tax: cents(tax),
+                total: cents(total),
+            }
+        })
+        .collect();
+    let method: Option<String> = conn
+        .query_row(
+            "SELECT method FROM payments WHERE order_id = ?1 AND amount > 0 ORDER BY paid_at DESC, id DESC LIMIT 1",
+            params![order_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    Ok(FiscalInvoice {
+        usin: usin("order", order_id),
+        source_type: "order".to_string(),
+        source_id: order_id,
+        date_time: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        buyer_name,
+        buyer_phone,
+        payment_method: method.unwrap_or_else(|| "cash".to_string()),
+        total_sale_value: items.iter().map(|i| i.sale_value).sum::<f64>(),
+        total_tax: items.iter().map(|i| i.tax).sum::<f64>(),
+        discount: cents(discount),
+        total_amount: cents(gross - discount),
+        items,
+    })
+}
+
+/// A checkout bill as one line: the final total and the taxes recorded on it.
+fn checkout_invoice(conn: &Connection, guest_id: i64) -> Result<FiscalInvoice, String> {
+    let (name, phone, status, checkout_total): (String, Option<String>, String, Option<f64>) = conn
+        .query_row(
+            "SELECT name, phone, status, checkout_total FROM customers WHERE id = ?1",
+            params![guest_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or(crate::validation::GUEST_NOT_FOUND.to_string())?;
+    let total = match checkout_total {
+        Some(total) if status == "checked_out" => to_cents(total),
+        _ => return Err(format!("Guest #{} has not checked out", guest_id)),
+    };
+
+    let tax: f64 = conn
+        .query_row("SELECT COALESCE(SUM(amount), 0) FROM tax_charges WHERE guest_id = ?1", params![guest_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let tax = to_cents(tax).min(total);
+    let sale_value = total - tax;
+    let method: Option<String> = conn
+        .query_row(
+            "SELECT method FROM payments WHERE guest_id = ?1 AND order_id IS NULL AND amount > 0
+             ORDER BY paid_at DESC, id DESC LIMIT 1",
+            params![guest_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let item = FiscalItem {
+        code: "STAY".to_string(),
+        name: "Room stay and charges".to_string(),
+        quantity: 1.0,
+        sale_value: cents(sale_value),
+        tax_rate: if sale_value > 0 { (tax as f64 * 10000.0 / sale_value as f64).round() / 100.0 } else { 0.0 },
+        tax: cents(tax),
+        total: cents(total),
+    };
+    Ok(FiscalInvoice {
+        usin: usin("checkout", guest_id),
+        source_type: "checkout".to_string(),
+        source_id: guest_id,
+        date_time: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        buyer_name: name,
+        buyer_phone: phone.filter(|p| !p.trim().is_empty()),
+        payment_method: method.unwrap_or_else(|| "cash".to_string()),
+        total_sale_value: item.sale_value,
+        total_tax: item.tax,
+        discount: 0.0,
+        total_amount: item.total,
+        items: vec![item],
+    })
+}
+
+const RECORD_COLUMNS: &str =
+    "id, source_type, source_id, usin, provider, status, fiscal_number, qr_payload, error, attempts, submitted_at, created_at";
+
+fn map_record(row: &rusqlite::Row) -> rusqlite::Result<FiscalRecord> {
+    Ok(FiscalRecord {
+        id: row.get(0)?,
+        source_type: row.get(1)?,
+        source_id: row.get(2)?,
+        usin: row.get(3)?,
+        provider: row.get(4)?,
+        status: row.get(5)?,
+        fiscal_number: row.get(6)?,
+        qr_payload: row.get(7)?,
+        error: row.get(8)?,
+        attempts: row.get(9)?,
+        submitted_at: row.get(10)?,
+        created_at: row.get(11)?,
+    })
+}
+
+fn find_record(conn: &Connection, source_type: &str, source_id: i64) -> Result<Option<FiscalRecord>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM fiscal_invoices WHERE source_type = ?1 AND source_id = ?2", RECORD_COLUMNS),
+        params![source_type, source_id],
+        map_record,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Report a paid order or a checkout bill to the fiscal provider and store the
+/// fiscal invoice number it returns. A bill is only ever submitted once: one
+/// already submitted is returned as it is, and one being submitted elsewhere
+/// is left to finish.
+pub fn fiscalize(conn: &Connection, source_type: &str, source_id: i64) -> Result<FiscalRecord, String> {
+    let source_type = parse_source_type(source_type)?;
+    if let Some(record) = find_record(conn, &source_type, source_id)?.filter(|r| r.status == "submitted") {
+        return Ok(record);
+    }
+    let provider_name = provider_name(conn)?;
+    let provider = load_provider(conn)?;
+    let invoice = match source_type.as_str() {
+        "order" => order_invoice(conn, source_id)?,
+        _ => checkout_invoice(conn, source_id)?,
+    };
+
+    // Claim the bill so a background submission and a reprint can't both send it
+    let claimed = conn
+        .execute(
+            "INSERT OR IGNORE INTO fiscal_invoices (source_type, source_id, usin, provider, status, attempts, attempted_at)
+             VALUES (?1, ?2, ?3, ?4, 'pending', 1, datetime('now'))",
+            params![source_type, source_id, invoice.usin, provider_name],
+        )
+        .map_err(|e| e.to_string())?
+        + conn
+            .execute(
+                "UPDATE fiscal_invoices SET status = 'pending', provider = ?3, attempts = attempts + 1, attempted_at = datetime('now')
+                 WHERE source_type = ?1 AND source_id = ?2
+                   AND (status = 'failed' OR (status = 'pending' AND attempted_at < datetime('now', ?4)))",
+                params![source_type, source_id, provider_name, STALE_PENDING],
+            )
+            .map_err(|e| e.to_string())?;
+    if claimed == 0 {
+        return Err(format!("{} is already being submitted", invoice.usin));
+    }
+
+    match provider.submit(&invoice) {
+        Ok(receipt) => {
+            conn.execute(
+                "UPDATE fiscal_invoices SET status = 'submitted', fiscal_number = ?3, qr_payload = ?4, error = NULL, submitted_at = ?5
+                 WHERE source_type = ?1 AND source_id = ?2",
+                params![source_type, source_id, receipt.fiscal_number, receipt.qr_payload, get_current_timestamp()],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Err(e) => {
+            conn.execute(
+                "UPDATE fiscal_invoices SET status = 'failed', error = ?3 WHERE source_type = ?1 AND source_id = ?2",
+                params![source_type, source_id, e],
+            )
+            .map_err(|e| e.to_string())?;
+            return Err(e);
+        }
+    }
+    find_record(conn, &source_type, source_id)?.ok_or("Fiscal record went missing".to_string())
+}
+
+/// `fiscalize` off the calling thread once a bill is settled, so taking a
+/// payment never waits on the tax authority. Does nothing while no provider
+/// is set up; failures are kept for `fiscalize_invoice` to retry.
+pub fn fiscalize_in_background(source_type: &'static str, source_id: i64) {
+    let db_path = get_db_path();
+    std::thread::spawn(move || {
+        let outcome = with_db_path_override(db_path, || -> Result<(), String> {
+            let conn = get_db_connection().map_err(|e| e.to_string())?;
+            if provider_name(&conn)? != "none" {
+                fiscalize(&conn, source_type, source_id)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = outcome {
+            println!("⚠️ Could not fiscalize {} #{}: {}", source_type, source_id, e);
+        }
+    });
+}
+
+/// The fiscal invoice to print on a receipt, when fiscal numbers are set to
+/// print. A bill that hasn't been reported yet is tried once more here.
+pub fn receipt_fiscal(conn: &Connection, source_type: &str, source_id: i64) -> Result<Option<FiscalRecord>, String> {
+    if get_setting_or(conn, "fiscal_print_on_receipt", "false")? != "true" || provider_name(conn)? == "none" {
+        return Ok(None);
+    }
+    let record = match find_record(conn, source_type, source_id)? {
+        Some(record) if record.status == "submitted" => record,
+        Some(record) if record.status == "pending" => return Ok(None),
+        _ => match fiscalize(conn, source_type, source_id) {
+            Ok(record) => record,
+            Err(e) => {
+                println!("⚠️ Printing {} #{} without a fiscal number: {}", source_type, source_id, e);
+                return Ok(None);
+            }
+        },
+    };
+    Ok(Some(record))
+}
+
+/// Choose the fiscal provider and its credentials. Tokens left out keep the
+/// saved ones, so the form can be resubmitted without them.
+#[command]
+pub fn set_fiscal_settings(settings: FiscalSettingsInput) -> Result<FiscalSettings, AppError> {
+    let provider = settings.provider.trim().to_lowercase();
+    if !PROVIDERS.contains(&provider.as_str()) {
+        return Err(format!("Provider must be one of: {}", PROVIDERS.join(", ")).into());
+    }
+    let trimmed = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let environment = trimmed(settings.fbr_environment).map(|e| e.to_lowercase()).unwrap_or_else(|| "sandbox".to_string());
+    if environment != "sandbox" && environment != "live" {
+        return Err("FBR environment must be sandbox or live".into());
+    }
+    let webhook_url = trimmed(settings.webhook_url);
+    if let Some(url) = &webhook_url {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(format!("'{}' must be an http:// or https:// address", url).into());
+        }
+    }
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    set_or_clear_setting(&tx, "fiscal_provider", Some(&provider))?;
+    set_or_clear_setting(&tx, "fiscal_fbr_environment", Some(&environment))?;
+    set_or_clear_setting(&tx, "fiscal_fbr_pos_id", trimmed(settings.fbr_pos_id).as_deref())?;
+    set_or_clear_setting(&tx, "fiscal_fbr_pct_code", trimmed(settings.fbr_pct_code).as_deref())?;
+    set_or_clear_setting(&tx, "fiscal_webhook_url", webhook_url.as_deref())?;
+    set_setting(&tx, "fiscal_print_on_receipt", &settings.print_on_receipt.to_string())?;
+    // Like other credentials, these are not synced to the other desk
+    for (key, value) in [("fiscal_fbr_token", settings.fbr_token), ("fiscal_webhook_token", settings.webhook_token)] {
+        if let Some(value) = trimmed(value) {
+            set_or_clear_setting(&tx, key, Some(&value))?;
+        }
+    }
+    tx.commit()?;
+    get_fiscal_settings()
+}
+
+#[command]
+pub fn get_fiscal_settings() -> Result<FiscalSettings, AppError> {
+    let conn = get_db_connection()?;
+    let provider = provider_name(&conn)?;
+    let has_secret = match provider.as_str() {
+        "fbr" => get_non_empty_setting(&conn, "fiscal_fbr_token")?.is_some(),
+        "webhook" => get_non_empty_setting(&conn, "fiscal_webhook_token")?.is_some(),
+        _ => false,
+    };
+    Ok(FiscalSettings {
+        provider,
+        fbr_environment: get_setting_or(&conn, "fiscal_fbr_environment", "sandbox")?,
+        fbr_pos_id: get_non_empty_setting(&conn, "fiscal_fbr_pos_id")?,
+        fbr_pct_code: get_non_empty_setting(&conn, "fiscal_fbr_pct_code")?,
+        webhook_url: get_non_empty_setting(&conn, "fiscal_webhook_url")?,
+        print_on_receipt: get_setting_or(&conn, "fiscal_print_on_receipt", "false")? == "true",
+        has_secret,
+    })
+}
+
+/// Submit (or retry) a paid order ("order") or a checkout bill ("checkout",
+/// by guest ID) to the fiscal provider and return its fiscal record.
+#[command]
+pub async fn fiscalize_invoice(source_type: String, source_id: i64) -> Result<FiscalRecord, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        Ok(fiscalize(&conn, &source_type, source_id)?)
+    })
+    .await
+}
+
+/// Bills reported to the fiscal provider, newest first, optionally only
+/// those "pending", "submitted" or "failed".
+#[command]
+pub fn get_fiscal_invoices(status: Option<String>) -> Result<Vec<FiscalRecord>, AppError> {
+    let status = status.map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+    if let Some(status) = &status {
+        if !STATUSES.contains(&status.as_str()) {
+            return Err(format!("Status must be one of: {}", STATUSES.join(", ")).into());
+        }
+    }
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM fiscal_invoices WHERE ?1 IS NULL OR status = ?1 ORDER BY created_at DESC, id DESC",
+        RECORD_COLUMNS
+    ))?;
+    let records = stmt
+        .query_map(params![status], map_record)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(records)
+}
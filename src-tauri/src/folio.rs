@@ -0,0 +1,133 @@
+use tauri::command;
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{GuestDeposit, GuestFolio};
+use crate::simple_commands::{parse_payment_method, to_cents};
+
+/// Sum of deposits a guest has paid towards their stay.
+pub fn guest_deposits_total(conn: &Connection, guest_id: i64) -> Result<f64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(amount), 0) FROM payments WHERE guest_id = ?1 AND is_deposit = 1",
+        params![guest_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub fn guest_deposits(conn: &Connection, guest_id: i64) -> Result<Vec<GuestDeposit>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, guest_id, amount, method, paid_at, note FROM payments
+             WHERE guest_id = ?1 AND is_deposit = 1 ORDER BY paid_at, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![guest_id], |row| {
+            Ok(GuestDeposit {
+                id: row.get(0)?,
+                guest_id: row.get(1)?,
+                amount: row.get(2)?,
+                method: row.get(3)?,
+                paid_at: row.get(4)?,
+                note: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Everything billed to an in-house guest so far, less deposits. Stays are
+/// counted up to today, the same way checkout_guest counts them.
+pub fn build_folio(conn: &Connection, guest_id: i64) -> Result<GuestFolio, String> {
+    let check_in: String = conn
+        .query_row(
+            "SELECT check_in FROM customers WHERE id = ?1 AND status = 'active'",
+            params![guest_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Active guest not found".to_string())?;
+
+    let check_in_date = NaiveDate::parse_from_str(&check_in, "%Y-%m-%d").map_err(|_| "Invalid check-in date format")?;
+    let stay_days = (chrono::Utc::now().date_naive() - check_in_date).num_days().max(1);
+
+    let room_total = crate::pricing::guest_room_total(conn, guest_id, stay_days)?;
+    let unpaid_food: f64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(total_amount - amount_paid), 0) FROM sales WHERE guest_id = ?1 AND paid = 0 AND deleted_at IS NULL",
+            params![guest_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let adjustments = crate::checkout_holds::guest_adjustments_total(conn, guest_id)?;
+    let extra_charges = crate::guest_charges::guest_charges_total(conn, guest_id)?;
+    let deposits = guest_deposits(conn, guest_id)?;
+
+    let total_charges = room_total + unpaid_food + adjustments + extra_charges;
+    let deposits_total: f64 = deposits.iter().map(|d| d.amount).sum();
+    Ok(GuestFolio {
+        guest_id,
+        stay_days,
+        room_total,
+        unpaid_food,
+        adjustments,
+        extra_charges,
+        total_charges,
+        deposits,
+        deposits_total,
+        balance: total_charges - deposits_total,
+    })
+}
+
+/// Record a deposit payment and return its ID.
+pub(crate) fn insert_deposit(conn: &Connection, guest_id: i64, amount: f64, method: &str, note: Option<String>) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO payments (guest_id, amount, method, paid_at, note, is_deposit, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 1, ?4)",
+        params![
+            guest_id,
+            to_cents(amount) as f64 / 100.0,
+            method,
+            get_current_timestamp(),
+            note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Take money from an in-house guest ahead of checkout. Checkout subtracts
+/// deposits from the bill and only settles the rest.
+#[command]
+pub fn add_guest_deposit(
+    guest_id: i64,
+    amount: f64,
+    method: String,
+    note: Option<String>,
+) -> Result<GuestFolio, AppError> {
+    let method = parse_payment_method(&method)?;
+    if !amount.is_finite() || to_cents(amount) <= 0 {
+        return Err("Deposit amount must be greater than 0".into());
+    }
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    // Fails for guests who aren't in house
+    build_folio(&tx, guest_id)?;
+
+    insert_deposit(&tx, guest_id, amount, &method, note)?;
+    let folio = build_folio(&tx, guest_id)?;
+    tx.commit()?;
+
+    Ok(folio)
+}
+
+/// Running balance for an in-house guest: charges so far less deposits paid.
+#[command]
+pub fn get_guest_folio(guest_id: i64) -> Result<GuestFolio, AppError> {
+    let conn = get_db_connection()?;
+    Ok(build_folio(&conn, guest_id)?)
+}
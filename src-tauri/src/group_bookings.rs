@@ -0,0 +1,394 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::json;
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{GroupBooking, GroupMember, GroupMemberResult, NewGroupBooking};
+use crate::money::Money;
+use crate::print_templates::html_escape;
+use crate::repository::get_setting_or;
+use crate::simple_commands::{book_room, checkout_active_guest, convert_reservation_to_checkin, insert_checkout_payment, parse_payment_method};
+use crate::validation::EMPTY_FIELD;
+
+struct GroupRow {
+    id: i64,
+    name: String,
+    contact_name: Option<String>,
+    contact_phone: Option<String>,
+    shared_folio: bool,
+    notes: Option<String>,
+    created_at: String,
+}
+
+fn load_group_row(conn: &Connection, group_id: i64) -> Result<GroupRow, AppError> {
+    conn.query_row(
+        "SELECT id, name, contact_name, contact_phone, shared_folio, notes, created_at FROM guest_groups WHERE id = ?1",
+        params![group_id],
+        |row| {
+            Ok(GroupRow {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                contact_name: row.get(2)?,
+                contact_phone: row.get(3)?,
+                shared_folio: row.get::<_, i32>(4)? == 1,
+                notes: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        },
+    )
+    .optional()?
+    .ok_or_else(|| format!("Group booking #{} not found", group_id).into())
+}
+
+/// What a checked-in member has been billed and has paid: the checkout bill
+/// against payments and company postings once they've left, or the folio so
+/// far against deposits while they're in house.
+fn stay_billing(conn: &Connection, guest_id: i64, status: &str) -> Result<(f64, f64), AppError> {
+    if status == "active" {
+        let folio = crate::folio::build_folio(conn, guest_id)?;
+        return Ok((folio.total_charges, folio.deposits_total));
+    }
+    Ok(conn.query_row(
+        "SELECT COALESCE(c.checkout_total, 0),
+                (SELECT COALESCE(SUM(p.amount), 0) FROM payments p WHERE p.guest_id = c.id AND p.order_id IS NULL)
+                + (SELECT COALESCE(SUM(ch.amount), 0) FROM company_charges ch WHERE ch.guest_id = c.id)
+         FROM customers c WHERE c.id = ?1",
+        params![guest_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?)
+}
+
+/// The group's rooms: stays for those that have checked in, reservations
+/// for the rest. Members are ordered by room number.
+fn group_members(conn: &Connection, group_id: i64) -> Result<Vec<GroupMember>, AppError> {
+    let mut members = {
+        let mut stmt = conn.prepare(
+            "SELECT NULL, c.id, c.room_id, r.number, c.name, c.check_in, c.check_out, c.status
+             FROM customers c LEFT JOIN resources r ON r.id = c.room_id
+             WHERE c.group_id = ?1
+             UNION ALL
+             SELECT res.id, NULL, res.room_id, r.number, res.guest_name, res.check_in, res.check_out, res.status
+             FROM reservations res JOIN resources r ON r.id = res.room_id
+             WHERE res.group_id = ?1 AND res.status != 'checked_in'
+             ORDER BY 4, 2",
+        )?;
+        let rows = stmt.query_map(params![group_id], |row| {
+            Ok(GroupMember {
+                reservation_id: row.get(0)?,
+                guest_id: row.get(1)?,
+                room_id: row.get(2)?,
+                room_number: row.get(3)?,
+                guest_name: row.get(4)?,
+                check_in: row.get(5)?,
+                check_out: row.get(6)?,
+                status: row.get(7)?,
+                charges: 0.0,
+                paid: 0.0,
+                balance: 0.0,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    for member in &mut members {
+        if let Some(guest_id) = member.guest_id {
+            let (charges, paid) = stay_billing(conn, guest_id, &member.status)?;
+            member.charges = charges;
+            member.paid = paid;
+            member.balance = charges - paid;
+        }
+    }
+    Ok(members)
+}
+
+fn build_group(conn: &Connection, group_id: i64) -> Result<GroupBooking, AppError> {
+    let group = load_group_row(conn, group_id)?;
+    let members = group_members(conn, group_id)?;
+    Ok(GroupBooking {
+        id: group.id,
+        name: group.name,
+        contact_name: group.contact_name,
+        contact_phone: group.contact_phone,
+        shared_folio: group.shared_folio,
+        notes: group.notes,
+        created_at: group.created_at,
+        balance: members.iter().map(|m| m.balance).sum(),
+        members,
+    })
+}
+
+/// Book several rooms for one party in one go. Every room is checked the
+/// same way a single reservation is, and either all are booked or none.
+/// Rooms without their own guest name are booked under the group's contact.
+#[command]
+pub fn create_group_booking(group: NewGroupBooking) -> Result<GroupBooking, AppError> {
+    let name = group.name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Group name cannot be empty"));
+    }
+    if group.rooms.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "rooms", "A group booking needs at least one room"));
+    }
+    let tidy = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let contact_name = tidy(group.contact_name);
+    let contact_phone = tidy(group.contact_phone);
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO guest_groups (name, contact_name, contact_phone, shared_folio, notes, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![name, contact_name, contact_phone, group.shared_folio, tidy(group.notes), get_current_timestamp()],
+    )?;
+    let group_id = tx.last_insert_rowid();
+
+    let mut reservation_ids = Vec::new();
+    for room in group.rooms {
+        let guest_name = tidy(room.guest_name)
+            .or_else(|| contact_name.clone())
+            .unwrap_or_else(|| name.to_string());
+        let reservation_id = book_room(
+            &tx,
+            room.room_id,
+            &guest_name,
+            tidy(room.phone).or_else(|| contact_phone.clone()),
+            &group.check_in,
+            &group.check_out,
+            room.daily_rate,
+            room.adults,
+            room.children,
+            Some(format!("Group: {}", name)),
+            Some(group_id),
+            group.override_occupancy_warning.unwrap_or(false),
+        )?;
+        reservation_ids.push(reservation_id);
+    }
+    tx.commit()?;
+
+    // One confirmation for the party, sent to the contact's booking
+    if let Some(first) = reservation_ids.first() {
+        crate::notifications::notify_in_background(
+            "booking_confirmation",
+            crate::notifications::NotificationSubject::Reservation(*first),
+        );
+    }
+    build_group(&conn, group_id)
+}
+
+/// Group bookings, newest first.
+#[command]
+pub fn get_group_bookings() -> Result<Vec<GroupBooking>, AppError> {
+    let conn = get_db_connection()?;
+    let ids = {
+        let mut stmt = conn.prepare("SELECT id FROM guest_groups ORDER BY created_at DESC, id DESC")?;
+        let rows = stmt.query_map([], |row| row.get::<_, i64>(0))?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    ids.into_iter().map(|id| build_group(&conn, id)).collect()
+}
+
+#[command]
+pub fn get_group_booking(group_id: i64) -> Result<GroupBooking, AppError> {
+    let conn = get_db_connection()?;
+    build_group(&conn, group_id)
+}
+
+/// Check in every room of the group still booked, through the regular
+/// reservation check-in. A room that can't be checked in (still occupied,
+/// over the occupancy cap) doesn't stop the others; its error is returned
+/// with it so the desk can deal with it and try again.
+#[command]
+pub fn check_in_group(
+    group_id: i64,
+    override_occupancy_warning: Option<bool>,
+    session_token: Option<String>,
+) -> Result<Vec<GroupMemberResult>, AppError> {
+    let conn = get_db_connection()?;
+    load_group_row(&conn, group_id)?;
+    let booked = group_members(&conn, group_id)?
+        .into_iter()
+        .filter(|m| m.status == "booked")
+        .collect::<Vec<_>>();
+    if booked.is_empty() {
+        return Err("No rooms in this group are waiting to check in".into());
+    }
+
+    let mut results = Vec::new();
+    for member in booked {
+        let reservation_id = member.reservation_id.unwrap_or_default();
+        let outcome = convert_reservation_to_checkin(reservation_id, override_occupancy_warning, session_token.clone())
+            .and_then(|guest_id| {
+                conn.execute("UPDATE customers SET group_id = ?1 WHERE id = ?2", params![group_id, guest_id])?;
+                Ok(guest_id)
+            });
+        results.push(GroupMemberResult {
+            room_number: member.room_number,
+            guest_id: outcome.as_ref().ok().copied(),
+            error: outcome.err().map(|e| e.to_string()),
+        });
+    }
+    Ok(results)
+}
+
+/// Check out every in-house room of the group, each settling its balance
+/// with `payment_method`. Nothing is checked out while any room still has
+/// an open checkout hold.
+#[command]
+pub fn check_out_group(
+    group_id: i64,
+    payment_method: Option<String>,
+    session_token: Option<String>,
+) -> Result<Vec<GroupMemberResult>, AppError> {
+    let payment_method = parse_payment_method(payment_method.as_deref().unwrap_or("cash"))?;
+    let conn = get_db_connection()?;
+    load_group_row(&conn, group_id)?;
+    let in_house = group_members(&conn, group_id)?
+        .into_iter()
+        .filter(|m| m.status == "active")
+        .collect::<Vec<_>>();
+    if in_house.is_empty() {
+        return Err("No rooms in this group are checked in".into());
+    }
+    for member in &in_house {
+        crate::checkout_holds::ensure_no_open_holds(&conn, member.guest_id.unwrap_or_default())?;
+    }
+
+    let mut results = Vec::new();
+    for member in in_house {
+        let guest_id = member.guest_id.unwrap_or_default();
+        let outcome = checkout_active_guest(guest_id, None, None, None, None, session_token.clone(), |tx, balance_due| {
+            Ok(insert_checkout_payment(tx, guest_id, balance_due, &payment_method)?)
+        });
+        results.push(GroupMemberResult {
+            room_number: member.room_number,
+            guest_id: Some(guest_id),
+            error: outcome.err().map(|e| e.to_string()),
+        });
+    }
+    Ok(results)
+}
+
+/// One invoice for the whole party: each room's charges, what has been paid
+/// and what's left, with the total the group owes when it shares a folio.
+#[command]
+pub fn build_group_invoice_html(group_id: i64) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let group = build_group(&conn, group_id)?;
+    let money = Money::load(&conn)?;
+
+    let billed: Vec<&GroupMember> = group.members.iter().filter(|m| m.guest_id.is_some()).collect();
+    let room_rows: String = if billed.is_empty() {
+        r#"<tr><td colspan="6" class="empty">No rooms have checked in yet</td></tr>"#.to_string()
+    } else {
+        billed
+            .iter()
+            .map(|m| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td>{} &ndash; {}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                    html_escape(m.room_number.as_deref().unwrap_or("Walk-in")),
+                    html_escape(&m.guest_name),
+                    m.check_in,
+                    m.check_out.as_deref().unwrap_or(""),
+                    money.format(m.charges, 2),
+                    money.format(m.paid, 2),
+                    money.format(m.balance, 2)
+                )
+            })
+            .collect()
+    };
+    let charges: f64 = billed.iter().map(|m| m.charges).sum();
+    let paid: f64 = billed.iter().map(|m| m.paid).sum();
+    let settlement = if group.shared_folio {
+        format!("Amount due from the group: <strong>{}</strong>", money.format(group.balance, 2))
+    } else {
+        "Each room settles its own bill.".to_string()
+    };
+
+    Ok(crate::templates::render(
+        "group_invoice",
+        &json!({
+            "business_name": get_setting_or(&conn, "business_name", "Business Manager")?,
+            "business_contact": crate::print_templates::business_contact(&conn)?,
+            "group_name": group.name,
+            "contact_name": group.contact_name.unwrap_or_default(),
+            "contact_phone": group.contact_phone.unwrap_or_default(),
+            "room_count": billed.len(),
+            "room_rows": room_rows,
+            "total_charges": money.format(charges, 2),
+            "total_paid": money.format(paid, 2),
+            "total_balance": money.format(group.balance, 2),
+            "settlement": settlement,
+            "generated_at": get_current_timestamp(),
+        }),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GroupRoomInput;
+    use crate::test_support::{add_test_room, days_from_today, same_amount, today, TestDb};
+
+    /// A shared-folio party booked into ST-201 and ST-202 for tonight.
+    fn book_party() -> GroupBooking {
+        let rooms = ["ST-201", "ST-202"]
+            .into_iter()
+            .map(|number| GroupRoomInput {
+                room_id: add_test_room(number),
+                guest_name: None,
+                phone: None,
+                daily_rate: None,
+                adults: Some(2),
+                children: None,
+            })
+            .collect();
+        create_group_booking(NewGroupBooking {
+            name: "Test party".to_string(),
+            contact_name: Some("Party Lead".to_string()),
+            contact_phone: None,
+            shared_folio: true,
+            check_in: today(),
+            check_out: days_from_today(1),
+            notes: None,
+            rooms,
+            override_occupancy_warning: None,
+        })
+        .unwrap()
+    }
+
+    fn check_in_party(group_id: i64) {
+        let failed: Vec<String> = check_in_group(group_id, None, None).unwrap().into_iter().filter_map(|r| r.error).collect();
+        assert!(failed.is_empty(), "group check-in failed: {}", failed.join("; "));
+    }
+
+    #[test]
+    fn a_group_books_every_room_at_once() {
+        let _db = TestDb::new();
+        let group = book_party();
+        assert_eq!(group.members.len(), 2, "group does not hold both rooms");
+        assert!(group.members.iter().all(|m| m.status == "booked"), "group rooms are not all booked");
+    }
+
+    #[test]
+    fn checking_in_a_group_puts_every_room_in_house() {
+        let _db = TestDb::new();
+        let group = book_party();
+        check_in_party(group.id);
+        let group = get_group_booking(group.id).unwrap();
+        assert!(group.members.iter().all(|m| m.status == "active"), "group rooms are not all in house");
+        assert!(!same_amount(group.balance, 0.0), "in-house group shows nothing owed");
+    }
+
+    #[test]
+    fn checking_out_a_group_settles_it_on_one_invoice() {
+        let _db = TestDb::new();
+        let group = book_party();
+        check_in_party(group.id);
+        let checked_out = check_out_group(group.id, Some("card".to_string()), None).unwrap();
+        assert!(checked_out.iter().all(|r| r.error.is_none()), "group check-out failed");
+        let group = get_group_booking(group.id).unwrap();
+        assert!(same_amount(group.balance, 0.0), "group still owes {:.2} after checkout", group.balance);
+        let html = build_group_invoice_html(group.id).unwrap();
+        assert!(html.contains("Test party") && html.contains("ST-201") && html.contains("ST-202"), "group invoice does not list both rooms");
+        assert!(html.contains("Amount due from the group"), "shared-folio group invoice has no group total");
+    }
+}
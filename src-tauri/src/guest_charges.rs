@@ -0,0 +1,141 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::audit_actor;
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::GuestCharge;
+use crate::validation::{validate_date_format, NEGATIVE_AMOUNT};
+
+const CATEGORIES: &[&str] = &["minibar", "laundry", "early_checkin", "late_checkout", "telephone", "other"];
+
+const CHARGE_COLUMNS: &str = "id, guest_id, category, description, quantity, unit_price, amount, charged_on,
+                              created_by, created_at";
+
+fn map_charge(row: &rusqlite::Row) -> rusqlite::Result<GuestCharge> {
+    Ok(GuestCharge {
+        id: row.get(0)?,
+        guest_id: row.get(1)?,
+        category: row.get(2)?,
+        description: row.get(3)?,
+        quantity: row.get(4)?,
+        unit_price: row.get(5)?,
+        amount: row.get(6)?,
+        charged_on: row.get(7)?,
+        created_by: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+/// Label used when a charge is added without a description, e.g. "Late checkout".
+fn category_label(category: &str) -> String {
+    let label = category.replace('_', " ");
+    let mut chars = label.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => label,
+    }
+}
+
+/// Sum of extra charges on a guest's folio.
+pub fn guest_charges_total(conn: &Connection, guest_id: i64) -> Result<f64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(amount), 0) FROM guest_charges WHERE guest_id = ?1",
+        params![guest_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub fn guest_charges(conn: &Connection, guest_id: i64) -> Result<Vec<GuestCharge>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM guest_charges WHERE guest_id = ?1 ORDER BY charged_on, id",
+            CHARGE_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![guest_id], map_charge).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Bill a miscellaneous item to an in-house guest; it is added to the checkout
+/// total and listed on the final invoice. `charged_on` defaults to today.
+#[command]
+pub fn add_guest_charge(
+    guest_id: i64,
+    category: String,
+    unit_price: f64,
+    quantity: Option<i64>,
+    description: Option<String>,
+    charged_on: Option<String>,
+    session_token: Option<String>,
+) -> Result<GuestCharge, AppError> {
+    let category = category.trim().to_lowercase();
+    if !CATEGORIES.contains(&category.as_str()) {
+        return Err(format!("category must be one of: {}", CATEGORIES.join(", ")).into());
+    }
+    if !unit_price.is_finite() || unit_price < 0.0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "unit_price", "Price must be >= 0"));
+    }
+    let quantity = quantity.unwrap_or(1);
+    if quantity < 1 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "quantity", "Quantity must be at least 1"));
+    }
+    let description = description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| category_label(&category));
+    let charged_on = charged_on.unwrap_or_else(crate::simple_commands::business_date_today);
+    validate_date_format(&charged_on)?;
+
+    let conn = get_db_connection()?;
+    let is_active: bool = conn
+        .query_row("SELECT 1 FROM customers WHERE id = ?1 AND status = 'active'", params![guest_id], |_| Ok(true))
+        .optional()?
+        .unwrap_or(false);
+    if !is_active {
+        return Err("Active guest not found".into());
+    }
+
+    conn.execute(
+        "INSERT INTO guest_charges (guest_id, category, description, quantity, unit_price, amount, charged_on, created_by, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            guest_id,
+            category,
+            description,
+            quantity,
+            unit_price,
+            unit_price * quantity as f64,
+            charged_on,
+            audit_actor(session_token.as_deref()),
+            get_current_timestamp()
+        ],
+    )?;
+
+    Ok(conn.query_row(
+        &format!("SELECT {} FROM guest_charges WHERE id = ?1", CHARGE_COLUMNS),
+        params![conn.last_insert_rowid()],
+        map_charge,
+    )?)
+}
+
+#[command]
+pub fn get_guest_charges(guest_id: i64) -> Result<Vec<GuestCharge>, AppError> {
+    let conn = get_db_connection()?;
+    Ok(guest_charges(&conn, guest_id)?)
+}
+
+/// Remove a charge posted by mistake. Only possible while the guest is in house.
+#[command]
+pub fn delete_guest_charge(charge_id: i64) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let affected = conn.execute(
+        "DELETE FROM guest_charges
+         WHERE id = ?1 AND guest_id IN (SELECT id FROM customers WHERE status = 'active')",
+        params![charge_id],
+    )?;
+    if affected == 0 {
+        return Err("Charge not found, or the guest has already checked out".into());
+    }
+    Ok("Charge deleted".to_string())
+}
@@ -0,0 +1,183 @@
+use tauri::command;
+use std::fs;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use crate::audit::{record_change, row_json};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::GuestAnonymization;
+use crate::offline_auth::require_session;
+use crate::validation::{GUEST_ANONYMIZED, GUEST_NOT_FOUND, UNAUTHORIZED};
+
+const ANONYMIZE_ROLES: &[&str] = &["manager", "admin"];
+const ANONYMIZED_NAME: &str = "Anonymized guest";
+
+/// The guest's profile and every stay on it. A stay from before profiles
+/// existed that was never linked stands alone.
+fn guest_stays(conn: &Connection, guest_id: i64) -> Result<(Option<i64>, Vec<i64>), AppError> {
+    let profile_id: Option<i64> = conn
+        .query_row("SELECT profile_id FROM customers WHERE id = ?1", params![guest_id], |row| row.get(0))
+        .optional()?
+        .ok_or_else(|| AppError::coded(GUEST_NOT_FOUND, format!("Guest #{} not found", guest_id)))?;
+    let Some(profile_id) = profile_id else {
+        return Ok((None, vec![guest_id]));
+    };
+    let mut stmt = conn.prepare("SELECT id FROM customers WHERE profile_id = ?1 ORDER BY check_in, id")?;
+    let stays = stmt.query_map(params![profile_id], |row| row.get(0))?.collect::<Result<Vec<i64>, _>>()?;
+    Ok((Some(profile_id), stays))
+}
+
+/// Every row `sql` returns for `id`, as JSON objects keyed by column.
+fn rows_json(conn: &Connection, sql: &str, id: i64) -> Result<Vec<Value>, AppError> {
+    let mut stmt = conn.prepare(sql)?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let rows = stmt.query_map(params![id], |row| row_json(row, &columns))?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+fn stay_json(conn: &Connection, stay_id: i64) -> Result<Value, AppError> {
+    let mut orders = rows_json(conn, "SELECT * FROM sales WHERE guest_id = ?1 ORDER BY id", stay_id)?;
+    for order in &mut orders {
+        if let Some(order_id) = order.get("id").and_then(Value::as_i64) {
+            order["items"] = json!(rows_json(conn, "SELECT * FROM sale_items WHERE order_id = ?1 ORDER BY id", order_id)?);
+        }
+    }
+    Ok(json!({
+        "stay": rows_json(conn, "SELECT * FROM customers WHERE id = ?1", stay_id)?.pop(),
+        "orders": orders,
+        "payments": rows_json(
+            conn,
+            "SELECT * FROM payments WHERE guest_id = ?1 OR order_id IN (SELECT id FROM sales WHERE guest_id = ?1) ORDER BY id",
+            stay_id,
+        )?,
+        "charges": rows_json(conn, "SELECT * FROM guest_charges WHERE guest_id = ?1 ORDER BY id", stay_id)?,
+        "adjustments": rows_json(conn, "SELECT * FROM guest_adjustments WHERE guest_id = ?1 ORDER BY id", stay_id)?,
+        "room_transfers": rows_json(conn, "SELECT * FROM room_transfers WHERE guest_id = ?1 ORDER BY id", stay_id)?,
+        "company_charges": rows_json(conn, "SELECT * FROM company_charges WHERE guest_id = ?1 ORDER BY id", stay_id)?,
+        "voucher_redemptions": rows_json(
+            conn,
+            "SELECT * FROM voucher_redemptions WHERE guest_id = ?1 OR order_id IN (SELECT id FROM sales WHERE guest_id = ?1) ORDER BY id",
+            stay_id,
+        )?,
+        "reservations": rows_json(conn, "SELECT * FROM reservations WHERE guest_id = ?1 ORDER BY id", stay_id)?,
+        "feedback": rows_json(conn, "SELECT * FROM guest_feedback WHERE guest_id = ?1", stay_id)?,
+        "messages": rows_json(conn, "SELECT * FROM notification_log WHERE guest_id = ?1 ORDER BY id", stay_id)?,
+    }))
+}
+
+/// Everything held about a guest, for a subject access request: their
+/// profile and, for every stay on it, the stay itself with its orders,
+/// payments, charges, reservations, feedback and the messages sent to them.
+/// `guest_id` can be any one of the guest's stays.
+#[command]
+pub fn export_guest_data(guest_id: i64) -> Result<Value, AppError> {
+    let conn = get_db_connection()?;
+    let (profile_id, stays) = guest_stays(&conn, guest_id)?;
+    let profile = match profile_id {
+        Some(profile_id) => rows_json(&conn, "SELECT * FROM guest_profiles WHERE id = ?1", profile_id)?.pop(),
+        None => None,
+    };
+    let stays = stays.into_iter().map(|stay_id| stay_json(&conn, stay_id)).collect::<Result<Vec<_>, _>>()?;
+    Ok(json!({
+        "exported_at": get_current_timestamp(),
+        "profile": profile,
+        "stays": stays,
+    }))
+}
+
+/// Erase a guest's personal details across their profile and all their stays.
+/// Names become "Anonymized guest"; phone numbers, ID documents, notes,
+/// feedback comments, message text and signatures are removed, along with
+/// the before/after values in the audit log that would still name them.
+/// Amounts, dates, rooms and payments stay, so the books still add up.
+/// Guests still in house can't be anonymized.
+pub(crate) fn anonymize_stays(conn: &Connection, guest_id: i64, actor: &str) -> Result<GuestAnonymization, AppError> {
+    let (profile_id, stays) = guest_stays(conn, guest_id)?;
+    if let Some(profile_id) = profile_id {
+        let already: Option<String> = conn.query_row(
+            "SELECT anonymized_at FROM guest_profiles WHERE id = ?1",
+            params![profile_id],
+            |row| row.get(0),
+        )?;
+        if already.is_some() {
+            return Err(AppError::coded(GUEST_ANONYMIZED, format!("Guest profile #{} has already been anonymized", profile_id)));
+        }
+    }
+    for stay_id in &stays {
+        let status: String = conn.query_row("SELECT status FROM customers WHERE id = ?1", params![stay_id], |row| row.get(0))?;
+        if status == "active" {
+            return Err(format!("Guest #{} is still in house; check them out before erasing their details", stay_id).into());
+        }
+    }
+
+    let now = get_current_timestamp();
+    let mut signatures = Vec::new();
+    let tx = conn.unchecked_transaction()?;
+    for stay_id in &stays {
+        let signature: Option<String> =
+            tx.query_row("SELECT signature_path FROM customers WHERE id = ?1", params![stay_id], |row| row.get(0))?;
+        signatures.extend(signature);
+        tx.execute(
+            "UPDATE customers SET name = ?1, phone = NULL, signature_path = NULL, import_row_hash = NULL, updated_at = ?2
+             WHERE id = ?3",
+            params![ANONYMIZED_NAME, now, stay_id],
+        )?;
+        tx.execute(
+            "UPDATE sales SET customer_name = ?1 WHERE guest_id = ?2 AND customer_name IS NOT NULL",
+            params![ANONYMIZED_NAME, stay_id],
+        )?;
+        tx.execute(
+            "UPDATE reservations SET guest_name = ?1, phone = NULL, notes = NULL WHERE guest_id = ?2",
+            params![ANONYMIZED_NAME, stay_id],
+        )?;
+        tx.execute("UPDATE notification_log SET recipient = '', message = '' WHERE guest_id = ?1", params![stay_id])?;
+        tx.execute("UPDATE guest_feedback SET comment = NULL WHERE guest_id = ?1", params![stay_id])?;
+        tx.execute(
+            "UPDATE company_charges SET description = 'Stay #' || guest_id WHERE guest_id = ?1 AND description LIKE 'Stay: %'",
+            params![stay_id],
+        )?;
+        tx.execute(
+            "UPDATE audit_log SET old_values = NULL, new_values = NULL WHERE entity = 'guest' AND entity_id = ?1",
+            params![stay_id],
+        )?;
+        record_change(&tx, actor, "guest", *stay_id, "anonymize", None, None)?;
+    }
+    if let Some(profile_id) = profile_id {
+        tx.execute(
+            "UPDATE guest_profiles
+             SET name = ?1, phone = NULL, phone_key = NULL, email = NULL, id_number = NULL, nationality = NULL,
+                 preferences = NULL, notes = NULL, anonymized_at = ?2, updated_at = ?2
+             WHERE id = ?3",
+            params![ANONYMIZED_NAME, now, profile_id],
+        )?;
+        tx.execute(
+            "UPDATE audit_log SET old_values = NULL, new_values = NULL WHERE entity = 'guest_profile' AND entity_id = ?1",
+            params![profile_id],
+        )?;
+        record_change(&tx, actor, "guest_profile", profile_id, "anonymize", None, None)?;
+    }
+    tx.commit()?;
+
+    for path in &signatures {
+        let _ = fs::remove_file(path);
+    }
+
+    Ok(GuestAnonymization {
+        profile_id,
+        stays_anonymized: stays.len() as i64,
+        signatures_removed: signatures.len() as i64,
+        anonymized_at: now,
+    })
+}
+
+/// Erase a guest's personal details (see anonymize_stays). Managers and
+/// admins only; it can't be undone.
+#[command]
+pub fn anonymize_guest(guest_id: i64, session_token: String) -> Result<GuestAnonymization, AppError> {
+    let session = require_session(&session_token)?;
+    if !ANONYMIZE_ROLES.contains(&session.role.as_str()) {
+        return Err(AppError::coded(UNAUTHORIZED, "Only a manager or admin can erase a guest's details"));
+    }
+    let conn = get_db_connection()?;
+    anonymize_stays(&conn, guest_id, &session.username)
+}
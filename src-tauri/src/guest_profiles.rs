@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{DuplicateGuestGroup, GuestMergeResult, GuestProfile, GuestProfileHistory, GuestProfileInput, ProfileStay};
+use crate::simple_commands::to_cents;
+use crate::validation::{validate_phone_number, EMPTY_FIELD, GUEST_ANONYMIZED, GUEST_PROFILE_NOT_FOUND};
+
+const SEARCH_LIMIT: i64 = 100;
+const FAVOURITE_ITEMS: i64 = 5;
+
+const PROFILE_COLUMNS: &str = "p.id, p.name, p.phone, p.email, p.id_number, p.nationality, p.preferences, p.notes,
+     (SELECT COUNT(*) FROM customers c WHERE c.profile_id = p.id),
+     (SELECT MAX(c.check_in) FROM customers c WHERE c.profile_id = p.id) AS last_check_in,
+     p.anonymized_at, p.created_at";
+
+fn map_profile(row: &rusqlite::Row) -> rusqlite::Result<GuestProfile> {
+    Ok(GuestProfile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        phone: row.get(2)?,
+        email: row.get(3)?,
+        id_number: row.get(4)?,
+        nationality: row.get(5)?,
+        preferences: row.get(6)?,
+        notes: row.get(7)?,
+        stay_count: row.get(8)?,
+        last_check_in: row.get(9)?,
+        anonymized_at: row.get(10)?,
+        created_at: row.get(11)?,
+    })
+}
+
+fn load_profile(conn: &Connection, profile_id: i64) -> Result<GuestProfile, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM guest_profiles p WHERE p.id = ?1", PROFILE_COLUMNS),
+        params![profile_id],
+        map_profile,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::coded(GUEST_PROFILE_NOT_FOUND, format!("Guest profile #{} not found", profile_id)))
+}
+
+/// A profile that can still be changed or have stays added: not anonymized.
+fn load_live_profile(conn: &Connection, profile_id: i64) -> Result<GuestProfile, AppError> {
+    let profile = load_profile(conn, profile_id)?;
+    if profile.anonymized_at.is_some() {
+        return Err(AppError::coded(GUEST_ANONYMIZED, format!("Guest profile #{} has been anonymized", profile_id)));
+    }
+    Ok(profile)
+}
+
+/// What two phone numbers are matched on: the last ten digits, so that
+/// 0300 1234567 and +92 300 1234567 are the same guest. Too few digits to be
+/// a real number matches nothing.
+fn phone_key(phone: &str) -> Option<String> {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 7 {
+        return None;
+    }
+    Some(digits[digits.len().saturating_sub(10)..].to_string())
+}
+
+/// The profile a new stay belongs to: the one with the same phone number, or
+/// a new profile when the guest hasn't stayed before (or gave no phone).
+pub(crate) fn profile_for_stay(conn: &Connection, name: &str, phone: Option<&str>) -> SqliteResult<i64> {
+    let key = phone.and_then(phone_key);
+    if let Some(key) = &key {
+        let existing: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM guest_profiles WHERE phone_key = ?1 ORDER BY id LIMIT 1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(profile_id) = existing {
+            return Ok(profile_id);
+        }
+    }
+    let now = get_current_timestamp();
+    conn.execute(
+        "INSERT INTO guest_profiles (name, phone, phone_key, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
+        params![name.trim(), phone.map(str::trim).filter(|p| !p.is_empty()), key, now],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Link a stay to its guest's profile: the one picked at check-in, or else
+/// the one matched by phone number. Returns the profile ID.
+pub(crate) fn attach_stay(
+    conn: &Connection,
+    stay_id: i64,
+    profile_id: Option<i64>,
+    name: &str,
+    phone: Option<&str>,
+) -> Result<i64, AppError> {
+    let profile_id = match profile_id {
+        Some(profile_id) => load_live_profile(conn, profile_id)?.id,
+        None => profile_for_stay(conn, name, phone)?,
+    };
+    conn.execute("UPDATE customers SET profile_id = ?1 WHERE id = ?2", params![profile_id, stay_id])?;
+    Ok(profile_id)
+}
+
+/// What's still owed on a stay: the running folio balance while the guest is
+/// in house, or the part of the checkout bill not yet paid or posted to a
+/// company account once they've left.
+fn stay_outstanding(conn: &Connection, stay_id: i64, status: &str) -> Result<f64, AppError> {
+    let owed = if status == "active" {
+        crate::folio::build_folio(conn, stay_id)?.balance
+    } else {
+        conn.query_row(
+            "SELECT COALESCE(c.checkout_total, 0)
+                    - (SELECT COALESCE(SUM(p.amount), 0) FROM payments p WHERE p.guest_id = c.id AND p.order_id IS NULL)
+                    - (SELECT COALESCE(SUM(ch.amount), 0) FROM company_charges ch WHERE ch.guest_id = c.id)
+             FROM customers c WHERE c.id = ?1",
+            params![stay_id],
+            |row| row.get(0),
+        )?
+    };
+    Ok(if to_cents(owed) > 0 { owed } else { 0.0 })
+}
+
+/// Guest profiles, most recently seen first, optionally narrowed to those
+/// whose name, phone, email or ID number contains `search`.
+#[command]
+pub fn get_guest_profiles(search: Option<String>) -> Result<Vec<GuestProfile>, AppError> {
+    let conn = get_db_connection()?;
+    let pattern = search
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("%{}%", s));
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM guest_profiles p
+         WHERE ?1 IS NULL OR p.name LIKE ?1 OR p.phone LIKE ?1 OR p.email LIKE ?1 OR p.id_number LIKE ?1
+         ORDER BY last_check_in DESC, p.id DESC
+         LIMIT ?2",
+        PROFILE_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![pattern, SEARCH_LIMIT], map_profile)?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+#[command]
+pub fn get_guest_profile(profile_id: i64) -> Result<GuestProfile, AppError> {
+    let conn = get_db_connection()?;
+    load_profile(&conn, profile_id)
+}
+
+/// Update who the guest is. Past stays keep the name and phone they were
+/// checked in under.
+#[command]
+pub fn update_guest_profile(
+    profile_id: i64,
+    profile: GuestProfileInput,
+    session_token: Option<String>,
+) -> Result<GuestProfile, AppError> {
+    let conn = get_db_connection()?;
+    load_live_profile(&conn, profile_id)?;
+
+    let name = profile.name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Guest name cannot be empty"));
+    }
+    let tidy = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let phone = tidy(profile.phone);
+    if let Some(phone) = &phone {
+        validate_phone_number(phone).map_err(|code| AppError::invalid(&code, "phone", format!("'{}' is not a phone number", phone)))?;
+    }
+    let email = tidy(profile.email);
+    if email.as_deref().is_some_and(|e| !e.contains('@')) {
+        return Err(AppError::invalid(EMPTY_FIELD, "email", "Enter a valid email address"));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let before = snapshot(&tx, "guest_profile", profile_id)?;
+    tx.execute(
+        "UPDATE guest_profiles
+         SET name = ?1, phone = ?2, phone_key = ?3, email = ?4, id_number = ?5, nationality = ?6,
+             preferences = ?7, notes = ?8, updated_at = ?9
+         WHERE id = ?10",
+        params![
+            name,
+            phone,
+            phone.as_deref().and_then(phone_key),
+            email,
+            tidy(profile.id_number),
+            tidy(profile.nationality),
+            tidy(profile.preferences),
+            tidy(profile.notes),
+            get_current_timestamp(),
+            profile_id,
+        ],
+    )?;
+    record_change(
+        &tx,
+        &audit_actor(session_token.as_deref()),
+        "guest_profile",
+        profile_id,
+        "update",
+        before,
+        snapshot(&tx, "guest_profile", profile_id)?,
+    )?;
+    tx.commit()?;
+
+    load_profile(&conn, profile_id)
+}
+
+/// A returning guest at a glance: every stay, what they've spent, what they
+/// still owe and what they usually order.
+#[command]
+pub fn get_guest_profile_history(profile_id: i64) -> Result<GuestProfileHistory, AppError> {
+    let conn = get_db_connection()?;
+    let profile = load_profile(&conn, profile_id)?;
+
+    let mut stays = {
+        let mut stmt = conn.prepare(
+            "SELECT c.id, r.number, c.check_in, c.check_out, c.status, c.adults, c.children, c.checkout_total,
+                    (SELECT COALESCE(SUM(s.amount_paid), 0) FROM sales s WHERE s.guest_id = c.id AND s.deleted_at IS NULL)
+             FROM customers c LEFT JOIN resources r ON r.id = c.room_id
+             WHERE c.profile_id = ?1
+             ORDER BY c.check_in DESC, c.id DESC",
+        )?;
+        let rows = stmt.query_map(params![profile_id], |row| {
+            let checkout_total: Option<f64> = row.get(7)?;
+            let paid_on_orders: f64 = row.get(8)?;
+            Ok(ProfileStay {
+                guest_id: row.get(0)?,
+                room_number: row.get(1)?,
+                check_in: row.get(2)?,
+                check_out: row.get(3)?,
+                status: row.get(4)?,
+                adults: row.get(5)?,
+                children: row.get(6)?,
+                spent: checkout_total.unwrap_or(0.0) + paid_on_orders,
+                outstanding: 0.0,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+    for stay in &mut stays {
+        stay.outstanding = stay_outstanding(&conn, stay.guest_id, &stay.status)?;
+    }
+
+    let favourite_items = {
+        let mut stmt = conn.prepare(
+            "SELECT si.item_name FROM sale_items si
+             JOIN sales s ON s.id = si.order_id
+             JOIN customers c ON c.id = s.guest_id
+             WHERE c.profile_id = ?1 AND s.deleted_at IS NULL
+             GROUP BY si.item_name
+             ORDER BY SUM(si.quantity) DESC, si.item_name
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![profile_id, FAVOURITE_ITEMS], |row| row.get::<_, String>(0))?;
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+
+    Ok(GuestProfileHistory {
+        total_stays: stays.len() as i64,
+        total_spent: stays.iter().map(|s| s.spent).sum(),
+        outstanding_balance: stays.iter().map(|s| s.outstanding).sum(),
+        profile,
+        stays,
+        favourite_items,
+    })
+}
+
+/// Names compared case- and spacing-insensitively.
+fn name_key(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Profiles that look like the same guest entered more than once: ones
+/// sharing a phone number, then ones sharing a name that the phone match
+/// didn't already put together. For the front desk to review and merge.
+#[command]
+pub fn find_duplicate_guests() -> Result<Vec<DuplicateGuestGroup>, AppError> {
+    let conn = get_db_connection()?;
+    let mut by_phone: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+    let mut by_name: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+    {
+        let mut stmt = conn.prepare("SELECT id, name, phone_key FROM guest_profiles WHERE anonymized_at IS NULL ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?))
+        })?;
+        for row in rows {
+            let (profile_id, name, phone_key) = row?;
+            if let Some(key) = phone_key {
+                by_phone.entry(key).or_default().push(profile_id);
+            }
+            let name = name_key(&name);
+            if !name.is_empty() {
+                by_name.entry(name).or_default().push(profile_id);
+            }
+        }
+    }
+
+    let mut groups: Vec<(&str, Vec<i64>)> = Vec::new();
+    for ids in by_phone.into_values().filter(|ids| ids.len() > 1) {
+        groups.push(("phone", ids));
+    }
+    for ids in by_name.into_values().filter(|ids| ids.len() > 1) {
+        if !groups.iter().any(|(_, grouped)| ids.iter().all(|id| grouped.contains(id))) {
+            groups.push(("name", ids));
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(matched_on, ids)| {
+            let profiles = ids.iter().map(|id| load_profile(&conn, *id)).collect::<Result<Vec<_>, _>>()?;
+            Ok(DuplicateGuestGroup { matched_on: matched_on.to_string(), profiles })
+        })
+        .collect()
+}
+
+/// Two free-text fields as one, without repeating what's already there.
+fn combine_text(primary: Option<String>, other: Option<String>) -> Option<String> {
+    match (primary, other) {
+        (Some(p), Some(o)) if !p.to_lowercase().contains(&o.to_lowercase()) => Some(format!("{}; {}", p, o)),
+        (None, o) => o,
+        (p, _) => p,
+    }
+}
+
+/// Fold duplicate profiles into `primary_id`: their stays move over, and with
+/// them the orders, payments, deposits and charges that belong to those
+/// stays. Contact details the primary profile lacks are taken from the
+/// duplicates; preferences and notes are combined. The duplicates are removed.
+#[command]
+pub fn merge_guests(
+    primary_id: i64,
+    duplicate_ids: Vec<i64>,
+    session_token: Option<String>,
+) -> Result<GuestMergeResult, AppError> {
+    let mut duplicate_ids = duplicate_ids;
+    duplicate_ids.sort_unstable();
+    duplicate_ids.dedup();
+    if duplicate_ids.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "duplicate_ids", "Pick at least one profile to merge"));
+    }
+    if duplicate_ids.contains(&primary_id) {
+        return Err(AppError::invalid(EMPTY_FIELD, "duplicate_ids", "A profile cannot be merged into itself"));
+    }
+
+    let conn = get_db_connection()?;
+    let mut merged = load_live_profile(&conn, primary_id)?;
+    let duplicates = duplicate_ids.iter().map(|id| load_live_profile(&conn, *id)).collect::<Result<Vec<_>, _>>()?;
+    for duplicate in &duplicates {
+        merged.phone = merged.phone.or(duplicate.phone.clone());
+        merged.email = merged.email.or(duplicate.email.clone());
+        merged.id_number = merged.id_number.or(duplicate.id_number.clone());
+        merged.nationality = merged.nationality.or(duplicate.nationality.clone());
+        merged.preferences = combine_text(merged.preferences, duplicate.preferences.clone());
+        merged.notes = combine_text(merged.notes, duplicate.notes.clone());
+    }
+
+    let actor = audit_actor(session_token.as_deref());
+    let tx = conn.unchecked_transaction()?;
+    let mut stays_moved = 0;
+    for duplicate in &duplicates {
+        stays_moved += tx.execute(
+            "UPDATE customers SET profile_id = ?1 WHERE profile_id = ?2",
+            params![primary_id, duplicate.id],
+        )? as i64;
+        let before = snapshot(&tx, "guest_profile", duplicate.id)?;
+        tx.execute("DELETE FROM guest_profiles WHERE id = ?1", params![duplicate.id])?;
+        record_change(&tx, &actor, "guest_profile", duplicate.id, "delete", before, None)?;
+    }
+    let before = snapshot(&tx, "guest_profile", primary_id)?;
+    tx.execute(
+        "UPDATE guest_profiles
+         SET phone = ?1, phone_key = ?2, email = ?3, id_number = ?4, nationality = ?5,
+             preferences = ?6, notes = ?7, updated_at = ?8
+         WHERE id = ?9",
+        params![
+            merged.phone,
+            merged.phone.as_deref().and_then(phone_key),
+            merged.email,
+            merged.id_number,
+            merged.nationality,
+            merged.preferences,
+            merged.notes,
+            get_current_timestamp(),
+            primary_id,
+        ],
+    )?;
+    record_change(&tx, &actor, "guest_profile", primary_id, "update", before, snapshot(&tx, "guest_profile", primary_id)?)?;
+    tx.commit()?;
+
+    Ok(GuestMergeResult {
+        profile: load_profile(&conn, primary_id)?,
+        profiles_merged: duplicates.len() as i64,
+        stays_moved,
+    })
+}
@@ -0,0 +1,397 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::audit_actor;
+use crate::db::{get_db_connection, get_current_timestamp, run_blocking};
+use crate::errors::AppError;
+use crate::models::{
+    InventoryItem, LowStockIngredient, LowStockItem, MenuItemProfitability, RecipeIngredient, RecipeIngredientInput,
+    StockMovement,
+};
+use crate::validation::{validate_date_format, EMPTY_FIELD, MENU_ITEM_NOT_FOUND, NEGATIVE_AMOUNT};
+
+// Reasons staff can give for changing stock by hand; orders record 'order'
+const ADJUSTMENT_REASONS: &[&str] = &["restock", "waste", "correction"];
+
+const ITEM_COLUMNS: &str = "id, name, unit, quantity, low_stock_limit, quantity <= low_stock_limit, unit_cost, updated_at";
+
+fn map_item(row: &rusqlite::Row) -> rusqlite::Result<InventoryItem> {
+    Ok(InventoryItem {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        unit: row.get(2)?,
+        quantity: row.get(3)?,
+        low_stock_limit: row.get(4)?,
+        low_stock: row.get(5)?,
+        unit_cost: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+pub fn load_item(conn: &Connection, item_id: i64) -> Result<InventoryItem, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM inventory_items WHERE id = ?1", ITEM_COLUMNS),
+        params![item_id],
+        map_item,
+    )
+    .optional()?
+    .ok_or_else(|| format!("Inventory item #{} not found", item_id).into())
+}
+
+fn validate_quantity(value: f64, field: &str) -> Result<(), AppError> {
+    if !value.is_finite() || value < 0.0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, field, format!("{} must be >= 0", field)));
+    }
+    Ok(())
+}
+
+pub fn record_movement(
+    conn: &Connection,
+    item_id: i64,
+    change: f64,
+    reason: &str,
+    order_id: Option<i64>,
+    note: Option<&str>,
+    created_by: &str,
+) -> Result<(), AppError> {
+    let now = get_current_timestamp();
+    conn.execute(
+        "UPDATE inventory_items SET quantity = quantity + ?1, updated_at = ?2 WHERE id = ?3",
+        params![change, now, item_id],
+    )?;
+    conn.execute(
+        "INSERT INTO stock_movements (inventory_item_id, change, reason, order_id, note, created_by, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![item_id, change, reason, order_id, note, created_by, now],
+    )?;
+    Ok(())
+}
+
+/// Take the ingredients for `portions` of a menu item out of stock. Called
+/// inside the order's transaction. Stock may go below zero: a miscount
+/// shouldn't stop the kitchen taking orders, and the low-stock report shows it.
+/// Returns the ingredients this took from above their limit to at or below it.
+pub fn deduct_recipe_stock(
+    conn: &Connection,
+    order_id: i64,
+    menu_item_id: i64,
+    portions: i32,
+    created_by: &str,
+) -> Result<Vec<LowStockItem>, AppError> {
+    let recipe: Vec<(i64, f64)> = conn
+        .prepare_cached("SELECT inventory_item_id, quantity FROM menu_item_recipes WHERE menu_item_id = ?1")?
+        .query_map(params![menu_item_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut ran_low = Vec::new();
+    for (item_id, per_portion) in recipe {
+        let was_low = load_item(conn, item_id)?.low_stock;
+        record_movement(conn, item_id, -(per_portion * portions as f64), "order", Some(order_id), None, created_by)?;
+        let item = load_item(conn, item_id)?;
+        if item.low_stock && !was_low {
+            ran_low.push(LowStockItem {
+                kind: "ingredient".to_string(),
+                id: item.id,
+                name: item.name,
+                stock_quantity: item.quantity,
+                low_stock_limit: item.low_stock_limit,
+                unit: Some(item.unit),
+            });
+        }
+    }
+    Ok(ran_low)
+}
+
+#[command]
+pub fn add_inventory_item(
+    name: String,
+    unit: Option<String>,
+    quantity: Option<f64>,
+    low_stock_limit: Option<f64>,
+    unit_cost: Option<f64>,
+) -> Result<InventoryItem, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Name cannot be empty"));
+    }
+    let unit = unit.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).unwrap_or_else(|| "pcs".to_string());
+    let quantity = quantity.unwrap_or(0.0);
+    let low_stock_limit = low_stock_limit.unwrap_or(0.0);
+    let unit_cost = unit_cost.unwrap_or(0.0);
+    validate_quantity(quantity, "quantity")?;
+    validate_quantity(low_stock_limit, "low_stock_limit")?;
+    validate_quantity(unit_cost, "unit_cost")?;
+
+    let conn = get_db_connection()?;
+    let now = get_current_timestamp();
+    conn.execute(
+        "INSERT INTO inventory_items (name, unit, quantity, low_stock_limit, unit_cost, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+        params![name, unit, quantity, low_stock_limit, unit_cost, now],
+    )?;
+    load_item(&conn, conn.last_insert_rowid())
+}
+
+/// Rename an ingredient or change its unit, limit or cost. Stock itself only
+/// changes through adjust_stock, so every change is on record.
+#[command]
+pub fn update_inventory_item(
+    item_id: i64,
+    name: Option<String>,
+    unit: Option<String>,
+    low_stock_limit: Option<f64>,
+    unit_cost: Option<f64>,
+) -> Result<InventoryItem, AppError> {
+    let conn = get_db_connection()?;
+    let item = load_item(&conn, item_id)?;
+
+    let name = match name {
+        Some(name) if name.trim().is_empty() => {
+            return Err(AppError::invalid(EMPTY_FIELD, "name", "Name cannot be empty"));
+        }
+        Some(name) => name.trim().to_string(),
+        None => item.name,
+    };
+    let unit = unit.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).unwrap_or(item.unit);
+    let low_stock_limit = low_stock_limit.unwrap_or(item.low_stock_limit);
+    let unit_cost = unit_cost.unwrap_or(item.unit_cost);
+    validate_quantity(low_stock_limit, "low_stock_limit")?;
+    validate_quantity(unit_cost, "unit_cost")?;
+
+    conn.execute(
+        "UPDATE inventory_items SET name = ?1, unit = ?2, low_stock_limit = ?3, unit_cost = ?4, updated_at = ?5 WHERE id = ?6",
+        params![name, unit, low_stock_limit, unit_cost, get_current_timestamp(), item_id],
+    )?;
+    load_item(&conn, item_id)
+}
+
+/// Delete an ingredient; it's dropped from every recipe that used it.
+#[command]
+pub fn delete_inventory_item(item_id: i64) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let item = load_item(&conn, item_id)?;
+    conn.execute("DELETE FROM inventory_items WHERE id = ?1", params![item_id])?;
+    Ok(format!("{} deleted", item.name))
+}
+
+/// All ingredients by name, each flagged when at or below its limit.
+#[command]
+pub fn get_stock_levels() -> Result<Vec<InventoryItem>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM inventory_items ORDER BY name COLLATE NOCASE", ITEM_COLUMNS))?;
+    let items = stmt.query_map([], map_item)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(items)
+}
+
+/// Change stock by hand: a delivery (positive), waste (negative) or a
+/// correction after a count (either way). Taking stock out can't leave
+/// less than zero.
+#[command]
+pub fn adjust_stock(
+    inventory_item_id: i64,
+    change: f64,
+    reason: String,
+    note: Option<String>,
+    session_token: Option<String>,
+) -> Result<InventoryItem, AppError> {
+    let reason = reason.trim().to_lowercase();
+    if !ADJUSTMENT_REASONS.contains(&reason.as_str()) {
+        return Err(format!("reason must be one of: {}", ADJUSTMENT_REASONS.join(", ")).into());
+    }
+    if !change.is_finite() || change == 0.0 {
+        return Err("Change must be a non-zero number".into());
+    }
+
+    let conn = get_db_connection()?;
+    let item = load_item(&conn, inventory_item_id)?;
+    if change < 0.0 && item.quantity + change < 0.0 {
+        return Err(AppError::invalid(
+            NEGATIVE_AMOUNT,
+            "change",
+            format!("Only {} {} of {} in stock", item.quantity, item.unit, item.name),
+        ));
+    }
+
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    let tx = conn.unchecked_transaction()?;
+    record_movement(
+        &tx,
+        inventory_item_id,
+        change,
+        &reason,
+        None,
+        note.as_deref(),
+        &audit_actor(session_token.as_deref()),
+    )?;
+    tx.commit()?;
+
+    load_item(&conn, inventory_item_id)
+}
+
+/// Stock changes, newest first, for one ingredient or all of them.
+#[command]
+pub fn get_stock_movements(inventory_item_id: Option<i64>, limit: Option<i64>) -> Result<Vec<StockMovement>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.inventory_item_id, i.name, m.change, m.reason, m.order_id, m.note, m.created_by, m.created_at
+         FROM stock_movements m JOIN inventory_items i ON i.id = m.inventory_item_id
+         WHERE ?1 IS NULL OR m.inventory_item_id = ?1
+         ORDER BY m.created_at DESC, m.id DESC
+         LIMIT ?2",
+    )?;
+    let movements = stmt
+        .query_map(params![inventory_item_id, limit.unwrap_or(200).clamp(1, 1000)], |row| {
+            Ok(StockMovement {
+                id: row.get(0)?,
+                inventory_item_id: row.get(1)?,
+                item_name: row.get(2)?,
+                change: row.get(3)?,
+                reason: row.get(4)?,
+                order_id: row.get(5)?,
+                note: row.get(6)?,
+                created_by: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(movements)
+}
+
+/// Replace what one portion of a menu item uses. An empty list removes the
+/// recipe, so ordering the item no longer touches stock.
+#[command]
+pub fn set_menu_item_recipe(menu_item_id: i64, ingredients: Vec<RecipeIngredientInput>) -> Result<Vec<RecipeIngredient>, AppError> {
+    for ingredient in &ingredients {
+        if !ingredient.quantity.is_finite() || ingredient.quantity <= 0.0 {
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "quantity", "Each ingredient needs a quantity above zero"));
+        }
+        if ingredients.iter().filter(|i| i.inventory_item_id == ingredient.inventory_item_id).count() > 1 {
+            return Err(format!("Ingredient #{} is listed more than once", ingredient.inventory_item_id).into());
+        }
+    }
+
+    let conn = get_db_connection()?;
+    let menu_item_exists: bool = conn
+        .query_row("SELECT 1 FROM menu_items WHERE id = ?1", params![menu_item_id], |_| Ok(true))
+        .optional()?
+        .unwrap_or(false);
+    if !menu_item_exists {
+        return Err(AppError::coded(MENU_ITEM_NOT_FOUND, format!("Menu item #{} not found", menu_item_id)));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM menu_item_recipes WHERE menu_item_id = ?1", params![menu_item_id])?;
+    for ingredient in &ingredients {
+        load_item(&tx, ingredient.inventory_item_id)?;
+        tx.execute(
+            "INSERT INTO menu_item_recipes (menu_item_id, inventory_item_id, quantity) VALUES (?1, ?2, ?3)",
+            params![menu_item_id, ingredient.inventory_item_id, ingredient.quantity],
+        )?;
+    }
+    tx.commit()?;
+
+    get_menu_item_recipe(menu_item_id)
+}
+
+#[command]
+pub fn get_menu_item_recipe(menu_item_id: i64) -> Result<Vec<RecipeIngredient>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT r.inventory_item_id, i.name, i.unit, r.quantity
+         FROM menu_item_recipes r JOIN inventory_items i ON i.id = r.inventory_item_id
+         WHERE r.menu_item_id = ?1
+         ORDER BY i.name COLLATE NOCASE",
+    )?;
+    let ingredients = stmt
+        .query_map(params![menu_item_id], |row| {
+            Ok(RecipeIngredient {
+                inventory_item_id: row.get(0)?,
+                name: row.get(1)?,
+                unit: row.get(2)?,
+                quantity: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ingredients)
+}
+
+/// Ingredients at or below their limit, most short first, with the menu
+/// items that will run out with them.
+#[command]
+pub async fn low_stock_report() -> Result<Vec<LowStockIngredient>, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT i.id, i.name, i.unit, i.quantity, i.low_stock_limit,
+                    (SELECT GROUP_CONCAT(m.name, char(31)) FROM menu_item_recipes r
+                     JOIN menu_items m ON m.id = r.menu_item_id
+                     WHERE r.inventory_item_id = i.id)
+             FROM inventory_items i
+             WHERE i.quantity <= i.low_stock_limit
+             ORDER BY i.low_stock_limit - i.quantity DESC, i.name COLLATE NOCASE",
+        )?;
+        let report = stmt
+            .query_map([], |row| {
+                let quantity: f64 = row.get(3)?;
+                let low_stock_limit: f64 = row.get(4)?;
+                let used_in: Option<String> = row.get(5)?;
+                Ok(LowStockIngredient {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    unit: row.get(2)?,
+                    quantity,
+                    low_stock_limit,
+                    shortfall: (low_stock_limit - quantity).max(0.0),
+                    used_in: used_in.map(|names| names.split('\u{1f}').map(str::to_string).collect()).unwrap_or_default(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(report)
+    })
+    .await
+}
+
+/// Revenue against ingredient cost for each menu item sold between the two
+/// dates (inclusive), most profitable first. Cost uses each item's current
+/// recipe and ingredient costs; voided lines are left out.
+#[command]
+pub fn menu_item_profitability(start_date: String, end_date: String) -> Result<Vec<MenuItemProfitability>, AppError> {
+    validate_date_format(&start_date)?;
+    validate_date_format(&end_date)?;
+
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.name, SUM(si.quantity), SUM(si.line_total),
+                (SELECT COALESCE(SUM(r.quantity * i.unit_cost), 0.0) FROM menu_item_recipes r
+                 JOIN inventory_items i ON i.id = r.inventory_item_id
+                 WHERE r.menu_item_id = m.id),
+                EXISTS (SELECT 1 FROM menu_item_recipes r WHERE r.menu_item_id = m.id)
+         FROM sale_items si
+         JOIN sales s ON s.id = si.order_id
+         JOIN menu_items m ON m.id = si.menu_item_id
+         WHERE date(s.created_at) >= ?1 AND date(s.created_at) <= ?2 AND s.deleted_at IS NULL
+           AND NOT EXISTS (SELECT 1 FROM voided_items v WHERE v.sale_item_id = si.id)
+         GROUP BY m.id",
+    )?;
+    let mut report = stmt
+        .query_map(params![start_date, end_date], |row| {
+            let quantity_sold: i64 = row.get(2)?;
+            let revenue: f64 = row.get(3)?;
+            let portion_cost: f64 = row.get(4)?;
+            let cost_of_goods = portion_cost * quantity_sold as f64;
+            let gross_profit = revenue - cost_of_goods;
+            Ok(MenuItemProfitability {
+                menu_item_id: row.get(0)?,
+                name: row.get(1)?,
+                quantity_sold,
+                revenue,
+                portion_cost,
+                cost_of_goods,
+                gross_profit,
+                margin_percent: (revenue > 0.0).then(|| gross_profit / revenue * 100.0),
+                costed: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    report.sort_by(|a, b| b.gross_profit.total_cmp(&a.gross_profit).then_with(|| a.name.cmp(&b.name)));
+    Ok(report)
+}
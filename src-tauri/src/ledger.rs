@@ -0,0 +1,798 @@
+use tauri::command;
+use std::collections::HashMap;
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+use crate::audit::audit_actor;
+use crate::db::{get_db_connection, log_audit_event};
+use crate::errors::AppError;
+use crate::models::{
+    JournalEntry, JournalLine, LedgerAccount, NewJournalLine, ProfitAndLoss, ProfitAndLossLine, TrialBalance,
+    TrialBalanceLine,
+};
+use crate::simple_commands::to_cents;
+use crate::validation::{
+    validate_date_format, EMPTY_FIELD, JOURNAL_UNBALANCED, LEDGER_ACCOUNT_CODE_EXISTS, LEDGER_ACCOUNT_NOT_FOUND,
+    NEGATIVE_AMOUNT,
+};
+
+const ACCOUNT_TYPES: &[&str] = &["asset", "liability", "equity", "revenue", "expense"];
+
+// (system_key, code, name, type). Payment methods map to the asset account of
+// the same key; expense categories get their own 5xxx account on first use.
+const SYSTEM_ACCOUNTS: &[(&str, &str, &str, &str)] = &[
+    ("cash", "1000", "Cash on Hand", "asset"),
+    ("card", "1010", "Card Settlements", "asset"),
+    ("bank_transfer", "1020", "Bank Account", "asset"),
+    ("accounts_receivable", "1100", "Accounts Receivable", "asset"),
+    ("guest_deposits", "2000", "Guest Deposits", "liability"),
+    ("tax_payable", "2100", "Tax Payable", "liability"),
+    ("owner_equity", "3000", "Owner's Equity", "equity"),
+    ("owner_drawings", "3100", "Owner Drawings", "equity"),
+    ("room_revenue", "4000", "Room Revenue", "revenue"),
+    ("fnb_revenue", "4100", "Food & Beverage Revenue", "revenue"),
+    ("service_charge_revenue", "4200", "Service Charge Revenue", "revenue"),
+];
+
+const FIRST_EXPENSE_CODE: i64 = 5000;
+
+/// What a source row should have posted: lines are (account key, cents) with
+/// debits positive and credits negative.
+struct Posting {
+    source_type: &'static str,
+    source_id: i64,
+    date: String,
+    memo: String,
+    lines: Vec<(String, i64)>,
+}
+
+impl Posting {
+    fn new(source_type: &'static str, source_id: i64, date: &str, memo: String, debit: String, credit: String, cents: i64) -> Self {
+        Posting {
+            source_type,
+            source_id,
+            date: date.chars().take(10).collect(),
+            memo,
+            lines: vec![(debit, cents), (credit, -cents)],
+        }
+    }
+
+    // Memo changes alone don't need a correcting entry
+    fn fingerprint(&self) -> String {
+        let lines: Vec<String> = self.lines.iter().map(|(key, cents)| format!("{}={}", key, cents)).collect();
+        format!("{}|{}", self.date, lines.join(";"))
+    }
+}
+
+fn ensure_system_accounts(conn: &Connection) -> Result<(), AppError> {
+    for (key, code, name, account_type) in SYSTEM_ACCOUNTS {
+        conn.execute(
+            "INSERT OR IGNORE INTO ledger_accounts (code, name, account_type, system_key) VALUES (?1, ?2, ?3, ?4)",
+            params![code, name, account_type, key],
+        )?;
+    }
+    Ok(())
+}
+
+fn expense_account_key(category: &str) -> String {
+    format!("expense:{}", category.trim().to_lowercase())
+}
+
+/// Account id for a system key, opening an expense account for a category
+/// that hasn't been posted to before.
+fn account_for_key(conn: &Connection, key: &str, cache: &mut HashMap<String, i64>) -> Result<i64, AppError> {
+    if let Some(id) = cache.get(key) {
+        return Ok(*id);
+    }
+    let existing: Option<i64> = conn
+        .query_row("SELECT id FROM ledger_accounts WHERE system_key = ?1", params![key], |row| row.get(0))
+        .optional()?;
+    let id = match (existing, key.strip_prefix("expense:")) {
+        (Some(id), _) => id,
+        (None, Some(_)) => {
+            let category: String = conn
+                .query_row(
+                    "SELECT name FROM expense_categories WHERE name = ?1 COLLATE NOCASE
+                     UNION ALL SELECT TRIM(category) FROM expenses WHERE TRIM(category) = ?1 COLLATE NOCASE
+                     LIMIT 1",
+                    params![&key["expense:".len()..]],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .unwrap_or_else(|| key["expense:".len()..].to_string());
+            let last: Option<i64> = conn.query_row(
+                "SELECT MAX(CAST(code AS INTEGER)) FROM ledger_accounts
+                 WHERE account_type = 'expense' AND code GLOB '5*' AND code NOT GLOB '*[^0-9]*'",
+                [],
+                |row| row.get(0),
+            )?;
+            let code = last.map(|c| c + 10).unwrap_or(FIRST_EXPENSE_CODE);
+            conn.execute(
+                "INSERT INTO ledger_accounts (code, name, account_type, system_key) VALUES (?1, ?2, 'expense', ?3)",
+                params![code.to_string(), category, key],
+            )?;
+            conn.last_insert_rowid()
+        }
+        (None, None) => return Err(format!("Ledger account '{}' is missing", key).into()),
+    };
+    cache.insert(key.to_string(), id);
+    Ok(id)
+}
+
+/// Everything checkouts, order payments, deposits, company accounts and
+/// expenses should have posted. Money is booked when it changes hands: order
+/// payments are food & beverage revenue, checkout payments room revenue, and
+/// deposits sit in Guest Deposits until the guest checks out. A stay put on a
+/// company account is room revenue owed in Accounts Receivable until the
+/// company pays. The food and service charge on a checkout bill are then moved
+/// out of room revenue into their own accounts. Tax charged on a stay bill is moved out of room revenue into
+/// Tax Payable, and tax on a walk-in order out of food & beverage revenue.
+/// Expenses are assumed paid in cash.
+fn expected_postings(conn: &Connection) -> Result<Vec<Posting>, AppError> {
+    let mut postings = Vec::new();
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, order_id, guest_id, amount, method, paid_at, is_deposit, refund_of FROM payments WHERE {} ORDER BY id",
+        crate::trash::outside_trash("payments.order_id")
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<i64>>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+            row.get::<_, f64>(3)?,
+            row.get::<_, String>(4)?,
+            row.get::<_, String>(5)?,
+            row.get::<_, bool>(6)?,
+            row.get::<_, Option<i64>>(7)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, order_id, guest_id, amount, method, paid_at, is_deposit, refund_of) = row?;
+        let cents = to_cents(amount);
+        if cents == 0 {
+            continue;
+        }
+        let (counter, what) = match (is_deposit, order_id) {
+            (true, _) => ("guest_deposits", format!("Deposit, guest #{}", guest_id.unwrap_or_default())),
+            (false, Some(order_id)) => ("fnb_revenue", format!("Order #{}", order_id)),
+            (false, None) => ("room_revenue", format!("Checkout, guest #{}", guest_id.unwrap_or_default())),
+        };
+        let memo = match refund_of {
+            Some(original) => format!("{} refund of payment #{} ({})", what, original, method),
+            None => format!("{} payment ({})", what, method),
+        };
+        postings.push(Posting::new("payment", id, &paid_at, memo, method, counter.to_string(), cents));
+    }
+
+    // Deposits become revenue once the guest has checked out
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.name, COALESCE(c.check_out, date(c.updated_at)), SUM(p.amount)
+         FROM customers c JOIN payments p ON p.guest_id = c.id AND p.is_deposit = 1
+         WHERE c.status = 'checked_out'
+         GROUP BY c.id ORDER BY c.id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, f64>(3)?))
+    })?;
+    for row in rows {
+        let (guest_id, name, check_out, deposits) = row?;
+        let cents = to_cents(deposits);
+        if cents != 0 {
+            let memo = format!("Deposits applied at checkout, {} (guest #{})", name, guest_id);
+            postings.push(Posting::new(
+                "deposit_applied",
+                guest_id,
+                &check_out,
+                memo,
+                "guest_deposits".to_string(),
+                "room_revenue".to_string(),
+                cents,
+            ));
+        }
+    }
+
+    // The checkout bill's food and service charge, as stored when the guest checked out
+    let mut stmt = conn.prepare(
+        "SELECT id, name, COALESCE(check_out, date(updated_at)), COALESCE(checkout_food, 0), COALESCE(checkout_service_charge, 0)
+         FROM customers
+         WHERE status = 'checked_out' AND (checkout_food <> 0 OR checkout_service_charge <> 0)
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, f64>(3)?,
+            row.get::<_, f64>(4)?,
+        ))
+    })?;
+    for row in rows {
+        let (guest_id, name, check_out, food, service_charge) = row?;
+        let (food, service_charge) = (to_cents(food), to_cents(service_charge));
+        let lines = [
+            ("room_revenue", food + service_charge),
+            ("fnb_revenue", -food),
+            ("service_charge_revenue", -service_charge),
+        ];
+        postings.push(Posting {
+            source_type: "checkout_split",
+            source_id: guest_id,
+            date: check_out.chars().take(10).collect(),
+            memo: format!("Food and service charge on the checkout bill, {} (guest #{})", name, guest_id),
+            lines: lines.iter().filter(|(_, cents)| *cents != 0).map(|(key, cents)| (key.to_string(), *cents)).collect(),
+        });
+    }
+
+    // Reversals of a reopened checkout are negative and post the other way round
+    let mut stmt = conn.prepare(
+        "SELECT ch.id, co.name, ch.description, ch.amount, ch.posted_on
+         FROM company_charges ch JOIN companies co ON co.id = ch.company_id
+         ORDER BY ch.id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, f64>(3)?,
+            row.get::<_, String>(4)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, company, description, amount, posted_on) = row?;
+        let cents = to_cents(amount);
+        if cents != 0 {
+            let memo = format!("{}, charged to {}", description, company);
+            postings.push(Posting::new(
+                "company_charge",
+                id,
+                &posted_on,
+                memo,
+                "accounts_receivable".to_string(),
+                "room_revenue".to_string(),
+                cents,
+            ));
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT p.id, co.name, p.amount, p.method, p.paid_on
+         FROM company_payments p JOIN companies co ON co.id = p.company_id
+         ORDER BY p.id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, f64>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, company, amount, method, paid_on) = row?;
+        let cents = to_cents(amount);
+        if cents != 0 {
+            let memo = format!("Payment from {} ({})", company, method);
+            postings.push(Posting::new("company_payment", id, &paid_on, memo, method, "accounts_receivable".to_string(), cents));
+        }
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, guest_id, order_id, name, percent, amount, charged_on FROM tax_charges WHERE {} ORDER BY id",
+        crate::trash::outside_trash("tax_charges.order_id")
+    ))?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Option<i64>>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, f64>(4)?,
+            row.get::<_, f64>(5)?,
+            row.get::<_, String>(6)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, guest_id, order_id, name, percent, amount, charged_on) = row?;
+        let cents = to_cents(amount);
+        if cents == 0 {
+            continue;
+        }
+        // A walk-in order's tax came in with its payment, as food & beverage revenue
+        let (revenue, memo) = match order_id {
+            Some(order_id) => ("fnb_revenue", format!("{} {}% on order #{}", name, percent, order_id)),
+            None => ("room_revenue", format!("{} {}% on checkout, guest #{}", name, percent, guest_id.unwrap_or_default())),
+        };
+        postings.push(Posting::new("tax_charge", id, &charged_on, memo, revenue.to_string(), "tax_payable".to_string(), cents));
+    }
+
+    let mut stmt = conn.prepare("SELECT id, date, category, description, amount, is_drawing FROM expenses WHERE deleted_at IS NULL ORDER BY id")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, f64>(4)?,
+            row.get::<_, bool>(5)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, date, category, description, amount, is_drawing) = row?;
+        let cents = to_cents(amount);
+        if cents == 0 {
+            continue;
+        }
+        let debit = if is_drawing { "owner_drawings".to_string() } else { expense_account_key(&category) };
+        let memo = match description.filter(|d| !d.trim().is_empty()) {
+            Some(description) => format!("{}: {}", category.trim(), description.trim()),
+            None => category.trim().to_string(),
+        };
+        postings.push(Posting::new("expense", id, &date, memo, debit, "cash".to_string(), cents));
+    }
+
+    Ok(postings)
+}
+
+
+/// Write an entry and its lines; `lines` are (account id, cents) with debits
+/// positive and credits negative.
+fn insert_entry(
+    tx: &Connection,
+    date: &str,
+    memo: &str,
+    source: Option<(&str, i64, &str)>,
+    reversal_of: Option<i64>,
+    lines: &[(i64, i64)],
+) -> Result<i64, AppError> {
+    let (source_type, source_id, fingerprint) = match source {
+        Some((source_type, source_id, fingerprint)) => (Some(source_type), Some(source_id), Some(fingerprint)),
+        None => (None, None, None),
+    };
+    tx.execute(
+        "INSERT INTO journal_entries (entry_date, memo, source_type, source_id, fingerprint, reversal_of)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![date, memo, source_type, source_id, fingerprint, reversal_of],
+    )?;
+    let entry_id = tx.last_insert_rowid();
+    for (account_id, cents) in lines {
+        tx.execute(
+            "INSERT INTO journal_lines (entry_id, account_id, debit, credit) VALUES (?1, ?2, ?3, ?4)",
+            params![entry_id, account_id, (*cents).max(0) as f64 / 100.0, (-cents).max(0) as f64 / 100.0],
+        )?;
+    }
+    Ok(entry_id)
+}
+
+/// Post the mirror image of an entry and mark the original as reversed. The
+/// reversal is dated today, or on the original's date if that is later, so
+/// closed periods aren't rewritten.
+fn reverse_entry(tx: &Connection, entry_id: i64, memo: &str) -> Result<i64, AppError> {
+    let (entry_date, source_type, source_id, fingerprint): (String, Option<String>, Option<i64>, Option<String>) = tx.query_row(
+        "SELECT entry_date, source_type, source_id, fingerprint FROM journal_entries WHERE id = ?1",
+        params![entry_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )?;
+    let mut stmt = tx.prepare("SELECT account_id, debit, credit FROM journal_lines WHERE entry_id = ?1 ORDER BY id")?;
+    let lines = stmt
+        .query_map(params![entry_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?, row.get::<_, f64>(2)?))
+        })?
+        .map(|line| line.map(|(account_id, debit, credit)| (account_id, to_cents(credit) - to_cents(debit))))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let today = chrono::Local::now().date_naive().to_string();
+    let date = if today > entry_date { today } else { entry_date };
+    let source = match (&source_type, source_id) {
+        (Some(source_type), Some(source_id)) => Some((source_type.as_str(), source_id, fingerprint.as_deref().unwrap_or(""))),
+        _ => None,
+    };
+    let reversal_id = insert_entry(tx, &date, memo, source, Some(entry_id), &lines)?;
+    tx.execute("UPDATE journal_entries SET reversed_by = ?1 WHERE id = ?2", params![reversal_id, entry_id])?;
+    Ok(reversal_id)
+}
+
+/// Bring the ledger in line with payments, checkouts, company accounts, taxes
+/// and expenses. Source rows without an entry are posted; when one was edited
+/// or deleted its entry is reversed and, if it still exists, posted again.
+/// Manual entries are left alone. Returns the number of entries written.
+pub fn post_ledger(conn: &Connection) -> Result<usize, AppError> {
+    // Immediate, so two windows syncing at once can't both post the same row
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+    ensure_system_accounts(&tx)?;
+
+    let mut posted: HashMap<(String, i64), (i64, String)> = HashMap::new();
+    {
+        let mut stmt = tx.prepare(
+            "SELECT id, source_type, source_id, fingerprint FROM journal_entries
+             WHERE source_type IS NOT NULL AND reversal_of IS NULL AND reversed_by IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(((row.get::<_, String>(1)?, row.get::<_, i64>(2)?), (row.get::<_, i64>(0)?, row.get::<_, String>(3)?)))
+        })?;
+        for row in rows {
+            let (source, entry) = row?;
+            posted.insert(source, entry);
+        }
+    }
+
+    let mut accounts = HashMap::new();
+    let mut written = 0;
+    for posting in expected_postings(&tx)? {
+        let fingerprint = posting.fingerprint();
+        match posted.remove(&(posting.source_type.to_string(), posting.source_id)) {
+            Some((_, current)) if current == fingerprint => continue,
+            Some((entry_id, _)) => {
+                reverse_entry(&tx, entry_id, &format!("Correction: {}", posting.memo))?;
+                written += 1;
+            }
+            None => {}
+        }
+        let lines = posting
+            .lines
+            .iter()
+            .map(|(key, cents)| Ok((account_for_key(&tx, key, &mut accounts)?, *cents)))
+            .collect::<Result<Vec<_>, AppError>>()?;
+        insert_entry(
+            &tx,
+            &posting.date,
+            &posting.memo,
+            Some((posting.source_type, posting.source_id, &fingerprint)),
+            None,
+            &lines,
+        )?;
+        written += 1;
+    }
+
+    // The source row is gone
+    for ((source_type, source_id), (entry_id, _)) in posted {
+        reverse_entry(&tx, entry_id, &format!("Reversal: {} #{} was deleted", source_type, source_id))?;
+        written += 1;
+    }
+
+    tx.commit()?;
+    Ok(written)
+}
+
+fn map_account(row: &rusqlite::Row) -> rusqlite::Result<LedgerAccount> {
+    Ok(LedgerAccount {
+        id: row.get(0)?,
+        code: row.get(1)?,
+        name: row.get(2)?,
+        account_type: row.get(3)?,
+        is_system: row.get::<_, Option<String>>(4)?.is_some(),
+    })
+}
+
+/// The chart of accounts, by code.
+#[command]
+pub fn get_ledger_accounts() -> Result<Vec<LedgerAccount>, AppError> {
+    let conn = get_db_connection()?;
+    post_ledger(&conn)?;
+    let mut stmt = conn.prepare("SELECT id, code, name, account_type, system_key FROM ledger_accounts ORDER BY code")?;
+    let accounts = stmt.query_map([], map_account)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(accounts)
+}
+
+/// Add an account for manual entries, e.g. a loan or fixed assets.
+#[command]
+pub fn add_ledger_account(code: String, name: String, account_type: String) -> Result<LedgerAccount, AppError> {
+    let code = code.trim();
+    let name = name.trim();
+    let account_type = account_type.trim().to_lowercase();
+    if code.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "code", "Account code cannot be empty"));
+    }
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Account name cannot be empty"));
+    }
+    if !ACCOUNT_TYPES.contains(&account_type.as_str()) {
+        return Err(format!("Account type must be one of: {}", ACCOUNT_TYPES.join(", ")).into());
+    }
+
+    let conn = get_db_connection()?;
+    ensure_system_accounts(&conn)?;
+    let taken: bool = conn
+        .query_row("SELECT 1 FROM ledger_accounts WHERE code = ?1", params![code], |_| Ok(true))
+        .optional()?
+        .unwrap_or(false);
+    if taken {
+        return Err(AppError::invalid(LEDGER_ACCOUNT_CODE_EXISTS, "code", format!("Account code {} is already in use", code)));
+    }
+    conn.execute(
+        "INSERT INTO ledger_accounts (code, name, account_type) VALUES (?1, ?2, ?3)",
+        params![code, name, account_type],
+    )?;
+    Ok(conn.query_row(
+        "SELECT id, code, name, account_type, system_key FROM ledger_accounts WHERE id = ?1",
+        params![conn.last_insert_rowid()],
+        map_account,
+    )?)
+}
+
+pub(crate) fn load_entries(conn: &Connection, condition: &str, values: &[&dyn rusqlite::ToSql]) -> Result<Vec<JournalEntry>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, entry_date, memo, source_type, source_id, reversal_of, reversed_by FROM journal_entries
+         WHERE {} ORDER BY entry_date, id",
+        condition
+    ))?;
+    let mut entries = stmt
+        .query_map(values, |row| {
+            Ok(JournalEntry {
+                id: row.get(0)?,
+                entry_date: row.get(1)?,
+                memo: row.get(2)?,
+                source_type: row.get(3)?,
+                source_id: row.get(4)?,
+                reversal_of: row.get(5)?,
+                reversed_by: row.get(6)?,
+                lines: Vec::new(),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT l.account_id, a.code, a.name, l.debit, l.credit FROM journal_lines l
+         JOIN ledger_accounts a ON a.id = l.account_id
+         WHERE l.entry_id = ?1 ORDER BY l.debit = 0, l.id",
+    )?;
+    for entry in &mut entries {
+        entry.lines = stmt
+            .query_map(params![entry.id], |row| {
+                Ok(JournalLine {
+                    account_id: row.get(0)?,
+                    account_code: row.get(1)?,
+                    account_name: row.get(2)?,
+                    debit: row.get(3)?,
+                    credit: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+    Ok(entries)
+}
+
+/// The journal between two dates (inclusive), oldest first.
+#[command]
+pub fn get_journal_entries(start_date: String, end_date: String) -> Result<Vec<JournalEntry>, AppError> {
+    validate_date_format(&start_date)?;
+    validate_date_format(&end_date)?;
+    let conn = get_db_connection()?;
+    post_ledger(&conn)?;
+    load_entries(&conn, "entry_date BETWEEN ?1 AND ?2", &[&start_date, &end_date])
+}
+
+/// Record a manual entry such as opening balances or an accountant's
+/// adjustment. Every line is either a debit or a credit and the two sides
+/// must balance.
+#[command]
+pub fn add_journal_entry(
+    entry_date: String,
+    memo: String,
+    lines: Vec<NewJournalLine>,
+    session_token: Option<String>,
+) -> Result<JournalEntry, AppError> {
+    validate_date_format(&entry_date)?;
+    let memo = memo.trim();
+    if memo.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "memo", "Describe what the entry is for"));
+    }
+    if lines.len() < 2 {
+        return Err(AppError::invalid(JOURNAL_UNBALANCED, "lines", "An entry needs at least two lines"));
+    }
+
+    let mut amounts = Vec::with_capacity(lines.len());
+    for line in &lines {
+        let debit = to_cents(line.debit.unwrap_or(0.0));
+        let credit = to_cents(line.credit.unwrap_or(0.0));
+        if debit < 0 || credit < 0 {
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "lines", "Debits and credits can't be negative"));
+        }
+        if (debit == 0) == (credit == 0) {
+            return Err(AppError::invalid(JOURNAL_UNBALANCED, "lines", "Each line is either a debit or a credit"));
+        }
+        amounts.push((line.account_id, debit - credit));
+    }
+    let difference: i64 = amounts.iter().map(|(_, cents)| cents).sum();
+    if difference != 0 {
+        return Err(AppError::invalid(
+            JOURNAL_UNBALANCED,
+            "lines",
+            format!("Debits and credits differ by {:.2}", difference.abs() as f64 / 100.0),
+        ));
+    }
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    for (account_id, _) in &amounts {
+        let exists: bool = tx
+            .query_row("SELECT 1 FROM ledger_accounts WHERE id = ?1", params![account_id], |_| Ok(true))
+            .optional()?
+            .unwrap_or(false);
+        if !exists {
+            return Err(AppError::invalid(LEDGER_ACCOUNT_NOT_FOUND, "lines", format!("Ledger account #{} not found", account_id)));
+        }
+    }
+    let entry_id = insert_entry(&tx, &entry_date, memo, None, None, &amounts)?;
+    log_audit_event(
+        &tx,
+        &audit_actor(session_token.as_deref()),
+        "journal_entry_added",
+        &format!("#{} on {}: {}", entry_id, entry_date, memo),
+    )?;
+    tx.commit()?;
+
+    load_entries(&conn, "id = ?1", &[&entry_id])?.pop().ok_or_else(|| "Journal entry was not saved".into())
+}
+
+/// Undo a manual entry with a reversing entry. Posted entries follow their
+/// source rows and are corrected by editing those instead.
+#[command]
+pub fn reverse_journal_entry(entry_id: i64, session_token: Option<String>) -> Result<JournalEntry, AppError> {
+    let conn = get_db_connection()?;
+    let (source_type, memo, reversal_of, reversed_by): (Option<String>, String, Option<i64>, Option<i64>) = conn
+        .query_row(
+            "SELECT source_type, memo, reversal_of, reversed_by FROM journal_entries WHERE id = ?1",
+            params![entry_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?
+        .ok_or_else(|| format!("Journal entry #{} not found", entry_id))?;
+    if source_type.is_some() {
+        return Err("Posted entries follow their payment or expense; change that instead".into());
+    }
+    if reversal_of.is_some() || reversed_by.is_some() {
+        return Err(format!("Journal entry #{} is already reversed", entry_id).into());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let reversal_id = reverse_entry(&tx, entry_id, &format!("Reversal of #{}: {}", entry_id, memo))?;
+    log_audit_event(&tx, &audit_actor(session_token.as_deref()), "journal_entry_reversed", &format!("#{} reversed by #{}", entry_id, reversal_id))?;
+    tx.commit()?;
+
+    load_entries(&conn, "id = ?1", &[&reversal_id])?.pop().ok_or_else(|| "Reversal was not saved".into())
+}
+
+/// Each account's net balance as of a date (inclusive, default today). Debit
+/// and credit totals match whenever the books balance.
+pub fn build_trial_balance(conn: &Connection, as_of: &str) -> Result<TrialBalance, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT a.id, a.code, a.name, a.account_type, SUM(l.debit), SUM(l.credit)
+         FROM ledger_accounts a
+         JOIN journal_lines l ON l.account_id = a.id
+         JOIN journal_entries e ON e.id = l.entry_id
+         WHERE e.entry_date <= ?1
+         GROUP BY a.id ORDER BY a.code",
+    )?;
+    let rows = stmt.query_map(params![as_of], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, f64>(4)?,
+            row.get::<_, f64>(5)?,
+        ))
+    })?;
+
+    let mut lines = Vec::new();
+    let (mut total_debit, mut total_credit) = (0i64, 0i64);
+    for row in rows {
+        let (account_id, code, name, account_type, debit, credit) = row?;
+        let balance = to_cents(debit) - to_cents(credit);
+        if balance == 0 {
+            continue;
+        }
+        total_debit += balance.max(0);
+        total_credit += (-balance).max(0);
+        lines.push(TrialBalanceLine {
+            account_id,
+            code,
+            name,
+            account_type,
+            debit: balance.max(0) as f64 / 100.0,
+            credit: (-balance).max(0) as f64 / 100.0,
+        });
+    }
+
+    Ok(TrialBalance {
+        as_of: as_of.to_string(),
+        lines,
+        total_debit: total_debit as f64 / 100.0,
+        total_credit: total_credit as f64 / 100.0,
+    })
+}
+
+#[command]
+pub fn trial_balance(as_of: Option<String>) -> Result<TrialBalance, AppError> {
+    let as_of = as_of.unwrap_or_else(|| chrono::Local::now().date_naive().to_string());
+    validate_date_format(&as_of)?;
+    let conn = get_db_connection()?;
+    post_ledger(&conn)?;
+    build_trial_balance(&conn, &as_of)
+}
+
+/// Revenue less expenses for the period (inclusive). Owner drawings are equity
+/// and don't reduce profit.
+pub fn build_profit_and_loss(conn: &Connection, start_date: &str, end_date: &str) -> Result<ProfitAndLoss, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT a.code, a.name, a.account_type, SUM(l.debit), SUM(l.credit)
+         FROM ledger_accounts a
+         JOIN journal_lines l ON l.account_id = a.id
+         JOIN journal_entries e ON e.id = l.entry_id
+         WHERE a.account_type IN ('revenue', 'expense') AND e.entry_date BETWEEN ?1 AND ?2
+         GROUP BY a.id ORDER BY a.code",
+    )?;
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, f64>(3)?,
+            row.get::<_, f64>(4)?,
+        ))
+    })?;
+
+    let (mut revenue, mut expenses) = (Vec::new(), Vec::new());
+    let (mut total_revenue, mut total_expenses) = (0i64, 0i64);
+    for row in rows {
+        let (code, name, account_type, debit, credit) = row?;
+        let net = to_cents(debit) - to_cents(credit);
+        if net == 0 {
+            continue;
+        }
+        if account_type == "revenue" {
+            total_revenue -= net;
+            revenue.push(ProfitAndLossLine { code, name, amount: -net as f64 / 100.0 });
+        } else {
+            total_expenses += net;
+            expenses.push(ProfitAndLossLine { code, name, amount: net as f64 / 100.0 });
+        }
+    }
+
+    Ok(ProfitAndLoss {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        revenue,
+        expenses,
+        total_revenue: total_revenue as f64 / 100.0,
+        total_expenses: total_expenses as f64 / 100.0,
+        net_income: (total_revenue - total_expenses) as f64 / 100.0,
+    })
+}
+
+#[command]
+pub fn profit_and_loss(start_date: String, end_date: String) -> Result<ProfitAndLoss, AppError> {
+    validate_date_format(&start_date)?;
+    validate_date_format(&end_date)?;
+    if start_date > end_date {
+        return Err(AppError::invalid(crate::validation::INVALID_DATE_FORMAT, "end_date", "End date is before the start date"));
+    }
+    let conn = get_db_connection()?;
+    post_ledger(&conn)?;
+    build_profit_and_loss(&conn, &start_date, &end_date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{checkout_guest, insert_food_order, set_tax_enabled};
+    use crate::test_support::{check_in, order_item, same_amount, TestDb};
+
+    #[test]
+    fn food_charged_to_the_room_is_booked_as_food_and_service_charge() {
+        let _db = TestDb::new();
+        set_tax_enabled(false).unwrap();
+        crate::service_charge::set_service_charge_percent("hotel".to_string(), 10.0).unwrap();
+        let guest_id = check_in("Diner", None);
+        insert_food_order(Some(guest_id), "guest".to_string(), None, vec![order_item("Dinner", 20.0, 1)], None, None, None).unwrap();
+        let totals = checkout_guest(guest_id, None, None, Some("cash".to_string()), None, None).unwrap();
+
+        let trial = trial_balance(None).unwrap();
+        let credit = |code: &str| trial.lines.iter().find(|l| l.code == code).map(|l| l.credit - l.debit).unwrap_or(0.0);
+        assert!(same_amount(credit("4000"), totals.room_total), "room revenue is {:.2}, expected {:.2}", credit("4000"), totals.room_total);
+        assert!(same_amount(credit("4100"), 20.0), "food & beverage revenue is {:.2}, expected 20.00", credit("4100"));
+        assert!(same_amount(credit("4200"), 2.0), "service charge revenue is {:.2}, expected 2.00", credit("4200"));
+    }
+}
@@ -1,64 +1,254 @@
 mod models;
 mod db;
+mod events;
+mod paging;
+mod repository;
 mod offline_auth;
 mod simple_commands;
 mod database_reset;
 mod export;
+mod export_jobs;
 mod print_templates;
 mod validation;
+mod errors;
 mod settings;
+mod registration;
+mod registration_report;
+mod stay_import;
+mod menu_import;
+mod expense_import;
+mod legacy_import;
+mod pricing;
+mod checkout_holds;
+mod guest_charges;
+mod room_transfers;
+mod stay_fees;
+mod checkout_reopen;
+mod room_types;
+mod menu_categories;
+mod combos;
+mod menu_pricing;
+mod trash;
+mod folio;
+mod guest_profiles;
+mod guest_privacy;
+mod group_bookings;
+mod maintenance;
+mod order_status;
+mod tables;
+mod order_voids;
+mod inventory;
+mod purchasing;
+mod companies;
+mod budgets;
+mod expense_categories;
+mod ledger;
+mod accounting_export;
+mod tax_report;
+mod fiscal;
+mod online_payments;
+mod vouchers;
+mod receivables;
+mod taxes;
+mod service_charge;
+mod rounding;
+mod templates;
+mod receipt_i18n;
+mod money;
+mod notifications;
+mod backup_schedule;
+mod cloud_backup;
+mod backup_crypto;
+mod db_encryption;
+mod migrations;
+mod tags;
+mod search;
+mod timeseries;
+mod night_audit;
+mod date_checks;
+mod sync;
+mod media;
+mod business_hours;
+mod audit;
+mod self_test;
+#[cfg(test)]
+mod test_support;
 
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use db::initialize_database;
 use offline_auth::{
     login_admin, get_security_question, reset_admin_password,
-    validate_admin_session, logout_admin, cleanup_sessions, logout_all_sessions,
+    validate_admin_session, get_session_info, heartbeat, get_session_policy, set_session_policy, logout_admin, cleanup_sessions, logout_all_sessions,
     check_is_setup, register_initial_admin, register_user, list_users, delete_user
 };
 use simple_commands::{
-    add_room, get_rooms, get_available_rooms_for_guest, update_room, delete_room, cleanup_soft_deleted_rooms,
-        add_guest, get_active_guests, get_all_guests, get_guest, checkout_guest, checkout_guest_with_discount, update_guest,
-    add_menu_item, get_menu_items, update_menu_item, delete_menu_item,
-        dashboard_stats, get_low_stock_items, add_food_order, get_food_orders, get_food_orders_by_guest, mark_order_paid,
-    add_expense, get_expenses, get_expenses_by_date_range, update_expense, delete_expense,
+    add_room, get_rooms, get_floor_occupancy, get_available_rooms_for_guest, update_room, delete_room, cleanup_soft_deleted_rooms,
+        add_guest, get_active_guests, get_occupancy_cap, set_occupancy_cap, get_all_guests, get_guests_page, get_guest, checkout_guest, checkout_guest_with_discount, update_guest, extend_stay,
+    add_reservation, get_reservations, cancel_reservation, convert_reservation_to_checkin, get_room_availability_calendar,
+    add_menu_item, get_menu_items, update_menu_item, delete_menu_item, set_menu_item_attributes,
+    bulk_update_prices, bulk_update_room_rates,
+    set_daily_specials, get_daily_specials, specials_performance_report,
+        dashboard_stats, dashboard_graphs, get_low_stock_items, add_food_order, get_food_orders, get_food_orders_page, get_food_orders_by_guest, mark_order_paid,
+    add_order_payment, get_order_payments, refund_payment, payments_by_method_report,
+    add_expense, get_expenses, get_expenses_page, get_expenses_by_date_range, update_expense, delete_expense, drawings_report,
     toggle_food_order_payment, delete_food_order, get_order_details,
     set_tax_rate, get_tax_rate, set_tax_enabled, get_tax_enabled,
     set_currency_code, get_currency_code, set_locale, get_locale,
-    set_business_name, get_business_name,
-    open_shift, close_shift, get_current_shift, get_shift_history,
+    set_business_name, get_business_name, set_business_info, get_business_info,
+    open_shift, close_shift, get_current_shift, get_shift_history, get_shift_reconciliation, get_denominations, set_denominations,
+    record_guest_feedback, feedback_report, ratings_by_room_report,
     // Generic alias commands
     add_resource, get_resources, get_available_resources_for_customer, update_resource, delete_resource,
     add_customer, get_active_customers, get_all_customers, get_customer, checkout_customer, checkout_customer_with_discount, update_customer,
     add_sale, get_sales, get_sales_by_customer, mark_sale_paid, toggle_sale_payment, delete_sale, get_sale_details,
     set_business_mode, get_business_mode
 };
-use database_reset::{reset_database, get_database_path, get_database_stats};
-use export::{export_history_csv, export_history_csv_with_dialog, create_database_backup};
-use print_templates::{build_order_receipt_html, build_final_invoice_html, build_final_invoice_html_with_discount, print_order_receipt};
+use stay_import::import_stay_history_xlsx;
+use menu_import::import_menu_csv;
+use expense_import::import_expenses_csv;
+use legacy_import::{import_legacy_data, get_import_adapters};
+use date_checks::{get_date_plausibility_settings, set_date_plausibility_settings, find_outlier_dates};
+use business_hours::{set_business_hours, get_business_hours, set_after_hours_strict, get_after_hours_strict, after_hours_summary};
+use pricing::{
+    set_rate_override, get_rate_overrides, delete_rate_override, get_effective_room_rate,
+    add_rate_plan, get_rate_plans, delete_rate_plan, quote_room_stay,
+    set_room_type_rate_bounds, get_room_type_rate_bounds, get_pricing_thresholds, set_pricing_thresholds,
+    add_closure_period, get_closure_periods, delete_closure_period,
+    pricing_suggestions, apply_pricing_suggestions
+};
+use checkout_holds::{
+    place_checkout_hold, release_checkout_hold, convert_hold_to_charge,
+    get_checkout_holds, get_guest_adjustments
+};
+use guest_charges::{add_guest_charge, get_guest_charges, delete_guest_charge};
+use room_transfers::transfer_guest_room;
+use stay_fees::{get_stay_fee_rules, set_stay_fee_rules};
+use checkout_reopen::{reopen_checkout, get_checkout_reopen_window, set_checkout_reopen_window};
+use room_types::{get_room_types, add_room_type, update_room_type, delete_room_type};
+use menu_categories::{get_menu_categories, add_menu_category, update_menu_category, delete_menu_category, reorder_menu_categories};
+use combos::{get_combos, add_combo, update_combo, delete_combo};
+use menu_pricing::{get_menu_price_rules, add_menu_price_rule, delete_menu_price_rule};
+use trash::{get_trash, restore_item, purge_trash};
+use folio::{add_guest_deposit, get_guest_folio};
+use guest_profiles::{get_guest_profiles, get_guest_profile, update_guest_profile, get_guest_profile_history, find_duplicate_guests, merge_guests};
+use guest_privacy::{export_guest_data, anonymize_guest};
+use group_bookings::{create_group_booking, get_group_bookings, get_group_booking, check_in_group, check_out_group, build_group_invoice_html};
+use maintenance::{add_maintenance_ticket, get_maintenance_tickets, update_maintenance_ticket, delete_maintenance_ticket};
+use order_status::{set_order_status, get_orders_by_status};
+use inventory::{
+    add_inventory_item, update_inventory_item, delete_inventory_item, get_stock_levels, adjust_stock,
+    get_stock_movements, set_menu_item_recipe, get_menu_item_recipe, low_stock_report, menu_item_profitability,
+};
+use purchasing::{
+    add_supplier, get_suppliers, create_purchase_order, get_purchase_orders, receive_purchase_order,
+    cancel_purchase_order, record_supplier_payment, get_supplier_payments,
+};
+use companies::{add_company, get_companies, post_to_company_account, record_company_payment, company_statement};
+use budgets::{get_expense_budgets, set_expense_budget, delete_expense_budget, budget_vs_actual};
+use expense_categories::{get_expense_categories, add_expense_category, rename_expense_category, merge_expense_categories, delete_expense_category};
+use ledger::{
+    get_ledger_accounts, add_ledger_account, get_journal_entries, add_journal_entry, reverse_journal_entry,
+    trial_balance, profit_and_loss,
+};
+use accounting_export::export_accounting_csv;
+use tax_report::{tax_report, export_tax_report};
+use fiscal::{set_fiscal_settings, get_fiscal_settings, fiscalize_invoice, get_fiscal_invoices};
+use online_payments::{set_payment_gateway_settings, get_payment_gateway_settings, create_online_payment, check_online_payment, cancel_online_payment, get_online_payments};
+use vouchers::{create_voucher, get_vouchers, deactivate_voucher, validate_voucher, redeem_voucher_on_order, get_voucher_redemptions};
+use receivables::receivables_aging_report;
+use taxes::{add_tax, get_taxes, update_tax, delete_tax, set_prices_include_tax, get_prices_include_tax};
+use service_charge::{set_service_charge_percent, get_service_charge_settings, service_charge_report};
+use rounding::{set_total_rounding, get_total_rounding};
+use templates::{list_print_templates, get_print_template, preview_print_template, save_print_template, reset_print_template};
+use receipt_i18n::{get_receipt_languages, set_receipt_locale, get_receipt_locale};
+use notifications::{set_notification_provider, get_notification_provider, set_notification_event, get_notification_events, send_payment_reminder, send_test_notification, get_notification_log};
+use backup_schedule::{set_backup_schedule, get_backup_schedule};
+use cloud_backup::{set_cloud_backup_provider, get_cloud_backup_provider, get_cloud_backup_status};
+use db_encryption::{enable_database_encryption, disable_database_encryption, get_database_encryption, unlock_database};
+use order_voids::{void_order_item, get_voided_items};
+use tables::{add_table, get_tables, get_open_tables, open_table, close_table, move_order_to_table, delete_table};
+use tags::{add_tag, rename_tag, delete_tag, list_tags, tag_entity, untag_entity, get_entity_tags};
+use search::global_search;
+use timeseries::{revenue_timeseries, expense_timeseries};
+use night_audit::{night_audit_report, export_night_audit};
+use sync::{get_sync_export, sync_pull, enable_sync_server, disable_sync_server, get_sync_server_status};
+use migrations::{get_migration_history, post_update_report, acknowledge_post_update_report};
+use database_reset::{reset_database, get_database_path, get_database_stats, check_data_integrity};
+use self_test::run_self_test;
+use audit::get_audit_log;
+use media::{get_media_integrity, relink_media_directory};
+use export::{export_history_csv, export_history_csv_with_dialog, create_database_backup, export_sql_dump};
+use export_jobs::{start_export_job, get_export_job_status, cancel_export_job};
+use print_templates::{build_order_receipt_html, build_final_invoice_html, build_final_invoice_html_with_discount, build_registration_card_html, print_order_receipt, print_order_receipt_silent, build_kitchen_ticket_html, print_kitchen_ticket, set_receipt_qr, get_receipt_qr};
+use registration::{
+    attach_registration_signature, get_registration_signature,
+    set_signature_retention_days, prune_registration_signatures
+};
+use registration_report::export_guest_registration_report;
 use settings::{
     backup_database, export_json_backup, restore_database_from_backup, get_reset_security_question, 
-    validate_security_answer, reset_application_data, select_backup_file, browse_backup_file
+    validate_security_answer, reset_application_data, select_backup_file, browse_backup_file,
+    set_backup_retention, get_backup_retention, list_backups, set_backup_encryption, get_backup_encryption,
+    get_restorable_tables, restore_tables_from_backup
 };
 
 use settings::{
-    store_business_logo, get_business_logo_path,
+    store_business_logo, get_business_logo_path, set_receipt_logo, get_receipt_logo,
     get_business_logo_data_url,
     set_primary_color, get_primary_color,
     set_receipt_header, get_receipt_header,
-    set_receipt_footer, get_receipt_footer
+    set_receipt_footer, get_receipt_footer, set_receipt_printer, get_receipt_printer,
+    set_kitchen_printer, get_kitchen_printer
 };
 
+// Exposed so the integration test runs the same scenario as the run_self_test command
+pub use self_test::run_self_test_scenario;
+
+/// Background work that needs an open database. Held back at startup while
+/// the database is locked, and started by `unlock_database` instead.
+pub(crate) fn start_database_services() {
+    // Drop signature images that have passed their retention period
+    if let Err(e) = prune_registration_signatures() {
+        eprintln!("Failed to prune registration signatures: {}", e);
+    }
+
+    // Serve menu/settings to the other front-desk PC if this one is a sync source
+    if let Err(e) = sync::start_configured_sync_server() {
+        eprintln!("Failed to start LAN sync server: {}", e);
+    }
+
+    // Take daily/weekly backups while the app is open
+    backup_schedule::start_backup_scheduler();
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize database on startup
-    if let Err(e) = initialize_database() {
-        eprintln!("Failed to initialize database: {}", e);
-        std::process::exit(1);
+    // A database encrypted with the admin password stays locked until the
+    // password is entered; the UI checks get_database_encryption and asks for it
+    if db_encryption::is_locked() {
+        println!("Database is encrypted; waiting for the admin password to unlock it");
+    } else {
+        // Initialize database on startup
+        if let Err(e) = initialize_database() {
+            eprintln!("Failed to initialize database: {}", e);
+            std::process::exit(1);
+        }
+        start_database_services();
     }
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
+            events::init(app.handle().clone());
+            // One-time notice after an update ran new migrations
+            if let Ok(conn) = db::get_db_connection() {
+                if let Ok(report) = migrations::build_post_update_report(&conn) {
+                    if report.pending {
+                        let _ = app.emit("app-updated", &report);
+                    }
+                }
+            }
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -72,6 +262,10 @@ pub fn run() {
             get_security_question, 
             reset_admin_password,
             validate_admin_session,
+            get_session_info,
+            heartbeat,
+            get_session_policy,
+            set_session_policy,
             logout_admin,
             cleanup_sessions,
             logout_all_sessions,
@@ -80,9 +274,19 @@ pub fn run() {
             register_user,
             list_users,
             delete_user,
+            // Tags
+            add_tag,
+            rename_tag,
+            delete_tag,
+            list_tags,
+            tag_entity,
+            untag_entity,
+            get_entity_tags,
+            global_search,
             // Room management
             add_room,
             get_rooms,
+            get_floor_occupancy,
             get_available_rooms_for_guest,
             update_room,
             delete_room,
@@ -97,10 +301,69 @@ pub fn run() {
             add_guest,
             get_active_guests,
             get_all_guests,
+            get_guests_page,
             get_guest,
             checkout_guest,
             checkout_guest_with_discount,
             update_guest,
+            extend_stay,
+            get_occupancy_cap,
+            set_occupancy_cap,
+            // Reservations
+            add_reservation,
+            get_reservations,
+            cancel_reservation,
+            convert_reservation_to_checkin,
+            get_room_availability_calendar,
+            // Checkout holds & folio adjustments
+            place_checkout_hold,
+            release_checkout_hold,
+            convert_hold_to_charge,
+            get_checkout_holds,
+            get_guest_adjustments,
+            // Extra folio charges
+            add_guest_charge,
+            get_guest_charges,
+            delete_guest_charge,
+            // Room transfers
+            transfer_guest_room,
+            // Early check-in / late check-out fees
+            get_stay_fee_rules,
+            set_stay_fee_rules,
+            // Reopening a mistaken checkout
+            reopen_checkout,
+            get_checkout_reopen_window,
+            set_checkout_reopen_window,
+            // Room types
+            get_room_types,
+            add_room_type,
+            update_room_type,
+            delete_room_type,
+            // Deposits & folio balance
+            add_guest_deposit,
+            get_guest_folio,
+            // Guest profiles & stay history
+            get_guest_profiles,
+            get_guest_profile,
+            update_guest_profile,
+            get_guest_profile_history,
+            find_duplicate_guests,
+            merge_guests,
+            // Guest data export & erasure
+            export_guest_data,
+            anonymize_guest,
+            // Group bookings
+            create_group_booking,
+            get_group_bookings,
+            get_group_booking,
+            check_in_group,
+            check_out_group,
+            build_group_invoice_html,
+            // Maintenance tickets
+            add_maintenance_ticket,
+            get_maintenance_tickets,
+            update_maintenance_ticket,
+            delete_maintenance_ticket,
             // Customer management (generic aliases)
             add_customer,
             get_active_customers,
@@ -114,14 +377,98 @@ pub fn run() {
             get_menu_items,
             update_menu_item,
             delete_menu_item,
+            set_menu_item_attributes,
+            import_menu_csv,
+            get_menu_categories,
+            add_menu_category,
+            update_menu_category,
+            delete_menu_category,
+            reorder_menu_categories,
+            get_combos,
+            add_combo,
+            update_combo,
+            delete_combo,
+            get_menu_price_rules,
+            add_menu_price_rule,
+            delete_menu_price_rule,
+            bulk_update_prices,
+            bulk_update_room_rates,
+            // Rate overrides & pricing suggestions
+            set_rate_override,
+            get_rate_overrides,
+            delete_rate_override,
+            get_effective_room_rate,
+            add_rate_plan,
+            get_rate_plans,
+            delete_rate_plan,
+            quote_room_stay,
+            set_room_type_rate_bounds,
+            get_room_type_rate_bounds,
+            get_pricing_thresholds,
+            set_pricing_thresholds,
+            add_closure_period,
+            get_closure_periods,
+            delete_closure_period,
+            pricing_suggestions,
+            apply_pricing_suggestions,
+            // Daily specials
+            set_daily_specials,
+            get_daily_specials,
+            specials_performance_report,
             // Food orders
             add_food_order,
             get_food_orders,
+            get_food_orders_page,
             get_food_orders_by_guest,
             mark_order_paid,
+            add_order_payment,
+            get_order_payments,
+            refund_payment,
+            payments_by_method_report,
             toggle_food_order_payment,
             delete_food_order,
             get_order_details,
+            // Order status (kitchen workflow)
+            set_order_status,
+            get_orders_by_status,
+            // Ingredient inventory
+            add_inventory_item,
+            update_inventory_item,
+            delete_inventory_item,
+            get_stock_levels,
+            adjust_stock,
+            get_stock_movements,
+            set_menu_item_recipe,
+            get_menu_item_recipe,
+            low_stock_report,
+            menu_item_profitability,
+            // Suppliers & purchasing
+            add_supplier,
+            get_suppliers,
+            create_purchase_order,
+            get_purchase_orders,
+            receive_purchase_order,
+            cancel_purchase_order,
+            record_supplier_payment,
+            get_supplier_payments,
+            // Company accounts (city ledger) & receivables
+            add_company,
+            get_companies,
+            post_to_company_account,
+            record_company_payment,
+            company_statement,
+            receivables_aging_report,
+            // Voided order lines
+            void_order_item,
+            get_voided_items,
+            // Restaurant tables
+            add_table,
+            get_tables,
+            get_open_tables,
+            open_table,
+            close_table,
+            move_order_to_table,
+            delete_table,
             // Sales (generic aliases)
             add_sale,
             get_sales,
@@ -133,35 +480,164 @@ pub fn run() {
             // Expenses
             add_expense,
             get_expenses,
+            get_expenses_page,
             get_expenses_by_date_range,
             update_expense,
             delete_expense,
+            get_trash,
+            restore_item,
+            purge_trash,
+            drawings_report,
+            get_expense_categories,
+            add_expense_category,
+            rename_expense_category,
+            merge_expense_categories,
+            delete_expense_category,
+            import_expenses_csv,
+            get_expense_budgets,
+            set_expense_budget,
+            delete_expense_budget,
+            budget_vs_actual,
+            // Ledger
+            get_ledger_accounts,
+            add_ledger_account,
+            get_journal_entries,
+            add_journal_entry,
+            reverse_journal_entry,
+            trial_balance,
+            profit_and_loss,
+            export_accounting_csv,
+            tax_report,
+            export_tax_report,
+            set_fiscal_settings,
+            get_fiscal_settings,
+            fiscalize_invoice,
+            get_fiscal_invoices,
+            set_payment_gateway_settings,
+            get_payment_gateway_settings,
+            create_online_payment,
+            check_online_payment,
+            cancel_online_payment,
+            get_online_payments,
+            create_voucher,
+            get_vouchers,
+            deactivate_voucher,
+            validate_voucher,
+            redeem_voucher_on_order,
+            get_voucher_redemptions,
+            // Date plausibility checks
+            get_date_plausibility_settings,
+            set_date_plausibility_settings,
+            find_outlier_dates,
+            // Business hours
+            set_business_hours,
+            get_business_hours,
+            set_after_hours_strict,
+            get_after_hours_strict,
+            after_hours_summary,
+            // Audit trail
+            get_audit_log,
             // Dashboard
             dashboard_stats,
+            dashboard_graphs,
+            revenue_timeseries,
+            expense_timeseries,
+            night_audit_report,
+            export_night_audit,
             get_low_stock_items,
             // Database management
+            get_migration_history,
+            post_update_report,
+            acknowledge_post_update_report,
             reset_database,
             get_database_path,
             get_database_stats,
+            check_data_integrity,
+            run_self_test,
+            get_media_integrity,
+            relink_media_directory,
+            // LAN sync
+            get_sync_export,
+            sync_pull,
+            enable_sync_server,
+            disable_sync_server,
+            get_sync_server_status,
             // Export & Print
             export_history_csv,
             export_history_csv_with_dialog,
+            start_export_job,
+            get_export_job_status,
+            cancel_export_job,
             create_database_backup,
+            export_sql_dump,
+            import_stay_history_xlsx,
+            import_legacy_data,
+            get_import_adapters,
             build_order_receipt_html,
             build_final_invoice_html,
             build_final_invoice_html_with_discount,
             print_order_receipt,
+            print_order_receipt_silent,
+            build_kitchen_ticket_html,
+            print_kitchen_ticket,
+            build_registration_card_html,
+            // Registration signatures
+            attach_registration_signature,
+            get_registration_signature,
+            set_signature_retention_days,
+            prune_registration_signatures,
+            // Guest register for the police / foreigners' registration office
+            export_guest_registration_report,
             // Settings
             set_tax_rate,
             get_tax_rate,
             set_tax_enabled,
             get_tax_enabled,
+            add_tax,
+            get_taxes,
+            update_tax,
+            delete_tax,
+            set_prices_include_tax,
+            get_prices_include_tax,
+            set_service_charge_percent,
+            get_service_charge_settings,
+            service_charge_report,
+            set_total_rounding,
+            get_total_rounding,
+            list_print_templates,
+            get_print_template,
+            preview_print_template,
+            save_print_template,
+            reset_print_template,
+            get_receipt_languages,
+            set_receipt_locale,
+            get_receipt_locale,
+            set_receipt_qr,
+            get_receipt_qr,
+            set_notification_provider,
+            get_notification_provider,
+            set_notification_event,
+            get_notification_events,
+            send_payment_reminder,
+            send_test_notification,
+            get_notification_log,
+            set_backup_schedule,
+            get_backup_schedule,
+            set_cloud_backup_provider,
+            get_cloud_backup_provider,
+            get_cloud_backup_status,
+            enable_database_encryption,
+            disable_database_encryption,
+            get_database_encryption,
+            unlock_database,
             set_currency_code,
             get_currency_code,
             set_locale,
             get_locale,
             set_business_name,
             get_business_name,
+            set_business_info,
+            get_business_info,
             set_business_mode,
             get_business_mode,
             // Backup & Reset
@@ -170,6 +646,13 @@ pub fn run() {
             restore_database_from_backup,
             select_backup_file,
             browse_backup_file,
+            set_backup_retention,
+            get_backup_retention,
+            list_backups,
+            set_backup_encryption,
+            get_backup_encryption,
+            get_restorable_tables,
+            restore_tables_from_backup,
             get_reset_security_question,
             validate_security_answer,
             reset_application_data
@@ -177,6 +660,8 @@ pub fn run() {
             // White-labeling (Phase 3)
             store_business_logo,
             get_business_logo_path,
+            set_receipt_logo,
+            get_receipt_logo,
             get_business_logo_data_url,
             set_primary_color,
             get_primary_color,
@@ -184,11 +669,22 @@ pub fn run() {
             get_receipt_header,
             set_receipt_footer,
             get_receipt_footer,
+            set_receipt_printer,
+            get_receipt_printer,
+            set_kitchen_printer,
+            get_kitchen_printer,
             // Shift management (Phase 4)
             open_shift,
             close_shift,
             get_current_shift,
-            get_shift_history
+            get_shift_history,
+            get_shift_reconciliation,
+            get_denominations,
+            set_denominations,
+            // Guest feedback
+            record_guest_feedback,
+            feedback_report,
+            ratings_by_room_report
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,234 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::events;
+use crate::models::MaintenanceTicket;
+use crate::validation::{EMPTY_FIELD, NEGATIVE_AMOUNT, ROOM_NOT_FOUND, ROOM_OUT_OF_SERVICE};
+
+const PRIORITIES: &[&str] = &["low", "normal", "high", "urgent"];
+const STATUSES: &[&str] = &["open", "in_progress", "resolved"];
+
+const TICKET_COLUMNS: &str = "t.id, t.room_id, r.number, t.issue, t.priority, t.status, t.out_of_service, t.cost,
+                              t.reported_by, t.created_at, t.updated_at, t.resolved_at";
+
+fn map_ticket(row: &rusqlite::Row) -> rusqlite::Result<MaintenanceTicket> {
+    Ok(MaintenanceTicket {
+        id: row.get(0)?,
+        room_id: row.get(1)?,
+        room_number: row.get(2)?,
+        issue: row.get(3)?,
+        priority: row.get(4)?,
+        status: row.get(5)?,
+        out_of_service: row.get::<_, i32>(6)? == 1,
+        cost: row.get(7)?,
+        reported_by: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+        resolved_at: row.get(11)?,
+    })
+}
+
+fn parse_choice(value: &str, allowed: &[&str], what: &str) -> Result<String, String> {
+    let value = value.trim().to_lowercase();
+    if !allowed.contains(&value.as_str()) {
+        return Err(format!("{} must be one of: {}", what, allowed.join(", ")));
+    }
+    Ok(value)
+}
+
+fn validate_cost(cost: Option<f64>) -> Result<(), AppError> {
+    match cost {
+        Some(cost) if !cost.is_finite() || cost < 0.0 => {
+            Err(AppError::invalid(NEGATIVE_AMOUNT, "cost", "Repair cost must be >= 0"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn load_ticket(conn: &Connection, ticket_id: i64) -> Result<MaintenanceTicket, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM maintenance_tickets t JOIN resources r ON r.id = t.room_id WHERE t.id = ?1",
+            TICKET_COLUMNS
+        ),
+        params![ticket_id],
+        map_ticket,
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or("Maintenance ticket not found".to_string())
+}
+
+fn takes_room_out_of_service(ticket: &MaintenanceTicket) -> bool {
+    ticket.out_of_service && ticket.status != "resolved"
+}
+
+/// Fail with ROOM_OUT_OF_SERVICE, naming the ticket, while an unresolved
+/// ticket has taken the room out of service.
+pub fn ensure_room_in_service(conn: &Connection, room_id: i64) -> Result<(), String> {
+    let ticket: Option<(i64, String)> = conn
+        .query_row(
+            "SELECT id, issue FROM maintenance_tickets
+             WHERE room_id = ?1 AND out_of_service = 1 AND status != 'resolved'
+             ORDER BY id LIMIT 1",
+            params![room_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match ticket {
+        Some((id, issue)) => Err(format!("{}: maintenance ticket #{} ({})", ROOM_OUT_OF_SERVICE, id, issue)),
+        None => Ok(()),
+    }
+}
+
+/// Open a ticket against a room. With `out_of_service` the room can't be
+/// given to a guest until the ticket is resolved; a guest already in the
+/// room is not affected.
+#[command]
+pub fn add_maintenance_ticket(
+    room_id: i64,
+    issue: String,
+    priority: Option<String>,
+    out_of_service: Option<bool>,
+    cost: Option<f64>,
+    session_token: Option<String>,
+) -> Result<MaintenanceTicket, AppError> {
+    let issue = issue.trim();
+    if issue.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "issue", "Issue cannot be empty"));
+    }
+    let priority = parse_choice(priority.as_deref().unwrap_or("normal"), PRIORITIES, "priority")?;
+    validate_cost(cost)?;
+
+    let conn = get_db_connection()?;
+    let room_exists: bool = conn
+        .query_row("SELECT 1 FROM resources WHERE id = ?1", params![room_id], |_| Ok(true))
+        .optional()?
+        .unwrap_or(false);
+    if !room_exists {
+        return Err(AppError::coded(ROOM_NOT_FOUND, "Room not found"));
+    }
+
+    let now = get_current_timestamp();
+    conn.execute(
+        "INSERT INTO maintenance_tickets (room_id, issue, priority, out_of_service, cost, reported_by, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)",
+        params![
+            room_id,
+            issue,
+            priority,
+            out_of_service.unwrap_or(false),
+            cost,
+            crate::audit::audit_actor(session_token.as_deref()),
+            now
+        ],
+    )?;
+
+    let ticket = load_ticket(&conn, conn.last_insert_rowid())?;
+    if takes_room_out_of_service(&ticket) {
+        events::room_status_changed(Some(room_id), "out_of_service");
+    }
+    Ok(ticket)
+}
+
+/// Tickets, optionally for one room and/or in one status. Unresolved tickets
+/// come first, most urgent first.
+#[command]
+pub fn get_maintenance_tickets(room_id: Option<i64>, status: Option<String>) -> Result<Vec<MaintenanceTicket>, AppError> {
+    let status = status.map(|s| parse_choice(&s, STATUSES, "status")).transpose()?;
+    let conn = get_db_connection()?;
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM maintenance_tickets t JOIN resources r ON r.id = t.room_id
+         WHERE (?1 IS NULL OR t.room_id = ?1) AND (?2 IS NULL OR t.status = ?2)
+         ORDER BY t.status = 'resolved',
+                  CASE t.priority WHEN 'urgent' THEN 0 WHEN 'high' THEN 1 WHEN 'normal' THEN 2 ELSE 3 END,
+                  t.created_at DESC, t.id DESC",
+        TICKET_COLUMNS
+    ))?;
+    let tickets = stmt
+        .query_map(params![room_id, status], map_ticket)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tickets)
+}
+
+/// Change any of a ticket's fields. Setting status to 'resolved' stamps
+/// resolved_at and returns an out-of-service room to use; reopening clears it.
+#[command]
+pub fn update_maintenance_ticket(
+    ticket_id: i64,
+    issue: Option<String>,
+    priority: Option<String>,
+    status: Option<String>,
+    out_of_service: Option<bool>,
+    cost: Option<f64>,
+) -> Result<MaintenanceTicket, AppError> {
+    let conn = get_db_connection()?;
+    let ticket = load_ticket(&conn, ticket_id)?;
+    let was_out_of_service = takes_room_out_of_service(&ticket);
+
+    let issue = match issue {
+        Some(issue) if issue.trim().is_empty() => {
+            return Err(AppError::invalid(EMPTY_FIELD, "issue", "Issue cannot be empty"));
+        }
+        Some(issue) => issue.trim().to_string(),
+        None => ticket.issue,
+    };
+    let priority = match priority {
+        Some(priority) => parse_choice(&priority, PRIORITIES, "priority")?,
+        None => ticket.priority,
+    };
+    let status = match status {
+        Some(status) => parse_choice(&status, STATUSES, "status")?,
+        None => ticket.status.clone(),
+    };
+    validate_cost(cost)?;
+
+    let now = get_current_timestamp();
+    let resolved_at = match (ticket.status == "resolved", status == "resolved") {
+        (false, true) => Some(now.clone()),
+        (true, true) => ticket.resolved_at,
+        _ => None,
+    };
+
+    conn.execute(
+        "UPDATE maintenance_tickets
+         SET issue = ?1, priority = ?2, status = ?3, out_of_service = ?4, cost = ?5, resolved_at = ?6, updated_at = ?7
+         WHERE id = ?8",
+        params![
+            issue,
+            priority,
+            status,
+            out_of_service.unwrap_or(ticket.out_of_service),
+            cost.or(ticket.cost),
+            resolved_at,
+            now,
+            ticket_id
+        ],
+    )?;
+
+    let updated = load_ticket(&conn, ticket_id)?;
+    match (was_out_of_service, takes_room_out_of_service(&updated)) {
+        (false, true) => events::room_status_changed(Some(updated.room_id), "out_of_service"),
+        (true, false) => events::room_status_changed(Some(updated.room_id), "in_service"),
+        _ => {}
+    }
+    Ok(updated)
+}
+
+#[command]
+pub fn delete_maintenance_ticket(ticket_id: i64) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let ticket = load_ticket(&conn, ticket_id).ok();
+    let affected = conn.execute("DELETE FROM maintenance_tickets WHERE id = ?1", params![ticket_id])?;
+    if affected == 0 {
+        return Err("Maintenance ticket not found".into());
+    }
+    if let Some(ticket) = ticket.filter(takes_room_out_of_service) {
+        events::room_status_changed(Some(ticket.room_id), "in_service");
+    }
+    Ok("Maintenance ticket deleted".to_string())
+}
@@ -0,0 +1,391 @@
+use tauri::command;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use rusqlite::{params, Connection};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use crate::db::{get_db_connection, get_current_timestamp, run_blocking};
+use crate::models::{MediaFeatureReport, MediaIntegrityReport, MissingMedia};
+use crate::repository::{get_non_empty_setting, get_setting, set_setting};
+
+// Built-in logo used on printed documents when no business logo is available
+const EMBEDDED_LOGO: &[u8] = include_bytes!("../logoforcheckout.png");
+
+// A path on this machine, so neither it nor business_logo_path is synced to the other desk
+const MEDIA_DIRECTORY_KEY: &str = "media_directory";
+
+// Folders under the media root that hold referenced files (logo assets, signatures)
+const MEDIA_SUBDIRS: &[&str] = &["assets", "media"];
+
+/// The per-user app-data folder that media paths are stored under by default.
+pub fn default_media_root() -> Result<PathBuf, String> {
+    let base = dirs::data_local_dir().ok_or("Failed to resolve app data directory".to_string())?;
+    Ok(base.join("hotel-app"))
+}
+
+/// The active media root: the relinked folder if one was set, else the default.
+pub fn media_root(conn: &Connection) -> Result<PathBuf, String> {
+    match get_non_empty_setting(conn, MEDIA_DIRECTORY_KEY)? {
+        Some(dir) => Ok(PathBuf::from(dir.trim())),
+        None => default_media_root(),
+    }
+}
+
+/// Where new media files of a feature should be written, e.g. `media_dir(&["assets"])`.
+pub fn media_dir(subdirs: &[&str]) -> Result<PathBuf, String> {
+    let root = match get_db_connection() {
+        Ok(conn) => media_root(&conn)?,
+        Err(_) => default_media_root()?,
+    };
+    Ok(subdirs.iter().fold(root, |dir, sub| dir.join(sub)))
+}
+
+/// The part of a stored path from its `assets`/`media` folder onwards, so a
+/// path recorded on another PC can be re-based onto this machine's media root.
+fn relative_media_path(stored: &Path) -> Option<PathBuf> {
+    let components: Vec<Component> = stored.components().collect();
+    let start = components.iter().rposition(|c| match c {
+        Component::Normal(name) => MEDIA_SUBDIRS.iter().any(|sub| name.eq_ignore_ascii_case(sub)),
+        _ => false,
+    })?;
+    Some(components[start..].iter().collect())
+}
+
+/// Locate a stored media file: the recorded path if it exists, otherwise the
+/// same file under the current media root. None when it can't be found.
+pub fn resolve_media_path(conn: &Connection, stored: &str) -> Option<PathBuf> {
+    let stored = stored.trim();
+    if stored.is_empty() {
+        return None;
+    }
+
+    let path = PathBuf::from(stored);
+    if path.is_file() {
+        return Some(path);
+    }
+
+    let candidate = media_root(conn).ok()?.join(relative_media_path(&path)?);
+    if candidate.is_file() {
+        Some(candidate)
+    } else {
+        None
+    }
+}
+
+pub fn guess_image_mime(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+        .as_str()
+    {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A stored image as a data URL. Missing, unreadable or empty files yield
+/// None so callers can fall back to a placeholder instead of failing.
+pub fn media_data_url(conn: &Connection, stored: &str) -> Option<String> {
+    let path = resolve_media_path(conn, stored)?;
+    let bytes = fs::read(&path).ok().filter(|b| !b.is_empty())?;
+    Some(format!("data:{};base64,{}", guess_image_mime(&path), BASE64_STANDARD.encode(bytes)))
+}
+
+/// The business logo chosen in settings, if it is set and present on disk.
+pub fn business_logo_data_url(conn: &Connection) -> Option<String> {
+    let stored = get_setting(conn, "business_logo_path").ok()??;
+    media_data_url(conn, &stored)
+}
+
+/// The logo bundled with the app.
+pub fn embedded_logo_data_url() -> Option<String> {
+    if EMBEDDED_LOGO.is_empty() {
+        return None;
+    }
+    Some(format!("data:image/png;base64,{}", BASE64_STANDARD.encode(EMBEDDED_LOGO)))
+}
+
+/// Logo for printed documents: the business logo, else the bundled one.
+pub fn document_logo_data_url(conn: &Connection) -> Option<String> {
+    business_logo_data_url(conn).or_else(embedded_logo_data_url)
+}
+
+struct MediaReference {
+    feature: &'static str,
+    owner: String,
+    path: String,
+    // Row to repoint when relinking: None for the logo setting, Some(id) for a guest signature
+    guest_id: Option<i64>,
+}
+
+fn media_references(conn: &Connection) -> Result<Vec<MediaReference>, String> {
+    let mut refs = Vec::new();
+
+    if let Some(path) = get_non_empty_setting(conn, "business_logo_path")? {
+        refs.push(MediaReference { feature: "logo", owner: "Business logo".to_string(), path, guest_id: None });
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, signature_path FROM customers
+             WHERE signature_path IS NOT NULL AND TRIM(signature_path) != '' ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        let (id, name, path) = row.map_err(|e| e.to_string())?;
+        refs.push(MediaReference {
+            feature: "signatures",
+            owner: format!("Guest #{} {}", id, name),
+            path,
+            guest_id: Some(id),
+        });
+    }
+
+    Ok(refs)
+}
+
+/// Every media file the database points at that can't be found, grouped by feature.
+pub fn media_integrity_report(conn: &Connection) -> Result<MediaIntegrityReport, String> {
+    let mut features: Vec<MediaFeatureReport> = ["logo", "signatures"]
+        .iter()
+        .map(|f| MediaFeatureReport { feature: f.to_string(), referenced: 0, missing: Vec::new() })
+        .collect();
+
+    for reference in media_references(conn)? {
+        let group = features
+            .iter_mut()
+            .find(|g| g.feature == reference.feature)
+            .expect("every reference feature has a group");
+        group.referenced += 1;
+        if resolve_media_path(conn, &reference.path).is_none() {
+            group.missing.push(MissingMedia { owner: reference.owner, path: reference.path });
+        }
+    }
+
+    Ok(MediaIntegrityReport {
+        media_root: media_root(conn)?.to_string_lossy().to_string(),
+        missing_total: features.iter().map(|g| g.missing.len()).sum(),
+        features,
+    })
+}
+
+#[command]
+pub async fn get_media_integrity() -> Result<MediaIntegrityReport, String> {
+    run_blocking(move || {
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        media_integrity_report(&conn)
+    })
+    .await
+}
+
+/// Point the app at a media folder copied from the old PC (the folder that
+/// contains `assets` and/or `media`), then rewrite references that now resolve.
+#[command]
+pub async fn relink_media_directory(path: String) -> Result<MediaIntegrityReport, String> {
+    run_blocking(move || {
+        let dir = PathBuf::from(path.trim());
+        if !dir.is_dir() {
+            return Err("Selected media folder does not exist".to_string());
+        }
+        if !MEDIA_SUBDIRS.iter().any(|sub| dir.join(sub).is_dir()) {
+            return Err(format!(
+                "Selected folder does not look like a media folder (expected a {} subfolder)",
+                MEDIA_SUBDIRS.join(" or ")
+            ));
+        }
+
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        set_setting(&tx, MEDIA_DIRECTORY_KEY, &dir.to_string_lossy())?;
+
+        for reference in media_references(&tx)? {
+            if Path::new(&reference.path).is_file() {
+                continue;
+            }
+            let resolved = match resolve_media_path(&tx, &reference.path) {
+                Some(p) => p.to_string_lossy().to_string(),
+                None => continue,
+            };
+            match reference.guest_id {
+                Some(id) => tx.execute("UPDATE customers SET signature_path = ?1 WHERE id = ?2", params![resolved, id]),
+                None => tx.execute(
+                    "UPDATE settings SET value = ?1, updated_at = ?2 WHERE key = 'business_logo_path'",
+                    params![resolved, get_current_timestamp()],
+                ),
+            }
+            .map_err(|e| e.to_string())?;
+        }
+
+        let report = media_integrity_report(&tx)?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(report)
+    })
+    .await
+}
+
+fn copy_dir_recursive(src: &Path, dest: &Path) -> std::io::Result<usize> {
+    fs::create_dir_all(dest)?;
+    let mut copied = 0;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copied += copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Copy the media folders next to a database backup as `<backup stem>_media`,
+/// so restoring on another PC brings logos and signatures along.
+pub fn backup_media_alongside(backup_db_path: &Path) -> Result<usize, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let root = media_root(&conn)?;
+    let dest = media_backup_dir(backup_db_path);
+
+    let mut copied = 0;
+    for sub in MEDIA_SUBDIRS {
+        let src = root.join(sub);
+        if src.is_dir() {
+            copied += copy_dir_recursive(&src, &dest.join(sub))
+                .map_err(|e| format!("Failed to back up {} folder: {}", sub, e))?;
+        }
+    }
+    Ok(copied)
+}
+
+pub fn media_backup_dir(backup_db_path: &Path) -> PathBuf {
+    let stem = backup_db_path.file_stem().and_then(|s| s.to_str()).unwrap_or("backup");
+    backup_db_path.with_file_name(format!("{}_media", stem))
+}
+
+/// After a restore, bring in the media folder saved next to the backup (if
+/// any) without overwriting files already here.
+pub fn restore_media_alongside(backup_db_path: &Path) -> Result<usize, String> {
+    let src = media_backup_dir(backup_db_path);
+    if !src.is_dir() {
+        return Ok(0);
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let root = media_root(&conn)?;
+    let mut restored = 0;
+    for sub in MEDIA_SUBDIRS {
+        let from = src.join(sub);
+        if !from.is_dir() {
+            continue;
+        }
+        for entry in walk_files(&from).map_err(|e| e.to_string())? {
+            let relative = entry.strip_prefix(&src).map_err(|e| e.to_string())?;
+            let target = root.join(relative);
+            if target.exists() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::copy(&entry, &target).map_err(|e| format!("Failed to restore media file: {}", e))?;
+            restored += 1;
+        }
+    }
+    Ok(restored)
+}
+
+fn walk_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            files.extend(walk_files(&entry.path())?);
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::print_templates::{build_final_invoice_html, build_registration_card_html};
+    use crate::test_support::{add_test_room, check_in, TestDb};
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n', b'S', b'T'];
+
+    /// Every image in a document is embedded; a file path or empty src would
+    /// render as a broken image.
+    fn images_embedded(html: &str) -> bool {
+        html.match_indices("<img src=\"").all(|(at, tag)| html[at + tag.len()..].starts_with("data:image/"))
+    }
+
+    /// A guest whose signature, like the business logo, was recorded under
+    /// `old_pc`, as references arrive in a backup restored from another PC.
+    fn references_from(old_pc: &Path) -> i64 {
+        let guest_id = check_in("Moved Guest", Some(add_test_room("ST-101")));
+        let conn = get_db_connection().unwrap();
+        set_setting(&conn, "business_logo_path", &old_pc.join("assets").join("logo.png").to_string_lossy()).unwrap();
+        let signature = old_pc.join("media").join("signatures").join("signature.png");
+        conn.execute("UPDATE customers SET signature_path = ?1 WHERE id = ?2", params![signature.to_string_lossy().to_string(), guest_id])
+            .unwrap();
+        guest_id
+    }
+
+    /// The media folder copied over from the old PC.
+    fn copy_media(to: &Path) {
+        for file in [to.join("assets").join("logo.png"), to.join("media").join("signatures").join("signature.png")] {
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(&file, PNG).unwrap();
+        }
+    }
+
+    #[test]
+    fn the_integrity_pass_lists_missing_files_by_feature() {
+        let db = TestDb::new();
+        references_from(&db.dir().join("old_pc"));
+        let report = media_integrity_report(&get_db_connection().unwrap()).unwrap();
+        let missing = |feature: &str| report.features.iter().find(|f| f.feature == feature).map_or(0, |f| f.missing.len());
+        assert_eq!(report.missing_total, 2, "wrong number of missing files");
+        assert_eq!((missing("logo"), missing("signatures")), (1, 1), "expected one missing logo and one missing signature");
+    }
+
+    #[test]
+    fn documents_fall_back_when_their_images_are_missing() {
+        let db = TestDb::new();
+        let guest_id = references_from(&db.dir().join("old_pc"));
+        let invoice = build_final_invoice_html(guest_id, None).unwrap();
+        assert!(images_embedded(&invoice), "invoice with a missing logo has an image that isn't embedded");
+        assert!(invoice.contains(r#"alt="Logo""#), "invoice with a missing logo does not fall back to the bundled logo");
+        let card = build_registration_card_html(guest_id).unwrap();
+        assert!(images_embedded(&card), "card with a missing signature has an image that isn't embedded");
+        assert!(card.contains(r#"class="signature-line""#), "card with a missing signature does not fall back to a signature line");
+        assert_eq!(crate::registration::get_registration_signature(guest_id).unwrap(), None, "missing signature file was returned");
+    }
+
+    #[test]
+    fn relinking_repoints_references_at_the_copied_folder() {
+        let db = TestDb::new();
+        let guest_id = references_from(&db.dir().join("old_pc"));
+        let copied = db.dir().join("copied_media");
+        copy_media(&copied);
+        let nowhere = tauri::async_runtime::block_on(relink_media_directory(db.dir().join("nowhere").to_string_lossy().to_string()));
+        assert!(nowhere.is_err(), "relinking to a folder that does not exist was accepted");
+        let relinked = tauri::async_runtime::block_on(relink_media_directory(copied.to_string_lossy().to_string())).unwrap();
+        assert_eq!(relinked.missing_total, 0, "references still missing after relinking");
+        let stored = get_setting(&get_db_connection().unwrap(), "business_logo_path").unwrap().unwrap_or_default();
+        assert!(Path::new(&stored).starts_with(&copied), "logo reference not repointed at the copied folder: {}", stored);
+
+        let embedded_png = format!("data:image/png;base64,{}", BASE64_STANDARD.encode(PNG));
+        assert!(build_final_invoice_html(guest_id, None).unwrap().contains(&embedded_png), "invoice does not show the relinked logo");
+        assert!(build_registration_card_html(guest_id).unwrap().contains(&embedded_png), "card does not show the relinked signature");
+    }
+}
@@ -0,0 +1,238 @@
+use tauri::command;
+use std::collections::HashSet;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::audit_actor;
+use crate::db::{get_current_timestamp, get_db_connection, log_audit_event};
+use crate::errors::AppError;
+use crate::models::MenuCategory;
+use crate::validation::{EMPTY_FIELD, MENU_CATEGORY_EXISTS, MENU_CATEGORY_IN_USE, MENU_CATEGORY_NOT_FOUND};
+
+const CATEGORY_NAME_MAX_CHARS: usize = 50;
+
+const CATEGORY_COLUMNS: &str = "c.id, c.name, c.sort_order, c.is_active,
+    (SELECT COUNT(*) FROM menu_items m WHERE m.is_active = 1 AND m.category = c.name COLLATE NOCASE)";
+
+fn validate_category_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Category name cannot be empty"));
+    }
+    if name.chars().count() > CATEGORY_NAME_MAX_CHARS {
+        return Err(format!("Category name must be at most {} characters", CATEGORY_NAME_MAX_CHARS).into());
+    }
+    Ok(name.to_string())
+}
+
+fn map_category(row: &rusqlite::Row) -> rusqlite::Result<MenuCategory> {
+    Ok(MenuCategory {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        sort_order: row.get(2)?,
+        is_active: row.get::<_, i32>(3)? == 1,
+        item_count: row.get(4)?,
+    })
+}
+
+fn load_category(conn: &Connection, category_id: i64) -> Result<MenuCategory, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM menu_categories c WHERE c.id = ?1", CATEGORY_COLUMNS),
+        params![category_id],
+        map_category,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::coded(MENU_CATEGORY_NOT_FOUND, format!("Menu category #{} not found", category_id)))
+}
+
+fn find_by_name(conn: &Connection, name: &str) -> Result<Option<(i64, String)>, AppError> {
+    Ok(conn
+        .query_row(
+            "SELECT id, name FROM menu_categories WHERE name = ?1 COLLATE NOCASE",
+            params![name.trim()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?)
+}
+
+fn insert_category(conn: &Connection, name: &str) -> Result<i64, AppError> {
+    conn.execute(
+        "INSERT INTO menu_categories (name, sort_order) VALUES (?1, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM menu_categories))",
+        params![name],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// The managed spelling of `name`, matched regardless of case. A category the
+/// menu doesn't have yet is added at the end, so items can be filed under a
+/// new category straight away.
+pub fn ensure_menu_category(conn: &Connection, name: &str) -> Result<String, AppError> {
+    let name = validate_category_name(name)?;
+    if let Some((_, stored)) = find_by_name(conn, &name)? {
+        return Ok(stored);
+    }
+    insert_category(conn, &name)?;
+    Ok(name)
+}
+
+fn list_categories(conn: &Connection) -> Result<Vec<MenuCategory>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM menu_categories c ORDER BY c.sort_order, c.name COLLATE NOCASE",
+        CATEGORY_COLUMNS
+    ))?;
+    let categories = stmt.query_map([], map_category)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(categories)
+}
+
+/// Menu categories in the order the menu shows them, inactive ones included.
+#[command]
+pub fn get_menu_categories() -> Result<Vec<MenuCategory>, AppError> {
+    let conn = get_db_connection()?;
+    list_categories(&conn)
+}
+
+/// Add a category at the end of the menu.
+#[command]
+pub fn add_menu_category(name: String) -> Result<MenuCategory, AppError> {
+    let name = validate_category_name(&name)?;
+    let conn = get_db_connection()?;
+    if let Some((_, existing)) = find_by_name(&conn, &name)? {
+        return Err(AppError::invalid(MENU_CATEGORY_EXISTS, "name", format!("Category '{}' already exists", existing)));
+    }
+    let category_id = insert_category(&conn, &name)?;
+    load_category(&conn, category_id)
+}
+
+/// Rename a category, carrying the new name over to its items, or switch it
+/// off to take all of its items off the menu at once.
+#[command]
+pub fn update_menu_category(
+    category_id: i64,
+    name: String,
+    is_active: bool,
+    session_token: Option<String>,
+) -> Result<MenuCategory, AppError> {
+    let name = validate_category_name(&name)?;
+    let conn = get_db_connection()?;
+    let current = load_category(&conn, category_id)?;
+    if let Some((other_id, existing)) = find_by_name(&conn, &name)? {
+        if other_id != category_id {
+            return Err(AppError::invalid(MENU_CATEGORY_EXISTS, "name", format!("Category '{}' already exists", existing)));
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let now = get_current_timestamp();
+    tx.execute(
+        "UPDATE menu_categories SET name = ?1, is_active = ?2, updated_at = ?3 WHERE id = ?4",
+        params![name, is_active, now, category_id],
+    )?;
+    if name != current.name {
+        let moved = tx.execute(
+            "UPDATE menu_items SET category = ?1, updated_at = ?2 WHERE category = ?3 COLLATE NOCASE",
+            params![name, now, current.name],
+        )?;
+        tx.execute("UPDATE menu_price_rules SET category = ?1 WHERE category = ?2", params![name, current.name])?;
+        log_audit_event(
+            &tx,
+            &audit_actor(session_token.as_deref()),
+            "menu_category_renamed",
+            &format!("'{}' renamed to '{}' ({} items updated)", current.name, name, moved),
+        )?;
+    }
+    tx.commit()?;
+    load_category(&conn, category_id)
+}
+
+/// Delete a category no menu item is filed under, with its price rules.
+#[command]
+pub fn delete_menu_category(category_id: i64) -> Result<(), AppError> {
+    let conn = get_db_connection()?;
+    let category = load_category(&conn, category_id)?;
+    if category.item_count > 0 {
+        return Err(AppError::coded(
+            MENU_CATEGORY_IN_USE,
+            format!("'{}' has {} items; move them to another category first", category.name, category.item_count),
+        ));
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM menu_price_rules WHERE category = ?1", params![category.name])?;
+    tx.execute("DELETE FROM menu_categories WHERE id = ?1", params![category_id])?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Put the categories in the order given. Categories left out of the list
+/// keep their relative order after the listed ones.
+#[command]
+pub fn reorder_menu_categories(category_ids: Vec<i64>) -> Result<Vec<MenuCategory>, AppError> {
+    let conn = get_db_connection()?;
+    let current = list_categories(&conn)?;
+    let mut seen = HashSet::new();
+    for category_id in &category_ids {
+        if !seen.insert(*category_id) {
+            return Err(format!("Menu category #{} is listed twice", category_id).into());
+        }
+        if !current.iter().any(|c| c.id == *category_id) {
+            return Err(AppError::coded(MENU_CATEGORY_NOT_FOUND, format!("Menu category #{} not found", category_id)));
+        }
+    }
+    let rest = current.iter().map(|c| c.id).filter(|id| !seen.contains(id));
+
+    let tx = conn.unchecked_transaction()?;
+    let now = get_current_timestamp();
+    for (position, category_id) in category_ids.iter().copied().chain(rest).enumerate() {
+        tx.execute(
+            "UPDATE menu_categories SET sort_order = ?1, updated_at = ?2 WHERE id = ?3",
+            params![position as i64 + 1, now, category_id],
+        )?;
+    }
+    tx.commit()?;
+    list_categories(&conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{add_menu_item, get_menu_items};
+    use crate::test_support::TestDb;
+
+    fn add_item(name: &str, category: &str) -> i64 {
+        add_menu_item(name.to_string(), 5.0, category.to_string(), None, None, None, None).unwrap()
+    }
+
+    #[test]
+    fn items_are_filed_under_the_existing_spelling_or_a_new_category() {
+        let _db = TestDb::new();
+        let mains = add_menu_category("Mains".to_string()).unwrap();
+        let drinks = add_menu_category("Drinks".to_string()).unwrap();
+        assert!(drinks.sort_order > mains.sort_order, "new category was not added at the end");
+        add_item("Soup", "MAINS");
+        add_item("Tea", "Hot drinks");
+        let categories = get_menu_categories().unwrap();
+        assert!(categories.iter().any(|c| c.name == "Hot drinks"), "new item's category was not added");
+        let soup_category = categories.iter().find(|c| c.id == mains.id).map(|c| (c.name.clone(), c.item_count));
+        assert_eq!(soup_category, Some(("Mains".to_string(), 1)), "item filed under the wrong spelling or not counted");
+    }
+
+    #[test]
+    fn the_menu_follows_the_category_order() {
+        let _db = TestDb::new();
+        let mains = add_menu_category("Mains".to_string()).unwrap();
+        let drinks = add_menu_category("Drinks".to_string()).unwrap();
+        let soup = add_item("Soup", "Mains");
+        let juice = add_item("Juice", "Drinks");
+        reorder_menu_categories(vec![drinks.id, mains.id]).unwrap();
+        let order: Vec<i64> = get_menu_items().unwrap().iter().map(|item| item.id).collect();
+        assert_eq!(order, vec![juice, soup], "menu not in category order");
+    }
+
+    #[test]
+    fn items_in_an_inactive_category_leave_the_menu() {
+        let _db = TestDb::new();
+        let mains = add_menu_category("Mains".to_string()).unwrap();
+        let soup = add_item("Soup", "Mains");
+        let renamed = update_menu_category(mains.id, "Starters".to_string(), false, None).unwrap();
+        assert!(renamed.item_count == 1 && !renamed.is_active, "renamed category lost its item or stayed active");
+        assert!(get_menu_items().unwrap().iter().all(|item| item.id != soup), "item in an inactive category is still on the menu");
+        assert!(delete_menu_category(mains.id).is_err(), "category with items was deleted");
+    }
+}
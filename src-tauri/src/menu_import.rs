@@ -0,0 +1,300 @@
+use tauri::command;
+use std::collections::HashMap;
+use std::path::Path;
+use calamine::{open_workbook_auto, Reader};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{MenuImportOptions, MenuImportReport, MenuImportRow};
+use crate::menu_categories::ensure_menu_category;
+use crate::stay_import::cell_text;
+
+/// Header captions accepted for each column, compared case-insensitively.
+const NAME_HEADERS: &[&str] = &["name", "item", "item name", "menu item"];
+const PRICE_HEADERS: &[&str] = &["price", "rate", "amount"];
+const CATEGORY_HEADERS: &[&str] = &["category", "section", "group"];
+const AVAILABLE_HEADERS: &[&str] = &["available", "availability", "is_available", "status"];
+
+struct MenuColumns {
+    name: usize,
+    price: usize,
+    category: Option<usize>,
+    available: Option<usize>,
+}
+
+// Category and availability are None when the row leaves them blank
+struct MenuRow {
+    name: String,
+    price: f64,
+    category: Option<String>,
+    available: Option<bool>,
+}
+
+/// Every non-blank row of the file with its 1-based row number, as trimmed text.
+pub(crate) fn read_rows(path: &str, sheet_name: Option<&str>) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let is_csv = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv") || ext.eq_ignore_ascii_case("txt"));
+
+    let rows: Vec<(usize, Vec<String>)> = if is_csv {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .map_err(|e| format!("Failed to open file: {}", e))?;
+        let mut rows = Vec::new();
+        for (idx, record) in reader.records().enumerate() {
+            let record = record.map_err(|e| format!("Failed to read row {}: {}", idx + 1, e))?;
+            // Spreadsheet programs often save CSV with a byte-order mark
+            let cells = record.iter().map(|cell| cell.trim_start_matches('\u{feff}').trim().to_string()).collect();
+            rows.push((idx + 1, cells));
+        }
+        rows
+    } else {
+        let mut workbook = open_workbook_auto(path).map_err(|e| format!("Failed to open workbook: {}", e))?;
+        let sheet = match sheet_name {
+            Some(name) => name.to_string(),
+            None => workbook.sheet_names().first().cloned().ok_or("Workbook has no sheets".to_string())?,
+        };
+        let range = workbook
+            .worksheet_range(&sheet)
+            .map_err(|e| format!("Failed to read sheet '{}': {}", sheet, e))?;
+        let offset = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+        range
+            .rows()
+            .enumerate()
+            .map(|(idx, row)| (offset + idx + 1, row.iter().map(|cell| cell_text(Some(cell))).collect()))
+            .collect()
+    };
+    Ok(rows.into_iter().filter(|(_, cells)| cells.iter().any(|cell| !cell.is_empty())).collect())
+}
+
+fn resolve_columns(header: &[String]) -> Result<MenuColumns, String> {
+    let find = |captions: &[&str]| header.iter().position(|cell| captions.iter().any(|c| cell.eq_ignore_ascii_case(c)));
+    Ok(MenuColumns {
+        name: find(NAME_HEADERS).ok_or("No name column found. The first row must hold the column headers".to_string())?,
+        price: find(PRICE_HEADERS).ok_or("No price column found".to_string())?,
+        category: find(CATEGORY_HEADERS),
+        available: find(AVAILABLE_HEADERS),
+    })
+}
+
+fn parse_price(text: &str) -> Result<f64, String> {
+    // Currency symbols and thousands separators are dropped
+    let cleaned: String = text.chars().filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-').collect();
+    if cleaned.is_empty() {
+        return Err("price is empty".to_string());
+    }
+    let price = cleaned.parse::<f64>().map_err(|_| format!("unrecognised price '{}'", text))?;
+    if price < 0.0 {
+        return Err("price is negative".to_string());
+    }
+    Ok(price)
+}
+
+fn parse_available(text: &str) -> Result<Option<bool>, String> {
+    match text.to_ascii_lowercase().as_str() {
+        "" => Ok(None),
+        "yes" | "y" | "true" | "1" | "available" | "in stock" => Ok(Some(true)),
+        "no" | "n" | "false" | "0" | "unavailable" | "sold out" | "out of stock" => Ok(Some(false)),
+        _ => Err(format!("unrecognised availability '{}'; use yes or no", text)),
+    }
+}
+
+fn parse_menu_row(cells: &[String], cols: &MenuColumns) -> Result<MenuRow, String> {
+    let cell = |idx: Option<usize>| idx.and_then(|i| cells.get(i)).map(String::as_str).unwrap_or("");
+    let name = cell(Some(cols.name));
+    if name.is_empty() {
+        return Err("name is empty".to_string());
+    }
+    Ok(MenuRow {
+        name: name.to_string(),
+        price: parse_price(cell(Some(cols.price)))?,
+        category: Some(cell(cols.category)).filter(|c| !c.is_empty()).map(str::to_string),
+        available: parse_available(cell(cols.available))?,
+    })
+}
+
+/// Add the row as a new item, or update the item of the same name when
+/// `update_existing` is set; an update keeps the item's category and
+/// availability where the row leaves them blank. Returns the item and whether
+/// it was created.
+fn upsert_item(
+    conn: &Connection,
+    item: &MenuRow,
+    default_category: Option<&str>,
+    update_existing: bool,
+) -> Result<Option<(i64, bool)>, String> {
+    let existing: Option<(i64, bool)> = conn
+        .query_row(
+            "SELECT id, is_active = 1 FROM menu_items WHERE name = ?1 COLLATE NOCASE",
+            params![item.name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match existing {
+        Some((item_id, _)) if update_existing => {
+            let category = item.category.as_deref().map(|c| ensure_menu_category(conn, c)).transpose()?;
+            // A deleted item of the same name comes back onto the menu
+            conn.execute(
+                "UPDATE menu_items
+                 SET name = ?1, price = ?2, category = COALESCE(?3, category), is_available = COALESCE(?4, is_available),
+                     is_active = 1, updated_at = ?5
+                 WHERE id = ?6",
+                params![item.name, item.price, category, item.available, get_current_timestamp(), item_id],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(Some((item_id, false)))
+        }
+        Some(_) => Ok(None),
+        None => {
+            let category = item
+                .category
+                .as_deref()
+                .or(default_category)
+                .ok_or("category is empty and no default category was given".to_string())?;
+            let category = ensure_menu_category(conn, category)?;
+            conn.execute(
+                "INSERT INTO menu_items (name, price, category, is_available, is_active) VALUES (?1, ?2, ?3, ?4, 1)",
+                params![item.name, item.price, category, item.available.unwrap_or(true)],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(Some((conn.last_insert_rowid(), true)))
+        }
+    }
+}
+
+/// Load menu items from a CSV file or spreadsheet whose first row names the
+/// columns: name and price, and optionally category and availability (blank
+/// means available). Items are matched to the menu by name. Each
+/// row is reported as created, updated, skipped (already on the menu) or
+/// failed with the reason. With `dry_run` nothing is saved.
+#[command]
+pub fn import_menu_csv(path: String, options: Option<MenuImportOptions>) -> Result<MenuImportReport, AppError> {
+    let options = options.unwrap_or_default();
+    let default_category = options.default_category.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    let mut rows = read_rows(path.trim(), options.sheet_name.as_deref())?.into_iter();
+    let (_, header) = rows.next().ok_or("The file has no rows".to_string())?;
+    let cols = resolve_columns(&header)?;
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    let mut report = MenuImportReport {
+        dry_run: options.dry_run,
+        created: 0,
+        updated: 0,
+        skipped: 0,
+        failed: 0,
+        rows: Vec::new(),
+    };
+    // Lower-cased name -> the row that first listed it
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for (row_number, cells) in rows {
+        let parsed = parse_menu_row(&cells, &cols);
+        let name = parsed.as_ref().ok().map(|item| item.name.clone());
+        let result = parsed.and_then(|item| {
+            if let Some(first) = seen.insert(item.name.to_lowercase(), row_number) {
+                return Err(format!("'{}' is already listed on row {}", item.name, first));
+            }
+            upsert_item(&tx, &item, default_category, options.update_existing)
+        });
+
+        let (status, reason, item_id) = match result {
+            Ok(Some((item_id, true))) => {
+                report.created += 1;
+                ("created", None, Some(item_id))
+            }
+            Ok(Some((item_id, false))) => {
+                report.updated += 1;
+                ("updated", None, Some(item_id))
+            }
+            Ok(None) => {
+                report.skipped += 1;
+                ("skipped", Some("already on the menu".to_string()), None)
+            }
+            Err(reason) => {
+                report.failed += 1;
+                ("failed", Some(reason), None)
+            }
+        };
+        report.rows.push(MenuImportRow { row: row_number, name, status: status.to_string(), reason, item_id });
+    }
+
+    if options.dry_run {
+        tx.rollback()?;
+    } else {
+        tx.commit()?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{same_amount, TestDb};
+
+    /// A menu sheet with a currency sign, a blank category and a blank name,
+    /// and the coffee listed twice under different spellings.
+    fn menu_sheet(db: &TestDb) -> std::path::PathBuf {
+        let path = db.dir().join("menu.csv");
+        std::fs::write(
+            &path,
+            "Item,Price,Category,Available\n\
+             Coffee,$2.50,Drinks,yes\n\
+             Cake,3,,no\n\
+             ,4,Drinks,yes\n\
+             COFFEE,9,Drinks,yes\n",
+        )
+        .unwrap();
+        path
+    }
+
+    fn import(path: &Path, update_existing: bool, dry_run: bool) -> MenuImportReport {
+        let options = MenuImportOptions { update_existing, dry_run, default_category: Some("Desserts".to_string()), sheet_name: None };
+        import_menu_csv(path.to_string_lossy().to_string(), Some(options)).unwrap()
+    }
+
+    fn menu_item(name: &str) -> (f64, String, bool) {
+        let conn = get_db_connection().unwrap();
+        conn.query_row("SELECT price, category, is_available FROM menu_items WHERE name = ?1", [name], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn a_dry_run_reports_the_rows_and_saves_nothing() {
+        let db = TestDb::new();
+        let preview = import(&menu_sheet(&db), false, true);
+        assert!(preview.created == 2 && preview.failed == 2, "dry run created {} and failed {} rows", preview.created, preview.failed);
+        let conn = get_db_connection().unwrap();
+        let saved: i64 = conn.query_row("SELECT COUNT(*) FROM menu_items", [], |row| row.get(0)).unwrap();
+        assert_eq!(saved, 0, "a dry run saved menu items");
+    }
+
+    #[test]
+    fn imported_items_fall_back_to_the_default_category() {
+        let db = TestDb::new();
+        let report = import(&menu_sheet(&db), false, false);
+        assert_eq!(report.rows[2].reason.as_deref(), Some("name is empty"), "blank name reported wrongly");
+        let (_, category, available) = menu_item("Cake");
+        assert!(category == "Desserts" && !available, "imported cake is {} and available={}", category, available);
+    }
+
+    #[test]
+    fn items_already_on_the_menu_are_skipped_or_updated() {
+        let db = TestDb::new();
+        let path = menu_sheet(&db);
+        import(&path, false, false);
+        assert_eq!(import(&path, false, false).skipped, 2, "a second import did not skip the items already on the menu");
+        std::fs::write(&path, "Name,Price\nCoffee,3.00\n").unwrap();
+        assert_eq!(import(&path, true, false).updated, 1, "an existing item was not updated");
+        let (price, category, _) = menu_item("Coffee");
+        assert!(same_amount(price, 3.0) && category == "Drinks", "updated coffee costs {:.2} under {}", price, category);
+    }
+}
@@ -0,0 +1,236 @@
+use tauri::command;
+use chrono::NaiveDateTime;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::business_hours::{parse_time, window_covers};
+use crate::db::{get_current_timestamp, get_db_connection};
+use crate::errors::AppError;
+use crate::models::{MenuPriceRule, MenuPriceRuleInput};
+use crate::validation::{EMPTY_FIELD, MENU_CATEGORY_NOT_FOUND, MENU_ITEM_NOT_FOUND, MENU_PRICE_RULE_NOT_FOUND, NEGATIVE_AMOUNT};
+
+const RULE_COLUMNS: &str = "r.id, r.name, r.menu_item_id, m.name, r.category, r.weekday_mask, r.start_time, r.end_time,
+    r.price, r.discount_percent";
+
+fn map_rule(row: &rusqlite::Row) -> rusqlite::Result<MenuPriceRule> {
+    Ok(MenuPriceRule {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        menu_item_id: row.get(2)?,
+        menu_item_name: row.get(3)?,
+        category: row.get(4)?,
+        weekday_mask: row.get(5)?,
+        start_time: row.get(6)?,
+        end_time: row.get(7)?,
+        price: row.get(8)?,
+        discount_percent: row.get(9)?,
+    })
+}
+
+fn load_rule(conn: &Connection, rule_id: i64) -> Result<MenuPriceRule, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM menu_price_rules r LEFT JOIN menu_items m ON m.id = r.menu_item_id WHERE r.id = ?1", RULE_COLUMNS),
+        params![rule_id],
+        map_rule,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::coded(MENU_PRICE_RULE_NOT_FOUND, format!("Price rule #{} not found", rule_id)))
+}
+
+/// The price `base_price` comes down to under one rule, to the cent.
+fn rule_price(price: Option<f64>, discount_percent: Option<f64>, base_price: f64) -> f64 {
+    let price = match (price, discount_percent) {
+        (Some(price), _) => price,
+        (None, Some(percent)) => base_price * (1.0 - percent / 100.0),
+        (None, None) => base_price,
+    };
+    (price * 100.0).round() / 100.0
+}
+
+/// The rule giving menu item `menu_item_id` its lowest price at `at`, and that
+/// price, when it beats `base_price`. Rules on the item and on its category
+/// are both considered.
+pub fn price_rule_for(conn: &Connection, menu_item_id: i64, base_price: f64, at: NaiveDateTime) -> Result<Option<(i64, f64)>, AppError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT r.id, r.weekday_mask, r.start_time, r.end_time, r.price, r.discount_percent
+         FROM menu_price_rules r JOIN menu_items m ON m.id = ?1
+         WHERE r.menu_item_id = m.id OR r.category = m.category",
+    )?;
+    let rules = stmt
+        .query_map(params![menu_item_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<f64>>(4)?,
+                row.get::<_, Option<f64>>(5)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut best: Option<(i64, f64)> = None;
+    for (rule_id, mask, start, end, price, discount_percent) in rules {
+        let (Ok(open), Ok(close)) = (parse_time(&start), parse_time(&end)) else {
+            continue;
+        };
+        let applies = (0..7u32).any(|weekday| (mask >> weekday) & 1 == 1 && window_covers(weekday, open, close, at));
+        if !applies {
+            continue;
+        }
+        let price = rule_price(price, discount_percent, base_price);
+        let lowest = best.map(|(_, lowest)| lowest).unwrap_or(base_price);
+        if price < lowest {
+            best = Some((rule_id, price));
+        }
+    }
+    Ok(best)
+}
+
+/// Price rules, item rules first, each group by name.
+#[command]
+pub fn get_menu_price_rules() -> Result<Vec<MenuPriceRule>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM menu_price_rules r LEFT JOIN menu_items m ON m.id = r.menu_item_id
+         ORDER BY r.menu_item_id IS NULL, r.name COLLATE NOCASE",
+        RULE_COLUMNS
+    ))?;
+    let rules = stmt.query_map([], map_rule)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(rules)
+}
+
+/// Add a happy-hour price for one menu item or a whole category. Orders taken
+/// on the rule's weekdays between its start and end time are charged the rule's
+/// price instead of the menu price, when that is lower.
+#[command]
+pub fn add_menu_price_rule(rule: MenuPriceRuleInput) -> Result<MenuPriceRule, AppError> {
+    let name = rule.name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Price rule name cannot be empty"));
+    }
+    if !(1..=127).contains(&rule.weekday_mask) {
+        return Err("Pick at least one weekday for the price rule".into());
+    }
+    let start = parse_time(&rule.start_time)?;
+    let end = parse_time(&rule.end_time)?;
+    if start == end {
+        return Err("Start and end time of a price rule must differ".into());
+    }
+    match (rule.price, rule.discount_percent) {
+        (Some(price), None) => {
+            if !price.is_finite() || price < 0.0 {
+                return Err(AppError::invalid(NEGATIVE_AMOUNT, "price", "Rule price must be >= 0"));
+            }
+        }
+        (None, Some(percent)) => {
+            if !percent.is_finite() || percent <= 0.0 || percent > 100.0 {
+                return Err("Discount must be more than 0% and at most 100%".into());
+            }
+        }
+        _ => return Err("Give a price rule either a price or a discount percentage".into()),
+    }
+
+    let conn = get_db_connection()?;
+    let category = match (rule.menu_item_id, rule.category.as_deref().map(str::trim)) {
+        (Some(menu_item_id), None) => {
+            let active: Option<bool> = conn
+                .query_row("SELECT is_active = 1 FROM menu_items WHERE id = ?1", params![menu_item_id], |row| row.get(0))
+                .optional()?;
+            if active != Some(true) {
+                return Err(AppError::coded(MENU_ITEM_NOT_FOUND, format!("Menu item #{} not found", menu_item_id)));
+            }
+            None
+        }
+        (None, Some(category)) if !category.is_empty() => {
+            let stored: String = conn
+                .query_row("SELECT name FROM menu_categories WHERE name = ?1 COLLATE NOCASE", params![category], |row| row.get(0))
+                .optional()?
+                .ok_or_else(|| AppError::coded(MENU_CATEGORY_NOT_FOUND, format!("Menu category '{}' not found", category)))?;
+            Some(stored)
+        }
+        _ => return Err("A price rule applies to either one menu item or one category".into()),
+    };
+
+    conn.execute(
+        "INSERT INTO menu_price_rules (name, menu_item_id, category, weekday_mask, start_time, end_time, price, discount_percent, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            name,
+            rule.menu_item_id,
+            category,
+            rule.weekday_mask,
+            start.format("%H:%M").to_string(),
+            end.format("%H:%M").to_string(),
+            rule.price,
+            rule.discount_percent,
+            get_current_timestamp()
+        ],
+    )?;
+    load_rule(&conn, conn.last_insert_rowid())
+}
+
+/// Delete a price rule. Orders already taken keep the price they were charged.
+#[command]
+pub fn delete_menu_price_rule(rule_id: i64) -> Result<(), AppError> {
+    let conn = get_db_connection()?;
+    load_rule(&conn, rule_id)?;
+    conn.execute("DELETE FROM menu_price_rules WHERE id = ?1", params![rule_id])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderItemInput;
+    use crate::simple_commands::{add_menu_item, get_menu_items, insert_food_order, order_item_details};
+    use crate::test_support::{order_item, same_amount, TestDb};
+
+    /// A rule for every day, in a few minutes around now (past midnight
+    /// when run just before it).
+    fn happy_hour(price: Option<f64>, discount_percent: Option<f64>, menu_item_id: Option<i64>, category: Option<&str>) -> MenuPriceRuleInput {
+        MenuPriceRuleInput {
+            name: "Happy hour".to_string(),
+            menu_item_id,
+            category: category.map(str::to_string),
+            weekday_mask: 127,
+            start_time: (chrono::Local::now() - chrono::Duration::minutes(1)).format("%H:%M").to_string(),
+            end_time: (chrono::Local::now() + chrono::Duration::minutes(3)).format("%H:%M").to_string(),
+            price,
+            discount_percent,
+        }
+    }
+
+    fn beer() -> i64 {
+        add_menu_item("Beer".to_string(), 5.0, "Bar".to_string(), None, None, None, None).unwrap()
+    }
+
+    #[test]
+    fn a_rule_sets_a_price_or_a_discount_but_not_both() {
+        let _db = TestDb::new();
+        let beer = beer();
+        assert!(add_menu_price_rule(happy_hour(Some(3.0), Some(10.0), Some(beer), None)).is_err(), "rule with both a price and a discount accepted");
+        let percent = add_menu_price_rule(happy_hour(None, Some(20.0), None, Some("bAR"))).unwrap();
+        assert_eq!(percent.category.as_deref(), Some("Bar"), "category rule not stored under the category's name");
+        assert_eq!(get_menu_price_rules().unwrap().len(), 1, "new price rule not listed");
+    }
+
+    #[test]
+    fn the_lowest_rule_price_is_shown_and_charged() {
+        let _db = TestDb::new();
+        let beer = beer();
+        add_menu_price_rule(happy_hour(Some(4.5), None, Some(beer), None)).unwrap();
+        let percent = add_menu_price_rule(happy_hour(None, Some(20.0), None, Some("Bar"))).unwrap();
+        let listed = get_menu_items().unwrap().into_iter().find(|m| m.id == beer).and_then(|m| m.rule_price);
+        assert!(listed.is_some_and(|p| same_amount(p, 4.0)), "menu shows happy-hour price {:?}, expected 4.00", listed);
+
+        let item = OrderItemInput { menu_item_id: Some(beer), ..order_item("Beer", 5.0, 2) };
+        let (order_id, _) = insert_food_order(None, "walk_in".to_string(), None, vec![item], None, None, None).unwrap();
+        let conn = get_db_connection().unwrap();
+        let lines = order_item_details(&conn, order_id).unwrap();
+        let line = lines.first().expect("order has no lines");
+        assert!(same_amount(line.unit_price, 4.0) && same_amount(line.line_total, 8.0), "happy-hour line charged {:.2} each", line.unit_price);
+        assert!(
+            line.original_unit_price.is_some_and(|p| same_amount(p, 5.0)) && line.price_rule_id == Some(percent.id),
+            "line does not record its menu price and the lowest rule"
+        );
+    }
+}
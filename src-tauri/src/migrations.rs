@@ -0,0 +1,798 @@
+use tauri::command;
+use std::collections::BTreeSet;
+use std::time::Instant;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::models::{MigrationRecord, PostUpdateReport};
+
+pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A schema change applied once, in id order. `run` returns the number of rows it
+/// touched (0 for pure DDL) so long backfills show up in the post-update report.
+struct Migration {
+    id: i64,
+    name: &'static str,
+    run: fn(&Connection) -> SqliteResult<usize>,
+}
+
+// Append new migrations at the end; never renumber or edit one that has shipped.
+const MIGRATIONS: &[Migration] = &[
+    Migration { id: 1, name: "guest party size", run: m0001_guest_party_size },
+    Migration { id: 2, name: "owner drawings", run: m0002_owner_drawings },
+    Migration { id: 3, name: "after-hours flag", run: m0003_after_hours_flag },
+    Migration { id: 4, name: "order payments", run: m0004_order_payments },
+    Migration { id: 5, name: "checkout payments", run: m0005_checkout_payments },
+    Migration { id: 6, name: "audit trail", run: m0006_audit_trail },
+    Migration { id: 7, name: "order notes", run: m0007_order_notes },
+    Migration { id: 8, name: "order status", run: m0008_order_status },
+    Migration { id: 9, name: "order tables", run: m0009_order_tables },
+    Migration { id: 10, name: "line item notes", run: m0010_line_item_notes },
+    Migration { id: 11, name: "ingredient cost", run: m0011_ingredient_cost },
+    Migration { id: 12, name: "nightly pricing", run: m0012_nightly_pricing },
+    Migration { id: 13, name: "guest deposits", run: m0013_guest_deposits },
+    Migration { id: 14, name: "payment refunds", run: m0014_payment_refunds },
+    Migration { id: 15, name: "checkout totals", run: m0015_checkout_totals },
+    Migration { id: 16, name: "named taxes", run: m0016_named_taxes },
+    Migration { id: 17, name: "service charge", run: m0017_service_charge },
+    Migration { id: 18, name: "settings timestamps", run: m0018_settings_timestamps },
+    Migration { id: 19, name: "search index", run: m0019_search_index },
+    Migration { id: 20, name: "shift payments", run: m0020_shift_payments },
+    Migration { id: 21, name: "expense categories", run: m0021_expense_categories },
+    Migration { id: 22, name: "expense tax", run: m0022_expense_tax },
+    Migration { id: 23, name: "guest profiles", run: m0023_guest_profiles },
+    Migration { id: 24, name: "guest anonymization", run: m0024_guest_anonymization },
+    Migration { id: 25, name: "group bookings", run: m0025_group_bookings },
+    Migration { id: 26, name: "room types", run: m0026_room_types },
+    Migration { id: 27, name: "room floor and building", run: m0027_room_location },
+    Migration { id: 28, name: "imported orders", run: m0028_imported_orders },
+    Migration { id: 29, name: "menu categories", run: m0029_menu_categories },
+    Migration { id: 30, name: "combo order lines", run: m0030_combo_lines },
+    Migration { id: 31, name: "menu price rules on order lines", run: m0031_line_price_rules },
+    Migration { id: 32, name: "trash for orders and expenses", run: m0032_soft_delete },
+    Migration { id: 33, name: "company charge reversals", run: m0033_company_charge_reversals },
+    Migration { id: 34, name: "order rounding", run: m0034_order_rounding },
+    Migration { id: 35, name: "checkout times", run: m0035_checkout_times },
+    Migration { id: 36, name: "tax timestamps", run: m0036_tax_timestamps },
+    Migration { id: 37, name: "order taxes", run: m0037_order_taxes },
+    Migration { id: 38, name: "price rule timestamps", run: m0038_price_rule_timestamps },
+    Migration { id: 39, name: "checkout revenue split", run: m0039_checkout_revenue_split },
+];
+
+fn m0001_guest_party_size(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "customers", "adults", "INTEGER NOT NULL DEFAULT 1")?;
+    add_column_if_missing(conn, "customers", "children", "INTEGER NOT NULL DEFAULT 0")?;
+    Ok(0)
+}
+
+fn m0002_owner_drawings(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "expenses", "is_drawing", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "shifts", "owner_drawings", "REAL DEFAULT 0.0")?;
+    Ok(0)
+}
+
+fn m0003_after_hours_flag(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sales", "after_hours", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "customers", "after_hours", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "shifts", "after_hours_count", "INTEGER DEFAULT 0")?;
+    Ok(0)
+}
+
+/// Orders already marked paid become one cash payment of the full amount.
+fn m0004_order_payments(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sales", "amount_paid", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "shifts", "cash_sales", "REAL DEFAULT 0.0")?;
+    let backfilled = conn.execute(
+        "INSERT INTO payments (order_id, amount, method, paid_at, note, created_at)
+         SELECT id, total_amount, 'cash', COALESCE(paid_at, created_at, CURRENT_TIMESTAMP), 'Recorded before split payments', CURRENT_TIMESTAMP
+         FROM sales s
+         WHERE paid = 1 AND total_amount > 0
+           AND NOT EXISTS (SELECT 1 FROM payments p WHERE p.order_id = s.id)",
+        [],
+    )?;
+    conn.execute("UPDATE sales SET amount_paid = total_amount WHERE paid = 1", [])?;
+    Ok(backfilled)
+}
+
+/// Checkout payments belong to a guest rather than an order, so payments.order_id
+/// becomes optional; SQLite can't relax NOT NULL in place, so the table is rebuilt.
+fn m0005_checkout_payments(conn: &Connection) -> SqliteResult<usize> {
+    if add_column_if_missing(conn, "payments", "guest_id", "INTEGER")? {
+        conn.execute_batch(
+            "CREATE TABLE payments_rebuilt (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                order_id INTEGER,
+                guest_id INTEGER,
+                amount REAL NOT NULL CHECK (amount > 0),
+                method TEXT NOT NULL CHECK (method IN ('cash', 'card', 'bank_transfer')),
+                paid_at DATETIME NOT NULL,
+                note TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (order_id) REFERENCES sales(id) ON DELETE CASCADE,
+                FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL
+            );
+            INSERT INTO payments_rebuilt (id, order_id, guest_id, amount, method, paid_at, note, created_at)
+                SELECT id, order_id, guest_id, amount, method, paid_at, note, created_at FROM payments;
+            DROP TABLE payments;
+            ALTER TABLE payments_rebuilt RENAME TO payments;
+            CREATE INDEX IF NOT EXISTS idx_payments_order_id ON payments(order_id);
+            CREATE INDEX IF NOT EXISTS idx_payments_paid_at ON payments(paid_at);
+            CREATE INDEX IF NOT EXISTS idx_payments_guest_id ON payments(guest_id);",
+        )?;
+    }
+    Ok(0)
+}
+
+fn m0006_audit_trail(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "audit_log", "entity", "TEXT")?;
+    add_column_if_missing(conn, "audit_log", "entity_id", "INTEGER")?;
+    add_column_if_missing(conn, "audit_log", "action", "TEXT")?;
+    add_column_if_missing(conn, "audit_log", "old_values", "TEXT")?;
+    add_column_if_missing(conn, "audit_log", "new_values", "TEXT")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON audit_log(entity, entity_id);
+         CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp ON audit_log(timestamp);",
+    )?;
+    Ok(0)
+}
+
+fn m0007_order_notes(conn: &Connection) -> SqliteResult<usize> {
+    Ok(add_column_if_missing(conn, "sales", "notes", "TEXT")? as usize)
+}
+
+/// Orders taken before status tracking have already left the kitchen: paid
+/// ones are closed, the rest served.
+fn m0008_order_status(conn: &Connection) -> SqliteResult<usize> {
+    let added = add_column_if_missing(conn, "sales", "status", "TEXT NOT NULL DEFAULT 'pending'")?;
+    add_column_if_missing(conn, "sales", "preparing_at", "DATETIME")?;
+    add_column_if_missing(conn, "sales", "served_at", "DATETIME")?;
+    add_column_if_missing(conn, "sales", "closed_at", "DATETIME")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_sales_status ON sales(status)", [])?;
+    if !added {
+        return Ok(0);
+    }
+    conn.execute(
+        "UPDATE sales SET status = CASE WHEN paid = 1 THEN 'closed' ELSE 'served' END,
+                          closed_at = CASE WHEN paid = 1 THEN COALESCE(paid_at, created_at) END",
+        [],
+    )
+}
+
+fn m0009_order_tables(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sales", "table_id", "INTEGER REFERENCES dining_tables(id) ON DELETE SET NULL")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_sales_table_id ON sales(table_id)", [])?;
+    Ok(0)
+}
+
+fn m0010_line_item_notes(conn: &Connection) -> SqliteResult<usize> {
+    Ok(add_column_if_missing(conn, "sale_items", "notes", "TEXT")? as usize)
+}
+
+/// Ingredients already received on a purchase order start at their latest cost.
+fn m0011_ingredient_cost(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "inventory_items", "unit_cost", "REAL NOT NULL DEFAULT 0")?;
+    conn.execute(
+        "UPDATE inventory_items SET unit_cost = (
+             SELECT l.unit_cost FROM purchase_order_lines l
+             WHERE l.inventory_item_id = inventory_items.id AND l.received_quantity > 0
+             ORDER BY l.id DESC LIMIT 1)
+         WHERE unit_cost = 0
+           AND EXISTS (SELECT 1 FROM purchase_order_lines l
+                       WHERE l.inventory_item_id = inventory_items.id AND l.received_quantity > 0)",
+        [],
+    )
+}
+
+/// Guests already in house keep their flat daily rate; only new stays are priced per night.
+fn m0012_nightly_pricing(conn: &Connection) -> SqliteResult<usize> {
+    Ok(add_column_if_missing(conn, "customers", "nightly_pricing", "INTEGER NOT NULL DEFAULT 0")? as usize)
+}
+
+/// Deposits are guest payments taken before checkout; the checkout settlement is the rest.
+fn m0013_guest_deposits(conn: &Connection) -> SqliteResult<usize> {
+    Ok(add_column_if_missing(conn, "payments", "is_deposit", "INTEGER NOT NULL DEFAULT 0")? as usize)
+}
+
+/// Refunds are negative payment rows pointing at the payment they reverse. The
+/// amount CHECK has to allow them, and SQLite can't change a CHECK in place.
+fn m0014_payment_refunds(conn: &Connection) -> SqliteResult<usize> {
+    if add_column_if_missing(conn, "payments", "refund_of", "INTEGER")? {
+        conn.execute_batch(
+            "CREATE TABLE payments_rebuilt (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                order_id INTEGER,
+                guest_id INTEGER,
+                amount REAL NOT NULL CHECK (amount > 0 OR refund_of IS NOT NULL),
+                method TEXT NOT NULL CHECK (method IN ('cash', 'card', 'bank_transfer')),
+                paid_at DATETIME NOT NULL,
+                note TEXT,
+                is_deposit INTEGER NOT NULL DEFAULT 0,
+                refund_of INTEGER,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (order_id) REFERENCES sales(id) ON DELETE CASCADE,
+                FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL,
+                FOREIGN KEY (refund_of) REFERENCES payments(id) ON DELETE CASCADE
+            );
+            INSERT INTO payments_rebuilt (id, order_id, guest_id, amount, method, paid_at, note, is_deposit, created_at)
+                SELECT id, order_id, guest_id, amount, method, paid_at, note, is_deposit, created_at FROM payments;
+            DROP TABLE payments;
+            ALTER TABLE payments_rebuilt RENAME TO payments;
+            CREATE INDEX IF NOT EXISTS idx_payments_order_id ON payments(order_id);
+            CREATE INDEX IF NOT EXISTS idx_payments_paid_at ON payments(paid_at);
+            CREATE INDEX IF NOT EXISTS idx_payments_guest_id ON payments(guest_id);
+            CREATE INDEX IF NOT EXISTS idx_payments_refund_of ON payments(refund_of);",
+        )?;
+    }
+    Ok(0)
+}
+
+/// What the guest was billed at checkout, after discounts, so later payments can be
+/// checked against it. Older checkouts stay NULL and aren't chased.
+fn m0015_checkout_totals(conn: &Connection) -> SqliteResult<usize> {
+    Ok(add_column_if_missing(conn, "customers", "checkout_total", "REAL")? as usize)
+}
+
+/// The single tax_rate setting becomes a tax called "Tax" on the whole bill
+/// (5% if it was never set, as before); the tax_enabled switch stays.
+fn m0016_named_taxes(conn: &Connection) -> SqliteResult<usize> {
+    let seeded = conn.execute(
+        "INSERT INTO taxes (name, percent, applies_to, created_at)
+         SELECT 'Tax', COALESCE((SELECT CAST(value AS REAL) FROM settings WHERE key = 'tax_rate'), 5.0), 'all', CURRENT_TIMESTAMP
+         WHERE NOT EXISTS (SELECT 1 FROM taxes)",
+        [],
+    )?;
+    conn.execute("DELETE FROM settings WHERE key = 'tax_rate'", [])?;
+    Ok(seeded)
+}
+
+/// Orders carry the service charge rate they were placed at, so voids recompute
+/// it the same way; total_amount includes the charge.
+fn m0017_service_charge(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sales", "service_charge_percent", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "sales", "service_charge", "REAL NOT NULL DEFAULT 0")?;
+    Ok(0)
+}
+
+// Settings tables from before updated_at existed; every settings write sets it
+fn m0018_settings_timestamps(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "settings", "updated_at", "TEXT NOT NULL DEFAULT ''")?;
+    Ok(0)
+}
+
+/// FTS5 index behind global_search, kept current by triggers. Each row's rowid is
+/// the source id * 8 + a per-kind number so triggers can replace it without a scan.
+fn m0019_search_index(conn: &Connection) -> SqliteResult<usize> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+             kind UNINDEXED, entity_id UNINDEXED, title, detail,
+             tokenize = 'unicode61 remove_diacritics 2'
+         );
+
+         CREATE TRIGGER IF NOT EXISTS search_customers_insert AFTER INSERT ON customers BEGIN
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             VALUES (NEW.id * 8 + 1, 'guest', NEW.id, NEW.name, COALESCE(NEW.phone, ''));
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_customers_update AFTER UPDATE OF name, phone ON customers BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 1;
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             VALUES (NEW.id * 8 + 1, 'guest', NEW.id, NEW.name, COALESCE(NEW.phone, ''));
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_customers_delete AFTER DELETE ON customers BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 1;
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS search_sales_insert AFTER INSERT ON sales
+         WHEN TRIM(COALESCE(NEW.customer_name, '')) != '' BEGIN
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             VALUES (NEW.id * 8 + 2, 'order', NEW.id, NEW.customer_name, '');
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_sales_update AFTER UPDATE OF customer_name ON sales BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 2;
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             SELECT NEW.id * 8 + 2, 'order', NEW.id, NEW.customer_name, ''
+             WHERE TRIM(COALESCE(NEW.customer_name, '')) != '';
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_sales_delete AFTER DELETE ON sales BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 2;
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS search_menu_items_insert AFTER INSERT ON menu_items
+         WHEN NEW.is_active = 1 BEGIN
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             VALUES (NEW.id * 8 + 3, 'menu_item', NEW.id, NEW.name, '');
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_menu_items_update AFTER UPDATE OF name, is_active ON menu_items BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 3;
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             SELECT NEW.id * 8 + 3, 'menu_item', NEW.id, NEW.name, '' WHERE NEW.is_active = 1;
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_menu_items_delete AFTER DELETE ON menu_items BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 3;
+         END;
+
+         CREATE TRIGGER IF NOT EXISTS search_expenses_insert AFTER INSERT ON expenses
+         WHEN TRIM(COALESCE(NEW.description, '')) != '' BEGIN
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             VALUES (NEW.id * 8 + 4, 'expense', NEW.id, NEW.description, NEW.category);
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_expenses_update AFTER UPDATE OF description, category ON expenses BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 4;
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             SELECT NEW.id * 8 + 4, 'expense', NEW.id, NEW.description, NEW.category
+             WHERE TRIM(COALESCE(NEW.description, '')) != '';
+         END;
+         CREATE TRIGGER IF NOT EXISTS search_expenses_delete AFTER DELETE ON expenses BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 4;
+         END;",
+    )?;
+
+    crate::search::rebuild_search_index(conn)
+}
+
+/// Tie each payment to the shift open when it was taken, so a shift's takings
+/// don't depend on clock times. A trigger does it so every way a payment is
+/// recorded, refunds included, is covered. Earlier payments are matched to
+/// shifts by time.
+fn m0020_shift_payments(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "shifts", "reconciliation", "TEXT")?;
+    add_column_if_missing(conn, "payments", "shift_id", "INTEGER")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_payments_shift_id ON payments(shift_id);
+         CREATE TRIGGER IF NOT EXISTS payments_assign_shift AFTER INSERT ON payments
+         WHEN NEW.shift_id IS NULL BEGIN
+             UPDATE payments
+             SET shift_id = (SELECT id FROM shifts WHERE status = 'open' ORDER BY id DESC LIMIT 1)
+             WHERE id = NEW.id;
+         END;",
+    )?;
+    conn.execute(
+        "UPDATE payments
+         SET shift_id = (
+             SELECT s.id FROM shifts s
+             WHERE payments.paid_at >= s.opened_at AND (s.closed_at IS NULL OR payments.paid_at <= s.closed_at)
+             ORDER BY s.id DESC LIMIT 1
+         )
+         WHERE shift_id IS NULL",
+        [],
+    )
+}
+
+/// Start the managed category list with the usual categories plus every one
+/// already used, so existing expenses stay valid. Typos come across as they are,
+/// to be merged into the right category afterwards.
+fn m0021_expense_categories(conn: &Connection) -> SqliteResult<usize> {
+    let mut seeded = 0;
+    for name in crate::expense_categories::DEFAULT_CATEGORIES {
+        seeded += conn.execute("INSERT OR IGNORE INTO expense_categories (name) VALUES (?1)", [name])?;
+    }
+    seeded += conn.execute(
+        "INSERT OR IGNORE INTO expense_categories (name)
+         SELECT DISTINCT TRIM(category) FROM expenses WHERE TRIM(category) != ''
+         UNION SELECT category FROM expense_budgets",
+        [],
+    )?;
+    Ok(seeded)
+}
+
+fn m0022_expense_tax(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "expenses", "tax_amount", "REAL NOT NULL DEFAULT 0")?;
+    Ok(0)
+}
+
+/// Every existing stay gets a profile: stays sharing a phone number share one,
+/// stays without a phone get one each.
+fn m0023_guest_profiles(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "customers", "profile_id", "INTEGER REFERENCES guest_profiles(id) ON DELETE SET NULL")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_customers_profile_id ON customers(profile_id);
+         CREATE INDEX IF NOT EXISTS idx_guest_profiles_phone_key ON guest_profiles(phone_key);",
+    )?;
+    let stays: Vec<(i64, String, Option<String>)> = {
+        let mut stmt = conn.prepare("SELECT id, name, phone FROM customers WHERE profile_id IS NULL ORDER BY id")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.collect::<SqliteResult<_>>()?
+    };
+    for (stay_id, name, phone) in &stays {
+        let profile_id = crate::guest_profiles::profile_for_stay(conn, name, phone.as_deref())?;
+        conn.execute("UPDATE customers SET profile_id = ?1 WHERE id = ?2", params![profile_id, stay_id])?;
+    }
+    Ok(stays.len())
+}
+
+fn m0024_guest_anonymization(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "guest_profiles", "anonymized_at", "TEXT")?;
+    Ok(0)
+}
+
+fn m0025_group_bookings(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "customers", "group_id", "INTEGER REFERENCES guest_groups(id) ON DELETE SET NULL")?;
+    add_column_if_missing(conn, "reservations", "group_id", "INTEGER REFERENCES guest_groups(id) ON DELETE SET NULL")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_customers_group_id ON customers(group_id);
+         CREATE INDEX IF NOT EXISTS idx_reservations_group_id ON reservations(group_id);",
+    )?;
+    Ok(0)
+}
+
+/// Every type already given to a bookable room becomes a managed room type,
+/// priced at the average rate of its rooms.
+fn m0026_room_types(conn: &Connection) -> SqliteResult<usize> {
+    conn.execute(
+        "INSERT OR IGNORE INTO room_types (name, base_rate)
+         SELECT TRIM(room_type), ROUND(AVG(daily_rate), 2) FROM resources
+         WHERE is_active = 1 AND TRIM(room_type) != ''
+         GROUP BY TRIM(room_type) COLLATE NOCASE",
+        [],
+    )
+}
+
+fn m0027_room_location(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "resources", "floor", "INTEGER")?;
+    add_column_if_missing(conn, "resources", "building", "TEXT")?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_resources_location ON resources(building, floor)", [])?;
+    Ok(0)
+}
+
+/// Orders brought in from other software carry a reference to what they were
+/// there, so importing the same data twice doesn't duplicate them.
+fn m0028_imported_orders(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sales", "import_ref", "TEXT")?;
+    conn.execute("CREATE UNIQUE INDEX IF NOT EXISTS idx_sales_import_ref ON sales(import_ref)", [])?;
+    Ok(0)
+}
+
+/// Every category the menu already uses becomes a managed one, in
+/// alphabetical order until they're rearranged.
+fn m0029_menu_categories(conn: &Connection) -> SqliteResult<usize> {
+    conn.execute(
+        "INSERT OR IGNORE INTO menu_categories (name, sort_order)
+         SELECT name, ROW_NUMBER() OVER (ORDER BY name COLLATE NOCASE) FROM (
+             SELECT TRIM(category) AS name FROM menu_items
+             WHERE TRIM(category) != ''
+             GROUP BY TRIM(category) COLLATE NOCASE
+         )",
+        [],
+    )
+}
+
+fn m0030_combo_lines(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sale_items", "combo_id", "INTEGER REFERENCES combos(id) ON DELETE SET NULL")?;
+    Ok(0)
+}
+
+fn m0031_line_price_rules(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sale_items", "original_unit_price", "REAL")?;
+    add_column_if_missing(conn, "sale_items", "price_rule_id", "INTEGER REFERENCES menu_price_rules(id) ON DELETE SET NULL")?;
+    Ok(0)
+}
+
+fn m0032_soft_delete(conn: &Connection) -> SqliteResult<usize> {
+    for table in ["sales", "expenses"] {
+        add_column_if_missing(conn, table, "deleted_at", "DATETIME")?;
+        add_column_if_missing(conn, table, "deleted_by", "TEXT")?;
+    }
+    // Trashed orders and expenses drop out of search, and come back on restore
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS search_sales_update;
+         CREATE TRIGGER search_sales_update AFTER UPDATE OF customer_name, deleted_at ON sales BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 2;
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             SELECT NEW.id * 8 + 2, 'order', NEW.id, NEW.customer_name, ''
+             WHERE TRIM(COALESCE(NEW.customer_name, '')) != '' AND NEW.deleted_at IS NULL;
+         END;
+
+         DROP TRIGGER IF EXISTS search_expenses_update;
+         CREATE TRIGGER search_expenses_update AFTER UPDATE OF description, category, deleted_at ON expenses BEGIN
+             DELETE FROM search_index WHERE rowid = OLD.id * 8 + 4;
+             INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+             SELECT NEW.id * 8 + 4, 'expense', NEW.id, NEW.description, NEW.category
+             WHERE TRIM(COALESCE(NEW.description, '')) != '' AND NEW.deleted_at IS NULL;
+         END;",
+    )?;
+    Ok(0)
+}
+
+/// A reopened checkout reverses what it posted to a company account with a
+/// negative row pointing at the charge, as refunds do for payments.
+fn m0033_company_charge_reversals(conn: &Connection) -> SqliteResult<usize> {
+    if add_column_if_missing(conn, "company_charges", "reversal_of", "INTEGER")? {
+        conn.execute_batch(
+            "CREATE TABLE company_charges_rebuilt (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                company_id INTEGER NOT NULL,
+                guest_id INTEGER,
+                amount REAL NOT NULL CHECK (amount > 0 OR reversal_of IS NOT NULL),
+                description TEXT NOT NULL,
+                posted_on TEXT NOT NULL,
+                created_by TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                reversal_of INTEGER,
+                FOREIGN KEY (company_id) REFERENCES companies(id),
+                FOREIGN KEY (guest_id) REFERENCES customers(id) ON DELETE SET NULL,
+                FOREIGN KEY (reversal_of) REFERENCES company_charges(id) ON DELETE CASCADE
+            );
+            INSERT INTO company_charges_rebuilt (id, company_id, guest_id, amount, description, posted_on, created_by, created_at)
+                SELECT id, company_id, guest_id, amount, description, posted_on, created_by, created_at FROM company_charges;
+            DROP TABLE company_charges;
+            ALTER TABLE company_charges_rebuilt RENAME TO company_charges;
+            CREATE INDEX IF NOT EXISTS idx_company_charges_company ON company_charges(company_id, posted_on);",
+        )?;
+    }
+    Ok(0)
+}
+
+fn m0034_order_rounding(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sales", "rounding", "REAL NOT NULL DEFAULT 0")?;
+    Ok(0)
+}
+
+/// When a stay was checked out and the check-out date it had before, so a
+/// checkout can be reopened. Stays checked out earlier have neither and
+/// can't be.
+fn m0035_checkout_times(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "customers", "checked_out_at", "TEXT")?;
+    add_column_if_missing(conn, "customers", "planned_check_out", "TEXT")?;
+    Ok(0)
+}
+
+/// When each tax was last changed, so LAN sync keeps the newer rate. Taxes
+/// from before fall back to when they were added.
+fn m0036_tax_timestamps(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "taxes", "updated_at", "TEXT")?;
+    Ok(0)
+}
+
+/// Walk-in and counter orders are taxed when placed, as they never reach a
+/// checkout; earlier orders carry no tax.
+fn m0037_order_taxes(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "sales", "tax_amount", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "tax_charges", "order_id", "INTEGER REFERENCES sales(id) ON DELETE CASCADE")?;
+    Ok(0)
+}
+
+/// When each menu price rule was last changed, so LAN sync keeps the newer
+/// copy of a rule. Rules from before fall back to when they were added.
+fn m0038_price_rule_timestamps(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "menu_price_rules", "updated_at", "TEXT")?;
+    Ok(0)
+}
+
+/// The food and service charge on each checkout bill, so the ledger books them
+/// apart from room revenue. Earlier checkouts stay all room revenue.
+fn m0039_checkout_revenue_split(conn: &Connection) -> SqliteResult<usize> {
+    add_column_if_missing(conn, "customers", "checkout_food", "REAL")?;
+    add_column_if_missing(conn, "customers", "checkout_service_charge", "REAL")?;
+    Ok(0)
+}
+
+/// ALTER TABLE ... ADD COLUMN, skipped when the column is already there (fresh installs
+/// get it from create_initial_schema).
+pub fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> SqliteResult<bool> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, column],
+            |_| Ok(true),
+        )
+        .optional()?
+        .unwrap_or(false);
+    if exists {
+        return Ok(false);
+    }
+    conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+    Ok(true)
+}
+
+fn column_snapshot(conn: &Connection) -> SqliteResult<BTreeSet<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT m.name || '.' || p.name
+         FROM sqlite_master m, pragma_table_info(m.name) p
+         WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%'",
+    )?;
+    let columns = stmt.query_map([], |row| row.get(0))?.collect();
+    columns
+}
+
+fn settings_snapshot(conn: &Connection) -> SqliteResult<BTreeSet<String>> {
+    let mut stmt = conn.prepare("SELECT key FROM settings")?;
+    let keys = stmt.query_map([], |row| row.get(0))?.collect();
+    keys
+}
+
+fn read_setting(conn: &Connection, key: &str) -> SqliteResult<Option<String>> {
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()
+}
+
+fn write_setting(conn: &Connection, key: &str, value: &str) -> SqliteResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)",
+        params![key, value, get_current_timestamp()],
+    )?;
+    Ok(())
+}
+
+/// Apply every migration not yet in schema_migrations, recording how long each took,
+/// how many rows it touched and which columns and settings it added. Also notes when
+/// the app version changed so the next launch can show what the update did.
+pub fn run_pending_migrations(conn: &Connection) -> SqliteResult<()> {
+    let applied: BTreeSet<i64> = {
+        let mut stmt = conn.prepare("SELECT id FROM schema_migrations")?;
+        let ids = stmt.query_map([], |row| row.get(0))?.collect::<SqliteResult<_>>()?;
+        ids
+    };
+
+    for migration in MIGRATIONS.iter().filter(|m| !applied.contains(&m.id)) {
+        println!("Applying migration {} ({})...", migration.id, migration.name);
+        let columns_before = column_snapshot(conn)?;
+        let settings_before = settings_snapshot(conn)?;
+        let started = Instant::now();
+
+        let tx = conn.unchecked_transaction()?;
+        let rows_affected = (migration.run)(&tx)?;
+        let schema_changes: Vec<String> = column_snapshot(&tx)?.difference(&columns_before).cloned().collect();
+        let seeded_settings: Vec<String> = settings_snapshot(&tx)?.difference(&settings_before).cloned().collect();
+
+        tx.execute(
+            "INSERT INTO schema_migrations (id, name, app_version, applied_at, duration_ms, rows_affected, schema_changes, seeded_settings)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                migration.id,
+                migration.name,
+                APP_VERSION,
+                get_current_timestamp(),
+                started.elapsed().as_millis() as i64,
+                rows_affected as i64,
+                serde_json::to_string(&schema_changes).unwrap_or_else(|_| "[]".to_string()),
+                serde_json::to_string(&seeded_settings).unwrap_or_else(|_| "[]".to_string()),
+            ],
+        )?;
+        tx.commit()?;
+    }
+
+    // A changed version on an existing install is an update; a first install is not.
+    match read_setting(conn, "app_version")? {
+        Some(previous) if previous != APP_VERSION => {
+            write_setting(conn, "previous_app_version", &previous)?;
+            write_setting(conn, "update_report_pending", "1")?;
+        }
+        _ => {}
+    }
+    write_setting(conn, "app_version", APP_VERSION)?;
+
+    Ok(())
+}
+
+fn load_migrations(conn: &Connection, app_version: Option<&str>) -> Result<Vec<MigrationRecord>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, app_version, applied_at, duration_ms, rows_affected, schema_changes, seeded_settings
+             FROM schema_migrations
+             WHERE ?1 IS NULL OR app_version = ?1
+             ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![app_version], |row| {
+            Ok(MigrationRecord {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                app_version: row.get(2)?,
+                applied_at: row.get(3)?,
+                duration_ms: row.get(4)?,
+                rows_affected: row.get(5)?,
+                schema_changes: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+                seeded_settings: serde_json::from_str(&row.get::<_, String>(7)?).unwrap_or_default(),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// What the current version's migrations did, built from schema_migrations.
+pub fn build_post_update_report(conn: &Connection) -> Result<PostUpdateReport, String> {
+    let previous_version = read_setting(conn, "previous_app_version").map_err(|e| e.to_string())?;
+    let pending = read_setting(conn, "update_report_pending").map_err(|e| e.to_string())?.as_deref() == Some("1");
+    let migrations = load_migrations(conn, Some(APP_VERSION))?;
+
+    let total_rows_affected = migrations.iter().map(|m| m.rows_affected).sum();
+    let total_duration_ms = migrations.iter().map(|m| m.duration_ms).sum();
+
+    let mut summary = format!("Updated to {}", APP_VERSION);
+    if !migrations.is_empty() {
+        let names: Vec<&str> = migrations.iter().map(|m| m.name.as_str()).collect();
+        summary.push_str(&format!(" \u{2014} {} added", names.join(", ")));
+    }
+    if total_rows_affected > 0 {
+        summary.push_str(&format!("; backfill touched {} rows", total_rows_affected));
+    }
+
+    Ok(PostUpdateReport {
+        previous_version,
+        current_version: APP_VERSION.to_string(),
+        pending,
+        migrations,
+        total_rows_affected,
+        total_duration_ms,
+        summary,
+    })
+}
+
+#[command]
+pub fn get_migration_history() -> Result<Vec<MigrationRecord>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    load_migrations(&conn, None)
+}
+
+#[command]
+pub fn post_update_report() -> Result<PostUpdateReport, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    build_post_update_report(&conn)
+}
+
+/// Called once the UI has shown the update notice, so it isn't shown again.
+#[command]
+pub fn acknowledge_post_update_report() -> Result<(), String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM settings WHERE key = 'update_report_pending'", [])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+
+    /// Wind the test database back to a 0.9.0 install that predates menu
+    /// categories and order rounding, with menu items whose categories need
+    /// backfilling, then upgrade it.
+    fn upgrade_from_0_9_0() -> crate::db::PooledConnection {
+        let conn = get_db_connection().unwrap();
+        conn.execute_batch(
+            "DELETE FROM schema_migrations WHERE id IN (29, 34);
+             UPDATE schema_migrations SET app_version = '0.9.0';
+             DELETE FROM menu_categories;
+             ALTER TABLE sales DROP COLUMN rounding;
+             INSERT INTO menu_items (name, price, category, is_available) VALUES
+                 ('Old tea', 2.0, 'Drinks', 1), ('Old juice', 3.0, 'drinks', 1), ('Old crisps', 1.0, 'Snacks', 1);
+             INSERT OR REPLACE INTO settings (key, value) VALUES ('app_version', '0.9.0');
+             DELETE FROM settings WHERE key IN ('previous_app_version', 'update_report_pending');",
+        )
+        .unwrap();
+        run_pending_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn the_history_records_what_each_upgrade_changed() {
+        let _db = TestDb::new();
+        upgrade_from_0_9_0();
+        let history = get_migration_history().unwrap();
+        let ids: Vec<i64> = history.iter().map(|m| m.id).collect();
+        assert_eq!(ids, (1..=MIGRATIONS.len() as i64).collect::<Vec<_>>(), "history doesn't list every migration once");
+        let upgraded: Vec<(i64, &str)> = history.iter().filter(|m| m.app_version == APP_VERSION).map(|m| (m.id, m.name.as_str())).collect();
+        assert_eq!(upgraded, vec![(29, "menu categories"), (34, "order rounding")], "migrations recorded for this version");
+        assert_eq!(history[28].rows_affected, 2, "menu category backfill recorded the wrong row count");
+        assert_eq!(history[33].schema_changes, vec!["sales.rounding".to_string()], "order rounding recorded the wrong changes");
+    }
+
+    #[test]
+    fn an_upgrade_is_reported_once() {
+        let _db = TestDb::new();
+        let conn = upgrade_from_0_9_0();
+        let report = post_update_report().unwrap();
+        assert!(report.previous_version.as_deref() == Some("0.9.0") && report.pending, "update from 0.9.0 not reported as pending");
+        assert!(
+            report.migrations.len() == 2 && report.total_rows_affected == 2,
+            "report covers {} migrations and {} rows", report.migrations.len(), report.total_rows_affected
+        );
+        let summary = format!("Updated to {} \u{2014} menu categories, order rounding added; backfill touched 2 rows", APP_VERSION);
+        assert_eq!(report.summary, summary, "update summary reads wrong");
+
+        acknowledge_post_update_report().unwrap();
+        run_pending_migrations(&conn).unwrap();
+        assert!(!post_update_report().unwrap().pending, "update notice shown again after it was acknowledged");
+        assert_eq!(get_migration_history().unwrap().len(), MIGRATIONS.len(), "a relaunch applied migrations again");
+    }
+}
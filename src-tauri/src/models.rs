@@ -1,5 +1,10 @@
 #![allow(dead_code)]
 
+// Every struct crossing the command boundary lives here and serializes with
+// snake_case field names, matching the TypeScript types in src/api/client.ts.
+// Legacy spellings the frontend still sends are accepted via #[serde(alias)]
+// for one release; new fields must not add aliases.
+
 use serde::{Deserialize, Serialize};
 
 // ===== CORE MODELS =====
@@ -7,23 +12,63 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Resource {
     pub id: i64,
+    #[serde(alias = "room_number")]
     pub number: String,
     pub room_type: String,
     pub daily_rate: f64,
     pub is_occupied: bool,
     pub guest_id: Option<i64>,
     pub guest_name: Option<String>,
+    // An unresolved maintenance ticket has taken the room out of service
+    #[serde(default)]
+    pub out_of_service: bool,
+    pub floor: Option<i64>, // 0 = ground floor
+    pub building: Option<String>, // Wing or building, for properties with more than one
+}
+
+// Rooms on one floor of one building; rooms without a floor or building are
+// grouped under None
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FloorOccupancy {
+    pub building: Option<String>,
+    pub floor: Option<i64>,
+    pub total_rooms: i64,
+    pub occupied_rooms: i64,
+    pub out_of_service_rooms: i64,
+    pub occupancy_percent: f64, // Occupied share of the rooms in service
 }
 
 // Backwards-compatible alias (commands/TS types can be migrated gradually)
 pub type Room = Resource;
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomType {
+    pub id: i64,
+    pub name: String,
+    pub base_rate: f64, // Suggested rate for new rooms of this type
+    pub max_occupancy: Option<i64>, // Adults + children per room; None = no limit
+    pub amenities: Vec<String>,
+    pub room_count: i64,
+    pub occupied_rooms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomTypeInput {
+    pub name: String,
+    pub base_rate: f64,
+    pub max_occupancy: Option<i64>,
+    #[serde(default)]
+    pub amenities: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewCustomer {
     pub name: String,
     pub phone: Option<String>,
     pub room_id: Option<i64>,  // Changed to Option to support walk-in customers
+    #[serde(alias = "check_in_date")]
     pub check_in: String, // YYYY-MM-DD format
+    #[serde(alias = "check_out_date")]
     pub check_out: Option<String>, // YYYY-MM-DD format
     pub daily_rate: f64,
 }
@@ -36,12 +81,16 @@ pub struct Customer {
     pub name: String,
     pub phone: Option<String>,
     pub room_id: Option<i64>,  // Changed to Option to support walk-in customers
+    #[serde(alias = "check_in_date")]
     pub check_in: String,
+    #[serde(alias = "check_out_date")]
     pub check_out: Option<String>,
     pub daily_rate: f64,
     pub status: String, // 'active' or 'checked_out'
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
 }
 
 pub type Guest = Customer;
@@ -55,6 +104,12 @@ pub struct ActiveCustomerRow {
     pub check_out: Option<String>,
     pub daily_rate: f64,
     pub is_walkin: bool,  // New field to identify walk-in customers
+    pub adults: i64,
+    pub children: i64,
+    #[serde(default)]
+    pub open_holds: Vec<CheckoutHold>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
 }
 
 pub type ActiveGuestRow = ActiveCustomerRow;
@@ -69,6 +124,122 @@ pub struct MenuItem {
     pub stock_quantity: i32,
     pub track_stock: i32,
     pub low_stock_limit: i32,
+    #[serde(default)]
+    pub attributes: Vec<MenuItemAttribute>,
+    /// Portions left when the item is on today's specials board.
+    #[serde(default)]
+    pub special_remaining: Option<i32>,
+    /// What the item sells for right now under a happy-hour price rule.
+    #[serde(default)]
+    pub rule_price: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MenuCategory {
+    pub id: i64,
+    pub name: String,
+    pub sort_order: i64,
+    pub is_active: bool, // Inactive categories' items are left off the menu
+    pub item_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MenuPriceRule {
+    pub id: i64,
+    pub name: String,
+    pub menu_item_id: Option<i64>, // Either one item...
+    pub menu_item_name: Option<String>,
+    pub category: Option<String>,  // ...or a whole category
+    pub weekday_mask: i64,         // bit 0 = Monday .. bit 6 = Sunday
+    pub start_time: String,        // HH:MM
+    pub end_time: String,          // HH:MM; at or before start_time runs past midnight
+    pub price: Option<f64>,        // A fixed price...
+    pub discount_percent: Option<f64>, // ...or a percentage off the menu price
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MenuPriceRuleInput {
+    pub name: String,
+    #[serde(default)]
+    pub menu_item_id: Option<i64>,
+    #[serde(default)]
+    pub category: Option<String>,
+    pub weekday_mask: i64,
+    pub start_time: String,
+    pub end_time: String,
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub discount_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComboItem {
+    pub menu_item_id: i64,
+    pub item_name: String,
+    pub quantity: i64,
+    pub unit_price: f64, // The item's own menu price
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Combo {
+    pub id: i64,
+    pub name: String,
+    pub price: f64,
+    pub items: Vec<ComboItem>,
+    pub items_price: f64, // What the items cost bought separately
+    pub available: bool,  // Every item is on the menu and available
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComboItemInput {
+    pub menu_item_id: i64,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComboInput {
+    pub name: String,
+    pub price: f64,
+    pub items: Vec<ComboItemInput>,
+}
+
+/// A selectable attribute on a menu item, e.g. "Spice level" with "Mild" / "Medium" / "Hot".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuItemAttribute {
+    pub name: String,
+    pub values: Vec<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MenuImportOptions {
+    #[serde(default)]
+    pub update_existing: bool, // Rows naming an item already on the menu update it instead of being skipped
+    #[serde(default)]
+    pub dry_run: bool,
+    pub default_category: Option<String>, // For rows with no category
+    pub sheet_name: Option<String>, // Spreadsheets only; the first sheet by default
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MenuImportRow {
+    pub row: usize,
+    pub name: Option<String>,
+    pub status: String, // 'created', 'updated', 'skipped' or 'failed'
+    pub reason: Option<String>,
+    pub item_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MenuImportReport {
+    pub dry_run: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub rows: Vec<MenuImportRow>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -82,6 +253,7 @@ pub struct NewOrderItem {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewFoodOrder {
     pub customer_type: String, // 'GUEST' or 'WALK_IN'
+    #[serde(alias = "customer_id")]
     pub guest_id: Option<i64>,
     pub customer_name: Option<String>,
     pub items: Vec<NewOrderItem>,
@@ -114,6 +286,8 @@ pub struct ExpenseInput {
     pub category: String,
     pub description: Option<String>,
     pub amount: f64,
+    #[serde(default)]
+    pub is_drawing: bool, // owner withdrawal, not a business expense
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -123,24 +297,422 @@ pub struct ExpenseRow {
     pub category: String,
     pub description: Option<String>,
     pub amount: f64,
+    pub is_drawing: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CheckoutTotals {
     pub room_total: f64,
     pub unpaid_food: f64,
+    pub adjustments: f64,
+    pub extra_charges: f64,
+    pub tax: f64, // All taxes on the bill after discounts, added or included; 0 when tax is switched off
+    pub taxes: Vec<TaxLine>,
+    pub rounding: f64, // Added to (or taken off) the total by the rounding setting
     pub grand_total: f64,
+    pub deposits: f64,
+    pub balance_due: f64, // grand_total less deposits; negative when a refund is owed
+    pub stay_days: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BusinessInfo {
+    pub name: String,
+    pub address: String,
+    pub phone: String,
+    pub email: String,
+    pub logo_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tax {
+    pub id: i64,
+    pub name: String,
+    pub percent: f64,
+    pub applies_to: String, // 'rooms', 'food' or 'all'
+    pub is_active: bool,
+}
+
+/// One tax charged on a bill.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaxLine {
+    pub tax_id: i64,
+    pub name: String,
+    pub percent: f64,
+    pub applies_to: String,
+    pub taxable: f64, // The part of the bill, after discounts and before tax, the tax is charged on
+    pub amount: f64,
+    pub included: bool, // Already in the prices, so not added to the total
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestDeposit {
+    pub id: i64,
+    pub guest_id: i64,
+    pub amount: f64,
+    pub method: String, // 'cash', 'card' or 'bank_transfer'
+    pub paid_at: String,
+    pub note: Option<String>,
+}
+
+/// Running balance of a stay so far, before any checkout discount or tax.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestFolio {
+    pub guest_id: i64,
     pub stay_days: i64,
+    pub room_total: f64,
+    pub unpaid_food: f64,
+    pub adjustments: f64,
+    pub extra_charges: f64,
+    pub total_charges: f64,
+    pub deposits: Vec<GuestDeposit>,
+    pub deposits_total: f64,
+    pub balance: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestProfile {
+    pub id: i64,
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub id_number: Option<String>, // CNIC, passport or other ID document number
+    pub nationality: Option<String>,
+    pub preferences: Option<String>, // e.g. "Quiet room, extra pillows"
+    pub notes: Option<String>,
+    pub stay_count: i64,
+    pub last_check_in: Option<String>,
+    pub anonymized_at: Option<String>, // Set once the guest's personal details were erased
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestProfileInput {
+    pub name: String,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub id_number: Option<String>,
+    pub nationality: Option<String>,
+    pub preferences: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileStay {
+    pub guest_id: i64,
+    pub room_number: Option<String>, // None for walk-ins
+    pub check_in: String,
+    pub check_out: Option<String>,
+    pub status: String,
+    pub adults: i64,
+    pub children: i64,
+    pub spent: f64, // The checkout bill plus whatever was paid on orders
+    pub outstanding: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestProfileHistory {
+    pub profile: GuestProfile,
+    pub stays: Vec<ProfileStay>, // Newest first
+    pub total_stays: i64,
+    pub total_spent: f64,
+    pub outstanding_balance: f64,
+    pub favourite_items: Vec<String>, // Most ordered first
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateGuestGroup {
+    pub matched_on: String, // "phone" or "name"
+    pub profiles: Vec<GuestProfile>, // Oldest first
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestMergeResult {
+    pub profile: GuestProfile, // The surviving profile
+    pub profiles_merged: i64,
+    pub stays_moved: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestAnonymization {
+    pub profile_id: Option<i64>,
+    pub stays_anonymized: i64,
+    pub signatures_removed: i64,
+    pub anonymized_at: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DashboardStats {
     pub total_guests_this_month: i64,
     pub total_income: f64,
-    pub total_expenses: f64,
+    pub total_expenses: f64, // business expenses only
+    pub owner_drawings: f64,
     pub profit_loss: f64,
     pub total_food_orders: i64,
     pub active_guests: i64,
+    pub persons_in_house: i64,
+    pub average_rating: Option<f64>,
+}
+
+/// Parallel label/value arrays, ready to hand to a chart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChartSeries {
+    pub labels: Vec<String>,
+    pub values: Vec<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DashboardGraphs {
+    pub range_start: String,
+    pub range_end: String,
+    pub monthly_income: ChartSeries,
+    pub monthly_expenses: ChartSeries,
+    pub expenses_by_category: ChartSeries,
+    pub occupancy: ChartSeries, // daily occupancy percentage
+    pub occupancy_persons: ChartSeries, // daily persons in house
+    pub top_items: ChartSeries, // quantity sold
+    pub generated_at: String,
+}
+
+/// Income per daily, weekly or monthly bucket; every series shares the same labels.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevenueTimeseries {
+    pub granularity: String, // 'daily', 'weekly' or 'monthly'
+    pub range_start: String,
+    pub range_end: String,
+    pub room_income: ChartSeries,
+    pub food_income: ChartSeries,
+    pub total_income: ChartSeries,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpenseTimeseries {
+    pub granularity: String,
+    pub range_start: String,
+    pub range_end: String,
+    pub expenses: ChartSeries, // business expenses only
+    pub owner_drawings: ChartSeries,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpenseCategory {
+    pub id: i64,
+    pub name: String,
+    pub expense_count: i64, // Expenses and drawings booked under it
+}
+
+/// Where each field is in an expense file: a header caption (case-insensitive)
+/// or a column letter such as "C", plus how to read it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpenseImportMapping {
+    pub date: String,
+    pub amount: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub default_category: Option<String>, // For rows with no category
+    // A bank statement's signed amounts: money out (negative) is imported and money in skipped
+    #[serde(default)]
+    pub bank_statement: bool,
+    #[serde(default)]
+    pub dry_run: bool,
+    pub sheet_name: Option<String>,
+    pub acknowledge_unusual_dates: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportCounts {
+    pub created: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+// A record from another system that was skipped or couldn't be imported
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportIssue {
+    pub entity: String, // 'room', 'guest', 'order' or 'expense'
+    pub source: String, // Where it came from, e.g. "guests.csv row 4"
+    pub status: String, // 'skipped' or 'failed'
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LegacyImportReport {
+    pub adapter: String,
+    pub dry_run: bool,
+    pub rooms: ImportCounts,
+    pub guests: ImportCounts,
+    pub orders: ImportCounts,
+    pub expenses: ImportCounts,
+    pub issues: Vec<ImportIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportAdapterInfo {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpenseImportRow {
+    pub row: usize,
+    pub status: String, // 'created', 'skipped' or 'failed'
+    pub reason: Option<String>,
+    pub expense_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashItem {
+    pub kind: String,        // "order" or "expense"
+    pub id: i64,
+    pub label: String,       // Customer for an order, description for an expense
+    pub amount: f64,
+    pub date: String,        // When the order was taken / the expense's date
+    pub deleted_at: String,
+    pub deleted_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpenseImportReport {
+    pub dry_run: bool,
+    pub created: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub total_amount: f64, // Of the expenses created
+    pub rows: Vec<ExpenseImportRow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpenseBudget {
+    pub category: String,
+    pub monthly_amount: f64,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetLine {
+    pub category: String,
+    pub budget: Option<f64>, // None when the category has no budget
+    pub actual: f64,
+    pub variance: Option<f64>, // actual - budget; positive means over plan
+    pub percent_used: Option<f64>, // None for a zero or missing budget
+    pub over_budget: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BudgetVsActualReport {
+    pub year: i32,
+    pub month: u32,
+    pub lines: Vec<BudgetLine>, // Furthest over budget first; unbudgeted spend last
+    pub total_budget: f64,
+    pub total_actual: f64, // All business expenses, budgeted or not
+    pub total_variance: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LedgerAccount {
+    pub id: i64,
+    pub code: String,
+    pub name: String,
+    pub account_type: String, // 'asset', 'liability', 'equity', 'revenue' or 'expense'
+    pub is_system: bool, // Posted to automatically
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JournalLine {
+    pub account_id: i64,
+    pub account_code: String,
+    pub account_name: String,
+    pub debit: f64,
+    pub credit: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub entry_date: String,
+    pub memo: String,
+    pub source_type: Option<String>, // 'payment', 'deposit_applied', 'expense'; None for manual entries
+    pub source_id: Option<i64>,
+    pub reversal_of: Option<i64>,
+    pub reversed_by: Option<i64>,
+    pub lines: Vec<JournalLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewJournalLine {
+    pub account_id: i64,
+    pub debit: Option<f64>,
+    pub credit: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrialBalanceLine {
+    pub account_id: i64,
+    pub code: String,
+    pub name: String,
+    pub account_type: String,
+    pub debit: f64,  // Net debit balance, or 0
+    pub credit: f64, // Net credit balance, or 0
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrialBalance {
+    pub as_of: String,
+    pub lines: Vec<TrialBalanceLine>, // Accounts with a balance, by code
+    pub total_debit: f64,
+    pub total_credit: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfitAndLossLine {
+    pub code: String,
+    pub name: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfitAndLoss {
+    pub start_date: String,
+    pub end_date: String,
+    pub revenue: Vec<ProfitAndLossLine>,
+    pub expenses: Vec<ProfitAndLossLine>,
+    pub total_revenue: f64,
+    pub total_expenses: f64,
+    pub net_income: f64,
+}
+
+/// One tax at one rate over the period of a tax return.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaxReportLine {
+    pub tax_id: Option<i64>,
+    pub name: String,
+    pub percent: f64,
+    pub applies_to: String,
+    pub taxable: f64, // Sales, net of tax, the tax was charged on
+    pub collected: f64,
+    pub bills: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpenseTaxLine {
+    pub category: String,
+    pub amount: f64, // Expenses in the category, tax included
+    pub tax: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaxReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub checkouts: i64,
+    pub gross_sales: f64, // Checkout bills and settled walk-in orders, tax included
+    pub net_sales: f64,   // Those sales less the tax on them
+    pub taxes: Vec<TaxReportLine>,
+    pub total_collected: f64,
+    pub expenses: Vec<ExpenseTaxLine>, // Business expenses with tax on them
+    pub total_expense_tax: f64,
+    pub net_payable: f64, // Tax collected less tax paid on expenses; negative means a refund is due
+    pub generated_at: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -179,6 +751,25 @@ pub struct OrderItemInput {
     pub item_name: String,
     pub unit_price: f64,
     pub quantity: i32,
+    #[serde(default)]
+    pub selected_attributes: Vec<(String, String)>,
+    #[serde(default)]
+    pub special_id: Option<i64>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub modifiers: Vec<OrderItemModifier>,
+    /// Set when the line is a combo; its components are what's cooked and taken from stock
+    #[serde(default)]
+    pub combo_id: Option<i64>,
+}
+
+/// A change to one order line, e.g. "Extra cheese" at +1.50 or "No onions" at 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderItemModifier {
+    pub name: String,
+    #[serde(default)]
+    pub price_delta: f64, // Per unit; negative takes money off
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -188,9 +779,13 @@ pub struct SaleSummary {
     pub paid: bool,
     pub paid_at: Option<String>,
     pub total_amount: f64,
+    pub amount_paid: f64,
+    pub payment_status: String, // 'paid', 'partially_paid' or 'unpaid'
     pub items: String, // comma-separated list
     pub guest_id: Option<i64>,
     pub guest_name: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
 }
 
 // Backwards-compatible alias (older command/TS naming)
@@ -206,47 +801,395 @@ pub struct SaleRecord {
     pub paid: bool,
     pub paid_at: Option<String>,
     pub total_amount: f64,
+    pub amount_paid: f64,
+    pub payment_status: String,
+    pub notes: Option<String>, // For the kitchen, e.g. "no onions"
+    pub status: String, // 'pending', 'preparing', 'served' or 'closed'
 }
 
 // Backwards-compatible alias
 pub type FoodOrderInfo = SaleRecord;
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct OrderItemDetail {
+pub struct OrderPayment {
     pub id: i64,
-    pub menu_item_id: Option<i64>,
-    pub item_name: String,
-    pub quantity: i64,
-    pub unit_price: f64,
-    pub line_total: f64,
+    pub order_id: i64,
+    pub amount: f64,
+    pub method: String, // 'cash', 'card' or 'bank_transfer'
+    pub paid_at: String,
+    pub note: Option<String>,
 }
 
+/// A refund is stored as a negative payment row; `amount` here is the positive sum given back.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct SaleDetails {
-    pub order: SaleRecord,
-    pub items: Vec<OrderItemDetail>,
+pub struct PaymentRefund {
+    pub id: i64,
+    pub payment_id: i64,
+    pub order_id: Option<i64>,
+    pub guest_id: Option<i64>,
+    pub amount: f64,
+    pub method: String,
+    pub reason: String,
+    pub refunded_at: String,
+    pub refunded_by: String,
 }
 
-// Backwards-compatible alias
-pub type FoodOrderDetails = SaleDetails;
-
-// ===== INVENTORY MODELS =====
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderPaymentSummary {
+    pub order_id: i64,
+    pub total_amount: f64,
+    pub amount_paid: f64,
+    pub balance: f64,
+    pub payment_status: String,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct LowStockItem {
-    pub id: i64,
-    pub name: String,
-    pub stock_quantity: i32,
-    pub low_stock_limit: i32,
+pub struct PaymentMethodTotal {
+    pub method: String,
+    pub order_amount: f64,
+    pub order_count: i64,
+    pub checkout_amount: f64,
+    pub checkout_count: i64,
+    pub total: f64,
 }
 
-// ===== SHIFT MANAGEMENT MODELS =====
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentsByMethodReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub methods: Vec<PaymentMethodTotal>,
+    pub total: f64,
+}
 
+/// A room stay as it appears on the night audit.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ShiftSummary {
-    pub id: i64,
-    pub opened_at: String,
-    pub closed_at: Option<String>,
+pub struct NightAuditStay {
+    pub guest_id: i64,
+    pub name: String,
+    pub room_number: String,
+    pub check_in: String,
+    pub check_out: Option<String>,
+    pub amount: f64, // The night's room rate; the billed total for check-outs
+    pub balance: Option<f64>, // Folio balance right now, for guests still in house
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NightAuditReport {
+    pub date: String,
+    pub check_ins: Vec<NightAuditStay>,
+    pub check_outs: Vec<NightAuditStay>,
+    pub in_house: Vec<NightAuditStay>, // Rooms occupied the night of `date`
+    pub total_rooms: i64,
+    pub occupancy_percent: f64,
+    pub room_revenue: f64, // The night's rate for every occupied room
+    pub food_orders: i64,
+    pub food_revenue: f64, // Orders placed on the day, after voids
+    pub payments: Vec<PaymentMethodTotal>,
+    pub payments_total: f64,
+    pub outstanding: Vec<ReceivableItem>, // Owed since `date` or earlier and still unpaid
+    pub outstanding_total: f64,
+    pub generated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderItemDetail {
+    pub id: i64,
+    pub menu_item_id: Option<i64>,
+    pub item_name: String,
+    pub quantity: i64,
+    pub unit_price: f64,
+    pub line_total: f64,
+    pub selected_attributes: Vec<(String, String)>,
+    pub notes: Option<String>,
+    pub modifiers: Vec<OrderItemModifier>,
+    pub voided: bool,
+    #[serde(default)]
+    pub combo_id: Option<i64>,
+    #[serde(default)]
+    pub components: Vec<OrderItemComponent>, // What a combo line is made of
+    /// The price before a happy-hour rule; unit_price is what was charged
+    #[serde(default)]
+    pub original_unit_price: Option<f64>,
+    #[serde(default)]
+    pub price_rule_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderItemComponent {
+    pub menu_item_id: Option<i64>,
+    pub item_name: String,
+    pub quantity: i64, // Per combo
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaleDetails {
+    pub order: SaleRecord,
+    pub items: Vec<OrderItemDetail>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+// Backwards-compatible alias
+pub type FoodOrderDetails = SaleDetails;
+
+// ===== INVENTORY MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LowStockItem {
+    pub kind: String, // 'menu_item' or 'ingredient'
+    pub id: i64,
+    pub name: String,
+    pub stock_quantity: f64,
+    pub low_stock_limit: f64,
+    pub unit: Option<String>, // Ingredients only
+}
+
+/// An ingredient and how much of it is on hand.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InventoryItem {
+    pub id: i64,
+    pub name: String,
+    pub unit: String, // e.g. 'kg', 'l', 'pcs'
+    pub quantity: f64,
+    pub low_stock_limit: f64,
+    pub low_stock: bool,
+    pub unit_cost: f64, // Per unit; set by hand or from the latest purchase order received
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecipeIngredientInput {
+    pub inventory_item_id: i64,
+    pub quantity: f64, // Per portion, in the ingredient's unit
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecipeIngredient {
+    pub inventory_item_id: i64,
+    pub name: String,
+    pub unit: String,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StockMovement {
+    pub id: i64,
+    pub inventory_item_id: i64,
+    pub item_name: String,
+    pub change: f64,
+    pub reason: String, // 'order', 'restock', 'waste' or 'correction'
+    pub order_id: Option<i64>,
+    pub note: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: String,
+}
+
+/// An ingredient at or below its limit, and the menu items that need it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LowStockIngredient {
+    pub id: i64,
+    pub name: String,
+    pub unit: String,
+    pub quantity: f64,
+    pub low_stock_limit: f64,
+    pub shortfall: f64, // How much to buy to get back to the limit
+    pub used_in: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MenuItemProfitability {
+    pub menu_item_id: i64,
+    pub name: String,
+    pub quantity_sold: i64,
+    pub revenue: f64,
+    pub portion_cost: f64, // Current recipe at current ingredient costs
+    pub cost_of_goods: f64,
+    pub gross_profit: f64,
+    pub margin_percent: Option<f64>, // None when nothing was charged
+    pub costed: bool, // False when the item has no recipe, so its cost is unknown
+}
+
+// ===== PURCHASING MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Supplier {
+    pub id: i64,
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub notes: Option<String>,
+    pub received_value: f64, // Goods received so far, at cost
+    pub paid: f64,
+    pub balance: f64, // Owed for goods received; negative when paid ahead
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderLineInput {
+    pub inventory_item_id: Option<i64>,
+    pub description: Option<String>, // Defaults to the inventory item's name
+    pub quantity: f64,
+    pub unit_cost: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrderLine {
+    pub id: i64,
+    pub inventory_item_id: Option<i64>,
+    pub description: String,
+    pub quantity: f64,
+    pub unit_cost: f64,
+    pub received_quantity: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurchaseOrder {
+    pub id: i64,
+    pub supplier_id: i64,
+    pub supplier_name: String,
+    pub status: String, // 'ordered', 'partially_received', 'received' or 'cancelled'
+    pub order_date: String,
+    pub expected_date: Option<String>,
+    pub notes: Option<String>,
+    pub total_amount: f64,
+    pub amount_paid: f64,
+    pub created_by: Option<String>,
+    pub received_at: Option<String>,
+    pub lines: Vec<PurchaseOrderLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiveLineInput {
+    pub line_id: i64,
+    pub quantity: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupplierPayment {
+    pub id: i64,
+    pub supplier_id: i64,
+    pub purchase_order_id: Option<i64>,
+    pub amount: f64,
+    pub method: String,
+    pub paid_on: String,
+    pub note: Option<String>,
+    pub expense_id: Option<i64>,
+    pub created_by: Option<String>,
+}
+
+// ===== COMPANY ACCOUNT MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Company {
+    pub id: i64,
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub billing_address: Option<String>,
+    pub credit_limit: Option<f64>, // None for no limit
+    pub notes: Option<String>,
+    pub charged: f64,
+    pub paid: f64,
+    pub balance: f64, // Still owed by the company
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompanyPayment {
+    pub id: i64,
+    pub company_id: i64,
+    pub amount: f64,
+    pub method: String,
+    pub paid_on: String,
+    pub note: Option<String>,
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompanyStatementEntry {
+    pub date: String,
+    pub kind: String, // 'charge' or 'payment'
+    pub reference: String, // e.g. "Guest #12" or "Payment #3"
+    pub description: String,
+    pub amount: f64, // Charges positive, payments negative
+    pub balance: f64, // Running balance after this entry
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompanyStatement {
+    pub company: Company,
+    pub start_date: String,
+    pub end_date: String,
+    pub opening_balance: f64,
+    pub entries: Vec<CompanyStatementEntry>,
+    pub total_charges: f64,
+    pub total_payments: f64,
+    pub closing_balance: f64,
+}
+
+// ===== RECEIVABLES MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceivableItem {
+    pub source: String, // 'order', 'checkout' or 'company'
+    pub reference_id: i64, // Order, guest or company charge id
+    pub name: String,
+    pub date: String, // When the money became owed
+    pub age_days: i64,
+    pub bucket: String, // '0-30', '31-60' or '61+'
+    pub amount: f64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AgingTotals {
+    pub days_0_30: f64,
+    pub days_31_60: f64,
+    pub days_61_plus: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceivablesAgingReport {
+    pub as_of: String,
+    pub items: Vec<ReceivableItem>, // Oldest first
+    pub orders: AgingTotals,
+    pub checkouts: AgingTotals,
+    pub companies: AgingTotals,
+    pub total: AgingTotals,
+}
+
+// ===== SERVICE CHARGE MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceChargeSetting {
+    pub business_mode: String,
+    pub percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceChargeDay {
+    pub date: String,
+    pub orders: i64,
+    pub charged: f64,
+    pub collected: f64, // Part of the charge covered by payments, or settled at checkout
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServiceChargeReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub days: Vec<ServiceChargeDay>,
+    pub total_charged: f64,
+    pub total_collected: f64,
+}
+
+// ===== SHIFT MANAGEMENT MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShiftSummary {
+    pub id: i64,
+    pub opened_at: String,
+    pub closed_at: Option<String>,
     pub opened_by: i64,
     pub closed_by: Option<i64>,
     pub start_cash: f64,
@@ -255,17 +1198,1460 @@ pub struct ShiftSummary {
     pub difference: f64,
     pub total_sales: f64,
     pub total_expenses: f64,
+    pub owner_drawings: f64,
+    pub after_hours_count: i64,
+    pub cash_sales: f64, // part of total_sales taken in cash; only this goes into the drawer
     pub status: String, // 'open' or 'closed'
     pub notes: Option<String>,
+    pub denominations: Option<Vec<DenominationCount>>,
 }
 
-// ===== EXPENSE MODELS =====
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DenominationCount {
+    pub value: f64,
+    pub count: i64,
+    pub subtotal: f64,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ExpenseRecord {
+pub struct ShiftPayment {
     pub id: i64,
-    pub date: String,
+    pub order_id: Option<i64>,
+    pub guest_id: Option<i64>, // Checkout payments and deposits
+    pub amount: f64, // Negative for refunds
+    pub method: String,
+    pub paid_at: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShiftMethodTotal {
+    pub method: String,
+    pub payment_count: i64,
+    pub taken: f64,
+    pub refunded: f64,
+    pub net: f64,
+}
+
+/// Expected against counted cash for one shift, with every payment taken
+/// during it. Stored on the shift when it is closed.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShiftReconciliation {
+    pub shift_id: i64,
+    pub status: String, // 'open' or 'closed'
+    pub opened_at: String,
+    pub closed_at: Option<String>,
+    pub start_cash: f64,
+    pub methods: Vec<ShiftMethodTotal>, // cash, card and bank_transfer, always in that order
+    pub payments: Vec<ShiftPayment>,
+    pub cash_sales: f64, // Net cash taken
+    pub total_expenses: f64,
+    pub owner_drawings: f64,
+    pub expected_cash: f64, // start_cash + cash_sales - total_expenses - owner_drawings
+    pub counted_cash: Option<f64>, // Set at close
+    pub difference: Option<f64>, // counted - expected; negative when the drawer is short
+    pub denominations: Option<Vec<DenominationCount>>,
+}
+
+// ===== GUEST FEEDBACK MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RatingBucket {
+    pub rating: i32,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeedbackComment {
+    pub guest_id: i64,
+    pub guest_name: String,
+    pub room_number: Option<String>,
+    pub rating: i32,
+    pub comment: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeedbackReport {
+    pub total_responses: i64,
+    pub average_rating: Option<f64>,
+    pub distribution: Vec<RatingBucket>,
+    pub recent_comments: Vec<FeedbackComment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomRatingRow {
+    pub room_id: i64,
+    pub room_number: String,
+    pub responses: i64,
+    pub average_rating: f64,
+}
+
+// ===== TAG MODELS =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagUsage {
+    pub tag: Tag,
+    pub guest_count: i64,
+    pub order_count: i64,
+}
+
+// ===== LAN SYNC MODELS =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuSyncRow {
+    pub name: String, // menu items are matched across installs by name
+    pub price: f64,
     pub category: String,
-    pub description: Option<String>,
-    pub amount: f64,
+    pub is_available: bool,
+    pub attributes: Option<String>,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingSyncRow {
+    pub key: String,
+    pub value: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuCategorySyncRow {
+    pub name: String,
+    pub sort_order: i64,
+    pub is_active: bool,
+    pub updated_at: String,
+}
+
+/// Price rules can't be edited, only added and deleted, so a rule is matched
+/// across installs by everything it says.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuPriceRuleSyncRow {
+    pub name: String,
+    pub menu_item_name: Option<String>, // the item's name, as ids differ between installs
+    pub category: Option<String>,
+    pub weekday_mask: i64,
+    pub start_time: String,
+    pub end_time: String,
+    pub price: Option<f64>,
+    pub discount_percent: Option<f64>,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ComboItemSyncRow {
+    pub menu_item_name: String,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComboSyncRow {
+    pub name: String,
+    pub price: f64,
+    pub items: Vec<ComboItemSyncRow>,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxSyncRow {
+    pub name: String,
+    pub percent: f64,
+    pub applies_to: String,
+    pub is_active: bool,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncSnapshot {
+    pub format_version: u32,
+    pub app_version: String,
+    pub generated_at: String,
+    pub menu_items: Option<Vec<MenuSyncRow>>,
+    pub settings: Option<Vec<SettingSyncRow>>,
+    // Sent with the menu since these were synced; older peers leave them out
+    #[serde(default)]
+    pub menu_categories: Option<Vec<MenuCategorySyncRow>>,
+    #[serde(default)]
+    pub menu_price_rules: Option<Vec<MenuPriceRuleSyncRow>>,
+    #[serde(default)]
+    pub combos: Option<Vec<ComboSyncRow>>,
+    #[serde(default)]
+    pub taxes: Option<Vec<TaxSyncRow>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncChange {
+    pub dataset: String,
+    pub key: String,
+    pub action: String, // 'added', 'updated' or 'skipped'
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncSummary {
+    pub peer_url: String,
+    pub peer_generated_at: String,
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub changes: Vec<SyncChange>,
+    pub skipped: Vec<SyncChange>, // rows not applied because the local copy is newer
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncServerStatus {
+    pub enabled: bool,
+    pub port: Option<u16>,
+    pub token: Option<String>,
+}
+
+// ===== MAINTENANCE MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseStats {
+    pub total_rooms: i32,
+    pub occupied_rooms: i32,
+    pub available_rooms: i32,
+    pub active_guests: i32,
+    pub total_guests: i32,
+    pub menu_items: i32,
+    pub food_orders: i32,
+    pub unpaid_orders: i32,
+    pub expenses: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DataIntegrityReport {
+    pub ok: bool,
+    pub database_ok: bool,
+    pub database_messages: Vec<String>,
+    pub media: MediaIntegrityReport,
+}
+
+// A logo upload: a path to an image file, or the image bytes themselves
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum LogoSource {
+    Path(String),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecurityQuestion {
+    pub id: String,
+    pub question: String,
+    pub answer: String,
+}
+
+// ===== EXPORT JOB MODELS =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportJobStatus {
+    pub job_id: String,
+    pub tab: String,
+    pub format: String,
+    pub status: String, // 'running', 'completed', 'cancelled' or 'failed'
+    pub rows_written: i64,
+    pub estimated_total: i64,
+    pub file_path: String,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+// ===== MEDIA MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MissingMedia {
+    pub owner: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MediaFeatureReport {
+    pub feature: String, // 'logo' or 'signatures'
+    pub referenced: usize,
+    pub missing: Vec<MissingMedia>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MediaIntegrityReport {
+    pub media_root: String,
+    pub missing_total: usize,
+    pub features: Vec<MediaFeatureReport>,
+}
+
+// ===== PRINT TEMPLATE MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrintTemplateInfo {
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub overridden: bool, // A customised copy is saved and used instead of the built-in layout
+    pub override_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrintTemplateSource {
+    pub name: String,
+    pub source: String, // What prints now: the customised copy, else the built-in layout
+    pub default_source: String,
+    pub overridden: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptQrSetting {
+    pub mode: String, // "off", "details" or "payment_link"
+    pub payment_link: Option<String>, // May use {reference}, {amount} and {currency}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReceiptLanguageInfo {
+    pub code: String,
+    pub name: String,
+    pub rtl: bool, // Printed right-to-left
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationProviderSettings {
+    pub provider: String, // "none", "twilio" or "whatsapp_business"
+    pub twilio_account_sid: Option<String>,
+    pub twilio_from: Option<String>,          // SMS sender number
+    pub twilio_whatsapp_from: Option<String>, // WhatsApp-enabled sender number
+    pub whatsapp_phone_number_id: Option<String>,
+    pub has_secret: bool, // The auth token / access token is saved; it is never sent back
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationEventSetting {
+    pub event_type: String, // "booking_confirmation", "checkout_invoice" or "payment_reminder"
+    pub enabled: bool,
+    pub channel: String, // "sms" or "whatsapp"
+    pub template: String, // Message text with {placeholders}
+    pub placeholders: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationLogEntry {
+    pub id: i64,
+    pub event_type: String,
+    pub channel: String,
+    pub recipient: String,
+    pub message: String,
+    pub status: String, // "sent" or "failed"
+    pub error: Option<String>,
+    pub provider_message_id: Option<String>,
+    pub guest_id: Option<i64>,
+    pub reservation_id: Option<i64>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    pub frequency: String, // "off", "daily" or "weekly"
+    pub directory: Option<String>,
+    pub retention: i64, // Scheduled backups kept; older ones are deleted
+    pub last_run: Option<String>,
+    pub last_backup_path: Option<String>,
+    pub last_error: Option<String>, // Cleared by the next successful backup
+    pub next_run: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupFileInfo {
+    pub file_name: String,
+    pub path: String,
+    pub kind: String, // "manual", "scheduled" or "before_restore"
+    pub size_bytes: u64, // The .db file alone
+    pub has_media: bool,
+    pub has_json_export: bool,
+    pub encrypted: bool, // Needs the backup passphrase to restore
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseEncryptionStatus {
+    pub encrypted: bool,
+    pub mode: Option<String>, // "keyring" or "password" while encrypted
+    pub username: Option<String>, // Admin whose password unlocks it, in password mode
+    pub locked: bool, // Waiting for the admin password; nothing else works until unlocked
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloudBackupSettingsInput {
+    pub provider: String, // "none", "s3", "google_drive" or "webdav"
+    pub s3_endpoint: Option<String>, // Defaults to AWS; set for R2, B2, MinIO, ...
+    pub s3_region: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_prefix: Option<String>, // Folder inside the bucket
+    pub s3_access_key_id: Option<String>,
+    pub s3_secret_access_key: Option<String>,
+    pub gdrive_client_id: Option<String>,
+    pub gdrive_client_secret: Option<String>,
+    pub gdrive_refresh_token: Option<String>,
+    pub gdrive_folder_id: Option<String>,
+    pub webdav_url: Option<String>, // Folder the backups are put in
+    pub webdav_username: Option<String>,
+    pub webdav_password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloudBackupSettings {
+    pub provider: String,
+    pub s3_endpoint: Option<String>,
+    pub s3_region: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_prefix: Option<String>,
+    pub s3_access_key_id: Option<String>,
+    pub gdrive_client_id: Option<String>,
+    pub gdrive_folder_id: Option<String>,
+    pub webdav_url: Option<String>,
+    pub webdav_username: Option<String>,
+    pub has_secret: bool, // The provider's secrets are saved; they are never sent back
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloudBackupStatus {
+    pub provider: String,
+    pub status: Option<String>, // "uploading", "uploaded" or "failed"; None before the first upload
+    pub last_attempt: Option<String>,
+    pub last_sync: Option<String>, // Last time a backup was uploaded successfully
+    pub last_error: Option<String>,
+    pub last_files: Vec<String>, // Where the last successful upload put each file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Voucher {
+    pub id: i64,
+    pub code: String,
+    pub kind: String, // "amount" or "percent"
+    pub value: f64, // Amount off, or percent off the bill
+    pub expires_on: Option<String>, // Last day it can be used
+    pub max_uses: Option<i64>, // 1 for single use; None for no limit
+    pub uses: i64,
+    pub is_active: bool,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewVoucher {
+    pub code: Option<String>, // Generated when left out
+    pub kind: String,
+    pub value: f64,
+    pub expires_on: Option<String>,
+    pub max_uses: Option<i64>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VoucherCheck {
+    pub voucher: Voucher,
+    pub uses_left: Option<i64>, // None for no limit
+    pub discount: Option<f64>, // What it takes off the amount given, when one was
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VoucherRedemption {
+    pub id: i64,
+    pub voucher_id: i64,
+    pub code: String,
+    pub order_id: Option<i64>,
+    pub guest_id: Option<i64>, // Set when it came off a checkout bill
+    pub amount: f64,
+    pub redeemed_by: Option<String>,
+    pub redeemed_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentGatewaySettingsInput {
+    pub provider: String, // "none" or "stripe"
+    pub stripe_secret_key: Option<String>, // sk_live_... or sk_test_...
+    pub stripe_publishable_key: Option<String>, // pk_..., for the card form
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PaymentGatewaySettings {
+    pub provider: String,
+    pub stripe_publishable_key: Option<String>,
+    pub test_mode: bool, // The saved secret key is a test key
+    pub has_secret: bool, // The secret key is saved; it is never sent back
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OnlinePayment {
+    pub id: i64,
+    pub source_type: String, // "order" or "folio" (source_id is then the guest ID)
+    pub source_id: i64,
+    pub provider: String,
+    pub intent_id: String, // The gateway's ID, e.g. Stripe's pi_...
+    pub client_secret: Option<String>, // Lets the card form confirm the payment
+    pub amount: f64,
+    pub currency: String,
+    pub status: String, // The gateway's status; "succeeded" once paid
+    pub payment_id: Option<i64>, // Payment recorded when it succeeded
+    pub note: Option<String>,
+    pub paid_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FiscalSettingsInput {
+    pub provider: String, // "none", "fbr" or "webhook"
+    pub fbr_environment: Option<String>, // "sandbox" (default) or "live"
+    pub fbr_pos_id: Option<String>, // POS ID issued when the outlet was registered with FBR
+    pub fbr_token: Option<String>,
+    pub fbr_pct_code: Option<String>, // PCT code reported on every line
+    pub webhook_url: Option<String>, // Another fiscal service, or a bridge to one
+    pub webhook_token: Option<String>, // Sent as a Bearer token when set
+    pub print_on_receipt: bool, // Print the fiscal invoice number and QR on receipts and invoices
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FiscalSettings {
+    pub provider: String,
+    pub fbr_environment: String,
+    pub fbr_pos_id: Option<String>,
+    pub fbr_pct_code: Option<String>,
+    pub webhook_url: Option<String>,
+    pub print_on_receipt: bool,
+    pub has_secret: bool, // The provider's token is saved; it is never sent back
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FiscalRecord {
+    pub id: i64,
+    pub source_type: String, // "order" or "checkout"
+    pub source_id: i64, // Order ID, or the guest ID for a checkout bill
+    pub usin: String, // Our invoice number as reported, e.g. "ORDER-12"
+    pub provider: String,
+    pub status: String, // "pending", "submitted" or "failed"
+    pub fiscal_number: Option<String>,
+    pub qr_payload: Option<String>,
+    pub error: Option<String>,
+    pub attempts: i64,
+    pub submitted_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableRestoreResult {
+    pub table: String,
+    pub inserted: i64, // Rows only in the backup
+    pub updated: i64,  // Rows matched by their natural key and overwritten
+    pub skipped: i64,  // Rows left alone, e.g. settings that belong to this PC
+}
+
+// ===== MIGRATION MODELS =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationRecord {
+    pub id: i64,
+    pub name: String,
+    pub app_version: String,
+    pub applied_at: String,
+    pub duration_ms: i64,
+    pub rows_affected: i64,
+    pub schema_changes: Vec<String>,  // "table.column" added by the migration
+    pub seeded_settings: Vec<String>, // settings keys the migration created
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostUpdateReport {
+    pub previous_version: Option<String>,
+    pub current_version: String,
+    pub pending: bool, // not yet acknowledged by the UI
+    pub migrations: Vec<MigrationRecord>,
+    pub total_rows_affected: i64,
+    pub total_duration_ms: i64,
+    pub summary: String,
+}
+
+// ===== RESERVATION MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Reservation {
+    pub id: i64,
+    pub room_id: i64,
+    pub room_number: String,
+    pub guest_name: String,
+    pub phone: Option<String>,
+    pub check_in: String,
+    pub check_out: String,
+    pub daily_rate: f64,
+    pub adults: i64,
+    pub children: i64,
+    pub notes: Option<String>,
+    pub status: String, // 'booked', 'cancelled' or 'checked_in'
+    pub guest_id: Option<i64>,
+    pub created_at: String,
+    pub group_id: Option<i64>, // Set when booked as part of a group
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupRoomInput {
+    pub room_id: i64,
+    pub guest_name: Option<String>, // Defaults to the group's contact
+    pub phone: Option<String>,
+    pub daily_rate: Option<f64>, // Defaults to the room's rate
+    pub adults: Option<i64>,
+    pub children: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewGroupBooking {
+    pub name: String, // e.g. "Khan wedding party"
+    pub contact_name: Option<String>,
+    pub contact_phone: Option<String>,
+    pub shared_folio: bool, // The group pays every room's bill together
+    pub check_in: String,
+    pub check_out: String,
+    pub notes: Option<String>,
+    pub rooms: Vec<GroupRoomInput>,
+    #[serde(default)]
+    pub override_occupancy_warning: Option<bool>, // Book past a soft occupancy cap
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupMember {
+    pub reservation_id: Option<i64>, // Set until the room checks in
+    pub guest_id: Option<i64>, // Set once it has
+    pub room_id: Option<i64>,
+    pub room_number: Option<String>,
+    pub guest_name: String,
+    pub check_in: String,
+    pub check_out: Option<String>,
+    pub status: String, // "booked" or "cancelled", then "active" or "checked_out"
+    pub charges: f64, // The checkout bill, or the folio so far while in house
+    pub paid: f64,
+    pub balance: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupBooking {
+    pub id: i64,
+    pub name: String,
+    pub contact_name: Option<String>,
+    pub contact_phone: Option<String>,
+    pub shared_folio: bool,
+    pub notes: Option<String>,
+    pub created_at: String,
+    pub members: Vec<GroupMember>,
+    pub balance: f64, // Left to pay across the rooms that have checked in
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupMemberResult {
+    pub room_number: Option<String>,
+    pub guest_id: Option<i64>,
+    pub error: Option<String>, // Why this room couldn't be checked in or out
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomCalendarDay {
+    pub date: String,
+    pub status: String, // 'free', 'occupied', 'reserved' or 'conflict'
+    pub guest_id: Option<i64>,
+    pub guest_name: Option<String>,
+    pub reservation_ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomCalendarRow {
+    pub room_id: i64,
+    pub room_number: String,
+    pub room_type: String,
+    pub days: Vec<RoomCalendarDay>,
+}
+
+// ===== OCCUPANCY CAP MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OccupancyCapSettings {
+    pub max_property_occupancy: Option<i64>, // None = no cap
+    pub mode: String,                        // 'hard' rejects, 'soft' warns until overridden
+}
+
+/// What reopen_checkout undid.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReopenedStay {
+    pub guest_id: i64,
+    pub check_out: Option<String>, // The expected check-out the stay had before it was closed
+    pub room_restored: bool, // false when someone else has the room now
+    pub payments_reversed: f64,
+    pub company_charges_reversed: f64,
+    pub taxes_reversed: f64,
+}
+
+// ===== STAY TIME FEE MODELS =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StayFeeRule {
+    pub fee_type: String, // 'flat' amount, or 'percent' of the stay's daily rate
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StayFeeRules {
+    pub check_in_time: String,  // HH:MM; arriving before it is an early check-in
+    pub check_out_time: String, // HH:MM; leaving after it is a late check-out
+    pub early_check_in: Option<StayFeeRule>, // None = no fee
+    pub late_check_out: Option<StayFeeRule>,
+}
+
+// ===== DATE PLAUSIBILITY MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatePlausibilitySettings {
+    pub past_days: i64,        // warn when a date is further back than this
+    pub future_days: i64,      // warn when a date is further ahead than this
+    pub earliest_date: String, // dates before this are rejected outright
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutlierDateRow {
+    pub table: String,
+    pub row_id: i64,
+    pub field: String,
+    pub date: String,
+    pub description: String,
+    pub entered_at: String,
+}
+
+// ===== CHECKOUT HOLD MODELS =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutHold {
+    pub id: i64,
+    pub guest_id: i64,
+    pub reason: String,
+    pub amount_estimate: Option<f64>,
+    pub status: String, // 'open', 'released' or 'converted'
+    pub placed_by: String,
+    pub placed_at: String,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<String>,
+    pub adjustment_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuestAdjustment {
+    pub id: i64,
+    pub guest_id: i64,
+    pub description: String,
+    pub amount: f64,
+    pub source: String,
+    pub created_by: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuestCharge {
+    pub id: i64,
+    pub guest_id: i64,
+    pub category: String, // 'minibar', 'laundry', 'late_checkout', 'telephone' or 'other'
+    pub description: String,
+    pub quantity: i64,
+    pub unit_price: f64,
+    pub amount: f64,
+    pub charged_on: String,
+    pub created_by: Option<String>,
+    pub created_at: String,
+}
+
+// ===== DAILY SPECIALS MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailySpecialInput {
+    pub menu_item_id: Option<i64>,
+    pub name: Option<String>,
+    pub price: Option<f64>,
+    pub prepared_quantity: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailySpecial {
+    pub id: i64,
+    pub special_date: String,
+    pub menu_item_id: Option<i64>,
+    pub name: String,
+    pub price: f64,
+    pub prepared_quantity: i32,
+    pub sold_quantity: i32,
+    pub remaining: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpecialsPerformanceRow {
+    pub date: String,
+    pub prepared: i64,
+    pub sold: i64,
+    pub waste: i64,
+}
+
+// ===== PRICING MODELS =====
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum PriceAdjustment {
+    Percent(f64),
+    Flat(f64),
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum PriceRounding {
+    #[default]
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "nearest_5")]
+    Nearest5,
+    #[serde(rename = "nearest_10")]
+    Nearest10,
+    #[serde(rename = "up_to_10")]
+    UpTo10,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceChange {
+    pub id: i64,
+    pub name: String,
+    pub old_price: f64,
+    pub new_price: f64,
+    pub skipped_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkPriceUpdateResult {
+    pub dry_run: bool,
+    pub changes: Vec<PriceChange>,
+    pub skipped: Vec<PriceChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RateOverride {
+    pub id: i64,
+    pub room_type: String,
+    pub date: String,
+    pub daily_rate: f64,
+    pub source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RatePlan {
+    pub id: i64,
+    pub name: String,
+    pub room_type: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub weekday_mask: Option<i64>, // bit 0 = Monday .. bit 6 = Sunday
+    pub daily_rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NightlyRate {
+    pub date: String,
+    pub rate: f64,
+    pub source: String, // 'override', 'plan' or 'base'
+    pub rate_plan_id: Option<i64>,
+}
+
+/// The part of a stay spent in one room; a room transfer ends one segment and starts the next.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StaySegment {
+    pub room_id: Option<i64>,
+    pub room_number: Option<String>,
+    pub start_date: String,
+    pub end_date: Option<String>, // The transfer date; None for the room the guest is in now
+    pub nights: i64,
+    pub daily_rate: f64, // The room's base rate; nightly pricing may vary it
+    pub total: f64,
+}
+
+/// A stay after extend_stay, with the room charge projected to the new check-out.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StayExtension {
+    pub guest_id: i64,
+    pub check_in: String,
+    pub previous_check_out: Option<String>,
+    pub check_out: String,
+    pub nights: i64,
+    pub added_nights: i64,
+    pub room_total: f64, // For the whole stay up to the new check-out
+    pub added_amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomTypeRateBounds {
+    pub room_type: String,
+    pub floor_rate: f64,
+    pub ceiling_rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClosurePeriod {
+    pub id: i64,
+    pub start_date: String,
+    pub end_date: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PricingThresholds {
+    pub high_occupancy: f64,
+    pub high_adjustment_pct: f64,
+    pub low_occupancy: f64,
+    pub low_adjustment_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingSuggestion {
+    /// Stable identifier ("room_type|date") passed back to apply_pricing_suggestions.
+    pub id: String,
+    pub room_type: String,
+    pub date: String,
+    pub occupied_rooms: i64,
+    pub total_rooms: i64,
+    pub occupancy: f64,
+    pub current_rate: f64,
+    pub suggested_rate: f64,
+    pub adjustment_pct: f64,
+}
+
+// ===== EXPENSE MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpenseRecord {
+    pub id: i64,
+    pub date: String,
+    pub category: String,
+    pub description: Option<String>,
+    pub amount: f64,
+    pub is_drawing: bool,
+    pub tax_amount: f64, // Tax included in the amount, reclaimable on the tax return
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DrawingRow {
+    pub id: i64,
+    pub date: String,
+    pub category: String,
+    pub description: Option<String>,
+    pub amount: f64,
+    pub running_total: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DrawingsReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub rows: Vec<DrawingRow>,
+    pub total: f64,
+}
+
+// ===== BUSINESS HOURS MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BusinessHours {
+    pub day: String,
+    pub open: Option<String>,  // None when the business is closed that day
+    pub close: Option<String>,
+    pub crosses_midnight: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AfterHoursRow {
+    pub kind: String, // 'order' or 'check_in'
+    pub id: i64,
+    pub created_at: String,
+    pub description: Option<String>,
+    pub amount: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AfterHoursSummary {
+    pub start_date: String,
+    pub end_date: String,
+    pub orders: i64,
+    pub check_ins: i64,
+    pub rows: Vec<AfterHoursRow>,
+}
+
+// ===== MAINTENANCE MODELS =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceTicket {
+    pub id: i64,
+    pub room_id: i64,
+    pub room_number: String,
+    pub issue: String,
+    pub priority: String, // 'low', 'normal', 'high' or 'urgent'
+    pub status: String,   // 'open', 'in_progress' or 'resolved'
+    pub out_of_service: bool,
+    pub cost: Option<f64>,
+    pub reported_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub resolved_at: Option<String>,
+}
+
+// ===== ORDER STATUS MODELS =====
+
+/// An order as the kitchen sees it, with when it reached each status.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KitchenOrder {
+    pub id: i64,
+    pub guest_id: Option<i64>,
+    pub customer_name: Option<String>, // Guest name, or the walk-in name
+    pub room_number: Option<String>,
+    pub table_id: Option<i64>,
+    pub table_number: Option<String>,
+    pub status: String, // 'pending', 'preparing', 'served' or 'closed'
+    pub notes: Option<String>,
+    pub created_at: String,
+    pub preparing_at: Option<String>,
+    pub served_at: Option<String>,
+    pub closed_at: Option<String>,
+    pub total_amount: f64,
+    pub paid: bool,
+    pub items: Vec<OrderItemDetail>,
+}
+
+// ===== VOID MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VoidedItem {
+    pub id: i64,
+    pub sale_item_id: i64,
+    pub order_id: i64,
+    pub item_name: String,
+    pub quantity: i64,
+    pub amount: f64, // Line total taken off the order
+    pub reason: String,
+    pub note: Option<String>,
+    pub voided_by: Option<String>,
+    pub voided_at: String,
+}
+
+// ===== RESTAURANT TABLE MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiningTable {
+    pub id: i64,
+    pub number: String,
+    pub capacity: i64,
+    pub status: String, // 'free' or 'open'
+    pub opened_at: Option<String>,
+    pub open_orders: i64,  // Orders on the table that aren't closed yet
+    pub unpaid_amount: f64, // Still owed on those orders
+}
+
+// ===== AUDIT TRAIL MODELS =====
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AuditLogFilter {
+    pub username: Option<String>,
+    pub entity: Option<String>, // 'guest', 'room', 'order' or 'expense'
+    pub entity_id: Option<i64>,
+    pub action: Option<String>, // 'create', 'update' or 'delete'
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub username: Option<String>,
+    pub event_type: String,
+    pub entity: Option<String>,
+    pub entity_id: Option<i64>,
+    pub action: Option<String>,
+    pub old_values: Option<serde_json::Value>,
+    pub new_values: Option<serde_json::Value>,
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditLogPage {
+    pub entries: Vec<AuditLogEntry>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+// ===== PAGINATION MODELS =====
+
+/// One page of a list command. Each command documents the sort keys and
+/// filters it accepts; unknown ones are rejected rather than ignored.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PageRequest {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub sort: Option<String>, // Sort key, '-' prefix for descending (e.g. '-created_at')
+    #[serde(default)]
+    pub filters: std::collections::HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PageResult<T> {
+    pub items: Vec<T>,
+    pub total: i64, // Rows matching the filters, across all pages
+    pub limit: i64,
+    pub offset: i64,
+    pub next_offset: Option<i64>, // None on the last page
+}
+
+// ===== SEARCH MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub kind: String, // 'guest', 'order', 'menu_item' or 'expense'
+    pub id: i64,      // Id in that kind's table
+    pub title: String,
+    pub detail: Option<String>, // Phone for guests, category for expenses
+}
+
+// ===== DATA CHANGE EVENT MODELS =====
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuestChangedEvent {
+    pub guest_id: Option<i64>,
+    pub change: String, // 'checked_in', 'updated', 'checked_out' or 'imported'
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomStatusChangedEvent {
+    pub room_id: Option<i64>,
+    pub change: String, // 'added', 'updated', 'deleted', 'occupied', 'vacated', 'out_of_service' or 'in_service'
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderCreatedEvent {
+    pub order_id: i64,
+    pub guest_id: Option<i64>,
+    pub table_id: Option<i64>,
+    pub total_amount: f64,
+}
+
+// ===== SELF-TEST MODELS =====
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SelfTestStep {
+    pub name: String,
+    pub status: String, // 'passed', 'failed' or 'skipped'
+    pub duration_ms: u64,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub started_at: String,
+    pub total_ms: u64,
+    pub steps: Vec<SelfTestStep>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::value::{MapDeserializer, SeqDeserializer, StrDeserializer};
+    use serde::de::{DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, VariantAccess, Visitor};
+    use std::cell::{Cell, RefCell};
+    use crate::offline_auth::{
+        HeartbeatResponse, LoginRequest, LoginResponse, PasswordResetRequest, PasswordResetResponse,
+        RegisterInitialAdminRequest, RegisterUserRequest, SecurityQuestionResponse, SessionInfo, SessionPolicy, UserInfo,
+    };
+    use crate::stay_import::{ImportRowResult, StayColumnMapping, StayImportReport};
+
+    type PlaceholderError = serde::de::value::Error;
+
+    /// Deserializes into any model with every field empty (0, "", false, None,
+    /// no items), so each response struct can be serialized without building
+    /// one by hand. Enums take their first variant.
+    #[derive(Clone, Copy)]
+    struct Placeholder<'a>(&'a PlaceholderFields);
+
+    /// Serde lists a field's aliases next to its name, and a model given both
+    /// fails as a duplicate; the alias is then skipped on the next attempt.
+    #[derive(Default)]
+    struct PlaceholderFields {
+        skipped: RefCell<Vec<&'static str>>,
+        last: Cell<&'static str>,
+    }
+
+    macro_rules! placeholder_values {
+        ($($method:ident => $visit:ident($($value:expr)?)),+ $(,)?) => {
+            $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PlaceholderError> {
+                visitor.$visit($($value)?)
+            })+
+        };
+    }
+
+    impl<'de> Deserializer<'de> for Placeholder<'_> {
+        type Error = PlaceholderError;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PlaceholderError> {
+            visitor.visit_unit()
+        }
+
+        placeholder_values! {
+            deserialize_bool => visit_bool(false),
+            deserialize_i8 => visit_i64(0),
+            deserialize_i16 => visit_i64(0),
+            deserialize_i32 => visit_i64(0),
+            deserialize_i64 => visit_i64(0),
+            deserialize_u8 => visit_u64(0),
+            deserialize_u16 => visit_u64(0),
+            deserialize_u32 => visit_u64(0),
+            deserialize_u64 => visit_u64(0),
+            deserialize_f32 => visit_f64(0.0),
+            deserialize_f64 => visit_f64(0.0),
+            deserialize_char => visit_char(' '),
+            deserialize_str => visit_str(""),
+            deserialize_string => visit_str(""),
+            deserialize_bytes => visit_bytes(&[]),
+            deserialize_byte_buf => visit_bytes(&[]),
+            deserialize_option => visit_none(),
+            deserialize_unit => visit_unit(),
+            deserialize_identifier => visit_str(""),
+            deserialize_ignored_any => visit_unit(),
+        }
+
+        fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, PlaceholderError> {
+            visitor.visit_unit()
+        }
+
+        fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, PlaceholderError> {
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PlaceholderError> {
+            visitor.visit_seq(SeqDeserializer::new(std::iter::empty::<Placeholder>()))
+        }
+
+        fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, PlaceholderError> {
+            visitor.visit_seq(SeqDeserializer::new((0..len).map(|_| self)))
+        }
+
+        fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, len: usize, visitor: V) -> Result<V::Value, PlaceholderError> {
+            self.deserialize_tuple(len, visitor)
+        }
+
+        fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, PlaceholderError> {
+            visitor.visit_map(MapDeserializer::new(std::iter::empty::<(Placeholder, Placeholder)>()))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, PlaceholderError> {
+            let state = self.0;
+            let fields = fields.iter().filter(|field| !state.skipped.borrow().contains(field)).map(move |field| {
+                state.last.set(field);
+                (*field, self)
+            });
+            visitor.visit_map(MapDeserializer::new(fields))
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, PlaceholderError> {
+            visitor.visit_enum(FirstVariant(variants[0], self))
+        }
+    }
+
+    impl<'de> IntoDeserializer<'de, PlaceholderError> for Placeholder<'_> {
+        type Deserializer = Self;
+
+        fn into_deserializer(self) -> Self {
+            self
+        }
+    }
+
+    struct FirstVariant<'a>(&'static str, Placeholder<'a>);
+
+    impl<'de, 'a> EnumAccess<'de> for FirstVariant<'a> {
+        type Error = PlaceholderError;
+        type Variant = Placeholder<'a>;
+
+        fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Placeholder<'a>), PlaceholderError> {
+            let name: StrDeserializer<PlaceholderError> = self.0.into_deserializer();
+            Ok((seed.deserialize(name)?, self.1))
+        }
+    }
+
+    impl<'de> VariantAccess<'de> for Placeholder<'_> {
+        type Error = PlaceholderError;
+
+        fn unit_variant(self) -> Result<(), PlaceholderError> {
+            Ok(())
+        }
+
+        fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, PlaceholderError> {
+            seed.deserialize(self)
+        }
+
+        fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, PlaceholderError> {
+            self.deserialize_tuple(len, visitor)
+        }
+
+        fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, PlaceholderError> {
+            self.deserialize_struct("", fields, visitor)
+        }
+    }
+
+    fn is_snake_case(name: &str) -> bool {
+        name.starts_with(|c: char| c.is_ascii_lowercase())
+            && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            && !name.contains("__")
+            && !name.ends_with('_')
+    }
+
+    /// Field names in `value` and the objects nested in it that aren't
+    /// snake_case, each with its path from `path`.
+    fn non_snake_case_fields(value: &serde_json::Value, path: &str, found: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Object(fields) => {
+                for (name, field) in fields {
+                    let field_path = format!("{}.{}", path, name);
+                    if !is_snake_case(name) {
+                        found.push(field_path.clone());
+                    }
+                    non_snake_case_fields(field, &field_path, found);
+                }
+            }
+            serde_json::Value::Array(items) => items.iter().for_each(|item| non_snake_case_fields(item, path, found)),
+            _ => {}
+        }
+    }
+
+    /// Serialize a placeholder `T` and list its fields that aren't snake_case.
+    fn model_field_offenders<T: Serialize + DeserializeOwned>(model: &str) -> Result<Vec<String>, String> {
+        let fields = PlaceholderFields::default();
+        let placeholder = loop {
+            match T::deserialize(Placeholder(&fields)) {
+                Ok(placeholder) => break placeholder,
+                Err(e) if e.to_string().starts_with("duplicate field") => fields.skipped.borrow_mut().push(fields.last.get()),
+                Err(e) => return Err(format!("can't build a placeholder {}: {}", model, e)),
+            }
+        };
+        let value = serde_json::to_value(placeholder).map_err(|e| e.to_string())?;
+        let mut found = Vec::new();
+        non_snake_case_fields(&value, model, &mut found);
+        Ok(found)
+    }
+
+    // Every public serialized struct in src/; the test below checks none is missing
+    macro_rules! check_field_names {
+        ($($model:ident $(<$param:ty>)?),+ $(,)?) => {
+            fn field_offenders() -> Result<Vec<String>, String> {
+                let mut offenders = Vec::new();
+                $(offenders.extend(model_field_offenders::<$model $(<$param>)?>(stringify!($model))?);)+
+                Ok(offenders)
+            }
+
+            const LISTED: &[&str] = &[$(stringify!($model)),+];
+        };
+    }
+
+    check_field_names!(
+        Resource, FloorOccupancy, RoomType, RoomTypeInput, NewCustomer, Customer, ActiveCustomerRow, MenuItem, MenuCategory,
+        MenuPriceRule, MenuPriceRuleInput, ComboItem, Combo, ComboItemInput, ComboInput, MenuItemAttribute,
+        MenuImportOptions, MenuImportRow, MenuImportReport, NewOrderItem, NewFoodOrder, OrderSummary, OrderItem,
+        ExpenseInput, ExpenseRow, CheckoutTotals, BusinessInfo, Tax, TaxLine, GuestDeposit, GuestFolio, GuestProfile,
+        GuestProfileInput, ProfileStay, GuestProfileHistory, DuplicateGuestGroup, GuestMergeResult, GuestAnonymization,
+        DashboardStats, ChartSeries, DashboardGraphs, RevenueTimeseries, ExpenseTimeseries, ExpenseCategory,
+        ExpenseImportMapping, ImportCounts, ImportIssue, LegacyImportReport, ImportAdapterInfo, ExpenseImportRow, TrashItem,
+        ExpenseImportReport, ExpenseBudget, BudgetLine, BudgetVsActualReport, LedgerAccount, JournalLine, JournalEntry,
+        NewJournalLine, TrialBalanceLine, TrialBalance, ProfitAndLossLine, ProfitAndLoss, TaxReportLine, ExpenseTaxLine,
+        TaxReport, MonthlyReport, HistoryQuery, HistoryRow, OrderItemInput, OrderItemModifier, SaleSummary, SaleRecord,
+        OrderPayment, PaymentRefund, OrderPaymentSummary, PaymentMethodTotal, PaymentsByMethodReport, NightAuditStay,
+        NightAuditReport, OrderItemDetail, OrderItemComponent, SaleDetails, LowStockItem, InventoryItem,
+        RecipeIngredientInput, RecipeIngredient, StockMovement, LowStockIngredient, MenuItemProfitability, Supplier,
+        PurchaseOrderLineInput, PurchaseOrderLine, PurchaseOrder, ReceiveLineInput, SupplierPayment, Company, CompanyPayment,
+        CompanyStatementEntry, CompanyStatement, ReceivableItem, AgingTotals, ReceivablesAgingReport, ServiceChargeSetting,
+        ServiceChargeDay, ServiceChargeReport, ShiftSummary, DenominationCount, ShiftPayment, ShiftMethodTotal,
+        ShiftReconciliation, RatingBucket, FeedbackComment, FeedbackReport, RoomRatingRow, Tag, TagUsage, MenuSyncRow,
+        SettingSyncRow, SyncSnapshot, SyncChange, SyncSummary, SyncServerStatus, DatabaseStats, DataIntegrityReport,
+        SecurityQuestion, ExportJobStatus, MissingMedia, MediaFeatureReport, MediaIntegrityReport, PrintTemplateInfo,
+        PrintTemplateSource, ReceiptQrSetting, ReceiptLanguageInfo, NotificationProviderSettings, NotificationEventSetting,
+        NotificationLogEntry, BackupSchedule, BackupFileInfo, DatabaseEncryptionStatus, CloudBackupSettingsInput,
+        CloudBackupSettings, CloudBackupStatus, Voucher, NewVoucher, VoucherCheck, VoucherRedemption,
+        PaymentGatewaySettingsInput, PaymentGatewaySettings, OnlinePayment, FiscalSettingsInput, FiscalSettings,
+        FiscalRecord, TableRestoreResult, MigrationRecord, PostUpdateReport, Reservation, GroupRoomInput, NewGroupBooking,
+        GroupMember, GroupBooking, GroupMemberResult, RoomCalendarDay, RoomCalendarRow, OccupancyCapSettings, ReopenedStay,
+        StayFeeRule, StayFeeRules, DatePlausibilitySettings, OutlierDateRow, CheckoutHold, GuestAdjustment, GuestCharge,
+        DailySpecialInput, DailySpecial, SpecialsPerformanceRow, PriceChange, BulkPriceUpdateResult, RateOverride, RatePlan,
+        NightlyRate, StaySegment, StayExtension, RoomTypeRateBounds, ClosurePeriod, PricingThresholds, PricingSuggestion,
+        ExpenseRecord, DrawingRow, DrawingsReport, BusinessHours, AfterHoursRow, AfterHoursSummary, MaintenanceTicket,
+        KitchenOrder, VoidedItem, DiningTable, AuditLogFilter, AuditLogEntry, AuditLogPage, PageRequest,
+        PageResult<SearchHit>, SearchHit, GuestChangedEvent, RoomStatusChangedEvent, OrderCreatedEvent,
+        SelfTestStep, SelfTestReport, MenuCategorySyncRow, MenuPriceRuleSyncRow, ComboItemSyncRow, ComboSyncRow, TaxSyncRow,
+        LoginRequest, LoginResponse, SecurityQuestionResponse, PasswordResetRequest, PasswordResetResponse, SessionInfo,
+        HeartbeatResponse, SessionPolicy, UserInfo, RegisterInitialAdminRequest, RegisterUserRequest, StayColumnMapping,
+        ImportRowResult, StayImportReport,
+    );
+
+    #[test]
+    fn every_model_serializes_with_snake_case_fields() {
+        let offenders = field_offenders().unwrap();
+        assert!(offenders.is_empty(), "fields not in snake_case: {}", offenders.join(", "));
+    }
+
+    /// Public structs in `source` that derive Serialize. Private ones (key
+    /// files, fiscal API payloads) never reach the frontend.
+    fn serialized_structs(source: &str) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut serialized = false;
+        for line in source.lines().map(str::trim) {
+            if line.starts_with("#[derive(") {
+                serialized = line.split(|c: char| !c.is_alphanumeric() && c != '_').any(|word| word == "Serialize");
+            } else if let Some(rest) = line.strip_prefix("pub struct ") {
+                if serialized {
+                    found.push(rest.split(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or(rest).to_string());
+                }
+                serialized = false;
+            } else if !line.starts_with("#[") && !line.starts_with("//") {
+                serialized = false;
+            }
+        }
+        found
+    }
+
+    // Otherwise a new model could slip in with camelCase fields, here or in
+    // the module that serves it
+    #[test]
+    fn the_field_name_check_covers_every_model() {
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut missing = Vec::new();
+        for entry in std::fs::read_dir(&src).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().and_then(|e| e.to_str()) != Some("rs") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            for name in serialized_structs(&source) {
+                if !LISTED.contains(&name.as_str()) {
+                    missing.push(format!("{}::{}", path.file_stem().unwrap().to_string_lossy(), name));
+                }
+            }
+        }
+        missing.sort();
+        assert!(missing.is_empty(), "models missing from the field name check: {:?}", missing);
+    }
 }
@@ -0,0 +1,119 @@
+use rusqlite::Connection;
+use crate::repository::get_non_empty_setting;
+
+// Symbols for currencies commonly set up; any other code prints as the code
+const SYMBOLS: &[(&str, &str)] = &[
+    ("USD", "$"),
+    ("EUR", "€"),
+    ("GBP", "£"),
+    ("JPY", "¥"),
+    ("INR", "₹"),
+    ("PKR", "Rs"),
+    ("LKR", "Rs"),
+    ("NPR", "Rs"),
+    ("BDT", "৳"),
+    ("NGN", "₦"),
+    ("PHP", "₱"),
+    ("KRW", "₩"),
+    ("TRY", "₺"),
+];
+
+// Languages that group with '.' and use ',' for decimals, with the currency after the amount
+const DOT_GROUPED: &[&str] = &["de", "es", "it", "pt", "nl", "id", "tr", "da"];
+// Languages that group with a space and use ',' for decimals, with the currency after the amount
+const SPACE_GROUPED: &[&str] = &["fr", "ru", "pl", "sv", "nb", "fi", "cs", "uk"];
+
+/// Formats amounts in the configured currency the way the configured locale
+/// writes numbers, e.g. "$1,234.50", "Rs 1,234" or "1.234,50 €".
+pub struct Money {
+    code: String,
+    group: &'static str,
+    decimal: &'static str,
+    symbol_after: bool,
+}
+
+impl Money {
+    /// The `currency_code` and `locale` settings; USD in en-US until they're set.
+    pub fn load(conn: &Connection) -> Result<Self, String> {
+        let code = get_non_empty_setting(conn, "currency_code")?.unwrap_or_else(|| "USD".to_string());
+        let locale = get_non_empty_setting(conn, "locale")?.unwrap_or_else(|| "en-US".to_string());
+        Ok(Self::new(&code, &locale))
+    }
+
+    pub fn new(code: &str, locale: &str) -> Self {
+        let language = locale.trim().split(['-', '_']).next().unwrap_or("").to_lowercase();
+        let (group, decimal, symbol_after) = if DOT_GROUPED.contains(&language.as_str()) {
+            (".", ",", true)
+        } else if SPACE_GROUPED.contains(&language.as_str()) {
+            ("\u{a0}", ",", true)
+        } else {
+            (",", ".", false)
+        };
+        Money { code: code.trim().to_uppercase(), group, decimal, symbol_after }
+    }
+
+    /// The ISO currency code, e.g. "PKR".
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    fn symbol(&self) -> &str {
+        SYMBOLS.iter().find(|(code, _)| *code == self.code).map_or(self.code.as_str(), |(_, symbol)| *symbol)
+    }
+
+    /// `amount` with `decimals` decimal places and the currency symbol.
+    pub fn format(&self, amount: f64, decimals: usize) -> String {
+        let amount = if amount.is_finite() { amount } else { 0.0 };
+        let fixed = format!("{:.*}", decimals, amount.abs());
+        let (whole, fraction) = fixed.split_once('.').unwrap_or((&fixed, ""));
+
+        let mut number = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                number.push_str(self.group);
+            }
+            number.push(digit);
+        }
+        if !fraction.is_empty() {
+            number.push_str(self.decimal);
+            number.push_str(fraction);
+        }
+
+        // Negative zero after rounding prints without a sign
+        let sign = if amount < 0.0 && fixed.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+        let symbol = self.symbol();
+        if self.symbol_after {
+            format!("{}{}\u{a0}{}", sign, number, symbol)
+        } else if symbol.chars().all(|c| c.is_ascii_alphabetic()) {
+            // Letter symbols like "Rs" or "AED" need a space before the amount
+            format!("{}{} {}", sign, symbol, number)
+        } else {
+            format!("{}{}{}", sign, symbol, number)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{insert_food_order, set_currency_code};
+    use crate::test_support::{order_item, TestDb};
+
+    #[test]
+    fn amounts_are_written_the_way_the_locale_writes_them() {
+        assert_eq!(Money::new("USD", "en-US").format(1234.5, 2), "$1,234.50");
+        assert_eq!(Money::new("pkr", "ur-PK").format(1234.0, 0), "Rs 1,234");
+        assert_eq!(Money::new("EUR", "de-DE").format(1234.5, 2), "1.234,50\u{a0}€");
+        assert_eq!(Money::new("AED", "en").format(-0.001, 2), "AED 0.00", "negative zero printed with a sign");
+    }
+
+    #[test]
+    fn receipts_use_the_configured_currency() {
+        let _db = TestDb::new();
+        set_currency_code("PKR".to_string()).unwrap();
+        crate::simple_commands::set_tax_enabled(false).unwrap();
+        let (order_id, _) = insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Coffee", 5.5, 2)], None, None, None).unwrap();
+        let receipt = crate::print_templates::build_order_receipt_html(order_id, None).unwrap();
+        assert!(receipt.contains("Rs 11.00"), "receipt does not use the configured currency");
+    }
+}
@@ -0,0 +1,303 @@
+use tauri::command;
+use std::fs;
+use chrono::NaiveDate;
+use rusqlite::{params, Connection};
+use serde_json::json;
+use crate::db::{get_current_timestamp, get_db_connection, run_blocking};
+use crate::errors::AppError;
+use crate::export::escape_csv;
+use crate::models::{NightAuditReport, NightAuditStay};
+use crate::money::Money;
+use crate::print_templates::html_escape;
+use crate::repository::get_setting_or;
+use crate::simple_commands::{payment_method_totals, to_cents};
+use crate::validation::validate_date_format;
+
+const STAY_COLUMNS: &str = "c.id, c.name, r.number, c.check_in, c.check_out, c.daily_rate, c.nightly_pricing, r.room_type";
+
+fn round_cents(amount: f64) -> f64 {
+    to_cents(amount) as f64 / 100.0
+}
+
+/// Room stays matching `condition` (on customers c joined to resources r),
+/// each with the rate for the night of `date`.
+fn load_stays(conn: &Connection, condition: &str, date: &str) -> Result<Vec<NightAuditStay>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM customers c JOIN resources r ON r.id = c.room_id WHERE {} ORDER BY r.number, c.id",
+        STAY_COLUMNS, condition
+    ))?;
+    let rows = stmt
+        .query_map(params![date], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, f64>(5)?,
+                row.get::<_, bool>(6)?,
+                row.get::<_, String>(7)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stays = Vec::new();
+    for (guest_id, name, room_number, check_in, check_out, daily_rate, nightly_pricing, room_type) in rows {
+        let rate = if nightly_pricing {
+            crate::pricing::nightly_rate(conn, &room_type, date, daily_rate)?.rate
+        } else {
+            daily_rate
+        };
+        stays.push(NightAuditStay { guest_id, name, room_number, check_in, check_out, amount: round_cents(rate), balance: None });
+    }
+    Ok(stays)
+}
+
+/// Build the report for `date`. Balances and receivables are as they stand
+/// now, so a report for an earlier day shows what is still owed from it.
+pub fn build_night_audit(conn: &Connection, date: &str) -> Result<NightAuditReport, AppError> {
+    validate_date_format(date)?;
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
+
+    let check_ins = load_stays(conn, "c.check_in = ?1", date)?;
+
+    let mut check_outs = load_stays(conn, "c.status = 'checked_out' AND substr(c.check_out, 1, 10) = ?1", date)?;
+    for stay in &mut check_outs {
+        let billed: f64 = conn.query_row(
+            "SELECT COALESCE(checkout_total, billed_amount, 0) FROM customers WHERE id = ?1",
+            params![stay.guest_id],
+            |row| row.get(0),
+        )?;
+        stay.amount = round_cents(billed);
+    }
+
+    // Occupied overnight, plus day stays that checked in and out on the date
+    let mut in_house = load_stays(
+        conn,
+        "c.check_in <= ?1
+         AND (c.status = 'active' OR substr(c.check_out, 1, 10) > ?1
+              OR (c.check_in = ?1 AND substr(c.check_out, 1, 10) = ?1))",
+        date,
+    )?;
+    let active: Vec<i64> = conn
+        .prepare("SELECT id FROM customers WHERE status = 'active'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    for stay in &mut in_house {
+        if active.contains(&stay.guest_id) {
+            stay.balance = Some(round_cents(crate::folio::build_folio(conn, stay.guest_id)?.balance));
+        }
+    }
+
+    let total_rooms: i64 = conn.query_row("SELECT COUNT(*) FROM resources WHERE is_active = 1", [], |row| row.get(0))?;
+    let occupancy_percent = if total_rooms > 0 {
+        (in_house.len() as f64 / total_rooms as f64 * 100.0).min(100.0)
+    } else {
+        0.0
+    };
+    let room_revenue = in_house.iter().map(|s| to_cents(s.amount)).sum::<i64>() as f64 / 100.0;
+
+    let (food_orders, food_revenue): (i64, f64) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(total_amount), 0) FROM sales WHERE date(created_at) = ?1 AND deleted_at IS NULL",
+        params![date],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let payments = payment_method_totals(conn, date, date)?;
+    let payments_total = payments.iter().map(|m| to_cents(m.total)).sum::<i64>() as f64 / 100.0;
+
+    let today = NaiveDate::parse_from_str(&crate::simple_commands::business_date_today(), "%Y-%m-%d")?;
+    let outstanding: Vec<_> = crate::receivables::outstanding_items(conn, today)?
+        .into_iter()
+        .filter(|item| NaiveDate::parse_from_str(item.date.get(..10).unwrap_or(&item.date), "%Y-%m-%d").is_ok_and(|d| d <= day))
+        .collect();
+    let outstanding_total = outstanding.iter().map(|i| to_cents(i.amount)).sum::<i64>() as f64 / 100.0;
+
+    Ok(NightAuditReport {
+        date: date.to_string(),
+        check_ins,
+        check_outs,
+        in_house,
+        total_rooms,
+        occupancy_percent,
+        room_revenue,
+        food_orders,
+        food_revenue: round_cents(food_revenue),
+        payments,
+        payments_total,
+        outstanding,
+        outstanding_total,
+        generated_at: get_current_timestamp(),
+    })
+}
+
+/// End-of-day summary for the owner: arrivals, departures, rooms occupied
+/// overnight, room and food revenue, payments by method and what is still owed.
+#[command]
+pub async fn night_audit_report(date: String) -> Result<NightAuditReport, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        build_night_audit(&conn, &date)
+    })
+    .await
+}
+
+fn stay_rows_csv(out: &mut String, title: &str, stays: &[NightAuditStay], amount_header: &str) {
+    out.push_str(&format!("{}\nGuest,Room,Check-in,Check-out,{},Balance\n", title, amount_header));
+    for stay in stays {
+        out.push_str(&format!(
+            "{},{},{},{},{:.2},{}\n",
+            escape_csv(&stay.name),
+            escape_csv(&stay.room_number),
+            stay.check_in,
+            stay.check_out.as_deref().unwrap_or(""),
+            stay.amount,
+            stay.balance.map(|b| format!("{:.2}", b)).unwrap_or_default()
+        ));
+    }
+    out.push('\n');
+}
+
+/// The report as CSV sections, one after another, ending with sign-off lines.
+fn night_audit_csv(report: &NightAuditReport, currency: &str) -> String {
+    let mut out = format!("Night audit,{}\nCurrency,{}\n\n", report.date, currency);
+    out.push_str("Summary\nItem,Value\n");
+    out.push_str(&format!("Rooms occupied,{} of {}\n", report.in_house.len(), report.total_rooms));
+    out.push_str(&format!("Occupancy %,{:.1}\n", report.occupancy_percent));
+    out.push_str(&format!("Check-ins,{}\nCheck-outs,{}\n", report.check_ins.len(), report.check_outs.len()));
+    out.push_str(&format!("Room revenue,{:.2}\n", report.room_revenue));
+    out.push_str(&format!("Food orders,{}\nFood revenue,{:.2}\n", report.food_orders, report.food_revenue));
+    out.push_str(&format!("Payments taken,{:.2}\nOutstanding,{:.2}\n\n", report.payments_total, report.outstanding_total));
+
+    stay_rows_csv(&mut out, "Check-ins", &report.check_ins, "Rate");
+    stay_rows_csv(&mut out, "Check-outs", &report.check_outs, "Billed");
+    stay_rows_csv(&mut out, "In house", &report.in_house, "Rate");
+
+    out.push_str("Payments by method\nMethod,Orders,Order Amount,Checkouts,Checkout Amount,Total\n");
+    for method in &report.payments {
+        out.push_str(&format!(
+            "{},{},{:.2},{},{:.2},{:.2}\n",
+            method.method, method.order_count, method.order_amount, method.checkout_count, method.checkout_amount, method.total
+        ));
+    }
+    out.push('\n');
+
+    out.push_str("Outstanding balances\nSource,Name,Date,Age (days),Amount\n");
+    for item in &report.outstanding {
+        out.push_str(&format!("{},{},{},{},{:.2}\n", item.source, escape_csv(&item.name), item.date, item.age_days, item.amount));
+    }
+    out.push_str(&format!("\nPrepared at,{}\nNight auditor signature,\nOwner signature,\n", report.generated_at));
+    out
+}
+
+fn stay_rows_html(stays: &[NightAuditStay], money: &Money) -> String {
+    if stays.is_empty() {
+        return r#"<tr><td colspan="5" class="empty">None</td></tr>"#.to_string();
+    }
+    stays
+        .iter()
+        .map(|stay| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{} &ndash; {}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                html_escape(&stay.name),
+                html_escape(&stay.room_number),
+                html_escape(&stay.check_in),
+                html_escape(stay.check_out.as_deref().unwrap_or("")),
+                money.format(stay.amount, 2),
+                stay.balance.map(|b| money.format(b, 2)).unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+/// The report rendered through the night_audit print template.
+fn night_audit_html(conn: &Connection, report: &NightAuditReport) -> Result<String, String> {
+    let money = Money::load(conn)?;
+    let payment_rows: String = report
+        .payments
+        .iter()
+        .map(|m| {
+            format!(
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                html_escape(&m.method.replace('_', " ")),
+                money.format(m.order_amount, 2),
+                money.format(m.checkout_amount, 2),
+                money.format(m.total, 2)
+            )
+        })
+        .collect();
+    let outstanding_rows: String = if report.outstanding.is_empty() {
+        r#"<tr><td colspan="4" class="empty">Nothing outstanding</td></tr>"#.to_string()
+    } else {
+        report
+            .outstanding
+            .iter()
+            .map(|item| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td></tr>",
+                    html_escape(&item.source),
+                    html_escape(&item.name),
+                    html_escape(&item.date),
+                    money.format(item.amount, 2)
+                )
+            })
+            .collect()
+    };
+
+    crate::templates::render(
+        "night_audit",
+        &json!({
+            "business_name": get_setting_or(conn, "business_name", "Business Manager")?,
+            "business_contact": crate::print_templates::business_contact(conn)?,
+            "date": report.date,
+            "rooms_occupied": report.in_house.len(),
+            "total_rooms": report.total_rooms,
+            "occupancy": format!("{:.1}%", report.occupancy_percent),
+            "check_in_count": report.check_ins.len(),
+            "check_out_count": report.check_outs.len(),
+            "room_revenue": money.format(report.room_revenue, 2),
+            "food_orders": report.food_orders,
+            "food_revenue": money.format(report.food_revenue, 2),
+            "payments_total": money.format(report.payments_total, 2),
+            "outstanding_total": money.format(report.outstanding_total, 2),
+            "check_in_rows": stay_rows_html(&report.check_ins, &money),
+            "check_out_rows": stay_rows_html(&report.check_outs, &money),
+            "in_house_rows": stay_rows_html(&report.in_house, &money),
+            "payment_rows": payment_rows,
+            "outstanding_rows": outstanding_rows,
+            "generated_at": report.generated_at,
+        }),
+    )
+}
+
+/// Save the night audit for `date` as "csv" or "pdf" in the exports folder
+/// and return the file's path. PDFs need Chrome, Edge or Chromium installed.
+#[command]
+pub async fn export_night_audit(date: String, format: String) -> Result<String, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        let report = build_night_audit(&conn, &date)?;
+        let stem = format!("night_audit_{}", date);
+
+        let path = match format.trim().to_lowercase().as_str() {
+            "csv" => {
+                let path = crate::export_jobs::default_export_path(&stem, "csv")?;
+                let csv = night_audit_csv(&report, Money::load(&conn)?.code());
+                fs::write(&path, csv).map_err(|e| format!("Failed to write night audit: {}", e))?;
+                path
+            }
+            "pdf" => {
+                let path = crate::export_jobs::default_export_path(&stem, "pdf")?;
+                let html_path = path.with_extension("html");
+                fs::write(&html_path, night_audit_html(&conn, &report)?).map_err(|e| format!("Failed to write night audit: {}", e))?;
+                let rendered = crate::print_templates::render_html_to_pdf(&html_path, &path);
+                let _ = fs::remove_file(&html_path);
+                rendered?;
+                path
+            }
+            other => return Err(format!("Unknown format '{}'; use csv or pdf", other).into()),
+        };
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+}
@@ -0,0 +1,518 @@
+use tauri::command;
+use base64::Engine;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::time::Duration;
+use crate::db::{get_db_connection, get_db_path, get_current_timestamp, run_blocking, with_db_path_override};
+use crate::errors::AppError;
+use crate::models::{NotificationEventSetting, NotificationLogEntry, NotificationProviderSettings};
+use crate::validation::EMPTY_FIELD;
+use crate::repository::{get_non_empty_setting, set_or_clear_setting};
+
+const PROVIDERS: &[&str] = &["none", "twilio", "whatsapp_business"];
+const CHANNELS: &[&str] = &["sms", "whatsapp"];
+const SEND_TIMEOUT_SECS: u64 = 15;
+const WHATSAPP_API_VERSION: &str = "v19.0";
+
+// Event types and the message each sends until it's customised
+const EVENTS: &[(&str, &str)] = &[
+    (
+        "booking_confirmation",
+        "Hi {guest_name}, your booking at {business_name} is confirmed: room {room}, {check_in} to {check_out}. Reference #{reference}.",
+    ),
+    (
+        "checkout_invoice",
+        "Thank you for staying at {business_name}, {guest_name}. Your bill for room {room} ({check_in} to {check_out}) came to {total}.",
+    ),
+    (
+        "payment_reminder",
+        "Hi {guest_name}, a balance of {balance} is due on your stay in room {room} at {business_name}. Please settle it at the front desk.",
+    ),
+];
+
+const PLACEHOLDERS: &[&str] = &["guest_name", "business_name", "room", "check_in", "check_out", "reference", "total", "balance"];
+
+/// Who a message is about; decides the recipient and the placeholder values.
+#[derive(Clone, Copy)]
+pub enum NotificationSubject {
+    Reservation(i64),
+    Guest(i64),
+}
+
+/// Something that can deliver a text message to a phone number.
+trait Provider {
+    /// Send `body` to `to` (E.164) and return the provider's message id.
+    fn send(&self, channel: &str, to: &str, body: &str) -> Result<String, String>;
+}
+
+struct Twilio {
+    account_sid: String,
+    auth_token: String,
+    sms_from: Option<String>,
+    whatsapp_from: Option<String>,
+}
+
+struct WhatsAppBusiness {
+    access_token: String,
+    phone_number_id: String,
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(Duration::from_secs(SEND_TIMEOUT_SECS)).build()
+}
+
+fn http_error(provider: &str, e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, resp) => {
+            format!("{} returned HTTP {}: {}", provider, code, resp.into_string().unwrap_or_default())
+        }
+        ureq::Error::Transport(t) => format!("Could not reach {}: {}", provider, t),
+    }
+}
+
+impl Provider for Twilio {
+    fn send(&self, channel: &str, to: &str, body: &str) -> Result<String, String> {
+        let (from, to) = match channel {
+            "whatsapp" => (
+                self.whatsapp_from.as_ref().map(|f| format!("whatsapp:{}", f)),
+                format!("whatsapp:{}", to),
+            ),
+            _ => (self.sms_from.clone(), to.to_string()),
+        };
+        let from = from.ok_or(format!("No Twilio sender number is set for {}", channel))?;
+        let url = format!("https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json", self.account_sid);
+        let credentials = base64::engine::general_purpose::STANDARD
+            .encode(format!("{}:{}", self.account_sid, self.auth_token));
+
+        let response: serde_json::Value = agent()
+            .post(&url)
+            .set("Authorization", &format!("Basic {}", credentials))
+            .send_form(&[("To", to.as_str()), ("From", from.as_str()), ("Body", body)])
+            .map_err(|e| http_error("Twilio", e))?
+            .into_json()
+            .map_err(|e| format!("Unexpected reply from Twilio: {}", e))?;
+        Ok(response["sid"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+impl Provider for WhatsAppBusiness {
+    /// Free-form text is only delivered within 24 hours of the guest's last
+    /// message; outside that window Meta requires an approved template.
+    fn send(&self, channel: &str, to: &str, body: &str) -> Result<String, String> {
+        if channel != "whatsapp" {
+            return Err("The WhatsApp Business API cannot send SMS".to_string());
+        }
+        let url = format!("https://graph.facebook.com/{}/{}/messages", WHATSAPP_API_VERSION, self.phone_number_id);
+        let response: serde_json::Value = agent()
+            .post(&url)
+            .set("Authorization", &format!("Bearer {}", self.access_token))
+            .send_json(serde_json::json!({
+                "messaging_product": "whatsapp",
+                "to": to.trim_start_matches('+'),
+                "type": "text",
+                "text": { "body": body },
+            }))
+            .map_err(|e| http_error("WhatsApp", e))?
+            .into_json()
+            .map_err(|e| format!("Unexpected reply from WhatsApp: {}", e))?;
+        Ok(response["messages"][0]["id"].as_str().unwrap_or_default().to_string())
+    }
+}
+
+fn provider_name(conn: &Connection) -> Result<String, String> {
+    Ok(get_non_empty_setting(conn, "notification_provider")?.unwrap_or_else(|| "none".to_string()))
+}
+
+fn load_provider(conn: &Connection) -> Result<Box<dyn Provider>, String> {
+    let require = |key: &str, what: &str| -> Result<String, String> {
+        get_non_empty_setting(conn, key)?.ok_or(format!("{} is not set", what))
+    };
+    match provider_name(conn)?.as_str() {
+        "twilio" => Ok(Box::new(Twilio {
+            account_sid: require("twilio_account_sid", "Twilio account SID")?,
+            auth_token: require("twilio_auth_token", "Twilio auth token")?,
+            sms_from: get_non_empty_setting(conn, "twilio_from")?,
+            whatsapp_from: get_non_empty_setting(conn, "twilio_whatsapp_from")?,
+        })),
+        "whatsapp_business" => Ok(Box::new(WhatsAppBusiness {
+            access_token: require("whatsapp_access_token", "WhatsApp access token")?,
+            phone_number_id: require("whatsapp_phone_number_id", "WhatsApp phone number ID")?,
+        })),
+        _ => Err("No SMS/WhatsApp provider is set up".to_string()),
+    }
+}
+
+/// A phone number as providers want it: "+" and digits only. Numbers have
+/// to carry their country code since guests can be from anywhere.
+fn normalize_phone(phone: &str) -> Result<String, String> {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    let international = phone.trim().starts_with('+') || phone.trim().starts_with("00");
+    let digits = if phone.trim().starts_with("00") { digits[2..].to_string() } else { digits };
+    if !international || !(8..=15).contains(&digits.len()) {
+        return Err(format!("'{}' needs to be an international number with its country code, e.g. +923001234567", phone.trim()));
+    }
+    Ok(format!("+{}", digits))
+}
+
+fn default_template(event_type: &str) -> Result<&'static str, String> {
+    EVENTS
+        .iter()
+        .find(|(event, _)| *event == event_type)
+        .map(|(_, template)| *template)
+        .ok_or_else(|| {
+            let events: Vec<&str> = EVENTS.iter().map(|(event, _)| *event).collect();
+            format!("Event type must be one of: {}", events.join(", "))
+        })
+}
+
+fn load_event(conn: &Connection, event_type: &str) -> Result<NotificationEventSetting, String> {
+    let default = default_template(event_type)?;
+    let row: Option<(bool, String, Option<String>)> = conn
+        .query_row(
+            "SELECT enabled, channel, template FROM notification_events WHERE event_type = ?1",
+            params![event_type],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let (enabled, channel, template) = row.unwrap_or((false, "sms".to_string(), None));
+    Ok(NotificationEventSetting {
+        event_type: event_type.to_string(),
+        enabled,
+        channel,
+        template: template.unwrap_or_else(|| default.to_string()),
+        placeholders: PLACEHOLDERS.iter().map(|p| p.to_string()).collect(),
+    })
+}
+
+/// Recipient phone and placeholder values for a message.
+type SubjectDetails = (Option<String>, Vec<(&'static str, String)>);
+
+fn subject_details(conn: &Connection, subject: NotificationSubject) -> Result<SubjectDetails, String> {
+    let money = crate::money::Money::load(conn)?;
+    let business_name = get_non_empty_setting(conn, "business_name")?.unwrap_or_else(|| "Business Manager".to_string());
+    match subject {
+        NotificationSubject::Reservation(id) => {
+            let (name, phone, room, check_in, check_out): (String, Option<String>, Option<String>, String, String) = conn
+                .query_row(
+                    "SELECT res.guest_name, res.phone, r.number, res.check_in, res.check_out
+                     FROM reservations res LEFT JOIN resources r ON r.id = res.room_id
+                     WHERE res.id = ?1",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .ok_or(format!("Reservation #{} not found", id))?;
+            Ok((phone, vec![
+                ("guest_name", name),
+                ("business_name", business_name),
+                ("room", room.unwrap_or_default()),
+                ("check_in", check_in),
+                ("check_out", check_out),
+                ("reference", id.to_string()),
+                ("total", String::new()),
+                ("balance", String::new()),
+            ]))
+        }
+        NotificationSubject::Guest(id) => {
+            type GuestRow = (String, Option<String>, Option<String>, String, Option<String>, String, Option<f64>);
+            let (name, phone, room, check_in, check_out, status, checkout_total): GuestRow = conn
+                .query_row(
+                    "SELECT c.name, c.phone, r.number, c.check_in, c.check_out, c.status, c.checkout_total
+                     FROM customers c LEFT JOIN resources r ON r.id = c.room_id
+                     WHERE c.id = ?1",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?)),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .ok_or(format!("{}: guest #{}", crate::validation::GUEST_NOT_FOUND, id))?;
+            let balance = if status == "active" { crate::folio::build_folio(conn, id)?.balance } else { 0.0 };
+            Ok((phone, vec![
+                ("guest_name", name),
+                ("business_name", business_name),
+                ("room", room.unwrap_or_default()),
+                ("check_in", check_in),
+                ("check_out", check_out.unwrap_or_default()),
+                ("reference", id.to_string()),
+                ("total", checkout_total.map(|t| money.format(t, 2)).unwrap_or_default()),
+                ("balance", money.format(balance.max(0.0), 2)),
+            ]))
+        }
+    }
+}
+
+fn fill_template(template: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (key, value)| text.replace(&format!("{{{}}}", key), value))
+}
+
+fn log_message(conn: &Connection, entry: &NotificationLogEntry) -> Result<i64, String> {
+    conn.execute(
+        "INSERT INTO notification_log (event_type, channel, recipient, message, status, error, provider_message_id, guest_id, reservation_id, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            entry.event_type,
+            entry.channel,
+            entry.recipient,
+            entry.message,
+            entry.status,
+            entry.error,
+            entry.provider_message_id,
+            entry.guest_id,
+            entry.reservation_id,
+            entry.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Send `message` over `channel` and record the outcome in the log, sent or not.
+fn deliver(
+    conn: &Connection,
+    event_type: &str,
+    channel: &str,
+    phone: &str,
+    message: String,
+    subject: Option<NotificationSubject>,
+) -> Result<NotificationLogEntry, String> {
+    let outcome = normalize_phone(phone).and_then(|to| {
+        let provider = load_provider(conn)?;
+        provider.send(channel, &to, &message).map(|id| (to, id))
+    });
+    let (recipient, status, error, provider_message_id) = match outcome {
+        Ok((to, id)) => (to, "sent", None, Some(id).filter(|id| !id.is_empty())),
+        Err(e) => (phone.trim().to_string(), "failed", Some(e), None),
+    };
+    let mut entry = NotificationLogEntry {
+        id: 0,
+        event_type: event_type.to_string(),
+        channel: channel.to_string(),
+        recipient,
+        message,
+        status: status.to_string(),
+        error,
+        provider_message_id,
+        guest_id: match subject {
+            Some(NotificationSubject::Guest(id)) => Some(id),
+            _ => None,
+        },
+        reservation_id: match subject {
+            Some(NotificationSubject::Reservation(id)) => Some(id),
+            _ => None,
+        },
+        created_at: get_current_timestamp(),
+    };
+    entry.id = log_message(conn, &entry)?;
+    Ok(entry)
+}
+
+/// Send the `event_type` message about `subject` if that event is switched on
+/// and a provider is set up. None when nothing was sent or attempted.
+fn notify(event_type: &str, subject: NotificationSubject) -> Result<Option<NotificationLogEntry>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let event = load_event(&conn, event_type)?;
+    if !event.enabled || provider_name(&conn)? == "none" {
+        return Ok(None);
+    }
+    let (phone, values) = subject_details(&conn, subject)?;
+    let Some(phone) = phone.filter(|p| !p.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let message = fill_template(&event.template, &values);
+    deliver(&conn, event_type, &event.channel, &phone, message, Some(subject)).map(Some)
+}
+
+/// `notify` off the calling thread, so bookings and checkouts never wait on
+/// the network. Failures end up in the notification log.
+pub fn notify_in_background(event_type: &'static str, subject: NotificationSubject) {
+    // The worker uses the caller's database, which is not the live one during the self-test
+    let db_path = get_db_path();
+    std::thread::spawn(move || {
+        if let Err(e) = with_db_path_override(db_path, || notify(event_type, subject)) {
+            println!("⚠️ Could not send {} notification: {}", event_type, e);
+        }
+    });
+}
+
+/// Choose the SMS/WhatsApp provider and its credentials. Secrets left out
+/// (None) keep the saved ones, so the form can be resubmitted without them.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub fn set_notification_provider(
+    provider: String,
+    twilio_account_sid: Option<String>,
+    twilio_auth_token: Option<String>,
+    twilio_from: Option<String>,
+    twilio_whatsapp_from: Option<String>,
+    whatsapp_access_token: Option<String>,
+    whatsapp_phone_number_id: Option<String>,
+) -> Result<NotificationProviderSettings, AppError> {
+    let provider = provider.trim().to_lowercase();
+    if !PROVIDERS.contains(&provider.as_str()) {
+        return Err(format!("Provider must be one of: {}", PROVIDERS.join(", ")).into());
+    }
+    let trimmed = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let twilio_from = trimmed(twilio_from).map(|n| normalize_phone(&n)).transpose()?;
+    let twilio_whatsapp_from = trimmed(twilio_whatsapp_from).map(|n| normalize_phone(&n)).transpose()?;
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    set_or_clear_setting(&tx, "notification_provider", Some(&provider))?;
+    set_or_clear_setting(&tx, "twilio_account_sid", trimmed(twilio_account_sid).as_deref())?;
+    set_or_clear_setting(&tx, "twilio_from", twilio_from.as_deref())?;
+    set_or_clear_setting(&tx, "twilio_whatsapp_from", twilio_whatsapp_from.as_deref())?;
+    set_or_clear_setting(&tx, "whatsapp_phone_number_id", trimmed(whatsapp_phone_number_id).as_deref())?;
+    // Tokens are left out of LAN sync (see sync::SYNCABLE_SETTING_KEYS)
+    if let Some(token) = trimmed(twilio_auth_token) {
+        set_or_clear_setting(&tx, "twilio_auth_token", Some(&token))?;
+    }
+    if let Some(token) = trimmed(whatsapp_access_token) {
+        set_or_clear_setting(&tx, "whatsapp_access_token", Some(&token))?;
+    }
+    tx.commit()?;
+    get_notification_provider()
+}
+
+#[command]
+pub fn get_notification_provider() -> Result<NotificationProviderSettings, AppError> {
+    let conn = get_db_connection()?;
+    let provider = provider_name(&conn)?;
+    let secret_key = match provider.as_str() {
+        "twilio" => Some("twilio_auth_token"),
+        "whatsapp_business" => Some("whatsapp_access_token"),
+        _ => None,
+    };
+    let has_secret = match secret_key {
+        Some(key) => get_non_empty_setting(&conn, key)?.is_some(),
+        None => false,
+    };
+    Ok(NotificationProviderSettings {
+        provider,
+        twilio_account_sid: get_non_empty_setting(&conn, "twilio_account_sid")?,
+        twilio_from: get_non_empty_setting(&conn, "twilio_from")?,
+        twilio_whatsapp_from: get_non_empty_setting(&conn, "twilio_whatsapp_from")?,
+        whatsapp_phone_number_id: get_non_empty_setting(&conn, "whatsapp_phone_number_id")?,
+        has_secret,
+    })
+}
+
+/// Switch an event's message on or off, pick its channel and optionally
+/// reword it. A blank template goes back to the default wording.
+#[command]
+pub fn set_notification_event(
+    event_type: String,
+    enabled: bool,
+    channel: String,
+    template: Option<String>,
+) -> Result<NotificationEventSetting, AppError> {
+    default_template(&event_type)?;
+    let channel = channel.trim().to_lowercase();
+    if !CHANNELS.contains(&channel.as_str()) {
+        return Err(format!("Channel must be one of: {}", CHANNELS.join(", ")).into());
+    }
+    let template = template.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+
+    let conn = get_db_connection()?;
+    conn.execute(
+        "INSERT OR REPLACE INTO notification_events (event_type, enabled, channel, template, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![event_type, enabled, channel, template, get_current_timestamp()],
+    )?;
+    Ok(load_event(&conn, &event_type)?)
+}
+
+#[command]
+pub fn get_notification_events() -> Result<Vec<NotificationEventSetting>, AppError> {
+    let conn = get_db_connection()?;
+    Ok(EVENTS
+        .iter()
+        .map(|(event, _)| load_event(&conn, event))
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Text an in-house guest the balance on their stay, on the channel the
+/// payment_reminder event is set to. Sent even while the event is switched
+/// off, since staff asked for it.
+#[command]
+pub async fn send_payment_reminder(guest_id: i64) -> Result<NotificationLogEntry, AppError> {
+    run_blocking(move || -> Result<NotificationLogEntry, String> {
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let event = load_event(&conn, "payment_reminder")?;
+        let subject = NotificationSubject::Guest(guest_id);
+        let (phone, values) = subject_details(&conn, subject)?;
+        let phone = phone
+            .filter(|p| !p.trim().is_empty())
+            .ok_or("This guest has no phone number on file".to_string())?;
+        let message = fill_template(&event.template, &values);
+        deliver(&conn, "payment_reminder", &event.channel, &phone, message, Some(subject))
+    })
+    .await
+    .map_err(AppError::from)
+}
+
+/// Send a one-off message to check the provider settings.
+#[command]
+pub async fn send_test_notification(phone: String, channel: String) -> Result<NotificationLogEntry, AppError> {
+    if phone.trim().is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "phone", "Phone number cannot be empty"));
+    }
+    let channel = channel.trim().to_lowercase();
+    if !CHANNELS.contains(&channel.as_str()) {
+        return Err(format!("Channel must be one of: {}", CHANNELS.join(", ")).into());
+    }
+    run_blocking(move || -> Result<NotificationLogEntry, String> {
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let business_name = get_non_empty_setting(&conn, "business_name")?.unwrap_or_else(|| "Business Manager".to_string());
+        let message = format!("Test message from {}. Guest notifications are working.", business_name);
+        deliver(&conn, "test", &channel, &phone, message, None)
+    })
+    .await
+    .map_err(AppError::from)
+}
+
+/// Most recent messages first.
+#[command]
+pub fn get_notification_log(limit: Option<i64>) -> Result<Vec<NotificationLogEntry>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, event_type, channel, recipient, message, status, error, provider_message_id, guest_id, reservation_id, created_at
+         FROM notification_log ORDER BY id DESC LIMIT ?1",
+    )?;
+    let entries = stmt
+        .query_map(params![limit.unwrap_or(100).clamp(1, 1000)], |row| {
+            Ok(NotificationLogEntry {
+                id: row.get(0)?,
+                event_type: row.get(1)?,
+                channel: row.get(2)?,
+                recipient: row.get(3)?,
+                message: row.get(4)?,
+                status: row.get(5)?,
+                error: row.get(6)?,
+                provider_message_id: row.get(7)?,
+                guest_id: row.get(8)?,
+                reservation_id: row.get(9)?,
+                created_at: row.get(10)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+
+    #[test]
+    fn a_message_that_cannot_be_sent_is_logged_as_failed() {
+        let _db = TestDb::new();
+        // No provider is set up, so the message fails without touching the network
+        let entry = tauri::async_runtime::block_on(send_test_notification("+1 555 0100 123".to_string(), "sms".to_string())).unwrap();
+        assert!(entry.status == "failed" && entry.recipient == "+1 555 0100 123", "unexpected test message outcome: {} to {}", entry.status, entry.recipient);
+        let log = get_notification_log(Some(1)).unwrap();
+        assert_eq!(log.first().map(|e| e.id), Some(entry.id), "the test message is not in the notification log");
+    }
+}
@@ -1,10 +1,19 @@
-use rusqlite::{Connection, Result as SqliteResult};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
-use chrono::{Utc, Duration};
-use crate::db::get_db_path;
-use std::sync::OnceLock;
+use chrono::{DateTime, Utc, Duration};
+use crate::db::{get_db_path, get_pooled_connection, PooledConnection};
+use crate::validation::{SESSION_EXPIRED, SESSION_IDLE_TIMEOUT, UNAUTHORIZED};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// How long a cached session is trusted before it is re-checked against admin_sessions,
+/// so revocations done directly in the DB still take effect promptly.
+pub(crate) const SESSION_REVALIDATE_SECONDS: i64 = 60;
+
+const DEFAULT_IDLE_TIMEOUT_MINUTES: i64 = 30;
+const DEFAULT_ABSOLUTE_SESSION_HOURS: i64 = 8;
 
 fn auth_debug_enabled() -> bool {
     static ENABLED: OnceLock<bool> = OnceLock::new();
@@ -61,14 +70,145 @@ pub struct PasswordResetResponse {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub admin_id: i32,
+    pub username: String,
+    pub role: String,
+    pub expires_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeartbeatResponse {
+    pub expires_at: String,
+    pub idle_expires_at: Option<String>,
+    pub idle_timeout_minutes: i64,
+}
+
+/// Automatic logout policy. `idle_timeout_minutes = 0` disables the idle check;
+/// `absolute_session_hours` caps a session regardless of activity.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SessionPolicy {
+    pub idle_timeout_minutes: i64,
+    pub absolute_session_hours: i64,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self {
+            idle_timeout_minutes: DEFAULT_IDLE_TIMEOUT_MINUTES,
+            absolute_session_hours: DEFAULT_ABSOLUTE_SESSION_HOURS,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedSession {
+    info: SessionInfo,
+    expires_at: DateTime<Utc>,
+    last_activity_at: DateTime<Utc>,
+    validated_at: DateTime<Utc>,
+}
+
+enum SessionState {
+    Active(SessionInfo, CachedSession),
+    IdleExpired,
+    Expired,
+}
+
+/// In-memory token -> session map shared by the async auth commands and the
+/// sync business commands. Entries never outlive the expiry stored in the DB.
+#[derive(Clone, Default)]
+pub struct SessionCache {
+    inner: Arc<RwLock<HashMap<String, CachedSession>>>,
+}
+
+impl SessionCache {
+    fn get(&self, token: &str) -> Option<CachedSession> {
+        self.inner.read().ok().and_then(|map| map.get(token).cloned())
+    }
+
+    fn insert(&self, token: &str, session: CachedSession) {
+        if let Ok(mut map) = self.inner.write() {
+            map.insert(token.to_string(), session);
+        }
+    }
+
+    fn remove(&self, token: &str) {
+        if let Ok(mut map) = self.inner.write() {
+            map.remove(token);
+        }
+    }
+
+    fn remove_admin(&self, admin_id: i32) {
+        if let Ok(mut map) = self.inner.write() {
+            map.retain(|_, s| s.info.admin_id != admin_id);
+        }
+    }
+
+    fn remove_expired(&self, now: DateTime<Utc>) {
+        if let Ok(mut map) = self.inner.write() {
+            map.retain(|_, s| s.expires_at > now);
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        if let Ok(mut map) = self.inner.write() {
+            map.clear();
+        }
+    }
+}
+
+pub fn session_cache() -> &'static SessionCache {
+    static CACHE: OnceLock<SessionCache> = OnceLock::new();
+    CACHE.get_or_init(SessionCache::default)
+}
+
+/// Loaded from the settings table on first use and kept in sync by set_session_policy,
+/// so the per-command session check never has to read settings.
+fn session_policy_cell() -> &'static RwLock<Option<SessionPolicy>> {
+    static POLICY: OnceLock<RwLock<Option<SessionPolicy>>> = OnceLock::new();
+    POLICY.get_or_init(|| RwLock::new(None))
+}
+
+/// Drop the cached session policy so the next check reads it from settings
+/// again, for when the settings change behind set_session_policy's back.
+pub fn forget_session_policy() {
+    if let Ok(mut guard) = session_policy_cell().write() {
+        *guard = None;
+    }
+}
+
+/// Resolve a session token for a command and record it as user activity. Rejects with
+/// SESSION_IDLE_TIMEOUT when the idle window has lapsed and SESSION_EXPIRED when the
+/// token is unknown or past its absolute expiry.
+pub fn require_session(session_token: &str) -> Result<SessionInfo, String> {
+    resolve_session(session_token, true)
+}
+
+fn resolve_session(session_token: &str, touch: bool) -> Result<SessionInfo, String> {
+    match AuthManager::new()
+        .session_state(session_token, touch)
+        .map_err(|e| e.to_string())?
+    {
+        SessionState::Active(info, _) => Ok(info),
+        SessionState::IdleExpired => Err(SESSION_IDLE_TIMEOUT.to_string()),
+        SessionState::Expired => Err(SESSION_EXPIRED.to_string()),
+    }
+}
+
 pub struct AuthManager {
     db_path: String,
+    #[cfg(test)]
+    clock: fn() -> DateTime<Utc>,
 }
 
 impl AuthManager {
     pub fn new() -> Self {
         Self {
             db_path: get_db_path().to_string_lossy().to_string(),
+            #[cfg(test)]
+            clock: Utc::now,
         }
     }
 
@@ -76,11 +216,77 @@ impl AuthManager {
     pub fn new_with_path(db_path: &str) -> Self {
         Self {
             db_path: db_path.to_string(),
+            #[cfg(test)]
+            clock: Utc::now,
+        }
+    }
+
+    /// Replace the time source used for session expiry decisions.
+    #[cfg(test)]
+    fn with_clock(mut self, clock: fn() -> DateTime<Utc>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    #[cfg(not(test))]
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    #[cfg(test)]
+    fn now(&self) -> DateTime<Utc> {
+        (self.clock)()
+    }
+
+    pub fn session_policy(&self) -> SqliteResult<SessionPolicy> {
+        if let Ok(guard) = session_policy_cell().read() {
+            if let Some(policy) = *guard {
+                return Ok(policy);
+            }
+        }
+
+        let conn = self.get_connection()?;
+        let read = |key: &str| -> SqliteResult<Option<i64>> {
+            let value: Option<String> = conn
+                .query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| row.get(0))
+                .optional()?;
+            Ok(value.and_then(|v| v.trim().parse::<i64>().ok()))
+        };
+
+        let defaults = SessionPolicy::default();
+        let policy = SessionPolicy {
+            idle_timeout_minutes: read("idle_timeout_minutes")?.unwrap_or(defaults.idle_timeout_minutes),
+            absolute_session_hours: read("absolute_session_hours")?.unwrap_or(defaults.absolute_session_hours),
+        };
+
+        if let Ok(mut guard) = session_policy_cell().write() {
+            *guard = Some(policy);
+        }
+        Ok(policy)
+    }
+
+    pub fn set_session_policy(&self, policy: SessionPolicy) -> SqliteResult<()> {
+        let conn = self.get_connection()?;
+        let now = self.now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        for (key, value) in [
+            ("idle_timeout_minutes", policy.idle_timeout_minutes),
+            ("absolute_session_hours", policy.absolute_session_hours),
+        ] {
+            conn.execute(
+                "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)",
+                [key, &value.to_string(), &now],
+            )?;
+        }
+
+        if let Ok(mut guard) = session_policy_cell().write() {
+            *guard = Some(policy);
         }
+        Ok(())
     }
 
-    fn get_connection(&self) -> SqliteResult<Connection> {
-        Connection::open(&self.db_path)
+    fn get_connection(&self) -> SqliteResult<PooledConnection> {
+        get_pooled_connection(std::path::Path::new(&self.db_path))
     }
 
     fn hash_password_pbkdf2(&self, password: &str, salt: &str) -> String {
@@ -120,6 +326,20 @@ impl AuthManager {
         }
     }
 
+    /// Whether `password` is `username`'s password, without signing in or
+    /// counting a failed attempt.
+    pub fn check_password(&self, username: &str, password: &str) -> SqliteResult<bool> {
+        let conn = self.get_connection()?;
+        let stored: Option<(String, String)> = conn
+            .query_row(
+                "SELECT password_hash, salt FROM admin_auth WHERE LOWER(username) = LOWER(?1)",
+                [username.trim()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(stored.is_some_and(|(hash, salt)| self.verify_password(password, &hash, &salt)))
+    }
+
     pub fn login(&self, request: LoginRequest) -> SqliteResult<LoginResponse> {
         let conn = self.get_connection()?;
 
@@ -177,13 +397,26 @@ impl AuthManager {
 
                     // Create session
                     let session_token = Uuid::new_v4().to_string();
-                    let expires_at = Utc::now() + Duration::hours(8);
+                    let now = self.now();
+                    let expires_at = now + Duration::hours(self.session_policy()?.absolute_session_hours);
 
                     conn.execute(
-                        "INSERT INTO admin_sessions (session_token, admin_id, expires_at) VALUES (?1, ?2, ?3)",
-                        [&session_token, &admin_id.to_string(), &expires_at.to_rfc3339()],
+                        "INSERT INTO admin_sessions (session_token, admin_id, expires_at, last_activity_at) VALUES (?1, ?2, ?3, ?4)",
+                        [&session_token, &admin_id.to_string(), &expires_at.to_rfc3339(), &now.to_rfc3339()],
                     )?;
 
+                    session_cache().insert(&session_token, CachedSession {
+                        info: SessionInfo {
+                            admin_id,
+                            username: normalized_username.clone(),
+                            role: role.clone(),
+                            expires_at: expires_at.to_rfc3339(),
+                        },
+                        expires_at,
+                        last_activity_at: now,
+                        validated_at: now,
+                    });
+
                     auth_debug!("login success username='{}' admin_id={}", normalized_username, admin_id);
                     self.log_security_event(&conn, &normalized_username, "successful_login")?;
 
@@ -296,12 +529,37 @@ impl AuthManager {
                     let password_hash = self.hash_password_pbkdf2(&request.new_password, &new_salt);
 
                     // Update password and reset failed attempts
-                    conn.execute(
+                    let tx = conn.unchecked_transaction()?;
+                    tx.execute(
                         "UPDATE admin_auth SET password_hash = ?1, salt = ?2, failed_attempts = 0, locked_until = NULL WHERE LOWER(username) = LOWER(?3)",
                         [&password_hash, &new_salt, &request.username],
                     )?;
 
-                    self.log_security_event(&conn, &request.username, "password_reset_successful")?;
+                    // A database encrypted with this admin's password must unlock with the
+                    // new one; if its key can't be re-sealed the old password stays
+                    if let Err(e) = crate::db_encryption::admin_password_changed(&request.username, &request.new_password) {
+                        drop(tx);
+                        self.log_security_event(&conn, &request.username, "password_reset_failed_key_reseal")?;
+                        return Ok(PasswordResetResponse {
+                            success: false,
+                            message: format!("Password not changed: the database key could not be re-sealed ({})", e),
+                        });
+                    }
+
+                    // A password change revokes every session the user still holds
+                    let admin_id: Option<i32> = tx
+                        .query_row(
+                            "SELECT id FROM admin_auth WHERE LOWER(username) = LOWER(?1)",
+                            [&request.username],
+                            |row| row.get(0),
+                        )
+                        .optional()?;
+                    if let Some(admin_id) = admin_id {
+                        self.revoke_user_sessions(&tx, admin_id)?;
+                    }
+
+                    self.log_security_event(&tx, &request.username, "password_reset_successful")?;
+                    tx.commit()?;
 
                     Ok(PasswordResetResponse {
                         success: true,
@@ -324,36 +582,133 @@ impl AuthManager {
     }
 
     pub fn validate_session(&self, session_token: &str) -> SqliteResult<bool> {
-        let conn = self.get_connection()?;
+        Ok(matches!(self.session_state(session_token, false)?, SessionState::Active(..)))
+    }
 
-        let expires_at_result: Result<String, rusqlite::Error> = conn.query_row(
-            "SELECT expires_at FROM admin_sessions WHERE session_token = ?1",
-            [session_token],
-            |row| row.get(0),
-        );
+    /// Record user activity on a session, pushing back its idle deadline.
+    pub fn heartbeat(&self, session_token: &str) -> Result<HeartbeatResponse, String> {
+        let policy = self.session_policy().map_err(|e| e.to_string())?;
+        match self.session_state(session_token, true).map_err(|e| e.to_string())? {
+            SessionState::Active(info, session) => Ok(HeartbeatResponse {
+                expires_at: info.expires_at,
+                idle_expires_at: (policy.idle_timeout_minutes > 0).then(|| {
+                    let idle_deadline = session.last_activity_at + Duration::minutes(policy.idle_timeout_minutes);
+                    idle_deadline.min(session.expires_at).to_rfc3339()
+                }),
+                idle_timeout_minutes: policy.idle_timeout_minutes,
+            }),
+            SessionState::IdleExpired => Err(SESSION_IDLE_TIMEOUT.to_string()),
+            SessionState::Expired => Err(SESSION_EXPIRED.to_string()),
+        }
+    }
 
-        match expires_at_result {
-            Ok(expires_at_str) => {
-                if let Ok(expires_at) = chrono::DateTime::parse_from_rfc3339(&expires_at_str) {
-                    if expires_at > Utc::now() {
-                        Ok(true)
-                    } else {
-                        // Session expired, remove it
-                        conn.execute(
-                            "DELETE FROM admin_sessions WHERE session_token = ?1",
-                            [session_token],
-                        )?;
-                        Ok(false)
-                    }
-                } else {
-                    Ok(false)
+    /// Check a session against its absolute expiry and the idle timeout, serving from the
+    /// in-memory cache while the entry is fresh and falling back to admin_sessions every
+    /// SESSION_REVALIDATE_SECONDS. `touch` marks the call as user activity.
+    fn session_state(&self, session_token: &str, touch: bool) -> SqliteResult<SessionState> {
+        let now = self.now();
+        let policy = self.session_policy()?;
+        let cached = session_cache().get(session_token);
+
+        let mut session = match cached {
+            Some(cached) if now - cached.validated_at < Duration::seconds(SESSION_REVALIDATE_SECONDS) => cached,
+            cached => match self.load_session(session_token, cached.map(|c| c.last_activity_at), now)? {
+                Some(session) => session,
+                None => {
+                    session_cache().remove(session_token);
+                    return Ok(SessionState::Expired);
                 }
-            }
-            Err(_) => Ok(false),
+            },
+        };
+
+        if session.expires_at <= now {
+            // Absolute expiry wins over any amount of activity
+            self.end_session(session_token)?;
+            return Ok(SessionState::Expired);
         }
+
+        if policy.idle_timeout_minutes > 0
+            && now - session.last_activity_at > Duration::minutes(policy.idle_timeout_minutes)
+        {
+            self.end_session(session_token)?;
+            let conn = self.get_connection()?;
+            self.log_security_event(&conn, &session.info.username, "idle_timeout")?;
+            return Ok(SessionState::IdleExpired);
+        }
+
+        if touch {
+            session.last_activity_at = now;
+        }
+        session_cache().insert(session_token, session.clone());
+
+        Ok(SessionState::Active(session.info.clone(), session))
     }
 
-    pub fn logout(&self, session_token: &str) -> SqliteResult<()> {
+    /// Re-read a session from admin_sessions, flushing the cached activity timestamp so
+    /// the idle window survives an app restart.
+    fn load_session(
+        &self,
+        session_token: &str,
+        cached_activity: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> SqliteResult<Option<CachedSession>> {
+        let conn = self.get_connection()?;
+        let row: Option<(String, Option<String>, i32, String, String)> = conn
+            .query_row(
+                "SELECT s.expires_at, s.last_activity_at, s.admin_id, a.username, COALESCE(a.role, 'admin')
+                 FROM admin_sessions s
+                 JOIN admin_auth a ON a.id = s.admin_id
+                 WHERE s.session_token = ?1",
+                [session_token],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .optional()?;
+
+        let Some((expires_at_str, last_activity_str, admin_id, username, role)) = row else {
+            return Ok(None);
+        };
+
+        let parse = |value: &str| {
+            chrono::DateTime::parse_from_rfc3339(value)
+                .ok()
+                .map(|parsed| parsed.with_timezone(&Utc))
+        };
+
+        let Some(expires_at) = parse(&expires_at_str) else {
+            return Ok(None);
+        };
+
+        // Sessions created before idle tracking count as active from now on
+        let stored_activity = last_activity_str.as_deref().and_then(parse);
+        let last_activity_at = match (stored_activity, cached_activity) {
+            (Some(stored), Some(cached)) => stored.max(cached),
+            (Some(stored), None) => stored,
+            (None, Some(cached)) => cached,
+            (None, None) => now,
+        };
+
+        if stored_activity != Some(last_activity_at) {
+            conn.execute(
+                "UPDATE admin_sessions SET last_activity_at = ?1 WHERE session_token = ?2",
+                [&last_activity_at.to_rfc3339(), session_token],
+            )?;
+        }
+
+        Ok(Some(CachedSession {
+            info: SessionInfo {
+                admin_id,
+                username,
+                role,
+                expires_at: expires_at_str,
+            },
+            expires_at,
+            last_activity_at,
+            validated_at: now,
+        }))
+    }
+
+    fn end_session(&self, session_token: &str) -> SqliteResult<()> {
+        session_cache().remove(session_token);
         let conn = self.get_connection()?;
         conn.execute(
             "DELETE FROM admin_sessions WHERE session_token = ?1",
@@ -362,14 +717,25 @@ impl AuthManager {
         Ok(())
     }
 
+    fn revoke_user_sessions(&self, conn: &Connection, admin_id: i32) -> SqliteResult<()> {
+        conn.execute("DELETE FROM admin_sessions WHERE admin_id = ?1", [admin_id])?;
+        session_cache().remove_admin(admin_id);
+        Ok(())
+    }
+
+    pub fn logout(&self, session_token: &str) -> SqliteResult<()> {
+        self.end_session(session_token)
+    }
+
     pub fn cleanup_expired_sessions(&self) -> SqliteResult<()> {
         let conn = self.get_connection()?;
-        let now = Utc::now().to_rfc3339();
+        let now = self.now();
         
         conn.execute(
             "DELETE FROM admin_sessions WHERE expires_at < ?1",
-            [&now],
+            [&now.to_rfc3339()],
         )?;
+        session_cache().remove_expired(now);
         
         Ok(())
     }
@@ -443,7 +809,7 @@ impl AuthManager {
         let username = username.trim();
         
         // Validate role
-        if !["admin", "manager", "staff"].contains(&role) {
+        if !["admin", "manager", "staff", "housekeeping"].contains(&role) {
             return Err(rusqlite::Error::InvalidParameterName(
                 format!("Invalid role: {}", role)
             ));
@@ -513,6 +879,7 @@ impl AuthManager {
             ));
         }
         
+        self.revoke_user_sessions(&conn, user_id)?;
         conn.execute("DELETE FROM admin_auth WHERE id = ?1", [user_id])?;
         Ok(())
     }
@@ -574,6 +941,49 @@ pub async fn validate_admin_session(session_token: String) -> Result<bool, Strin
     }
 }
 
+#[tauri::command]
+pub async fn get_session_info(session_token: String) -> Result<SessionInfo, String> {
+    resolve_session(&session_token, false)
+}
+
+#[tauri::command]
+pub async fn heartbeat(session_token: String) -> Result<HeartbeatResponse, String> {
+    AuthManager::new().heartbeat(&session_token)
+}
+
+#[tauri::command]
+pub async fn get_session_policy() -> Result<SessionPolicy, String> {
+    AuthManager::new()
+        .session_policy()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+#[tauri::command]
+pub async fn set_session_policy(
+    session_token: String,
+    idle_timeout_minutes: i64,
+    absolute_session_hours: i64,
+) -> Result<(), String> {
+    let session = require_session(&session_token)?;
+    if session.role != "admin" {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    if !(0..=24 * 60).contains(&idle_timeout_minutes) {
+        return Err("Idle timeout must be between 0 and 1440 minutes".to_string());
+    }
+    if !(1..=72).contains(&absolute_session_hours) {
+        return Err("Session length must be between 1 and 72 hours".to_string());
+    }
+
+    AuthManager::new()
+        .set_session_policy(SessionPolicy {
+            idle_timeout_minutes,
+            absolute_session_hours,
+        })
+        .map_err(|e| format!("Database error: {}", e))
+}
+
 #[tauri::command]
 pub async fn logout_admin(session_token: String) -> Result<(), String> {
     let auth_manager = AuthManager::new();
@@ -603,6 +1013,7 @@ pub async fn logout_all_sessions() -> Result<(), String> {
         Ok(conn) => {
             match conn.execute("DELETE FROM admin_sessions", []) {
                 Ok(_) => {
+                    session_cache().clear();
                     // Log the security event
                     let timestamp = Utc::now().to_rfc3339();
                     let _ = conn.execute(
@@ -687,8 +1098,8 @@ pub async fn register_user(request: RegisterUserRequest) -> Result<(), String> {
     if request.password.len() < 6 {
         return Err("Password must be at least 6 characters".to_string());
     }
-    if !["admin", "manager", "staff"].contains(&request.role.as_str()) {
-        return Err("Invalid role. Must be admin, manager, or staff".to_string());
+    if !["admin", "manager", "staff", "housekeeping"].contains(&request.role.as_str()) {
+        return Err("Invalid role. Must be admin, manager, staff, or housekeeping".to_string());
     }
 
     auth_manager
@@ -717,3 +1128,110 @@ pub async fn delete_user(user_id: i32) -> Result<(), String> {
         .delete_user(user_id)
         .map_err(|e| format!("Failed to delete user: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use crate::db::get_db_connection;
+    use crate::test_support::{admin_session, TestDb};
+
+    /// How far the session clock runs ahead of the real one, in seconds.
+    static CLOCK_OFFSET_SECONDS: AtomicI64 = AtomicI64::new(0);
+
+    fn test_clock() -> DateTime<Utc> {
+        Utc::now() + Duration::seconds(CLOCK_OFFSET_SECONDS.load(Ordering::SeqCst))
+    }
+
+    fn advance_clock(seconds: i64) {
+        CLOCK_OFFSET_SECONDS.fetch_add(seconds, Ordering::SeqCst);
+    }
+
+    /// A manager on the test clock, which starts again at the real time.
+    fn auth() -> AuthManager {
+        CLOCK_OFFSET_SECONDS.store(0, Ordering::SeqCst);
+        AuthManager::new().with_clock(test_clock)
+    }
+
+    /// Heartbeats often enough to stay active until the clock is `before`
+    /// seconds short of `lifetime`, the last one late enough to revalidate.
+    fn keep_alive(auth: &AuthManager, token: &str, lifetime: i64, before: i64) {
+        let policy = auth.session_policy().unwrap();
+        let step = if policy.idle_timeout_minutes > 0 { policy.idle_timeout_minutes * 30 } else { 3600 };
+        let mut elapsed = 0;
+        while elapsed + step + SESSION_REVALIDATE_SECONDS < lifetime - before {
+            advance_clock(step);
+            elapsed += step;
+            auth.heartbeat(token).unwrap();
+        }
+        advance_clock(lifetime - before - elapsed);
+        auth.heartbeat(token).unwrap();
+    }
+
+    #[test]
+    fn activity_keeps_a_session_alive_until_it_goes_idle() {
+        let _db = TestDb::new();
+        let auth = auth();
+        let idle = auth.session_policy().unwrap().idle_timeout_minutes * 60;
+        assert!(idle > 0, "the default policy has no idle timeout");
+        let token = admin_session();
+        advance_clock(idle - 60);
+        auth.heartbeat(&token).unwrap();
+        advance_clock(idle - 60);
+        assert!(auth.validate_session(&token).unwrap(), "session expired despite activity");
+        advance_clock(120);
+        let idle_out = auth.heartbeat(&token);
+        assert!(idle_out.as_ref().is_err_and(|e| e == SESSION_IDLE_TIMEOUT), "idle session was not timed out: {:?}", idle_out.err());
+    }
+
+    #[test]
+    fn an_active_session_still_ends_at_its_absolute_expiry() {
+        let _db = TestDb::new();
+        let auth = auth();
+        let token = admin_session();
+        let lifetime = auth.session_policy().unwrap().absolute_session_hours * 3600;
+        keep_alive(&auth, &token, lifetime, 30);
+        advance_clock(40);
+        let capped = auth.heartbeat(&token);
+        assert!(capped.as_ref().is_err_and(|e| e == SESSION_EXPIRED), "session outlived its absolute expiry: {:?}", capped.err());
+    }
+
+    #[test]
+    fn a_fresh_session_is_served_from_the_cache() {
+        let db = TestDb::new();
+        let token = admin_session();
+        // Pointed at a database that can't be opened, so it only answers from the cache
+        let missing = db.dir().join("no-such-folder").join("none.db");
+        let cache_only = AuthManager::new_with_path(&missing.to_string_lossy());
+        for _ in 0..100 {
+            assert!(cache_only.validate_session(&token).unwrap(), "cached session was not served without the database");
+        }
+    }
+
+    #[test]
+    fn a_revoked_session_ends_once_the_cache_revalidates() {
+        let _db = TestDb::new();
+        let auth = auth();
+        let token = admin_session();
+        let conn = get_db_connection().unwrap();
+        conn.execute("DELETE FROM admin_sessions WHERE session_token = ?1", [&token]).unwrap();
+        assert!(auth.validate_session(&token).unwrap(), "session ended before the cache revalidated");
+        advance_clock(SESSION_REVALIDATE_SECONDS + 1);
+        assert!(!auth.validate_session(&token).unwrap(), "revoked session still valid after the revalidation window");
+    }
+
+    #[test]
+    fn a_cached_session_expires_on_time() {
+        let _db = TestDb::new();
+        let auth = auth();
+        let token = admin_session();
+        let lifetime = auth.session_policy().unwrap().absolute_session_hours * 3600;
+        keep_alive(&auth, &token, lifetime, 30);
+        // Expiry is taken from the cached entry, even if the database later says otherwise
+        let conn = get_db_connection().unwrap();
+        conn.execute("UPDATE admin_sessions SET expires_at = '2999-01-01T00:00:00+00:00' WHERE session_token = ?1", [&token])
+            .unwrap();
+        advance_clock(40);
+        assert!(!auth.validate_session(&token).unwrap(), "cached session outlived its expiry");
+    }
+}
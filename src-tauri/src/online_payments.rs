@@ -0,0 +1,363 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::time::Duration;
+use crate::db::{get_db_connection, get_current_timestamp, run_blocking};
+use crate::errors::AppError;
+use crate::models::{OnlinePayment, PaymentGatewaySettings, PaymentGatewaySettingsInput};
+use crate::repository::{get_non_empty_setting, set_or_clear_setting};
+use crate::simple_commands::to_cents;
+
+const PROVIDERS: &[&str] = &["none", "stripe"];
+const SOURCE_TYPES: &[&str] = &["order", "folio"];
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+const STRIPE_API: &str = "https://api.stripe.com/v1";
+// Currencies Stripe takes in whole units rather than cents
+const ZERO_DECIMAL_CURRENCIES: &[&str] = &[
+    "bif", "clp", "djf", "gnf", "jpy", "kmf", "krw", "mga", "pyg", "rwf", "ugx", "vnd", "vuv", "xaf", "xof", "xpf",
+];
+
+/// A payment as the gateway sees it.
+struct GatewayIntent {
+    id: String,
+    client_secret: Option<String>,
+    status: String,
+    amount_received: f64,
+}
+
+/// Somewhere card payments are taken online. Every call to the network for
+/// online payments goes through here.
+trait PaymentGateway {
+    fn create_intent(&self, amount: f64, currency: &str, description: &str, metadata: &[(&str, String)]) -> Result<GatewayIntent, String>;
+    fn retrieve_intent(&self, intent_id: &str) -> Result<GatewayIntent, String>;
+    fn cancel_intent(&self, intent_id: &str) -> Result<GatewayIntent, String>;
+}
+
+struct Stripe {
+    secret_key: String,
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS)).build()
+}
+
+/// Stripe's own message for a failed call when it sent one.
+fn stripe_error(e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, resp) => {
+            let body = resp.into_string().unwrap_or_default();
+            let message = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v["error"]["message"].as_str().map(|m| m.to_string()))
+                .unwrap_or(body);
+            format!("Stripe returned HTTP {}: {}", code, message)
+        }
+        ureq::Error::Transport(t) => format!("Could not reach Stripe: {}", t),
+    }
+}
+
+impl Stripe {
+    fn minor_units(currency: &str) -> f64 {
+        if ZERO_DECIMAL_CURRENCIES.contains(&currency) {
+            1.0
+        } else {
+            100.0
+        }
+    }
+
+    fn parse(response: ureq::Response) -> Result<GatewayIntent, String> {
+        let intent: serde_json::Value = response.into_json().map_err(|e| format!("Unexpected reply from Stripe: {}", e))?;
+        let currency = intent["currency"].as_str().unwrap_or_default();
+        Ok(GatewayIntent {
+            id: intent["id"].as_str().ok_or("Stripe did not return a payment intent ID")?.to_string(),
+            client_secret: intent["client_secret"].as_str().map(|s| s.to_string()),
+            status: intent["status"].as_str().unwrap_or("unknown").to_string(),
+            amount_received: intent["amount_received"].as_i64().unwrap_or(0) as f64 / Self::minor_units(currency),
+        })
+    }
+}
+
+impl PaymentGateway for Stripe {
+    fn create_intent(&self, amount: f64, currency: &str, description: &str, metadata: &[(&str, String)]) -> Result<GatewayIntent, String> {
+        let minor = (amount * Self::minor_units(currency)).round() as i64;
+        let mut form: Vec<(String, String)> = vec![
+            ("amount".to_string(), minor.to_string()),
+            ("currency".to_string(), currency.to_string()),
+            ("description".to_string(), description.to_string()),
+            ("automatic_payment_methods[enabled]".to_string(), "true".to_string()),
+        ];
+        form.extend(metadata.iter().map(|(key, value)| (format!("metadata[{}]", key), value.clone())));
+        let form: Vec<(&str, &str)> = form.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        let response = agent()
+            .post(&format!("{}/payment_intents", STRIPE_API))
+            .set("Authorization", &format!("Bearer {}", self.secret_key))
+            .send_form(&form)
+            .map_err(stripe_error)?;
+        Self::parse(response)
+    }
+
+    fn retrieve_intent(&self, intent_id: &str) -> Result<GatewayIntent, String> {
+        let response = agent()
+            .get(&format!("{}/payment_intents/{}", STRIPE_API, intent_id))
+            .set("Authorization", &format!("Bearer {}", self.secret_key))
+            .call()
+            .map_err(stripe_error)?;
+        Self::parse(response)
+    }
+
+    fn cancel_intent(&self, intent_id: &str) -> Result<GatewayIntent, String> {
+        let response = agent()
+            .post(&format!("{}/payment_intents/{}/cancel", STRIPE_API, intent_id))
+            .set("Authorization", &format!("Bearer {}", self.secret_key))
+            .send_form(&[])
+            .map_err(stripe_error)?;
+        Self::parse(response)
+    }
+}
+
+fn provider_name(conn: &Connection) -> Result<String, String> {
+    Ok(get_non_empty_setting(conn, "payment_gateway_provider")?.unwrap_or_else(|| "none".to_string()))
+}
+
+fn load_gateway(conn: &Connection) -> Result<Box<dyn PaymentGateway>, String> {
+    match provider_name(conn)?.as_str() {
+        "stripe" => Ok(Box::new(Stripe {
+            secret_key: get_non_empty_setting(conn, "payment_gateway_stripe_secret_key")?
+                .ok_or("Stripe secret key is not set")?,
+        })),
+        _ => Err("No online payment gateway is set up".to_string()),
+    }
+}
+
+const PAYMENT_COLUMNS: &str =
+    "id, source_type, source_id, provider, intent_id, client_secret, amount, currency, status, payment_id, note, paid_at, created_at";
+
+fn map_payment(row: &rusqlite::Row) -> rusqlite::Result<OnlinePayment> {
+    Ok(OnlinePayment {
+        id: row.get(0)?,
+        source_type: row.get(1)?,
+        source_id: row.get(2)?,
+        provider: row.get(3)?,
+        intent_id: row.get(4)?,
+        client_secret: row.get(5)?,
+        amount: row.get(6)?,
+        currency: row.get(7)?,
+        status: row.get(8)?,
+        payment_id: row.get(9)?,
+        note: row.get(10)?,
+        paid_at: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+fn load_payment(conn: &Connection, id: i64) -> Result<OnlinePayment, String> {
+    conn.query_row(&format!("SELECT {} FROM payment_intents WHERE id = ?1", PAYMENT_COLUMNS), params![id], map_payment)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or(format!("Online payment #{} not found", id))
+}
+
+/// What is left to pay on an order, or on an in-house guest's folio.
+fn amount_due(conn: &Connection, source_type: &str, source_id: i64) -> Result<f64, String> {
+    match source_type {
+        "order" => Ok(crate::simple_commands::order_payment_summary(conn, source_id)?.balance),
+        _ => Ok((to_cents(crate::folio::build_folio(conn, source_id)?.balance).max(0)) as f64 / 100.0),
+    }
+}
+
+/// Bring a payment up to date with the gateway's `status`. The first time it
+/// is seen as succeeded the money is recorded: as a card payment on the order,
+/// or a card deposit on the folio. An order paid some other way in the
+/// meantime, or a guest who has left, gets a note instead so the card payment
+/// can be refunded; so does whatever a partly paid order couldn't take.
+pub(crate) fn apply_intent_status(conn: &Connection, id: i64, status: &str, amount_received: f64) -> Result<OnlinePayment, String> {
+    let tx = rusqlite::Transaction::new_unchecked(conn, TransactionBehavior::Immediate).map_err(|e| e.to_string())?;
+    let payment = load_payment(&tx, id)?;
+    let now = get_current_timestamp();
+    if payment.status == "succeeded" || status != "succeeded" {
+        tx.execute(
+            "UPDATE payment_intents SET status = ?1, updated_at = ?2 WHERE id = ?3 AND status != 'succeeded'",
+            params![status, now, id],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        return load_payment(conn, id);
+    }
+
+    let received = if amount_received > 0.0 { amount_received } else { payment.amount };
+    let note = format!("Online card payment {}", payment.intent_id);
+    let (payment_id, unapplied) = match payment.source_type.as_str() {
+        "order" => {
+            let due = amount_due(&tx, "order", payment.source_id)?;
+            if to_cents(due) <= 0 {
+                (None, Some(format!("Order #{} was already paid; refund this payment in the gateway", payment.source_id)))
+            } else {
+                let amount = to_cents(received).min(to_cents(due)) as f64 / 100.0;
+                crate::simple_commands::insert_payment(&tx, payment.source_id, amount, "card", Some(note))?;
+                let payment_id = tx.last_insert_rowid();
+                crate::simple_commands::sync_order_payment_status(&tx, payment.source_id)?;
+                // Part of the order was paid some other way after the intent was created
+                let left_over = to_cents(received) - to_cents(amount);
+                let unapplied = if left_over > 0 {
+                    let money = crate::money::Money::load(&tx)?;
+                    Some(format!(
+                        "Order #{} only had {} left to pay; refund the other {} in the gateway",
+                        payment.source_id,
+                        money.format(amount, 2),
+                        money.format(left_over as f64 / 100.0, 2)
+                    ))
+                } else {
+                    None
+                };
+                (Some(payment_id), unapplied)
+            }
+        }
+        _ => match crate::folio::build_folio(&tx, payment.source_id) {
+            Ok(_) => (Some(crate::folio::insert_deposit(&tx, payment.source_id, received, "card", Some(note))?), None),
+            Err(_) => (None, Some(format!("Guest #{} had already checked out; refund this payment in the gateway", payment.source_id))),
+        },
+    };
+    tx.execute(
+        "UPDATE payment_intents SET status = 'succeeded', payment_id = ?1, note = ?2, paid_at = ?3, updated_at = ?3 WHERE id = ?4",
+        params![payment_id, unapplied, now, id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if payment.source_type == "order" && payment_id.is_some() && amount_due(conn, "order", payment.source_id)? <= 0.0 {
+        crate::fiscal::fiscalize_in_background("order", payment.source_id);
+    }
+    load_payment(conn, id)
+}
+
+/// Choose the online payment gateway and its keys. A secret key left out
+/// keeps the saved one, so the form can be resubmitted without it.
+#[command]
+pub fn set_payment_gateway_settings(settings: PaymentGatewaySettingsInput) -> Result<PaymentGatewaySettings, AppError> {
+    let provider = settings.provider.trim().to_lowercase();
+    if !PROVIDERS.contains(&provider.as_str()) {
+        return Err(format!("Provider must be one of: {}", PROVIDERS.join(", ")).into());
+    }
+    let trimmed = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+    let secret_key = trimmed(settings.stripe_secret_key);
+    if let Some(key) = &secret_key {
+        if !key.starts_with("sk_") && !key.starts_with("rk_") {
+            return Err("The Stripe secret key starts with sk_ (or rk_ for a restricted key)".into());
+        }
+    }
+    let publishable_key = trimmed(settings.stripe_publishable_key);
+    if publishable_key.as_deref().is_some_and(|key| !key.starts_with("pk_")) {
+        return Err("The Stripe publishable key starts with pk_".into());
+    }
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    set_or_clear_setting(&tx, "payment_gateway_provider", Some(&provider))?;
+    set_or_clear_setting(&tx, "payment_gateway_stripe_publishable_key", publishable_key.as_deref())?;
+    // The secret key is not in sync::SYNCABLE_SETTING_KEYS, so it never leaves this machine
+    if let Some(key) = secret_key {
+        set_or_clear_setting(&tx, "payment_gateway_stripe_secret_key", Some(&key))?;
+    }
+    tx.commit()?;
+    get_payment_gateway_settings()
+}
+
+#[command]
+pub fn get_payment_gateway_settings() -> Result<PaymentGatewaySettings, AppError> {
+    let conn = get_db_connection()?;
+    let secret_key = get_non_empty_setting(&conn, "payment_gateway_stripe_secret_key")?;
+    Ok(PaymentGatewaySettings {
+        provider: provider_name(&conn)?,
+        stripe_publishable_key: get_non_empty_setting(&conn, "payment_gateway_stripe_publishable_key")?,
+        test_mode: secret_key.as_deref().is_some_and(|key| key.contains("_test_")),
+        has_secret: secret_key.is_some(),
+    })
+}
+
+/// Start an online card payment for what is left to pay on an order ("order")
+/// or an in-house guest's folio ("folio", by guest ID). The returned client
+/// secret lets the card form complete it; `check_online_payment` then picks
+/// up the result.
+#[command]
+pub async fn create_online_payment(source_type: String, source_id: i64) -> Result<OnlinePayment, AppError> {
+    let source_type = source_type.trim().to_lowercase();
+    if !SOURCE_TYPES.contains(&source_type.as_str()) {
+        return Err(format!("Payment must be for one of: {}", SOURCE_TYPES.join(", ")).into());
+    }
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        let gateway = load_gateway(&conn)?;
+        let amount = amount_due(&conn, &source_type, source_id)?;
+        if to_cents(amount) <= 0 {
+            return Err(match source_type.as_str() {
+                "order" => AppError::from(crate::validation::ORDER_ALREADY_PAID),
+                _ => AppError::from("Nothing is due on the folio"),
+            });
+        }
+        let currency = crate::money::Money::load(&conn)?.code().to_lowercase();
+        let description = match source_type.as_str() {
+            "order" => format!("Order #{}", source_id),
+            _ => format!("Folio for guest #{}", source_id),
+        };
+        let intent = gateway.create_intent(
+            amount,
+            &currency,
+            &description,
+            &[("source_type", source_type.clone()), ("source_id", source_id.to_string())],
+        )?;
+
+        conn.execute(
+            "INSERT INTO payment_intents (source_type, source_id, provider, intent_id, client_secret, amount, currency, status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![source_type, source_id, provider_name(&conn)?, intent.id, intent.client_secret, amount, currency, intent.status],
+        )?;
+        Ok(load_payment(&conn, conn.last_insert_rowid())?)
+    })
+    .await
+}
+
+/// Ask the gateway how an online payment stands and, once it has gone
+/// through, record it against the order or folio. Safe to poll.
+#[command]
+pub async fn check_online_payment(id: i64) -> Result<OnlinePayment, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        let payment = load_payment(&conn, id)?;
+        if payment.status == "succeeded" || payment.status == "canceled" {
+            return Ok(payment);
+        }
+        let intent = load_gateway(&conn)?.retrieve_intent(&payment.intent_id)?;
+        Ok(apply_intent_status(&conn, id, &intent.status, intent.amount_received)?)
+    })
+    .await
+}
+
+/// Call off an online payment that hasn't gone through.
+#[command]
+pub async fn cancel_online_payment(id: i64) -> Result<OnlinePayment, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        let payment = load_payment(&conn, id)?;
+        if payment.status == "succeeded" {
+            return Err("This payment has gone through; refund it instead".into());
+        }
+        let intent = load_gateway(&conn)?.cancel_intent(&payment.intent_id)?;
+        Ok(apply_intent_status(&conn, id, &intent.status, intent.amount_received)?)
+    })
+    .await
+}
+
+/// Online payments, newest first, optionally only those for one order or folio.
+#[command]
+pub fn get_online_payments(source_type: Option<String>, source_id: Option<i64>) -> Result<Vec<OnlinePayment>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM payment_intents
+         WHERE (?1 IS NULL OR source_type = ?1) AND (?2 IS NULL OR source_id = ?2)
+         ORDER BY created_at DESC, id DESC",
+        PAYMENT_COLUMNS
+    ))?;
+    let payments = stmt
+        .query_map(params![source_type.map(|s| s.trim().to_lowercase()), source_id], map_payment)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(payments)
+}
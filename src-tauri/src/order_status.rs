@@ -0,0 +1,122 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::KitchenOrder;
+use crate::simple_commands::order_item_details;
+use crate::validation::{INVALID_ORDER_STATUS, ORDER_NOT_FOUND, ORDER_STATUS_BACKWARDS};
+
+// In workflow order; each status after the first has a `<status>_at` column
+const ORDER_STATUSES: &[&str] = &["pending", "preparing", "served", "closed"];
+
+const ORDER_COLUMNS: &str = "s.id, s.guest_id, COALESCE(c.name, s.customer_name), r.number, s.table_id, t.number,
+                             s.status, s.notes, s.created_at, s.preparing_at, s.served_at, s.closed_at,
+                             s.total_amount, s.paid";
+
+const ORDER_FROM: &str = "FROM sales s
+                          LEFT JOIN customers c ON s.guest_id = c.id
+                          LEFT JOIN resources r ON c.room_id = r.id
+                          LEFT JOIN dining_tables t ON s.table_id = t.id";
+
+fn map_order(row: &rusqlite::Row) -> rusqlite::Result<KitchenOrder> {
+    Ok(KitchenOrder {
+        id: row.get(0)?,
+        guest_id: row.get(1)?,
+        customer_name: row.get(2)?,
+        room_number: row.get(3)?,
+        table_id: row.get(4)?,
+        table_number: row.get(5)?,
+        status: row.get(6)?,
+        notes: row.get(7)?,
+        created_at: row.get(8)?,
+        preparing_at: row.get(9)?,
+        served_at: row.get(10)?,
+        closed_at: row.get(11)?,
+        total_amount: row.get(12)?,
+        paid: row.get::<_, i32>(13)? == 1,
+        items: Vec::new(),
+    })
+}
+
+fn status_rank(status: &str) -> Result<usize, AppError> {
+    ORDER_STATUSES.iter().position(|s| *s == status).ok_or_else(|| {
+        AppError::invalid(
+            INVALID_ORDER_STATUS,
+            "status",
+            format!("status must be one of: {}", ORDER_STATUSES.join(", ")),
+        )
+    })
+}
+
+pub fn load_order(conn: &Connection, order_id: i64) -> Result<KitchenOrder, AppError> {
+    let mut order = conn
+        .query_row(
+            &format!("SELECT {} {} WHERE s.id = ?1 AND s.deleted_at IS NULL", ORDER_COLUMNS, ORDER_FROM),
+            params![order_id],
+            map_order,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::coded(ORDER_NOT_FOUND, format!("Order #{} not found", order_id)))?;
+    order.items = order_item_details(conn, order_id)?;
+    Ok(order)
+}
+
+/// Move an order along pending → preparing → served → closed, stamping when
+/// it reached the new status. Stages may be skipped but never reversed;
+/// setting the current status again changes nothing.
+#[command]
+pub fn set_order_status(order_id: i64, status: String, session_token: Option<String>) -> Result<KitchenOrder, AppError> {
+    let conn = get_db_connection()?;
+    advance_order_status(&conn, order_id, &status.trim().to_lowercase(), &audit_actor(session_token.as_deref()))
+}
+
+/// What set_order_status does, on a connection or transaction the caller holds.
+pub fn advance_order_status(conn: &Connection, order_id: i64, status: &str, actor: &str) -> Result<KitchenOrder, AppError> {
+    let target = status_rank(status)?;
+    let order = load_order(conn, order_id)?;
+    let current = status_rank(&order.status)?;
+    if target == current {
+        return Ok(order);
+    }
+    if target < current {
+        return Err(AppError::coded(
+            ORDER_STATUS_BACKWARDS,
+            format!("Order #{} is already {} and can't go back to {}", order_id, order.status, status),
+        ));
+    }
+
+    let before = snapshot(conn, "order", order_id)?;
+    conn.execute(
+        &format!("UPDATE sales SET status = ?1, {}_at = ?2 WHERE id = ?3", status),
+        params![status, get_current_timestamp(), order_id],
+    )?;
+    record_change(conn, actor, "order", order_id, "update", before, snapshot(conn, "order", order_id)?)?;
+
+    load_order(conn, order_id)
+}
+
+/// Orders in one status, oldest first so the kitchen works through them in
+/// turn. Without a status, every order that isn't closed yet.
+#[command]
+pub fn get_orders_by_status(status: Option<String>) -> Result<Vec<KitchenOrder>, AppError> {
+    let status = status.map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty());
+    if let Some(status) = &status {
+        status_rank(status)?;
+    }
+
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} {}
+         WHERE s.deleted_at IS NULL AND ((?1 IS NULL AND s.status != 'closed') OR s.status = ?1)
+         ORDER BY s.created_at, s.id",
+        ORDER_COLUMNS, ORDER_FROM
+    ))?;
+    let mut orders = stmt
+        .query_map(params![status], map_order)?
+        .collect::<Result<Vec<_>, _>>()?;
+    for order in &mut orders {
+        order.items = order_item_details(&conn, order.id)?;
+    }
+    Ok(orders)
+}
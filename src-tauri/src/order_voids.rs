@@ -0,0 +1,147 @@
+use tauri::command;
+use rusqlite::{params, OptionalExtension};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::VoidedItem;
+use crate::simple_commands::{business_date_today, order_payment_summary, sync_order_payment_status};
+use crate::validation::{EMPTY_FIELD, ORDER_ALREADY_PAID, ORDER_ITEM_ALREADY_VOIDED, ORDER_ITEM_NOT_FOUND};
+
+// Why a line came off the bill; 'other' needs a note saying why
+const VOID_REASONS: &[&str] = &["comp", "wrong_item", "quality_issue", "kitchen_error", "customer_changed_mind", "other"];
+
+/// Take a line off an order: its total becomes zero, the order total is
+/// recalculated and the void is kept for management review. Lines already
+/// paid for can't be voided; refund the payment first.
+#[command]
+pub fn void_order_item(
+    order_item_id: i64,
+    reason: String,
+    note: Option<String>,
+    session_token: Option<String>,
+) -> Result<VoidedItem, AppError> {
+    let reason = reason.trim().to_lowercase();
+    if !VOID_REASONS.contains(&reason.as_str()) {
+        return Err(format!("reason must be one of: {}", VOID_REASONS.join(", ")).into());
+    }
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if reason == "other" && note.is_none() {
+        return Err(AppError::invalid(EMPTY_FIELD, "note", "Say why the item was voided"));
+    }
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+
+    let (order_id, item_name, quantity, line_total): (i64, String, i64, f64) = tx
+        .query_row(
+            "SELECT order_id, item_name, quantity, line_total FROM sale_items WHERE id = ?1",
+            params![order_item_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::coded(ORDER_ITEM_NOT_FOUND, format!("Order item #{} not found", order_item_id)))?;
+
+    let already_voided: bool = tx
+        .query_row("SELECT 1 FROM voided_items WHERE sale_item_id = ?1", params![order_item_id], |_| Ok(true))
+        .optional()?
+        .unwrap_or(false);
+    if already_voided {
+        return Err(AppError::coded(ORDER_ITEM_ALREADY_VOIDED, format!("'{}' was already voided", item_name)));
+    }
+
+    let summary = order_payment_summary(&tx, order_id)?;
+    if summary.amount_paid > summary.total_amount - line_total + 0.005 {
+        return Err(AppError::coded(
+            ORDER_ALREADY_PAID,
+            format!("Order #{} has payments covering '{}'; refund them before voiding it", order_id, item_name),
+        ));
+    }
+
+    let actor = audit_actor(session_token.as_deref());
+    let now = get_current_timestamp();
+    let before = snapshot(&tx, "order", order_id)?;
+
+    tx.execute("UPDATE sale_items SET line_total = 0 WHERE id = ?1", params![order_item_id])?;
+    tx.execute(
+        "UPDATE sales SET
+             service_charge = ROUND((SELECT COALESCE(SUM(line_total), 0) FROM sale_items WHERE order_id = ?1) * service_charge_percent / 100.0, 2),
+             total_amount = MAX(0, (SELECT COALESCE(SUM(line_total), 0) FROM sale_items WHERE order_id = ?1)
+                 + ROUND((SELECT COALESCE(SUM(line_total), 0) FROM sale_items WHERE order_id = ?1) * service_charge_percent / 100.0, 2)
+                 - (SELECT COALESCE(SUM(amount), 0) FROM voucher_redemptions WHERE order_id = ?1)),
+             rounding = 0
+         WHERE id = ?1",
+        params![order_id],
+    )?;
+    // A walk-in order was taxed when placed; the tax follows the lines that are left
+    let walk_in: Option<Option<String>> = tx
+        .query_row(
+            "SELECT (SELECT MIN(charged_on) FROM tax_charges WHERE order_id = ?1) FROM sales WHERE id = ?1 AND guest_id IS NULL",
+            params![order_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(charged_on) = walk_in {
+        let food: f64 = tx.query_row("SELECT COALESCE(SUM(line_total), 0) FROM sale_items WHERE order_id = ?1", params![order_id], |row| row.get(0))?;
+        let charged_on = charged_on.unwrap_or_else(business_date_today);
+        let tax = crate::taxes::record_order_taxes(&tx, order_id, food, &charged_on)?;
+        tx.execute("UPDATE sales SET total_amount = total_amount + ?1 WHERE id = ?2", params![tax, order_id])?;
+    }
+    crate::rounding::round_order_total(&tx, order_id)?;
+    sync_order_payment_status(&tx, order_id)?;
+    tx.execute(
+        "INSERT INTO voided_items (sale_item_id, order_id, item_name, quantity, amount, reason, note, voided_by, voided_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![order_item_id, order_id, item_name, quantity, line_total, reason, note, actor, now],
+    )?;
+    let void_id = tx.last_insert_rowid();
+    record_change(&tx, &actor, "order", order_id, "update", before, snapshot(&tx, "order", order_id)?)?;
+
+    tx.commit()?;
+
+    Ok(VoidedItem {
+        id: void_id,
+        sale_item_id: order_item_id,
+        order_id,
+        item_name,
+        quantity,
+        amount: line_total,
+        reason,
+        note,
+        voided_by: Some(actor),
+        voided_at: now,
+    })
+}
+
+/// Voided lines, newest first, for reviewing comped food. Dates are
+/// inclusive YYYY-MM-DD and both optional.
+#[command]
+pub fn get_voided_items(start_date: Option<String>, end_date: Option<String>) -> Result<Vec<VoidedItem>, AppError> {
+    for date in [&start_date, &end_date].into_iter().flatten() {
+        crate::validation::validate_date_format(date)?;
+    }
+
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, sale_item_id, order_id, item_name, quantity, amount, reason, note, voided_by, voided_at
+         FROM voided_items
+         WHERE (?1 IS NULL OR date(voided_at) >= ?1) AND (?2 IS NULL OR date(voided_at) <= ?2)
+         ORDER BY voided_at DESC, id DESC",
+    )?;
+    let voids = stmt
+        .query_map(params![start_date, end_date], |row| {
+            Ok(VoidedItem {
+                id: row.get(0)?,
+                sale_item_id: row.get(1)?,
+                order_id: row.get(2)?,
+                item_name: row.get(3)?,
+                quantity: row.get(4)?,
+                amount: row.get(5)?,
+                reason: row.get(6)?,
+                note: row.get(7)?,
+                voided_by: row.get(8)?,
+                voided_at: row.get(9)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(voids)
+}
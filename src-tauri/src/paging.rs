@@ -0,0 +1,95 @@
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use crate::errors::AppError;
+use crate::models::{PageRequest, PageResult};
+use crate::validation::{validate_date_format, INVALID_PAGE_REQUEST};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+
+/// What a list command lets the caller sort and filter by. Keys map to SQL
+/// written here, never to caller text, so only the values are bound.
+pub struct ListSpec {
+    pub sorts: &'static [(&'static str, &'static str)], // key, column
+    pub default_sort: &'static str,
+    pub tie_breaker: &'static str, // Appended so pages don't overlap on ties
+    pub filters: &'static [(&'static str, &'static str)], // key, condition; every ? gets the value
+}
+
+/// A resolved PageRequest, ready to splice into a query.
+pub struct PageQuery {
+    pub conditions: String, // " AND ..." for each filter, empty when there are none
+    pub values: Vec<Value>,
+    pub order_by: String,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+impl PageQuery {
+    pub fn limit_clause(&self) -> String {
+        format!(" LIMIT {} OFFSET {}", self.limit, self.offset)
+    }
+
+    /// Count the rows `from_where` matches; it must end with the conditions.
+    pub fn count(&self, conn: &Connection, from_where: &str) -> Result<i64, AppError> {
+        Ok(conn.query_row(&format!("SELECT COUNT(*) {}", from_where), params_from_iter(self.values.iter()), |row| row.get(0))?)
+    }
+
+    pub fn into_result<T>(self, items: Vec<T>, total: i64) -> PageResult<T> {
+        let end = self.offset + items.len() as i64;
+        PageResult {
+            items,
+            total,
+            limit: self.limit,
+            offset: self.offset,
+            next_offset: (end < total).then_some(end),
+        }
+    }
+}
+
+impl ListSpec {
+    pub fn resolve(&self, page: Option<PageRequest>) -> Result<PageQuery, AppError> {
+        let page = page.unwrap_or_default();
+        let limit = page.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let offset = page.offset.unwrap_or(0).max(0);
+
+        let sort = page.sort.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or(self.default_sort);
+        let (key, direction) = match sort.strip_prefix('-') {
+            Some(key) => (key, "DESC"),
+            None => (sort, "ASC"),
+        };
+        let column = self.sorts.iter().find(|(k, _)| *k == key).map(|(_, c)| *c).ok_or_else(|| {
+            AppError::invalid(INVALID_PAGE_REQUEST, "sort", format!("Can't sort by '{}'; use one of: {}", key, keys(self.sorts)))
+        })?;
+        let order_by = format!(" ORDER BY {} {}, {}", column, direction, self.tie_breaker);
+
+        // Sorted so the same filters always build the same SQL
+        let mut filters: Vec<(String, String)> = page.filters.into_iter().collect();
+        filters.sort();
+        let mut conditions = String::new();
+        let mut values = Vec::new();
+        for (key, value) in filters {
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            let condition = self.filters.iter().find(|(k, _)| *k == key).map(|(_, c)| *c).ok_or_else(|| {
+                AppError::invalid(INVALID_PAGE_REQUEST, "filters", format!("Unknown filter '{}'; use one of: {}", key, keys(self.filters)))
+            })?;
+            if key.ends_with("_date") {
+                validate_date_format(value)?;
+            }
+            conditions.push_str(" AND ");
+            conditions.push_str(condition);
+            for _ in condition.matches('?') {
+                values.push(Value::Text(value.to_string()));
+            }
+        }
+
+        Ok(PageQuery { conditions, values, order_by, limit, offset })
+    }
+}
+
+fn keys(pairs: &[(&str, &str)]) -> String {
+    pairs.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(", ")
+}
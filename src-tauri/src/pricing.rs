@@ -0,0 +1,748 @@
+use tauri::command;
+use chrono::{Duration, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::{get_db_connection, log_audit_event};
+use crate::models::*;
+use crate::offline_auth::require_session;
+use crate::validation::validate_date_format;
+use crate::repository::{get_parsed_setting, set_setting};
+
+const MAX_SUGGESTION_DAYS: u32 = 90;
+
+impl Default for PricingThresholds {
+    fn default() -> Self {
+        Self {
+            high_occupancy: 0.8,
+            high_adjustment_pct: 15.0,
+            low_occupancy: 0.3,
+            low_adjustment_pct: -10.0,
+        }
+    }
+}
+
+fn load_thresholds(conn: &Connection) -> Result<PricingThresholds, String> {
+    let defaults = PricingThresholds::default();
+    Ok(PricingThresholds {
+        high_occupancy: get_parsed_setting::<f64>(conn, "pricing_high_occupancy")?.unwrap_or(defaults.high_occupancy),
+        high_adjustment_pct: get_parsed_setting::<f64>(conn, "pricing_high_adjustment_pct")?.unwrap_or(defaults.high_adjustment_pct),
+        low_occupancy: get_parsed_setting::<f64>(conn, "pricing_low_occupancy")?.unwrap_or(defaults.low_occupancy),
+        low_adjustment_pct: get_parsed_setting::<f64>(conn, "pricing_low_adjustment_pct")?.unwrap_or(defaults.low_adjustment_pct),
+    })
+}
+
+/// Percentage change suggested for a given occupancy ratio, if any.
+fn adjustment_for_occupancy(thresholds: &PricingThresholds, occupancy: f64) -> Option<f64> {
+    if occupancy > thresholds.high_occupancy {
+        Some(thresholds.high_adjustment_pct)
+    } else if occupancy < thresholds.low_occupancy {
+        Some(thresholds.low_adjustment_pct)
+    } else {
+        None
+    }
+}
+
+/// Apply a percentage to the base rate and keep the result within the room type's bounds.
+fn suggested_rate(base_rate: f64, adjustment_pct: f64, bounds: Option<&RoomTypeRateBounds>) -> f64 {
+    let mut rate = base_rate * (100.0 + adjustment_pct) / 100.0;
+    if let Some(bounds) = bounds {
+        rate = rate.max(bounds.floor_rate).min(bounds.ceiling_rate);
+    }
+    (rate * 100.0).round() / 100.0
+}
+
+fn is_closed(conn: &Connection, date: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT 1 FROM closure_periods WHERE start_date <= ?1 AND end_date >= ?1 LIMIT 1",
+        params![date],
+        |_| Ok(true),
+    )
+    .optional()
+    .map(|found| found.unwrap_or(false))
+    .map_err(|e| e.to_string())
+}
+
+fn build_suggestions(conn: &Connection, days_ahead: u32) -> Result<Vec<PricingSuggestion>, String> {
+    let thresholds = load_thresholds(conn)?;
+    let today = chrono::Local::now().date_naive();
+
+    // Active inventory per room type with its base (average) rate
+    let mut stmt = conn
+        .prepare(
+            "SELECT room_type, COUNT(*), AVG(daily_rate) FROM resources
+             WHERE is_active = 1 GROUP BY room_type ORDER BY room_type",
+        )
+        .map_err(|e| e.to_string())?;
+    let room_types = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, f64>(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut suggestions = Vec::new();
+    for offset in 0..days_ahead.min(MAX_SUGGESTION_DAYS) as i64 {
+        let date = (today + Duration::days(offset)).format("%Y-%m-%d").to_string();
+        if is_closed(conn, &date)? {
+            continue;
+        }
+
+        for (room_type, total_rooms, base_rate) in &room_types {
+            // In-house stays occupy a night until their expected check-out (open-ended
+            // stays only tonight), and booked reservations the nights they cover.
+            let occupied_rooms: i64 = conn
+                .query_row(
+                    "SELECT COUNT(DISTINCT occupied.room_id) FROM (
+                         SELECT c.room_id FROM customers c
+                         WHERE c.status = 'active' AND c.check_in <= ?2
+                           AND ((c.check_out IS NOT NULL AND c.check_out > ?2) OR (c.check_out IS NULL AND ?2 = ?3))
+                         UNION
+                         SELECT res.room_id FROM reservations res
+                         WHERE res.status = 'booked' AND res.check_in <= ?2 AND res.check_out > ?2
+                     ) occupied
+                     JOIN resources r ON r.id = occupied.room_id
+                     WHERE r.is_active = 1 AND r.room_type = ?1",
+                    params![room_type, date, today.format("%Y-%m-%d").to_string()],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+
+            let occupancy = occupied_rooms as f64 / *total_rooms as f64;
+            let Some(adjustment_pct) = adjustment_for_occupancy(&thresholds, occupancy) else {
+                continue;
+            };
+
+            let bounds: Option<RoomTypeRateBounds> = conn
+                .query_row(
+                    "SELECT room_type, floor_rate, ceiling_rate FROM room_type_rate_bounds WHERE room_type = ?1",
+                    params![room_type],
+                    |row| Ok(RoomTypeRateBounds { room_type: row.get(0)?, floor_rate: row.get(1)?, ceiling_rate: row.get(2)? }),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+
+            let current_rate: f64 = conn
+                .query_row(
+                    "SELECT daily_rate FROM rate_overrides WHERE room_type = ?1 AND date = ?2",
+                    params![room_type, date],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .unwrap_or(*base_rate);
+
+            let suggested = suggested_rate(*base_rate, adjustment_pct, bounds.as_ref());
+            if (suggested - current_rate).abs() < 0.005 {
+                continue;
+            }
+
+            suggestions.push(PricingSuggestion {
+                id: format!("{}|{}", room_type, date),
+                room_type: room_type.clone(),
+                date: date.clone(),
+                occupied_rooms,
+                total_rooms: *total_rooms,
+                occupancy,
+                current_rate,
+                suggested_rate: suggested,
+                adjustment_pct,
+            });
+        }
+    }
+
+    Ok(suggestions)
+}
+
+fn upsert_rate_override(conn: &Connection, room_type: &str, date: &str, daily_rate: f64, source: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO rate_overrides (room_type, date, daily_rate, source) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(room_type, date) DO UPDATE SET daily_rate = excluded.daily_rate, source = excluded.source",
+        params![room_type, date, daily_rate, source],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// ===== RATE OVERRIDES =====
+
+#[command]
+pub fn set_rate_override(room_type: String, date: String, daily_rate: f64) -> Result<String, String> {
+    validate_date_format(&date)?;
+    if room_type.trim().is_empty() {
+        return Err("Room type cannot be empty".to_string());
+    }
+    if !daily_rate.is_finite() || daily_rate < 0.0 {
+        return Err("Daily rate must be >= 0".to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    upsert_rate_override(&conn, room_type.trim(), &date, daily_rate, "manual")?;
+    Ok("Rate override saved".to_string())
+}
+
+#[command]
+pub fn get_rate_overrides(start_date: String, end_date: String) -> Result<Vec<RateOverride>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, room_type, date, daily_rate, source FROM rate_overrides
+             WHERE date >= ?1 AND date <= ?2 ORDER BY date, room_type",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![start_date, end_date], |row| {
+            Ok(RateOverride {
+                id: row.get(0)?,
+                room_type: row.get(1)?,
+                date: row.get(2)?,
+                daily_rate: row.get(3)?,
+                source: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn delete_rate_override(override_id: i64) -> Result<String, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let affected = conn
+        .execute("DELETE FROM rate_overrides WHERE id = ?1", params![override_id])
+        .map_err(|e| e.to_string())?;
+    if affected == 0 {
+        return Err("Rate override not found".to_string());
+    }
+    Ok("Rate override deleted".to_string())
+}
+
+/// The nightly rate for a room on a date: the room type's override if one exists,
+/// then any rate plan covering the date, otherwise the room's own daily_rate.
+#[command]
+pub fn get_effective_room_rate(room_id: i64, date: String) -> Result<f64, String> {
+    validate_date_format(&date)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let (room_type, daily_rate): (String, f64) = conn
+        .query_row(
+            "SELECT room_type, daily_rate FROM resources WHERE id = ?1",
+            params![room_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Room not found".to_string())?;
+    Ok(nightly_rate(&conn, &room_type, &date, daily_rate)?.rate)
+}
+
+// ===== RATE PLANS =====
+
+const RATE_PLAN_COLUMNS: &str = "id, name, room_type, start_date, end_date, weekday_mask, daily_rate";
+
+fn map_rate_plan(row: &rusqlite::Row) -> rusqlite::Result<RatePlan> {
+    Ok(RatePlan {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        room_type: row.get(2)?,
+        start_date: row.get(3)?,
+        end_date: row.get(4)?,
+        weekday_mask: row.get(5)?,
+        daily_rate: row.get(6)?,
+    })
+}
+
+/// The rate for one night of a room type. A per-date override wins; otherwise the
+/// most specific plan covering the night (range and weekdays, then the narrowest
+/// range, then weekdays alone), otherwise `base_rate`.
+pub fn nightly_rate(conn: &Connection, room_type: &str, date: &str, base_rate: f64) -> Result<NightlyRate, String> {
+    let override_rate: Option<f64> = conn
+        .query_row(
+            "SELECT daily_rate FROM rate_overrides WHERE room_type = ?1 AND date = ?2",
+            params![room_type, date],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if let Some(rate) = override_rate {
+        return Ok(NightlyRate { date: date.to_string(), rate, source: "override".to_string(), rate_plan_id: None });
+    }
+
+    // strftime('%w') counts from Sunday = 0; the mask counts from Monday = bit 0
+    let plan: Option<(i64, f64)> = conn
+        .prepare_cached(
+            "SELECT id, daily_rate FROM rate_plans
+             WHERE room_type = ?1
+               AND (start_date IS NULL OR start_date <= ?2) AND (end_date IS NULL OR end_date >= ?2)
+               AND (weekday_mask IS NULL OR (weekday_mask >> ((CAST(strftime('%w', ?2) AS INTEGER) + 6) % 7)) & 1 = 1)
+             ORDER BY (start_date IS NOT NULL) + (weekday_mask IS NOT NULL) DESC,
+                      start_date IS NOT NULL DESC,
+                      julianday(end_date) - julianday(start_date),
+                      id DESC
+             LIMIT 1",
+        )
+        .map_err(|e| e.to_string())?
+        .query_row(params![room_type, date], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    Ok(match plan {
+        Some((id, rate)) => NightlyRate { date: date.to_string(), rate, source: "plan".to_string(), rate_plan_id: Some(id) },
+        None => NightlyRate { date: date.to_string(), rate: base_rate, source: "base".to_string(), rate_plan_id: None },
+    })
+}
+
+/// Rates for `nights` nights from `check_in`. Without a room there's nothing to
+/// price by, so every night is `base_rate`.
+pub fn stay_nightly_rates(
+    conn: &Connection,
+    room_id: Option<i64>,
+    base_rate: f64,
+    check_in: &str,
+    nights: i64,
+) -> Result<Vec<NightlyRate>, String> {
+    let start = NaiveDate::parse_from_str(check_in, "%Y-%m-%d").map_err(|_| "Invalid check-in date format".to_string())?;
+    let room_type: Option<String> = match room_id {
+        Some(room_id) => conn
+            .query_row("SELECT room_type FROM resources WHERE id = ?1", params![room_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?,
+        None => None,
+    };
+
+    (0..nights.max(1))
+        .map(|offset| {
+            let date = (start + Duration::days(offset)).format("%Y-%m-%d").to_string();
+            match &room_type {
+                Some(room_type) => nightly_rate(conn, room_type, &date, base_rate),
+                None => Ok(NightlyRate { date, rate: base_rate, source: "base".to_string(), rate_plan_id: None }),
+            }
+        })
+        .collect()
+}
+
+fn days_between(from: &str, to: &str) -> Result<i64, String> {
+    let from = NaiveDate::parse_from_str(from, "%Y-%m-%d").map_err(|_| format!("Invalid date format: {}", from))?;
+    let to = NaiveDate::parse_from_str(to, "%Y-%m-%d").map_err(|_| format!("Invalid date format: {}", to))?;
+    Ok((to - from).num_days())
+}
+
+fn price_segment(
+    conn: &Connection,
+    room_id: Option<i64>,
+    daily_rate: f64,
+    start_date: String,
+    end_date: Option<String>,
+    nights: i64,
+    nightly_pricing: bool,
+) -> Result<StaySegment, String> {
+    let total = if nights == 0 {
+        0.0
+    } else if nightly_pricing {
+        stay_nightly_rates(conn, room_id, daily_rate, &start_date, nights)?.iter().map(|n| n.rate).sum()
+    } else {
+        nights as f64 * daily_rate
+    };
+    let room_number: Option<String> = match room_id {
+        Some(room_id) => conn
+            .query_row("SELECT number FROM resources WHERE id = ?1", params![room_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?,
+        None => None,
+    };
+    Ok(StaySegment { room_id, room_number, start_date, end_date, nights, daily_rate, total })
+}
+
+/// A guest's `stay_days` nights split by room: one segment per room transfer,
+/// each billed at the rate of the room it left, then the current room from the
+/// last transfer on. Without transfers it's a single segment for the whole stay.
+pub fn guest_room_segments(conn: &Connection, guest_id: i64, stay_days: i64) -> Result<Vec<StaySegment>, String> {
+    let (room_id, check_in, daily_rate, nightly_pricing): (Option<i64>, String, f64, bool) = conn
+        .query_row(
+            "SELECT room_id, check_in, daily_rate, nightly_pricing FROM customers WHERE id = ?1",
+            params![guest_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare("SELECT from_room_id, from_rate, effective_date FROM room_transfers WHERE guest_id = ?1 ORDER BY effective_date, id")
+        .map_err(|e| e.to_string())?;
+    let transfers = stmt
+        .query_map(params![guest_id], |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, f64>(1)?, row.get::<_, String>(2)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut segments = Vec::new();
+    let mut start = check_in;
+    let mut nights_left = stay_days;
+    for (from_room_id, from_rate, effective_date) in transfers {
+        let nights = days_between(&start, &effective_date)?.clamp(0, nights_left);
+        segments.push(price_segment(conn, from_room_id, from_rate, start, Some(effective_date.clone()), nights, nightly_pricing)?);
+        nights_left -= nights;
+        start = effective_date;
+    }
+    segments.push(price_segment(conn, room_id, daily_rate, start, None, nights_left, nightly_pricing)?);
+    Ok(segments)
+}
+
+/// Room charge for a guest's stay of `stay_days` nights, across every room they
+/// were in. Stays checked in before nightly pricing keep their flat daily rate.
+pub fn guest_room_total(conn: &Connection, guest_id: i64, stay_days: i64) -> Result<f64, String> {
+    Ok(guest_room_segments(conn, guest_id, stay_days)?.iter().map(|s| s.total).sum())
+}
+
+/// Add a seasonal or weekday rate for a room type. Give a date range (inclusive),
+/// a weekday mask (bit 0 = Monday .. bit 6 = Sunday, e.g. 96 for Sat+Sun) or both.
+#[command]
+pub fn add_rate_plan(
+    name: String,
+    room_type: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    weekday_mask: Option<i64>,
+    daily_rate: f64,
+) -> Result<RatePlan, String> {
+    if name.trim().is_empty() {
+        return Err("Rate plan name cannot be empty".to_string());
+    }
+    if room_type.trim().is_empty() {
+        return Err("Room type cannot be empty".to_string());
+    }
+    if !daily_rate.is_finite() || daily_rate < 0.0 {
+        return Err("Daily rate must be >= 0".to_string());
+    }
+    match (&start_date, &end_date) {
+        (Some(start), Some(end)) => {
+            validate_date_format(start)?;
+            validate_date_format(end)?;
+            if end < start {
+                return Err("End date must not be before the start date".to_string());
+            }
+        }
+        (None, None) => {}
+        _ => return Err("Give both a start and an end date, or neither".to_string()),
+    }
+    if let Some(mask) = weekday_mask {
+        if !(1..=127).contains(&mask) {
+            return Err("Weekday mask must be between 1 and 127".to_string());
+        }
+    }
+    if start_date.is_none() && weekday_mask.is_none() {
+        return Err("A rate plan needs a date range, weekdays or both".to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO rate_plans (name, room_type, start_date, end_date, weekday_mask, daily_rate) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![name.trim(), room_type.trim(), start_date, end_date, weekday_mask, daily_rate],
+    )
+    .map_err(|e| e.to_string())?;
+
+    conn.query_row(
+        &format!("SELECT {} FROM rate_plans WHERE id = ?1", RATE_PLAN_COLUMNS),
+        params![conn.last_insert_rowid()],
+        map_rate_plan,
+    )
+    .map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_rate_plans(room_type: Option<String>) -> Result<Vec<RatePlan>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM rate_plans WHERE (?1 IS NULL OR room_type = ?1)
+             ORDER BY room_type, start_date IS NULL, start_date, name",
+            RATE_PLAN_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![room_type], map_rate_plan).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn delete_rate_plan(plan_id: i64) -> Result<String, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let affected = conn
+        .execute("DELETE FROM rate_plans WHERE id = ?1", params![plan_id])
+        .map_err(|e| e.to_string())?;
+    if affected == 0 {
+        return Err("Rate plan not found".to_string());
+    }
+    Ok("Rate plan deleted".to_string())
+}
+
+/// Night-by-night rates for a prospective stay, so the desk can quote it before
+/// checking the guest in. `daily_rate` defaults to the room's own rate.
+#[command]
+pub fn quote_room_stay(
+    room_id: i64,
+    check_in: String,
+    check_out: String,
+    daily_rate: Option<f64>,
+) -> Result<Vec<NightlyRate>, String> {
+    crate::validation::validate_date_range(&check_in, &check_out)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let room_rate: f64 = conn
+        .query_row("SELECT daily_rate FROM resources WHERE id = ?1", params![room_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or("Room not found".to_string())?;
+
+    let nights = (NaiveDate::parse_from_str(&check_out, "%Y-%m-%d").map_err(|e| e.to_string())?
+        - NaiveDate::parse_from_str(&check_in, "%Y-%m-%d").map_err(|e| e.to_string())?)
+    .num_days();
+    stay_nightly_rates(&conn, Some(room_id), daily_rate.unwrap_or(room_rate), &check_in, nights)
+}
+
+// ===== PRICING BOUNDS, THRESHOLDS AND CLOSURES =====
+
+#[command]
+pub fn set_room_type_rate_bounds(room_type: String, floor_rate: f64, ceiling_rate: f64) -> Result<String, String> {
+    if room_type.trim().is_empty() {
+        return Err("Room type cannot be empty".to_string());
+    }
+    if !floor_rate.is_finite() || !ceiling_rate.is_finite() || floor_rate < 0.0 || ceiling_rate < floor_rate {
+        return Err("Floor must be >= 0 and not above the ceiling".to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO room_type_rate_bounds (room_type, floor_rate, ceiling_rate) VALUES (?1, ?2, ?3)",
+        params![room_type.trim(), floor_rate, ceiling_rate],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok("Rate bounds saved".to_string())
+}
+
+#[command]
+pub fn get_room_type_rate_bounds() -> Result<Vec<RoomTypeRateBounds>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT room_type, floor_rate, ceiling_rate FROM room_type_rate_bounds ORDER BY room_type")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok(RoomTypeRateBounds { room_type: row.get(0)?, floor_rate: row.get(1)?, ceiling_rate: row.get(2)? }))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn get_pricing_thresholds() -> Result<PricingThresholds, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    load_thresholds(&conn)
+}
+
+#[command]
+pub fn set_pricing_thresholds(thresholds: PricingThresholds) -> Result<String, String> {
+    let in_range = |v: f64| (0.0..=1.0).contains(&v);
+    if !in_range(thresholds.high_occupancy) || !in_range(thresholds.low_occupancy) {
+        return Err("Occupancy thresholds must be between 0 and 1".to_string());
+    }
+    if thresholds.low_occupancy >= thresholds.high_occupancy {
+        return Err("Low occupancy threshold must be below the high threshold".to_string());
+    }
+    if thresholds.low_adjustment_pct <= -100.0 {
+        return Err("Low occupancy adjustment must be above -100%".to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    for (key, value) in [
+        ("pricing_high_occupancy", thresholds.high_occupancy),
+        ("pricing_high_adjustment_pct", thresholds.high_adjustment_pct),
+        ("pricing_low_occupancy", thresholds.low_occupancy),
+        ("pricing_low_adjustment_pct", thresholds.low_adjustment_pct),
+    ] {
+        set_setting(&conn, key, &value.to_string())?;
+    }
+    Ok("Pricing thresholds saved".to_string())
+}
+
+#[command]
+pub fn add_closure_period(start_date: String, end_date: String, reason: Option<String>) -> Result<i64, String> {
+    crate::validation::validate_date_range(&start_date, &end_date).or_else(|e| {
+        // A single-day closure has start == end
+        if start_date == end_date { validate_date_format(&start_date) } else { Err(e) }
+    })?;
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO closure_periods (start_date, end_date, reason) VALUES (?1, ?2, ?3)",
+        params![start_date, end_date, reason],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+#[command]
+pub fn get_closure_periods() -> Result<Vec<ClosurePeriod>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT id, start_date, end_date, reason FROM closure_periods ORDER BY start_date")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| Ok(ClosurePeriod { id: row.get(0)?, start_date: row.get(1)?, end_date: row.get(2)?, reason: row.get(3)? }))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn delete_closure_period(closure_id: i64) -> Result<String, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let affected = conn
+        .execute("DELETE FROM closure_periods WHERE id = ?1", params![closure_id])
+        .map_err(|e| e.to_string())?;
+    if affected == 0 {
+        return Err("Closure period not found".to_string());
+    }
+    Ok("Closure period deleted".to_string())
+}
+
+// ===== PRICING SUGGESTIONS =====
+
+/// Suggest per-room-type rate changes for the coming days based on projected occupancy.
+/// Nothing is written; see apply_pricing_suggestions.
+#[command]
+pub fn pricing_suggestions(days_ahead: u32) -> Result<Vec<PricingSuggestion>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    build_suggestions(&conn, days_ahead)
+}
+
+/// Write the selected suggestions as rate overrides. Suggestions are recomputed so a
+/// stale selection can't apply a rate the engine no longer proposes.
+#[command]
+pub fn apply_pricing_suggestions(session_token: String, selected: Vec<String>) -> Result<Vec<PricingSuggestion>, String> {
+    let session = require_session(&session_token)?;
+    if selected.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let furthest = selected
+        .iter()
+        .filter_map(|id| id.rsplit_once('|'))
+        .filter_map(|(_, date)| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .max()
+        .ok_or("No valid suggestion ids selected".to_string())?;
+    let days_ahead = ((furthest - today).num_days() + 1).max(0) as u32;
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let suggestions = build_suggestions(&conn, days_ahead)?;
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut applied = Vec::new();
+    for suggestion in suggestions.into_iter().filter(|s| selected.contains(&s.id)) {
+        upsert_rate_override(&tx, &suggestion.room_type, &suggestion.date, suggestion.suggested_rate, "suggestion")?;
+        log_audit_event(
+            &tx,
+            &session.username,
+            "pricing_suggestion_applied",
+            &format!(
+                "{} on {}: {:.2} -> {:.2} ({:+.1}% at {:.0}% occupancy)",
+                suggestion.room_type,
+                suggestion.date,
+                suggestion.current_rate,
+                suggestion.suggested_rate,
+                suggestion.adjustment_pct,
+                suggestion.occupancy * 100.0
+            ),
+        )
+        .map_err(|e| e.to_string())?;
+        applied.push(suggestion);
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{add_reservation, add_room, get_rooms};
+    use crate::test_support::{admin_session, room_type, TestDb, TEST_RATE};
+
+    const PEAK: &str = "Peak";
+
+    fn day(offset: i64) -> String {
+        (chrono::Local::now().date_naive() + Duration::days(offset)).format("%Y-%m-%d").to_string()
+    }
+
+    /// Five Peak rooms booked per night at 100%, exactly 80%, 20%, 40%, and
+    /// 100% while closed, two to six days out.
+    fn book_peak_week() {
+        crate::room_types::add_room_type(room_type(PEAK, None)).unwrap();
+        let mut rooms = Vec::new();
+        for n in 1..=5 {
+            let number = format!("P-{}", n);
+            add_room(number.clone(), PEAK.to_string(), TEST_RATE, None, None, None).unwrap();
+            rooms.push(get_rooms(None, None).unwrap().into_iter().find(|r| r.number == number).unwrap().id);
+        }
+        for (offset, booked) in [(2, 5), (3, 4), (4, 1), (5, 2), (6, 5)] {
+            for room_id in &rooms[..booked] {
+                add_reservation(
+                    *room_id, "Peak guest".to_string(), None, day(offset), day(offset + 1), None, Some(1), Some(0), None, None,
+                )
+                .unwrap();
+            }
+        }
+        add_closure_period(day(6), day(6), Some("Refurbishment".to_string())).unwrap();
+    }
+
+    /// Suggestions for the booked nights; the empty ones around them are
+    /// discounted too.
+    fn suggested() -> Vec<(String, f64, f64)> {
+        pricing_suggestions(7)
+            .unwrap()
+            .into_iter()
+            .filter(|s| s.room_type == PEAK && (2..=6).any(|offset| s.date == day(offset)))
+            .map(|s| (s.date, s.adjustment_pct, s.suggested_rate))
+            .collect()
+    }
+
+    #[test]
+    fn suggestions_follow_the_occupancy_thresholds() {
+        let _db = TestDb::new();
+        book_peak_week();
+        assert_eq!(
+            suggested(),
+            vec![(day(2), 15.0, 115.0), (day(4), -10.0, 90.0)],
+            "expected +15% on the full night and -10% on the 20% night only"
+        );
+    }
+
+    #[test]
+    fn suggestions_stay_within_the_rate_bounds() {
+        let _db = TestDb::new();
+        book_peak_week();
+        set_room_type_rate_bounds(PEAK.to_string(), 95.0, 112.0).unwrap();
+        assert_eq!(
+            suggested(),
+            vec![(day(2), 15.0, 112.0), (day(4), -10.0, 95.0)],
+            "suggestions not kept between the 95.00 floor and 112.00 ceiling"
+        );
+    }
+
+    #[test]
+    fn applied_suggestions_become_rate_overrides() {
+        let _db = TestDb::new();
+        book_peak_week();
+        set_room_type_rate_bounds(PEAK.to_string(), 95.0, 112.0).unwrap();
+        let selected = vec![format!("{}|{}", PEAK, day(2)), format!("{}|{}", PEAK, day(4))];
+        let applied = apply_pricing_suggestions(admin_session(), selected).unwrap();
+        assert_eq!(applied.len(), 2, "not every selected suggestion was applied");
+
+        let conn = get_db_connection().unwrap();
+        let mut stmt = conn.prepare("SELECT date, daily_rate, source FROM rate_overrides WHERE room_type = ?1 ORDER BY date").unwrap();
+        let overrides = stmt
+            .query_map(params![PEAK], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?, row.get::<_, String>(2)?)))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected = vec![(day(2), 112.0, "suggestion".to_string()), (day(4), 95.0, "suggestion".to_string())];
+        assert_eq!(overrides, expected, "applying wrote the wrong overrides");
+        let logged: i64 = conn
+            .query_row("SELECT COUNT(*) FROM audit_log WHERE event_type = 'pricing_suggestion_applied'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(logged, 2, "applied suggestions weren't audit-logged");
+        assert!(suggested().is_empty(), "applied suggestions are still suggested");
+    }
+}
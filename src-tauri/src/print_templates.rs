@@ -1,150 +1,133 @@
-use base64::{Engine, prelude::BASE64_STANDARD};
-use rusqlite::OptionalExtension;
-use std::path::PathBuf;
-
-// Include the JPG logo as a compile-time embedded resource for final invoices
-const LOGO_DATA: &[u8] = include_bytes!("../logoforcheckout.png");
-
-fn get_logo_base64() -> String {
-    // Use the embedded JPG logo for final invoices (ICO is too large at 410KB)
-    if !LOGO_DATA.is_empty() {
-        let embedded_logo = BASE64_STANDARD.encode(LOGO_DATA);
-        println!("✅ Using embedded JPG logo data, size: {} bytes, base64 length: {}", LOGO_DATA.len(), embedded_logo.len());
-        
-        // Check if the logo starts with JPEG header
-        if LOGO_DATA.len() >= 3 && &LOGO_DATA[0..3] == b"\xFF\xD8\xFF" {
-            println!("✅ Valid JPEG header detected");
-        } else {
-            println!("❌ WARNING: Invalid JPEG header!");
-            println!("First 16 bytes: {:?}", &LOGO_DATA[..16.min(LOGO_DATA.len())]);
-        }
-        
-        return embedded_logo;
-    }
-    
-    println!("⚠️  Warning: Embedded logo data is empty");
-    return "".to_string();
-}
-
-fn ensure_settings_table(conn: &rusqlite::Connection) -> Result<(), String> {
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS settings (\
-            key TEXT PRIMARY KEY,\
-            value TEXT NOT NULL,\
-            updated_at TEXT NOT NULL\
-        )",
-        [],
-    )
-    .map_err(|e| format!("Failed to ensure settings table: {}", e))?;
-
-    // If the table exists from an older version without `updated_at`, migrate it.
-    let has_updated_at: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('settings') WHERE name = 'updated_at'",
-            [],
-            |row| row.get(0),
-        )
-        .map_err(|e| format!("Failed to inspect settings table: {}", e))?;
+use serde_json::json;
+use crate::models::ReceiptQrSetting;
+use crate::repository::{get_setting_or, set_setting};
 
-    if has_updated_at == 0 {
-        conn.execute(
-            "ALTER TABLE settings ADD COLUMN updated_at TEXT NOT NULL DEFAULT ''",
-            [],
-        )
-        .map_err(|e| format!("Failed to migrate settings table: {}", e))?;
+/// The address, phone and email lines under the business name, whichever are set.
+pub(crate) fn business_contact(conn: &rusqlite::Connection) -> Result<String, String> {
+    let address = get_setting_or(conn, "business_address", "")?;
+    let phone = get_setting_or(conn, "business_phone", "")?;
+    let email = get_setting_or(conn, "business_email", "")?;
+    let mut parts = Vec::new();
+    if !address.trim().is_empty() {
+        parts.push(address.trim().to_string());
     }
-    Ok(())
+    if !phone.trim().is_empty() {
+        parts.push(format!("Tel: {}", phone.trim()));
+    }
+    if !email.trim().is_empty() {
+        parts.push(email.trim().to_string());
+    }
+    Ok(parts.join(" | "))
 }
 
-fn get_setting_or(conn: &rusqlite::Connection, key: &str, default_value: &str) -> Result<String, String> {
-    ensure_settings_table(conn)?;
-    let mut stmt = conn
-        .prepare("SELECT value FROM settings WHERE key = ?1")
-        .map_err(|e| format!("Failed to prepare setting query: {}", e))?;
+fn escape_multiline(text: &str) -> String {
+    html_escape(text)
+        .replace("\r\n", "\n")
+        .replace("\n", "<br>")
+}
 
-    let value: Option<String> = stmt
-        .query_row([key], |row| row.get(0))
-        .optional()
-        .map_err(|e| format!("Failed to read setting {}: {}", key, e))?;
+const QR_MODES: &[&str] = &["off", "details", "payment_link"];
 
-    Ok(value.unwrap_or_else(|| default_value.to_string()))
+/// What a printout's QR code is about: `reference` names the document
+/// ("ORDER-12", "INVOICE-7") and `amount_due` is what's left to pay on it.
+struct QrSubject<'a> {
+    reference: String,
+    summary: String,
+    amount_due: f64,
+    labels: &'a crate::receipt_i18n::ReceiptLabels,
 }
 
-fn get_setting_optional(conn: &rusqlite::Connection, key: &str) -> Result<Option<String>, String> {
-    ensure_settings_table(conn)?;
-    let mut stmt = conn
-        .prepare("SELECT value FROM settings WHERE key = ?1")
-        .map_err(|e| format!("Failed to prepare setting query: {}", e))?;
-
-    let value: Option<String> = stmt
-        .query_row([key], |row| row.get(0))
-        .optional()
-        .map_err(|e| format!("Failed to read setting {}: {}", key, e))?;
-
-    Ok(value.and_then(|v| {
-        let t = v.trim().to_string();
-        if t.is_empty() { None } else { Some(t) }
-    }))
-}
+/// The QR code block for a receipt or invoice, as inline SVG. With a payment
+/// link configured and money still due the code opens the link; otherwise it
+/// carries the document's details as a digital copy. Empty while QR codes
+/// are off.
+fn qr_code_html(conn: &rusqlite::Connection, money: &crate::money::Money, subject: QrSubject) -> Result<String, String> {
+    let mode = get_setting_or(conn, "receipt_qr_mode", "off")?;
+    let link = get_setting_or(conn, "receipt_qr_payment_link", "")?;
+    let (data, caption) = match mode.as_str() {
+        "payment_link" if !link.trim().is_empty() && subject.amount_due > 0.0 => (
+            link.trim()
+                .replace("{reference}", &subject.reference)
+                .replace("{amount}", &format!("{:.2}", subject.amount_due))
+                .replace("{currency}", money.code()),
+            subject.labels.t("Scan to pay"),
+        ),
+        "details" | "payment_link" => (subject.summary, subject.labels.t("Scan for a digital copy")),
+        _ => return Ok(String::new()),
+    };
 
-fn escape_multiline(text: &str) -> String {
-    html_escape(text)
-        .replace("\r\n", "\n")
-        .replace("\n", "<br>")
+    Ok(format!(
+        r#"<div class="qr-code" style="text-align: center; margin: 8px 0;">{}<div style="font-size: 10px;">{}</div></div>"#,
+        qr_svg(&data)?, caption
+    ))
 }
 
-fn guess_image_mime(path: &PathBuf) -> &'static str {
-    match path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase()
-        .as_str()
-    {
-        "png" => "image/png",
-        "jpg" | "jpeg" => "image/jpeg",
-        "svg" => "image/svg+xml",
-        "webp" => "image/webp",
-        _ => "application/octet-stream",
-    }
+/// `data` as a QR code in inline SVG.
+fn qr_svg(data: &str) -> Result<String, String> {
+    let code = qrcode::QrCode::new(data.as_bytes()).map_err(|e| format!("Could not build QR code: {}", e))?;
+    let svg = code
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(110, 110)
+        .build();
+    // Inline SVG can't carry the XML declaration the renderer starts with
+    Ok(svg.find("<svg").map_or(svg.as_str(), |start| &svg[start..]).to_string())
 }
 
-fn get_business_logo_data_url(conn: &rusqlite::Connection) -> Result<Option<String>, String> {
-    let path = match get_setting_optional(conn, "business_logo_path")? {
-        Some(p) => p,
-        None => return Ok(None),
+/// The fiscal invoice number, and its QR code when the provider gave one, once
+/// the bill has been reported to the tax authority. Empty unless fiscal
+/// numbers are set to print.
+fn fiscal_html(conn: &rusqlite::Connection, source_type: &str, source_id: i64, labels: &crate::receipt_i18n::ReceiptLabels) -> Result<String, String> {
+    let Some(record) = crate::fiscal::receipt_fiscal(conn, source_type, source_id)? else {
+        return Ok(String::new());
+    };
+    let qr = match &record.qr_payload {
+        Some(data) => qr_svg(data)?,
+        None => String::new(),
     };
+    Ok(format!(
+        r#"<div class="fiscal-invoice" style="text-align: center; margin: 8px 0; font-size: 11px;">{}<div>{}: <strong>{}</strong></div></div>"#,
+        qr,
+        labels.t("Fiscal invoice no."),
+        html_escape(record.fiscal_number.as_deref().unwrap_or_default())
+    ))
+}
 
-    let path_buf = PathBuf::from(path);
-    if !path_buf.exists() || !path_buf.is_file() {
-        return Ok(None);
+/// Choose what the QR code on receipts and invoices holds: "off", "details"
+/// (a digital copy of the totals) or "payment_link". The link may use
+/// {reference}, {amount} and {currency}, e.g. "https://pay.example.com/?ref={reference}&amount={amount}".
+#[tauri::command]
+pub fn set_receipt_qr(mode: String, payment_link: Option<String>) -> Result<ReceiptQrSetting, String> {
+    let mode = mode.trim().to_lowercase();
+    if !QR_MODES.contains(&mode.as_str()) {
+        return Err(format!("QR mode must be one of: {}", QR_MODES.join(", ")));
     }
-
-    let bytes = std::fs::read(&path_buf)
-        .map_err(|e| format!("Failed to read stored logo: {}", e))?;
-    if bytes.is_empty() {
-        return Ok(None);
+    let payment_link = payment_link.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+    if mode == "payment_link" && payment_link.is_none() {
+        return Err("A payment link is needed for payment QR codes".to_string());
     }
 
-    let mime = guess_image_mime(&path_buf);
-    let b64 = BASE64_STANDARD.encode(bytes);
-    Ok(Some(format!("data:{};base64,{}", mime, b64)))
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    for (key, value) in [("receipt_qr_mode", mode.as_str()), ("receipt_qr_payment_link", payment_link.as_deref().unwrap_or(""))] {
+        set_setting(&conn, key, value)?;
+    }
+    Ok(ReceiptQrSetting { mode, payment_link })
 }
 
-fn format_money(amount: f64, currency_code: &str, decimals: usize) -> String {
-    let safe_amount = if amount.is_finite() { amount } else { 0.0 };
-    match decimals {
-        0 => format!("{} {:.0}", currency_code, safe_amount),
-        2 => format!("{} {:.2}", currency_code, safe_amount),
-        _ => format!("{} {}", currency_code, safe_amount),
-    }
+#[tauri::command]
+pub fn get_receipt_qr() -> Result<ReceiptQrSetting, String> {
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    let link = get_setting_or(&conn, "receipt_qr_payment_link", "")?;
+    Ok(ReceiptQrSetting {
+        mode: get_setting_or(&conn, "receipt_qr_mode", "off")?,
+        payment_link: Some(link).filter(|l| !l.trim().is_empty()),
+    })
 }
 
 /// Print a food order receipt
 #[tauri::command]
-pub fn print_order_receipt(order_id: i64) -> Result<String, String> {
+pub fn print_order_receipt(order_id: i64, locale: Option<String>) -> Result<String, String> {
     // Generate the HTML receipt
-    let mut html = build_order_receipt_html(order_id)?;
+    let mut html = build_order_receipt_html(order_id, locale)?;
     
     // Add auto-print JavaScript before the closing </head> tag
     let auto_print_script = String::from(r#"
@@ -195,17 +178,250 @@ pub fn print_order_receipt(order_id: i64) -> Result<String, String> {
     Ok("Receipt opened in browser - print dialog will appear automatically".to_string())
 }
 
+/// Chromium-based browsers that can render HTML to PDF headlessly, most
+/// likely first. Edge ships with Windows, so that platform always has one.
+fn pdf_renderer_candidates() -> Vec<std::path::PathBuf> {
+    #[cfg(target_os = "windows")]
+    let candidates: Vec<std::path::PathBuf> = ["ProgramFiles(x86)", "ProgramFiles", "LocalAppData"]
+        .iter()
+        .filter_map(|var| std::env::var_os(var).map(std::path::PathBuf::from))
+        .flat_map(|root| {
+            [
+                root.join(r"Microsoft\Edge\Application\msedge.exe"),
+                root.join(r"Google\Chrome\Application\chrome.exe"),
+            ]
+        })
+        .collect();
+
+    #[cfg(target_os = "macos")]
+    let candidates: Vec<std::path::PathBuf> = [
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    ]
+    .iter()
+    .map(std::path::PathBuf::from)
+    .collect();
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let candidates: Vec<std::path::PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|dir| {
+            ["chromium", "chromium-browser", "google-chrome", "google-chrome-stable", "microsoft-edge"]
+                .map(|name| dir.join(name))
+        })
+        .collect();
+
+    candidates.into_iter().filter(|p| p.is_file()).collect()
+}
+
+pub(crate) fn render_html_to_pdf(html_path: &std::path::Path, pdf_path: &std::path::Path) -> Result<(), String> {
+    let renderer = pdf_renderer_candidates()
+        .into_iter()
+        .next()
+        .ok_or("No Chrome, Edge or Chromium installation found to render the document".to_string())?;
+
+    let output = std::process::Command::new(&renderer)
+        .args(["--headless", "--disable-gpu", "--no-pdf-header-footer", "--print-to-pdf-no-header"])
+        .arg(format!("--print-to-pdf={}", pdf_path.to_string_lossy()))
+        .arg(html_path)
+        .output()
+        .map_err(|e| format!("Failed to start {}: {}", renderer.display(), e))?;
+
+    if !pdf_path.is_file() {
+        return Err(format!(
+            "Rendering to PDF failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Hand a PDF to the OS print spooler; `printer` None means the default printer.
+fn submit_to_spooler(pdf_path: &std::path::Path, printer: Option<&str>) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let output = {
+        // Single-quoted PowerShell strings are literal; only quotes need doubling
+        let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+        let script = match printer {
+            Some(printer) => format!(
+                "Start-Process -FilePath {} -Verb PrintTo -ArgumentList ([char]34 + {} + [char]34) -WindowStyle Hidden",
+                quote(&pdf_path.to_string_lossy()),
+                quote(printer)
+            ),
+            None => format!(
+                "Start-Process -FilePath {} -Verb Print -WindowStyle Hidden",
+                quote(&pdf_path.to_string_lossy())
+            ),
+        };
+        std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .output()
+            .map_err(|e| format!("Failed to start PowerShell: {}", e))?
+    };
+
+    #[cfg(not(target_os = "windows"))]
+    let output = {
+        let mut lp = std::process::Command::new("lp");
+        if let Some(printer) = printer {
+            lp.args(["-d", printer]);
+        }
+        lp.arg(pdf_path)
+            .output()
+            .map_err(|e| format!("Failed to run lp: {}", e))?
+    };
+
+    if !output.status.success() {
+        return Err(format!(
+            "The print spooler rejected the receipt: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Render `html` and send it to the printer named in setting `printer_key`,
+/// or the system default when that is blank. Returns the printer used.
+fn print_html_silently(html: String, printer_key: &str, file_stem: &str) -> Result<String, String> {
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    let printer = get_setting_or(&conn, printer_key, "")?.trim().to_string();
+    let printer = Some(printer).filter(|p| !p.is_empty());
+
+    let stem = format!("{}_{}", file_stem, uuid::Uuid::new_v4());
+    let html_path = std::env::temp_dir().join(format!("{}.html", stem));
+    let pdf_path = std::env::temp_dir().join(format!("{}.pdf", stem));
+    std::fs::write(&html_path, html).map_err(|e| format!("Failed to write print file: {}", e))?;
+
+    let rendered = render_html_to_pdf(&html_path, &pdf_path);
+    let _ = std::fs::remove_file(&html_path);
+    rendered?;
+
+    let submitted = submit_to_spooler(&pdf_path, printer.as_deref());
+    // lp copies the file into the spool; on Windows the PDF handler may still be reading it
+    if cfg!(not(target_os = "windows")) {
+        let _ = std::fs::remove_file(&pdf_path);
+    }
+    submitted?;
+
+    Ok(printer.unwrap_or_else(|| "the default printer".to_string()))
+}
+
+/// Print a food order receipt straight to the configured receipt printer (or
+/// the system default) with no dialog. The receipt is rendered to PDF by a
+/// headless Chrome/Edge and submitted to the OS print spooler.
+#[tauri::command]
+pub async fn print_order_receipt_silent(order_id: i64, locale: Option<String>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let html = build_order_receipt_html(order_id, locale)?;
+        let printer = print_html_silently(html, "receipt_printer", &format!("receipt_{}", order_id))?;
+        Ok(format!("Receipt sent to {}", printer))
+    })
+    .await
+    .map_err(|e| format!("Printing did not finish: {}", e))?
+}
+
+/// Kitchen order ticket: what to cook and for whom, in print large enough to
+/// read across a kitchen. No prices or payment details.
+#[tauri::command]
+pub fn build_kitchen_ticket_html(order_id: i64) -> Result<String, String> {
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+
+    // Table orders go to the table, guest orders to the room; walk-ins have neither
+    let (created_at, serve_to, notes): (String, String, Option<String>) = conn
+        .query_row(
+            "SELECT fo.created_at,
+                    CASE WHEN t.number IS NOT NULL THEN 'Table ' || t.number
+                         WHEN r.number IS NOT NULL THEN 'Room ' || r.number
+                         ELSE COALESCE(g.name, fo.customer_name, 'Walk-in') END,
+                    fo.notes
+             FROM sales fo
+             LEFT JOIN customers g ON fo.guest_id = g.id
+             LEFT JOIN resources r ON g.room_id = r.id
+             LEFT JOIN dining_tables t ON fo.table_id = t.id
+             WHERE fo.id = ?1",
+            [order_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Order not found: {}", e))?;
+
+    let time = chrono::DateTime::parse_from_rfc3339(&created_at)
+        .map(|t| t.format("%H:%M").to_string())
+        .unwrap_or(created_at);
+
+    let items = crate::simple_commands::order_item_details(&conn, order_id)?;
+
+    let mut items_html = String::new();
+    for item in items.into_iter().filter(|item| !item.voided) {
+        items_html.push_str(&format!(
+            r#"<div class="item"><span class="qty">{}&times;</span> {}</div>"#,
+            item.quantity,
+            html_escape(&item.item_name)
+        ));
+        // A combo is cooked as its components
+        for component in item.components {
+            items_html.push_str(&format!(
+                r#"<div class="attribute">&rarr; {}&times; {}</div>"#,
+                component.quantity * item.quantity,
+                html_escape(&component.item_name)
+            ));
+        }
+        for (name, value) in item.selected_attributes {
+            items_html.push_str(&format!(
+                r#"<div class="attribute">&rarr; {}: {}</div>"#,
+                html_escape(&name),
+                html_escape(&value)
+            ));
+        }
+        for modifier in item.modifiers {
+            items_html.push_str(&format!(r#"<div class="attribute">&rarr; {}</div>"#, html_escape(&modifier.name)));
+        }
+        if let Some(notes) = item.notes {
+            items_html.push_str(&format!(r#"<div class="attribute">&rarr; NOTE: {}</div>"#, escape_multiline(&notes)));
+        }
+    }
+
+    let notes_html = match notes.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        Some(notes) => format!(r#"<div class="notes">NOTE: {}</div>"#, escape_multiline(notes)),
+        None => String::new(),
+    };
+
+    crate::templates::render(
+        "kitchen_ticket",
+        &json!({
+            "order_id": order_id,
+            "serve_to": serve_to,
+            "time": time,
+            "items_html": items_html,
+            "notes_html": notes_html,
+        }),
+    )
+}
+
+/// Print the kitchen ticket for an order to the kitchen printer (or the
+/// system default when none is set), with no dialog.
+#[tauri::command]
+pub async fn print_kitchen_ticket(order_id: i64) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let html = build_kitchen_ticket_html(order_id)?;
+        let printer = print_html_silently(html, "kitchen_printer", &format!("kitchen_{}", order_id))?;
+        Ok(format!("Kitchen ticket sent to {}", printer))
+    })
+    .await
+    .map_err(|e| format!("Printing did not finish: {}", e))?
+}
+
 /// Generate HTML receipt for a food order
 #[tauri::command]
-pub fn build_order_receipt_html(order_id: i64) -> Result<String, String> {
+pub fn build_order_receipt_html(order_id: i64, locale: Option<String>) -> Result<String, String> {
     let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    let labels = crate::receipt_i18n::receipt_labels(&conn, locale.as_deref())?;
 
-    let currency_code = get_setting_or(&conn, "currency_code", "USD")?
-        .trim()
-        .to_uppercase();
+    let money = crate::money::Money::load(&conn)?;
 
     let business_name = get_setting_or(&conn, "business_name", "Business Manager")?;
-    let business_address = get_setting_or(&conn, "business_address", "")?;
+    let business_address = business_contact(&conn)?;
 
     let receipt_header = get_setting_or(&conn, "receipt_header", "")?;
     let receipt_footer = get_setting_or(&conn, "receipt_footer", "")?;
@@ -213,7 +429,8 @@ pub fn build_order_receipt_html(order_id: i64) -> Result<String, String> {
     // Get order details with optional guest information
     let mut stmt = conn.prepare(
         "SELECT fo.id, fo.created_at, fo.total_amount, fo.paid, fo.customer_type, fo.customer_name,
-                g.name as guest_name, r.number as room_number
+                g.name as guest_name, r.number as room_number, fo.amount_paid,
+                fo.service_charge_percent, fo.service_charge, fo.rounding
             FROM sales fo
             LEFT JOIN customers g ON fo.guest_id = g.id
             LEFT JOIN resources r ON g.room_id = r.id
@@ -230,29 +447,24 @@ pub fn build_order_receipt_html(order_id: i64) -> Result<String, String> {
             row.get::<_, Option<String>>(5)?,               // customer_name
             row.get::<_, Option<String>>(6)?,               // customer_name (from customers table)
             row.get::<_, Option<String>>(7)?,               // room_number
+            row.get::<_, f64>(8)?,                          // amount_paid
+            row.get::<_, f64>(9)?,                          // service_charge_percent
+            row.get::<_, f64>(10)?,                         // service_charge
+            row.get::<_, f64>(11)?,                         // rounding
         ))
     }).map_err(|e| format!("Order not found: {}", e))?;
     
-    let (_id, created_at, total_amount, paid_status, customer_type, customer_name, guest_name, room_number) = order_row;
+    let (_id, created_at, total_amount, paid_status, customer_type, customer_name, guest_name, room_number, amount_paid,
+         service_charge_percent, service_charge, rounding) = order_row;
     let is_paid = paid_status != 0;
     
     // Logo: use saved business logo if available, otherwise fall back to embedded logo.
-    let logo_src = match get_business_logo_data_url(&conn)? {
-        Some(src) => src,
-        None => {
-            let embedded = get_logo_base64();
-            if embedded.is_empty() {
-                "".to_string()
-            } else {
-                format!("data:image/jpeg;base64,{}", embedded)
-            }
-        }
-    };
+    let logo_src = crate::media::document_logo_data_url(&conn).unwrap_or_default();
 
     let logo_html = if logo_src.is_empty() {
         "".to_string()
     } else {
-        format!(r#"<img src=\"{}\" alt=\"Logo\" class=\"logo\">"#, logo_src)
+        format!(r#"<img src="{}" alt="Logo" class="logo">"#, logo_src)
     };
 
     let receipt_header_html = if receipt_header.trim().is_empty() {
@@ -276,253 +488,186 @@ pub fn build_order_receipt_html(order_id: i64) -> Result<String, String> {
     };
     
     // Get order items
-    let mut stmt = conn.prepare(
-        "SELECT item_name, quantity, unit_price, line_total
-            FROM sale_items 
-         WHERE order_id = ?
-         ORDER BY item_name"
-    ).map_err(|e| format!("Failed to prepare items query: {}", e))?;
-    
-    let item_rows = stmt.query_map([order_id], |row| {
-        Ok((
-            row.get::<_, String>(0)?,    // item_name
-            row.get::<_, i32>(1)?,       // quantity
-            row.get::<_, f64>(2)?,       // unit_price
-            row.get::<_, f64>(3)?,       // line_total
-        ))
-    }).map_err(|e| format!("Failed to execute items query: {}", e))?;
+    let mut items = crate::simple_commands::order_item_details(&conn, order_id)?;
+    items.sort_by(|a, b| a.item_name.cmp(&b.item_name));
     
     let mut items_html = String::new();
-    for item in item_rows {
-        let (item_name, quantity, unit_price, line_total) = item.map_err(|e| format!("Failed to read item: {}", e))?;
-        let unit_price_fmt = format_money(unit_price, &currency_code, 2);
-        let line_total_fmt = format_money(line_total, &currency_code, 2);
+    for item in items {
+        let unit_price_fmt = money.format(item.unit_price, 2);
+        let line_total_fmt = money.format(item.line_total, 2);
+        let void_marker = if item.voided { format!(" ({})", labels.t("VOID")) } else { String::new() };
         items_html.push_str(&format!(
-            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
-            html_escape(&item_name), quantity, unit_price_fmt, line_total_fmt
+            "<tr><td>{}{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            html_escape(&item.item_name), void_marker, item.quantity, unit_price_fmt, line_total_fmt
         ));
+
+        // Kitchen preferences go directly under the item they apply to
+        for (name, value) in item.selected_attributes {
+            items_html.push_str(&format!(
+                r#"<tr class="item-attribute"><td colspan="4">&nbsp;&nbsp;↳ {}: {}</td></tr>"#,
+                html_escape(&name), html_escape(&value)
+            ));
+        }
+        // Modifiers are priced per unit and already included in the line total
+        for modifier in item.modifiers {
+            let delta = if modifier.price_delta == 0.0 {
+                String::new()
+            } else if modifier.price_delta > 0.0 {
+                format!(" (+{})", money.format(modifier.price_delta, 2))
+            } else {
+                format!(" (&minus;{})", money.format(-modifier.price_delta, 2))
+            };
+            items_html.push_str(&format!(
+                r#"<tr class="item-attribute"><td colspan="4">&nbsp;&nbsp;↳ {}{}</td></tr>"#,
+                html_escape(&modifier.name), delta
+            ));
+        }
+        if let Some(notes) = item.notes {
+            items_html.push_str(&format!(
+                r#"<tr class="item-attribute"><td colspan="4">&nbsp;&nbsp;↳ {}: {}</td></tr>"#,
+                labels.t("Note"), html_escape(&notes)
+            ));
+        }
     }
     
-    let payment_status = if is_paid { "✓ PAID" } else { "⚠ UNPAID" };
-    let payment_color = if is_paid { "#28a745" } else { "#dc3545" };
+    let (payment_status, payment_color) = if is_paid {
+        (format!("✓ {}", labels.t("PAID")), "#28a745")
+    } else if amount_paid > 0.0 {
+        (format!("◐ {} &ndash; {} {}", labels.t("PART PAID"), money.format(total_amount - amount_paid, 2), labels.t("due")), "#fd7e14")
+    } else {
+        (format!("⚠ {}", labels.t("UNPAID")), "#dc3545")
+    };
     
     // Determine customer display information
     let customer_display = match customer_type.as_str() {
         "walk_in" => {
-            customer_name.unwrap_or_else(|| "Walk-in Customer".to_string())
+            customer_name.unwrap_or_else(|| labels.t("Walk-in Customer").to_string())
         },
         _ => {
-            guest_name.unwrap_or_else(|| "Guest".to_string())
+            guest_name.unwrap_or_else(|| labels.t("Guest").to_string())
         }
     };
     
     let room_display = if customer_type == "walk_in" {
-        "Walk-in".to_string()
+        labels.t("Walk-in").to_string()
     } else {
-        room_number.unwrap_or_else(|| "N/A".to_string())
+        room_number.unwrap_or_else(|| labels.t("N/A").to_string())
     };
 
-    let total_amount_fmt = format_money(total_amount, &currency_code, 2);
-
-    let html = format!(r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Food Order Receipt #{}</title>
-    <style>
-        body {{
-            font-family: 'Segoe UI', Tahoma, Geneva, Verdana, sans-serif;
-            max-width: 600px;
-            margin: 0 auto;
-            padding: 20px;
-            line-height: 1.6;
-            color: #333;
-        }}
-        .header {{
-            text-align: center;
-            border-bottom: 2px solid #333;
-            padding-bottom: 20px;
-            margin-bottom: 30px;
-        }}
-        .logo {{
-            max-width: 120px;
-            height: auto;
-            margin-bottom: 15px;
-            display: block;
-            border: 2px solid #333;
-            background: #fff;
-            padding: 8px;
-        }}
-        .hotel-name {{
-            font-size: 28px;
-            font-weight: bold;
-            color: #2c3e50;
-            margin: 0;
-        }}
-        .hotel-subtitle {{
-            font-size: 14px;
-            color: #7f8c8d;
-            margin: 5px 0 0 0;
-            line-height: 1.4;
-        }}
-        .receipt-title {{
-            font-size: 24px;
-            margin: 20px 0 10px 0;
-            color: #34495e;
-        }}
-        .order-info {{
-            background-color: #f8f9fa;
-            padding: 20px;
-            border-radius: 8px;
-            margin-bottom: 30px;
-        }}
-        .info-row {{
-            display: flex;
-            justify-content: space-between;
-            margin-bottom: 10px;
-            padding: 5px 0;
-        }}
-        .info-label {{
-            font-weight: bold;
-            color: #495057;
-        }}
-        .payment-status {{
-            font-weight: bold;
-            color: {};
-            font-size: 18px;
-        }}
-        table {{
-            width: 100%;
-            border-collapse: collapse;
-            margin-bottom: 30px;
-            background-color: white;
-            box-shadow: 0 2px 4px rgba(0,0,0,0.1);
-        }}
-        th, td {{
-            padding: 12px;
-            text-align: left;
-            border-bottom: 1px solid #dee2e6;
-        }}
-        th {{
-            background-color: #495057;
-            color: white;
-            font-weight: bold;
-        }}
-        .text-right {{
-            text-align: right;
-        }}
-        .total-row {{
-            background-color: #f8f9fa;
-            font-weight: bold;
-            font-size: 18px;
-        }}
-        .footer {{
-            text-align: center;
-            margin-top: 40px;
-            padding-top: 20px;
-            border-top: 1px solid #dee2e6;
-            color: #6c757d;
-            font-size: 14px;
-        }}
-        .brand-message {{
-            margin-top: 10px;
-            font-size: 13px;
-            color: #444;
-            line-height: 1.4;
-        }}
-        @media print {{
-            body {{
-                margin: 0;
-                padding: 15px;
-            }}
-            .no-print {{
-                display: none;
-            }}
-        }}
-    </style>
-</head>
-<body>
-    <div class="header">
-        {}
-        <h1 class="hotel-name">{}</h1>
-        <p class="hotel-subtitle">{}</p>
-        {}
-        <h2 class="receipt-title">Food Order Receipt</h2>
-    </div>
-
-    <div class="order-info">
-        <div class="info-row">
-            <span class="info-label">Order #:</span>
-            <span>{}</span>
-        </div>
-        <div class="info-row">
-            <span class="info-label">Date:</span>
-            <span>{}</span>
-        </div>
-        <div class="info-row">
-            <span class="info-label">Customer:</span>
-            <span>{}</span>
-        </div>
-        <div class="info-row">
-            <span class="info-label">Room:</span>
-            <span>{}</span>
-        </div>
-        <div class="info-row">
-            <span class="info-label">Payment Status:</span>
-            <span class="payment-status">{}</span>
-        </div>
-    </div>
-
-    <table>
-        <thead>
-            <tr>
-                <th>Item</th>
-                <th class="text-right">Qty</th>
-                <th class="text-right">Unit Price</th>
-                <th class="text-right">Total</th>
-            </tr>
-        </thead>
-        <tbody>
-            {}
-        </tbody>
-        <tfoot>
-            <tr class="total-row">
-                <td colspan="3"><strong>Grand Total</strong></td>
-                <td class="text-right"><strong>{}</strong></td>
-            </tr>
-        </tfoot>
-    </table>
-
-    <div class="footer">
-        <p>Thank you for dining with us!</p>
-        {}
-        <p>Receipt generated on {}</p>
-    </div>
-</body>
-</html>"#, 
-        order_id,
-        payment_color,
-        logo_html,
-    html_escape(&business_name),
-    html_escape(&business_address),
-        receipt_header_html,
-        order_id,
-        formatted_date,
-        html_escape(&customer_display),
-        html_escape(&room_display),
-        payment_status,
-        items_html,
-        total_amount_fmt,
-        receipt_footer_html,
-        chrono::Local::now().format("%B %d, %Y at %I:%M %p")
-    );
+    let total_amount_fmt = money.format(total_amount, 2);
+
+    // The service charge is part of the total; show it on its own line above it
+    let service_charge_row = if service_charge > 0.0 {
+        format!(
+            r#"<tr><td colspan="3">{} ({:.1}%)</td><td class="text-right">{}</td></tr>"#,
+            labels.t("Service charge"),
+            service_charge_percent,
+            money.format(service_charge, 2)
+        )
+    } else {
+        "".to_string()
+    };
+
+    // Vouchers already came off the total; list them under the service charge
+    let voucher_rows: String = crate::vouchers::redemptions_for(&conn, Some(order_id), None)?
+        .iter()
+        .map(|(code, amount)| {
+            format!(
+                r#"<tr><td colspan="3">{} {}</td><td class="text-right">-{}</td></tr>"#,
+                labels.t("Voucher"),
+                html_escape(code),
+                money.format(*amount, 2)
+            )
+        })
+        .collect();
+
+    let rounding_row = if rounding != 0.0 {
+        format!(
+            r#"<tr><td colspan="3">{}</td><td class="text-right">{}</td></tr>"#,
+            labels.t("Rounding"),
+            money.format(rounding, 2)
+        )
+    } else {
+        "".to_string()
+    };
+
+    // A walk-in order was taxed when placed: taxes added on top are listed
+    // above the total. A guest's order is taxed on their checkout bill, so only
+    // taxes already in its prices show, worked out from the total less the
+    // untaxed rounding adjustment. Included taxes are itemized under the total.
+    let recorded = crate::taxes::order_tax_lines(&conn, order_id)?;
+    let added_tax_rows: String = recorded
+        .iter()
+        .filter(|t| !t.included && t.amount > 0.0)
+        .map(|t| {
+            format!(
+                r#"<tr><td colspan="3">{} ({:.1}%)</td><td class="text-right">{}</td></tr>"#,
+                html_escape(&t.name),
+                t.percent,
+                money.format(t.amount, 2)
+            )
+        })
+        .collect();
+    let taxes = if recorded.is_empty() {
+        let unrounded = total_amount - rounding;
+        crate::taxes::bill_taxes(&conn, 0.0, unrounded, unrounded)?
+    } else {
+        recorded
+    };
+    let tax_rows: String = taxes
+        .iter()
+        .filter(|t| t.included && t.amount > 0.0)
+        .map(|t| {
+            format!(
+                r#"<tr><td colspan="3">{} {} ({:.1}%)</td><td class="text-right">{}</td></tr>"#,
+                labels.t("Includes"),
+                html_escape(&t.name),
+                t.percent,
+                money.format(t.amount, 2)
+            )
+        })
+        .collect();
+
+    let qr_code_html = qr_code_html(&conn, &money, QrSubject {
+        reference: format!("ORDER-{}", order_id),
+        summary: format!("{} | Order #{} | {} | Total {}", business_name, order_id, formatted_date, total_amount_fmt),
+        amount_due: if is_paid { 0.0 } else { total_amount - amount_paid },
+        labels: &labels,
+    })?;
+
+    let fiscal_html = fiscal_html(&conn, "order", order_id, &labels)?;
+
+    let mut context = json!({
+        "order_id": order_id,
+        "payment_color": payment_color,
+        "logo_html": logo_html,
+        "business_name": business_name,
+        "business_contact": business_address,
+        "receipt_header_html": receipt_header_html,
+        "order_date": formatted_date,
+        "customer": customer_display,
+        "room": room_display,
+        "payment_status": payment_status,
+        "items_html": items_html,
+        "service_charge_row": service_charge_row,
+        "added_tax_rows": added_tax_rows,
+        "voucher_rows": voucher_rows,
+        "rounding_row": rounding_row,
+        "total": total_amount_fmt,
+        "tax_rows": tax_rows,
+        "fiscal_html": fiscal_html,
+        "qr_code_html": qr_code_html,
+        "receipt_footer_html": receipt_footer_html,
+        "generated_at": chrono::Local::now().format("%B %d, %Y at %I:%M %p").to_string(),
+    });
+    labels.apply_to(&mut context);
+    let html = crate::templates::render("order_receipt", &context)?;
     
     // Debug: Print first 500 characters to see if logo is embedded
     if html.len() > 500 {
         println!("🔍 HTML PREVIEW (first 500 chars): {}", &html[..500]);
     }
-    if html.contains("data:image/jpeg;base64,") {
+    if html.contains("data:image/") {
         println!("✅ Logo image tag found in HTML!");
     } else {
         println!("❌ Logo image tag NOT found in HTML!");
@@ -533,8 +678,8 @@ pub fn build_order_receipt_html(order_id: i64) -> Result<String, String> {
 
 /// Generate HTML invoice for a guest's final bill
 #[tauri::command]
-pub fn build_final_invoice_html(guest_id: i64) -> Result<String, String> {
-    build_final_invoice_html_with_discount(guest_id, "flat".to_string(), 0.0, "".to_string())
+pub fn build_final_invoice_html(guest_id: i64, locale: Option<String>) -> Result<String, String> {
+    build_final_invoice_html_with_discount(guest_id, "flat".to_string(), 0.0, "".to_string(), locale)
 }
 
 /// Generate HTML invoice for a guest's final bill with discount information
@@ -543,37 +688,27 @@ pub fn build_final_invoice_html_with_discount(
     guest_id: i64, 
     discount_type: String, 
     discount_amount: f64, 
-    _discount_description: String
+    _discount_description: String,
+    locale: Option<String>,
 ) -> Result<String, String> {
     let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    let labels = crate::receipt_i18n::receipt_labels(&conn, locale.as_deref())?;
 
-    let currency_code = get_setting_or(&conn, "currency_code", "USD")?
-        .trim()
-        .to_uppercase();
+    let money = crate::money::Money::load(&conn)?;
 
     let business_name = get_setting_or(&conn, "business_name", "Business Manager")?;
-    let business_address = get_setting_or(&conn, "business_address", "")?;
+    let business_address = business_contact(&conn)?;
 
     let receipt_header = get_setting_or(&conn, "receipt_header", "")?;
     let receipt_footer = get_setting_or(&conn, "receipt_footer", "")?;
     
     // Logo: use saved business logo if available, otherwise fall back to embedded logo.
-    let logo_src = match get_business_logo_data_url(&conn)? {
-        Some(src) => src,
-        None => {
-            let embedded = get_logo_base64();
-            if embedded.is_empty() {
-                "".to_string()
-            } else {
-                format!("data:image/jpeg;base64,{}", embedded)
-            }
-        }
-    };
+    let logo_src = crate::media::document_logo_data_url(&conn).unwrap_or_default();
 
     let logo_html = if logo_src.is_empty() {
         "".to_string()
     } else {
-        format!(r#"<img src=\"{}\" alt=\"Logo\" style=\"width: auto; height: 60px; max-width: 120px; object-fit: contain; display: block; margin: 0 auto; -webkit-print-color-adjust: exact; print-color-adjust: exact;\">"#, logo_src)
+        format!(r#"<img src="{}" alt="Logo" style="width: auto; height: 60px; max-width: 120px; object-fit: contain; display: block; margin: 0 auto; -webkit-print-color-adjust: exact; print-color-adjust: exact;">"#, logo_src)
     };
 
     let receipt_header_html = if receipt_header.trim().is_empty() {
@@ -588,6 +723,18 @@ pub fn build_final_invoice_html_with_discount(
         format!(r#"<div style=\"margin-top: 8px; font-size: 10px; color: #333; line-height: 1.35; text-align: center;\">{}</div>"#, escape_multiline(receipt_footer.trim()))
     };
     
+    let signature_html = match crate::registration::load_signature_data_url(&conn, guest_id)? {
+        Some(src) => format!(
+            r#"<div style="margin: 8px 0; text-align: center; font-size: 9px;">
+            <img src="{}" alt="Signature" style="max-height: 40px; max-width: 160px; display: block; margin: 0 auto;">
+            {}
+        </div>"#,
+            src,
+            labels.t("Signed at check-in")
+        ),
+        None => "".to_string(),
+    };
+
     if logo_src.is_empty() {
         println!("❌ WARNING: Logo base64 data is EMPTY for final invoice!");
     } else {
@@ -616,24 +763,41 @@ pub fn build_final_invoice_html_with_discount(
         ))
     }).map_err(|e| format!("Guest not found: {}", e))?;
     
-        let (_id, name, _phone, check_in, check_out, daily_rate, _status, room_number) = guest_row;
+        let (_id, name, _phone, check_in, check_out, _daily_rate, status, room_number) = guest_row;
+    
+    // A bill drawn up before the guest leaves shows the late check-out fee
+    // checkout will post: it's posted here and rolled back once the invoice is done
+    let preview = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    if status == "active" {
+        crate::stay_fees::post_late_check_out_fee(&preview, guest_id, chrono::Local::now().naive_local())?;
+    }
     
-    // Calculate room charges
-    let checkout_date = check_out.clone().unwrap_or_else(|| {
-        chrono::Local::now().format("%Y-%m-%d").to_string()
+    // An active guest is billed up to today, the way checkout bills them
+    let checkout_date = check_out.clone().filter(|_| status != "active").unwrap_or_else(|| {
+        chrono::Utc::now().date_naive().format("%Y-%m-%d").to_string()
     });
     
     let days = calculate_stay_days(&check_in, &checkout_date)?;
-    let room_total = days as f64 * daily_rate;
+    let segments = crate::pricing::guest_room_segments(&conn, guest_id, days as i64)?;
     
-    // Get food order details with items (ALL orders, both paid and unpaid)
-    let mut total_food_cost = 0.0;
+    // The totals are the bill checkout charges; the rows below only itemize it
+    let vouchers = crate::vouchers::redemptions_for(&conn, None, Some(guest_id))?;
+    let voucher_total: f64 = vouchers.iter().map(|(_, amount)| amount).sum();
+    let (discount_pct, discount_flat) = match discount_type.as_str() {
+        "percentage" => (Some(discount_amount), None),
+        "flat" => (None, Some(discount_amount)),
+        _ => (None, None),
+    };
+    let redeemed = (voucher_total > 0.0).then_some(crate::simple_commands::BillVoucher::Redeemed(voucher_total));
+    let (bill, _) = crate::simple_commands::guest_bill(&conn, guest_id, days as i64, discount_pct, discount_flat, redeemed)?;
+    let room_total = bill.room_total;
     
     // Get all food orders for this guest (both paid and unpaid)
+    let mut service_charge_total = 0.0;
     let mut order_stmt = conn.prepare(
-        "SELECT fo.id, fo.total_amount, fo.paid
+        "SELECT fo.id, fo.total_amount, fo.paid, fo.amount_paid, fo.service_charge_percent, fo.service_charge
             FROM sales fo
-         WHERE fo.guest_id = ?
+         WHERE fo.guest_id = ? AND fo.deleted_at IS NULL
          ORDER BY fo.created_at"
     ).map_err(|e| format!("Failed to prepare food orders query: {}", e))?;
     
@@ -642,42 +806,40 @@ pub fn build_final_invoice_html_with_discount(
             row.get::<_, i64>(0)?,   // order_id
             row.get::<_, f64>(1)?,   // total_amount
             row.get::<_, bool>(2)?,  // paid
+            row.get::<_, f64>(3)?,   // amount_paid
+            row.get::<_, f64>(4)?,   // service_charge_percent
+            row.get::<_, f64>(5)?,   // service_charge
         ))
     }).map_err(|e| format!("Failed to execute food orders query: {}", e))?;
     
     // For each order, get the items
     let mut food_table_rows = String::new();
     for order_result in sales {
-        let (order_id, _amount, paid) = order_result.map_err(|e| format!("Failed to read order: {}", e))?;
-        
-        let mut item_stmt = conn.prepare(
-            "SELECT oi.quantity, oi.item_name, oi.unit_price
-               FROM sale_items oi
-             WHERE oi.order_id = ?"
-        ).map_err(|e| format!("Failed to prepare order items query: {}", e))?;
+        let (order_id, _amount, paid, amount_paid, service_charge_percent, service_charge) =
+            order_result.map_err(|e| format!("Failed to read order: {}", e))?;
         
-        let items = item_stmt.query_map([order_id], |row| {
-            Ok((
-                row.get::<_, i32>(0)?,    // quantity
-                row.get::<_, String>(1)?, // item_name
-                row.get::<_, f64>(2)?,    // unit_price
-            ))
-        }).map_err(|e| format!("Failed to execute order items query: {}", e))?;
+        let items = crate::simple_commands::order_item_details(&conn, order_id)?;
+        let strike_through = if paid { "text-decoration: line-through; opacity: 0.6;" } else { "" };
         
-        for item_result in items {
-            let (quantity, name, unit_price) = item_result.map_err(|e| format!("Failed to read item: {}", e))?;
-            let line_total = quantity as f64 * unit_price;
-            
-            // Only include UNPAID food orders in the total calculation
-            if !paid {
-                total_food_cost += line_total;
+        for item in items {
+            let quantity = item.quantity;
+            // Modifiers are folded into the unit price and listed after the name
+            let line_total = item.line_total;
+            let unit_price = if quantity > 0 { line_total / quantity as f64 } else { item.unit_price };
+            let mut name = if item.modifiers.is_empty() {
+                item.item_name
+            } else {
+                let modifiers: Vec<String> = item.modifiers.into_iter().map(|m| m.name).collect();
+                format!("{} ({})", item.item_name, modifiers.join(", "))
+            };
+            if item.voided {
+                name.push_str(&format!(" ({})", labels.t("VOID")));
             }
             
             // Add table row for this item with clear paid/unpaid indication
-            let status_indicator = if paid { " [PAID]" } else { " [UNPAID]" };
-            let strike_through = if paid { "text-decoration: line-through; opacity: 0.6;" } else { "" };
-            let unit_price_fmt = format_money(unit_price, &currency_code, 0);
-            let line_total_fmt = format_money(line_total, &currency_code, 0);
+            let status_indicator = format!(" [{}]", labels.t(if paid { "PAID" } else { "UNPAID" }));
+            let unit_price_fmt = money.format(unit_price, 0);
+            let line_total_fmt = money.format(line_total, 0);
             food_table_rows.push_str(&format!(
                 r#"<div class="table-row" style="{}">
                     <div class="table-cell"><strong>{}{}</strong></div>
@@ -693,437 +855,358 @@ pub fn build_final_invoice_html_with_discount(
                 line_total_fmt
             ));
         }
+        
+        if service_charge > 0.0 {
+            if !paid {
+                service_charge_total += service_charge;
+            }
+            food_table_rows.push_str(&format!(
+                r#"<div class="table-row" style="{}">
+                    <div class="table-cell"><strong>{} #{} ({:.1}%)</strong></div>
+                    <div class="table-cell center">-</div>
+                    <div class="table-cell center">-</div>
+                    <div class="table-cell right">{}</div>
+                </div>"#,
+                strike_through,
+                labels.t("Service charge on order"),
+                order_id,
+                service_charge_percent,
+                money.format(service_charge, 0)
+            ));
+        }
+        
+        // Part-paid orders: what was already received comes off the bill
+        if !paid && amount_paid > 0.0 {
+            food_table_rows.push_str(&format!(
+                r#"<div class="table-row">
+                    <div class="table-cell"><strong>{} #{}</strong></div>
+                    <div class="table-cell center">-</div>
+                    <div class="table-cell center">-</div>
+                    <div class="table-cell right">-{}</div>
+                </div>"#,
+                labels.t("Paid on order"),
+                order_id,
+                money.format(amount_paid, 0)
+            ));
+        }
+
+        // Vouchers taken off an order lowered its total below its items
+        if !paid {
+            for (code, amount) in crate::vouchers::redemptions_for(&conn, Some(order_id), None)? {
+                food_table_rows.push_str(&format!(
+                    r#"<div class="table-row">
+                    <div class="table-cell"><strong>{} {} #{}</strong></div>
+                    <div class="table-cell center">-</div>
+                    <div class="table-cell center">-</div>
+                    <div class="table-cell right">-{}</div>
+                </div>"#,
+                    labels.t("Voucher"),
+                    html_escape(&code),
+                    order_id,
+                    money.format(amount, 0)
+                ));
+            }
+        }
     }
     
     // If no food items, show a simple message
     if food_table_rows.is_empty() {
-        let zero_fmt = format_money(0.0, &currency_code, 0);
-        food_table_rows = r#"<div class="table-row">
-            <div class="table-cell">No food orders</div>
+        let zero_fmt = money.format(0.0, 0);
+        food_table_rows = format!(r#"<div class="table-row">
+            <div class="table-cell">{}</div>
             <div class="table-cell center">-</div>
             <div class="table-cell center">-</div>
-            <div class="table-cell right">__ZERO__</div>
-        </div>"#.to_string().replace("__ZERO__", &zero_fmt);
+            <div class="table-cell right">{}</div>
+        </div>"#, labels.t("No food orders"), zero_fmt);
     }
     
-    // Calculate totals (only unpaid food items are included in final total)
-    let subtotal_before_discount = room_total + total_food_cost;
-    
-    // Apply discount
-    let discount_value = if discount_amount > 0.0 {
-        match discount_type.as_str() {
-            "percentage" => {
-                if discount_amount > 100.0 {
-                    0.0 // Cap at 100%
-                } else {
-                    subtotal_before_discount * (discount_amount / 100.0)
-                }
-            },
-            "flat" => discount_amount,
-            _ => 0.0
-        }
+    // Folio adjustments such as damage charges converted from checkout holds
+    let adjustments = crate::checkout_holds::guest_adjustments(&conn, guest_id)?;
+    let adjustments_html = if adjustments.is_empty() {
+        "".to_string()
     } else {
-        0.0
+        let rows: String = adjustments
+            .iter()
+            .map(|a| {
+                format!(
+                    r#"<div class="table-row">
+                    <div class="table-cell"><strong>{}</strong></div>
+                    <div class="table-cell center">{}</div>
+                    <div class="table-cell center">-</div>
+                    <div class="table-cell right">{}</div>
+                </div>"#,
+                    html_escape(&a.description),
+                    html_escape(a.created_at.get(..10).unwrap_or(&a.created_at)),
+                    money.format(a.amount, 0)
+                )
+            })
+            .collect();
+        format!(
+            r#"<div class="section-header">{}</div>
+        <div class="table-header">
+            <div class="table-cell">{}</div>
+            <div class="table-cell center">{}</div>
+            <div class="table-cell center">-</div>
+            <div class="table-cell right">{}</div>
+        </div>
+        {}"#,
+            labels.t("ADJUSTMENTS"),
+            labels.t("Description"),
+            labels.t("Date"),
+            labels.t("Total"),
+            rows
+        )
     };
-    
-    let subtotal = (subtotal_before_discount - discount_value).max(0.0);
-    
-    // Get tax settings
-    let tax_enabled = crate::simple_commands::get_tax_enabled().unwrap_or(true);
-    let tax_rate = if tax_enabled {
-        crate::simple_commands::get_tax_rate().unwrap_or(5.0) / 100.0
+
+    // Minibar, laundry, late checkout and other extras billed to the room
+    let charges = crate::guest_charges::guest_charges(&conn, guest_id)?;
+    let charges_html = if charges.is_empty() {
+        "".to_string()
+    } else {
+        let rows: String = charges
+            .iter()
+            .map(|c| {
+                format!(
+                    r#"<div class="table-row">
+                    <div class="table-cell">{} <span style="font-size: 9px;">({})</span></div>
+                    <div class="table-cell center">{}</div>
+                    <div class="table-cell center">{}</div>
+                    <div class="table-cell right">{}</div>
+                </div>"#,
+                    html_escape(&c.description),
+                    html_escape(&c.charged_on),
+                    c.quantity,
+                    money.format(c.unit_price, 0),
+                    money.format(c.amount, 0)
+                )
+            })
+            .collect();
+        format!(
+            r#"<div class="section-header">{}</div>
+        <div class="table-header">
+            <div class="table-cell">{}</div>
+            <div class="table-cell center">{}</div>
+            <div class="table-cell center">{}</div>
+            <div class="table-cell right">{}</div>
+        </div>
+        {}"#,
+            labels.t("EXTRA CHARGES"),
+            labels.t("Item"),
+            labels.t("Qty"),
+            labels.t("Unit Price"),
+            labels.t("Total"),
+            rows
+        )
+    };
+
+    // Guest tags (VIP, Corporate, ...) are shown next to the name on the folio
+    let guest_tags = crate::tags::tags_for_entity(&conn, "guest", guest_id)?;
+    let customer_display = if guest_tags.is_empty() {
+        name.clone()
     } else {
-        0.0
+        let names: Vec<&str> = guest_tags.iter().map(|t| t.name.as_str()).collect();
+        format!("{} ({})", name, names.join(", "))
     };
-    let tax_amount = subtotal * tax_rate;
-    let final_total = subtotal + tax_amount;
+
+    // Unresolved checkout holds are shown prominently so the desk can't miss them
+    let holds = crate::checkout_holds::open_holds(&conn, Some(guest_id))?;
+    let holds_html = if holds.is_empty() {
+        "".to_string()
+    } else {
+        let items: String = holds
+            .iter()
+            .map(|h| {
+                let estimate = h
+                    .amount_estimate
+                    .map(|e| format!(" ({} {})", labels.t("est."), money.format(e, 0)))
+                    .unwrap_or_default();
+                format!(
+                    "<div>#{} {}{} &mdash; {} {}</div>",
+                    h.id,
+                    html_escape(&h.reason),
+                    estimate,
+                    labels.t("reported by"),
+                    html_escape(&h.placed_by)
+                )
+            })
+            .collect();
+        format!(
+            r#"<div style="margin: 8px 0; padding: 6px; border: 2px solid #b00020; color: #b00020; font-size: 10px;">
+            <strong>{} &mdash; {}</strong>
+            {}
+        </div>"#,
+            labels.t("CHECKOUT HOLD"),
+            labels.t("resolve before checkout"),
+            items
+        )
+    };
+
+    // Only unpaid food is in the total; what's left of it past the service charge is the food line
+    let total_food_cost = bill.unpaid_food - service_charge_total;
+    let subtotal_before_discount = room_total + bill.unpaid_food + bill.adjustments + bill.extra_charges;
+    let discounted = bill.grand_total - bill.rounding - crate::taxes::tax_to_add(&bill.taxes);
+    let discount_value = (subtotal_before_discount - voucher_total - discounted).max(0.0);
+    let taxes = bill.taxes;
+    let (final_total, rounding, deposits_total) = (bill.grand_total, bill.rounding, bill.deposits);
     
     // Create receipt in the format requested
     let current_date = chrono::Local::now();
     let formatted_date = current_date.format("%d-%m-%Y");
     let formatted_time = current_date.format("%I:%M %p");
 
-    let daily_rate_fmt = format_money(daily_rate, &currency_code, 0);
-    let room_total_fmt = format_money(room_total, &currency_code, 0);
-    let total_food_cost_fmt = format_money(total_food_cost, &currency_code, 0);
-    let subtotal_before_discount_fmt = format_money(subtotal_before_discount, &currency_code, 0);
-    let final_total_fmt = format_money(final_total, &currency_code, 0);
+    // With nightly pricing the rate varies; show the average so days x rate = room total
+    let daily_rate_fmt = money.format(room_total / days as f64, 0);
+    let room_total_fmt = money.format(room_total, 0);
+    // One line per room the guest stayed in, so a mid-stay transfer shows both rates
+    let room_rows: String = segments
+        .iter()
+        .filter(|s| s.nights > 0)
+        .map(|s| format!(r#"<div class="table-row">
+            <div class="table-cell">{} {} - {}</div>
+            <div class="table-cell center">{}</div>
+            <div class="table-cell center">{}</div>
+            <div class="table-cell right">{}</div>
+        </div>"#,
+            labels.t("Room"),
+            html_escape(s.room_number.as_deref().unwrap_or(&room_number)),
+            labels.t("Accommodation"),
+            s.nights,
+            money.format(s.total / s.nights as f64, 0),
+            money.format(s.total, 0)))
+        .collect();
+    let total_food_cost_fmt = money.format(total_food_cost, 0);
+    let subtotal_before_discount_fmt = money.format(subtotal_before_discount, 0);
+    let final_total_fmt = money.format(final_total, 0);
     
-    let html = format!(r#"<!DOCTYPE html>
-<html>
-<head>
-    <meta charset="UTF-8">
-    <title>Final Invoice</title>
-    <style>
-        @page {{
-            size: A4;
-            margin: 15mm;
-        }}
-        
-        * {{
-            margin: 0;
-            padding: 0;
-            box-sizing: border-box;
-        }}
-        
-        body {{
-            font-family: Arial, sans-serif;
-            font-size: 11px;
-            line-height: 1.4;
-            color: #000;
-            background: #fff;
-            max-width: 600px;
-            margin: 0 auto;
-            padding: 15px;
-        }}
-        
-        .invoice {{
-            border: 1px solid #333;
-            padding: 20px;
-            background: #fff;
-            page-break-inside: avoid;
-        }}
-        
-        .header {{
-            text-align: center;
-            margin-bottom: 15px;
-            border-bottom: 1px solid #333;
-            padding-bottom: 10px;
-        }}
-        
-        .logo {{
-            width: 120px;
-            height: 60px;
-            margin: 0 auto 15px;
-            display: block;
-            border: 2px solid #333;
-            background: #fff;
-            padding: 5px;
-            border-radius: 4px;
-            box-shadow: 0 2px 4px rgba(0,0,0,0.1);
-            object-fit: contain;
-        }}
-        
-        .logo::after {{
-            content: "LOGO";
-            display: block;
-            text-align: center;
-            color: #666;
-            font-size: 12px;
-            margin-top: 20px;
-        }}
-        
-        .hotel-name {{
-            font-size: 16px;
-            font-weight: bold;
-            margin-bottom: 3px;
-        }}
-        
-        .hotel-address {{
-            font-size: 9px;
-            color: #666;
-            margin-bottom: 2px;
-        }}
-        
-        .receipt-title {{
-            font-size: 14px;
-            font-weight: bold;
-            margin-top: 10px;
-            color: #2c5282;
-        }}
-        
-        .info-section {{
-            margin-bottom: 15px;
-        }}
-        
-        .info-row {{
-            display: flex;
-            justify-content: space-between;
-            margin-bottom: 3px;
-            font-size: 10px;
-        }}
-        
-        .info-label {{
-            font-weight: bold;
-            color: #666;
-        }}
-        
-        .divider {{
-            border-top: 1px solid #333;
-            margin: 12px 0;
-        }}
-        
-        .section-header {{
-            font-weight: bold;
-            margin: 12px 0 8px 0;
-            text-align: center;
-            text-decoration: underline;
-            font-size: 11px;
-        }}
-        
-        .table-header {{
-            display: grid;
-            grid-template-columns: 2fr 1fr 1fr 1fr;
-            gap: 8px;
-            padding: 6px 0;
-            border-bottom: 1px solid #333;
-            font-weight: bold;
-            font-size: 10px;
-            background: #f5f5f5;
-        }}
-        
-        .table-row {{
-            display: grid;
-            grid-template-columns: 2fr 1fr 1fr 1fr;
-            gap: 8px;
-            padding: 4px 0;
-            border-bottom: 1px dotted #ccc;
-            font-size: 10px;
-        }}
-        
-        .table-cell {{
-            text-align: left;
-        }}
-        
-        .table-cell.center {{
-            text-align: center;
-        }}
-        
-        .table-cell.right {{
-            text-align: right;
-        }}
-        
-        .total-section {{
-            margin-top: 12px;
-            border-top: 1px solid #333;
-            padding-top: 8px;
-        }}
-        
-        .total-row {{
-            display: flex;
-            justify-content: space-between;
-            margin-bottom: 3px;
-            font-size: 10px;
-        }}
-        
-        .grand-total {{
-            font-weight: bold;
-            font-size: 12px;
-            border-top: 2px solid #333;
-            padding-top: 6px;
-            margin-top: 6px;
-        }}
-        
-        .payment-status {{
-            text-align: center;
-            margin: 12px 0;
-            padding: 6px;
-            border: 1px solid #333;
-            font-weight: bold;
-            background: #f0f0f0;
-            font-size: 11px;
-        }}
-        
-        .footer {{
-            text-align: center;
-            margin-top: 12px;
-            font-size: 10px;
-            font-style: italic;
-        }}
-        
-        .contact-info {{
-            text-align: center;
-            margin-top: 8px;
-            font-size: 9px;
-            color: #666;
-        }}
-        
-        @media print {{
-            body {{
-                margin: 0;
-                padding: 8px;
-                max-width: none;
-            }}
-            
-            .invoice {{
-                border: 1px solid #000;
-                margin: 0;
-                padding: 15px;
-            }}
-            
-            .payment-status {{
-                background: #fff !important;
-            }}
-            
-            .table-header {{
-                background: #fff !important;
-            }}
-            
-            .logo {{
-                max-width: 100px !important;
-                height: auto !important;
-                border: 1px solid #000 !important;
-                background: #fff !important;
-                padding: 3px !important;
-                display: block !important;
-                margin: 0 auto 10px !important;
-                -webkit-print-color-adjust: exact !important;
-                print-color-adjust: exact !important;
-            }}
-        }}
-    </style>
-</head>
-<body>
-    <div class="invoice">
-        <div class="header">
-            <div class="logo-container" style="text-align: center; margin-bottom: 20px; padding: 10px;">
-                {}
-            </div>
-            <div class="hotel-name">{}</div>
-            <div class="hotel-address">{}</div>
-            {}
-            <div class="receipt-title">Final Invoice</div>
-        </div>
-        
-        <div class="info-section">
-            <div class="info-row">
-                <span class="info-label">Customer:</span>
-                <span>{}</span>
-            </div>
-            <div class="info-row">
-                <span class="info-label">Date:</span>
+    // Conditional total rows
+    let service_charge_row = if service_charge_total > 0.0 {
+        format!(r#"<div class="total-row">
+                <span>{}:</span>
                 <span>{}</span>
-            </div>
-            <div class="info-row">
-                <span class="info-label">Room:</span>
+            </div>"#, labels.t("Service charge"), money.format(service_charge_total, 0))
+    } else {
+        "".to_string()
+    };
+    let charges_row = if charges.is_empty() {
+        "".to_string()
+    } else {
+        format!(r#"<div class="total-row">
+                <span>{}:</span>
                 <span>{}</span>
-            </div>
-            <div class="info-row">
-                <span class="info-label">Check-in:</span>
+            </div>"#, labels.t("Extra Charges"), money.format(bill.extra_charges, 0))
+    };
+    let adjustments_row = if adjustments.is_empty() {
+        "".to_string()
+    } else {
+        format!(r#"<div class="total-row">
+                <span>{}:</span>
                 <span>{}</span>
-            </div>
-            <div class="info-row">
-                <span class="info-label">Check-out:</span>
+            </div>"#, labels.t("Adjustments"), money.format(bill.adjustments, 0))
+    };
+    let discount_row = if discount_value > 0.0 {
+        let discount_label = if discount_type == "percentage" {
+            format!("{} ({:.1}%):", labels.t("Discount"), discount_amount)
+        } else {
+            format!("{}:", labels.t("Discount"))
+        };
+        let discount_fmt = format!("-{}", money.format(discount_value, 0));
+        format!(r#"<div class="total-row">
                 <span>{}</span>
-            </div>
-        </div>
-        
-        <div class="divider"></div>
-        
-        <div class="section-header">ROOM CHARGES</div>
-        <div class="table-header">
-            <div class="table-cell">Description</div>
-            <div class="table-cell center">Days</div>
-            <div class="table-cell center">Rate</div>
-            <div class="table-cell right">Total</div>
-        </div>
-        <div class="table-row">
-            <div class="table-cell">Room {} - Accommodation</div>
-            <div class="table-cell center">{}</div>
-            <div class="table-cell center">{}</div>
-            <div class="table-cell right">{}</div>
-        </div>
-        
-        <div class="section-header">FOOD ORDERS</div>
-        <div class="table-header">
-            <div class="table-cell">Item</div>
-            <div class="table-cell center">Qty</div>
-            <div class="table-cell center">Unit Price</div>
-            <div class="table-cell right">Total</div>
-        </div>
-        {}
-        
-        <div class="total-section">
-            <div class="total-row">
-                <span>Room Charges:</span>
                 <span>{}</span>
-            </div>
-            <div class="total-row">
-                <span>Food Orders:</span>
+            </div>"#, discount_label, discount_fmt)
+    } else {
+        "".to_string()
+    };
+    let voucher_rows: String = vouchers
+        .iter()
+        .map(|(code, amount)| format!(r#"<div class="total-row">
+                <span>{} {}:</span>
+                <span>-{}</span>
+            </div>"#, labels.t("Voucher"), html_escape(code), money.format(*amount, 0)))
+        .collect();
+    // One row per tax charged; none when tax is switched off
+    let tax_rows: String = taxes
+        .iter()
+        .map(|t| format!(r#"<div class="total-row">
+                <span>{}{} ({:.1}%):</span>
                 <span>{}</span>
-            </div>
-            <div class="total-row">
-                <span>Subtotal:</span>
+            </div>"#,
+            if t.included { format!("{} ", labels.t("Includes")) } else { String::new() },
+            html_escape(&t.name),
+            t.percent,
+            money.format(t.amount, 0)))
+        .collect();
+    let rounding_row = if rounding != 0.0 {
+        format!(r#"<div class="total-row">
+                <span>{}:</span>
                 <span>{}</span>
+            </div>"#, labels.t("Rounding"), money.format(rounding, 2))
+    } else {
+        "".to_string()
+    };
+    let deposit_rows = if deposits_total > 0.0 {
+        format!(r#"<div class="total-row">
+                <span>{}:</span>
+                <span>-{}</span>
             </div>
-            {}
-            {}
             <div class="total-row grand-total">
-                <span>Grand Total:</span>
-                <span>{}</span>
-            </div>
-        </div>
-        
-        <div class="payment-status">
-            PAID BY: CASH
-        </div>
-        
-        <div style="margin: 8px 0; padding: 6px; border: 1px solid #333; font-size: 9px; text-align: center; background: #f9f9f9;">
-            <strong>NOTE:</strong> Only unpaid food orders are included in the total amount.<br>
-            Paid orders are shown with [PAID] status and crossed out for reference only.
-        </div>
-        
-        <div class="footer">
-            Thank you for your stay!<br>
-            {}<br>
-            Invoice generated on {} at {}
-        </div>
-        
-        <div class="contact-info">
-            Receipt generated on {} at {}
-        </div>
-    </div>
-</body>
-</html>"#,
-    logo_html,                    // Logo image HTML
-    html_escape(&business_name),  // Business name
-    html_escape(&business_address), // Business address
-    receipt_header_html,
-        html_escape(&name),           // Customer name
-        formatted_date,               // Current date
-        html_escape(&room_number),    // Room number
-        html_escape(&check_in),       // Check-in date
-        html_escape(&checkout_date),  // Check-out date
-        html_escape(&room_number),    // Room number for charges table
-        days,                         // Number of days
-        daily_rate_fmt,              // Daily rate
-        room_total_fmt,              // Total room charges
-        food_table_rows,             // Food items table rows
-        room_total_fmt,              // Room charges in totals
-        total_food_cost_fmt,         // Food cost
-        subtotal_before_discount_fmt, // Subtotal before discount
-        // Discount row - conditionally included
-        if discount_value > 0.0 {
-            let discount_label = if discount_type == "percentage" {
-                format!("Discount ({:.1}%):", discount_amount)
-            } else {
-                "Discount:".to_string()
-            };
-            let discount_fmt = format!("-{}", format_money(discount_value, &currency_code, 0));
-            format!(r#"<div class="total-row">
+                <span>{}:</span>
                 <span>{}</span>
-                <span>{}</span>
-            </div>"#, discount_label, discount_fmt)
-        } else {
-            "".to_string()
-        },
-        // Tax row - conditionally included
-        if tax_enabled {
-            let tax_fmt = format_money(tax_amount, &currency_code, 0);
-            format!(r#"<div class="total-row">
-                <span>Tax ({:.1}%):</span>
-                <span>{}</span>
-            </div>"#, tax_rate * 100.0, tax_fmt)
-        } else {
-            "".to_string()
-        },
-        final_total_fmt,             // Final total
-        receipt_footer_html,          // Receipt footer
-        formatted_date,              // Date for footer
-        formatted_time,              // Time for footer
-        formatted_date,              // Date for contact info
-        formatted_time               // Time for contact info
-    );
+            </div>"#,
+            labels.t("Deposit paid"),
+            money.format(deposits_total, 0),
+            labels.t("Balance due"),
+            money.format(final_total - deposits_total, 0))
+    } else {
+        "".to_string()
+    };
+
+    let qr_code_html = qr_code_html(&conn, &money, QrSubject {
+        reference: format!("INVOICE-{}", guest_id),
+        summary: format!("{} | Invoice for {} | {} | Total {}", business_name, name, formatted_date, final_total_fmt),
+        amount_due: final_total - deposits_total,
+        labels: &labels,
+    })?;
+
+    let fiscal_html = fiscal_html(&conn, "checkout", guest_id, &labels)?;
+
+    let mut context = json!({
+        "logo_html": logo_html,
+        "business_name": business_name,
+        "business_contact": business_address,
+        "receipt_header_html": receipt_header_html,
+        "customer": customer_display,
+        "invoice_date": formatted_date.to_string(),
+        "invoice_time": formatted_time.to_string(),
+        "room_number": room_number,
+        "check_in": check_in,
+        "check_out": checkout_date,
+        "holds_html": holds_html,
+        "days": days,
+        "daily_rate": daily_rate_fmt,
+        "room_total": room_total_fmt,
+        "room_rows": room_rows,
+        "food_rows": food_table_rows,
+        "charges_html": charges_html,
+        "adjustments_html": adjustments_html,
+        "food_total": total_food_cost_fmt,
+        "service_charge_row": service_charge_row,
+        "charges_row": charges_row,
+        "adjustments_row": adjustments_row,
+        "subtotal": subtotal_before_discount_fmt,
+        "discount_row": discount_row,
+        "voucher_rows": voucher_rows,
+        "tax_rows": tax_rows,
+        "rounding_row": rounding_row,
+        "grand_total": final_total_fmt,
+        "deposit_rows": deposit_rows,
+        "signature_html": signature_html,
+        "fiscal_html": fiscal_html,
+        "qr_code_html": qr_code_html,
+        "receipt_footer_html": receipt_footer_html,
+    });
+    labels.apply_to(&mut context);
+    let html = crate::templates::render("final_invoice", &context)?;
     
     // Debug: Print first 500 characters to see if logo is embedded
     if html.len() > 500 {
@@ -1138,11 +1221,11 @@ pub fn build_final_invoice_html_with_discount(
         println!("📄 Complete HTML written to {:?} for inspection", debug_path);
     }
     
-    if html.contains("data:image/jpeg;base64,") {
+    if html.contains("data:image/") {
         println!("✅ Logo image tag found in FINAL INVOICE HTML!");
         // Find the logo src and print the first 100 characters of base64
-        if let Some(start) = html.find("data:image/jpeg;base64,") {
-            let base64_start = start + "data:image/jpeg;base64,".len();
+        if let Some(start) = html.find(";base64,") {
+            let base64_start = start + ";base64,".len();
             if let Some(end) = html[base64_start..].find("\"") {
                 let base64_sample = &html[base64_start..base64_start + end.min(100)];
                 println!("🔍 Base64 in HTML (first 100 chars): {}", base64_sample);
@@ -1156,6 +1239,65 @@ pub fn build_final_invoice_html_with_discount(
     Ok(html)
 }
 
+/// Generate the guest registration card. Reprints embed the signature captured at
+/// check-in, so the card always reflects what the guest actually signed.
+#[tauri::command]
+pub fn build_registration_card_html(guest_id: i64) -> Result<String, String> {
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let business_name = get_setting_or(&conn, "business_name", "Business Manager")?;
+    let business_address = business_contact(&conn)?;
+    let house_rules = get_setting_or(&conn, "house_rules", "")?;
+
+    let (name, phone, check_in, check_out, room_number, terms_accepted_at) = conn.query_row(
+        "SELECT g.name, g.phone, g.check_in, g.check_out, r.number, g.terms_accepted_at
+            FROM customers g
+            LEFT JOIN resources r ON g.room_id = r.id
+         WHERE g.id = ?",
+        [guest_id],
+        |row| Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, Option<String>>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, Option<String>>(5)?,
+        ))
+    ).map_err(|e| format!("Guest not found: {}", e))?;
+
+    let house_rules_html = if house_rules.trim().is_empty() {
+        "".to_string()
+    } else {
+        format!(r#"<div class="rules"><h3>House Rules</h3>{}</div>"#, escape_multiline(house_rules.trim()))
+    };
+
+    let signature_html = match crate::registration::load_signature_data_url(&conn, guest_id)? {
+        Some(src) => format!(
+            r#"<img src="{}" alt="Guest signature" class="signature"><div>Signed at check-in{}</div>"#,
+            src,
+            terms_accepted_at
+                .map(|at| format!(" &mdash; terms accepted {}", html_escape(&at)))
+                .unwrap_or_default()
+        ),
+        None => r#"<div class="signature-line"></div><div>Guest signature</div>"#.to_string(),
+    };
+
+    crate::templates::render(
+        "registration_card",
+        &json!({
+            "business_name": business_name,
+            "business_contact": business_address,
+            "guest_name": name,
+            "phone": phone.unwrap_or_default(),
+            "room": room_number.unwrap_or_else(|| "Walk-in".to_string()),
+            "check_in": check_in,
+            "check_out": check_out.unwrap_or_default(),
+            "house_rules_html": house_rules_html,
+            "signature_html": signature_html,
+        }),
+    )
+}
+
 fn calculate_stay_days(check_in: &str, check_out: &str) -> Result<i32, String> {
     let check_in_date = chrono::NaiveDate::parse_from_str(check_in, "%Y-%m-%d")
         .map_err(|e| format!("Invalid check-in date: {}", e))?;
@@ -1169,10 +1311,52 @@ fn calculate_stay_days(check_in: &str, check_out: &str) -> Result<i32, String> {
     Ok(days.max(1))
 }
 
-fn html_escape(text: &str) -> String {
+pub(crate) fn html_escape(text: &str) -> String {
     text.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
         .replace('"', "&quot;")
         .replace('\'', "&#39;")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::insert_food_order;
+    use crate::test_support::{add_test_room, check_in, order_item, TestDb};
+
+    fn coffee_order() -> i64 {
+        insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Coffee", 5.0, 2)], None, None, None).unwrap().0
+    }
+
+    #[test]
+    fn receipts_carry_a_payment_qr_code_when_one_is_set() {
+        let _db = TestDb::new();
+        let order_id = coffee_order();
+        assert!(!build_order_receipt_html(order_id, None).unwrap().contains("<svg"), "receipt has a QR code with QR codes off");
+        set_receipt_qr("payment_link".to_string(), Some("https://pay.example.com/?ref={reference}&amount={amount}".to_string())).unwrap();
+        let receipt = build_order_receipt_html(order_id, None).unwrap();
+        assert!(receipt.contains("<svg") && receipt.contains("Scan to pay"), "receipt has no payment QR code");
+    }
+
+    #[test]
+    fn payment_qr_codes_need_a_link() {
+        let _db = TestDb::new();
+        assert!(set_receipt_qr("payment_link".to_string(), Some("  ".to_string())).is_err(), "payment QR codes were turned on without a link");
+        assert!(set_receipt_qr("sometimes".to_string(), None).is_err(), "an unknown QR mode was accepted");
+    }
+
+    #[test]
+    fn the_final_invoice_totals_the_bill_checkout_charges() {
+        let _db = TestDb::new();
+        crate::service_charge::set_service_charge_percent("hotel".to_string(), 10.0).unwrap();
+        let guest_id = check_in("Invoiced", Some(add_test_room("PT-101")));
+        insert_food_order(Some(guest_id), "guest".to_string(), None, vec![order_item("Dinner", 12.35, 1)], None, None, None).unwrap();
+        let invoice = build_final_invoice_html_with_discount(guest_id, "percentage".to_string(), 7.5, String::new(), None).unwrap();
+
+        let conn = crate::db::get_db_connection().unwrap();
+        let (bill, _) = crate::simple_commands::guest_bill(&conn, guest_id, 1, Some(7.5), None, None).unwrap();
+        let total = crate::money::Money::load(&conn).unwrap().format(bill.grand_total, 0);
+        assert!(invoice.contains(&total), "invoice does not show the checkout total {}", total);
+    }
+}
@@ -0,0 +1,405 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{
+    PurchaseOrder, PurchaseOrderLine, PurchaseOrderLineInput, ReceiveLineInput, Supplier, SupplierPayment,
+};
+use crate::validation::{validate_date_format, EMPTY_FIELD, NEGATIVE_AMOUNT};
+
+// Expense category supplier payments are booked under
+pub const SUPPLIER_EXPENSE_CATEGORY: &str = "Supplier Purchases";
+
+// Quantities closer than this are treated as equal when checking what's left to receive
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+const SUPPLIER_COLUMNS: &str = "s.id, s.name, s.contact_name, s.phone, s.email, s.notes,
+    (SELECT COALESCE(SUM(l.received_quantity * l.unit_cost), 0.0)
+     FROM purchase_order_lines l JOIN purchase_orders po ON po.id = l.purchase_order_id
+     WHERE po.supplier_id = s.id),
+    (SELECT COALESCE(SUM(p.amount), 0.0) FROM supplier_payments p WHERE p.supplier_id = s.id)";
+
+const ORDER_COLUMNS: &str = "po.id, po.supplier_id, s.name, po.status, po.order_date, po.expected_date, po.notes,
+    po.total_amount, (SELECT COALESCE(SUM(p.amount), 0.0) FROM supplier_payments p WHERE p.purchase_order_id = po.id),
+    po.created_by, po.received_at";
+
+fn trimmed(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn map_supplier(row: &rusqlite::Row) -> rusqlite::Result<Supplier> {
+    let received_value: f64 = row.get(6)?;
+    let paid: f64 = row.get(7)?;
+    Ok(Supplier {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        contact_name: row.get(2)?,
+        phone: row.get(3)?,
+        email: row.get(4)?,
+        notes: row.get(5)?,
+        received_value,
+        paid,
+        balance: received_value - paid,
+    })
+}
+
+fn load_supplier(conn: &Connection, supplier_id: i64) -> Result<Supplier, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM suppliers s WHERE s.id = ?1", SUPPLIER_COLUMNS),
+        params![supplier_id],
+        map_supplier,
+    )
+    .optional()?
+    .ok_or_else(|| format!("Supplier #{} not found", supplier_id).into())
+}
+
+fn load_lines(conn: &Connection, purchase_order_id: i64) -> Result<Vec<PurchaseOrderLine>, AppError> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, inventory_item_id, description, quantity, unit_cost, received_quantity
+         FROM purchase_order_lines WHERE purchase_order_id = ?1 ORDER BY id",
+    )?;
+    let lines = stmt
+        .query_map(params![purchase_order_id], |row| {
+            Ok(PurchaseOrderLine {
+                id: row.get(0)?,
+                inventory_item_id: row.get(1)?,
+                description: row.get(2)?,
+                quantity: row.get(3)?,
+                unit_cost: row.get(4)?,
+                received_quantity: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(lines)
+}
+
+fn map_order(row: &rusqlite::Row) -> rusqlite::Result<PurchaseOrder> {
+    Ok(PurchaseOrder {
+        id: row.get(0)?,
+        supplier_id: row.get(1)?,
+        supplier_name: row.get(2)?,
+        status: row.get(3)?,
+        order_date: row.get(4)?,
+        expected_date: row.get(5)?,
+        notes: row.get(6)?,
+        total_amount: row.get(7)?,
+        amount_paid: row.get(8)?,
+        created_by: row.get(9)?,
+        received_at: row.get(10)?,
+        lines: Vec::new(),
+    })
+}
+
+fn load_order(conn: &Connection, purchase_order_id: i64) -> Result<PurchaseOrder, AppError> {
+    let mut order = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM purchase_orders po JOIN suppliers s ON s.id = po.supplier_id WHERE po.id = ?1",
+                ORDER_COLUMNS
+            ),
+            params![purchase_order_id],
+            map_order,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::from(format!("Purchase order #{} not found", purchase_order_id)))?;
+    order.lines = load_lines(conn, purchase_order_id)?;
+    Ok(order)
+}
+
+#[command]
+pub fn add_supplier(
+    name: String,
+    contact_name: Option<String>,
+    phone: Option<String>,
+    email: Option<String>,
+    notes: Option<String>,
+) -> Result<Supplier, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Supplier name cannot be empty"));
+    }
+
+    let conn = get_db_connection()?;
+    conn.execute(
+        "INSERT INTO suppliers (name, contact_name, phone, email, notes, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![name, trimmed(contact_name), trimmed(phone), trimmed(email), trimmed(notes), get_current_timestamp()],
+    )?;
+    load_supplier(&conn, conn.last_insert_rowid())
+}
+
+/// Suppliers by name, with what's owed to each for goods already received.
+#[command]
+pub fn get_suppliers() -> Result<Vec<Supplier>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM suppliers s ORDER BY s.name COLLATE NOCASE", SUPPLIER_COLUMNS))?;
+    let suppliers = stmt.query_map([], map_supplier)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(suppliers)
+}
+
+/// Order stock from a supplier. Lines tied to an inventory item add to its
+/// stock when received; other lines are paid for but not stocked.
+#[command]
+pub fn create_purchase_order(
+    supplier_id: i64,
+    lines: Vec<PurchaseOrderLineInput>,
+    expected_date: Option<String>,
+    notes: Option<String>,
+    session_token: Option<String>,
+) -> Result<PurchaseOrder, AppError> {
+    if lines.is_empty() {
+        return Err("A purchase order needs at least one line".into());
+    }
+    if let Some(date) = &expected_date {
+        validate_date_format(date)?;
+    }
+    for line in &lines {
+        if !line.quantity.is_finite() || line.quantity <= 0.0 {
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "quantity", "Each line needs a quantity above zero"));
+        }
+        if !line.unit_cost.is_finite() || line.unit_cost < 0.0 {
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "unit_cost", "Unit cost must be >= 0"));
+        }
+    }
+
+    let conn = get_db_connection()?;
+    load_supplier(&conn, supplier_id)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let total: f64 = lines.iter().map(|l| l.quantity * l.unit_cost).sum();
+    let now = get_current_timestamp();
+    tx.execute(
+        "INSERT INTO purchase_orders (supplier_id, order_date, expected_date, notes, total_amount, created_by, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            supplier_id,
+            crate::simple_commands::business_date_today(),
+            expected_date,
+            trimmed(notes),
+            total,
+            audit_actor(session_token.as_deref()),
+            now
+        ],
+    )?;
+    let purchase_order_id = tx.last_insert_rowid();
+
+    for line in lines {
+        let description = match (trimmed(line.description), line.inventory_item_id) {
+            (Some(description), _) => description,
+            (None, Some(item_id)) => crate::inventory::load_item(&tx, item_id)?.name,
+            (None, None) => {
+                return Err(AppError::invalid(EMPTY_FIELD, "description", "Lines without an inventory item need a description"));
+            }
+        };
+        if let Some(item_id) = line.inventory_item_id {
+            crate::inventory::load_item(&tx, item_id)?;
+        }
+        tx.execute(
+            "INSERT INTO purchase_order_lines (purchase_order_id, inventory_item_id, description, quantity, unit_cost)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![purchase_order_id, line.inventory_item_id, description, line.quantity, line.unit_cost],
+        )?;
+    }
+    tx.commit()?;
+
+    load_order(&conn, purchase_order_id)
+}
+
+/// Purchase orders, newest first, optionally for one supplier and/or in one status.
+#[command]
+pub fn get_purchase_orders(supplier_id: Option<i64>, status: Option<String>) -> Result<Vec<PurchaseOrder>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM purchase_orders po JOIN suppliers s ON s.id = po.supplier_id
+         WHERE (?1 IS NULL OR po.supplier_id = ?1) AND (?2 IS NULL OR po.status = ?2)
+         ORDER BY po.order_date DESC, po.id DESC",
+        ORDER_COLUMNS
+    ))?;
+    let mut orders = stmt
+        .query_map(params![supplier_id, trimmed(status)], map_order)?
+        .collect::<Result<Vec<_>, _>>()?;
+    for order in &mut orders {
+        order.lines = load_lines(&conn, order.id)?;
+    }
+    Ok(orders)
+}
+
+/// Book goods in. Without `lines`, everything still outstanding is received;
+/// otherwise only the given quantities, which can't exceed what's outstanding.
+/// Stocked lines are added to inventory as a restock and set the item's unit cost.
+#[command]
+pub fn receive_purchase_order(
+    purchase_order_id: i64,
+    lines: Option<Vec<ReceiveLineInput>>,
+    session_token: Option<String>,
+) -> Result<PurchaseOrder, AppError> {
+    let conn = get_db_connection()?;
+    let order = load_order(&conn, purchase_order_id)?;
+    if order.status == "cancelled" || order.status == "received" {
+        return Err(format!("Purchase order #{} is already {}", purchase_order_id, order.status).into());
+    }
+
+    let receipts: Vec<(&PurchaseOrderLine, f64)> = match &lines {
+        None => order.lines.iter().map(|l| (l, l.quantity - l.received_quantity)).collect(),
+        Some(inputs) => {
+            let mut receipts = Vec::with_capacity(inputs.len());
+            for input in inputs {
+                let line = order
+                    .lines
+                    .iter()
+                    .find(|l| l.id == input.line_id)
+                    .ok_or_else(|| format!("Line #{} is not on purchase order #{}", input.line_id, purchase_order_id))?;
+                if !input.quantity.is_finite() || input.quantity <= 0.0 {
+                    return Err(AppError::invalid(NEGATIVE_AMOUNT, "quantity", "Received quantity must be above zero"));
+                }
+                if input.quantity > line.quantity - line.received_quantity + QUANTITY_EPSILON {
+                    return Err(format!(
+                        "Only {} of '{}' is still to be received",
+                        line.quantity - line.received_quantity,
+                        line.description
+                    )
+                    .into());
+                }
+                receipts.push((line, input.quantity));
+            }
+            receipts
+        }
+    };
+
+    let actor = audit_actor(session_token.as_deref());
+    let note = format!("PO #{}", purchase_order_id);
+    let tx = conn.unchecked_transaction()?;
+    for (line, quantity) in receipts.into_iter().filter(|(_, q)| *q > QUANTITY_EPSILON) {
+        tx.execute(
+            "UPDATE purchase_order_lines SET received_quantity = received_quantity + ?1 WHERE id = ?2",
+            params![quantity, line.id],
+        )?;
+        if let Some(item_id) = line.inventory_item_id {
+            crate::inventory::record_movement(&tx, item_id, quantity, "restock", None, Some(&note), &actor)?;
+            tx.execute("UPDATE inventory_items SET unit_cost = ?1 WHERE id = ?2", params![line.unit_cost, item_id])?;
+        }
+    }
+
+    let outstanding: f64 = tx.query_row(
+        "SELECT COALESCE(SUM(MAX(quantity - received_quantity, 0)), 0.0) FROM purchase_order_lines WHERE purchase_order_id = ?1",
+        params![purchase_order_id],
+        |row| row.get(0),
+    )?;
+    let fully_received = outstanding <= QUANTITY_EPSILON;
+    tx.execute(
+        "UPDATE purchase_orders SET status = ?1, received_at = CASE WHEN ?2 THEN ?3 ELSE received_at END WHERE id = ?4",
+        params![
+            if fully_received { "received" } else { "partially_received" },
+            fully_received,
+            get_current_timestamp(),
+            purchase_order_id
+        ],
+    )?;
+    tx.commit()?;
+
+    load_order(&conn, purchase_order_id)
+}
+
+/// Cancel an order nothing has been received against yet.
+#[command]
+pub fn cancel_purchase_order(purchase_order_id: i64) -> Result<PurchaseOrder, AppError> {
+    let conn = get_db_connection()?;
+    let order = load_order(&conn, purchase_order_id)?;
+    if order.status != "ordered" {
+        return Err(format!("Purchase order #{} is {} and can't be cancelled", purchase_order_id, order.status).into());
+    }
+    conn.execute("UPDATE purchase_orders SET status = 'cancelled' WHERE id = ?1", params![purchase_order_id])?;
+    load_order(&conn, purchase_order_id)
+}
+
+/// Pay a supplier, optionally against one purchase order. The payment is
+/// also booked as an expense on `paid_on` so it counts in the expense totals.
+#[command]
+pub fn record_supplier_payment(
+    supplier_id: i64,
+    amount: f64,
+    method: String,
+    paid_on: String,
+    purchase_order_id: Option<i64>,
+    note: Option<String>,
+    session_token: Option<String>,
+) -> Result<SupplierPayment, AppError> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "amount", "Amount must be positive"));
+    }
+    let method = crate::simple_commands::parse_payment_method(&method)?;
+    validate_date_format(&paid_on)?;
+    let note = trimmed(note);
+
+    let conn = get_db_connection()?;
+    let supplier = load_supplier(&conn, supplier_id)?;
+    if let Some(po_id) = purchase_order_id {
+        let order = load_order(&conn, po_id)?;
+        if order.supplier_id != supplier_id {
+            return Err(format!("Purchase order #{} is not from {}", po_id, supplier.name).into());
+        }
+    }
+
+    let actor = audit_actor(session_token.as_deref());
+    let description = match purchase_order_id {
+        Some(po_id) => format!("{} (PO #{})", supplier.name, po_id),
+        None => supplier.name.clone(),
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    // Put the category back if it was deleted while unused
+    tx.execute("INSERT OR IGNORE INTO expense_categories (name) VALUES (?1)", params![SUPPLIER_EXPENSE_CATEGORY])?;
+    tx.execute(
+        "INSERT INTO expenses (date, category, description, amount) VALUES (?1, ?2, ?3, ?4)",
+        params![paid_on, SUPPLIER_EXPENSE_CATEGORY, description, amount],
+    )?;
+    let expense_id = tx.last_insert_rowid();
+    record_change(&tx, &actor, "expense", expense_id, "create", None, snapshot(&tx, "expense", expense_id)?)?;
+
+    tx.execute(
+        "INSERT INTO supplier_payments (supplier_id, purchase_order_id, amount, method, paid_on, note, expense_id, created_by, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![supplier_id, purchase_order_id, amount, method, paid_on, note, expense_id, actor, get_current_timestamp()],
+    )?;
+    let payment_id = tx.last_insert_rowid();
+    tx.commit()?;
+
+    Ok(SupplierPayment {
+        id: payment_id,
+        supplier_id,
+        purchase_order_id,
+        amount,
+        method,
+        paid_on,
+        note,
+        expense_id: Some(expense_id),
+        created_by: Some(actor),
+    })
+}
+
+/// Payments to one supplier, newest first.
+#[command]
+pub fn get_supplier_payments(supplier_id: i64) -> Result<Vec<SupplierPayment>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, supplier_id, purchase_order_id, amount, method, paid_on, note, expense_id, created_by
+         FROM supplier_payments WHERE supplier_id = ?1
+         ORDER BY paid_on DESC, id DESC",
+    )?;
+    let payments = stmt
+        .query_map(params![supplier_id], |row| {
+            Ok(SupplierPayment {
+                id: row.get(0)?,
+                supplier_id: row.get(1)?,
+                purchase_order_id: row.get(2)?,
+                amount: row.get(3)?,
+                method: row.get(4)?,
+                paid_on: row.get(5)?,
+                note: row.get(6)?,
+                expense_id: row.get(7)?,
+                created_by: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(payments)
+}
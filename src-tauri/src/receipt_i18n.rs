@@ -0,0 +1,260 @@
+use tauri::command;
+use rusqlite::Connection;
+use serde_json::{json, Map, Value};
+use crate::db::get_db_connection;
+use crate::errors::AppError;
+use crate::models::ReceiptLanguageInfo;
+use crate::repository::{get_non_empty_setting, set_or_clear_setting};
+
+struct ReceiptLanguage {
+    code: &'static str,
+    name: &'static str,
+    rtl: bool,
+    // English label -> translation; labels missing here print in English
+    strings: &'static [(&'static str, &'static str)],
+}
+
+const URDU: &[(&str, &str)] = &[
+    ("Food Order Receipt", "کھانے کے آرڈر کی رسید"),
+    ("Final Invoice", "حتمی بل"),
+    ("Order #", "آرڈر نمبر"),
+    ("Date", "تاریخ"),
+    ("Customer", "گاہک"),
+    ("Room", "کمرہ"),
+    ("Payment Status", "ادائیگی کی صورتحال"),
+    ("Item", "آئٹم"),
+    ("Qty", "تعداد"),
+    ("Unit Price", "فی یونٹ قیمت"),
+    ("Total", "کل"),
+    ("Grand Total", "کل رقم"),
+    ("Subtotal", "ذیلی کل"),
+    ("Check-in", "آمد"),
+    ("Check-out", "روانگی"),
+    ("ROOM CHARGES", "کمرے کے اخراجات"),
+    ("Room Charges", "کمرے کے اخراجات"),
+    ("FOOD ORDERS", "کھانے کے آرڈر"),
+    ("Food Orders", "کھانے کے آرڈر"),
+    ("EXTRA CHARGES", "اضافی اخراجات"),
+    ("Extra Charges", "اضافی اخراجات"),
+    ("ADJUSTMENTS", "ردوبدل"),
+    ("Adjustments", "ردوبدل"),
+    ("Description", "تفصیل"),
+    ("Days", "دن"),
+    ("Rate", "نرخ"),
+    ("Accommodation", "رہائش"),
+    ("Service charge", "سروس چارج"),
+    ("Service charge on order", "آرڈر پر سروس چارج"),
+    ("Paid on order", "آرڈر پر ادا شدہ"),
+    ("No food orders", "کوئی کھانے کا آرڈر نہیں"),
+    ("Includes", "شامل"),
+    ("Discount", "رعایت"),
+    ("Rounding", "راؤنڈنگ"),
+    ("Deposit paid", "جمع شدہ پیشگی"),
+    ("Balance due", "واجب الادا رقم"),
+    ("PAID", "ادا شدہ"),
+    ("UNPAID", "غیر ادا شدہ"),
+    ("PART PAID", "جزوی ادا شدہ"),
+    ("due", "باقی"),
+    ("VOID", "منسوخ"),
+    ("Note", "نوٹ"),
+    ("NOTE", "نوٹ"),
+    ("Walk-in Customer", "عام گاہک"),
+    ("Walk-in", "عام"),
+    ("Guest", "مہمان"),
+    ("N/A", "دستیاب نہیں"),
+    ("CHECKOUT HOLD", "روانگی روک"),
+    ("resolve before checkout", "روانگی سے پہلے حل کریں"),
+    ("est.", "تخمینہ"),
+    ("reported by", "رپورٹ کنندہ"),
+    ("Signed at check-in", "آمد پر دستخط شدہ"),
+    ("Scan to pay", "ادائیگی کے لیے اسکین کریں"),
+    ("Scan for a digital copy", "ڈیجیٹل کاپی کے لیے اسکین کریں"),
+    ("Fiscal invoice no.", "مالیاتی انوائس نمبر"),
+    ("Voucher", "واؤچر"),
+    ("PAID BY: CASH", "ادائیگی: نقد"),
+    ("Only unpaid food orders are included in the total amount.", "کل رقم میں صرف غیر ادا شدہ کھانے کے آرڈر شامل ہیں۔"),
+    ("Paid orders are shown with [PAID] status and crossed out for reference only.", "ادا شدہ آرڈر صرف حوالے کے لیے کاٹ کر دکھائے گئے ہیں۔"),
+    ("Thank you for dining with us!", "ہمارے ساتھ کھانا کھانے کا شکریہ!"),
+    ("Thank you for your stay!", "قیام کا شکریہ!"),
+    ("Receipt generated on", "رسید تیار کی گئی"),
+    ("Invoice generated on", "بل تیار کیا گیا"),
+    ("at", "بوقت"),
+];
+
+const ARABIC: &[(&str, &str)] = &[
+    ("Food Order Receipt", "إيصال طلب طعام"),
+    ("Final Invoice", "الفاتورة النهائية"),
+    ("Order #", "رقم الطلب"),
+    ("Date", "التاريخ"),
+    ("Customer", "العميل"),
+    ("Room", "الغرفة"),
+    ("Payment Status", "حالة الدفع"),
+    ("Item", "الصنف"),
+    ("Qty", "الكمية"),
+    ("Unit Price", "سعر الوحدة"),
+    ("Total", "المجموع"),
+    ("Grand Total", "الإجمالي"),
+    ("Subtotal", "المجموع الفرعي"),
+    ("Check-in", "تسجيل الوصول"),
+    ("Check-out", "تسجيل المغادرة"),
+    ("ROOM CHARGES", "رسوم الغرفة"),
+    ("Room Charges", "رسوم الغرفة"),
+    ("FOOD ORDERS", "طلبات الطعام"),
+    ("Food Orders", "طلبات الطعام"),
+    ("EXTRA CHARGES", "رسوم إضافية"),
+    ("Extra Charges", "رسوم إضافية"),
+    ("ADJUSTMENTS", "التسويات"),
+    ("Adjustments", "التسويات"),
+    ("Description", "الوصف"),
+    ("Days", "الأيام"),
+    ("Rate", "السعر"),
+    ("Accommodation", "الإقامة"),
+    ("Service charge", "رسوم الخدمة"),
+    ("Service charge on order", "رسوم الخدمة على الطلب"),
+    ("Paid on order", "المدفوع على الطلب"),
+    ("No food orders", "لا توجد طلبات طعام"),
+    ("Includes", "يشمل"),
+    ("Discount", "الخصم"),
+    ("Rounding", "التقريب"),
+    ("Deposit paid", "العربون المدفوع"),
+    ("Balance due", "الرصيد المستحق"),
+    ("PAID", "مدفوع"),
+    ("UNPAID", "غير مدفوع"),
+    ("PART PAID", "مدفوع جزئياً"),
+    ("due", "مستحق"),
+    ("VOID", "ملغى"),
+    ("Note", "ملاحظة"),
+    ("NOTE", "ملاحظة"),
+    ("Walk-in Customer", "عميل مباشر"),
+    ("Walk-in", "مباشر"),
+    ("Guest", "ضيف"),
+    ("N/A", "غير متوفر"),
+    ("CHECKOUT HOLD", "تعليق المغادرة"),
+    ("resolve before checkout", "يجب حله قبل المغادرة"),
+    ("est.", "تقديري"),
+    ("reported by", "أبلغ عنه"),
+    ("Signed at check-in", "تم التوقيع عند الوصول"),
+    ("Scan to pay", "امسح للدفع"),
+    ("Scan for a digital copy", "امسح للحصول على نسخة رقمية"),
+    ("Fiscal invoice no.", "رقم الفاتورة الضريبية"),
+    ("Voucher", "قسيمة"),
+    ("PAID BY: CASH", "طريقة الدفع: نقداً"),
+    ("Only unpaid food orders are included in the total amount.", "يشمل المبلغ الإجمالي طلبات الطعام غير المدفوعة فقط."),
+    ("Paid orders are shown with [PAID] status and crossed out for reference only.", "تظهر الطلبات المدفوعة مشطوبة للرجوع إليها فقط."),
+    ("Thank you for dining with us!", "شكراً لتناولكم الطعام معنا!"),
+    ("Thank you for your stay!", "شكراً لإقامتكم!"),
+    ("Receipt generated on", "تم إصدار الإيصال في"),
+    ("Invoice generated on", "تم إصدار الفاتورة في"),
+    ("at", "الساعة"),
+];
+
+const LANGUAGES: &[ReceiptLanguage] = &[
+    ReceiptLanguage { code: "en", name: "English", rtl: false, strings: &[] },
+    ReceiptLanguage { code: "ur", name: "اردو (Urdu)", rtl: true, strings: URDU },
+    ReceiptLanguage { code: "ar", name: "العربية (Arabic)", rtl: true, strings: ARABIC },
+];
+
+/// The receipt language for a locale such as "ur-PK" or "ar", by its
+/// language part. None for languages receipts aren't translated into.
+fn language_for(locale: &str) -> Option<&'static ReceiptLanguage> {
+    let code = locale.trim().split(['-', '_']).next()?.to_lowercase();
+    LANGUAGES.iter().find(|l| l.code == code)
+}
+
+/// Printed labels in one language.
+pub struct ReceiptLabels(&'static ReceiptLanguage);
+
+impl ReceiptLabels {
+    /// `label` in this language, or as given when there's no translation.
+    pub fn t<'a>(&self, label: &'a str) -> &'a str {
+        self.0.strings.iter().find(|(en, _)| *en == label).map_or(label, |(_, translated)| *translated)
+    }
+
+    /// Adds `lang`, `dir` and the `i18n` map the templates' `t` helper reads.
+    pub fn apply_to(&self, context: &mut Value) {
+        if let Some(context) = context.as_object_mut() {
+            let strings: Map<String, Value> =
+                self.0.strings.iter().map(|(en, translated)| (en.to_string(), json!(translated))).collect();
+            context.insert("lang".to_string(), json!(self.0.code));
+            context.insert("dir".to_string(), json!(if self.0.rtl { "rtl" } else { "ltr" }));
+            context.insert("i18n".to_string(), Value::Object(strings));
+        }
+    }
+}
+
+/// Labels for a printout: the locale asked for on the print call, else the
+/// receipt language setting, else the app locale. Anything without a
+/// translation prints in English.
+pub fn receipt_labels(conn: &Connection, locale: Option<&str>) -> Result<ReceiptLabels, String> {
+    let locale = match locale.filter(|l| !l.trim().is_empty()) {
+        Some(locale) => Some(locale.to_string()),
+        None => match get_non_empty_setting(conn, "receipt_locale")? {
+            Some(locale) => Some(locale),
+            None => get_non_empty_setting(conn, "locale")?,
+        },
+    };
+    Ok(ReceiptLabels(locale.as_deref().and_then(language_for).unwrap_or(&LANGUAGES[0])))
+}
+
+/// Languages receipts and invoices can be printed in.
+#[command]
+pub fn get_receipt_languages() -> Result<Vec<ReceiptLanguageInfo>, AppError> {
+    Ok(LANGUAGES
+        .iter()
+        .map(|l| ReceiptLanguageInfo { code: l.code.to_string(), name: l.name.to_string(), rtl: l.rtl })
+        .collect())
+}
+
+/// Print receipts and invoices in `locale` regardless of the app locale.
+/// Clearing it goes back to following `set_locale`.
+#[command]
+pub fn set_receipt_locale(locale: Option<String>) -> Result<Option<String>, AppError> {
+    let locale = locale.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+    if let Some(locale) = &locale {
+        if language_for(locale).is_none() {
+            let codes: Vec<&str> = LANGUAGES.iter().map(|l| l.code).collect();
+            return Err(format!("Receipts can be printed in: {}", codes.join(", ")).into());
+        }
+    }
+    let conn = get_db_connection()?;
+    set_or_clear_setting(&conn, "receipt_locale", locale.as_deref())?;
+    Ok(locale)
+}
+
+/// The receipt language setting; None when receipts follow the app locale.
+#[command]
+pub fn get_receipt_locale() -> Result<Option<String>, AppError> {
+    let conn = get_db_connection()?;
+    Ok(get_non_empty_setting(&conn, "receipt_locale")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::print_templates::build_order_receipt_html;
+    use crate::simple_commands::insert_food_order;
+    use crate::test_support::{order_item, TestDb};
+
+    fn coffee_order() -> i64 {
+        insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Coffee", 5.0, 2)], None, None, None).unwrap().0
+    }
+
+    #[test]
+    fn arabic_receipts_are_translated_and_right_to_left() {
+        let _db = TestDb::new();
+        let receipt = build_order_receipt_html(coffee_order(), Some("ar".to_string())).unwrap();
+        assert!(receipt.contains(r#"dir="rtl""#), "Arabic receipt is not right-to-left");
+        assert!(receipt.contains("الإجمالي"), "Arabic receipt is not translated");
+    }
+
+    #[test]
+    fn the_receipt_language_setting_applies_unless_the_print_asks_for_another() {
+        let _db = TestDb::new();
+        let order_id = coffee_order();
+        set_receipt_locale(Some("ur-PK".to_string())).unwrap();
+        assert!(build_order_receipt_html(order_id, None).unwrap().contains("کل رقم"), "receipt not printed in the receipt language");
+        let english = build_order_receipt_html(order_id, Some("en".to_string())).unwrap();
+        assert!(english.contains("Grand Total") && english.contains(r#"dir="ltr""#), "the language asked for was not used");
+        assert!(set_receipt_locale(Some("xx".to_string())).is_err(), "a language without translations was accepted");
+    }
+}
@@ -0,0 +1,166 @@
+use tauri::command;
+use std::collections::HashMap;
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use crate::db::{get_db_connection, run_blocking};
+use crate::errors::AppError;
+use crate::models::{AgingTotals, ReceivableItem, ReceivablesAgingReport};
+use crate::simple_commands::{business_date_today, to_cents};
+
+fn bucket_for(age_days: i64) -> &'static str {
+    match age_days {
+        i64::MIN..=30 => "0-30",
+        31..=60 => "31-60",
+        _ => "61+",
+    }
+}
+
+fn add_to_totals(totals: &mut AgingTotals, item: &ReceivableItem) {
+    match item.bucket.as_str() {
+        "0-30" => totals.days_0_30 += item.amount,
+        "31-60" => totals.days_31_60 += item.amount,
+        _ => totals.days_61_plus += item.amount,
+    }
+    totals.total += item.amount;
+}
+
+fn receivable(source: &str, reference_id: i64, name: String, date: String, amount: f64, today: NaiveDate) -> ReceivableItem {
+    let age_days = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .map(|d| (today - d).num_days().max(0))
+        .unwrap_or(0);
+    ReceivableItem {
+        source: source.to_string(),
+        reference_id,
+        name,
+        date,
+        age_days,
+        bucket: bucket_for(age_days).to_string(),
+        amount: to_cents(amount) as f64 / 100.0,
+    }
+}
+
+/// Orders with money still owed, except those of guests who have checked
+/// out, since checkout bills a guest's unpaid orders.
+fn unpaid_orders(conn: &Connection, today: NaiveDate) -> Result<Vec<ReceivableItem>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT s.id, COALESCE(c.name, s.customer_name, 'Walk-in'), date(s.created_at), s.total_amount - s.amount_paid
+         FROM sales s LEFT JOIN customers c ON s.guest_id = c.id
+         WHERE s.paid = 0 AND s.deleted_at IS NULL AND (c.id IS NULL OR c.status != 'checked_out')",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, f64>(3)?))
+    })?;
+    let mut items = Vec::new();
+    for row in rows {
+        let (order_id, name, date, owed) = row?;
+        if to_cents(owed) > 0 {
+            items.push(receivable("order", order_id, name, date, owed, today));
+        }
+    }
+    Ok(items)
+}
+
+/// Checkouts whose bill isn't covered by the guest's payments (deposits, the
+/// checkout settlement, less refunds) or a posting to a company account.
+fn unsettled_checkouts(conn: &Connection, today: NaiveDate) -> Result<Vec<ReceivableItem>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.name, c.check_out,
+                c.checkout_total
+                - (SELECT COALESCE(SUM(p.amount), 0) FROM payments p WHERE p.guest_id = c.id AND p.order_id IS NULL)
+                - (SELECT COALESCE(SUM(ch.amount), 0) FROM company_charges ch WHERE ch.guest_id = c.id)
+         FROM customers c
+         WHERE c.status = 'checked_out' AND c.checkout_total IS NOT NULL AND c.check_out IS NOT NULL",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, f64>(3)?))
+    })?;
+    let mut items = Vec::new();
+    for row in rows {
+        let (guest_id, name, check_out, owed) = row?;
+        if to_cents(owed) > 0 {
+            items.push(receivable("checkout", guest_id, name, check_out, owed, today));
+        }
+    }
+    Ok(items)
+}
+
+/// Company balances split by charge (less any reversal of it), with payments
+/// set against the oldest charges first, so only what's genuinely still unpaid ages.
+fn company_balances(conn: &Connection, today: NaiveDate) -> Result<Vec<ReceivableItem>, AppError> {
+    let mut paid_by_company: HashMap<i64, i64> = HashMap::new();
+    {
+        let mut stmt = conn.prepare("SELECT company_id, SUM(amount) FROM company_payments GROUP BY company_id")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, f64>(1)?)))?;
+        for row in rows {
+            let (company_id, paid) = row?;
+            paid_by_company.insert(company_id, to_cents(paid));
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT ch.id, ch.company_id, co.name, ch.posted_on,
+                ch.amount + COALESCE((SELECT SUM(r.amount) FROM company_charges r WHERE r.reversal_of = ch.id), 0)
+         FROM company_charges ch JOIN companies co ON co.id = ch.company_id
+         WHERE ch.reversal_of IS NULL
+         ORDER BY ch.company_id, ch.posted_on, ch.id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, i64>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, f64>(4)?,
+        ))
+    })?;
+    let mut items = Vec::new();
+    for row in rows {
+        let (charge_id, company_id, name, posted_on, amount) = row?;
+        let unallocated = paid_by_company.entry(company_id).or_insert(0);
+        let applied = (*unallocated).min(to_cents(amount));
+        *unallocated -= applied;
+        let owed = to_cents(amount) - applied;
+        if owed > 0 {
+            items.push(receivable("company", charge_id, name, posted_on, owed as f64 / 100.0, today));
+        }
+    }
+    Ok(items)
+}
+
+/// Every receivable, oldest first.
+pub(crate) fn outstanding_items(conn: &Connection, today: NaiveDate) -> Result<Vec<ReceivableItem>, AppError> {
+    let mut items = unpaid_orders(conn, today)?;
+    items.extend(unsettled_checkouts(conn, today)?);
+    items.extend(company_balances(conn, today)?);
+    items.sort_by(|a, b| b.age_days.cmp(&a.age_days).then(a.source.cmp(&b.source)).then(a.reference_id.cmp(&b.reference_id)));
+    Ok(items)
+}
+
+/// Money owed to the business, aged by how long it has been owed: unpaid
+/// orders from the day they were placed, unsettled checkouts from the
+/// checkout date and company account charges from the day they were posted.
+#[command]
+pub async fn receivables_aging_report() -> Result<ReceivablesAgingReport, AppError> {
+    run_blocking(move || {
+        let as_of = business_date_today();
+        let today = NaiveDate::parse_from_str(&as_of, "%Y-%m-%d").map_err(|e| e.to_string())?;
+        let conn = get_db_connection()?;
+        let items = outstanding_items(&conn, today)?;
+
+        let mut orders = AgingTotals::default();
+        let mut checkouts = AgingTotals::default();
+        let mut companies = AgingTotals::default();
+        let mut total = AgingTotals::default();
+        for item in &items {
+            match item.source.as_str() {
+                "order" => add_to_totals(&mut orders, item),
+                "checkout" => add_to_totals(&mut checkouts, item),
+                _ => add_to_totals(&mut companies, item),
+            }
+            add_to_totals(&mut total, item);
+        }
+
+        Ok(ReceivablesAgingReport { as_of, items, orders, checkouts, companies, total })
+    })
+    .await
+}
@@ -0,0 +1,220 @@
+use tauri::command;
+use std::fs;
+use std::path::PathBuf;
+use rusqlite::{params, Connection, OptionalExtension};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::repository::{get_parsed_setting, set_setting};
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+const MAX_SIGNATURE_BYTES: usize = 512 * 1024;
+const DEFAULT_SIGNATURE_RETENTION_DAYS: i64 = 365;
+
+fn get_signatures_dir() -> Result<PathBuf, String> {
+    // Signatures live next to the logo assets under the media root.
+    crate::media::media_dir(&["media", "signatures"])
+}
+
+/// Decode a signature upload (raw base64 or a data URL) and check it is a PNG within the size cap.
+fn decode_signature_png(png_base64: &str) -> Result<Vec<u8>, String> {
+    let payload = png_base64.trim();
+    let payload = match payload.split_once(',') {
+        Some((prefix, data)) if prefix.starts_with("data:") => {
+            if !prefix.eq_ignore_ascii_case("data:image/png;base64") {
+                return Err("Signature must be a PNG image".to_string());
+            }
+            data
+        }
+        _ => payload,
+    };
+
+    // Reject oversized uploads before decoding them
+    if payload.len() > MAX_SIGNATURE_BYTES * 4 / 3 + 4 {
+        return Err(format!("Signature image exceeds the {} KB limit", MAX_SIGNATURE_BYTES / 1024));
+    }
+
+    let bytes = BASE64_STANDARD
+        .decode(payload)
+        .map_err(|_| "Signature is not valid base64 data".to_string())?;
+
+    if bytes.len() > MAX_SIGNATURE_BYTES {
+        return Err(format!("Signature image exceeds the {} KB limit", MAX_SIGNATURE_BYTES / 1024));
+    }
+    if !bytes.starts_with(PNG_SIGNATURE) {
+        return Err("Signature must be a PNG image".to_string());
+    }
+
+    Ok(bytes)
+}
+
+/// The stored signature for a stay as a data URL, if one was captured and the file can still be found.
+pub fn load_signature_data_url(conn: &Connection, guest_id: i64) -> Result<Option<String>, String> {
+    let path: Option<String> = conn
+        .query_row(
+            "SELECT signature_path FROM customers WHERE id = ?1",
+            params![guest_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+
+    Ok(path.and_then(|p| crate::media::media_data_url(conn, &p)))
+}
+
+#[command]
+pub fn attach_registration_signature(guest_id: i64, png_base64: String) -> Result<String, String> {
+    let bytes = decode_signature_png(&png_base64)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+
+    let previous: Option<Option<String>> = conn
+        .query_row(
+            "SELECT signature_path FROM customers WHERE id = ?1",
+            params![guest_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return Err("Guest not found".to_string()),
+    };
+
+    let dir = get_signatures_dir()?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create signatures directory: {}", e))?;
+
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let dest = dir.join(format!("guest_{}_{}.png", guest_id, timestamp));
+    fs::write(&dest, &bytes)
+        .map_err(|e| format!("Failed to store signature: {}", e))?;
+
+    let dest_str = dest.to_string_lossy().to_string();
+    conn.execute(
+        "UPDATE customers SET signature_path = ?1, terms_accepted_at = ?2 WHERE id = ?3",
+        params![dest_str, get_current_timestamp(), guest_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // A re-sign replaces the earlier capture
+    if let Some(old) = previous.filter(|p| p != &dest_str) {
+        let _ = fs::remove_file(old);
+    }
+
+    Ok(dest_str)
+}
+
+#[command]
+pub fn get_registration_signature(guest_id: i64) -> Result<Option<String>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    load_signature_data_url(&conn, guest_id)
+}
+
+#[command]
+pub fn set_signature_retention_days(days: i64) -> Result<String, String> {
+    if !(1..=3650).contains(&days) {
+        return Err("Retention period must be between 1 and 3650 days".to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    set_setting(&conn, "signature_retention_days", &days.to_string())?;
+
+    Ok(format!("Signatures will be kept for {} days after checkout", days))
+}
+
+/// Remove signature files for stays that ended longer ago than the retention period.
+/// The terms_accepted_at stamp is kept as the record that terms were accepted.
+#[command]
+pub fn prune_registration_signatures() -> Result<usize, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+
+    let retention_days = get_parsed_setting::<i64>(&conn, "signature_retention_days")?
+        .unwrap_or(DEFAULT_SIGNATURE_RETENTION_DAYS);
+
+    let cutoff = (chrono::Local::now().date_naive() - chrono::Duration::days(retention_days))
+        .format("%Y-%m-%d")
+        .to_string();
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, signature_path FROM customers
+             WHERE signature_path IS NOT NULL
+               AND status = 'checked_out'
+               AND check_out IS NOT NULL AND check_out < ?1",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let expired = stmt
+        .query_map(params![cutoff], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for (guest_id, path) in &expired {
+        let _ = fs::remove_file(path);
+        conn.execute(
+            "UPDATE customers SET signature_path = NULL WHERE id = ?1",
+            params![guest_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    Ok(expired.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::print_templates::{build_final_invoice_html, build_registration_card_html};
+    use crate::test_support::{add_test_room, check_in, count, TestDb};
+
+    const PNG_HEADER: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+    fn signature_png() -> String {
+        BASE64_STANDARD.encode([PNG_HEADER, b"test signature".as_slice()].concat())
+    }
+
+    fn guest() -> i64 {
+        check_in("Signing Guest", Some(add_test_room("ST-101")))
+    }
+
+    #[test]
+    fn only_png_signatures_up_to_the_size_limit_are_accepted() {
+        let _db = TestDb::new();
+        let guest_id = guest();
+        let gif = BASE64_STANDARD.encode(b"GIF89a test signature");
+        assert!(attach_registration_signature(guest_id, gif).is_err_and(|e| e.contains("must be a PNG")), "GIF signature accepted");
+        let labelled_jpeg = format!("data:image/jpeg;base64,{}", signature_png());
+        assert!(
+            attach_registration_signature(guest_id, labelled_jpeg).is_err_and(|e| e.contains("must be a PNG")),
+            "signature labelled as a JPEG accepted"
+        );
+        let oversized = BASE64_STANDARD.encode([PNG_HEADER, &vec![0u8; 600 * 1024]].concat());
+        assert!(
+            attach_registration_signature(guest_id, oversized).is_err_and(|e| e.contains("exceeds the 512 KB limit")),
+            "600 KB signature accepted"
+        );
+        assert_eq!(get_registration_signature(guest_id).unwrap(), None, "a rejected upload was stored");
+        let card = build_registration_card_html(guest_id).unwrap();
+        assert!(card.contains(r#"class="signature-line""#), "unsigned card has no signature line");
+    }
+
+    #[test]
+    fn a_stored_signature_stamps_the_terms_and_prints_on_the_card_and_invoice() {
+        let _db = TestDb::new();
+        let guest_id = guest();
+        let data_url = format!("data:image/png;base64,{}", signature_png());
+        attach_registration_signature(guest_id, data_url.clone()).unwrap();
+        assert_eq!(get_registration_signature(guest_id).unwrap(), Some(data_url.clone()), "stored signature not returned as it was captured");
+        let accepted = count("SELECT COUNT(*) FROM customers WHERE id = ?1 AND terms_accepted_at IS NOT NULL", guest_id);
+        assert_eq!(accepted, 1, "terms_accepted_at not stamped");
+        let card = build_registration_card_html(guest_id).unwrap();
+        assert!(
+            card.contains(&data_url) && card.contains("Signed at check-in") && !card.contains(r#"class="signature-line""#),
+            "reprinted card does not embed the stored signature"
+        );
+        let invoice = build_final_invoice_html(guest_id, None).unwrap();
+        assert!(invoice.contains(&data_url) && invoice.contains("Signed at check-in"), "invoice footer does not show the signature");
+    }
+}
@@ -0,0 +1,155 @@
+use tauri::command;
+use std::fs;
+use rusqlite::{params, Connection};
+use serde_json::json;
+use crate::db::{get_current_timestamp, get_db_connection, run_blocking};
+use crate::errors::AppError;
+use crate::export::escape_csv;
+use crate::print_templates::html_escape;
+use crate::repository::get_setting_or;
+use crate::tax_report::validate_period;
+
+/// One line of the guest register.
+struct RegisteredGuest {
+    name: String,
+    nationality: Option<String>,
+    id_number: Option<String>,
+    phone: Option<String>,
+    room: String,
+    arrival: String,
+    departure: Option<String>,
+    adults: i64,
+    children: i64,
+}
+
+impl RegisteredGuest {
+    /// The register is only complete once nationality and document are filled in.
+    fn is_missing_details(&self) -> bool {
+        self.nationality.is_none() || self.id_number.is_none()
+    }
+}
+
+/// Everyone who stayed in a room at some point between the two dates
+/// (inclusive), by arrival. Nationality and document number come from the
+/// guest's profile; walk-ins didn't stay overnight and aren't listed.
+fn registered_guests(conn: &Connection, start_date: &str, end_date: &str) -> Result<Vec<RegisteredGuest>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT c.name, NULLIF(TRIM(p.nationality), ''), NULLIF(TRIM(p.id_number), ''),
+                COALESCE(NULLIF(TRIM(c.phone), ''), p.phone), r.number, c.check_in, c.check_out, c.adults, c.children
+         FROM customers c
+         JOIN resources r ON r.id = c.room_id
+         LEFT JOIN guest_profiles p ON p.id = c.profile_id
+         WHERE c.check_in <= ?2 AND (c.check_out IS NULL OR c.check_out >= ?1)
+         ORDER BY c.check_in, c.id",
+    )?;
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok(RegisteredGuest {
+            name: row.get(0)?,
+            nationality: row.get(1)?,
+            id_number: row.get(2)?,
+            phone: row.get(3)?,
+            room: row.get(4)?,
+            arrival: row.get(5)?,
+            departure: row.get(6)?,
+            adults: row.get(7)?,
+            children: row.get(8)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<Vec<_>, _>>()?)
+}
+
+fn registration_csv(guests: &[RegisteredGuest], start_date: &str, end_date: &str, business_name: &str) -> String {
+    let mut out = format!("Guest register,{},{} to {}\n\n", escape_csv(business_name), start_date, end_date);
+    out.push_str("S.No,Name,Nationality,ID / passport no.,Phone,Room,Arrival,Departure,Adults,Children\n");
+    for (i, guest) in guests.iter().enumerate() {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            i + 1,
+            escape_csv(&guest.name),
+            escape_csv(guest.nationality.as_deref().unwrap_or("")),
+            escape_csv(guest.id_number.as_deref().unwrap_or("")),
+            escape_csv(guest.phone.as_deref().unwrap_or("")),
+            escape_csv(&guest.room),
+            guest.arrival,
+            guest.departure.as_deref().unwrap_or(""),
+            guest.adults,
+            guest.children
+        ));
+    }
+    out
+}
+
+/// The register rendered through the guest_registration print template, with
+/// guests missing nationality or document number highlighted.
+fn registration_html(conn: &Connection, guests: &[RegisteredGuest], start_date: &str, end_date: &str) -> Result<String, String> {
+    let guest_rows: String = if guests.is_empty() {
+        r#"<tr><td colspan="10" class="empty">No guests stayed in this period</td></tr>"#.to_string()
+    } else {
+        guests
+            .iter()
+            .enumerate()
+            .map(|(i, g)| {
+                format!(
+                    "<tr{}><td class=\"num\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                    if g.is_missing_details() { " class=\"missing\"" } else { "" },
+                    i + 1,
+                    html_escape(&g.name),
+                    html_escape(g.nationality.as_deref().unwrap_or("")),
+                    html_escape(g.id_number.as_deref().unwrap_or("")),
+                    html_escape(g.phone.as_deref().unwrap_or("")),
+                    html_escape(&g.room),
+                    g.arrival,
+                    g.departure.as_deref().unwrap_or(""),
+                    g.adults,
+                    g.children
+                )
+            })
+            .collect()
+    };
+
+    crate::templates::render(
+        "guest_registration",
+        &json!({
+            "business_name": get_setting_or(conn, "business_name", "Business Manager")?,
+            "business_contact": crate::print_templates::business_contact(conn)?,
+            "start_date": start_date,
+            "end_date": end_date,
+            "guest_count": guests.len(),
+            "missing_count": guests.iter().filter(|g| g.is_missing_details()).count(),
+            "guest_rows": guest_rows,
+            "generated_at": get_current_timestamp(),
+        }),
+    )
+}
+
+/// Save the register of guests who stayed between the two dates, in the
+/// format police and foreigners' registration offices ask hotels for, as
+/// "csv" or printable "html" in the exports folder, and return the file's path.
+#[command]
+pub async fn export_guest_registration_report(start_date: String, end_date: String, format: String) -> Result<String, AppError> {
+    validate_period(&start_date, &end_date)?;
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        let guests = registered_guests(&conn, &start_date, &end_date)?;
+        let stem = format!("guest_register_{}_{}", start_date, end_date);
+
+        let path = match format.trim().to_lowercase().as_str() {
+            "csv" => {
+                let path = crate::export_jobs::default_export_path(&stem, "csv")?;
+                let business_name = get_setting_or(&conn, "business_name", "Business Manager")?;
+                let csv = registration_csv(&guests, &start_date, &end_date, &business_name);
+                fs::write(&path, csv).map_err(|e| format!("Failed to write guest register: {}", e))?;
+                path
+            }
+            "html" => {
+                let path = crate::export_jobs::default_export_path(&stem, "html")?;
+                let html = registration_html(&conn, &guests, &start_date, &end_date)?;
+                fs::write(&path, html).map_err(|e| format!("Failed to write guest register: {}", e))?;
+                path
+            }
+            other => return Err(format!("Unknown format '{}'; use csv or html", other).into()),
+        };
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+}
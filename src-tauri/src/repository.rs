@@ -0,0 +1,48 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use std::str::FromStr;
+use crate::db::get_current_timestamp;
+
+// Typed queries shared by the command modules. Add a query here once a second
+// module needs it, rather than copying the SQL.
+
+/// A setting's saved value, or None if it was never saved.
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("Failed to read setting {}: {}", key, e))
+}
+
+/// A setting's value, or `default` if it was never saved.
+pub fn get_setting_or(conn: &Connection, key: &str, default: &str) -> Result<String, String> {
+    Ok(get_setting(conn, key)?.unwrap_or_else(|| default.to_string()))
+}
+
+/// A setting's value, treating a blank value the same as an unset one.
+pub fn get_non_empty_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    Ok(get_setting(conn, key)?.filter(|v| !v.trim().is_empty()))
+}
+
+/// A setting parsed as `T`; None when unset or not a valid `T`.
+pub fn get_parsed_setting<T: FromStr>(conn: &Connection, key: &str) -> Result<Option<T>, String> {
+    Ok(get_setting(conn, key)?.and_then(|v| v.trim().parse().ok()))
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)",
+        params![key, value, get_current_timestamp()],
+    )
+    .map_err(|e| format!("Failed to save setting {}: {}", key, e))?;
+    Ok(())
+}
+
+/// Save `value`, or delete the setting when it is None.
+pub fn set_or_clear_setting(conn: &Connection, key: &str, value: Option<&str>) -> Result<(), String> {
+    match value {
+        Some(value) => set_setting(conn, key, value),
+        None => conn
+            .execute("DELETE FROM settings WHERE key = ?1", params![key])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to clear setting {}: {}", key, e)),
+    }
+}
@@ -0,0 +1,172 @@
+use tauri::command;
+use chrono::NaiveDate;
+use rusqlite::{params, OptionalExtension, Transaction, TransactionBehavior};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::events;
+use crate::models::StaySegment;
+use crate::simple_commands::{business_date_today, overlapping_reservation};
+use crate::validation::{validate_date_format, GUEST_NOT_ACTIVE, GUEST_NOT_FOUND, ROOM_NOT_FOUND, ROOM_OCCUPIED, ROOM_RESERVED};
+
+/// Move an in-house guest to another room from `effective_date` (today, or
+/// back-dated to when they actually moved). Nights before that date stay
+/// billed at the old room's rate and nights from it at the new room's; the
+/// final invoice lists each room separately. Returns the stay's rooms so far.
+#[command]
+pub fn transfer_guest_room(
+    guest_id: i64,
+    new_room_id: i64,
+    effective_date: String,
+    session_token: Option<String>,
+) -> Result<Vec<StaySegment>, AppError> {
+    validate_date_format(&effective_date)?;
+    let conn = get_db_connection()?;
+
+    let (status, room_id, check_in, check_out, daily_rate): (String, Option<i64>, String, Option<String>, f64) = conn
+        .query_row(
+            "SELECT status, room_id, check_in, check_out, daily_rate FROM customers WHERE id = ?1",
+            params![guest_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::coded(GUEST_NOT_FOUND, format!("Guest #{} not found", guest_id)))?;
+    if status != "active" {
+        return Err(AppError::coded(GUEST_NOT_ACTIVE, "Only in-house guests can change rooms"));
+    }
+    let Some(room_id) = room_id else {
+        return Err("Walk-in customers have no room to transfer".into());
+    };
+    if room_id == new_room_id {
+        return Err("The guest is already in that room".into());
+    }
+
+    // Billing in the current room started at check-in or at the last transfer
+    let last_transfer: Option<String> = conn.query_row(
+        "SELECT MAX(effective_date) FROM room_transfers WHERE guest_id = ?1",
+        params![guest_id],
+        |row| row.get(0),
+    )?;
+    let current_since = last_transfer.unwrap_or_else(|| check_in.clone());
+    if effective_date < current_since {
+        return Err(format!("The transfer can't be dated before {}, when the guest moved into their current room", current_since).into());
+    }
+
+    // One business date for the whole transfer: the future-date check, how long
+    // the guest keeps the room and the nights priced in the returned segments
+    let today = business_date_today();
+    if effective_date > today {
+        return Err("A transfer can't be dated in the future".into());
+    }
+    let today = NaiveDate::parse_from_str(&today, "%Y-%m-%d").map_err(|_| "Invalid business date")?;
+
+    let actor = audit_actor(session_token.as_deref());
+    // Immediate, so two transfers into the same room can't both pass the checks below
+    let tx = Transaction::new_unchecked(&conn, TransactionBehavior::Immediate)?;
+    let new_rate: f64 = tx
+        .query_row(
+            "SELECT daily_rate FROM resources WHERE id = ?1 AND is_active = 1",
+            params![new_room_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::coded(ROOM_NOT_FOUND, "Room not found or inactive"))?;
+    let occupied: bool = tx.query_row("SELECT is_occupied FROM resources WHERE id = ?1", params![new_room_id], |row| row.get(0))?;
+    if occupied {
+        return Err(AppError::coded(ROOM_OCCUPIED, "Room is already occupied"));
+    }
+    crate::maintenance::ensure_room_in_service(&tx, new_room_id)?;
+
+    // The guest keeps the new room until their check-out, and at least for
+    // tonight when the stay is open-ended or running over
+    let tomorrow = (today + chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let staying_until = check_out.unwrap_or_default().max(tomorrow);
+    if let Some((id, name, from, to)) = overlapping_reservation(&tx, new_room_id, &effective_date, &staying_until, None)? {
+        return Err(format!(
+            "{}: reservation #{} for {} holds the room from {} to {}",
+            ROOM_RESERVED, id, name, from, to
+        ).into());
+    }
+
+    let before = snapshot(&tx, "guest", guest_id)?;
+    tx.execute(
+        "INSERT INTO room_transfers (guest_id, from_room_id, to_room_id, from_rate, to_rate, effective_date, created_by)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![guest_id, room_id, new_room_id, daily_rate, new_rate, effective_date, actor],
+    )?;
+    tx.execute(
+        "UPDATE customers SET room_id = ?1, daily_rate = ?2, updated_at = ?3 WHERE id = ?4",
+        params![new_room_id, new_rate, get_current_timestamp(), guest_id],
+    )?;
+    tx.execute("UPDATE resources SET is_occupied = 0, guest_id = NULL WHERE id = ?1", params![room_id])?;
+    tx.execute("UPDATE resources SET is_occupied = 1, guest_id = ?1 WHERE id = ?2", params![guest_id, new_room_id])?;
+    record_change(&tx, &actor, "guest", guest_id, "update", before, snapshot(&tx, "guest", guest_id)?)?;
+    tx.commit()?;
+
+    events::guest_changed(Some(guest_id), "room_transferred");
+    events::room_status_changed(Some(room_id), "vacated");
+    events::room_status_changed(Some(new_room_id), "occupied");
+
+    // Priced up to the business date, so the nights match the reservation check above
+    let check_in_date = NaiveDate::parse_from_str(&check_in, "%Y-%m-%d").map_err(|_| "Invalid check-in date format")?;
+    let stay_days = (today - check_in_date).num_days().max(1);
+    Ok(crate::pricing::guest_room_segments(&conn, guest_id, stay_days)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{add_guest, add_reservation, add_room, checkout_guest, get_rooms};
+    use crate::test_support::{add_test_room, days_from_today, same_amount, today, TestDb, TEST_RATE};
+
+    /// A guest who checked into ST-301 (at TEST_RATE) two nights ago, and
+    /// ST-302 (at 150) to move to; returns the guest and both room ids.
+    fn guest_to_move() -> (i64, i64, i64) {
+        let first = add_test_room("ST-301");
+        add_room("ST-302".to_string(), "Standard".to_string(), 150.0, None, None, None).unwrap();
+        let second = get_rooms(None, None).unwrap().into_iter().find(|r| r.number == "ST-302").expect("room ST-302 not listed").id;
+        let guest_id = add_guest(
+            "Transfer Guest".to_string(), None, Some(first), days_from_today(-2), None, TEST_RATE,
+            Some(1), Some(0), None, Some(true), None, None,
+        )
+        .unwrap();
+        (guest_id, first, second)
+    }
+
+    #[test]
+    fn a_guest_cannot_move_into_a_room_reserved_for_tonight() {
+        let _db = TestDb::new();
+        let (guest_id, _, second) = guest_to_move();
+        add_reservation(second, "Booked Guest".to_string(), None, today(), days_from_today(1), None, None, None, None, None).unwrap();
+        let blocked = transfer_guest_room(guest_id, second, days_from_today(-1), None);
+        assert!(blocked.is_err_and(|e| e.to_string().contains(ROOM_RESERVED)), "guest was moved into a room reserved for tonight");
+    }
+
+    #[test]
+    fn a_transfer_frees_the_old_room_and_bills_each_at_its_rate() {
+        let _db = TestDb::new();
+        let (guest_id, first, second) = guest_to_move();
+        let segments = transfer_guest_room(guest_id, second, days_from_today(-1), None).unwrap();
+        assert_eq!(segments.len(), 2, "transfer did not leave two room segments");
+        assert_eq!(segments[0].nights, 1, "wrong nights in the old room");
+        assert!(same_amount(segments[0].total, TEST_RATE), "old room billed {:.2}", segments[0].total);
+        let rooms = get_rooms(None, None).unwrap();
+        assert!(rooms.iter().any(|r| r.id == first && !r.is_occupied), "old room was not freed");
+        assert!(rooms.iter().any(|r| r.id == second && r.is_occupied), "new room is not occupied");
+
+        // One night in the first room, then the rest of the stay in the second
+        let totals = checkout_guest(guest_id, None, None, Some("cash".to_string()), None, None).unwrap();
+        let expected = TEST_RATE + 150.0 * (totals.stay_days - 1) as f64;
+        assert!(same_amount(totals.room_total, expected), "room total {:.2}, expected {:.2}", totals.room_total, expected);
+        let html = crate::print_templates::build_final_invoice_html(guest_id, None).unwrap();
+        assert!(html.contains("ST-301") && html.contains("ST-302"), "final invoice does not list both rooms");
+    }
+
+    #[test]
+    fn a_transfer_cannot_be_dated_before_the_last_move() {
+        let _db = TestDb::new();
+        let (guest_id, first, second) = guest_to_move();
+        transfer_guest_room(guest_id, second, days_from_today(-1), None).unwrap();
+        assert!(transfer_guest_room(guest_id, first, days_from_today(-2), None).is_err(), "a transfer was dated before the guest's last move");
+    }
+}
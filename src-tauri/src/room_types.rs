@@ -0,0 +1,267 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::audit_actor;
+use crate::db::{get_current_timestamp, get_db_connection, log_audit_event};
+use crate::errors::AppError;
+use crate::models::{RoomType, RoomTypeInput};
+use crate::validation::{EMPTY_FIELD, NEGATIVE_AMOUNT, ROOM_OVER_CAPACITY, ROOM_TYPE_EXISTS, ROOM_TYPE_IN_USE, ROOM_TYPE_NOT_FOUND};
+
+const ROOM_TYPE_NAME_MAX_CHARS: usize = 50;
+
+const ROOM_TYPE_COLUMNS: &str = "t.id, t.name, t.base_rate, t.max_occupancy, t.amenities,
+    (SELECT COUNT(*) FROM resources r WHERE r.is_active = 1 AND r.room_type = t.name COLLATE NOCASE),
+    (SELECT COUNT(*) FROM resources r WHERE r.is_active = 1 AND r.is_occupied = 1 AND r.room_type = t.name COLLATE NOCASE)";
+
+fn map_room_type(row: &rusqlite::Row) -> rusqlite::Result<RoomType> {
+    let amenities: String = row.get(4)?;
+    Ok(RoomType {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        base_rate: row.get(2)?,
+        max_occupancy: row.get(3)?,
+        amenities: serde_json::from_str(&amenities).unwrap_or_default(),
+        room_count: row.get(5)?,
+        occupied_rooms: row.get(6)?,
+    })
+}
+
+fn load_room_type(conn: &Connection, room_type_id: i64) -> Result<RoomType, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM room_types t WHERE t.id = ?1", ROOM_TYPE_COLUMNS),
+        params![room_type_id],
+        map_room_type,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::coded(ROOM_TYPE_NOT_FOUND, format!("Room type #{} not found", room_type_id)))
+}
+
+fn find_by_name(conn: &Connection, name: &str) -> Result<Option<(i64, String)>, AppError> {
+    Ok(conn
+        .query_row(
+            "SELECT id, name FROM room_types WHERE name = ?1 COLLATE NOCASE",
+            params![name.trim()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?)
+}
+
+/// Trimmed name and amenities, with blank and repeated amenities dropped.
+fn validate_input(input: RoomTypeInput) -> Result<(String, f64, Option<i64>, String), AppError> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Room type name cannot be empty"));
+    }
+    if name.chars().count() > ROOM_TYPE_NAME_MAX_CHARS {
+        return Err(format!("Room type name must be at most {} characters", ROOM_TYPE_NAME_MAX_CHARS).into());
+    }
+    if !input.base_rate.is_finite() || input.base_rate < 0.0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "base_rate", "Base rate must be >= 0"));
+    }
+    if input.max_occupancy.is_some_and(|max| max < 1) {
+        return Err("Maximum occupancy must be at least 1".into());
+    }
+    let mut amenities: Vec<String> = Vec::new();
+    for amenity in input.amenities.iter().map(|a| a.trim()).filter(|a| !a.is_empty()) {
+        if !amenities.iter().any(|a| a.eq_ignore_ascii_case(amenity)) {
+            amenities.push(amenity.to_string());
+        }
+    }
+    let amenities = serde_json::to_string(&amenities).map_err(|e| e.to_string())?;
+    Ok((name.to_string(), input.base_rate, input.max_occupancy, amenities))
+}
+
+/// The managed spelling of `name`, matched regardless of case. Rooms can only
+/// be given a type that exists.
+pub fn resolve_room_type(conn: &Connection, name: &str) -> Result<String, AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "room_type", "Room type cannot be empty"));
+    }
+    find_by_name(conn, name)?.map(|(_, stored)| stored).ok_or_else(|| {
+        AppError::invalid(
+            ROOM_TYPE_NOT_FOUND,
+            "room_type",
+            format!("Unknown room type '{}'. Add it under room types first", name.trim()),
+        )
+    })
+}
+
+/// Refuse a stay of `persons` in a room whose type sets a lower maximum occupancy.
+pub fn check_room_capacity(conn: &Connection, room_id: i64, persons: i64) -> Result<(), AppError> {
+    let limit: Option<(String, i64)> = conn
+        .query_row(
+            "SELECT t.name, t.max_occupancy FROM resources r
+             JOIN room_types t ON t.name = r.room_type COLLATE NOCASE
+             WHERE r.id = ?1 AND t.max_occupancy IS NOT NULL",
+            params![room_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    match limit {
+        Some((name, max)) if persons > max => Err(AppError::coded(
+            ROOM_OVER_CAPACITY,
+            format!("A {} room sleeps at most {}; this stay is for {}", name, max, persons),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Room types with how many rooms of each there are and how many are occupied.
+#[command]
+pub fn get_room_types() -> Result<Vec<RoomType>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM room_types t ORDER BY t.name COLLATE NOCASE", ROOM_TYPE_COLUMNS))?;
+    let room_types = stmt.query_map([], map_room_type)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(room_types)
+}
+
+#[command]
+pub fn add_room_type(room_type: RoomTypeInput) -> Result<RoomType, AppError> {
+    let (name, base_rate, max_occupancy, amenities) = validate_input(room_type)?;
+    let conn = get_db_connection()?;
+    if let Some((_, existing)) = find_by_name(&conn, &name)? {
+        return Err(AppError::invalid(ROOM_TYPE_EXISTS, "name", format!("Room type '{}' already exists", existing)));
+    }
+    conn.execute(
+        "INSERT INTO room_types (name, base_rate, max_occupancy, amenities) VALUES (?1, ?2, ?3, ?4)",
+        params![name, base_rate, max_occupancy, amenities],
+    )?;
+    load_room_type(&conn, conn.last_insert_rowid())
+}
+
+/// Change a room type. A new name carries over to its rooms and to the rate
+/// plans, overrides and rate bounds set for it. Existing room rates are left
+/// as they are; the base rate only prices rooms added later.
+#[command]
+pub fn update_room_type(room_type_id: i64, room_type: RoomTypeInput, session_token: Option<String>) -> Result<RoomType, AppError> {
+    let (name, base_rate, max_occupancy, amenities) = validate_input(room_type)?;
+    let conn = get_db_connection()?;
+    let current = load_room_type(&conn, room_type_id)?;
+    if let Some((other_id, existing)) = find_by_name(&conn, &name)? {
+        if other_id != room_type_id {
+            return Err(AppError::invalid(ROOM_TYPE_EXISTS, "name", format!("Room type '{}' already exists", existing)));
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE room_types SET name = ?1, base_rate = ?2, max_occupancy = ?3, amenities = ?4, updated_at = ?5 WHERE id = ?6",
+        params![name, base_rate, max_occupancy, amenities, get_current_timestamp(), room_type_id],
+    )?;
+    if name != current.name {
+        for table in ["resources", "rate_plans", "rate_overrides", "room_type_rate_bounds"] {
+            tx.execute(
+                &format!("UPDATE {} SET room_type = ?1 WHERE room_type = ?2 COLLATE NOCASE", table),
+                params![name, current.name],
+            )?;
+        }
+        log_audit_event(
+            &tx,
+            &audit_actor(session_token.as_deref()),
+            "room_type_renamed",
+            &format!("'{}' renamed to '{}' ({} rooms updated)", current.name, name, current.room_count),
+        )?;
+    }
+    tx.commit()?;
+    if name != current.name {
+        crate::events::room_status_changed(None, "updated");
+    }
+    load_room_type(&conn, room_type_id)
+}
+
+/// Delete a room type no room uses, along with its rate plans and bounds.
+#[command]
+pub fn delete_room_type(room_type_id: i64) -> Result<(), AppError> {
+    let conn = get_db_connection()?;
+    let room_type = load_room_type(&conn, room_type_id)?;
+    if room_type.room_count > 0 {
+        return Err(AppError::coded(
+            ROOM_TYPE_IN_USE,
+            format!("'{}' has {} rooms; give them another type first", room_type.name, room_type.room_count),
+        ));
+    }
+    let tx = conn.unchecked_transaction()?;
+    for table in ["rate_plans", "rate_overrides", "room_type_rate_bounds"] {
+        tx.execute(&format!("DELETE FROM {} WHERE room_type = ?1 COLLATE NOCASE", table), params![room_type.name])?;
+    }
+    tx.execute("DELETE FROM room_types WHERE id = ?1", params![room_type_id])?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{add_guest, add_room, get_rooms};
+    use crate::test_support::{room_type, today, TestDb, TEST_RATE};
+
+    fn add_twin() -> RoomType {
+        add_room_type(RoomTypeInput {
+            amenities: vec!["Wi-Fi".to_string(), " wi-fi ".to_string(), "Balcony".to_string()],
+            ..room_type("Twin test", Some(2))
+        })
+        .unwrap()
+    }
+
+    /// Add room ST-701 of the given type and return its id.
+    fn add_twin_room(type_name: &str) -> i64 {
+        add_room("ST-701".to_string(), type_name.to_string(), TEST_RATE, None, None, None).unwrap();
+        get_rooms(None, None).unwrap().into_iter().find(|r| r.number == "ST-701").expect("room ST-701 not listed").id
+    }
+
+    #[test]
+    fn amenities_are_saved_once_each() {
+        let _db = TestDb::new();
+        assert_eq!(add_twin().amenities, ["Wi-Fi", "Balcony"], "duplicate amenity kept");
+    }
+
+    #[test]
+    fn room_type_names_are_unique_whatever_their_case() {
+        let _db = TestDb::new();
+        add_twin();
+        assert!(add_room_type(room_type("twin TEST", None)).is_err(), "a room type was added twice");
+    }
+
+    #[test]
+    fn rooms_take_the_stored_name_of_a_known_type() {
+        let _db = TestDb::new();
+        add_twin();
+        let unknown = add_room("ST-701".to_string(), "No such type".to_string(), TEST_RATE, None, None, None);
+        assert!(unknown.is_err(), "a room was given an unknown type");
+        let room_id = add_twin_room("twin test");
+        let room = get_rooms(None, None).unwrap().into_iter().find(|r| r.id == room_id).unwrap();
+        assert_eq!(room.room_type, "Twin test", "room type not stored as defined");
+    }
+
+    #[test]
+    fn check_in_is_refused_beyond_the_types_occupancy() {
+        let _db = TestDb::new();
+        add_twin();
+        let room_id = add_twin_room("Twin test");
+        let crowded = add_guest(
+            "Crowded Guest".to_string(), None, Some(room_id), today(), None, TEST_RATE,
+            Some(2), Some(1), None, None, None, None,
+        );
+        assert!(crowded.is_err(), "three people were checked into a room for two");
+    }
+
+    #[test]
+    fn renaming_a_type_carries_over_to_its_rooms() {
+        let _db = TestDb::new();
+        let twin = add_twin();
+        let room_id = add_twin_room("Twin test");
+        let renamed = update_room_type(twin.id, room_type("Twin room test", Some(3)), None).unwrap();
+        assert_eq!(renamed.room_count, 1, "renamed type lost its room");
+        let room = get_rooms(None, None).unwrap().into_iter().find(|r| r.id == room_id).unwrap();
+        assert_eq!(room.room_type, "Twin room test", "rename did not carry over to the room");
+    }
+
+    #[test]
+    fn a_type_in_use_cannot_be_deleted() {
+        let _db = TestDb::new();
+        let twin = add_twin();
+        add_twin_room("Twin test");
+        assert!(delete_room_type(twin.id).is_err(), "a room type in use was deleted");
+        let unused = add_room_type(room_type("Spare test", None)).unwrap();
+        delete_room_type(unused.id).unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+use tauri::command;
+use rusqlite::{params, Connection};
+use crate::db::get_db_connection;
+use crate::errors::AppError;
+use crate::simple_commands::to_cents;
+use crate::repository::{get_setting, set_setting};
+
+/// Rounding rules for grand totals and the step each rounds to, in cents.
+const ROUNDING_RULES: &[(&str, i64)] = &[("none", 0), ("0.05", 5), ("1", 100), ("5", 500), ("10", 1000)];
+
+fn parse_rule(rule: &str) -> Option<(&'static str, i64)> {
+    ROUNDING_RULES.iter().copied().find(|(name, _)| *name == rule)
+}
+
+/// The rounding rule from the settings; "none" until one is chosen.
+pub fn rounding_rule(conn: &Connection) -> Result<String, String> {
+    Ok(get_setting(conn, "total_rounding")?.and_then(|v| parse_rule(&v)).map_or("none", |(name, _)| name).to_string())
+}
+
+/// A grand total rounded to the nearest step of the configured rule (halves
+/// round up), with the adjustment that takes the total there.
+pub fn round_total(conn: &Connection, total: f64) -> Result<(f64, f64), String> {
+    let step = parse_rule(&rounding_rule(conn)?).map_or(0, |(_, step)| step);
+    let cents = to_cents(total);
+    if step == 0 {
+        return Ok((total, 0.0));
+    }
+    let rounded = (cents as f64 / step as f64).round() as i64 * step;
+    Ok((rounded as f64 / 100.0, (rounded - cents) as f64 / 100.0))
+}
+
+/// Round a walk-in order's total under the configured rule, keeping the
+/// adjustment on the order for its receipt. `total_amount` may already carry an
+/// earlier adjustment; it is taken off first. Orders charged to a room stay
+/// unrounded, since they are rounded with the checkout bill. Returns the new total.
+pub(crate) fn round_order_total(conn: &Connection, order_id: i64) -> Result<f64, String> {
+    let (total, rounding, guest_id): (f64, f64, Option<i64>) = conn
+        .query_row(
+            "SELECT total_amount, rounding, guest_id FROM sales WHERE id = ?1",
+            params![order_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| e.to_string())?;
+    let unrounded = (to_cents(total) - to_cents(rounding)) as f64 / 100.0;
+    let (total, rounding) = if guest_id.is_some() { (unrounded, 0.0) } else { round_total(conn, unrounded)? };
+    conn.execute(
+        "UPDATE sales SET total_amount = ?1, rounding = ?2 WHERE id = ?3",
+        params![total, rounding, order_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(total)
+}
+
+/// Set how grand totals are rounded: "none", "0.05", "1", "5" or "10".
+#[command]
+pub fn set_total_rounding(rule: String) -> Result<String, AppError> {
+    let (rule, _) = parse_rule(rule.trim()).ok_or_else(|| {
+        AppError::from(format!(
+            "Rounding must be one of: {}",
+            ROUNDING_RULES.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+        ))
+    })?;
+    let conn = get_db_connection()?;
+    set_setting(&conn, "total_rounding", rule)?;
+    Ok(rule.to_string())
+}
+
+#[command]
+pub fn get_total_rounding() -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    Ok(rounding_rule(&conn)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{checkout_guest, insert_food_order};
+    use crate::test_support::{check_in, order_item, same_amount, TestDb};
+
+    /// Order a 3.40 espresso, for the guest or as a walk-in.
+    fn order_espresso(guest_id: Option<i64>) -> i64 {
+        let customer_type = if guest_id.is_some() { "guest" } else { "walk_in" };
+        insert_food_order(guest_id, customer_type.to_string(), None, vec![order_item("Espresso", 3.4, 1)], None, None, None).unwrap().0
+    }
+
+    /// An order's total and the rounding in it.
+    fn order_total(order_id: i64) -> (f64, f64) {
+        get_db_connection()
+            .unwrap()
+            .query_row("SELECT total_amount, rounding FROM sales WHERE id = ?1", params![order_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+    }
+
+    #[test]
+    fn walk_in_orders_are_rounded_and_the_receipt_shows_it() {
+        let _db = TestDb::new();
+        set_total_rounding("1".to_string()).unwrap();
+        crate::simple_commands::set_tax_enabled(false).unwrap();
+        let order_id = order_espresso(None);
+        let (total, rounding) = order_total(order_id);
+        assert!(same_amount(total, 3.0) && same_amount(rounding, -0.4), "walk-in order is {:.2} ({:.2} rounding), expected 3.00", total, rounding);
+        let receipt = crate::print_templates::build_order_receipt_html(order_id, None).unwrap();
+        assert!(receipt.contains("Rounding") && receipt.contains("-$0.40"), "order receipt does not show the rounding adjustment");
+    }
+
+    #[test]
+    fn room_charged_orders_are_rounded_with_the_bill() {
+        let _db = TestDb::new();
+        set_total_rounding("1".to_string()).unwrap();
+        let guest_id = check_in("Rounder", None);
+        let (total, _) = order_total(order_espresso(Some(guest_id)));
+        assert!(same_amount(total, 3.4), "room-charged order was rounded on its own to {:.2}", total);
+
+        let totals = checkout_guest(guest_id, None, None, Some("cash".to_string()), None, None).unwrap();
+        assert!(same_amount(totals.grand_total, totals.grand_total.round()), "checkout total {:.2} is not rounded", totals.grand_total);
+        let unrounded = totals.room_total + totals.unpaid_food + crate::taxes::tax_to_add(&totals.taxes);
+        assert!(same_amount(totals.grand_total - totals.rounding, unrounded), "checkout rounding does not explain the total");
+    }
+}
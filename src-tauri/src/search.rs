@@ -0,0 +1,78 @@
+use tauri::command;
+use rusqlite::{params, Connection, Result as SqliteResult};
+use crate::db::get_db_connection;
+use crate::errors::AppError;
+use crate::models::SearchHit;
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+
+/// Refill search_index from the source tables. Triggers keep it current after
+/// that, so this only runs when the index is created.
+pub fn rebuild_search_index(conn: &Connection) -> SqliteResult<usize> {
+    conn.execute("DELETE FROM search_index", [])?;
+    let mut indexed = conn.execute(
+        "INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+         SELECT id * 8 + 1, 'guest', id, name, COALESCE(phone, '') FROM customers",
+        [],
+    )?;
+    indexed += conn.execute(
+        "INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+         SELECT id * 8 + 2, 'order', id, customer_name, '' FROM sales
+         WHERE TRIM(COALESCE(customer_name, '')) != '' AND deleted_at IS NULL",
+        [],
+    )?;
+    indexed += conn.execute(
+        "INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+         SELECT id * 8 + 3, 'menu_item', id, name, '' FROM menu_items WHERE is_active = 1",
+        [],
+    )?;
+    indexed += conn.execute(
+        "INSERT INTO search_index (rowid, kind, entity_id, title, detail)
+         SELECT id * 8 + 4, 'expense', id, description, category FROM expenses
+         WHERE TRIM(COALESCE(description, '')) != '' AND deleted_at IS NULL",
+        [],
+    )?;
+    Ok(indexed)
+}
+
+/// Each word of `term` as a quoted prefix, so "jo smi" finds "John Smith" and
+/// FTS5 operators typed by the user are matched literally.
+fn match_expression(term: &str) -> Option<String> {
+    let words: Vec<String> = term
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+/// Guests (name or phone), named orders, active menu items and expenses
+/// (description or category) matching every word of `term`, best match first.
+#[command]
+pub fn global_search(term: String, limit: Option<i64>) -> Result<Vec<SearchHit>, AppError> {
+    let Some(expression) = match_expression(&term) else {
+        return Ok(Vec::new());
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT kind, entity_id, title, detail FROM search_index
+         WHERE search_index MATCH ?1
+         ORDER BY rank
+         LIMIT ?2",
+    )?;
+    let hits = stmt
+        .query_map(params![expression, limit], |row| {
+            Ok(SearchHit {
+                kind: row.get(0)?,
+                id: row.get(1)?,
+                title: row.get(2)?,
+                detail: row.get::<_, Option<String>>(3)?.filter(|d| !d.is_empty()),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(hits)
+}
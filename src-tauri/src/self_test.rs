@@ -0,0 +1,861 @@
+use std::fs;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use rusqlite::{params, OptionalExtension};
+use crate::db::{close_pooled_connections, get_db_connection, get_current_timestamp, initialize_database, with_db_path_override};
+use crate::models::{
+    GuestProfileInput, OrderItemInput, OrderItemModifier, PageRequest,
+    RoomTypeInput, SelfTestReport, SelfTestStep,
+};
+use crate::simple_commands::{
+    add_expense, add_guest, add_order_payment, add_room, business_date_today, checkout_guest,
+    get_order_payments, get_rooms, insert_food_order, mark_order_paid,
+};
+
+const TEST_ROOM: &str = "ST-101";
+const TEST_GUEST: &str = "Self Test Guest";
+const TEST_RATE: f64 = 100.0;
+const TEST_ADMIN: &str = "selftest";
+const TEST_ADMIN_PASSWORD: &str = "self-test-pass";
+
+fn room_type(name: &str, max_occupancy: Option<i64>) -> RoomTypeInput {
+    RoomTypeInput { name: name.to_string(), base_rate: TEST_RATE, max_occupancy, amenities: Vec::new() }
+}
+
+/// Sign in as the self-test's own admin, creating it on first use.
+fn admin_session() -> Result<String, String> {
+    let auth = crate::offline_auth::AuthManager::new();
+    if !auth.check_password(TEST_ADMIN, TEST_ADMIN_PASSWORD).unwrap_or(false) {
+        auth.register_initial_admin(TEST_ADMIN, TEST_ADMIN_PASSWORD, "Self test?", "yes").map_err(|e| e.to_string())?;
+    }
+    let request = crate::offline_auth::LoginRequest { username: TEST_ADMIN.to_string(), password: TEST_ADMIN_PASSWORD.to_string() };
+    auth.login(request).map_err(|e| e.to_string())?.session_token.ok_or("the self-test admin could not log in".to_string())
+}
+
+fn self_test_enabled() -> bool {
+    if cfg!(debug_assertions) {
+        return true;
+    }
+    let env_enabled = std::env::var("HOTEL_SELF_TEST")
+        .map(|v| {
+            let v = v.trim();
+            v == "1" || v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("yes")
+        })
+        .unwrap_or(false);
+    if env_enabled {
+        return true;
+    }
+    get_db_connection()
+        .ok()
+        .and_then(|conn| {
+            conn.query_row("SELECT value FROM settings WHERE key = 'self_test_enabled'", [], |row| row.get::<_, String>(0))
+                .optional()
+                .ok()
+                .flatten()
+        })
+        .is_some_and(|v| v == "1")
+}
+
+/// Fail the current step unless `condition` holds.
+macro_rules! expect {
+    ($condition:expr, $($message:tt)+) => {
+        if !$condition {
+            return Err(format!($($message)+));
+        }
+    };
+}
+
+/// Runs steps in order; once one fails the rest are reported as skipped,
+/// since each step builds on the state the previous ones left behind.
+struct Scenario {
+    steps: Vec<SelfTestStep>,
+    failed: bool,
+}
+
+impl Scenario {
+    fn step<T: Default>(&mut self, name: &str, run: impl FnOnce() -> Result<T, String>) -> T {
+        if self.failed {
+            self.steps.push(SelfTestStep {
+                name: name.to_string(),
+                status: "skipped".to_string(),
+                duration_ms: 0,
+                detail: None,
+            });
+            return T::default();
+        }
+
+        let started = Instant::now();
+        let outcome = catch_unwind(AssertUnwindSafe(run)).unwrap_or_else(|panic| {
+            let message = panic
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| "unknown panic".to_string());
+            Err(format!("panicked: {}", message))
+        });
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let (status, detail, value) = match outcome {
+            Ok(value) => ("passed", None, value),
+            Err(e) => {
+                self.failed = true;
+                ("failed", Some(e), T::default())
+            }
+        };
+        self.steps.push(SelfTestStep { name: name.to_string(), status: status.to_string(), duration_ms, detail });
+        value
+    }
+}
+
+fn same_amount(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 0.005
+}
+
+fn count(sql: &str, param: i64) -> Result<i64, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    conn.query_row(sql, params![param], |row| row.get(0)).map_err(|e| e.to_string())
+}
+
+/// A stand-in fiscal service on localhost that answers `requests` invoice
+/// submissions with "FISCAL-" and the invoice's USIN. Returns its URL.
+fn fake_fiscal_service(requests: usize) -> Result<String, String> {
+    use std::io::{BufRead, BufReader, Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").map_err(|e| e.to_string())?;
+    let url = format!("http://{}/fiscal", listener.local_addr().map_err(|e| e.to_string())?);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(requests).flatten() {
+            let mut reader = BufReader::new(&stream);
+            let mut length = 0;
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 2 {
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+                line.clear();
+            }
+            let mut body = vec![0; length];
+            let _ = reader.read_exact(&mut body);
+            let invoice: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            let reply = serde_json::json!({
+                "fiscal_number": format!("FISCAL-{}", invoice["usin"].as_str().unwrap_or_default()),
+                "qr": invoice["usin"],
+            })
+            .to_string();
+            let _ = write!(
+                &stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                reply.len(),
+                reply
+            );
+        }
+    });
+    Ok(url)
+}
+
+/// The scripted check-in to export scenario, driven through the real command
+/// functions against a fresh database in `work_dir`.
+fn run_scenario(work_dir: &Path) -> Vec<SelfTestStep> {
+    let mut scenario = Scenario { steps: Vec::new(), failed: false };
+    let today = business_date_today();
+    let backup_dir = work_dir.join("backups");
+
+    scenario.step("initialize database", || {
+        initialize_database().map_err(|e| e.to_string())?;
+        // Keep media for this database inside the work dir rather than the live media folder
+        let media_dir = work_dir.join("media_root");
+        fs::create_dir_all(&media_dir).map_err(|e| e.to_string())?;
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('media_directory', ?1, ?2)",
+            params![media_dir.to_string_lossy().to_string(), get_current_timestamp()],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    });
+
+    let room_id = scenario.step("create room", || {
+        crate::room_types::add_room_type(room_type("Standard", None))?;
+        add_room(TEST_ROOM.to_string(), "Standard".to_string(), TEST_RATE, None, None, None)?;
+        let room = get_rooms(None, None)?
+            .into_iter()
+            .find(|r| r.number == TEST_ROOM)
+            .ok_or("room not listed after add_room".to_string())?;
+        expect!(!room.is_occupied, "new room is already occupied");
+        Ok(room.id)
+    });
+
+    let guest_id = scenario.step("check in guest", || {
+        let guest_id = add_guest(
+            TEST_GUEST.to_string(), None, Some(room_id), today.clone(), None, TEST_RATE,
+            Some(1), Some(0), None, None, None, None,
+        )?;
+        let occupied = get_rooms(None, None)?.into_iter().any(|r| r.id == room_id && r.is_occupied && r.guest_id == Some(guest_id));
+        expect!(occupied, "room {} not marked occupied by guest {}", room_id, guest_id);
+        Ok(guest_id)
+    });
+
+    let order_id = scenario.step("add order", || {
+        let items = vec![OrderItemInput {
+            menu_item_id: None,
+            item_name: "Self-test tea".to_string(),
+            unit_price: 2.5,
+            quantity: 4,
+            selected_attributes: Vec::new(),
+            special_id: None,
+            notes: None,
+            modifiers: vec![OrderItemModifier { name: "No sugar".to_string(), price_delta: 0.0 }],
+            combo_id: None,
+        }];
+        let (order_id, _) = insert_food_order(Some(guest_id), "guest".to_string(), None, items, None, None, None)?;
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let total: f64 = conn
+            .query_row("SELECT total_amount FROM sales WHERE id = ?1", params![order_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        expect!(same_amount(total, 10.0), "order total is {:.2}, expected 10.00", total);
+        let ticket = crate::print_templates::build_kitchen_ticket_html(order_id)?;
+        expect!(ticket.contains("Self-test tea"), "kitchen ticket does not list the item");
+        expect!(ticket.contains("No sugar"), "kitchen ticket does not list the item's modifier");
+        crate::templates::save_print_template("kitchen_ticket".to_string(), "<p>SELF-TEST {{order_id}}</p>{{{items_html}}}".to_string())?;
+        let custom = crate::print_templates::build_kitchen_ticket_html(order_id);
+        crate::templates::reset_print_template("kitchen_ticket".to_string())?;
+        expect!(custom?.contains(&format!("SELF-TEST {}", order_id)), "kitchen ticket ignores the customised template");
+        let served = crate::order_status::set_order_status(order_id, "served".to_string(), None)?;
+        expect!(served.served_at.is_some(), "order {} has no served time", order_id);
+        expect!(
+            crate::order_status::set_order_status(order_id, "preparing".to_string(), None).is_err(),
+            "order {} went back from served to preparing",
+            order_id
+        );
+        Ok(order_id)
+    });
+
+    let shift_id = scenario.step("open shift", || {
+        let shift_id = crate::simple_commands::open_shift(1, 50.0)?;
+        expect!(crate::simple_commands::open_shift(1, 50.0).is_err(), "a second shift opened while shift {} is open", shift_id);
+        Ok(shift_id)
+    });
+
+    scenario.step("pay order", || {
+        // Split across two methods so partial payments are covered too
+        let partial = add_order_payment(order_id, 4.0, "card".to_string(), None, None)?;
+        expect!(partial.payment_status == "partially_paid", "status after first payment is {}", partial.payment_status);
+        expect!(same_amount(partial.balance, 6.0), "balance after first payment is {:.2}, expected 6.00", partial.balance);
+        mark_order_paid(order_id, None)?;
+        let paid = count("SELECT COUNT(*) FROM sales WHERE id = ?1 AND paid = 1 AND paid_at IS NOT NULL", order_id)?;
+        expect!(paid == 1, "order {} not marked paid", order_id);
+        let payments = get_order_payments(order_id)?;
+        expect!(payments.len() == 2, "{} payments recorded, expected 2", payments.len());
+        // A partial refund reopens the order until the difference is paid again
+        let unsigned = crate::simple_commands::refund_payment(payments[0].id, 1.0, "Self-test refund".to_string(), None);
+        expect!(unsigned.is_err(), "a payment was refunded without a manager signed in");
+        crate::simple_commands::refund_payment(payments[0].id, 1.0, "Self-test refund".to_string(), Some(admin_session()?))?;
+        let reopened = count("SELECT COUNT(*) FROM sales WHERE id = ?1 AND paid = 0", order_id)?;
+        expect!(reopened == 1, "order {} still marked paid after a refund", order_id);
+        add_order_payment(order_id, 1.0, "cash".to_string(), None, None)?;
+        Ok(())
+    });
+
+    scenario.step("add expense", || {
+        // A typo'd category is merged into the right one, taking its expenses along
+        expect!(
+            add_expense(today.clone(), "Suplies".to_string(), None, 1.0, None, None, None, None).is_err(),
+            "expense accepted under a category that doesn't exist"
+        );
+        let typo = crate::expense_categories::add_expense_category("Suplies".to_string())?;
+        let expense_id = add_expense(today.clone(), "suplies".to_string(), Some("Self-test".to_string()), 15.0, None, None, None, Some(1.5))?;
+        let supplies = crate::expense_categories::add_expense_category("Supplies".to_string())?;
+        let merged = crate::expense_categories::merge_expense_categories(typo.id, supplies.id, None)?;
+        expect!(merged.expense_count == 1, "merged category has {} expenses, expected 1", merged.expense_count);
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let category: String = conn
+            .query_row("SELECT category FROM expenses WHERE id = ?1", params![expense_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        expect!(category == "Supplies", "merged expense is filed under '{}'", category);
+        expect!(count("SELECT COUNT(*) FROM expenses WHERE id = ?1", expense_id)? == 1, "expense {} not saved", expense_id);
+        let audited = count("SELECT COUNT(*) FROM audit_log WHERE entity = 'expense' AND entity_id = ?1 AND action = 'create'", expense_id)?;
+        expect!(audited == 1, "expense {} has {} audit entries, expected 1", expense_id, audited);
+        Ok(())
+    });
+
+    scenario.step("expense budgets", || {
+        crate::budgets::set_expense_budget("supplies".to_string(), 10.0)?;
+        crate::expense_categories::add_expense_category("Laundry".to_string())?;
+        crate::budgets::set_expense_budget("Laundry".to_string(), 40.0)?;
+        let (year, month) = (today[..4].parse().unwrap_or(0), today[5..7].parse().unwrap_or(0));
+        let report = crate::budgets::budget_vs_actual(year, month)?;
+        let supplies = report.lines.first().ok_or("budget report has no lines".to_string())?;
+        expect!(supplies.category == "Supplies" && supplies.over_budget, "supplies is not listed first as over budget");
+        expect!(supplies.variance.is_some_and(|v| same_amount(v, 5.0)), "supplies variance is {:?}, expected 5.00", supplies.variance);
+        expect!(same_amount(report.total_variance, -35.0), "total variance is {:.2}, expected -35.00", report.total_variance);
+        Ok(())
+    });
+
+    scenario.step("paged lists", || {
+        let page = |sort: &str, filters: &[(&str, String)]| PageRequest {
+            limit: Some(1),
+            offset: None,
+            sort: Some(sort.to_string()),
+            filters: filters.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+        };
+        let orders = crate::simple_commands::get_food_orders_page(Some(page("-created_at", &[("guest_id", guest_id.to_string())])), None)?;
+        expect!(orders.total == 1 && orders.items.len() == 1, "{} orders paged for the guest, expected 1", orders.total);
+        expect!(orders.items[0].id == order_id && orders.next_offset.is_none(), "paged order is #{}, expected #{}", orders.items[0].id, order_id);
+        let guests = crate::simple_commands::get_guests_page(Some(page("name", &[("search", TEST_GUEST.to_string())])), None)?;
+        expect!(guests.items.iter().any(|g| g.id == guest_id), "guest search did not find {}", TEST_GUEST);
+        let expenses = crate::simple_commands::get_expenses_page(Some(page("-amount", &[("start_date", today.clone()), ("category", "Supplies".to_string())])))?;
+        expect!(expenses.total >= 1 && expenses.items.len() == 1, "{} expenses paged, expected at least 1", expenses.total);
+        expect!(
+            crate::simple_commands::get_expenses_page(Some(page("description; DROP TABLE expenses", &[]))).is_err(),
+            "an unknown sort key was accepted"
+        );
+        Ok(())
+    });
+
+    scenario.step("global search", || {
+        let hits = crate::search::global_search("self tes".to_string(), None)?;
+        expect!(hits.iter().any(|h| h.kind == "guest" && h.id == guest_id), "searching 'self tes' did not find the guest");
+        expect!(hits.iter().any(|h| h.kind == "expense"), "searching 'self tes' did not find the expense");
+        crate::simple_commands::update_guest(guest_id, Some("Renamed Guest".to_string()), None, None, None, None, None, None, None)?;
+        let renamed = crate::search::global_search("renamed".to_string(), None)?;
+        crate::simple_commands::update_guest(guest_id, Some(TEST_GUEST.to_string()), None, None, None, None, None, None, None)?;
+        expect!(renamed.iter().any(|h| h.id == guest_id), "search index missed the guest's new name");
+        expect!(crate::search::global_search("\" OR *".to_string(), None).is_ok(), "search syntax in the term caused an error");
+        Ok(())
+    });
+
+    scenario.step("check out guest", || {
+        crate::guest_charges::add_guest_charge(guest_id, "minibar".to_string(), 2.5, Some(2), None, None, None)?;
+        let folio = crate::folio::add_guest_deposit(guest_id, 20.0, "cash".to_string(), None)?;
+        expect!(same_amount(folio.balance, TEST_RATE - 15.0), "folio balance is {:.2}, expected {:.2}", folio.balance, TEST_RATE - 15.0);
+        crate::simple_commands::set_tax_rate(10.0)?;
+        crate::simple_commands::set_tax_enabled(true)?;
+        crate::taxes::add_tax("GST".to_string(), 5.0, "rooms".to_string())?;
+        let totals = checkout_guest(guest_id, None, None, Some("card".to_string()), None, None)?;
+        expect!(totals.stay_days == 1, "stay_days is {}, expected 1", totals.stay_days);
+        expect!(same_amount(totals.room_total, TEST_RATE), "room total is {:.2}, expected {:.2}", totals.room_total, TEST_RATE);
+        expect!(same_amount(totals.unpaid_food, 0.0), "paid order still billed: unpaid food {:.2}", totals.unpaid_food);
+        expect!(same_amount(totals.extra_charges, 5.0), "extra charges are {:.2}, expected 5.00", totals.extra_charges);
+        expect!(totals.taxes.len() == 2, "{} taxes charged, expected 2", totals.taxes.len());
+        expect!(same_amount(totals.tax, (TEST_RATE + 5.0) * 0.15), "tax is {:.2}, expected 15% of the bill", totals.tax);
+        let expected = (TEST_RATE + 5.0) * 1.15;
+        expect!(same_amount(totals.grand_total, expected), "grand total is {:.2}, expected {:.2}", totals.grand_total, expected);
+        expect!(same_amount(totals.balance_due, expected - 20.0), "balance due is {:.2}, expected {:.2}", totals.balance_due, expected - 20.0);
+        let settled = count("SELECT COUNT(*) FROM payments WHERE guest_id = ?1 AND order_id IS NULL AND method = 'card'", guest_id)?;
+        expect!(settled == 1, "{} checkout payments recorded, expected 1", settled);
+        let aging = tauri::async_runtime::block_on(crate::receivables::receivables_aging_report())?;
+        expect!(aging.items.is_empty(), "{} receivables left after a settled checkout", aging.items.len());
+        let freed = get_rooms(None, None)?.into_iter().any(|r| r.id == room_id && !r.is_occupied);
+        expect!(freed, "room {} still occupied after checkout", room_id);
+        Ok(())
+    });
+
+    scenario.step("income trends", || {
+        let revenue = tauri::async_runtime::block_on(crate::timeseries::revenue_timeseries(
+            "weekly".to_string(), today.clone(), today.clone(),
+        ))?;
+        expect!(revenue.total_income.values.len() == 1, "{} weekly buckets for one day, expected 1", revenue.total_income.values.len());
+        expect!(same_amount(revenue.room_income.values[0], TEST_RATE), "room income is {:.2}, expected {:.2}", revenue.room_income.values[0], TEST_RATE);
+        expect!(same_amount(revenue.food_income.values[0], 10.0), "food income is {:.2}, expected 10.00", revenue.food_income.values[0]);
+        let spending = tauri::async_runtime::block_on(crate::timeseries::expense_timeseries(
+            "daily".to_string(), today.clone(), today.clone(),
+        ))?;
+        expect!(spending.expenses.labels == vec![today.clone()], "daily expense buckets are {:?}", spending.expenses.labels);
+        expect!(same_amount(spending.expenses.values[0], 15.0), "expenses are {:.2}, expected 15.00", spending.expenses.values[0]);
+        Ok(())
+    });
+
+    scenario.step("night audit", || {
+        let report = tauri::async_runtime::block_on(crate::night_audit::night_audit_report(today.clone()))?;
+        expect!(report.check_outs.iter().any(|s| s.guest_id == guest_id), "night audit does not list the check-out");
+        expect!(report.in_house.iter().any(|s| s.guest_id == guest_id), "night audit does not count the day stay as in house");
+        expect!(same_amount(report.food_revenue, 10.0), "night audit food revenue is {:.2}, expected 10.00", report.food_revenue);
+        expect!(!report.payments.is_empty(), "night audit shows no payments");
+        let path = PathBuf::from(tauri::async_runtime::block_on(crate::night_audit::export_night_audit(today.clone(), "csv".to_string()))?);
+        let contents = fs::read_to_string(&path).map_err(|e| e.to_string());
+        let _ = fs::remove_file(&path);
+        let contents = contents?;
+        expect!(contents.contains("Check-outs") && contents.contains(TEST_GUEST), "night audit CSV does not list the guest");
+        expect!(contents.contains("Owner signature"), "night audit CSV has no sign-off line");
+        Ok(())
+    });
+
+    scenario.step("close shift", || {
+        // Cash: 6.00 settling the order, 1.00 after the refund and the 20.00 deposit
+        let running = crate::simple_commands::get_shift_reconciliation(shift_id)?;
+        expect!(same_amount(running.cash_sales, 27.0), "shift cash sales are {:.2}, expected 27.00", running.cash_sales);
+        let card = &running.methods[1];
+        expect!(same_amount(card.refunded, 1.0), "shift card refunds are {:.2}, expected 1.00", card.refunded);
+        expect!(card.payment_count == 3, "{} card payments in the shift, expected 3", card.payment_count);
+        let closed = crate::simple_commands::close_shift(shift_id, 1, Some(running.expected_cash - 2.0), None, None)?;
+        expect!(same_amount(closed.difference, -2.0), "shift difference is {:.2}, expected -2.00", closed.difference);
+        let stored = crate::simple_commands::get_shift_reconciliation(shift_id)?;
+        expect!(stored.status == "closed" && stored.counted_cash.is_some(), "closed shift has no stored reconciliation");
+        expect!(stored.payments.len() == running.payments.len(), "stored reconciliation lists {} payments, expected {}", stored.payments.len(), running.payments.len());
+        Ok(())
+    });
+
+    scenario.step("ledger", || {
+        // Food: 4.00 + 6.00 - 1.00 + 1.00; rooms: the checkout bill, deposit included, less its 15% tax
+        let room_revenue = TEST_RATE + 5.0;
+        let pnl = crate::ledger::profit_and_loss(today.clone(), today.clone())?;
+        expect!(same_amount(pnl.total_revenue, 10.0 + room_revenue), "ledger revenue is {:.2}, expected {:.2}", pnl.total_revenue, 10.0 + room_revenue);
+        expect!(same_amount(pnl.total_expenses, 15.0), "ledger expenses are {:.2}, expected 15.00", pnl.total_expenses);
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        expect!(crate::ledger::post_ledger(&conn)? == 0, "posting the ledger twice wrote new entries");
+
+        // Editing an expense reverses its entry and posts the new amount
+        let expense_id: i64 = conn
+            .query_row("SELECT id FROM expenses WHERE description = 'Self-test'", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        crate::simple_commands::update_expense(expense_id, None, None, None, Some(12.0), None, None, None, None)?;
+        let corrected = crate::ledger::profit_and_loss(today.clone(), today.clone())?;
+        crate::simple_commands::update_expense(expense_id, None, None, None, Some(15.0), None, None, None, None)?;
+        expect!(same_amount(corrected.total_expenses, 12.0), "ledger expenses after an edit are {:.2}, expected 12.00", corrected.total_expenses);
+
+        let accounts = crate::ledger::get_ledger_accounts()?;
+        let account = |code: &str| accounts.iter().find(|a| a.code == code).map(|a| a.id).ok_or(format!("no ledger account {}", code));
+        let (cash, equity) = (account("1000")?, account("3000")?);
+        let line = |account_id, debit: f64, credit: f64| crate::models::NewJournalLine { account_id, debit: Some(debit), credit: Some(credit) };
+        expect!(
+            crate::ledger::add_journal_entry(today.clone(), "Opening float".to_string(), vec![line(cash, 100.0, 0.0), line(equity, 0.0, 90.0)], None).is_err(),
+            "an unbalanced journal entry was accepted"
+        );
+        crate::ledger::add_journal_entry(today.clone(), "Opening float".to_string(), vec![line(cash, 100.0, 0.0), line(equity, 0.0, 100.0)], None)?;
+        let trial = crate::ledger::trial_balance(None)?;
+        expect!(same_amount(trial.total_debit, trial.total_credit), "trial balance is off: {:.2} debit, {:.2} credit", trial.total_debit, trial.total_credit);
+        expect!(!trial.lines.iter().any(|l| l.code == "2000"), "guest deposits still held after checkout");
+        let balance = |trial: &crate::models::TrialBalance, code: &str| {
+            trial.lines.iter().find(|l| l.code == code).map(|l| l.debit - l.credit).unwrap_or(0.0)
+        };
+        let tax_payable = balance(&trial, "2100");
+        expect!(same_amount(tax_payable, -room_revenue * 0.15), "tax payable is {:.2}, expected {:.2} owed", tax_payable, room_revenue * 0.15);
+
+        // A stay on a company account is owed until the company pays
+        use crate::companies::{add_company, post_to_company_account, record_company_payment};
+        let company = add_company("Self-test Ledger Co".to_string(), None, None, None, None, None, None)?;
+        add_room("ST-LEDGER".to_string(), "Standard".to_string(), TEST_RATE, None, None, None)?;
+        let room_id = get_rooms(None, None)?.into_iter().find(|r| r.number == "ST-LEDGER").ok_or("room ST-LEDGER not listed".to_string())?.id;
+        let guest = add_guest(
+            "Ledger Company Guest".to_string(), None, Some(room_id), today.clone(), None, TEST_RATE,
+            Some(1), Some(0), None, None, None, None,
+        )?;
+        let bill = post_to_company_account(guest, company.id, None, None, None)?;
+        record_company_payment(company.id, 40.0, "bank_transfer".to_string(), today.clone(), None, None)?;
+        let trial = crate::ledger::trial_balance(None)?;
+        let receivable = balance(&trial, "1100");
+        expect!(same_amount(receivable, bill.balance_due - 40.0), "accounts receivable is {:.2}, expected {:.2}", receivable, bill.balance_due - 40.0);
+        let tax_payable = balance(&trial, "2100");
+        expect!(same_amount(tax_payable, -(room_revenue * 0.15 + bill.tax)), "tax payable is {:.2} after the company stay", tax_payable);
+        expect!(same_amount(trial.total_debit, trial.total_credit), "trial balance is off after a company stay");
+
+        // Later steps count today's stays and tax without this one; its entries are reversed
+        conn.execute_batch(&format!(
+            "DELETE FROM tax_charges WHERE guest_id = {guest};
+             DELETE FROM company_charges WHERE company_id = {company};
+             DELETE FROM company_payments WHERE company_id = {company};
+             DELETE FROM companies WHERE id = {company};
+             DELETE FROM customers WHERE id = {guest};
+             DELETE FROM resources WHERE id = {room_id};",
+            company = company.id
+        ))
+        .map_err(|e| e.to_string())?;
+        let trial = crate::ledger::trial_balance(None)?;
+        expect!(same_amount(balance(&trial, "1100"), 0.0), "accounts receivable left over after the company stay was removed");
+        Ok(())
+    });
+
+    scenario.step("accounting export", || {
+        let path = PathBuf::from(tauri::async_runtime::block_on(crate::accounting_export::export_accounting_csv(
+            "xero".to_string(), today.clone(), today.clone(),
+        ))?);
+        let contents = fs::read_to_string(&path).map_err(|e| e.to_string());
+        let _ = fs::remove_file(&path);
+        let contents = contents?;
+        expect!(contents.starts_with("*Narration,*Date"), "Xero export has the wrong header");
+        let net: f64 = contents.lines().skip(1).filter_map(|l| l.rsplit(',').next()?.parse::<f64>().ok()).sum();
+        expect!(same_amount(net, 0.0), "Xero journal lines sum to {:.2}, expected 0.00", net);
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let quickbooks = crate::accounting_export::build_accounting_csv(&conn, "QuickBooks", &today, &today)?;
+        expect!(quickbooks.contains("Opening float") && quickbooks.contains("Cash on Hand"), "QuickBooks export is missing the manual entry");
+        expect!(crate::accounting_export::build_accounting_csv(&conn, "sage", &today, &today).is_err(), "an unknown format was accepted");
+        Ok(())
+    });
+
+    scenario.step("tax return", || {
+        let report = tauri::async_runtime::block_on(crate::tax_report::tax_report(today.clone(), today.clone()))?;
+        let collected = (TEST_RATE + 5.0) * 0.15;
+        expect!(report.taxes.len() == 2, "{} taxes on the tax return, expected 2", report.taxes.len());
+        expect!(same_amount(report.total_collected, collected), "tax collected is {:.2}, expected {:.2}", report.total_collected, collected);
+        expect!(same_amount(report.total_expense_tax, 1.5), "tax on expenses is {:.2}, expected 1.50", report.total_expense_tax);
+        expect!(same_amount(report.net_payable, collected - 1.5), "net tax payable is {:.2}, expected {:.2}", report.net_payable, collected - 1.5);
+        let path = PathBuf::from(tauri::async_runtime::block_on(crate::tax_report::export_tax_report(
+            today.clone(), today.clone(), "csv".to_string(),
+        ))?);
+        let contents = fs::read_to_string(&path).map_err(|e| e.to_string());
+        let _ = fs::remove_file(&path);
+        expect!(contents?.contains("GST,5,rooms,1,"), "tax return CSV does not list GST");
+        Ok(())
+    });
+
+    scenario.step("fiscal invoice", || {
+        let service = fake_fiscal_service(2)?;
+        crate::fiscal::set_fiscal_settings(crate::models::FiscalSettingsInput {
+            provider: "webhook".to_string(),
+            fbr_environment: None,
+            fbr_pos_id: None,
+            fbr_token: None,
+            fbr_pct_code: None,
+            webhook_url: Some(service),
+            webhook_token: Some("self-test".to_string()),
+            print_on_receipt: true,
+        })?;
+        let outcome = (|| -> Result<(), String> {
+            let order = tauri::async_runtime::block_on(crate::fiscal::fiscalize_invoice("order".to_string(), order_id))?;
+            expect!(order.status == "submitted", "order fiscal status is {}, expected submitted", order.status);
+            let expected = format!("FISCAL-ORDER-{}", order_id);
+            expect!(order.fiscal_number.as_deref() == Some(expected.as_str()), "order fiscal number is {:?}, expected {}", order.fiscal_number, expected);
+            let again = tauri::async_runtime::block_on(crate::fiscal::fiscalize_invoice("order".to_string(), order_id))?;
+            expect!(again.attempts == 1, "a submitted order was sent again");
+            let receipt = crate::print_templates::build_order_receipt_html(order_id, None)?;
+            expect!(receipt.contains(&format!("Fiscal invoice no.: <strong>{}</strong>", expected)), "receipt does not print the fiscal invoice number");
+            let checkout = tauri::async_runtime::block_on(crate::fiscal::fiscalize_invoice("checkout".to_string(), guest_id))?;
+            expect!(checkout.usin == format!("INVOICE-{}", guest_id), "checkout was reported as {}", checkout.usin);
+            let invoice = crate::print_templates::build_final_invoice_html(guest_id, None)?;
+            expect!(invoice.contains(&format!("FISCAL-INVOICE-{}", guest_id)), "invoice does not print the fiscal invoice number");
+            let submitted = crate::fiscal::get_fiscal_invoices(Some("submitted".to_string()))?;
+            expect!(submitted.len() == 2, "{} fiscal invoices submitted, expected 2", submitted.len());
+            Ok(())
+        })();
+        crate::fiscal::set_fiscal_settings(crate::models::FiscalSettingsInput {
+            provider: "none".to_string(),
+            fbr_environment: None,
+            fbr_pos_id: None,
+            fbr_token: None,
+            fbr_pct_code: None,
+            webhook_url: None,
+            webhook_token: None,
+            print_on_receipt: false,
+        })?;
+        outcome
+    });
+
+    scenario.step("online payment", || {
+        let unconfigured = tauri::async_runtime::block_on(crate::online_payments::create_online_payment("order".to_string(), order_id));
+        expect!(unconfigured.is_err(), "an online payment was started with no gateway set up");
+        let settings = crate::online_payments::set_payment_gateway_settings(crate::models::PaymentGatewaySettingsInput {
+            provider: "stripe".to_string(),
+            stripe_secret_key: Some("sk_test_selftest".to_string()),
+            stripe_publishable_key: Some("pk_test_selftest".to_string()),
+        });
+        let cleared = crate::online_payments::set_payment_gateway_settings(crate::models::PaymentGatewaySettingsInput {
+            provider: "none".to_string(),
+            stripe_secret_key: None,
+            stripe_publishable_key: None,
+        });
+        let settings = settings?;
+        expect!(settings.has_secret && settings.test_mode, "the Stripe test key was not saved");
+        expect!(cleared?.has_secret, "the saved secret key was dropped when left out");
+
+        let items = vec![OrderItemInput {
+            menu_item_id: None,
+            item_name: "Self-test tea".to_string(),
+            unit_price: 4.0,
+            quantity: 1,
+            selected_attributes: Vec::new(),
+            special_id: None,
+            notes: None,
+            modifiers: Vec::new(),
+            combo_id: None,
+        }];
+        let (online_order, _) = insert_food_order(None, "walk_in".to_string(), Some("Walk-in".to_string()), items, None, None, None)?;
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        // Tax is on by now, so the tea costs more than its menu price
+        let due: f64 = conn
+            .query_row("SELECT total_amount FROM sales WHERE id = ?1", params![online_order], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        expect!(due > 4.0, "walk-in order total is {:.2}, expected tax on top of 4.00", due);
+        conn.execute(
+            "INSERT INTO payment_intents (source_type, source_id, provider, intent_id, amount, currency, status)
+             VALUES ('order', ?1, 'stripe', 'pi_selftest', ?2, 'usd', 'requires_payment_method')",
+            params![online_order, due],
+        )
+        .map_err(|e| e.to_string())?;
+        let intent = conn.last_insert_rowid();
+        let pending = crate::online_payments::apply_intent_status(&conn, intent, "processing", 0.0)?;
+        expect!(pending.payment_id.is_none(), "a processing payment was recorded as paid");
+        let paid = crate::online_payments::apply_intent_status(&conn, intent, "succeeded", due)?;
+        crate::online_payments::apply_intent_status(&conn, intent, "succeeded", due)?;
+        expect!(paid.payment_id.is_some() && paid.paid_at.is_some(), "the succeeded payment was not recorded");
+        let payments = get_order_payments(online_order)?;
+        expect!(payments.len() == 1 && payments[0].method == "card", "{} payments on the order, expected one card payment", payments.len());
+        expect!(count("SELECT paid FROM sales WHERE id = ?1", online_order)? == 1, "the order was not marked paid");
+        Ok(())
+    });
+
+    scenario.step("vouchers", || {
+        let voucher = crate::vouchers::create_voucher(crate::models::NewVoucher {
+            code: Some("selftest10".to_string()),
+            kind: "percent".to_string(),
+            value: 10.0,
+            expires_on: None,
+            max_uses: Some(1),
+            note: None,
+        }, None)?;
+        expect!(voucher.code == "SELFTEST10", "voucher code saved as {}", voucher.code);
+        let check = crate::vouchers::validate_voucher("selftest10".to_string(), Some(50.0))?;
+        expect!(check.uses_left == Some(1), "single-use voucher has {:?} uses left", check.uses_left);
+        expect!(check.discount.is_some_and(|d| same_amount(d, 5.0)), "10% voucher takes {:?} off 50.00", check.discount);
+        let expired = crate::vouchers::create_voucher(crate::models::NewVoucher {
+            code: None,
+            kind: "amount".to_string(),
+            value: 5.0,
+            expires_on: Some("2020-01-01".to_string()),
+            max_uses: None,
+            note: None,
+        }, None)?;
+        let rejected = crate::vouchers::validate_voucher(expired.code, None);
+        expect!(rejected.is_err_and(|e| e.code() == crate::validation::VOUCHER_EXPIRED), "an expired voucher was accepted");
+
+        let items = vec![OrderItemInput {
+            menu_item_id: None,
+            item_name: "Self-test dinner".to_string(),
+            unit_price: 20.0,
+            quantity: 1,
+            selected_attributes: Vec::new(),
+            special_id: None,
+            notes: None,
+            modifiers: Vec::new(),
+            combo_id: None,
+        }];
+        let (voucher_order, _) = insert_food_order(None, "walk_in".to_string(), Some("Walk-in".to_string()), items, None, None, None)?;
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let before = crate::simple_commands::order_payment_summary(&conn, voucher_order)?;
+        drop(conn);
+        let summary = crate::vouchers::redeem_voucher_on_order(voucher_order, "SELFTEST10".to_string(), None)?;
+        let expected = before.total_amount * 0.9;
+        expect!(same_amount(summary.balance, expected), "order balance after the voucher is {:.2}, expected {:.2}", summary.balance, expected);
+        let again = crate::vouchers::redeem_voucher_on_order(voucher_order, "SELFTEST10".to_string(), None);
+        expect!(again.is_err_and(|e| e.code() == crate::validation::VOUCHER_USED_UP), "a single-use voucher was used twice");
+        let receipt = crate::print_templates::build_order_receipt_html(voucher_order, None)?;
+        expect!(receipt.contains("Voucher SELFTEST10"), "receipt does not show the voucher");
+        let redemptions = crate::vouchers::get_voucher_redemptions(Some(voucher.id))?;
+        expect!(redemptions.len() == 1 && redemptions[0].order_id == Some(voucher_order), "voucher redemption was not recorded");
+        mark_order_paid(voucher_order, None)?;
+        Ok(())
+    });
+
+    let profile_id = scenario.step("guest profiles", || {
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let profile_id: Option<i64> = conn
+            .query_row("SELECT profile_id FROM customers WHERE id = ?1", params![guest_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        let profile_id = profile_id.ok_or("checked-in guest has no profile".to_string())?;
+        crate::guest_profiles::update_guest_profile(
+            profile_id,
+            GuestProfileInput {
+                name: TEST_GUEST.to_string(),
+                phone: Some("+92 300 1234567".to_string()),
+                email: None,
+                id_number: None,
+                nationality: Some("Pakistani".to_string()),
+                preferences: Some("Quiet room".to_string()),
+                notes: None,
+            },
+            None,
+        )?;
+        let returning = add_guest(
+            TEST_GUEST.to_string(), Some("0300-1234567".to_string()), None, today.clone(), None, TEST_RATE,
+            Some(1), Some(0), None, None, None, None,
+        )?;
+        let history = crate::guest_profiles::get_guest_profile_history(profile_id)?;
+        expect!(history.total_stays == 2, "returning guest's profile has {} stays, expected 2", history.total_stays);
+        expect!(history.stays.iter().any(|s| s.guest_id == returning), "returning stay was not matched by phone");
+        expect!(history.profile.preferences.as_deref() == Some("Quiet room"), "profile lost the guest's preferences");
+        expect!(history.favourite_items.iter().any(|i| i == "Self-test tea"), "history does not show what the guest ordered");
+        expect!(!same_amount(history.outstanding_balance, 0.0), "in-house stay shows nothing outstanding");
+        checkout_guest(returning, None, None, Some("cash".to_string()), None, None)?;
+        let history = crate::guest_profiles::get_guest_profile_history(profile_id)?;
+        expect!(same_amount(history.outstanding_balance, 0.0), "settled stays still show {:.2} outstanding", history.outstanding_balance);
+        Ok(profile_id)
+    });
+
+    scenario.step("merge duplicate guests", || {
+        let duplicate_stay = add_guest(
+            format!("  {}  ", TEST_GUEST.to_uppercase()), None, None, today.clone(), None, TEST_RATE,
+            Some(1), Some(0), None, None, None, None,
+        )?;
+        let groups = crate::guest_profiles::find_duplicate_guests()?;
+        let group = groups
+            .iter()
+            .find(|g| g.profiles.iter().any(|p| p.id == profile_id))
+            .ok_or("the re-entered guest was not flagged as a duplicate".to_string())?;
+        expect!(group.matched_on == "name", "duplicate matched on {}, expected name", group.matched_on);
+        let duplicate_ids: Vec<i64> = group.profiles.iter().map(|p| p.id).filter(|id| *id != profile_id).collect();
+        let result = crate::guest_profiles::merge_guests(profile_id, duplicate_ids.clone(), None)?;
+        expect!(result.stays_moved == 1 && result.profile.stay_count == 3, "merge moved {} stays, profile has {}", result.stays_moved, result.profile.stay_count);
+        expect!(result.profile.preferences.as_deref() == Some("Quiet room"), "merge lost the guest's preferences");
+        let left = crate::guest_profiles::get_guest_profile(duplicate_ids[0]);
+        expect!(left.is_err_and(|e| e.code() == crate::validation::GUEST_PROFILE_NOT_FOUND), "merged profile was not removed");
+        checkout_guest(duplicate_stay, None, None, Some("cash".to_string()), None, None)?;
+        Ok(())
+    });
+
+    scenario.step("guest register", || {
+        let path = PathBuf::from(tauri::async_runtime::block_on(
+            crate::registration_report::export_guest_registration_report(today.clone(), today.clone(), "csv".to_string()),
+        )?);
+        let contents = fs::read_to_string(&path).map_err(|e| e.to_string());
+        let _ = fs::remove_file(&path);
+        let expected = format!("{},Pakistani,,+92 300 1234567,{},{}", TEST_GUEST, TEST_ROOM, today);
+        expect!(contents?.contains(&expected), "guest register CSV does not list the guest with their nationality");
+        let path = PathBuf::from(tauri::async_runtime::block_on(
+            crate::registration_report::export_guest_registration_report(today.clone(), today.clone(), "html".to_string()),
+        )?);
+        let html = fs::read_to_string(&path).map_err(|e| e.to_string());
+        let _ = fs::remove_file(&path);
+        expect!(html?.contains("class=\"missing\""), "guest register does not flag the missing document number");
+        Ok(())
+    });
+
+    scenario.step("guest data export and erasure", || {
+        let leaving = add_guest(
+            "Self-test Leaver".to_string(), Some("+44 7700 900123".to_string()), None, today.clone(), None, TEST_RATE,
+            Some(1), Some(0), None, None, None, None,
+        )?;
+        checkout_guest(leaving, None, None, Some("cash".to_string()), None, None)?;
+        let data = crate::guest_privacy::export_guest_data(leaving)?;
+        let exported = data.to_string();
+        expect!(exported.contains("Self-test Leaver") && exported.contains("+44 7700 900123"), "guest data export is missing their details");
+        expect!(data["stays"][0]["payments"].as_array().is_some_and(|p| !p.is_empty()), "guest data export has no payments");
+
+        let conn = get_db_connection().map_err(|e| e.to_string())?;
+        let paid = |conn: &rusqlite::Connection| -> Result<f64, String> {
+            conn.query_row("SELECT COALESCE(SUM(amount), 0) FROM payments WHERE guest_id = ?1", params![leaving], |row| row.get(0))
+                .map_err(|e| e.to_string())
+        };
+        let paid_before = paid(&conn)?;
+        let result = crate::guest_privacy::anonymize_stays(&conn, leaving, "self_test")?;
+        expect!(result.stays_anonymized == 1, "{} stays anonymized, expected 1", result.stays_anonymized);
+        let exported = crate::guest_privacy::export_guest_data(leaving)?.to_string();
+        expect!(!exported.contains("Self-test Leaver") && !exported.contains("7700"), "guest details survived anonymization");
+        expect!(same_amount(paid(&conn)?, paid_before), "anonymization changed the guest's payments");
+        let again = crate::guest_privacy::anonymize_stays(&conn, leaving, "self_test");
+        expect!(again.is_err_and(|e| e.code() == crate::validation::GUEST_ANONYMIZED), "a guest was anonymized twice");
+        Ok(())
+    });
+
+    scenario.step("generate invoice", || {
+        crate::simple_commands::set_business_info(
+            "Self-test Hotel".to_string(),
+            Some("1 Test Street".to_string()),
+            Some("555-0100".to_string()),
+            None,
+        )?;
+        let html = crate::print_templates::build_final_invoice_html(guest_id, None)?;
+        expect!(html.contains(TEST_GUEST), "invoice does not name the guest");
+        expect!(html.contains("Self-test Hotel") && html.contains("Tel: 555-0100"), "invoice does not show the business details");
+        expect!(html.contains(TEST_ROOM), "invoice does not show the room");
+        expect!(html.contains("Minibar"), "invoice does not list the minibar charge");
+        expect!(html.contains("Balance due"), "invoice does not show the balance after the deposit");
+        expect!(html.contains("Tax (10.0%)") && html.contains("GST (5.0%)"), "invoice does not itemize the taxes");
+        crate::taxes::set_prices_include_tax(true)?;
+        let inclusive = crate::print_templates::build_final_invoice_html(guest_id, None);
+        crate::taxes::set_prices_include_tax(false)?;
+        expect!(inclusive?.contains("Includes GST"), "tax-inclusive invoice does not show the tax as included");
+        crate::rounding::set_total_rounding("10".to_string())?;
+        let rounded = crate::print_templates::build_final_invoice_html(guest_id, None);
+        crate::rounding::set_total_rounding("none".to_string())?;
+        expect!(rounded?.contains("Rounding:"), "invoice does not show the rounding adjustment");
+        Ok(())
+    });
+
+    scenario.step("export CSV", || {
+        let path = PathBuf::from(tauri::async_runtime::block_on(crate::export::export_history_csv("guests".to_string(), serde_json::json!({})))?);
+        // The export lands in the live exports folder, so read it and remove it straight away
+        let contents = fs::read_to_string(&path).map_err(|e| e.to_string());
+        let _ = fs::remove_file(&path);
+        let contents = contents?;
+        expect!(contents.lines().count() == 2, "expected header and one guest row, got {} lines", contents.lines().count());
+        expect!(contents.contains(TEST_GUEST), "exported CSV does not contain the guest");
+        expect!(contents.contains("Total Bill (USD)"), "exported CSV does not name the currency");
+        Ok(())
+    });
+
+    let backup_path = scenario.step("take backup", || {
+        fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+        tauri::async_runtime::block_on(crate::settings::backup_database(backup_dir.to_string_lossy().to_string()))?;
+        let backup = fs::read_dir(&backup_dir)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .find(|p| p.extension().is_some_and(|ext| ext == "db"))
+            .ok_or("no .db file written to the backup folder".to_string())?;
+        Ok(backup)
+    });
+
+    scenario.step("restore backup", || {
+        // A change made after the backup must be gone once it is restored
+        add_expense(today.clone(), "Supplies".to_string(), Some("After backup".to_string()), 1.0, None, None, None, None)?;
+        tauri::async_runtime::block_on(crate::settings::restore_database_from_backup(backup_path.to_string_lossy().to_string(), None))?;
+        let expenses = count("SELECT COUNT(*) FROM expenses WHERE amount > ?1 AND deleted_at IS NULL", 0)?;
+        expect!(expenses == 1, "{} expenses after restore, expected 1", expenses);
+        let guests = count("SELECT COUNT(*) FROM customers WHERE id = ?1 AND status = 'checked_out'", guest_id)?;
+        expect!(guests == 1, "checked-out guest missing after restore");
+        Ok(())
+    });
+
+    scenario.step("check integrity", || {
+        let report = tauri::async_runtime::block_on(crate::database_reset::check_data_integrity())?;
+        expect!(report.database_ok, "database integrity check failed: {}", report.database_messages.join("; "));
+        expect!(report.ok, "{} referenced media file(s) missing", report.media.missing_total);
+        Ok(())
+    });
+
+    scenario.steps
+}
+
+/// Run the end-to-end scenario against a throwaway database in the temp
+/// folder and report each step. The live database is never touched, and the
+/// temp folder is removed whether the scenario passes or not.
+pub fn run_self_test_scenario() -> SelfTestReport {
+    let started_at = get_current_timestamp();
+    let started = Instant::now();
+    let work_dir = std::env::temp_dir().join(format!("hotel-self-test-{}", uuid::Uuid::new_v4()));
+
+    let steps = match fs::create_dir_all(&work_dir) {
+        Ok(()) => {
+            let steps = with_db_path_override(work_dir.join("self_test.db"), || run_scenario(&work_dir));
+            // Pooled connections would keep the throwaway database open and the folder undeletable
+            close_pooled_connections();
+            steps
+        }
+        Err(e) => vec![SelfTestStep {
+            name: "create temp folder".to_string(),
+            status: "failed".to_string(),
+            duration_ms: 0,
+            detail: Some(e.to_string()),
+        }],
+    };
+    let _ = fs::remove_dir_all(&work_dir);
+
+    SelfTestReport {
+        passed: steps.iter().all(|s| s.status == "passed"),
+        started_at,
+        total_ms: started.elapsed().as_millis() as u64,
+        steps,
+    }
+}
+
+/// One-click diagnostic for support. Only available in debug builds, when
+/// HOTEL_SELF_TEST is set, or with the `self_test_enabled` setting on.
+#[tauri::command]
+pub async fn run_self_test() -> Result<SelfTestReport, String> {
+    if !self_test_enabled() {
+        return Err("Self-test is not enabled on this installation".to_string());
+    }
+    tauri::async_runtime::spawn_blocking(run_self_test_scenario)
+        .await
+        .map_err(|e| format!("Self-test did not finish: {}", e))
+}
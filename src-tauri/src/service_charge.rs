@@ -0,0 +1,144 @@
+use tauri::command;
+use rusqlite::{params, Connection};
+use crate::db::{get_db_connection, run_blocking};
+use crate::errors::AppError;
+use crate::models::{ServiceChargeDay, ServiceChargeReport, ServiceChargeSetting};
+use crate::simple_commands::{to_cents, BUSINESS_MODES};
+use crate::validation::{validate_date_format, NEGATIVE_AMOUNT};
+use crate::repository::{get_parsed_setting, get_setting_or, set_setting};
+
+fn setting_key(business_mode: &str) -> String {
+    format!("service_charge_percent_{}", business_mode)
+}
+
+fn read_percent(conn: &Connection, business_mode: &str) -> Result<f64, String> {
+    Ok(get_parsed_setting::<f64>(conn, &setting_key(business_mode))?.unwrap_or(0.0))
+}
+
+/// Service charge percent for the current business mode; 0 until one is set.
+pub fn service_charge_percent(conn: &Connection) -> Result<f64, String> {
+    read_percent(conn, &get_setting_or(conn, "business_mode", "hotel")?)
+}
+
+/// The charge on an order's items, rounded to the cent.
+pub fn service_charge_on(items_total: f64, percent: f64) -> f64 {
+    to_cents(items_total * percent / 100.0) as f64 / 100.0
+}
+
+/// Set the service charge added to food orders while in `business_mode`.
+/// Orders already placed keep the rate they were placed at.
+#[command]
+pub fn set_service_charge_percent(business_mode: String, percent: f64) -> Result<ServiceChargeSetting, AppError> {
+    let business_mode = business_mode.trim().to_lowercase();
+    if !BUSINESS_MODES.contains(&business_mode.as_str()) {
+        return Err(format!("Business mode must be one of: {}", BUSINESS_MODES.join(", ")).into());
+    }
+    if !percent.is_finite() || !(0.0..=100.0).contains(&percent) {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "percent", "Service charge must be between 0 and 100"));
+    }
+
+    let conn = get_db_connection()?;
+    set_setting(&conn, &setting_key(&business_mode), &percent.to_string())?;
+    Ok(ServiceChargeSetting { business_mode, percent })
+}
+
+/// The service charge for every business mode.
+#[command]
+pub fn get_service_charge_settings() -> Result<Vec<ServiceChargeSetting>, AppError> {
+    let conn = get_db_connection()?;
+    let mut settings = Vec::with_capacity(BUSINESS_MODES.len());
+    for mode in BUSINESS_MODES {
+        settings.push(ServiceChargeSetting {
+            business_mode: mode.to_string(),
+            percent: read_percent(&conn, mode)?,
+        });
+    }
+    Ok(settings)
+}
+
+/// Service charge on orders placed between two dates (inclusive), per day.
+/// A guest's orders count as collected once the guest has checked out, since
+/// checkout settles them.
+#[command]
+pub async fn service_charge_report(start_date: String, end_date: String) -> Result<ServiceChargeReport, AppError> {
+    run_blocking(move || {
+        validate_date_format(&start_date)?;
+        validate_date_format(&end_date)?;
+        if end_date < start_date {
+            return Err("End date cannot be before start date".into());
+        }
+        let conn = get_db_connection()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT date(s.created_at), COUNT(*), SUM(s.service_charge),
+                    SUM(CASE
+                        WHEN s.paid = 1 OR c.status = 'checked_out' THEN s.service_charge
+                        WHEN s.total_amount > 0 THEN s.service_charge * MIN(s.amount_paid / s.total_amount, 1.0)
+                        ELSE 0
+                    END)
+             FROM sales s LEFT JOIN customers c ON s.guest_id = c.id
+             WHERE s.service_charge > 0 AND s.deleted_at IS NULL AND date(s.created_at) >= ?1 AND date(s.created_at) <= ?2
+             GROUP BY 1
+             ORDER BY 1",
+        )?;
+        let days = stmt
+            .query_map(params![start_date, end_date], |row| {
+                Ok(ServiceChargeDay {
+                    date: row.get(0)?,
+                    orders: row.get(1)?,
+                    charged: row.get(2)?,
+                    collected: to_cents(row.get(3)?) as f64 / 100.0,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let total_charged = days.iter().map(|d| to_cents(d.charged)).sum::<i64>() as f64 / 100.0;
+        let total_collected = days.iter().map(|d| to_cents(d.collected)).sum::<i64>() as f64 / 100.0;
+        Ok(ServiceChargeReport { start_date, end_date, days, total_charged, total_collected })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{insert_food_order, mark_order_paid};
+    use crate::test_support::{order_item, same_amount, today, TestDb};
+
+    /// Two 5.00 coffees with a 10% service charge in hotel mode, untaxed.
+    fn charged_order() -> i64 {
+        set_service_charge_percent("hotel".to_string(), 10.0).unwrap();
+        crate::simple_commands::set_tax_enabled(false).unwrap();
+        insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Coffee", 5.0, 2)], None, None, None).unwrap().0
+    }
+
+    #[test]
+    fn orders_add_the_service_charge_and_the_receipt_shows_it() {
+        let _db = TestDb::new();
+        let order_id = charged_order();
+        let (total, charge): (f64, f64) = get_db_connection()
+            .unwrap()
+            .query_row("SELECT total_amount, service_charge FROM sales WHERE id = ?1", params![order_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert!(same_amount(charge, 1.0), "service charge is {:.2}, expected 1.00", charge);
+        assert!(same_amount(total, 11.0), "order total is {:.2}, expected 11.00", total);
+        let receipt = crate::print_templates::build_order_receipt_html(order_id, None).unwrap();
+        assert!(receipt.contains("Service charge (10.0%)"), "receipt does not show the service charge");
+    }
+
+    #[test]
+    fn the_report_counts_the_charge_once_the_order_is_paid() {
+        let _db = TestDb::new();
+        let order_id = charged_order();
+        mark_order_paid(order_id, None).unwrap();
+        let report = tauri::async_runtime::block_on(service_charge_report(today(), today())).unwrap();
+        assert!(same_amount(report.total_collected, 1.0), "collected service charge is {:.2}, expected 1.00", report.total_collected);
+    }
+
+    #[test]
+    fn the_charge_must_be_a_percentage() {
+        let _db = TestDb::new();
+        assert!(set_service_charge_percent("hotel".to_string(), 120.0).is_err(), "a service charge over 100% was accepted");
+        assert!(set_service_charge_percent("spa".to_string(), 10.0).is_err(), "an unknown business mode was accepted");
+    }
+}
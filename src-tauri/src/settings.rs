@@ -5,6 +5,10 @@ use serde_json::{json, Value};
 use rusqlite::{Connection, OptionalExtension};
 use std::collections::HashMap;
 use base64::Engine;
+use crate::models::{BackupFileInfo, LogoSource, SecurityQuestion, TableRestoreResult};
+use crate::errors::AppError;
+use crate::db::run_blocking;
+use crate::repository::{get_setting, get_setting_or, set_setting};
 
 fn is_valid_hex_color(value: &str) -> bool {
     let s = value.trim();
@@ -12,79 +16,114 @@ fn is_valid_hex_color(value: &str) -> bool {
     (s.len() == 6 || s.len() == 3) && s.chars().all(|c| c.is_ascii_hexdigit())
 }
 
-fn upsert_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
-    let now = chrono::Local::now().to_rfc3339();
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)",
-        rusqlite::params![key, value, now],
-    )
-    .map_err(|e| format!("Failed to save setting {}: {}", key, e))?;
-    Ok(())
-}
-
-fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
-    conn.query_row(
-        "SELECT value FROM settings WHERE key = ?1",
-        [key],
-        |row| row.get(0),
-    )
-    .optional()
-    .map_err(|e| format!("Failed to read setting {}: {}", key, e))
-}
-
 fn get_assets_dir() -> Result<std::path::PathBuf, String> {
     // Store assets in a protected per-user app-data directory.
     // This keeps the logo available even if the user deletes the original file.
-    let base = dirs::data_local_dir().ok_or("Failed to resolve app data directory".to_string())?;
-    Ok(base.join("hotel-app").join("assets"))
+    crate::media::media_dir(&["assets"])
 }
 
-/// Copy an uploaded logo into app_data/assets and persist its path.
+/// Write logo bytes into app_data/assets and point the business logo at them.
 /// Returns the stored logo path.
-#[command]
-pub async fn store_business_logo(source_path: String) -> Result<String, String> {
+fn save_logo(bytes: &[u8], ext: &str) -> Result<String, String> {
     use crate::db::get_db_connection;
 
+    let assets_dir = get_assets_dir()?;
+    fs::create_dir_all(&assets_dir)
+        .map_err(|e| format!("Failed to create assets directory: {}", e))?;
+
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let filename = format!("business_logo_{}.{}", timestamp, ext);
+    let dest = assets_dir.join(filename);
+
+    fs::write(&dest, bytes)
+        .map_err(|e| format!("Failed to store logo file: {}", e))?;
+
+    let dest_str = dest.to_string_lossy().to_string();
+    let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    set_setting(&conn, "business_logo_path", &dest_str)?;
+    Ok(dest_str)
+}
+
+fn read_logo_file(source_path: &str) -> Result<(Vec<u8>, std::path::PathBuf), String> {
     let source = std::path::PathBuf::from(source_path.trim());
     if !source.exists() {
-        return Err("Selected logo file does not exist".to_string());
+        return Err("Selected logo file does not exist".into());
     }
     if !source.is_file() {
-        return Err("Selected logo path is not a file".to_string());
+        return Err("Selected logo path is not a file".into());
     }
+    let bytes = fs::read(&source).map_err(|e| format!("Failed to read logo file: {}", e))?;
+    Ok((bytes, source))
+}
 
-    let assets_dir = get_assets_dir()?;
-    fs::create_dir_all(&assets_dir)
-        .map_err(|e| format!("Failed to create assets directory: {}", e))?;
+/// The file extension for image bytes the templates can show, from their
+/// leading bytes. None for anything else.
+fn image_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.len() > 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]).trim_start().to_lowercase();
+        (head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg"))).then_some("svg")
+    }
+}
 
+/// Copy an uploaded logo into app_data/assets and persist its path.
+/// Returns the stored logo path.
+#[command]
+pub async fn store_business_logo(source_path: String) -> Result<String, AppError> {
+    let (bytes, source) = read_logo_file(&source_path)?;
     let ext = source
         .extension()
         .and_then(|e| e.to_str())
         .map(|s| s.to_lowercase())
         .unwrap_or_else(|| "png".to_string());
+    save_logo(&bytes, &ext).map_err(AppError::from)
+}
 
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("business_logo_{}.{}", timestamp, ext);
-    let dest = assets_dir.join(filename);
+/// Set the logo printed on receipts and invoices from a file path or from the
+/// image bytes themselves (e.g. a drag-and-dropped file). The image is copied
+/// into app data, so it keeps working in a packaged build and after the
+/// original is moved. Returns the stored logo path.
+#[command]
+pub async fn set_receipt_logo(logo: LogoSource) -> Result<String, AppError> {
+    // Same limit as the logo preview
+    const MAX_BYTES: usize = 5 * 1024 * 1024;
 
-    fs::copy(&source, &dest)
-        .map_err(|e| format!("Failed to store logo file: {}", e))?;
+    let bytes = match logo {
+        LogoSource::Path(path) => read_logo_file(&path)?.0,
+        LogoSource::Bytes(bytes) => bytes,
+    };
+    if bytes.is_empty() {
+        return Err("Logo image is empty".into());
+    }
+    if bytes.len() > MAX_BYTES {
+        return Err("Logo image is too large (max 5MB)".into());
+    }
+    let ext = image_extension(&bytes).ok_or("Logo must be a PNG, JPEG, WebP or SVG image")?;
+    save_logo(&bytes, ext).map_err(AppError::from)
+}
 
-    let dest_str = dest.to_string_lossy().to_string();
-    let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    upsert_setting(&conn, "business_logo_path", &dest_str)?;
-    Ok(dest_str)
+/// The logo templates print, as a data URL: the uploaded logo when it's on
+/// this PC, else the one bundled with the app.
+#[command]
+pub async fn get_receipt_logo() -> Result<Option<String>, AppError> {
+    let conn = crate::db::get_db_connection()?;
+    Ok(crate::media::document_logo_data_url(&conn))
 }
 
 #[command]
-pub async fn get_business_logo_path() -> Result<Option<String>, String> {
+pub async fn get_business_logo_path() -> Result<Option<String>, AppError> {
     use crate::db::get_db_connection;
     let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    get_setting(&conn, "business_logo_path")
+    get_setting(&conn, "business_logo_path").map_err(AppError::from)
 }
 
 #[command]
-pub async fn get_business_logo_data_url() -> Result<Option<String>, String> {
+pub async fn get_business_logo_data_url() -> Result<Option<String>, AppError> {
     use crate::db::get_db_connection;
 
     let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
@@ -93,10 +132,11 @@ pub async fn get_business_logo_data_url() -> Result<Option<String>, String> {
         _ => return Ok(None),
     };
 
-    let path_buf = std::path::PathBuf::from(path.trim());
-    if !path_buf.exists() || !path_buf.is_file() {
-        return Ok(None);
-    }
+    // A restored backup may point at a logo that isn't on this PC yet
+    let path_buf = match crate::media::resolve_media_path(&conn, &path) {
+        Some(p) => p,
+        None => return Ok(None),
+    };
 
     let bytes = std::fs::read(&path_buf)
         .map_err(|e| format!("Failed to read stored logo: {}", e))?;
@@ -107,46 +147,34 @@ pub async fn get_business_logo_data_url() -> Result<Option<String>, String> {
     // Keep previews reasonably small.
     const MAX_BYTES: usize = 5 * 1024 * 1024;
     if bytes.len() > MAX_BYTES {
-        return Err("Logo file is too large to preview (max 5MB)".to_string());
+        return Err("Logo file is too large to preview (max 5MB)".into());
     }
 
-    let mime = match path_buf
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("")
-        .to_lowercase()
-        .as_str()
-    {
-        "png" => "image/png",
-        "jpg" | "jpeg" => "image/jpeg",
-        "svg" => "image/svg+xml",
-        "webp" => "image/webp",
-        _ => "application/octet-stream",
-    };
+    let mime = crate::media::guess_image_mime(&path_buf);
 
     let b64 = base64::engine::general_purpose::STANDARD.encode(bytes);
     Ok(Some(format!("data:{};base64,{}", mime, b64)))
 }
 
 #[command]
-pub async fn set_primary_color(color: String) -> Result<(), String> {
+pub async fn set_primary_color(color: String) -> Result<(), AppError> {
     use crate::db::get_db_connection;
     let trimmed = color.trim();
     if trimmed.is_empty() {
         // allow clearing
         let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-        upsert_setting(&conn, "primary_color", "")?;
+        set_setting(&conn, "primary_color", "")?;
         return Ok(());
     }
     if !is_valid_hex_color(trimmed) {
-        return Err("Primary color must be a valid hex value like #2b576d".to_string());
+        return Err("Primary color must be a valid hex value like #2b576d".into());
     }
     let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    upsert_setting(&conn, "primary_color", trimmed)
+    set_setting(&conn, "primary_color", trimmed).map_err(AppError::from)
 }
 
 #[command]
-pub async fn get_primary_color() -> Result<Option<String>, String> {
+pub async fn get_primary_color() -> Result<Option<String>, AppError> {
     use crate::db::get_db_connection;
     let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
     let value = get_setting(&conn, "primary_color")?;
@@ -157,107 +185,320 @@ pub async fn get_primary_color() -> Result<Option<String>, String> {
 }
 
 #[command]
-pub async fn set_receipt_header(value: String) -> Result<(), String> {
+pub async fn set_receipt_header(value: String) -> Result<(), AppError> {
     use crate::db::get_db_connection;
     let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    upsert_setting(&conn, "receipt_header", value.trim())
+    set_setting(&conn, "receipt_header", value.trim()).map_err(AppError::from)
 }
 
 #[command]
-pub async fn get_receipt_header() -> Result<Option<String>, String> {
+pub async fn get_receipt_header() -> Result<Option<String>, AppError> {
     use crate::db::get_db_connection;
     let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    get_setting(&conn, "receipt_header")
+    get_setting(&conn, "receipt_header").map_err(AppError::from)
 }
 
 #[command]
-pub async fn set_receipt_footer(value: String) -> Result<(), String> {
+pub async fn set_receipt_footer(value: String) -> Result<(), AppError> {
     use crate::db::get_db_connection;
     let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    upsert_setting(&conn, "receipt_footer", value.trim())
+    set_setting(&conn, "receipt_footer", value.trim()).map_err(AppError::from)
 }
 
 #[command]
-pub async fn get_receipt_footer() -> Result<Option<String>, String> {
+pub async fn get_receipt_footer() -> Result<Option<String>, AppError> {
     use crate::db::get_db_connection;
     let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
-    get_setting(&conn, "receipt_footer")
+    get_setting(&conn, "receipt_footer").map_err(AppError::from)
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-pub struct SecurityQuestion {
-    pub id: String,
-    pub question: String,
-    pub answer: String,
+/// Printer used for silent receipt printing. Blank means the system default.
+/// Printer names are per machine and are not synced to the other desk.
+#[command]
+pub async fn set_receipt_printer(name: String) -> Result<(), AppError> {
+    use crate::db::get_db_connection;
+    let name = name.trim();
+    if name.chars().any(|c| c.is_control()) {
+        return Err("Printer name cannot contain control characters".into());
+    }
+    let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    set_setting(&conn, "receipt_printer", name).map_err(AppError::from)
+}
+
+#[command]
+pub async fn get_receipt_printer() -> Result<Option<String>, AppError> {
+    use crate::db::get_db_connection;
+    let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    let value = get_setting(&conn, "receipt_printer")?;
+    Ok(value.filter(|v| !v.trim().is_empty()))
+}
+
+/// Printer for kitchen order tickets. Blank means the system default.
+#[command]
+pub async fn set_kitchen_printer(name: String) -> Result<(), AppError> {
+    use crate::db::get_db_connection;
+    let name = name.trim();
+    if name.chars().any(|c| c.is_control()) {
+        return Err("Printer name cannot contain control characters".into());
+    }
+    let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    set_setting(&conn, "kitchen_printer", name).map_err(AppError::from)
+}
+
+#[command]
+pub async fn get_kitchen_printer() -> Result<Option<String>, AppError> {
+    use crate::db::get_db_connection;
+    let conn = get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    let value = get_setting(&conn, "kitchen_printer")?;
+    Ok(value.filter(|v| !v.trim().is_empty()))
 }
 
 // Backup database to external location
 #[command]
-pub async fn backup_database(backup_path: String) -> Result<String, String> {
-    use crate::db::get_db_path;
+pub async fn backup_database(backup_path: String) -> Result<String, AppError> {
+    run_blocking(move || {
+        let backup_dir = Path::new(&backup_path);
     
-    let db_path = get_db_path();
-    let backup_dir = Path::new(&backup_path);
+        if !backup_dir.exists() {
+            return Err("Backup directory does not exist".into());
+        }
     
-    if !backup_dir.exists() {
-        return Err("Backup directory does not exist".to_string());
-    }
+        // Create timestamp for backup file
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+        let backup_file_path = write_backup(backup_dir, &timestamp)?;
     
-    // Create timestamp for backup file
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let backup_file_name = format!("business_backup_{}.db", timestamp);
-    let backup_file_path = backup_dir.join(&backup_file_name);
+        let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+        if let Some(keep) = read_backup_retention(&conn)? {
+            let removed = prune_backups(backup_dir, BackupKind::Manual, keep)?;
+            if removed > 0 {
+                println!("Removed {} old backups", removed);
+            }
+        }
+        crate::cloud_backup::upload_in_background(backup_file_path.clone());
     
-    // Copy database file
-    if let Err(e) = fs::copy(&db_path, &backup_file_path) {
-        return Err(format!("Failed to copy database: {}", e));
+        Ok(format!("Backup created successfully at: {}", backup_file_path.display()))
+    })
+    .await
+}
+
+const MAX_BACKUP_RETENTION: i64 = 365;
+
+/// Which backups a file belongs to; each kind is pruned on its own so manual
+/// backups never push out scheduled ones or the copy taken before a restore.
+#[derive(Clone, Copy, PartialEq)]
+pub enum BackupKind {
+    Manual,
+    Scheduled,
+    BeforeRestore,
+}
+
+impl BackupKind {
+    fn of(stamp: &str) -> Self {
+        if stamp.starts_with("scheduled_") {
+            BackupKind::Scheduled
+        } else if stamp.starts_with("before_restore_") {
+            BackupKind::BeforeRestore
+        } else {
+            BackupKind::Manual
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BackupKind::Manual => "manual",
+            BackupKind::Scheduled => "scheduled",
+            BackupKind::BeforeRestore => "before_restore",
+        }
+    }
+}
+
+/// The `backup_retention` setting: how many manual backups to keep per
+/// folder. None keeps them all.
+fn read_backup_retention(conn: &Connection) -> Result<Option<i64>, String> {
+    Ok(get_setting(conn, "backup_retention")?.and_then(|v| v.trim().parse().ok()).filter(|keep| *keep > 0))
+}
+
+/// The `business_backup_*.db` files in `backup_dir` with their stamps, newest first.
+fn backup_files(backup_dir: &Path) -> Result<Vec<(String, std::path::PathBuf)>, String> {
+    let mut files: Vec<(String, std::path::PathBuf)> = fs::read_dir(backup_dir)
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stamp = name.strip_prefix("business_backup_")?.strip_suffix(".db")?.to_string();
+            Some((stamp, entry.path()))
+        })
+        .filter(|(_, path)| path.is_file())
+        .collect();
+    // Stamps end in YYYYMMDD_HHMMSS, so ordering on that part puts the newest first
+    files.sort_by(|(a, _), (b, _)| stamp_time(b).cmp(stamp_time(a)).then_with(|| b.cmp(a)));
+    Ok(files)
+}
+
+fn stamp_time(stamp: &str) -> &str {
+    stamp.get(stamp.len().saturating_sub(15)..).unwrap_or(stamp)
+}
+
+/// Delete the oldest backups of `kind` in `backup_dir` beyond the newest
+/// `keep`, with their media folders and JSON exports. Returns how many went.
+pub fn prune_backups(backup_dir: &Path, kind: BackupKind, keep: i64) -> Result<usize, String> {
+    let mut removed = 0;
+    let old = backup_files(backup_dir)?
+        .into_iter()
+        .filter(|(stamp, _)| BackupKind::of(stamp) == kind)
+        .skip(keep.max(1) as usize);
+    for (stamp, db_file) in old {
+        fs::remove_file(&db_file).map_err(|e| format!("Failed to remove {}: {}", db_file.display(), e))?;
+        let media = crate::media::media_backup_dir(&db_file);
+        if media.is_dir() {
+            let _ = fs::remove_dir_all(&media);
+        }
+        let _ = fs::remove_file(backup_dir.join(format!("business_data_{}.json", stamp)));
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Keep only the newest `keep` manual backups in each backup folder, pruned
+/// after every backup. None keeps every backup.
+#[command]
+pub async fn set_backup_retention(keep: Option<i64>) -> Result<Option<i64>, AppError> {
+    if let Some(keep) = keep {
+        if !(1..=MAX_BACKUP_RETENTION).contains(&keep) {
+            return Err(format!("Keep between 1 and {} backups", MAX_BACKUP_RETENTION).into());
+        }
+    }
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    set_setting(&conn, "backup_retention", &keep.map(|k| k.to_string()).unwrap_or_default())?;
+    Ok(keep)
+}
+
+#[command]
+pub async fn get_backup_retention() -> Result<Option<i64>, AppError> {
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    Ok(read_backup_retention(&conn)?)
+}
+
+/// Encrypt backups with `passphrase` (AES-256-GCM) from now on, so copies
+/// on a USB stick or in the cloud don't expose guest details. None stops
+/// encrypting; backups already taken stay as they are.
+#[command]
+pub async fn set_backup_encryption(passphrase: Option<String>) -> Result<bool, AppError> {
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    if let Some(passphrase) = &passphrase {
+        if passphrase.chars().count() < crate::backup_crypto::MIN_PASSPHRASE_LEN {
+            return Err(AppError::invalid(
+                crate::validation::WEAK_PASSPHRASE,
+                "passphrase",
+                &format!("Passphrase must be at least {} characters", crate::backup_crypto::MIN_PASSPHRASE_LEN),
+            ));
+        }
+    }
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    set_setting(&conn, "backup_passphrase", passphrase.as_deref().unwrap_or(""))?;
+    Ok(passphrase.is_some())
+}
+
+/// Whether new backups are encrypted. The passphrase itself is never sent back.
+#[command]
+pub async fn get_backup_encryption() -> Result<bool, AppError> {
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    Ok(crate::backup_crypto::saved_passphrase(&conn)?.is_some())
+}
+
+/// The backups in `backup_path`, newest first, with their size and when
+/// they were taken.
+#[command]
+pub async fn list_backups(backup_path: String) -> Result<Vec<BackupFileInfo>, AppError> {
+    run_blocking(move || {
+        let backup_dir = Path::new(&backup_path);
+        if !backup_dir.is_dir() {
+            return Err("Backup directory does not exist".into());
+        }
+
+        let mut backups = Vec::new();
+        for (stamp, path) in backup_files(backup_dir)? {
+            let metadata = fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let created_at = chrono::NaiveDateTime::parse_from_str(stamp_time(&stamp), "%Y%m%d_%H%M%S")
+                .ok()
+                .and_then(|t| t.and_local_timezone(chrono::Local).single())
+                .or_else(|| metadata.modified().ok().map(chrono::DateTime::<chrono::Local>::from))
+                .map(|t| t.to_rfc3339());
+            let media = crate::media::media_backup_dir(&path);
+            backups.push(BackupFileInfo {
+                file_name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                path: path.to_string_lossy().to_string(),
+                kind: BackupKind::of(&stamp).as_str().to_string(),
+                size_bytes: metadata.len(),
+                has_media: media.is_dir(),
+                has_json_export: backup_dir.join(format!("business_data_{}.json", stamp)).is_file(),
+                encrypted: crate::backup_crypto::is_encrypted(&path),
+                created_at,
+            });
+        }
+        Ok(backups)
+    })
+    .await
+}
+
+/// Write `business_backup_<stamp>.db` into `backup_dir`, with the media folder
+/// beside it and a `business_data_<stamp>.json` export.
+pub fn write_backup(backup_dir: &Path, stamp: &str) -> Result<std::path::PathBuf, String> {
+    let db_path = crate::db::get_db_path();
+    let backup_file_path = backup_dir.join(format!("business_backup_{}.db", stamp));
+    
+    // Copy database file, WAL included, as one consistent snapshot
+    crate::db_encryption::copy_plaintext(&db_path, &backup_file_path)?;
+    
+    // Keep logos and signatures with the backup so it restores completely on another PC
+    match crate::media::backup_media_alongside(&backup_file_path) {
+        Ok(count) => println!("Backed up {} media files", count),
+        Err(e) => println!("Warning: media backup failed: {}", e),
     }
     
     // Also create a JSON export for data portability
-    match export_data_to_json(&backup_dir, &timestamp) {
+    match export_data_to_json(backup_dir, stamp) {
         Ok(_) => println!("JSON export created successfully"),
         Err(e) => println!("Warning: JSON export failed: {}", e),
     }
     
-    Ok(format!("Backup created successfully at: {}", backup_file_path.display()))
+    // Encrypt before anything is pruned or uploaded so guest data never leaves in plaintext
+    let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+    if let Some(passphrase) = crate::backup_crypto::saved_passphrase(&conn)? {
+        crate::backup_crypto::encrypt_backup(&backup_file_path, &passphrase)?;
+    }
+    
+    Ok(backup_file_path)
 }
 
 // Export JSON backup specifically
 #[command]
-pub async fn export_json_backup(backup_path: String) -> Result<String, String> {
-    let backup_dir = Path::new(&backup_path);
+pub async fn export_json_backup(backup_path: String) -> Result<String, AppError> {
+    run_blocking(move || {
+        let backup_dir = Path::new(&backup_path);
     
-    if !backup_dir.exists() {
-        return Err("Backup directory does not exist".to_string());
-    }
+        if !backup_dir.exists() {
+            return Err("Backup directory does not exist".into());
+        }
     
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
     
-    match export_data_to_json(&backup_dir, &timestamp) {
-        Ok(_) => Ok(format!("JSON backup created successfully at: {}", backup_dir.display())),
-        Err(e) => Err(format!("Failed to create JSON backup: {}", e)),
-    }
+        match export_data_to_json(&backup_dir, &timestamp) {
+            Ok(_) => Ok(format!("JSON backup created successfully at: {}", backup_dir.display())),
+            Err(e) => Err(format!("Failed to create JSON backup: {}", e).into()),
+        }
+    })
+    .await
 }
 
 // Export data to JSON format
 fn export_data_to_json(backup_dir: &Path, timestamp: &str) -> Result<(), String> {
-    use crate::db::get_db_path;
-    
-    let db_path = get_db_path();
-    let conn = Connection::open(&db_path)
+    let conn = crate::db::get_db_connection()
         .map_err(|e| format!("Failed to open database: {}", e))?;
     
     let mut export_data = HashMap::new();
 
-    let business_name: String = conn
-        .query_row(
-            "SELECT value FROM settings WHERE key = 'business_name'",
-            [],
-            |row| row.get(0),
-        )
-        .optional()
-        .map_err(|e| format!("Failed to read business_name: {}", e))?
-        .unwrap_or_else(|| "Business Manager".to_string());
+    let business_name = get_setting_or(&conn, "business_name", "Business Manager")?;
     
     // Export all tables
     let tables = vec![
@@ -341,87 +582,318 @@ fn export_table(conn: &Connection, table_name: &str) -> Result<Value, String> {
 
 // Restore database from backup file with comprehensive safety checks
 #[command]
-pub async fn restore_database_from_backup(backup_file_path: String) -> Result<String, String> {
-    use crate::db::get_db_path;
+pub async fn restore_database_from_backup(backup_file_path: String, passphrase: Option<String>) -> Result<String, AppError> {
+    run_blocking(move || {
+        use crate::db::get_db_path;
+    
+        // Step 1: Validate input file path
+        let backup_path = Path::new(&backup_file_path);
+        if !backup_path.exists() {
+            return Err("Backup file does not exist. Please check the file path.".into());
+        }
     
-    // Step 1: Validate input file path
-    let backup_path = Path::new(&backup_file_path);
-    if !backup_path.exists() {
-        return Err("Backup file does not exist. Please check the file path.".to_string());
-    }
+        // Check if it's actually a database file
+        if let Some(extension) = backup_path.extension() {
+            if extension != "db" {
+                return Err("File must have .db extension to be a valid database backup.".into());
+            }
+        } else {
+            return Err("Backup file must have .db extension.".into());
+        }
+    
+        let db_path = get_db_path();
     
-    // Check if it's actually a database file
-    if let Some(extension) = backup_path.extension() {
-        if extension != "db" {
-            return Err("File must have .db extension to be a valid database backup.".to_string());
+        // Step 2: Create backup directory and backup current database
+        let current_backup_dir = db_path.parent().ok_or("Failed to get app directory")?.join("backups");
+        if !current_backup_dir.exists() {
+            fs::create_dir_all(&current_backup_dir)
+                .map_err(|e| format!("Failed to create backup directory: {}", e))?;
         }
-    } else {
-        return Err("Backup file must have .db extension.".to_string());
-    }
     
-    let db_path = get_db_path();
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+        let current_backup_name = format!("business_backup_before_restore_{}.db", timestamp);
+        let current_backup_path = current_backup_dir.join(&current_backup_name);
     
-    // Step 2: Create backup directory and backup current database
-    let current_backup_dir = db_path.parent().ok_or("Failed to get app directory")?.join("backups");
-    if !current_backup_dir.exists() {
-        fs::create_dir_all(&current_backup_dir)
-            .map_err(|e| format!("Failed to create backup directory: {}", e))?;
-    }
+        // Encrypted backups are restored from a decrypted copy, deleted once the restore is done
+        let decrypted = decrypt_for_restore(backup_path, passphrase, &current_backup_dir.join(format!("decrypted_{}", timestamp)))?;
+        let source = decrypted.as_ref().map_or(backup_path, |d| d.db_file.as_path());
     
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let current_backup_name = format!("business_backup_before_restore_{}.db", timestamp);
-    let current_backup_path = current_backup_dir.join(&current_backup_name);
+        // Backup current database first (safety net)
+        crate::db_encryption::copy_plaintext(&db_path, &current_backup_path)
+            .map_err(|e| format!("Failed to backup current database: {}", e))?;
     
-    // Backup current database first (safety net)
-    fs::copy(&db_path, &current_backup_path)
-        .map_err(|e| format!("Failed to backup current database: {}", e))?;
+        // Step 3: Comprehensive validation of backup file
+        let backup_validation_result = validate_backup_database(source);
+        if let Err(validation_error) = backup_validation_result {
+            return Err(format!("Backup file validation failed: {}", validation_error).into());
+        }
     
-    // Step 3: Comprehensive validation of backup file
-    let backup_validation_result = validate_backup_database(&backup_path);
-    if let Err(validation_error) = backup_validation_result {
-        return Err(format!("Backup file validation failed: {}", validation_error));
-    }
+        // Step 4: Test restore in a temporary location first
+        let temp_restore_path = current_backup_dir.join(format!("temp_restore_test_{}.db", timestamp));
+        fs::copy(source, &temp_restore_path)
+            .map_err(|e| format!("Failed to create temporary restore test: {}", e))?;
     
-    // Step 4: Test restore in a temporary location first
-    let temp_restore_path = current_backup_dir.join(format!("temp_restore_test_{}.db", timestamp));
-    fs::copy(&backup_path, &temp_restore_path)
-        .map_err(|e| format!("Failed to create temporary restore test: {}", e))?;
+        // Test if the restored database can be opened and basic operations work
+        let test_result = test_database_functionality(&temp_restore_path);
     
-    // Test if the restored database can be opened and basic operations work
-    let test_result = test_database_functionality(&temp_restore_path);
+        // Clean up temp file
+        let _ = fs::remove_file(&temp_restore_path);
     
-    // Clean up temp file
-    let _ = fs::remove_file(&temp_restore_path);
+        if let Err(test_error) = test_result {
+            return Err(format!("Backup file functionality test failed: {}. Your current database is safe.", test_error).into());
+        }
     
-    if let Err(test_error) = test_result {
-        return Err(format!("Backup file functionality test failed: {}. Your current database is safe.", test_error));
-    }
+        // Step 5: Perform the actual restore (we know it's safe now). It is
+        // copied in under the write lock, so commands writing meanwhile wait for it.
+        // The backup is plaintext; this encrypts it again if the live database is encrypted
+        crate::db_encryption::restore_plaintext(source, &db_path)
+            .map_err(|e| {
+                // If this fails, try to restore the original
+                let _ = crate::db_encryption::restore_plaintext(&current_backup_path, &db_path);
+                format!("{}. Original database restored.", e)
+            })?;
+    
+        // Step 6: Final verification of restored database
+        let final_verification = test_database_functionality(&db_path);
+        if let Err(verification_error) = final_verification {
+            // Critical error - restore the original database immediately
+            crate::db_encryption::restore_plaintext(&current_backup_path, &db_path)
+                .map_err(|e| format!("CRITICAL ERROR: Failed to restore original database: {}", e))?;
+            return Err(format!("Restored database verification failed: {}. Original database has been restored.", verification_error).into());
+        }
     
-    // Step 5: Perform the actual restore (we know it's safe now)
-    fs::copy(&backup_path, &db_path)
-        .map_err(|e| {
-            // If this fails, try to restore the original
-            let _ = fs::copy(&current_backup_path, &db_path);
-            format!("Failed to restore database: {}. Original database restored.", e)
-        })?;
+        // Step 7: Bring back media saved with the backup and flag anything still missing
+        let mut media_note = String::new();
+        if let Err(e) = crate::media::restore_media_alongside(source) {
+            media_note.push_str(&format!("\n⚠️ Media files could not be restored: {}", e));
+        }
+        if let Ok(conn) = crate::db::get_db_connection() {
+            if let Ok(report) = crate::media::media_integrity_report(&conn) {
+                if report.missing_total > 0 {
+                    media_note.push_str(&format!(
+                        "\n⚠️ {} media file(s) referenced by this backup are missing. Use \"Relink media folder\" to point at a copy.",
+                        report.missing_total
+                    ));
+                }
+            }
+        }
     
-    // Step 6: Final verification of restored database
-    let final_verification = test_database_functionality(&db_path);
-    if let Err(verification_error) = final_verification {
-        // Critical error - restore the original database immediately
-        fs::copy(&current_backup_path, &db_path)
-            .map_err(|e| format!("CRITICAL ERROR: Failed to restore original database: {}", e))?;
-        return Err(format!("Restored database verification failed: {}. Original database has been restored.", verification_error));
+        Ok(format!(
+            "✅ Database restored successfully!\n\
+             📁 Restored from: {}\n\
+             💾 Previous database backed up to: {}\n\
+             🔍 All safety checks passed.{}",
+            backup_path.display(),
+            current_backup_path.display(),
+            media_note
+        ))
+    })
+    .await
+}
+
+/// A decrypted copy of `backup_path` in `scratch` when it is encrypted,
+/// using `passphrase` or else the saved one.
+fn decrypt_for_restore(
+    backup_path: &Path,
+    passphrase: Option<String>,
+    scratch: &Path,
+) -> Result<Option<crate::backup_crypto::DecryptedBackup>, AppError> {
+    if !crate::backup_crypto::is_encrypted(backup_path) {
+        return Ok(None);
     }
-    
-    Ok(format!(
-        "✅ Database restored successfully!\n\
-         📁 Restored from: {}\n\
-         💾 Previous database backed up to: {}\n\
-         🔍 All safety checks passed.",
-        backup_path.display(),
-        current_backup_path.display()
-    ))
+    let passphrase = match passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => Some(passphrase),
+        None => {
+            let conn = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+            crate::backup_crypto::saved_passphrase(&conn)?
+        }
+    };
+    let passphrase = passphrase.ok_or_else(|| {
+        AppError::invalid(
+            crate::validation::BACKUP_PASSPHRASE_REQUIRED,
+            "passphrase",
+            "This backup is encrypted. Enter the passphrase it was taken with.",
+        )
+    })?;
+    Ok(Some(crate::backup_crypto::decrypt_backup(backup_path, &passphrase, scratch)?))
+}
+
+// Tables that can be restored on their own, the columns that identify a row
+// across databases, and columns that describe what's happening right now
+// (who is in a room, what is in stock) and so are never taken from a backup.
+const RESTORABLE_TABLES: &[(&str, &[&str], &[&str])] = &[
+    ("settings", &["key"], &[]),
+    ("resources", &["number"], &["is_occupied", "guest_id"]),
+    ("menu_items", &["name"], &["stock_quantity"]),
+    ("inventory_items", &["name"], &["quantity"]),
+    ("suppliers", &["name"], &[]),
+    ("companies", &["name"], &[]),
+    ("taxes", &["name"], &[]),
+    ("tags", &["name"], &[]),
+    ("dining_tables", &["number"], &["status", "opened_at"]),
+    ("rate_overrides", &["room_type", "date"], &[]),
+    ("room_type_rate_bounds", &["room_type"], &[]),
+    ("business_hours", &["weekday"], &[]),
+];
+
+fn table_columns(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name FROM pragma_table_info(?1)")
+        .map_err(|e| format!("Failed to read columns of {}: {}", table, e))?;
+    let columns = stmt
+        .query_map([table], |row| row.get(0))
+        .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())
+        .map_err(|e| format!("Failed to read columns of {}: {}", table, e))?;
+    Ok(columns)
+}
+
+/// Merge one table from `backup` into `live`: rows whose natural key is
+/// already here are overwritten, the rest are added with new ids.
+fn merge_table(
+    backup: &Connection,
+    live: &Connection,
+    table: &str,
+    keys: &[&str],
+    live_only: &[&str],
+) -> Result<TableRestoreResult, String> {
+    let backup_columns = table_columns(backup, table)?;
+    if backup_columns.is_empty() {
+        return Err(format!("The backup has no {} table", table));
+    }
+    // Columns added since the backup was taken keep their defaults
+    let live_columns = table_columns(live, table)?;
+    let columns: Vec<&String> = backup_columns
+        .iter()
+        .filter(|c| live_columns.contains(c) && c.as_str() != "id" && !live_only.contains(&c.as_str()))
+        .collect();
+    let key_positions: Vec<usize> = keys
+        .iter()
+        .map(|k| columns.iter().position(|c| c.as_str() == *k).ok_or(format!("The backup's {} table has no {} column", table, k)))
+        .collect::<Result<_, _>>()?;
+    let values: Vec<&String> = columns.iter().copied().filter(|c| !keys.contains(&c.as_str())).collect();
+
+    let quoted = |names: &[&String]| names.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>();
+    let key_match = keys.iter().enumerate().map(|(i, k)| format!("\"{}\" = ?{}", k, i + 1)).collect::<Vec<_>>().join(" AND ");
+    let find_sql = format!("SELECT rowid FROM {} WHERE {}", table, key_match);
+    let insert_sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        quoted(&columns).join(", "),
+        (1..=columns.len()).map(|i| format!("?{}", i)).collect::<Vec<_>>().join(", ")
+    );
+    let update_sql = format!(
+        "UPDATE {} SET {} WHERE rowid = ?{}",
+        table,
+        values.iter().enumerate().map(|(i, c)| format!("\"{}\" = ?{}", c, i + 1)).collect::<Vec<_>>().join(", "),
+        values.len() + 1
+    );
+
+    let mut stmt = backup
+        .prepare(&format!("SELECT {} FROM {}", quoted(&columns).join(", "), table))
+        .map_err(|e| format!("Failed to read {} from the backup: {}", table, e))?;
+    let rows = stmt
+        .query_map([], |row| (0..columns.len()).map(|i| row.get::<_, rusqlite::types::Value>(i)).collect::<Result<Vec<_>, _>>())
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read {} from the backup: {}", table, e))?;
+
+    let mut result = TableRestoreResult { table: table.to_string(), inserted: 0, updated: 0, skipped: 0 };
+    for row in rows {
+        let key: Vec<&rusqlite::types::Value> = key_positions.iter().map(|&i| &row[i]).collect();
+        if table == "settings" {
+            let syncable = matches!(key[0], rusqlite::types::Value::Text(k) if crate::sync::is_syncable_setting(k));
+            if !syncable {
+                result.skipped += 1;
+                continue;
+            }
+        }
+        let existing: Option<i64> = live
+            .query_row(&find_sql, rusqlite::params_from_iter(key), |r| r.get(0))
+            .optional()
+            .map_err(|e| format!("Failed to look up {} row: {}", table, e))?;
+        match existing {
+            // Nothing but the key to copy, e.g. a tag
+            Some(_) if values.is_empty() => result.skipped += 1,
+            Some(rowid) => {
+                let params = columns
+                    .iter()
+                    .zip(&row)
+                    .filter(|(c, _)| !keys.contains(&c.as_str()))
+                    .map(|(_, v)| v.clone())
+                    .chain(std::iter::once(rusqlite::types::Value::Integer(rowid)));
+                live.execute(&update_sql, rusqlite::params_from_iter(params))
+                    .map_err(|e| format!("Failed to update {} row: {}", table, e))?;
+                result.updated += 1;
+            }
+            None => {
+                live.execute(&insert_sql, rusqlite::params_from_iter(&row))
+                    .map_err(|e| format!("Failed to add {} row: {}", table, e))?;
+                result.inserted += 1;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// The tables `restore_tables_from_backup` can bring back on their own.
+#[command]
+pub async fn get_restorable_tables() -> Result<Vec<String>, AppError> {
+    Ok(RESTORABLE_TABLES.iter().map(|(table, _, _)| table.to_string()).collect())
+}
+
+/// Restore only `tables` from a backup, merged into the live database by
+/// natural key (room number, menu item name, ...) instead of replacing the
+/// whole file. Everything else, including guests and sales, is untouched.
+/// A copy of the current database is kept first, as with a full restore.
+#[command]
+pub async fn restore_tables_from_backup(
+    backup_file_path: String,
+    tables: Vec<String>,
+    passphrase: Option<String>,
+) -> Result<Vec<TableRestoreResult>, AppError> {
+    run_blocking(move || {
+        let backup_path = Path::new(&backup_file_path);
+        if !backup_path.is_file() {
+            return Err("Backup file does not exist. Please check the file path.".into());
+        }
+        if tables.is_empty() {
+            return Err(AppError::invalid(crate::validation::EMPTY_FIELD, "tables", "Choose at least one table to restore"));
+        }
+        let mut selected = Vec::new();
+        for name in &tables {
+            let table = RESTORABLE_TABLES.iter().find(|(table, _, _)| *table == name.trim()).ok_or_else(|| {
+                let names: Vec<&str> = RESTORABLE_TABLES.iter().map(|(table, _, _)| *table).collect();
+                format!("'{}' can't be restored on its own. Choose from: {}", name.trim(), names.join(", "))
+            })?;
+            if !selected.contains(&table) {
+                selected.push(table);
+            }
+        }
+
+        let db_path = crate::db::get_db_path();
+        let current_backup_dir = db_path.parent().ok_or("Failed to get app directory")?.join("backups");
+        fs::create_dir_all(&current_backup_dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+
+        let decrypted = decrypt_for_restore(backup_path, passphrase, &current_backup_dir.join(format!("decrypted_{}", timestamp)))?;
+        let source = decrypted.as_ref().map_or(backup_path, |d| d.db_file.as_path());
+        validate_backup_database(source).map_err(|e| format!("Backup file validation failed: {}", e))?;
+
+        // Safety net, as for a full restore
+        crate::db_encryption::copy_plaintext(&db_path, &current_backup_dir.join(format!("business_backup_before_restore_{}.db", timestamp)))
+            .map_err(|e| format!("Failed to backup current database: {}", e))?;
+
+        let backup = Connection::open_with_flags(source, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("Cannot open backup file as SQLite database: {}", e))?;
+        let live = crate::db::get_db_connection().map_err(|e| format!("Failed to open database: {}", e))?;
+        let tx = live.unchecked_transaction().map_err(|e| e.to_string())?;
+        let results = selected
+            .iter()
+            .map(|(table, keys, live_only)| merge_table(&backup, &tx, table, keys, live_only))
+            .collect::<Result<Vec<_>, _>>()?;
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(results)
+    })
+    .await
 }
 
 // Comprehensive validation function for backup databases
@@ -522,6 +994,8 @@ fn validate_backup_database(backup_path: &Path) -> Result<(), String> {
 fn test_database_functionality(db_path: &Path) -> Result<(), String> {
     let test_conn = Connection::open(&db_path)
         .map_err(|e| format!("Cannot open database for testing: {}", e))?;
+    crate::db_encryption::apply_key(&test_conn, db_path)
+        .map_err(|e| format!("Cannot open database for testing: {}", e))?;
     
     // Test basic queries on essential tables
     let test_queries = vec![
@@ -567,7 +1041,7 @@ fn test_database_functionality(db_path: &Path) -> Result<(), String> {
 
 // Get security question for reset validation
 #[command]
-pub async fn get_reset_security_question() -> Result<SecurityQuestion, String> {
+pub async fn get_reset_security_question() -> Result<SecurityQuestion, AppError> {
     // For now, return a hardcoded security question
     // In a real app, this might be stored in the database or config
     Ok(SecurityQuestion {
@@ -579,7 +1053,7 @@ pub async fn get_reset_security_question() -> Result<SecurityQuestion, String> {
 
 // Validate security question answer
 #[command]
-pub async fn validate_security_answer(question_id: String, answer: String) -> Result<bool, String> {
+pub async fn validate_security_answer(question_id: String, answer: String) -> Result<bool, AppError> {
     // Get the security question
     let security_question = get_reset_security_question().await?;
     
@@ -593,118 +1067,129 @@ pub async fn validate_security_answer(question_id: String, answer: String) -> Re
 
 // Reset all application data with automatic backup
 #[command]
-pub async fn reset_application_data() -> Result<String, String> {
-    use crate::db::get_db_path;
-    
-    // Create automatic backup before reset
-    let backup_result = create_automatic_backup_before_reset().await;
-    match backup_result {
-        Ok(backup_path) => println!("Automatic backup created at: {}", backup_path),
-        Err(e) => return Err(format!("Failed to create backup before reset: {}", e)),
-    }
-    
-    let db_path = get_db_path();
-    let conn = Connection::open(&db_path)
-        .map_err(|e| format!("Failed to open database: {}", e))?;
-    
-    // Verify database integrity before reset
-    let integrity_check: Result<String, _> = conn.query_row(
-        "PRAGMA integrity_check",
-        [],
-        |row| row.get(0)
-    );
-    
-    match integrity_check {
-        Ok(result) if result != "ok" => {
-            return Err(format!("Database integrity check failed: {}", result));
-        },
-        Err(e) => {
-            return Err(format!("Failed to check database integrity: {}", e));
-        },
-        _ => {} // OK, continue
-    }
-    
-    // List of tables to clear (preserve structure, clear data)
-    // This comment is for reference - tables are handled in the correct order below
+pub async fn reset_application_data() -> Result<String, AppError> {
+    run_blocking(move || {
+        // Create automatic backup before reset
+        let backup_result = create_automatic_backup_before_reset();
+        match backup_result {
+            Ok(backup_path) => println!("Automatic backup created at: {}", backup_path),
+            Err(e) => return Err(format!("Failed to create backup before reset: {}", e).into()),
+        }
     
-    // Start transaction
-    let tx = conn.unchecked_transaction()
-        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let conn = crate::db::get_db_connection()
+            .map_err(|e| format!("Failed to open database: {}", e))?;
     
-    // Disable foreign key constraints temporarily for reset
-    tx.execute("PRAGMA foreign_keys = OFF", [])
-        .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
+        // Verify database integrity before reset
+        let integrity_check: Result<String, _> = conn.query_row(
+            "PRAGMA integrity_check",
+            [],
+            |row| row.get(0)
+        );
     
-    // Clear data tables in correct order (child tables first)
-    let tables_to_clear = vec![
-        "sale_items",     // Clear child table first
-        "sales",          // Then parent sales
-        "expenses",       // Independent table
-        "customers"       // Finally customers table
-    ];
+        match integrity_check {
+            Ok(result) if result != "ok" => {
+                return Err(format!("Database integrity check failed: {}", result).into());
+            },
+            Err(e) => {
+                return Err(format!("Failed to check database integrity: {}", e).into());
+            },
+            _ => {} // OK, continue
+        }
     
-    for table in tables_to_clear {
-        tx.execute(&format!("DELETE FROM {}", table), [])
-            .map_err(|e| format!("Failed to clear table {}: {}", table, e))?;
+        // List of tables to clear (preserve structure, clear data)
+        // This comment is for reference - tables are handled in the correct order below
+    
+        // Start transaction
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    
+        // Disable foreign key constraints temporarily for reset
+        tx.execute("PRAGMA foreign_keys = OFF", [])
+            .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
+    
+        // Clear data tables in correct order (child tables first)
+        let tables_to_clear = vec![
+            "sale_item_modifiers", // Clear child tables first
+            "sale_item_components",
+            "guest_charges",
+            "room_transfers",
+            "company_charges",
+            "company_payments",
+            "sale_items",
+            "sales",          // Then parent sales
+            "expenses",       // Independent table
+            "reservations",   // Bookings hold rooms, which are reset below
+            "customers",      // Then the stays
+            "guest_profiles"  // Finally the guests they belong to
+        ];
+    
+        for table in tables_to_clear {
+            tx.execute(&format!("DELETE FROM {}", table), [])
+                .map_err(|e| format!("Failed to clear table {}: {}", table, e))?;
         
-        // Reset auto-increment
-        tx.execute(&format!("DELETE FROM sqlite_sequence WHERE name = '{}'", table), [])
-            .map_err(|e| format!("Failed to reset sequence for {}: {}", table, e))?;
-    }
-    
-    // Reset specific tables with default data
-    let tables_to_reset = vec![
-        "resources",
-        "menu_items"
-    ];
+            // Reset auto-increment
+            tx.execute(&format!("DELETE FROM sqlite_sequence WHERE name = '{}'", table), [])
+                .map_err(|e| format!("Failed to reset sequence for {}: {}", table, e))?;
+        }
     
-    for table in tables_to_reset {
-        tx.execute(&format!("DELETE FROM {}", table), [])
-            .map_err(|e| format!("Failed to clear table {}: {}", table, e))?;
+        // Reset specific tables with default data
+        let tables_to_reset = vec![
+            "resources",
+            "combo_items", // Combos are made of menu items
+            "combos",
+            "menu_price_rules",
+            "menu_items"
+        ];
+    
+        for table in tables_to_reset {
+            tx.execute(&format!("DELETE FROM {}", table), [])
+                .map_err(|e| format!("Failed to clear table {}: {}", table, e))?;
         
-        // Reset auto-increment
-        tx.execute(&format!("DELETE FROM sqlite_sequence WHERE name = '{}'", table), [])
-            .map_err(|e| format!("Failed to reset sequence for {}: {}", table, e))?;
-    }
+            // Reset auto-increment
+            tx.execute(&format!("DELETE FROM sqlite_sequence WHERE name = '{}'", table), [])
+                .map_err(|e| format!("Failed to reset sequence for {}: {}", table, e))?;
+        }
     
-    // Re-seed with default data
-    match seed_default_data(&tx) {
-        Ok(_) => {},
-        Err(e) => {
-            return Err(format!("Failed to seed default data: {}", e));
+        // Re-seed with default data
+        match seed_default_data(&tx) {
+            Ok(_) => {},
+            Err(e) => {
+                return Err(format!("Failed to seed default data: {}", e).into());
+            }
         }
-    }
     
-    // Re-enable foreign key constraints
-    tx.execute("PRAGMA foreign_keys = ON", [])
-        .map_err(|e| format!("Failed to re-enable foreign keys: {}", e))?;
+        // Re-enable foreign key constraints
+        tx.execute("PRAGMA foreign_keys = ON", [])
+            .map_err(|e| format!("Failed to re-enable foreign keys: {}", e))?;
     
-    // Commit transaction
-    tx.commit()
-        .map_err(|e| format!("Failed to commit reset transaction: {}", e))?;
+        // Commit transaction
+        tx.commit()
+            .map_err(|e| format!("Failed to commit reset transaction: {}", e))?;
     
-    // Verify database integrity after reset
-    let final_integrity_check: Result<String, _> = conn.query_row(
-        "PRAGMA integrity_check",
-        [],
-        |row| row.get(0)
-    );
+        // Verify database integrity after reset
+        let final_integrity_check: Result<String, _> = conn.query_row(
+            "PRAGMA integrity_check",
+            [],
+            |row| row.get(0)
+        );
     
-    match final_integrity_check {
-        Ok(result) if result != "ok" => {
-            return Err(format!("Database integrity check failed after reset: {}", result));
-        },
-        Err(e) => {
-            return Err(format!("Failed to check database integrity after reset: {}", e));
-        },
-        _ => {} // OK
-    }
+        match final_integrity_check {
+            Ok(result) if result != "ok" => {
+                return Err(format!("Database integrity check failed after reset: {}", result).into());
+            },
+            Err(e) => {
+                return Err(format!("Failed to check database integrity after reset: {}", e).into());
+            },
+            _ => {} // OK
+        }
     
-    Ok("Application data has been reset successfully. Backup created automatically.".to_string())
+        Ok("Application data has been reset successfully. Backup created automatically.".to_string())
+    })
+    .await
 }
 
 // Create automatic backup before reset
-async fn create_automatic_backup_before_reset() -> Result<String, String> {
+fn create_automatic_backup_before_reset() -> Result<String, String> {
     use crate::db::get_db_path;
     
     let db_path = get_db_path();
@@ -724,8 +1209,7 @@ async fn create_automatic_backup_before_reset() -> Result<String, String> {
     let backup_file_path = backup_dir.join(&backup_file_name);
     
     // Copy database file
-    fs::copy(&db_path, &backup_file_path)
-        .map_err(|e| format!("Failed to copy database: {}", e))?;
+    crate::db_encryption::copy_plaintext(&db_path, &backup_file_path)?;
     
     // Also create a JSON export for data portability
     if let Err(e) = export_data_to_json(&backup_dir, &format!("before_reset_{}", timestamp)) {
@@ -746,7 +1230,7 @@ fn seed_default_data(_conn: &rusqlite::Transaction) -> Result<(), String> {
 
 // Find latest backup file automatically
 #[command]
-pub async fn select_backup_file() -> Result<String, String> {
+pub async fn select_backup_file() -> Result<String, AppError> {
     use crate::db::get_db_path;
     
     let db_path = get_db_path();
@@ -789,12 +1273,12 @@ pub async fn select_backup_file() -> Result<String, String> {
     
     // If no backups found, provide helpful error message
     let user_dir = std::env::var("USERPROFILE").unwrap_or_else(|_| "C:\\Users\\Default".to_string());
-    Err(format!("No backup files found. Please check these locations:\n1. App backup directory: {}\\backups\n2. Desktop: {}\\Desktop\n3. Downloads folder", app_dir.display(), user_dir))
+    Err(format!("No backup files found. Please check these locations:\n1. App backup directory: {}\\backups\n2. Desktop: {}\\Desktop\n3. Downloads folder", app_dir.display(), user_dir).into())
 }
 
 // Open file browser to manually select backup file
 #[command] 
-pub async fn browse_backup_file() -> Result<String, String> {
+pub async fn browse_backup_file() -> Result<String, AppError> {
     use crate::db::get_db_path;
     
     let db_path = get_db_path();
@@ -825,7 +1309,7 @@ pub async fn browse_backup_file() -> Result<String, String> {
     }
     
     if available_backups.is_empty() {
-        Err("No backup files found. Please use the 'Find Latest' button to automatically find your latest backup, or manually enter the full path to your backup file.\n\nBackup files should be named like 'business_backup_YYYYMMDD_HHMMSS.db'".to_string())
+        Err("No backup files found. Please use the 'Find Latest' button to automatically find your latest backup, or manually enter the full path to your backup file.\n\nBackup files should be named like 'business_backup_YYYYMMDD_HHMMSS.db'".into())
     } else {
         // Sort by modification time and show available files
         let mut backup_info = String::from("✅ Found backup files! Please copy and paste one of these paths:\n\n");
@@ -844,6 +1328,135 @@ pub async fn browse_backup_file() -> Result<String, String> {
         
         backup_info.push_str("💡 Instructions:\n1. Copy one of the paths above\n2. Paste it in the text field\n3. Or use 'Find Latest' for automatic selection");
         
-        Err(backup_info)
+        Err(backup_info.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::async_runtime::block_on;
+    use crate::db::get_db_connection;
+    use crate::test_support::{add_test_room, count, days_from_today, same_amount, TestDb, TEST_RATE};
+
+    /// Back the test database up into `name` in the test folder and return
+    /// the backups there, newest first.
+    fn back_up(db: &TestDb, name: &str) -> Vec<BackupFileInfo> {
+        let dir = db.dir().join(name).to_string_lossy().to_string();
+        fs::create_dir_all(&dir).unwrap();
+        block_on(backup_database(dir.clone())).unwrap();
+        block_on(list_backups(dir)).unwrap()
+    }
+
+    #[test]
+    fn backups_are_encrypted_with_the_saved_passphrase() {
+        let db = TestDb::new();
+        block_on(set_backup_encryption(Some("correct horse".to_string()))).unwrap();
+        let backups = back_up(&db, "encrypted");
+        let file = backups.first().expect("no encrypted backup written");
+        let head = fs::read(&file.path).unwrap();
+        assert!(file.encrypted && !head.starts_with(b"SQLite format 3"), "backup was written in plaintext");
+    }
+
+    #[test]
+    fn an_encrypted_backup_restores_only_with_its_passphrase() {
+        let db = TestDb::new();
+        block_on(set_backup_encryption(Some("correct horse".to_string()))).unwrap();
+        let backups = back_up(&db, "encrypted");
+        // Without the saved passphrase the backup can only be restored by entering it
+        block_on(set_backup_encryption(None)).unwrap();
+        let path = backups.first().expect("no encrypted backup written").path.clone();
+        let missing = block_on(restore_database_from_backup(path.clone(), None));
+        assert!(missing.is_err(), "encrypted backup restored without a passphrase");
+        let wrong = block_on(restore_database_from_backup(path.clone(), Some("wrong horse".to_string())));
+        assert!(wrong.is_err(), "encrypted backup restored with the wrong passphrase");
+        block_on(restore_database_from_backup(path, Some("correct horse".to_string()))).unwrap();
+    }
+
+    #[test]
+    fn a_backup_holds_writes_still_in_the_wal() {
+        let db = TestDb::new();
+        // A connection kept open stops the WAL being checkpointed into the file
+        let open = get_db_connection().unwrap();
+        add_test_room("101");
+        let backup = back_up(&db, "backups").remove(0).path;
+        drop(open);
+        let copy = Connection::open_with_flags(&backup, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY).unwrap();
+        let rooms: i64 = copy.query_row("SELECT COUNT(*) FROM resources WHERE number = '101'", [], |row| row.get(0)).unwrap();
+        assert_eq!(rooms, 1, "room added before the backup is missing from it");
+    }
+
+    #[test]
+    fn a_restore_reaches_connections_already_open() {
+        let db = TestDb::new();
+        add_test_room("101");
+        let backup = back_up(&db, "backups").remove(0).path;
+        let open = get_db_connection().unwrap();
+        add_test_room("102");
+        block_on(restore_database_from_backup(backup, None)).unwrap();
+        let rooms: Vec<String> = open
+            .prepare("SELECT number FROM resources ORDER BY number")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rooms, vec!["101".to_string()], "a connection open during the restore still sees the old database");
+    }
+
+    #[test]
+    fn restoring_a_table_leaves_the_rest_of_the_database_alone() {
+        let db = TestDb::new();
+        let room_id = add_test_room("101");
+        let backup = back_up(&db, "backups").remove(0).path;
+        let conn = get_db_connection().unwrap();
+        conn.execute("UPDATE resources SET daily_rate = 1 WHERE id = ?1", [room_id]).unwrap();
+        crate::expense_categories::add_expense_category("Laundry".to_string()).unwrap();
+        drop(conn);
+
+        let results = block_on(restore_tables_from_backup(backup, vec!["resources".to_string()], None)).unwrap();
+        assert!(results.len() == 1 && results[0].updated >= 1, "rooms were not merged from the backup");
+        let conn = get_db_connection().unwrap();
+        let rate: f64 = conn.query_row("SELECT daily_rate FROM resources WHERE id = ?1", [room_id], |row| row.get(0)).unwrap();
+        assert!(same_amount(rate, TEST_RATE), "room rate is {:.2} after restoring rooms, expected {:.2}", rate, TEST_RATE);
+        let laundry: i64 = conn
+            .query_row("SELECT COUNT(*) FROM expense_categories WHERE name = 'Laundry'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(laundry, 1, "restoring rooms dropped a category added after the backup");
+    }
+
+    #[test]
+    fn sales_are_not_restored_on_their_own() {
+        let db = TestDb::new();
+        let backup = back_up(&db, "backups").remove(0).path;
+        let sales = block_on(restore_tables_from_backup(backup, vec!["sales".to_string()], None));
+        assert!(sales.is_err(), "sales were restored on their own");
+    }
+
+    #[test]
+    fn retention_prunes_old_manual_backups_but_not_scheduled_ones() {
+        let db = TestDb::new();
+        let dir = db.dir().join("backups");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("business_backup_20000101_000000.db"), b"old manual").unwrap();
+        fs::write(dir.join("business_backup_scheduled_20000101_000000.db"), b"old scheduled").unwrap();
+        block_on(set_backup_retention(Some(1))).unwrap();
+        let backups = back_up(&db, "backups");
+        let kinds: Vec<&str> = backups.iter().map(|b| b.kind.as_str()).collect();
+        assert!(kinds.len() == 2 && kinds.contains(&"manual") && kinds.contains(&"scheduled"), "unexpected backups after pruning: {:?}", kinds);
+        assert!(!dir.join("business_backup_20000101_000000.db").exists(), "the old manual backup was kept");
+        assert!(backups.iter().all(|b| b.size_bytes > 0 && b.created_at.is_some()), "backups listed without a size or date");
+    }
+
+    #[test]
+    fn resetting_clears_reservations_along_with_the_rooms() {
+        let _db = TestDb::new();
+        let room_id = add_test_room("101");
+        crate::simple_commands::add_reservation(
+            room_id, "Booked Guest".to_string(), None, days_from_today(1), days_from_today(3), None, None, None, None, None,
+        )
+        .unwrap();
+        block_on(reset_application_data()).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM reservations WHERE id > ?1", 0), 0, "reservations survived the reset");
     }
 }
@@ -1,66 +1,97 @@
 use crate::models::*;
+use crate::errors::AppError;
+use crate::validation::{EMPTY_FIELD, GUEST_NOT_ACTIVE, MENU_ITEM_NOT_FOUND, NEGATIVE_AMOUNT, ORDER_NOT_FOUND, ROOM_NOT_FOUND, ROOM_NUMBER_EXISTS, ROOM_OCCUPIED};
 use crate::db::*;
-use rusqlite::params;
-use tauri::command;
+use crate::date_checks::{check_date_plausibility, log_acknowledged_dates};
+use crate::business_hours::{check_after_hours, count_after_hours};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::notifications::NotificationSubject;
+use crate::events;
+use crate::paging::ListSpec;
+use crate::repository::{get_parsed_setting, get_setting, get_setting_or, set_or_clear_setting, set_setting};
+use rusqlite::{params, OptionalExtension};
+use tauri::{command, Emitter};
 use chrono::{NaiveDate, Utc, Datelike};
+use std::collections::HashMap;
 
 // ===== ROOM COMMANDS =====
 
 #[command]
-pub fn add_room(number: String, room_type: String, daily_rate: f64) -> Result<String, String> {
+pub fn add_room(
+    number: String,
+    room_type: String,
+    daily_rate: f64,
+    floor: Option<i64>,
+    building: Option<String>,
+    session_token: Option<String>,
+) -> Result<String, AppError> {
     println!("🐛 DEBUG add_room - Received parameters:");
     println!("  number: {:?}", number);
     println!("  room_type: {:?}", room_type);
     println!("  daily_rate: {:?}", daily_rate);
     
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let actor = audit_actor(session_token.as_deref());
+    let conn = get_db_connection()?;
     
     // Validate input
     if number.trim().is_empty() {
-        return Err("Room number cannot be empty".to_string());
-    }
-    if room_type.trim().is_empty() {
-        return Err("Room type cannot be empty".to_string());
+        return Err(AppError::invalid(EMPTY_FIELD, "number", "Room number cannot be empty"));
     }
+    let room_type = crate::room_types::resolve_room_type(&conn, &room_type)?;
     if daily_rate <= 0.0 {
-        return Err("Daily rate must be greater than 0".to_string());
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "daily_rate", "Daily rate must be greater than 0"));
     }
     
     println!("🐛 DEBUG add_room - Executing INSERT query...");
     let result = conn.execute(
-        "INSERT INTO resources (number, room_type, daily_rate, is_occupied, is_active, resource_type) VALUES (?1, ?2, ?3, 0, 1, 'ROOM')",
-        params![number.trim(), room_type.trim(), daily_rate],
+        "INSERT INTO resources (number, room_type, daily_rate, is_occupied, is_active, resource_type, floor, building)
+         VALUES (?1, ?2, ?3, 0, 1, 'ROOM', ?4, ?5)",
+        params![number.trim(), room_type, daily_rate, floor, building_name(building)],
     );
     
     match result {
         Ok(rows_affected) => {
             println!("✅ DEBUG add_room - Success! Rows affected: {}", rows_affected);
+            let room_id = conn.last_insert_rowid();
+            record_change(&conn, &actor, "room", room_id, "create", None, snapshot(&conn, "room", room_id)?)?;
+            events::room_status_changed(Some(room_id), "added");
             Ok(format!("Room {} added successfully", number))
         },
         Err(e) => {
             println!("❌ DEBUG add_room - SQL Error: {}", e);
             if e.to_string().contains("UNIQUE constraint failed") {
-                Err(format!("Room {} already exists", number))
+                Err(AppError::coded(ROOM_NUMBER_EXISTS, format!("Room {} already exists", number)))
             } else {
-                Err(e.to_string())
+                Err(e.into())
             }
         }
     }
 }
 
+/// A building name as stored: trimmed, with a blank one meaning none.
+fn building_name(building: Option<String>) -> Option<String> {
+    building.map(|b| b.trim().to_string()).filter(|b| !b.is_empty())
+}
+
+/// Active rooms, optionally only those on `floor` and/or in `building`.
 #[command]
-pub fn get_rooms() -> Result<Vec<Room>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_rooms(floor: Option<i64>, building: Option<String>) -> Result<Vec<Room>, AppError> {
+    let conn = get_db_connection()?;
     
     let mut stmt = conn.prepare(
-           "SELECT r.id, r.number, r.room_type, r.daily_rate, r.is_occupied, r.guest_id, c.name as guest_name 
+           "SELECT r.id, r.number, r.room_type, r.daily_rate, r.is_occupied, r.guest_id, c.name as guest_name,
+                   EXISTS (SELECT 1 FROM maintenance_tickets t
+                           WHERE t.room_id = r.id AND t.out_of_service = 1 AND t.status != 'resolved') as out_of_service,
+                   r.floor, r.building
             FROM resources r 
             LEFT JOIN customers c ON r.guest_id = c.id AND c.status = 'active'
          WHERE r.is_active = 1 
+           AND (?1 IS NULL OR r.floor = ?1)
+           AND (?2 IS NULL OR r.building = ?2 COLLATE NOCASE)
          ORDER BY r.number"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
-    let room_iter = stmt.query_map([], |row| {
+    let room_iter = stmt.query_map(params![floor, building_name(building)], |row| {
         Ok(Room {
             id: row.get(0)?,
             number: row.get(1)?,
@@ -69,38 +100,93 @@ pub fn get_rooms() -> Result<Vec<Room>, String> {
             is_occupied: row.get::<_, i32>(4)? == 1,
             guest_id: row.get(5)?,
             guest_name: row.get(6)?,
+            out_of_service: row.get(7)?,
+            floor: row.get(8)?,
+            building: row.get(9)?,
         })
-    }).map_err(|e| e.to_string())?;
+    })?;
     
     let mut rooms = Vec::new();
     for room in room_iter {
-        rooms.push(room.map_err(|e| e.to_string())?);
+        rooms.push(room?);
     }
     
     Ok(rooms)
 }
 
+/// Room counts and occupancy for each floor of each building, ground floor first.
+#[command]
+pub fn get_floor_occupancy() -> Result<Vec<FloorOccupancy>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT building, floor, COUNT(*), SUM(is_occupied), SUM(out_of_service)
+         FROM (SELECT r.building, r.floor, r.is_occupied,
+                      EXISTS (SELECT 1 FROM maintenance_tickets t
+                              WHERE t.room_id = r.id AND t.out_of_service = 1 AND t.status != 'resolved') AS out_of_service
+               FROM resources r WHERE r.is_active = 1)
+         GROUP BY building COLLATE NOCASE, floor
+         ORDER BY building IS NULL, building COLLATE NOCASE, floor IS NULL, floor"
+    )?;
+    let floors = stmt.query_map([], |row| {
+        let total_rooms: i64 = row.get(2)?;
+        let occupied_rooms: i64 = row.get(3)?;
+        let out_of_service_rooms: i64 = row.get(4)?;
+        let in_service = total_rooms - out_of_service_rooms;
+        Ok(FloorOccupancy {
+            building: row.get(0)?,
+            floor: row.get(1)?,
+            total_rooms,
+            occupied_rooms,
+            out_of_service_rooms,
+            occupancy_percent: if in_service > 0 {
+                (occupied_rooms as f64 / in_service as f64 * 100.0).min(100.0)
+            } else {
+                0.0
+            },
+        })
+    })?.collect::<Result<Vec<_>, _>>()?;
+    Ok(floors)
+}
+
+/// Rooms free for a stay from `check_in` (default today) to `check_out`.
+/// Booked reservations overlapping those nights make a room unavailable; an
+/// open-ended stay is checked against its first night only. For a future
+/// stay, a room whose current guest leaves by then counts as free.
 #[command]
-pub fn get_available_rooms_for_guest(guest_id: Option<i64>) -> Result<Vec<Room>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_available_rooms_for_guest(guest_id: Option<i64>, check_in: Option<String>, check_out: Option<String>) -> Result<Vec<Room>, AppError> {
+    let conn = get_db_connection()?;
+    
+    let today = business_date_today();
+    let from = check_in.unwrap_or_else(|| today.clone());
+    validate_date_format(&from)?;
+    let to = match check_out {
+        Some(to) => {
+            crate::validation::validate_date_range(&from, &to)?;
+            to
+        }
+        None => next_day(&from)?,
+    };
     
-    let mut query = String::from(
-           "SELECT r.id, r.number, r.room_type, r.daily_rate, r.is_occupied, r.guest_id, c.name as guest_name 
+    // If editing an existing guest, also include their current room
+    let mut stmt = conn.prepare(
+           "SELECT r.id, r.number, r.room_type, r.daily_rate, r.is_occupied, r.guest_id, c.name as guest_name,
+                   EXISTS (SELECT 1 FROM maintenance_tickets t
+                           WHERE t.room_id = r.id AND t.out_of_service = 1 AND t.status != 'resolved') as out_of_service,
+                   r.floor, r.building
             FROM resources r 
             LEFT JOIN customers c ON r.guest_id = c.id AND c.status = 'active'
-         WHERE r.is_active = 1 AND (r.is_occupied = 0"
-    );
-    
-    // If editing an existing guest, also include their current room
-    if let Some(gid) = guest_id {
-        query.push_str(&format!(" OR r.guest_id = {}", gid));
-    }
-    
-    query.push_str(") ORDER BY r.number");
-    
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+         WHERE r.is_active = 1
+           AND (r.guest_id = ?1
+                OR ((r.is_occupied = 0 OR (?2 > ?4 AND c.check_out IS NOT NULL AND c.check_out <= ?2))
+                    AND NOT out_of_service
+                    AND NOT EXISTS (
+                        SELECT 1 FROM reservations res
+                        WHERE res.room_id = r.id AND res.status = 'booked'
+                          AND res.check_in < ?3 AND res.check_out > ?2)))
+         ORDER BY r.number"
+    )?;
     
-    let room_iter = stmt.query_map([], |row| {
+    let room_iter = stmt.query_map(params![guest_id, from, to, today], |row| {
         Ok(Room {
             id: row.get(0)?,
             number: row.get(1)?,
@@ -109,20 +195,32 @@ pub fn get_available_rooms_for_guest(guest_id: Option<i64>) -> Result<Vec<Room>,
             is_occupied: row.get::<_, i32>(4)? == 1,
             guest_id: row.get(5)?,
             guest_name: row.get(6)?,
+            out_of_service: row.get(7)?,
+            floor: row.get(8)?,
+            building: row.get(9)?,
         })
-    }).map_err(|e| e.to_string())?;
+    })?;
     
     let mut rooms = Vec::new();
     for room in room_iter {
-        rooms.push(room.map_err(|e| e.to_string())?);
+        rooms.push(room?);
     }
     
     Ok(rooms)
 }
 
 #[command]
-pub fn update_room(room_id: i64, number: Option<String>, daily_rate: Option<f64>) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn update_room(
+    room_id: i64,
+    number: Option<String>,
+    daily_rate: Option<f64>,
+    room_type: Option<String>,
+    floor: Option<i64>,
+    building: Option<String>,
+    session_token: Option<String>,
+) -> Result<String, AppError> {
+    let actor = audit_actor(session_token.as_deref());
+    let conn = get_db_connection()?;
     
     // Build dynamic update query
     let mut update_parts = Vec::new();
@@ -130,7 +228,7 @@ pub fn update_room(room_id: i64, number: Option<String>, daily_rate: Option<f64>
     
     if let Some(ref num) = number {
         if num.trim().is_empty() {
-            return Err("Room number cannot be empty".to_string());
+            return Err(AppError::invalid(EMPTY_FIELD, "number", "Room number cannot be empty"));
         }
         update_parts.push("number = ?");
         params.push(Box::new(num.trim().to_string()));
@@ -138,14 +236,30 @@ pub fn update_room(room_id: i64, number: Option<String>, daily_rate: Option<f64>
     
     if let Some(rate) = daily_rate {
         if rate < 0.0 {
-            return Err("Daily rate must be positive".to_string());
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "daily_rate", "Daily rate must be positive"));
         }
         update_parts.push("daily_rate = ?");
         params.push(Box::new(rate));
     }
     
+    if let Some(ref room_type) = room_type {
+        update_parts.push("room_type = ?");
+        params.push(Box::new(crate::room_types::resolve_room_type(&conn, room_type)?));
+    }
+    
+    if let Some(floor) = floor {
+        update_parts.push("floor = ?");
+        params.push(Box::new(floor));
+    }
+    
+    // A blank building takes the room out of any building
+    if building.is_some() {
+        update_parts.push("building = ?");
+        params.push(Box::new(building_name(building)));
+    }
+    
     if update_parts.is_empty() {
-        return Err("No fields to update".to_string());
+        return Err("No fields to update".into());
     }
     
     let query = format!("UPDATE resources SET {} WHERE id = ?", update_parts.join(", "));
@@ -153,6 +267,7 @@ pub fn update_room(room_id: i64, number: Option<String>, daily_rate: Option<f64>
     
     let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
     
+    let before = snapshot(&conn, "room", room_id)?;
     let affected = conn.execute(&query, &*param_refs).map_err(|e| {
         if e.to_string().contains("UNIQUE constraint failed") {
             "Room number already exists".to_string()
@@ -162,16 +277,19 @@ pub fn update_room(room_id: i64, number: Option<String>, daily_rate: Option<f64>
     })?;
     
     if affected == 0 {
-        return Err("Room not found".to_string());
+        return Err(AppError::coded(ROOM_NOT_FOUND, "Room not found"));
     }
+    record_change(&conn, &actor, "room", room_id, "update", before, snapshot(&conn, "room", room_id)?)?;
+    events::room_status_changed(Some(room_id), "updated");
     
     Ok("Room updated successfully".to_string())
 }
 
 #[command]
-pub fn delete_room(id: i64) -> Result<String, String> {
+pub fn delete_room(id: i64, session_token: Option<String>) -> Result<String, AppError> {
     println!("🐛 DEBUG delete_room - Received id: {:?}", id);
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let actor = audit_actor(session_token.as_deref());
+    let conn = get_db_connection()?;
     
     // Check if room is in use by active guests
     println!("🐛 DEBUG delete_room - Checking for active guests...");
@@ -187,12 +305,23 @@ pub fn delete_room(id: i64) -> Result<String, String> {
     println!("🐛 DEBUG delete_room - Active guests count: {}", guest_count);
     
     if guest_count > 0 {
-        return Err("Cannot delete room with active guests".to_string());
+        return Err("Cannot delete room with active guests".into());
+    }
+    
+    // A booking still to come would be left without a room
+    if let Some((reservation_id, name, from, to)) = overlapping_reservation(&conn, id, &business_date_today(), "9999-12-31", None)? {
+        return Err(format!(
+            "{}: reservation #{} for {} holds the room from {} to {}; cancel or move it first",
+            crate::validation::ROOM_RESERVED, reservation_id, name, from, to
+        ).into());
     }
     
-    // Hard delete the room so the room number can be reused
+    // Hard delete the room so the room number can be reused, along with its past and cancelled bookings
     println!("🐛 DEBUG delete_room - Executing DELETE query...");
-    let affected = conn.execute(
+    let tx = conn.unchecked_transaction()?;
+    let before = snapshot(&tx, "room", id)?;
+    tx.execute("DELETE FROM reservations WHERE room_id = ?1", params![id])?;
+    let affected = tx.execute(
         "DELETE FROM resources WHERE id = ?1",
         params![id],
     ).map_err(|e| {
@@ -203,24 +332,30 @@ pub fn delete_room(id: i64) -> Result<String, String> {
     println!("🐛 DEBUG delete_room - Rows affected: {}", affected);
     
     if affected == 0 {
-        return Err("Room not found".to_string());
+        return Err(AppError::coded(ROOM_NOT_FOUND, "Room not found"));
     }
+    record_change(&tx, &actor, "room", id, "delete", before, None)?;
+    tx.commit()?;
+    events::room_status_changed(Some(id), "deleted");
     
     println!("✅ DEBUG delete_room - Success!");
     Ok("Room deleted successfully".to_string())
 }
 
 #[command]
-pub fn cleanup_soft_deleted_rooms() -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn cleanup_soft_deleted_rooms() -> Result<String, AppError> {
+    let conn = get_db_connection()?;
     
     // Remove any soft-deleted rooms that might be blocking UNIQUE constraints
     let affected = conn.execute(
         "DELETE FROM resources WHERE is_active = 0",
         [],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     println!("🧹 Cleaned up {} soft-deleted rooms", affected);
+    if affected > 0 {
+        events::room_status_changed(None, "deleted");
+    }
     Ok(format!("Cleaned up {} soft-deleted rooms", affected))
 }
 
@@ -228,34 +363,63 @@ pub fn cleanup_soft_deleted_rooms() -> Result<String, String> {
 // These provide business-generic command names while keeping legacy "room" commands.
 
 #[command]
-pub fn add_resource(number: String, resource_type: String, daily_rate: f64) -> Result<String, String> {
-    add_room(number, resource_type, daily_rate)
+pub fn add_resource(
+    number: String,
+    resource_type: String,
+    daily_rate: f64,
+    floor: Option<i64>,
+    building: Option<String>,
+    session_token: Option<String>,
+) -> Result<String, AppError> {
+    add_room(number, resource_type, daily_rate, floor, building, session_token)
 }
 
 #[command]
-pub fn get_resources() -> Result<Vec<Room>, String> {
-    get_rooms()
+pub fn get_resources(floor: Option<i64>, building: Option<String>) -> Result<Vec<Room>, AppError> {
+    get_rooms(floor, building)
 }
 
 #[command]
-pub fn get_available_resources_for_customer(customer_id: Option<i64>) -> Result<Vec<Room>, String> {
-    get_available_rooms_for_guest(customer_id)
+pub fn get_available_resources_for_customer(customer_id: Option<i64>, check_in: Option<String>, check_out: Option<String>) -> Result<Vec<Room>, AppError> {
+    get_available_rooms_for_guest(customer_id, check_in, check_out)
 }
 
 #[command]
-pub fn update_resource(resource_id: i64, number: Option<String>, daily_rate: Option<f64>) -> Result<String, String> {
-    update_room(resource_id, number, daily_rate)
+pub fn update_resource(
+    resource_id: i64,
+    number: Option<String>,
+    daily_rate: Option<f64>,
+    resource_type: Option<String>,
+    floor: Option<i64>,
+    building: Option<String>,
+    session_token: Option<String>,
+) -> Result<String, AppError> {
+    update_room(resource_id, number, daily_rate, resource_type, floor, building, session_token)
 }
 
 #[command]
-pub fn delete_resource(id: i64) -> Result<String, String> {
-    delete_room(id)
+pub fn delete_resource(id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    delete_room(id, session_token)
 }
 
 // ===== GUEST COMMANDS =====
 
 #[command]
-pub fn add_guest(name: String, phone: Option<String>, room_id: Option<i64>, check_in: String, check_out: Option<String>, daily_rate: f64) -> Result<i64, String> {
+#[allow(clippy::too_many_arguments)]
+pub fn add_guest(
+    name: String,
+    phone: Option<String>,
+    room_id: Option<i64>,
+    check_in: String,
+    check_out: Option<String>,
+    daily_rate: f64,
+    adults: Option<i64>,
+    children: Option<i64>,
+    override_occupancy_warning: Option<bool>,
+    acknowledge_unusual_date: Option<bool>,
+    session_token: Option<String>,
+    profile_id: Option<i64>,
+) -> Result<i64, AppError> {
     println!("🐛 DEBUG add_guest - Received parameters:");
     println!("  name: {:?}", name);
     println!("  phone: {:?}", phone);
@@ -264,8 +428,43 @@ pub fn add_guest(name: String, phone: Option<String>, room_id: Option<i64>, chec
     println!("  check_out: {:?}", check_out);
     println!("  daily_rate: {:?}", daily_rate);
     
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let conn = get_db_connection()?;
+    
+    // Start a transaction to ensure both operations succeed or fail together
+    let tx = conn.unchecked_transaction()?;
+    let guest_id = check_in_stay(
+        &tx, name, phone, room_id, check_in, check_out, daily_rate, adults, children,
+        override_occupancy_warning.unwrap_or(false), acknowledge_unusual_date.unwrap_or(false), session_token.as_deref(), profile_id, None,
+    )?;
+    tx.commit()?;
+    events::guest_changed(Some(guest_id), "checked_in");
+    if room_id.is_some() {
+        events::room_status_changed(room_id, "occupied");
+    }
     
+    Ok(guest_id)
+}
+
+/// Validate and insert one stay on `conn`, normally an open transaction, and
+/// mark its room occupied. `reservation_id` is the booking being checked in,
+/// which doesn't count against the room. Returns the guest id.
+#[allow(clippy::too_many_arguments)]
+fn check_in_stay(
+    conn: &rusqlite::Connection,
+    name: String,
+    phone: Option<String>,
+    room_id: Option<i64>,
+    check_in: String,
+    check_out: Option<String>,
+    daily_rate: f64,
+    adults: Option<i64>,
+    children: Option<i64>,
+    override_occupancy_warning: bool,
+    acknowledged: bool,
+    session_token: Option<&str>,
+    profile_id: Option<i64>,
+    reservation_id: Option<i64>,
+) -> Result<i64, AppError> {
     // Validate inputs
     validate_date_format(&check_in)?;
     if let Some(ref checkout) = check_out {
@@ -274,7 +473,18 @@ pub fn add_guest(name: String, phone: Option<String>, room_id: Option<i64>, chec
     validate_positive_amount(daily_rate, "daily_rate")?;
     
     if name.trim().is_empty() {
-        return Err("Guest name cannot be empty".to_string());
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Guest name cannot be empty"));
+    }
+    
+    let mut unusual_dates = vec![check_date_plausibility(conn, "check_in", &check_in, acknowledged)?];
+    if let Some(ref checkout) = check_out {
+        unusual_dates.push(check_date_plausibility(conn, "check_out", checkout, acknowledged)?);
+    }
+    
+    let adults = adults.unwrap_or(1);
+    let children = children.unwrap_or(0);
+    if adults < 1 || children < 0 {
+        return Err("A stay needs at least one adult and a non-negative number of children".into());
     }
     
     // For walk-in customers (no room), room_id will be None
@@ -284,10 +494,10 @@ pub fn add_guest(name: String, phone: Option<String>, room_id: Option<i64>, chec
             "SELECT COUNT(*) FROM resources WHERE id = ?1 AND is_active = 1",
             params![room_id_val],
             |row| row.get(0)
-        ).map_err(|e| e.to_string())?;
+        )?;
         
         if room_exists == 0 {
-            return Err("Room not found or inactive".to_string());
+            return Err(AppError::coded(ROOM_NOT_FOUND, "Room not found or inactive"));
         }
         
         // Check if room is already occupied
@@ -295,72 +505,562 @@ pub fn add_guest(name: String, phone: Option<String>, room_id: Option<i64>, chec
             "SELECT COUNT(*) FROM resources WHERE id = ?1 AND is_occupied = 1",
             params![room_id_val],
             |row| row.get(0)
-        ).map_err(|e| e.to_string())?;
+        )?;
         
         if room_occupied > 0 {
-            return Err("Room is already occupied".to_string());
+            return Err(AppError::coded(ROOM_OCCUPIED, "Room is already occupied"));
         }
+        
+        // Someone else's booking may already hold the room for these nights; an open-ended stay needs tonight
+        let until = match check_out {
+            Some(ref checkout) if checkout.as_str() > check_in.as_str() => checkout.clone(),
+            _ => next_day(&check_in)?,
+        };
+        if let Some((id, booked_name, from, to)) = overlapping_reservation(conn, room_id_val, &check_in, &until, reservation_id)? {
+            return Err(format!(
+                "{}: reservation #{} for {} holds the room from {} to {}",
+                crate::validation::ROOM_RESERVED, id, booked_name, from, to
+            ).into());
+        }
+        
+        crate::maintenance::ensure_room_in_service(conn, room_id_val)?;
+        crate::room_types::check_room_capacity(conn, room_id_val, adults + children)?;
+    }
+    
+    // Walk-in customers don't stay overnight, so only room guests count toward the cap
+    if room_id.is_some() {
+        check_occupancy_cap(conn, room_id, &check_in, check_out.as_deref(), adults + children, override_occupancy_warning)?;
     }
     
+    let after_hours = check_after_hours(conn, session_token)?;
     let now = get_current_timestamp();
     
-    // Start a transaction to ensure both operations succeed or fail together
-    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    // Insert the guest; the room is charged per night from rate plans, with
+    // daily_rate covering nights no plan applies to
+    conn.execute(
+        "INSERT INTO customers (name, phone, room_id, check_in, check_out, daily_rate, status, adults, children, after_hours, nightly_pricing, created_at, updated_at) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'active', ?7, ?8, ?9, 1, ?10, ?11)",
+        params![name.trim(), phone, room_id, check_in, check_out, daily_rate, adults, children, after_hours, now, now],
+    )?;
     
-    // Insert the guest
-    tx.execute(
-        "INSERT INTO customers (name, phone, room_id, check_in, check_out, daily_rate, status, created_at, updated_at) 
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'active', ?7, ?8)",
-        params![name.trim(), phone, room_id, check_in, check_out, daily_rate, now, now],
-    ).map_err(|e| e.to_string())?;
+    let guest_id = conn.last_insert_rowid();
     
-    let guest_id = tx.last_insert_rowid();
+    // A returning guest's stay joins their profile, picked at the desk or matched by phone
+    crate::guest_profiles::attach_stay(conn, guest_id, profile_id, &name, phone.as_deref())?;
+    crate::stay_fees::post_early_check_in_fee(conn, guest_id, chrono::Local::now().naive_local())?;
     
     // Update room status to occupied only if room_id is provided
     if let Some(room_id_val) = room_id {
-        tx.execute(
+        conn.execute(
             "UPDATE resources SET is_occupied = 1, guest_id = ?1 WHERE id = ?2",
             params![guest_id, room_id_val],
-        ).map_err(|e| e.to_string())?;
+        )?;
     }
     
-    // Commit the transaction
-    tx.commit().map_err(|e| e.to_string())?;
+    log_acknowledged_dates(conn, &format!("guest {}", guest_id), &unusual_dates)?;
+    record_change(conn, &audit_actor(session_token), "guest", guest_id, "create", None, snapshot(conn, "guest", guest_id)?)?;
     
     Ok(guest_id)
 }
 
+// ===== OCCUPANCY CAP =====
+
+/// Persons (adults + children) staying overnight on `date`, in one aggregate query:
+/// in-house guests plus booked reservations not yet checked in. Active guests past
+/// their expected check-out are still in house, so they count for today. Whoever
+/// is in or booked into `except_room` is left out, for the party taking that room.
+pub fn persons_in_house(conn: &rusqlite::Connection, date: &str, except_room: Option<i64>) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT (SELECT COALESCE(SUM(adults + children), 0) FROM customers
+                 WHERE status = 'active' AND room_id IS NOT NULL AND room_id != COALESCE(?3, -1)
+                   AND check_in <= ?1
+                   AND (check_out IS NULL OR check_out > ?1 OR ?1 <= ?2))
+              + (SELECT COALESCE(SUM(adults + children), 0) FROM reservations
+                 WHERE status = 'booked' AND check_in <= ?1 AND check_out > ?1
+                   AND room_id != COALESCE(?3, -1))",
+        params![date, business_date_today(), except_room],
+        |row| row.get(0)
+    ).map_err(|e| e.to_string())
+}
+
+fn load_occupancy_cap(conn: &rusqlite::Connection) -> Result<OccupancyCapSettings, String> {
+    Ok(OccupancyCapSettings {
+        max_property_occupancy: get_parsed_setting::<i64>(conn, "max_property_occupancy")?.filter(|cap| *cap > 0),
+        mode: get_setting(conn, "occupancy_cap_mode")?
+            .filter(|m| m.as_str() == "soft")
+            .unwrap_or_else(|| "hard".to_string()),
+    })
+}
+
+/// Reject (hard mode) or warn (soft mode, unless overridden) when `arriving` more persons
+/// in `room_id` would take the property over its occupancy cap on any night from
+/// `check_in` up to `check_out`. An open-ended stay is checked for its first night.
+pub fn check_occupancy_cap(
+    conn: &rusqlite::Connection,
+    room_id: Option<i64>,
+    check_in: &str,
+    check_out: Option<&str>,
+    arriving: i64,
+    override_warning: bool,
+) -> Result<(), String> {
+    let settings = load_occupancy_cap(conn)?;
+    let Some(cap) = settings.max_property_occupancy else {
+        return Ok(());
+    };
+
+    let first = NaiveDate::parse_from_str(check_in, "%Y-%m-%d").map_err(|_| format!("Invalid date format: {}", check_in))?;
+    let nights = match check_out {
+        Some(check_out) => {
+            let last = NaiveDate::parse_from_str(check_out, "%Y-%m-%d").map_err(|_| format!("Invalid date format: {}", check_out))?;
+            (last - first).num_days().max(1)
+        }
+        None => 1,
+    };
+    for night in 0..nights {
+        let date = (first + chrono::Duration::days(night)).format("%Y-%m-%d").to_string();
+        let current = persons_in_house(conn, &date, room_id)?;
+        if current + arriving <= cap {
+            continue;
+        }
+
+        let details = format!("on {} {} expected + {} arriving exceeds the cap of {}", date, current, arriving, cap);
+        if settings.mode == "soft" {
+            if override_warning {
+                return Ok(());
+            }
+            return Err(format!("{}: {}", crate::validation::OCCUPANCY_CAP_WARNING, details));
+        }
+        return Err(format!("{}: {}", crate::validation::OCCUPANCY_CAP_EXCEEDED, details));
+    }
+    Ok(())
+}
+
+#[command]
+pub fn get_occupancy_cap() -> Result<OccupancyCapSettings, AppError> {
+    let conn = get_db_connection()?;
+    load_occupancy_cap(&conn).map_err(AppError::from)
+}
+
+#[command]
+pub fn set_occupancy_cap(max_property_occupancy: Option<i64>, mode: String) -> Result<String, AppError> {
+    if !["hard", "soft"].contains(&mode.as_str()) {
+        return Err("Occupancy cap mode must be 'hard' or 'soft'".into());
+    }
+    if max_property_occupancy.is_some_and(|cap| cap < 1) {
+        return Err("Maximum occupancy must be at least 1".into());
+    }
+
+    let conn = get_db_connection()?;
+    set_or_clear_setting(&conn, "max_property_occupancy", max_property_occupancy.map(|cap| cap.to_string()).as_deref())?;
+    set_setting(&conn, "occupancy_cap_mode", &mode)?;
+
+    Ok("Occupancy cap saved".to_string())
+}
+
+// ===== RESERVATION COMMANDS =====
+
+// Bookings further out than this are almost certainly a mistyped year
+const MAX_BOOKING_DAYS_AHEAD: i64 = 730;
+
+/// The day after `date`, used as the end of an open-ended stay's first night.
+fn next_day(date: &str) -> Result<String, String> {
+    let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid date format: {}", date))?;
+    Ok((day + chrono::Duration::days(1)).format("%Y-%m-%d").to_string())
+}
+
+/// A booked reservation on `room_id` overlapping the nights from `check_in` up to `check_out`.
+pub(crate) fn overlapping_reservation(
+    conn: &rusqlite::Connection,
+    room_id: i64,
+    check_in: &str,
+    check_out: &str,
+    exclude_id: Option<i64>,
+) -> Result<Option<(i64, String, String, String)>, String> {
+    conn.query_row(
+        "SELECT id, guest_name, check_in, check_out FROM reservations
+         WHERE room_id = ?1 AND status = 'booked' AND check_in < ?3 AND check_out > ?2
+           AND id != COALESCE(?4, -1)
+         ORDER BY check_in LIMIT 1",
+        params![room_id, check_in, check_out, exclude_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+    ).optional().map_err(|e| e.to_string())
+}
+
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub fn add_reservation(
+    room_id: i64,
+    guest_name: String,
+    phone: Option<String>,
+    check_in: String,
+    check_out: String,
+    daily_rate: Option<f64>,
+    adults: Option<i64>,
+    children: Option<i64>,
+    notes: Option<String>,
+    override_occupancy_warning: Option<bool>,
+) -> Result<i64, AppError> {
+    let conn = get_db_connection()?;
+    let reservation_id = book_room(
+        &conn, room_id, &guest_name, phone, &check_in, &check_out, daily_rate, adults, children, notes, None,
+        override_occupancy_warning.unwrap_or(false),
+    )?;
+    crate::notifications::notify_in_background("booking_confirmation", NotificationSubject::Reservation(reservation_id));
+    Ok(reservation_id)
+}
+
+/// Validate and insert one reservation, optionally as part of a group
+/// booking. Returns the reservation id.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn book_room(
+    conn: &rusqlite::Connection,
+    room_id: i64,
+    guest_name: &str,
+    phone: Option<String>,
+    check_in: &str,
+    check_out: &str,
+    daily_rate: Option<f64>,
+    adults: Option<i64>,
+    children: Option<i64>,
+    notes: Option<String>,
+    group_id: Option<i64>,
+    override_occupancy_warning: bool,
+) -> Result<i64, AppError> {
+    if guest_name.trim().is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "guest_name", "Guest name cannot be empty"));
+    }
+    crate::validation::validate_date_range(check_in, check_out)?;
+
+    let today = business_date_today();
+    if check_in < today.as_str() {
+        return Err("A reservation cannot start in the past".into());
+    }
+    let days_ahead = (NaiveDate::parse_from_str(check_in, "%Y-%m-%d")?
+        - chrono::Local::now().date_naive()).num_days();
+    if days_ahead > MAX_BOOKING_DAYS_AHEAD {
+        return Err(format!(
+            "{}: check_in {} is {} days ahead; bookings can be made up to {} days in advance",
+            crate::validation::UNUSUAL_DATE, check_in, days_ahead, MAX_BOOKING_DAYS_AHEAD
+        ).into());
+    }
+
+    let adults = adults.unwrap_or(1);
+    let children = children.unwrap_or(0);
+    if adults < 1 || children < 0 {
+        return Err("A stay needs at least one adult and a non-negative number of children".into());
+    }
+
+    let room: Option<(f64, Option<String>)> = conn.query_row(
+        "SELECT r.daily_rate, CASE WHEN r.is_occupied = 1 THEN COALESCE(c.check_out, '9999-12-31') END
+         FROM resources r
+         LEFT JOIN customers c ON r.guest_id = c.id AND c.status = 'active'
+         WHERE r.id = ?1 AND r.is_active = 1",
+        params![room_id],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).optional()?;
+    let (room_rate, occupied_until) = room.ok_or(crate::validation::ROOM_NOT_FOUND.to_string())?;
+    crate::room_types::check_room_capacity(conn, room_id, adults + children)?;
+
+    // The current guest is expected to leave on their check-out date; without one they block the room
+    if occupied_until.is_some_and(|until| until.as_str() > check_in) {
+        return Err(AppError::coded(ROOM_OCCUPIED, format!("the current guest is still in the room on {}", check_in)));
+    }
+    if let Some((id, name, from, to)) = overlapping_reservation(conn, room_id, check_in, check_out, None)? {
+        return Err(format!(
+            "{}: reservation #{} for {} already holds the room from {} to {}",
+            crate::validation::ROOM_RESERVED, id, name, from, to
+        ).into());
+    }
+    check_occupancy_cap(conn, Some(room_id), check_in, Some(check_out), adults + children, override_occupancy_warning)?;
+
+    let daily_rate = daily_rate.unwrap_or(room_rate);
+    validate_positive_amount(daily_rate, "daily_rate")?;
+
+    let now = get_current_timestamp();
+    conn.execute(
+        "INSERT INTO reservations (room_id, guest_name, phone, check_in, check_out, daily_rate, adults, children, notes, status, group_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, 'booked', ?10, ?11, ?11)",
+        params![room_id, guest_name.trim(), phone, check_in, check_out, daily_rate, adults, children, notes, group_id, now],
+    )?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+/// Reservations ordered by arrival. `from_date` keeps stays that haven't ended by then.
+#[command]
+pub fn get_reservations(status: Option<String>, from_date: Option<String>) -> Result<Vec<Reservation>, AppError> {
+    if let Some(ref date) = from_date {
+        validate_date_format(date)?;
+    }
+    let conn = get_db_connection()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT res.id, res.room_id, r.number, res.guest_name, res.phone, res.check_in, res.check_out,
+                res.daily_rate, res.adults, res.children, res.notes, res.status, res.guest_id, res.created_at, res.group_id
+         FROM reservations res
+         JOIN resources r ON r.id = res.room_id
+         WHERE (?1 IS NULL OR res.status = ?1) AND (?2 IS NULL OR res.check_out > ?2)
+         ORDER BY res.check_in, r.number"
+    )?;
+
+    let rows = stmt.query_map(params![status, from_date], |row| {
+        Ok(Reservation {
+            id: row.get(0)?,
+            room_id: row.get(1)?,
+            room_number: row.get(2)?,
+            guest_name: row.get(3)?,
+            phone: row.get(4)?,
+            check_in: row.get(5)?,
+            check_out: row.get(6)?,
+            daily_rate: row.get(7)?,
+            adults: row.get(8)?,
+            children: row.get(9)?,
+            notes: row.get(10)?,
+            status: row.get(11)?,
+            guest_id: row.get(12)?,
+            created_at: row.get(13)?,
+            group_id: row.get(14)?,
+        })
+    })?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+#[command]
+pub fn cancel_reservation(reservation_id: i64) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let rows = conn.execute(
+        "UPDATE reservations SET status = 'cancelled', updated_at = ?1 WHERE id = ?2 AND status = 'booked'",
+        params![get_current_timestamp(), reservation_id],
+    )?;
+
+    if rows == 0 {
+        return Err("Reservation not found or no longer booked".into());
+    }
+    Ok("Reservation cancelled".to_string())
+}
+
+/// Check the booked guest in today through the regular check-in path and mark
+/// the reservation as used. Returns the new guest id.
+#[command]
+pub fn convert_reservation_to_checkin(
+    reservation_id: i64,
+    override_occupancy_warning: Option<bool>,
+    session_token: Option<String>,
+) -> Result<i64, AppError> {
+    let conn = get_db_connection()?;
+    let reservation: (i64, String, Option<String>, String, String, f64, i64, i64) = conn.query_row(
+        "SELECT room_id, guest_name, phone, check_in, check_out, daily_rate, adults, children
+         FROM reservations WHERE id = ?1 AND status = 'booked'",
+        params![reservation_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?))
+    ).optional()?
+        .ok_or("Reservation not found or no longer booked".to_string())?;
+    let (room_id, guest_name, phone, check_in, check_out, daily_rate, adults, children) = reservation;
+
+    let today = business_date_today();
+    if today < check_in {
+        return Err(format!("Reservation starts on {}; it cannot be checked in before then", check_in).into());
+    }
+    if today >= check_out {
+        return Err(format!("Reservation ended on {}", check_out).into());
+    }
+
+    // Dates were checked when the booking was made. The stay and the used
+    // booking are written together, so neither is left behind on its own.
+    let tx = conn.unchecked_transaction()?;
+    let guest_id = check_in_stay(
+        &tx, guest_name, phone, Some(room_id), today, Some(check_out), daily_rate, Some(adults), Some(children),
+        override_occupancy_warning.unwrap_or(false), true, session_token.as_deref(), None, Some(reservation_id),
+    )?;
+    let converted = tx.execute(
+        "UPDATE reservations SET status = 'checked_in', guest_id = ?1, updated_at = ?2 WHERE id = ?3 AND status = 'booked'",
+        params![guest_id, get_current_timestamp(), reservation_id],
+    )?;
+    if converted == 0 {
+        return Err("Reservation not found or no longer booked".into());
+    }
+    tx.commit()?;
+    events::guest_changed(Some(guest_id), "checked_in");
+    events::room_status_changed(Some(room_id), "occupied");
+
+    Ok(guest_id)
+}
+
+// ===== AVAILABILITY CALENDAR =====
+
+const MAX_CALENDAR_DAYS: i64 = 93;
+
+/// Per-room, per-day status for `start_date..=end_date`: free, occupied (an
+/// in-house or past stay), reserved (a booking), or conflict when more than
+/// one stay or booking claims the same night.
+#[command]
+pub fn get_room_availability_calendar(start_date: String, end_date: String) -> Result<Vec<RoomCalendarRow>, AppError> {
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d").map_err(|_| format!("Invalid date format: {}", start_date))?;
+    let end = NaiveDate::parse_from_str(&end_date, "%Y-%m-%d").map_err(|_| format!("Invalid date format: {}", end_date))?;
+    let span = (end - start).num_days() + 1;
+    if span < 1 {
+        return Err("End date must not be before start date".into());
+    }
+    if span > MAX_CALENDAR_DAYS {
+        return Err(format!("Calendar range is limited to {} days", MAX_CALENDAR_DAYS).into());
+    }
+    let end_exclusive = (end + chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let today = chrono::Local::now().date_naive();
+
+    let conn = get_db_connection()?;
+
+    // Stays: nights from check_in up to check_out. An in-house guest holds the room at
+    // least through tonight, and indefinitely when no check-out date was given.
+    let mut stmt = conn.prepare(
+        "SELECT id, name, room_id, check_in, check_out, status FROM customers
+         WHERE room_id IS NOT NULL AND status IN ('active', 'checked_out')
+           AND check_in < ?2 AND (status = 'active' OR check_out > ?1)"
+    )?;
+    let stays = stmt.query_map(params![start_date, end_exclusive], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, String>(5)?,
+        ))
+    })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, guest_name, room_id, check_in, check_out FROM reservations
+         WHERE status = 'booked' AND check_in < ?2 AND check_out > ?1"
+    )?;
+    let bookings = stmt.query_map(params![start_date, end_exclusive], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, String>(4)?,
+        ))
+    })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let parse = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+
+    let mut rows: Vec<RoomCalendarRow> = get_rooms(None, None)?
+        .into_iter()
+        .map(|room| RoomCalendarRow {
+            room_id: room.id,
+            room_number: room.number,
+            room_type: room.room_type,
+            days: (0..span)
+                .map(|offset| RoomCalendarDay {
+                    date: (start + chrono::Duration::days(offset)).format("%Y-%m-%d").to_string(),
+                    status: "free".to_string(),
+                    guest_id: None,
+                    guest_name: None,
+                    reservation_ids: Vec::new(),
+                })
+                .collect(),
+        })
+        .collect();
+
+    // Mark the nights in [from, to) that fall inside the range
+    let mut claim = |room_id: i64, from: NaiveDate, to: NaiveDate, mark: &dyn Fn(&mut RoomCalendarDay)| {
+        let Some(row) = rows.iter_mut().find(|r| r.room_id == room_id) else {
+            return;
+        };
+        let first = (from - start).num_days().max(0);
+        let last = (to - start).num_days().min(span);
+        for offset in first..last {
+            let day = &mut row.days[offset as usize];
+            let claimed = day.guest_id.is_some() || !day.reservation_ids.is_empty();
+            mark(day);
+            if claimed {
+                day.status = "conflict".to_string();
+            }
+        }
+    };
+
+    for (guest_id, name, room_id, check_in, check_out, status) in stays {
+        let Some(from) = parse(&check_in) else { continue };
+        let expected_out = check_out.as_deref().and_then(parse);
+        let to = if status == "active" {
+            match expected_out {
+                Some(out) => out.max(today + chrono::Duration::days(1)),
+                None => end + chrono::Duration::days(1),
+            }
+        } else {
+            expected_out.unwrap_or(from + chrono::Duration::days(1))
+        };
+        claim(room_id, from, to, &|day| {
+            day.status = "occupied".to_string();
+            day.guest_id = Some(guest_id);
+            day.guest_name = Some(name.clone());
+        });
+    }
+
+    for (reservation_id, name, room_id, check_in, check_out) in bookings {
+        let (Some(from), Some(to)) = (parse(&check_in), parse(&check_out)) else { continue };
+        claim(room_id, from, to, &|day| {
+            if day.guest_id.is_none() && day.reservation_ids.is_empty() {
+                day.status = "reserved".to_string();
+                day.guest_name = Some(name.clone());
+            }
+            day.reservation_ids.push(reservation_id);
+        });
+    }
+
+    Ok(rows)
+}
+
 // ===== CUSTOMER (ALIAS) COMMANDS =====
 // Generic naming wrappers for legacy "guest" commands.
 
 #[command]
-pub fn add_customer(name: String, phone: Option<String>, room_id: Option<i64>, check_in: String, check_out: Option<String>, daily_rate: f64) -> Result<i64, String> {
-    add_guest(name, phone, room_id, check_in, check_out, daily_rate)
+#[allow(clippy::too_many_arguments)]
+pub fn add_customer(
+    name: String,
+    phone: Option<String>,
+    room_id: Option<i64>,
+    check_in: String,
+    check_out: Option<String>,
+    daily_rate: f64,
+    adults: Option<i64>,
+    children: Option<i64>,
+    override_occupancy_warning: Option<bool>,
+    acknowledge_unusual_date: Option<bool>,
+    session_token: Option<String>,
+    profile_id: Option<i64>,
+) -> Result<i64, AppError> {
+    add_guest(name, phone, room_id, check_in, check_out, daily_rate, adults, children, override_occupancy_warning, acknowledge_unusual_date, session_token, profile_id)
 }
 
 #[command]
-pub fn get_active_customers() -> Result<Vec<ActiveGuestRow>, String> {
-    get_active_guests()
+pub fn get_active_customers(tag_ids: Option<Vec<i64>>) -> Result<Vec<ActiveGuestRow>, AppError> {
+    get_active_guests(tag_ids)
 }
 
 #[command]
-pub fn get_all_customers() -> Result<Vec<Guest>, String> {
-    get_all_guests()
+pub fn get_all_customers(tag_ids: Option<Vec<i64>>) -> Result<Vec<Guest>, AppError> {
+    get_all_guests(tag_ids)
 }
 
 #[command]
-pub fn get_customer(customer_id: i64) -> Result<ActiveGuestRow, String> {
+pub fn get_customer(customer_id: i64) -> Result<ActiveGuestRow, AppError> {
     get_guest(customer_id)
 }
 
 #[command]
-pub fn checkout_customer(customer_id: i64, check_out_date: String) -> Result<f64, String> {
+pub fn checkout_customer(customer_id: i64, check_out_date: String, payment_method: Option<String>, session_token: Option<String>) -> Result<f64, AppError> {
     checkout_guest_with_discount(
         customer_id,
         check_out_date,
         "flat".to_string(),
         0.0,
         "".to_string(),
+        payment_method,
+        session_token,
+        None,
     )
 }
 
@@ -369,17 +1069,24 @@ pub fn checkout_customer_with_discount(
     customer_id: i64,
     check_out_date: String,
     discount_amount: f64,
-) -> Result<f64, String> {
+    payment_method: Option<String>,
+    session_token: Option<String>,
+    voucher_code: Option<String>,
+) -> Result<f64, AppError> {
     checkout_guest_with_discount(
         customer_id,
         check_out_date,
         "flat".to_string(),
         discount_amount,
         "".to_string(),
+        payment_method,
+        session_token,
+        voucher_code,
     )
 }
 
 #[command]
+#[allow(clippy::too_many_arguments)]
 pub fn update_customer(
     guest_id: i64,
     name: Option<String>,
@@ -388,24 +1095,27 @@ pub fn update_customer(
     check_in: Option<String>,
     check_out: Option<String>,
     daily_rate: Option<f64>,
- ) -> Result<bool, String> {
-    update_guest(guest_id, name, phone, room_id, check_in, check_out, daily_rate)
+    acknowledge_unusual_date: Option<bool>,
+    session_token: Option<String>,
+ ) -> Result<bool, AppError> {
+    update_guest(guest_id, name, phone, room_id, check_in, check_out, daily_rate, acknowledge_unusual_date, session_token)
 }
 
 #[command]
-pub fn get_active_guests() -> Result<Vec<ActiveGuestRow>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_active_guests(tag_ids: Option<Vec<i64>>) -> Result<Vec<ActiveGuestRow>, AppError> {
+    let conn = get_db_connection()?;
     
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT g.id, g.name, r.number, g.check_in, g.check_out, g.daily_rate, 
-                CASE WHEN g.room_id IS NULL THEN 1 ELSE 0 END as is_walkin
+                CASE WHEN g.room_id IS NULL THEN 1 ELSE 0 END as is_walkin, g.adults, g.children
          FROM customers g 
          LEFT JOIN resources r ON g.room_id = r.id 
-         WHERE g.status = 'active'
+         WHERE g.status = 'active'{}
          ORDER BY 
             CASE WHEN g.room_id IS NULL THEN 1 ELSE 0 END,  -- Walk-ins first
-            r.number"
-    ).map_err(|e| e.to_string())?;
+            r.number",
+        crate::tags::tag_filter_clause("guest", "g.id", tag_ids.as_deref())
+    ))?;
     
     let guest_iter = stmt.query_map([], |row| {
         Ok(ActiveGuestRow {
@@ -416,57 +1126,124 @@ pub fn get_active_guests() -> Result<Vec<ActiveGuestRow>, String> {
             check_out: row.get(4)?,
             daily_rate: row.get(5)?,
             is_walkin: row.get::<_, i32>(6)? == 1,
+            adults: row.get(7)?,
+            children: row.get(8)?,
+            open_holds: Vec::new(),
+            tags: Vec::new(),
         })
-    }).map_err(|e| e.to_string())?;
+    })?;
     
     let mut guests = Vec::new();
     for guest in guest_iter {
-        guests.push(guest.map_err(|e| e.to_string())?);
+        guests.push(guest?);
+    }
+    
+    // Attach open checkout holds so the front desk sees them in the list
+    let mut holds_by_guest: HashMap<i64, Vec<CheckoutHold>> = HashMap::new();
+    for hold in crate::checkout_holds::open_holds(&conn, None)? {
+        holds_by_guest.entry(hold.guest_id).or_default().push(hold);
+    }
+    let mut tags_by_guest = crate::tags::tags_by_entity(&conn, "guest")?;
+    for guest in guests.iter_mut() {
+        guest.open_holds = holds_by_guest.remove(&guest.guest_id).unwrap_or_default();
+        guest.tags = tags_by_guest.remove(&guest.guest_id).unwrap_or_default();
     }
     
     Ok(guests)
 }
 
 #[command]
-pub fn get_all_guests() -> Result<Vec<Guest>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_all_guests(tag_ids: Option<Vec<i64>>) -> Result<Vec<Guest>, AppError> {
+    let conn = get_db_connection()?;
     
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT id, name, phone, room_id, check_in, check_out, daily_rate, status, created_at, updated_at
             FROM customers 
-         ORDER BY created_at DESC"
-    ).map_err(|e| e.to_string())?;
+         WHERE 1=1{}
+         ORDER BY created_at DESC",
+        crate::tags::tag_filter_clause("guest", "id", tag_ids.as_deref())
+    ))?;
     
-    let guest_iter = stmt.query_map([], |row| {
-        Ok(Guest {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            phone: row.get(2)?,
-            room_id: row.get(3)?,
-            check_in: row.get(4)?,
-            check_out: row.get(5)?,
-            daily_rate: row.get(6)?,
-            status: row.get(7)?,
-            created_at: row.get(8)?,
-            updated_at: row.get(9)?,
-        })
-    }).map_err(|e| e.to_string())?;
+    let guest_iter = stmt.query_map([], map_guest)?;
     
+    let mut tags_by_guest = crate::tags::tags_by_entity(&conn, "guest")?;
     let mut guests = Vec::new();
     for guest in guest_iter {
-        guests.push(guest.map_err(|e| e.to_string())?);
+        let mut guest = guest?;
+        guest.tags = tags_by_guest.remove(&guest.id).unwrap_or_default();
+        guests.push(guest);
     }
     
     Ok(guests)
 }
 
+const GUEST_LIST: ListSpec = ListSpec {
+    sorts: &[("created_at", "created_at"), ("name", "name COLLATE NOCASE"), ("check_in", "check_in"), ("check_out", "check_out"), ("status", "status")],
+    default_sort: "-created_at",
+    tie_breaker: "id DESC",
+    filters: &[
+        ("status", "status = ?"),
+        ("room_id", "room_id = ?"),
+        ("search", "(name LIKE '%' || ? || '%' OR phone LIKE '%' || ? || '%')"),
+        ("start_date", "check_in >= ?"),
+        ("end_date", "check_in <= ?"),
+    ],
+};
+
+/// Guests a page at a time. Sorts: created_at, name, check_in, check_out,
+/// status. Filters: status, room_id, search (name or phone), start_date and
+/// end_date (on check-in).
+#[command]
+pub fn get_guests_page(page: Option<PageRequest>, tag_ids: Option<Vec<i64>>) -> Result<PageResult<Guest>, AppError> {
+    let query = GUEST_LIST.resolve(page)?;
+    let conn = get_db_connection()?;
+    
+    let from_where = format!(
+        "FROM customers WHERE 1=1{}{}",
+        query.conditions,
+        crate::tags::tag_filter_clause("guest", "id", tag_ids.as_deref())
+    );
+    let total = query.count(&conn, &from_where)?;
+    
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, name, phone, room_id, check_in, check_out, daily_rate, status, created_at, updated_at {}{}{}",
+        from_where, query.order_by, query.limit_clause()
+    ))?;
+    let mut tags_by_guest = crate::tags::tags_by_entity(&conn, "guest")?;
+    let guests = stmt
+        .query_map(rusqlite::params_from_iter(query.values.iter()), map_guest)?
+        .map(|guest| guest.map(|mut guest| {
+            guest.tags = tags_by_guest.remove(&guest.id).unwrap_or_default();
+            guest
+        }))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    Ok(query.into_result(guests, total))
+}
+
+fn map_guest(row: &rusqlite::Row) -> rusqlite::Result<Guest> {
+    Ok(Guest {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        phone: row.get(2)?,
+        room_id: row.get(3)?,
+        check_in: row.get(4)?,
+        check_out: row.get(5)?,
+        daily_rate: row.get(6)?,
+        status: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+        tags: Vec::new(),
+    })
+}
+
 #[command]
-pub fn get_guest(guest_id: i64) -> Result<ActiveGuestRow, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_guest(guest_id: i64) -> Result<ActiveGuestRow, AppError> {
+    let conn = get_db_connection()?;
     
-    let result = conn.query_row(
+    let mut result = conn.query_row(
         "SELECT g.id, g.name, r.number, g.check_in, g.check_out, g.daily_rate,
-                CASE WHEN g.room_id IS NULL THEN 1 ELSE 0 END as is_walkin
+                CASE WHEN g.room_id IS NULL THEN 1 ELSE 0 END as is_walkin, g.adults, g.children
          FROM customers g 
          LEFT JOIN resources r ON g.room_id = r.id 
          WHERE g.id = ?1",
@@ -480,6 +1257,10 @@ pub fn get_guest(guest_id: i64) -> Result<ActiveGuestRow, String> {
                 check_out: row.get(4)?,
                 daily_rate: row.get(5)?,
                 is_walkin: row.get::<_, i32>(6)? == 1,
+                adults: row.get(7)?,
+                children: row.get(8)?,
+                open_holds: Vec::new(),
+                tags: Vec::new(),
             })
         }
     ).map_err(|e| {
@@ -490,18 +1271,48 @@ pub fn get_guest(guest_id: i64) -> Result<ActiveGuestRow, String> {
         }
     })?;
     
+    result.open_holds = crate::checkout_holds::open_holds(&conn, Some(guest_id))?;
+    result.tags = crate::tags::tags_for_entity(&conn, "guest", guest_id)?;
+    
     Ok(result)
 }
 
 #[command]
-pub fn checkout_guest(guest_id: i64, discount_flat: Option<f64>, discount_pct: Option<f64>) -> Result<CheckoutTotals, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn checkout_guest(
+    guest_id: i64,
+    discount_flat: Option<f64>,
+    discount_pct: Option<f64>,
+    payment_method: Option<String>,
+    session_token: Option<String>,
+    voucher_code: Option<String>,
+) -> Result<CheckoutTotals, AppError> {
+    let payment_method = parse_payment_method(payment_method.as_deref().unwrap_or("cash"))?;
+    checkout_active_guest(guest_id, None, discount_flat, discount_pct, voucher_code, session_token, |tx, balance_due| {
+        Ok(insert_checkout_payment(tx, guest_id, balance_due, &payment_method)?)
+    })
+}
+
+/// Check out an active guest and free their room. `settle` runs inside the
+/// checkout transaction with the balance left after deposits, and decides how
+/// it's paid (a payment row, or a posting to a company account). A voucher
+/// comes off after the discounts and before tax. The stay is billed up to
+/// `check_out`, or today when none is given.
+pub(crate) fn checkout_active_guest(
+    guest_id: i64,
+    check_out: Option<NaiveDate>,
+    discount_flat: Option<f64>,
+    discount_pct: Option<f64>,
+    voucher_code: Option<String>,
+    session_token: Option<String>,
+    settle: impl FnOnce(&rusqlite::Connection, f64) -> Result<(), AppError>,
+) -> Result<CheckoutTotals, AppError> {
+    let conn = get_db_connection()?;
     
     // Get guest details
-    let (check_in, daily_rate): (String, f64) = conn.query_row(
-        "SELECT check_in, daily_rate FROM customers WHERE id = ?1 AND status = 'active'",
+    let check_in: String = conn.query_row(
+        "SELECT check_in FROM customers WHERE id = ?1 AND status = 'active'",
         params![guest_id],
-        |row| Ok((row.get(0)?, row.get(1)?))
+        |row| row.get(0)
     ).map_err(|e| {
         if e.to_string().contains("no rows") {
             "Active guest not found".to_string()
@@ -510,84 +1321,179 @@ pub fn checkout_guest(guest_id: i64, discount_flat: Option<f64>, discount_pct: O
         }
     })?;
     
+    crate::checkout_holds::ensure_no_open_holds(&conn, guest_id)?;
+    
+    // Start a transaction to ensure all operations succeed or fail together;
+    // the late check-out fee is only charged if the checkout goes through
+    let tx = conn.unchecked_transaction()?;
+    crate::stay_fees::post_late_check_out_fee(&tx, guest_id, chrono::Local::now().naive_local())?;
+    
     // Calculate stay days
     let check_in_date = NaiveDate::parse_from_str(&check_in, "%Y-%m-%d")
         .map_err(|_| "Invalid check-in date format")?;
-    let today = Utc::now().date_naive();
-    let stay_days = (today - check_in_date).num_days().max(1);
-    
-    // Calculate room total
-    let room_total = stay_days as f64 * daily_rate;
-    
-    // Calculate unpaid food total
-    let unpaid_food: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(total_amount), 0) FROM sales WHERE guest_id = ?1 AND paid = 0",
-        params![guest_id],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    // Calculate subtotal
-    let mut subtotal = room_total + unpaid_food;
-    
-    // Apply discounts
-    if let Some(pct) = discount_pct {
-        if pct > 0.0 && pct <= 100.0 {
-            subtotal *= (100.0 - pct) / 100.0;
-        }
-    }
-    
-    if let Some(flat) = discount_flat {
-        if flat > 0.0 {
-            subtotal -= flat;
-        }
-    }
+    let check_out = check_out.unwrap_or_else(|| Utc::now().date_naive());
+    let stay_days = (check_out - check_in_date).num_days().max(1);
     
-    // Clamp to >= 0
-    let grand_total = subtotal.max(0.0);
+    let (totals, voucher) = guest_bill(&tx, guest_id, stay_days, discount_pct, discount_flat, voucher_code.map(BillVoucher::Code))?;
+    let (food, service_charge) = bill_food_shares(&tx, guest_id, &totals)?;
     
     // Update guest status and free up the room
     let now = get_current_timestamp();
-    let today_str = today.format("%Y-%m-%d").to_string();
+    let today_str = check_out.format("%Y-%m-%d").to_string();
     
-    // Start a transaction to ensure both operations succeed or fail together
-    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let before = snapshot(&tx, "guest", guest_id)?;
     
     // Get the room_id before updating guest status
     let room_id: Option<i64> = tx.query_row(
         "SELECT room_id FROM customers WHERE id = ?1",
         params![guest_id],
         |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
+    )?;
     
-    // Update guest status
+    // Update guest status, keeping the check-out date it replaces for reopen_checkout
     tx.execute(
-        "UPDATE customers SET status = 'checked_out', check_out = ?1, updated_at = ?2 WHERE id = ?3",
-        params![today_str, now, guest_id],
-    ).map_err(|e| e.to_string())?;
+        "UPDATE customers SET status = 'checked_out', check_out = ?1, updated_at = ?2,
+                billed_amount = CASE WHEN nightly_pricing = 1 THEN ?3 ELSE billed_amount END,
+                checkout_total = ?4, checkout_food = ?5, checkout_service_charge = ?6,
+                checked_out_at = ?2, planned_check_out = check_out
+         WHERE id = ?7",
+        params![today_str, now, totals.room_total, totals.grand_total, food, service_charge, guest_id],
+    )?;
     
     // Update room status to not occupied
     if let Some(room_id) = room_id {
         tx.execute(
             "UPDATE resources SET is_occupied = 0, guest_id = NULL WHERE id = ?1",
             params![room_id],
-        )
-        .map_err(|e| e.to_string())?;
+        )?;
     }
     
-    // Commit the transaction
-    tx.commit().map_err(|e| e.to_string())?;
+    settle(&tx, totals.balance_due)?;
+    crate::taxes::record_bill_taxes(&tx, guest_id, &today_str, &totals.taxes)?;
+    let actor = audit_actor(session_token.as_deref());
+    if let Some((voucher_id, off)) = voucher {
+        crate::vouchers::record_redemption(&tx, voucher_id, off, None, Some(guest_id), &actor)?;
+    }
+    record_change(&tx, &actor, "guest", guest_id, "update", before, snapshot(&tx, "guest", guest_id)?)?;
+    
+    // Commit the transaction
+    tx.commit()?;
+    events::guest_changed(Some(guest_id), "checked_out");
+    if room_id.is_some() {
+        events::room_status_changed(room_id, "vacated");
+    }
+    crate::notifications::notify_in_background("checkout_invoice", NotificationSubject::Guest(guest_id));
+    crate::fiscal::fiscalize_in_background("checkout", guest_id);
+    
+    Ok(totals)
+}
+
+/// The voucher on a bill: a code to price now, or what one already took off.
+pub(crate) enum BillVoucher {
+    Code(String),
+    Redeemed(f64),
+}
+
+/// What a guest owes for `stay_days` nights: room, unpaid food, folio
+/// adjustments and extras, less a percentage and then a flat discount and any
+/// voucher, plus tax, rounded under the rounding rule. Both checkouts and the
+/// final invoice bill through this. A voucher code to redeem comes back
+/// alongside the totals.
+pub(crate) fn guest_bill(
+    conn: &rusqlite::Connection,
+    guest_id: i64,
+    stay_days: i64,
+    discount_pct: Option<f64>,
+    discount_flat: Option<f64>,
+    voucher: Option<BillVoucher>,
+) -> Result<(CheckoutTotals, Option<(i64, f64)>), AppError> {
+    // Calculate room total
+    let room_total = crate::pricing::guest_room_total(conn, guest_id, stay_days)?;
+    
+    // Calculate unpaid food total
+    let unpaid_food: f64 = conn.query_row(
+        "SELECT COALESCE(SUM(total_amount - amount_paid), 0) FROM sales WHERE guest_id = ?1 AND paid = 0 AND deleted_at IS NULL",
+        params![guest_id],
+        |row| row.get(0)
+    )?;
+    
+    // Folio adjustments (e.g. damage charges converted from checkout holds)
+    let adjustments = crate::checkout_holds::guest_adjustments_total(conn, guest_id)?;
+    
+    // Minibar, laundry and other extras billed to the room
+    let extra_charges = crate::guest_charges::guest_charges_total(conn, guest_id)?;
+    
+    // Calculate subtotal
+    let mut subtotal = room_total + unpaid_food + adjustments + extra_charges;
+    
+    // Apply discounts
+    if let Some(pct) = discount_pct {
+        if pct > 0.0 && pct <= 100.0 {
+            subtotal *= (100.0 - pct) / 100.0;
+        }
+    }
+    
+    if let Some(flat) = discount_flat {
+        if flat > 0.0 {
+            subtotal -= flat;
+        }
+    }
+    
+    // Clamp to >= 0, take off any voucher, then add each tax on its share of the discounted bill
+    let subtotal = subtotal.max(0.0);
+    let (voucher, voucher_off) = match voucher {
+        Some(BillVoucher::Code(code)) if !code.trim().is_empty() => {
+            let (voucher_id, off) = crate::vouchers::price_voucher(conn, &code, subtotal)?;
+            (Some((voucher_id, off)), off)
+        }
+        Some(BillVoucher::Redeemed(off)) => (None, off.min(subtotal)),
+        _ => (None, 0.0),
+    };
+    let subtotal = subtotal - voucher_off;
+    let taxes = crate::taxes::bill_taxes(conn, room_total + adjustments + extra_charges, unpaid_food, subtotal)?;
+    let tax: f64 = taxes.iter().map(|t| t.amount).sum();
+    let (grand_total, rounding) = crate::rounding::round_total(conn, subtotal + crate::taxes::tax_to_add(&taxes))?;
     
-    Ok(CheckoutTotals {
+    // Deposits were taken up front; only the rest is settled now
+    let deposits = crate::folio::guest_deposits_total(conn, guest_id)?;
+    
+    let totals = CheckoutTotals {
         room_total,
         unpaid_food,
+        adjustments,
+        extra_charges,
+        tax,
+        taxes,
+        rounding,
         grand_total,
+        balance_due: grand_total - deposits,
+        deposits,
         stay_days,
-    })
+    };
+    Ok((totals, voucher))
+}
+
+/// The food and the service charge on it within a guest's bill, after the
+/// bill's discounts and before tax, for the ledger to book apart from rooms.
+/// Part-paid orders count for what is left on them.
+fn bill_food_shares(conn: &rusqlite::Connection, guest_id: i64, totals: &CheckoutTotals) -> Result<(f64, f64), AppError> {
+    let unpaid_service: f64 = conn.query_row(
+        "SELECT COALESCE(SUM((total_amount - amount_paid) * service_charge / total_amount), 0) FROM sales
+         WHERE guest_id = ?1 AND paid = 0 AND deleted_at IS NULL AND total_amount > 0",
+        params![guest_id],
+        |row| row.get(0),
+    )?;
+    let billed = totals.room_total + totals.unpaid_food + totals.adjustments + totals.extra_charges;
+    let discounted = totals.grand_total - totals.rounding - crate::taxes::tax_to_add(&totals.taxes);
+    let share = if billed > 0.0 { discounted / billed } else { 0.0 };
+    let cents = |amount: f64| to_cents(amount * share) as f64 / 100.0;
+    Ok((cents(totals.unpaid_food - unpaid_service), cents(unpaid_service)))
 }
 
 #[command]
-pub fn update_guest(guest_id: i64, name: Option<String>, phone: Option<String>, room_id: Option<i64>, check_in: Option<String>, check_out: Option<String>, daily_rate: Option<f64>) -> Result<bool, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+#[allow(clippy::too_many_arguments)]
+pub fn update_guest(guest_id: i64, name: Option<String>, phone: Option<String>, room_id: Option<i64>, check_in: Option<String>, check_out: Option<String>, daily_rate: Option<f64>, acknowledge_unusual_date: Option<bool>, session_token: Option<String>) -> Result<bool, AppError> {
+    let conn = get_db_connection()?;
     
     // Check if guest exists
     let guest_exists: bool = conn.query_row(
@@ -597,10 +1503,11 @@ pub fn update_guest(guest_id: i64, name: Option<String>, phone: Option<String>,
     ).unwrap_or(false);
     
     if !guest_exists {
-        return Err("Guest not found or not active".to_string());
+        return Err(AppError::coded(GUEST_NOT_ACTIVE, "Guest not found or not active"));
     }
     
     // If room_id is being updated, check room availability
+    let mut moved_from: Option<Option<i64>> = None;
     if let Some(new_room_id) = room_id {
         // Check if the new room is available (not occupied by another guest)
         let room_occupied: bool = conn.query_row(
@@ -610,7 +1517,7 @@ pub fn update_guest(guest_id: i64, name: Option<String>, phone: Option<String>,
         ).unwrap_or(false);
         
         if room_occupied {
-            return Err("Room is already occupied by another guest".to_string());
+            return Err(AppError::coded(ROOM_OCCUPIED, "Room is already occupied by another guest"));
         }
         
         // Check if room exists
@@ -621,24 +1528,44 @@ pub fn update_guest(guest_id: i64, name: Option<String>, phone: Option<String>,
         ).unwrap_or(false);
         
         if !room_exists {
-            return Err("Room not found".to_string());
+            return Err(AppError::coded(ROOM_NOT_FOUND, "Room not found"));
+        }
+        
+        // Moving to another room needs that room in service; staying put doesn't
+        let current_room: Option<i64> = conn.query_row(
+            "SELECT room_id FROM customers WHERE id = ?1",
+            params![guest_id],
+            |row| row.get(0)
+        )?;
+        if current_room != Some(new_room_id) {
+            crate::maintenance::ensure_room_in_service(&conn, new_room_id)?;
+            moved_from = Some(current_room);
         }
     }
     
     // Validate daily_rate if provided
     if let Some(rate) = daily_rate {
         if rate <= 0.0 {
-            return Err("Daily rate must be positive".to_string());
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "daily_rate", "Daily rate must be positive"));
         }
     }
     
     // Validate name if provided
     if let Some(ref guest_name) = name {
         if guest_name.trim().is_empty() {
-            return Err("Guest name cannot be empty".to_string());
+            return Err(AppError::invalid(EMPTY_FIELD, "name", "Guest name cannot be empty"));
         }
     }
     
+    let acknowledged = acknowledge_unusual_date.unwrap_or(false);
+    let mut unusual_dates = Vec::new();
+    if let Some(ref checkin) = check_in {
+        unusual_dates.push(check_date_plausibility(&conn, "check_in", checkin, acknowledged)?);
+    }
+    if let Some(ref checkout) = check_out {
+        unusual_dates.push(check_date_plausibility(&conn, "check_out", checkout, acknowledged)?);
+    }
+    
     // Build dynamic update query
     let mut update_fields = Vec::new();
     let mut params_vec: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
@@ -691,12 +1618,98 @@ pub fn update_guest(guest_id: i64, name: Option<String>, phone: Option<String>,
     
     let params_refs: Vec<&dyn rusqlite::ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
     
-    conn.execute(&query, params_refs.as_slice())
-        .map_err(|e| e.to_string())?;
+    let before = snapshot(&conn, "guest", guest_id)?;
+    conn.execute(&query, params_refs.as_slice())?;
+    record_change(&conn, &audit_actor(session_token.as_deref()), "guest", guest_id, "update", before, snapshot(&conn, "guest", guest_id)?)?;
+    
+    log_acknowledged_dates(&conn, &format!("guest {}", guest_id), &unusual_dates)?;
+    
+    events::guest_changed(Some(guest_id), "updated");
+    if let Some(old_room) = moved_from {
+        if old_room.is_some() {
+            events::room_status_changed(old_room, "vacated");
+        }
+        events::room_status_changed(room_id, "occupied");
+    }
     
     Ok(true)
 }
 
+/// Move an in-house guest's check-out to a later date. The room must be free
+/// of booked reservations for the added nights, and the property under its
+/// occupancy cap; the final bill then covers the longer stay. Returns the
+/// stay's room charge projected to the new check-out.
+#[command]
+pub fn extend_stay(
+    guest_id: i64,
+    new_check_out: String,
+    acknowledge_unusual_date: Option<bool>,
+    override_occupancy_warning: Option<bool>,
+    session_token: Option<String>,
+) -> Result<StayExtension, AppError> {
+    validate_date_format(&new_check_out)?;
+    let conn = get_db_connection()?;
+    // The room and cap checks and the update stand or fall together
+    let tx = conn.unchecked_transaction()?;
+
+    let (room_id, check_in, check_out, party): (Option<i64>, String, Option<String>, i64) = tx.query_row(
+        "SELECT room_id, check_in, check_out, adults + children FROM customers WHERE id = ?1 AND status = 'active'",
+        params![guest_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+    ).optional()?
+        .ok_or_else(|| AppError::coded(GUEST_NOT_ACTIVE, "Guest not found or not active"))?;
+    let Some(room_id) = room_id else {
+        return Err("Walk-in customers have no stay to extend".into());
+    };
+
+    // Nights already covered run to the current check-out, or to today for an open-ended stay
+    let today = business_date_today();
+    let covered_until = check_out.clone().unwrap_or_else(|| today.clone()).max(check_in.clone());
+    if new_check_out <= covered_until {
+        return Err(format!("The new check-out must be after {}", covered_until).into());
+    }
+    let unusual = check_date_plausibility(&tx, "check_out", &new_check_out, acknowledge_unusual_date.unwrap_or(false))?;
+
+    if let Some((id, name, from, to)) = overlapping_reservation(&tx, room_id, &covered_until, &new_check_out, None)? {
+        return Err(format!(
+            "{}: reservation #{} for {} holds the room from {} to {}",
+            crate::validation::ROOM_RESERVED, id, name, from, to
+        ).into());
+    }
+    check_occupancy_cap(&tx, Some(room_id), &covered_until, Some(&new_check_out), party, override_occupancy_warning.unwrap_or(false))?;
+
+    let before = snapshot(&tx, "guest", guest_id)?;
+    tx.execute(
+        "UPDATE customers SET check_out = ?1, updated_at = ?2 WHERE id = ?3",
+        params![new_check_out, get_current_timestamp(), guest_id],
+    )?;
+    record_change(&tx, &audit_actor(session_token.as_deref()), "guest", guest_id, "update", before, snapshot(&tx, "guest", guest_id)?)?;
+    log_acknowledged_dates(&tx, &format!("guest {}", guest_id), &[unusual])?;
+    tx.commit()?;
+    events::guest_changed(Some(guest_id), "stay_extended");
+
+    let nights_until = |date: &str| -> Result<i64, String> {
+        let from = NaiveDate::parse_from_str(&check_in, "%Y-%m-%d").map_err(|_| "Invalid check-in date format")?;
+        let to = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| "Invalid check-out date format")?;
+        Ok((to - from).num_days().max(1))
+    };
+    let previous_nights = nights_until(&covered_until)?;
+    let nights = nights_until(&new_check_out)?;
+    let previous_total = crate::pricing::guest_room_total(&conn, guest_id, previous_nights)?;
+    let room_total = crate::pricing::guest_room_total(&conn, guest_id, nights)?;
+
+    Ok(StayExtension {
+        guest_id,
+        check_in,
+        previous_check_out: check_out,
+        check_out: new_check_out,
+        nights,
+        added_nights: nights - previous_nights,
+        room_total,
+        added_amount: room_total - previous_total,
+    })
+}
+
 // ===== MENU COMMANDS =====
 
 #[command]
@@ -708,18 +1721,19 @@ pub fn add_menu_item(
     track_stock: Option<i32>,
     stock_quantity: Option<i32>,
     low_stock_limit: Option<i32>,
-) -> Result<i64, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+) -> Result<i64, AppError> {
+    let conn = get_db_connection()?;
     
     validate_positive_amount(price, "price")?;
     
     if name.trim().is_empty() {
-        return Err("Menu item name cannot be empty".to_string());
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Menu item name cannot be empty"));
     }
     
     if category.trim().is_empty() {
-        return Err("Menu item category cannot be empty".to_string());
+        return Err(AppError::invalid(EMPTY_FIELD, "category", "Menu item category cannot be empty"));
     }
+    let category = crate::menu_categories::ensure_menu_category(&conn, &category)?;
     
     let available = is_available.unwrap_or(true);
     let track_stock = track_stock.unwrap_or(0);
@@ -727,13 +1741,13 @@ pub fn add_menu_item(
     let low_stock_limit = low_stock_limit.unwrap_or(5);
 
     if track_stock != 0 && track_stock != 1 {
-        return Err("track_stock must be 0 or 1".to_string());
+        return Err("track_stock must be 0 or 1".into());
     }
     if stock_quantity < 0 {
-        return Err("stock_quantity must be non-negative".to_string());
+        return Err("stock_quantity must be non-negative".into());
     }
     if low_stock_limit < 0 {
-        return Err("low_stock_limit must be non-negative".to_string());
+        return Err("low_stock_limit must be non-negative".into());
     }
     
     let result = conn.execute(
@@ -741,7 +1755,7 @@ pub fn add_menu_item(
         params![
             name.trim(),
             price,
-            category.trim(),
+            category,
             if available { 1 } else { 0 },
             stock_quantity,
             track_stock,
@@ -753,23 +1767,28 @@ pub fn add_menu_item(
         Ok(_) => Ok(conn.last_insert_rowid()),
         Err(e) => {
             if e.to_string().contains("UNIQUE constraint failed") {
-                Err(format!("Menu item '{}' already exists", name))
+                Err(format!("Menu item '{}' already exists", name).into())
             } else {
-                Err(e.to_string())
+                Err(e.into())
             }
         }
     }
 }
 
 #[tauri::command]
-pub fn get_menu_items() -> Result<Vec<MenuItem>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_menu_items() -> Result<Vec<MenuItem>, AppError> {
+    let conn = get_db_connection()?;
     
     let mut stmt = conn.prepare(
-        "SELECT id, name, price, category, is_available, stock_quantity, track_stock, low_stock_limit FROM menu_items WHERE is_active = 1 AND is_available = 1 ORDER BY name"
-    ).map_err(|e| e.to_string())?;
-    
-    let item_iter = stmt.query_map([], |row| {
+        "SELECT m.id, m.name, m.price, m.category, m.is_available, m.stock_quantity, m.track_stock, m.low_stock_limit, m.attributes,
+                (SELECT ds.prepared_quantity - ds.sold_quantity FROM daily_specials ds
+                  WHERE ds.menu_item_id = m.id AND ds.special_date = ?1 AND ds.rolled_off = 0)
+         FROM menu_items m LEFT JOIN menu_categories c ON c.name = m.category COLLATE NOCASE
+         WHERE m.is_active = 1 AND m.is_available = 1 AND COALESCE(c.is_active, 1) = 1
+         ORDER BY c.sort_order IS NULL, c.sort_order, m.category COLLATE NOCASE, m.name"
+    )?;
+    
+    let item_iter = stmt.query_map(params![business_date_today()], |row| {
         Ok(MenuItem {
             id: row.get(0)?,
             name: row.get(1)?,
@@ -779,12 +1798,21 @@ pub fn get_menu_items() -> Result<Vec<MenuItem>, String> {
             stock_quantity: row.get(5)?,
             track_stock: row.get(6)?,
             low_stock_limit: row.get(7)?,
+            attributes: parse_menu_attributes(row.get(8)?),
+            special_remaining: row.get(9)?,
+            rule_price: None,
         })
-    }).map_err(|e| e.to_string())?;
+    })?;
     
     let mut items = Vec::new();
     for item in item_iter {
-        items.push(item.map_err(|e| e.to_string())?);
+        items.push(item?);
+    }
+
+    // What a happy-hour price rule makes each item cost right now
+    let now = chrono::Local::now().naive_local();
+    for item in &mut items {
+        item.rule_price = crate::menu_pricing::price_rule_for(&conn, item.id, item.price, now)?.map(|(_, price)| price);
     }
     
     Ok(items)
@@ -800,7 +1828,7 @@ pub fn update_menu_item(
     track_stock: Option<i32>,
     stock_quantity: Option<i32>,
     low_stock_limit: Option<i32>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     println!("🐛 DEBUG update_menu_item - Received parameters:");
     println!("  item_id: {:?}", item_id);
     println!("  name: {:?}", name);
@@ -811,7 +1839,7 @@ pub fn update_menu_item(
     println!("  stock_quantity: {:?}", stock_quantity);
     println!("  low_stock_limit: {:?}", low_stock_limit);
     
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let conn = get_db_connection()?;
     
     // Build dynamic update query
     let mut update_parts = Vec::new();
@@ -819,7 +1847,7 @@ pub fn update_menu_item(
     
     if let Some(ref item_name) = name {
         if item_name.trim().is_empty() {
-            return Err("Menu item name cannot be empty".to_string());
+            return Err(AppError::invalid(EMPTY_FIELD, "name", "Menu item name cannot be empty"));
         }
         update_parts.push("name = ?");
         params.push(Box::new(item_name.trim().to_string()));
@@ -827,7 +1855,7 @@ pub fn update_menu_item(
     
     if let Some(item_price) = price {
         if item_price < 0.0 {
-            return Err("Price must be positive".to_string());
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "price", "Price must be positive"));
         }
         update_parts.push("price = ?");
         params.push(Box::new(item_price));
@@ -835,7 +1863,7 @@ pub fn update_menu_item(
     
     if let Some(ref cat) = category {
         update_parts.push("category = ?");
-        params.push(Box::new(cat.to_string()));
+        params.push(Box::new(crate::menu_categories::ensure_menu_category(&conn, cat)?));
     }
     
     if let Some(available) = is_available {
@@ -845,7 +1873,7 @@ pub fn update_menu_item(
 
     if let Some(track) = track_stock {
         if track != 0 && track != 1 {
-            return Err("track_stock must be 0 or 1".to_string());
+            return Err("track_stock must be 0 or 1".into());
         }
         update_parts.push("track_stock = ?");
         params.push(Box::new(track));
@@ -853,7 +1881,7 @@ pub fn update_menu_item(
 
     if let Some(stock) = stock_quantity {
         if stock < 0 {
-            return Err("stock_quantity must be non-negative".to_string());
+            return Err("stock_quantity must be non-negative".into());
         }
         update_parts.push("stock_quantity = ?");
         params.push(Box::new(stock));
@@ -861,16 +1889,19 @@ pub fn update_menu_item(
 
     if let Some(limit) = low_stock_limit {
         if limit < 0 {
-            return Err("low_stock_limit must be non-negative".to_string());
+            return Err("low_stock_limit must be non-negative".into());
         }
         update_parts.push("low_stock_limit = ?");
         params.push(Box::new(limit));
     }
     
     if update_parts.is_empty() {
-        return Err("No fields to update".to_string());
+        return Err("No fields to update".into());
     }
     
+    update_parts.push("updated_at = ?");
+    params.push(Box::new(get_current_timestamp()));
+    
     let query = format!("UPDATE menu_items SET {} WHERE id = ?", update_parts.join(", "));
     params.push(Box::new(item_id));
     
@@ -885,16 +1916,16 @@ pub fn update_menu_item(
     })?;
     
     if affected == 0 {
-        return Err("Menu item not found".to_string());
+        return Err(AppError::coded(MENU_ITEM_NOT_FOUND, "Menu item not found"));
     }
     
     Ok("Menu item updated successfully".to_string())
 }
 
 #[command]
-pub fn delete_menu_item(item_id: i64) -> Result<String, String> {
+pub fn delete_menu_item(item_id: i64) -> Result<String, AppError> {
     println!("🐛 DEBUG delete_menu_item - Received item_id: {:?}", item_id);
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let conn = get_db_connection()?;
     
     // Check if menu item is used in any orders
     println!("🐛 DEBUG delete_menu_item - Checking for existing orders...");
@@ -913,8 +1944,8 @@ pub fn delete_menu_item(item_id: i64) -> Result<String, String> {
         // Soft delete by setting is_available = 0
         println!("🐛 DEBUG delete_menu_item - Item used in orders, doing soft delete...");
         let affected = conn.execute(
-            "UPDATE menu_items SET is_available = 0 WHERE id = ?1",
-            params![item_id],
+            "UPDATE menu_items SET is_available = 0, updated_at = ?2 WHERE id = ?1",
+            params![item_id, get_current_timestamp()],
         ).map_err(|e| {
             println!("❌ DEBUG delete_menu_item - Error in soft delete: {}", e);
             e.to_string()
@@ -923,7 +1954,7 @@ pub fn delete_menu_item(item_id: i64) -> Result<String, String> {
         println!("🐛 DEBUG delete_menu_item - Soft delete affected rows: {}", affected);
         
         if affected == 0 {
-            return Err("Menu item not found".to_string());
+            return Err(AppError::coded(MENU_ITEM_NOT_FOUND, "Menu item not found"));
         }
         
         println!("✅ DEBUG delete_menu_item - Soft delete success!");
@@ -942,7 +1973,7 @@ pub fn delete_menu_item(item_id: i64) -> Result<String, String> {
         println!("🐛 DEBUG delete_menu_item - Hard delete affected rows: {}", affected);
         
         if affected == 0 {
-            return Err("Menu item not found".to_string());
+            return Err(AppError::coded(MENU_ITEM_NOT_FOUND, "Menu item not found"));
         }
         
         println!("✅ DEBUG delete_menu_item - Hard delete success!");
@@ -950,332 +1981,1770 @@ pub fn delete_menu_item(item_id: i64) -> Result<String, String> {
     }
 }
 
-// ===== DASHBOARD COMMANDS =====
+// ===== MENU ITEM ATTRIBUTES =====
 
-#[command]
-pub fn dashboard_stats() -> Result<DashboardStats, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    let now = Utc::now();
-    let current_month_start = format!("{}-{:02}-01", now.year(), now.month());
-    let current_month_end = format!("{}-{:02}-{:02}", now.year(), now.month(), 
-        NaiveDate::from_ymd_opt(
-            if now.month() == 12 { now.year() + 1 } else { now.year() }, 
-            if now.month() == 12 { 1 } else { now.month() + 1 }, 
-            1
-        ).unwrap().pred_opt().unwrap().day()
-    );
-    
-    // Total guests this month (checked in this month)
-    let total_guests_this_month: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM customers WHERE check_in >= ?1 AND check_in <= ?2",
-        params![current_month_start, current_month_end],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    // Active guests
-    let active_guests: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM customers WHERE status = 'active'",
-        [],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    // Total income this month
-    let room_income: f64 = conn.query_row(
-        "SELECT COALESCE(SUM((julianday(COALESCE(check_out, date('now'))) - julianday(check_in) + 1) * daily_rate), 0)
-         FROM customers 
-         WHERE status = 'checked_out' 
-         AND check_out >= ?1 AND check_out <= ?2",
-        params![current_month_start, current_month_end],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    let food_income: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(total_amount), 0) 
-         FROM sales 
-         WHERE paid = 1 
-         AND date(paid_at) >= ?1 AND date(paid_at) <= ?2",
-        params![current_month_start, current_month_end],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    let total_income = room_income + food_income;
-    
-    // Total expenses this month
-    let total_expenses: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(amount), 0) FROM expenses WHERE date >= ?1 AND date <= ?2",
-        params![current_month_start, current_month_end],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    // Total food orders this month
-    let total_food_orders: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM sales WHERE date(created_at) >= ?1 AND date(created_at) <= ?2",
-        params![current_month_start, current_month_end],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    Ok(DashboardStats {
-        total_guests_this_month,
-        total_income,
-        total_expenses,
-        profit_loss: total_income - total_expenses,
-        total_food_orders,
-        active_guests,
-    })
+pub fn parse_menu_attributes(raw: Option<String>) -> Vec<MenuItemAttribute> {
+    raw.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default()
 }
 
-// Get low stock items for dashboard alerts
-#[tauri::command]
-pub fn get_low_stock_items() -> Result<Vec<LowStockItem>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    let mut stmt = conn.prepare(
-        "SELECT id, name, stock_quantity, low_stock_limit 
-         FROM menu_items 
-         WHERE track_stock = 1 
-         AND stock_quantity <= low_stock_limit
-         ORDER BY stock_quantity ASC"
-    ).map_err(|e| e.to_string())?;
-    
-    let items = stmt.query_map([], |row| {
-        Ok(LowStockItem {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            stock_quantity: row.get(2)?,
-            low_stock_limit: row.get(3)?,
-        })
-    }).map_err(|e| e.to_string())?;
-    
-    items.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+pub fn parse_selected_attributes(raw: Option<String>) -> Vec<(String, String)> {
+    raw.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default()
 }
 
-// ===== FOOD ORDER COMMANDS =====
-
 #[command]
-pub fn add_food_order(guest_id: Option<i64>, customer_type: String, customer_name: Option<String>, items: Vec<OrderItemInput>) -> Result<i64, String> {
-    println!("🐛 DEBUG add_food_order - Received parameters:");
-    println!("  guest_id: {:?}", guest_id);
-    println!("  customer_type: {:?}", customer_type);
-    println!("  customer_name: {:?}", customer_name);
-    println!("  items count: {:?}", items.len());
-    
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    if items.is_empty() {
-        return Err("Order must have at least one item".to_string());
-    }
-    
-    // Check stock availability for tracked items BEFORE starting transaction
-    for item in &items {
-        if let Some(menu_item_id) = item.menu_item_id {
-            let stock_info: Result<(i32, i32), _> = conn.query_row(
-                "SELECT stock_quantity, track_stock FROM menu_items WHERE id = ?1",
-                params![menu_item_id],
-                |row| Ok((row.get(0)?, row.get(1)?))
-            );
-            
-            if let Ok((current_stock, track_stock)) = stock_info {
-                if track_stock == 1 && current_stock < item.quantity {
-                    return Err(format!(
-                        "Insufficient stock for '{}'. Available: {}, Requested: {}",
-                        item.item_name, current_stock, item.quantity
-                    ));
-                }
+pub fn set_menu_item_attributes(item_id: i64, attributes: Vec<MenuItemAttribute>) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+
+    let mut cleaned: Vec<MenuItemAttribute> = Vec::with_capacity(attributes.len());
+    for attribute in attributes {
+        let name = attribute.name.trim().to_string();
+        if name.is_empty() {
+            return Err("Attribute name cannot be empty".into());
+        }
+        if cleaned.iter().any(|a| a.name.eq_ignore_ascii_case(&name)) {
+            return Err(format!("Attribute '{}' is defined more than once", name).into());
+        }
+
+        let mut values: Vec<String> = Vec::with_capacity(attribute.values.len());
+        for value in attribute.values {
+            let value = value.trim().to_string();
+            if value.is_empty() {
+                return Err(format!("Attribute '{}' has an empty value", name).into());
             }
+            if values.iter().any(|v| v.eq_ignore_ascii_case(&value)) {
+                return Err(format!("Attribute '{}' lists value '{}' more than once", name, value).into());
+            }
+            values.push(value);
+        }
+        if values.is_empty() {
+            return Err(format!("Attribute '{}' must have at least one value", name).into());
         }
+
+        cleaned.push(MenuItemAttribute {
+            name,
+            values,
+            required: attribute.required,
+        });
     }
-    
-    // Calculate total
-    let total_amount: f64 = items.iter().map(|item| item.unit_price * item.quantity as f64).sum();
-    println!("🐛 DEBUG add_food_order - Total amount: {:?}", total_amount);
-    
-    // Insert order
-    println!("🐛 DEBUG add_food_order - Inserting food order...");
-    let _rows_affected = conn.execute(
-        "INSERT INTO sales (guest_id, customer_type, customer_name, created_at, paid, total_amount) 
-         VALUES (?1, ?2, ?3, ?4, 0, ?5)",
-        params![guest_id, customer_type, customer_name, get_current_timestamp(), total_amount],
-    ).map_err(|e| e.to_string())?;
-    
-    let order_id = conn.last_insert_rowid();
-    
-    // Insert order items and decrement stock
-    for item in items {
-        conn.execute(
-            "INSERT INTO sale_items (order_id, menu_item_id, item_name, unit_price, quantity, line_total)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![order_id, item.menu_item_id, item.item_name, item.unit_price, item.quantity, 
-                   item.unit_price * item.quantity as f64],
-        ).map_err(|e| e.to_string())?;
-        
-        // Decrement stock for tracked items
-        if let Some(menu_item_id) = item.menu_item_id {
-            conn.execute(
-                "UPDATE menu_items 
-                 SET stock_quantity = stock_quantity - ?1 
-                 WHERE id = ?2 AND track_stock = 1",
-                params![item.quantity, menu_item_id],
-            ).map_err(|e| format!("Failed to decrement stock: {}", e))?;
+
+    let json = if cleaned.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&cleaned)?)
+    };
+
+    let affected = conn.execute(
+        "UPDATE menu_items SET attributes = ?1, updated_at = ?2 WHERE id = ?3",
+        params![json, get_current_timestamp(), item_id],
+    )?;
+
+    if affected == 0 {
+        return Err(AppError::coded(MENU_ITEM_NOT_FOUND, "Menu item not found"));
+    }
+
+    Ok("Menu item attributes updated successfully".to_string())
+}
+
+/// Check an order line's selections against its menu item's attribute definitions and
+/// return them normalized to the defined spelling.
+fn validate_selected_attributes(
+    item_name: &str,
+    definitions: &[MenuItemAttribute],
+    selected: &[(String, String)],
+) -> Result<Vec<(String, String)>, String> {
+    let mut normalized: Vec<(String, String)> = Vec::with_capacity(selected.len());
+
+    for (name, value) in selected {
+        let definition = definitions
+            .iter()
+            .find(|d| d.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Unknown attribute '{}' for '{}'", name.trim(), item_name))?;
+
+        if normalized.iter().any(|(n, _)| n == &definition.name) {
+            return Err(format!("Attribute '{}' selected more than once for '{}'", definition.name, item_name));
+        }
+
+        let allowed = definition
+            .values
+            .iter()
+            .find(|v| v.eq_ignore_ascii_case(value.trim()))
+            .ok_or_else(|| format!(
+                "Invalid value '{}' for attribute '{}' on '{}'. Allowed: {}",
+                value.trim(),
+                definition.name,
+                item_name,
+                definition.values.join(", ")
+            ))?;
+
+        normalized.push((definition.name.clone(), allowed.clone()));
+    }
+
+    for definition in definitions.iter().filter(|d| d.required) {
+        if !normalized.iter().any(|(n, _)| n == &definition.name) {
+            return Err(format!("Attribute '{}' is required for '{}'", definition.name, item_name));
         }
     }
-    
-    Ok(order_id)
+
+    Ok(normalized)
 }
 
-#[tauri::command]
-pub fn get_food_orders_by_guest(guest_id: i64) -> Result<Vec<FoodOrderSummary>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    let mut stmt = conn.prepare(
-        "SELECT fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount,
-                GROUP_CONCAT(oi.item_name || ' x' || oi.quantity) as items
-            FROM sales fo
-            LEFT JOIN sale_items oi ON fo.id = oi.order_id
-         WHERE fo.guest_id = ?1
-         GROUP BY fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount
-         ORDER BY fo.created_at DESC"
+// ===== DAILY SPECIALS =====
+
+/// The calendar day specials are prepared for.
+pub fn business_date_today() -> String {
+    chrono::Local::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+/// Close out specials from earlier days so unsold portions can no longer be ordered.
+fn roll_off_past_specials(conn: &rusqlite::Connection) -> Result<(), String> {
+    conn.execute(
+        "UPDATE daily_specials SET rolled_off = 1 WHERE special_date < ?1 AND rolled_off = 0",
+        params![business_date_today()],
     ).map_err(|e| e.to_string())?;
-    
-    let orders = stmt.query_map([guest_id], |row| {
-        Ok(FoodOrderSummary {
-            id: row.get(0)?,
-            created_at: row.get(1)?,
-            paid: row.get::<_, i32>(2)? == 1,
-            paid_at: row.get(3)?,
-            total_amount: row.get(4)?,
-            items: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
-            guest_id: Some(guest_id),
-            guest_name: None, // This function doesn't need guest name since it's for a specific guest
-        })
-    }).map_err(|e| e.to_string())?;
-    
-    orders.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    Ok(())
 }
 
-#[command]
-pub fn get_food_orders() -> Result<Vec<FoodOrderSummary>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
+fn query_daily_specials(conn: &rusqlite::Connection, date: &str) -> Result<Vec<DailySpecial>, String> {
     let mut stmt = conn.prepare(
-        "SELECT fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount,
-                GROUP_CONCAT(oi.item_name || ' x' || oi.quantity) as items,
-                fo.guest_id,
-                COALESCE(g.name, 'Walk-in') as guest_name
-            FROM sales fo
-            LEFT JOIN sale_items oi ON fo.id = oi.order_id
-            LEFT JOIN customers g ON fo.guest_id = g.id
-         GROUP BY fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount, fo.guest_id, g.name
-         ORDER BY fo.created_at DESC"
+        "SELECT id, special_date, menu_item_id, name, price, prepared_quantity, sold_quantity
+         FROM daily_specials WHERE special_date = ?1 ORDER BY name"
     ).map_err(|e| e.to_string())?;
-    
-    let orders = stmt.query_map([], |row| {
-        Ok(FoodOrderSummary {
+
+    let rows = stmt.query_map(params![date], |row| {
+        let prepared_quantity: i32 = row.get(5)?;
+        let sold_quantity: i32 = row.get(6)?;
+        Ok(DailySpecial {
             id: row.get(0)?,
-            created_at: row.get(1)?,
-            paid: row.get::<_, i32>(2)? == 1,
-            paid_at: row.get(3)?,
-            total_amount: row.get(4)?,
-            items: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
-            guest_id: row.get(6)?,
-            guest_name: row.get(7)?,
+            special_date: row.get(1)?,
+            menu_item_id: row.get(2)?,
+            name: row.get(3)?,
+            price: row.get(4)?,
+            prepared_quantity,
+            sold_quantity,
+            remaining: prepared_quantity - sold_quantity,
         })
     }).map_err(|e| e.to_string())?;
-    
-    orders.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
-}
 
-#[tauri::command]
-pub fn mark_order_paid(order_id: i64) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    let rows_affected = conn.execute(
-        "UPDATE sales SET paid = 1, paid_at = ?1 WHERE id = ?2",
-        params![get_current_timestamp(), order_id],
-    ).map_err(|e| e.to_string())?;
-    
-    if rows_affected == 0 {
-        Err("Order not found".to_string())
-    } else {
-        Ok("Order marked as paid".to_string())
-    }
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
 }
 
-// ===== EXPENSE COMMANDS =====
-
 #[command]
-pub fn add_expense(date: String, category: String, description: Option<String>, amount: f64) -> Result<i64, String> {
-    if amount <= 0.0 {
-        return Err("Amount must be positive".to_string());
+pub fn set_daily_specials(date: String, entries: Vec<DailySpecialInput>) -> Result<Vec<DailySpecial>, AppError> {
+    crate::validation::validate_date_format(&date)?;
+    if date < business_date_today() {
+        return Err("Specials cannot be set for a past date".into());
     }
-    
-    validate_date_format(&date)?;
-    
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    conn.execute(
-        "INSERT INTO expenses (date, category, description, amount) VALUES (?1, ?2, ?3, ?4)",
-        params![date, category, description, amount],
-    ).map_err(|e| e.to_string())?;
-    
-    Ok(conn.last_insert_rowid())
-}
 
-#[command]
-pub fn get_expenses(start_date: Option<String>, end_date: Option<String>) -> Result<Vec<ExpenseRecord>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    let (query, params): (String, Vec<String>) = match (start_date, end_date) {
-        (Some(start), Some(end)) => {
+    let conn = get_db_connection()?;
+    roll_off_past_specials(&conn)?;
+
+    // Resolve every entry to a (menu_item_id, name, price, prepared) row first
+    let mut resolved: Vec<(Option<i64>, String, f64, i32)> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if entry.prepared_quantity < 0 {
+            return Err("Prepared quantity cannot be negative".into());
+        }
+
+        let (menu_item_id, name, price) = match entry.menu_item_id {
+            Some(menu_item_id) => {
+                let (name, price): (String, f64) = conn.query_row(
+                    "SELECT name, price FROM menu_items WHERE id = ?1 AND is_active = 1",
+                    params![menu_item_id],
+                    |row| Ok((row.get(0)?, row.get(1)?))
+                ).map_err(|_| format!("Menu item {} not found", menu_item_id))?;
+                (Some(menu_item_id), name, entry.price.unwrap_or(price))
+            }
+            None => {
+                let name = entry.name.as_deref().map(str::trim).unwrap_or("").to_string();
+                if name.is_empty() {
+                    return Err("Custom specials need a name".into());
+                }
+                let price = entry.price.ok_or_else(|| format!("Custom special '{}' needs a price", name))?;
+                (None, name, price)
+            }
+        };
+
+        validate_positive_amount(price, "price")?;
+        if resolved.iter().any(|(_, n, _, _)| n.eq_ignore_ascii_case(&name)) {
+            return Err(format!("'{}' is listed more than once", name).into());
+        }
+        resolved.push((menu_item_id, name, price, entry.prepared_quantity));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let existing = query_daily_specials(&tx, &date)?;
+
+    // Specials dropped from the board can only go if nothing was sold yet
+    for special in &existing {
+        if !resolved.iter().any(|(_, n, _, _)| n.eq_ignore_ascii_case(&special.name)) {
+            if special.sold_quantity > 0 {
+                return Err(format!(
+                    "Cannot remove '{}': {} portions already sold",
+                    special.name, special.sold_quantity
+                ).into());
+            }
+            tx.execute("DELETE FROM daily_specials WHERE id = ?1", params![special.id])?;
+        }
+    }
+
+    for (menu_item_id, name, price, prepared) in resolved {
+        match existing.iter().find(|s| s.name.eq_ignore_ascii_case(&name)) {
+            Some(special) => {
+                if prepared < special.sold_quantity {
+                    return Err(format!(
+                        "Prepared quantity for '{}' cannot be below the {} already sold",
+                        name, special.sold_quantity
+                    ).into());
+                }
+                tx.execute(
+                    "UPDATE daily_specials SET menu_item_id = ?1, price = ?2, prepared_quantity = ?3 WHERE id = ?4",
+                    params![menu_item_id, price, prepared, special.id],
+                )?;
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO daily_specials (special_date, menu_item_id, name, price, prepared_quantity)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![date, menu_item_id, name, price, prepared],
+                )?;
+            }
+        }
+    }
+
+    tx.commit()?;
+    query_daily_specials(&conn, &date).map_err(AppError::from)
+}
+
+#[command]
+pub fn get_daily_specials(date: Option<String>) -> Result<Vec<DailySpecial>, AppError> {
+    let conn = get_db_connection()?;
+    roll_off_past_specials(&conn)?;
+    query_daily_specials(&conn, &date.unwrap_or_else(business_date_today)).map_err(AppError::from)
+}
+
+#[command]
+pub async fn specials_performance_report(start_date: String, end_date: String) -> Result<Vec<SpecialsPerformanceRow>, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        roll_off_past_specials(&conn)?;
+
+        // Waste only counts once the day has rolled off; today's leftovers are still for sale
+        let mut stmt = conn.prepare(
+            "SELECT special_date,
+                    SUM(prepared_quantity),
+                    SUM(sold_quantity),
+                    SUM(CASE WHEN rolled_off = 1 THEN prepared_quantity - sold_quantity ELSE 0 END)
+             FROM daily_specials
+             WHERE special_date >= ?1 AND special_date <= ?2
+             GROUP BY special_date
+             ORDER BY special_date"
+        )?;
+
+        let rows = stmt.query_map(params![start_date, end_date], |row| {
+            Ok(SpecialsPerformanceRow {
+                date: row.get(0)?,
+                prepared: row.get(1)?,
+                sold: row.get(2)?,
+                waste: row.get(3)?,
+            })
+        })?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+    })
+    .await
+}
+
+/// Take `quantity` portions of a special inside the caller's transaction. The guarded
+/// UPDATE means two orders racing for the last portion cannot both succeed.
+fn claim_special_portions(
+    conn: &rusqlite::Connection,
+    special_id: Option<i64>,
+    menu_item_id: Option<i64>,
+    quantity: i32,
+) -> Result<(), String> {
+    let today = business_date_today();
+    let special: Option<(i64, String)> = match (special_id, menu_item_id) {
+        (Some(id), _) => conn.query_row(
+            "SELECT id, name FROM daily_specials WHERE id = ?1 AND special_date = ?2 AND rolled_off = 0",
+            params![id, today],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).map(Some).map_err(|_| format!("{}: special is not available today", crate::validation::SPECIAL_SOLD_OUT))?,
+        (None, Some(menu_item_id)) => conn.query_row(
+            "SELECT id, name FROM daily_specials WHERE menu_item_id = ?1 AND special_date = ?2 AND rolled_off = 0",
+            params![menu_item_id, today],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).ok(),
+        (None, None) => None,
+    };
+
+    let Some((id, name)) = special else {
+        return Ok(());
+    };
+
+    let claimed = conn.execute(
+        "UPDATE daily_specials SET sold_quantity = sold_quantity + ?1
+         WHERE id = ?2 AND prepared_quantity - sold_quantity >= ?1",
+        params![quantity, id],
+    ).map_err(|e| e.to_string())?;
+
+    if claimed == 0 {
+        let remaining: i32 = conn.query_row(
+            "SELECT prepared_quantity - sold_quantity FROM daily_specials WHERE id = ?1",
+            params![id],
+            |row| row.get(0)
+        ).map_err(|e| e.to_string())?;
+        return Err(format!(
+            "{}: '{}' has {} portion(s) remaining, {} requested",
+            crate::validation::SPECIAL_SOLD_OUT, name, remaining, quantity
+        ));
+    }
+
+    Ok(())
+}
+
+// ===== BULK PRICE UPDATES =====
+
+const DEFAULT_MAX_REASONABLE_AMOUNT: f64 = 1_000_000.0;
+
+fn apply_price_rounding(price: f64, rounding: PriceRounding) -> f64 {
+    match rounding {
+        PriceRounding::None => (price * 100.0).round() / 100.0,
+        PriceRounding::Nearest5 => (price / 5.0).round() * 5.0,
+        PriceRounding::Nearest10 => (price / 10.0).round() * 10.0,
+        PriceRounding::UpTo10 => (price / 10.0).ceil() * 10.0,
+    }
+}
+
+fn adjusted_price(old_price: f64, adjustment: PriceAdjustment, rounding: PriceRounding) -> f64 {
+    let raw = match adjustment {
+        PriceAdjustment::Percent(pct) => old_price * (100.0 + pct) / 100.0,
+        PriceAdjustment::Flat(amount) => old_price + amount,
+    };
+    apply_price_rounding(raw, rounding)
+}
+
+fn max_reasonable_amount(conn: &rusqlite::Connection) -> Result<f64, String> {
+    Ok(get_parsed_setting::<f64>(conn, "max_reasonable_amount")?
+        .filter(|v| *v > 0.0)
+        .unwrap_or(DEFAULT_MAX_REASONABLE_AMOUNT))
+}
+
+/// Work out old -> new prices for a set of (id, name, price) rows, splitting out the
+/// ones that would end up negative or above max_reasonable_amount.
+fn plan_price_changes(
+    rows: Vec<(i64, String, f64)>,
+    adjustment: PriceAdjustment,
+    rounding: PriceRounding,
+    max_amount: f64,
+) -> (Vec<PriceChange>, Vec<PriceChange>) {
+    let mut changes = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (id, name, old_price) in rows {
+        let new_price = adjusted_price(old_price, adjustment, rounding);
+        let skipped_reason = if !new_price.is_finite() || new_price < 0.0 {
+            Some("New price would be negative".to_string())
+        } else if new_price > max_amount {
+            Some(format!("New price exceeds the maximum of {:.2}", max_amount))
+        } else {
+            None
+        };
+
+        let change = PriceChange { id, name, old_price, new_price, skipped_reason };
+        if change.skipped_reason.is_some() {
+            skipped.push(change);
+        } else if (change.new_price - change.old_price).abs() >= 0.005 {
+            changes.push(change);
+        }
+    }
+
+    (changes, skipped)
+}
+
+fn validate_price_adjustment(adjustment: PriceAdjustment) -> Result<(), String> {
+    let value = match adjustment {
+        PriceAdjustment::Percent(pct) => pct,
+        PriceAdjustment::Flat(amount) => amount,
+    };
+    if !value.is_finite() {
+        return Err("Adjustment must be a finite number".to_string());
+    }
+    if let PriceAdjustment::Percent(pct) = adjustment {
+        if pct <= -100.0 {
+            return Err("Percentage decrease must be less than 100%".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn bulk_update_prices(
+    app: tauri::AppHandle,
+    category: Option<String>,
+    adjustment: PriceAdjustment,
+    rounding: Option<PriceRounding>,
+    dry_run: bool,
+) -> Result<BulkPriceUpdateResult, AppError> {
+    let result = update_menu_prices(category, adjustment, rounding, dry_run)?;
+    if !result.dry_run && !result.changes.is_empty() {
+        let _ = app.emit("menu-changed", &result.changes);
+    }
+    Ok(result)
+}
+
+/// Adjust the prices of every active menu item (or one category's), logging
+/// each change to menu_price_history. A dry run only reports what would change.
+pub fn update_menu_prices(
+    category: Option<String>,
+    adjustment: PriceAdjustment,
+    rounding: Option<PriceRounding>,
+    dry_run: bool,
+) -> Result<BulkPriceUpdateResult, AppError> {
+    validate_price_adjustment(adjustment)?;
+    let conn = get_db_connection()?;
+    let max_amount = max_reasonable_amount(&conn)?;
+    let category = category.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, price FROM menu_items
+         WHERE is_active = 1 AND (?1 IS NULL OR LOWER(category) = LOWER(?1))
+         ORDER BY name"
+    )?;
+    let rows = stmt.query_map(params![category], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(stmt);
+
+    let (changes, skipped) = plan_price_changes(rows, adjustment, rounding.unwrap_or_default(), max_amount);
+
+    if !dry_run && !changes.is_empty() {
+        let now = get_current_timestamp();
+        let tx = conn.unchecked_transaction()?;
+        for change in &changes {
+            tx.execute(
+                "UPDATE menu_items SET price = ?1, updated_at = ?2 WHERE id = ?3",
+                params![change.new_price, now, change.id],
+            )?;
+            tx.execute(
+                "INSERT INTO menu_price_history (menu_item_id, old_price, new_price, changed_at) VALUES (?1, ?2, ?3, ?4)",
+                params![change.id, change.old_price, change.new_price, now],
+            )?;
+        }
+        tx.commit()?;
+    }
+
+    Ok(BulkPriceUpdateResult { dry_run, changes, skipped })
+}
+
+#[tauri::command]
+pub fn bulk_update_room_rates(
+    app: tauri::AppHandle,
+    room_type: Option<String>,
+    adjustment: PriceAdjustment,
+    rounding: Option<PriceRounding>,
+    dry_run: bool,
+) -> Result<BulkPriceUpdateResult, AppError> {
+    let result = update_room_rates(room_type, adjustment, rounding, dry_run)?;
+    if !result.dry_run && !result.changes.is_empty() {
+        let _ = app.emit("rooms-changed", &result.changes);
+    }
+    Ok(result)
+}
+
+/// Adjust the daily rate of every active room (or one room type's), logging
+/// each change to room_rate_history. A dry run only reports what would change.
+pub fn update_room_rates(
+    room_type: Option<String>,
+    adjustment: PriceAdjustment,
+    rounding: Option<PriceRounding>,
+    dry_run: bool,
+) -> Result<BulkPriceUpdateResult, AppError> {
+    validate_price_adjustment(adjustment)?;
+    let conn = get_db_connection()?;
+    let max_amount = max_reasonable_amount(&conn)?;
+    let room_type = room_type.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+
+    let mut stmt = conn.prepare(
+        "SELECT id, number, daily_rate FROM resources
+         WHERE is_active = 1 AND (?1 IS NULL OR LOWER(room_type) = LOWER(?1))
+         ORDER BY number"
+    )?;
+    let rows = stmt.query_map(params![room_type], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(stmt);
+
+    let (changes, skipped) = plan_price_changes(rows, adjustment, rounding.unwrap_or_default(), max_amount);
+
+    if !dry_run && !changes.is_empty() {
+        let now = get_current_timestamp();
+        let tx = conn.unchecked_transaction()?;
+        for change in &changes {
+            tx.execute(
+                "UPDATE resources SET daily_rate = ?1 WHERE id = ?2",
+                params![change.new_price, change.id],
+            )?;
+            tx.execute(
+                "INSERT INTO room_rate_history (room_id, old_rate, new_rate, changed_at) VALUES (?1, ?2, ?3, ?4)",
+                params![change.id, change.old_price, change.new_price, now],
+            )?;
+        }
+        tx.commit()?;
+    }
+
+    Ok(BulkPriceUpdateResult { dry_run, changes, skipped })
+}
+
+// ===== DASHBOARD COMMANDS =====
+
+#[command]
+pub async fn dashboard_stats() -> Result<DashboardStats, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+    
+        let now = Utc::now();
+        let current_month_start = format!("{}-{:02}-01", now.year(), now.month());
+        let current_month_end = format!("{}-{:02}-{:02}", now.year(), now.month(), 
+            NaiveDate::from_ymd_opt(
+                if now.month() == 12 { now.year() + 1 } else { now.year() }, 
+                if now.month() == 12 { 1 } else { now.month() + 1 }, 
+                1
+            ).unwrap().pred_opt().unwrap().day()
+        );
+    
+        // Total guests this month (checked in this month)
+        let total_guests_this_month: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM customers WHERE check_in >= ?1 AND check_in <= ?2",
+            params![current_month_start, current_month_end],
+            |row| row.get(0)
+        )?;
+    
+        // Active guests
+        let active_guests: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM customers WHERE status = 'active'",
+            [],
+            |row| row.get(0)
+        )?;
+    
+        // Total income this month
+        let room_income: f64 = conn.query_row(
+            "SELECT COALESCE(SUM(COALESCE(billed_amount, (julianday(COALESCE(check_out, date('now'))) - julianday(check_in) + 1) * daily_rate)), 0)
+             FROM customers 
+             WHERE status = 'checked_out' 
+             AND check_out >= ?1 AND check_out <= ?2",
+            params![current_month_start, current_month_end],
+            |row| row.get(0)
+        )?;
+    
+        // Food income is counted as payments come in, so part-paid orders contribute what was received
+        let food_income: f64 = conn.query_row(
+            &format!(
+                "SELECT COALESCE(SUM(amount), 0) 
+                 FROM payments 
+                 WHERE order_id IS NOT NULL AND date(paid_at) >= ?1 AND date(paid_at) <= ?2 AND {}",
+                crate::trash::outside_trash("payments.order_id")
+            ),
+            params![current_month_start, current_month_end],
+            |row| row.get(0)
+        )?;
+    
+        // Refunds to guests reverse room income in the month they're given; order
+        // refunds are already netted off food income as negative payments
+        let guest_refunds: f64 = conn.query_row(
+            "SELECT COALESCE(-SUM(amount), 0)
+             FROM payments
+             WHERE refund_of IS NOT NULL AND order_id IS NULL AND date(paid_at) >= ?1 AND date(paid_at) <= ?2",
+            params![current_month_start, current_month_end],
+            |row| row.get(0)
+        )?;
+    
+        let total_income = room_income + food_income - guest_refunds;
+    
+        // Total expenses this month; owner drawings are reported separately, not as a business cost
+        let (total_expenses, owner_drawings): (f64, f64) = conn.query_row(
+            "SELECT COALESCE(SUM(CASE WHEN is_drawing = 0 THEN amount END), 0),
+                    COALESCE(SUM(CASE WHEN is_drawing = 1 THEN amount END), 0)
+             FROM expenses WHERE date >= ?1 AND date <= ?2 AND deleted_at IS NULL",
+            params![current_month_start, current_month_end],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        )?;
+    
+        // Total food orders this month
+        let total_food_orders: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sales WHERE date(created_at) >= ?1 AND date(created_at) <= ?2 AND deleted_at IS NULL",
+            params![current_month_start, current_month_end],
+            |row| row.get(0)
+        )?;
+    
+        let persons_in_house = persons_in_house(&conn, &business_date_today(), None)?;
+    
+        // Average guest rating this month
+        let average_rating: Option<f64> = conn.query_row(
+            "SELECT AVG(rating) FROM guest_feedback WHERE date(created_at) >= ?1 AND date(created_at) <= ?2",
+            params![current_month_start, current_month_end],
+            |row| row.get(0)
+        )?;
+    
+        Ok(DashboardStats {
+            total_guests_this_month,
+            total_income,
+            total_expenses,
+            owner_drawings,
+            profit_loss: total_income - total_expenses,
+            total_food_orders,
+            active_guests,
+            persons_in_house,
+            average_rating,
+        })
+    })
+    .await
+}
+
+/// Upper bound on points per chart series; longer series are bucket-averaged down to this.
+pub(crate) const MAX_CHART_POINTS: usize = 120;
+
+/// Reduce a series to at most `max_points` by averaging consecutive buckets. The first and
+/// last points are kept untouched so the chart starts and ends on real values.
+pub(crate) fn downsample_series(series: ChartSeries, max_points: usize) -> ChartSeries {
+    let len = series.values.len();
+    if len <= max_points || max_points < 3 {
+        return series;
+    }
+
+    let interior = len - 2;
+    let buckets = max_points - 2;
+    let mut out = ChartSeries::default();
+    out.labels.push(series.labels[0].clone());
+    out.values.push(series.values[0]);
+
+    for b in 0..buckets {
+        let start = 1 + b * interior / buckets;
+        let end = 1 + (b + 1) * interior / buckets;
+        if start == end {
+            continue;
+        }
+        let slice = &series.values[start..end];
+        out.labels.push(series.labels[start].clone());
+        out.values.push(slice.iter().sum::<f64>() / slice.len() as f64);
+    }
+
+    out.labels.push(series.labels[len - 1].clone());
+    out.values.push(series.values[len - 1]);
+    out
+}
+
+fn month_labels(start: NaiveDate, end: NaiveDate) -> Vec<String> {
+    let mut labels = Vec::new();
+    let (mut year, mut month) = (start.year(), start.month());
+    while (year, month) <= (end.year(), end.month()) {
+        labels.push(format!("{}-{:02}", year, month));
+        if month == 12 {
+            year += 1;
+            month = 1;
+        } else {
+            month += 1;
+        }
+    }
+    labels
+}
+
+/// Everything the dashboard charts need for a date range in one payload. Income follows the
+/// same rules as dashboard_stats: room income on check-out, food income when paid.
+#[command]
+pub async fn dashboard_graphs(range_start: String, range_end: String) -> Result<DashboardGraphs, AppError> {
+    run_blocking(move || {
+        crate::validation::validate_date_format(&range_start)?;
+        crate::validation::validate_date_format(&range_end)?;
+        let start = NaiveDate::parse_from_str(&range_start, "%Y-%m-%d")?;
+        let end = NaiveDate::parse_from_str(&range_end, "%Y-%m-%d")?;
+        if end < start {
+            return Err("Range end must not be before range start".into());
+        }
+
+        let conn = get_db_connection()?;
+        let months = month_labels(start, end);
+        let month_index: HashMap<&str, usize> = months.iter().enumerate().map(|(i, m)| (m.as_str(), i)).collect();
+        let mut income = vec![0.0; months.len()];
+        let mut expenses = vec![0.0; months.len()];
+
+        // Room income by month of check-out
+        let mut stmt = conn.prepare(
+            "SELECT substr(check_out, 1, 7),
+                    SUM(COALESCE(billed_amount, (julianday(check_out) - julianday(check_in) + 1) * daily_rate))
+             FROM customers
+             WHERE status = 'checked_out' AND check_out >= ?1 AND check_out <= ?2
+             GROUP BY 1"
+        )?;
+        let room_rows = stmt.query_map(params![range_start, range_end], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+        for row in room_rows {
+            let (month, amount) = row?;
+            if let Some(&i) = month_index.get(month.as_str()) {
+                income[i] += amount;
+            }
+        }
+
+        // Food income by month paid; refunds (negative rows) reverse income in the month given
+        let mut stmt = conn.prepare(&format!(
+            "SELECT strftime('%Y-%m', paid_at), SUM(amount)
+             FROM payments
+             WHERE (order_id IS NOT NULL OR refund_of IS NOT NULL) AND date(paid_at) >= ?1 AND date(paid_at) <= ?2 AND {}
+             GROUP BY 1",
+            crate::trash::outside_trash("payments.order_id")
+        ))?;
+        let sale_rows = stmt.query_map(params![range_start, range_end], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+        for row in sale_rows {
+            let (month, amount) = row?;
+            if let Some(&i) = month_index.get(month.as_str()) {
+                income[i] += amount;
+            }
+        }
+
+        // Expenses: one pass feeds both the monthly series and the category breakdown
+        let mut by_category: Vec<(String, f64)> = Vec::new();
+        let mut stmt = conn.prepare(
+            "SELECT substr(date, 1, 7), category, SUM(amount)
+             FROM expenses
+             WHERE date >= ?1 AND date <= ?2 AND is_drawing = 0 AND deleted_at IS NULL
+             GROUP BY 1, 2"
+        )?;
+        let expense_rows = stmt.query_map(params![range_start, range_end], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)?))
+        })?;
+        for row in expense_rows {
+            let (month, category, amount) = row?;
+            if let Some(&i) = month_index.get(month.as_str()) {
+                expenses[i] += amount;
+            }
+            match by_category.iter_mut().find(|(c, _)| *c == category) {
+                Some((_, total)) => *total += amount,
+                None => by_category.push((category, amount)),
+            }
+        }
+        by_category.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        // Daily occupancy: sweep the stays overlapping the range once
+        let total_rooms: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM resources WHERE is_active = 1",
+            [],
+            |row| row.get(0)
+        )?;
+        let days = (end - start).num_days() as usize + 1;
+        let mut occupied = vec![0i64; days + 1];
+        let mut persons = vec![0i64; days + 1];
+        let today = business_date_today();
+        let mut stmt = conn.prepare(
+            "SELECT check_in, COALESCE(check_out, ?3), adults + children FROM customers
+             WHERE room_id IS NOT NULL AND check_in <= ?2 AND COALESCE(check_out, ?3) >= ?1"
+        )?;
+        let stays = stmt.query_map(params![range_start, range_end, today], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+        })?;
+        for stay in stays {
+            let (check_in, check_out, party_size) = stay?;
+            let (Ok(arrive), Ok(depart)) = (
+                NaiveDate::parse_from_str(check_in.get(..10).unwrap_or(&check_in), "%Y-%m-%d"),
+                NaiveDate::parse_from_str(check_out.get(..10).unwrap_or(&check_out), "%Y-%m-%d"),
+            ) else {
+                continue;
+            };
+            // A stay occupies the nights from arrival up to (not including) departure;
+            // same-day stays still count for their one night.
+            let first = arrive.max(start);
+            let last = (depart - chrono::Duration::days(1)).max(arrive).min(end);
+            if last < first {
+                continue;
+            }
+            let (first_idx, after_last_idx) = ((first - start).num_days() as usize, (last - start).num_days() as usize + 1);
+            occupied[first_idx] += 1;
+            occupied[after_last_idx] -= 1;
+            persons[first_idx] += party_size;
+            persons[after_last_idx] -= party_size;
+        }
+        let mut occupancy = ChartSeries::default();
+        let mut occupancy_persons = ChartSeries::default();
+        let (mut running, mut running_persons) = (0i64, 0i64);
+        for (offset, (delta, persons_delta)) in occupied.iter().zip(&persons).take(days).enumerate() {
+            running += delta;
+            running_persons += persons_delta;
+            let label = (start + chrono::Duration::days(offset as i64)).format("%Y-%m-%d").to_string();
+            occupancy_persons.labels.push(label.clone());
+            occupancy_persons.values.push(running_persons as f64);
+            occupancy.labels.push(label);
+            occupancy.values.push(if total_rooms > 0 {
+                (running as f64 / total_rooms as f64 * 100.0).min(100.0)
+            } else {
+                0.0
+            });
+        }
+
+        // Top five items by quantity sold
+        let mut stmt = conn.prepare(
+            "SELECT si.item_name, SUM(si.quantity)
+             FROM sale_items si
+             JOIN sales s ON s.id = si.order_id
+             WHERE date(s.created_at) >= ?1 AND date(s.created_at) <= ?2 AND s.deleted_at IS NULL
+             GROUP BY si.item_name
+             ORDER BY 2 DESC, si.item_name
+             LIMIT 5"
+        )?;
+        let mut top_items = ChartSeries::default();
+        let item_rows = stmt.query_map(params![range_start, range_end], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+        for row in item_rows {
+            let (name, quantity) = row?;
+            top_items.labels.push(name);
+            top_items.values.push(quantity);
+        }
+
+        Ok(DashboardGraphs {
+            monthly_income: downsample_series(ChartSeries { labels: months.clone(), values: income }, MAX_CHART_POINTS),
+            monthly_expenses: downsample_series(ChartSeries { labels: months, values: expenses }, MAX_CHART_POINTS),
+            expenses_by_category: ChartSeries {
+                labels: by_category.iter().map(|(c, _)| c.clone()).collect(),
+                values: by_category.iter().map(|(_, a)| *a).collect(),
+            },
+            occupancy: downsample_series(occupancy, MAX_CHART_POINTS),
+            occupancy_persons: downsample_series(occupancy_persons, MAX_CHART_POINTS),
+            top_items,
+            range_start,
+            range_end,
+            generated_at: get_current_timestamp(),
+        })
+    })
+    .await
+}
+
+// Get low stock items for dashboard alerts; orders that run an ingredient low also emit "stock-low"
+#[tauri::command]
+pub fn get_low_stock_items() -> Result<Vec<LowStockItem>, AppError> {
+    let conn = get_db_connection()?;
+    
+    // Menu items that track their own stock, then recipe ingredients
+    let mut stmt = conn.prepare(
+        "SELECT 'menu_item', id, name, CAST(stock_quantity AS REAL), CAST(low_stock_limit AS REAL), NULL
+         FROM menu_items 
+         WHERE track_stock = 1 
+         AND stock_quantity <= low_stock_limit
+         UNION ALL
+         SELECT 'ingredient', id, name, quantity, low_stock_limit, unit
+         FROM inventory_items
+         WHERE quantity <= low_stock_limit
+         ORDER BY 4 ASC"
+    )?;
+    
+    let items = stmt.query_map([], |row| {
+        Ok(LowStockItem {
+            kind: row.get(0)?,
+            id: row.get(1)?,
+            name: row.get(2)?,
+            stock_quantity: row.get(3)?,
+            low_stock_limit: row.get(4)?,
+            unit: row.get(5)?,
+        })
+    })?;
+    
+    items.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+// ===== FOOD ORDER COMMANDS =====
+
+/// Unit price of an order line with its modifiers applied.
+fn line_unit_price(item: &OrderItemInput) -> f64 {
+    item.unit_price + item.modifiers.iter().map(|m| m.price_delta).sum::<f64>()
+}
+
+fn validate_modifiers(item: &OrderItemInput) -> Result<(), AppError> {
+    for modifier in &item.modifiers {
+        if modifier.name.trim().is_empty() {
+            return Err(AppError::invalid(EMPTY_FIELD, "modifiers", format!("A modifier on '{}' has no name", item.item_name)));
+        }
+        if !modifier.price_delta.is_finite() {
+            return Err(format!("Modifier '{}' on '{}' has an invalid price", modifier.name.trim(), item.item_name).into());
+        }
+    }
+    if line_unit_price(item) < 0.0 {
+        return Err(AppError::invalid(
+            NEGATIVE_AMOUNT,
+            "modifiers",
+            format!("Modifiers take '{}' below zero", item.item_name),
+        ));
+    }
+    Ok(())
+}
+
+/// Modifiers on one order line, in the order they were added.
+pub fn item_modifiers(conn: &rusqlite::Connection, sale_item_id: i64) -> rusqlite::Result<Vec<OrderItemModifier>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT name, price_delta FROM sale_item_modifiers WHERE sale_item_id = ?1 ORDER BY id",
+    )?;
+    let modifiers = stmt
+        .query_map([sale_item_id], |row| Ok(OrderItemModifier { name: row.get(0)?, price_delta: row.get(1)? }))?
+        .collect();
+    modifiers
+}
+
+/// What a combo line is made of; empty for any other line.
+pub fn item_components(conn: &rusqlite::Connection, sale_item_id: i64) -> rusqlite::Result<Vec<OrderItemComponent>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT menu_item_id, item_name, quantity FROM sale_item_components WHERE sale_item_id = ?1 ORDER BY id",
+    )?;
+    let components = stmt
+        .query_map([sale_item_id], |row| Ok(OrderItemComponent { menu_item_id: row.get(0)?, item_name: row.get(1)?, quantity: row.get(2)? }))?
+        .collect();
+    components
+}
+
+/// An order's lines with their attributes, notes, modifiers and combo components.
+pub fn order_item_details(conn: &rusqlite::Connection, order_id: i64) -> Result<Vec<OrderItemDetail>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, menu_item_id, item_name, quantity, unit_price, line_total, attributes, notes,
+                EXISTS (SELECT 1 FROM voided_items v WHERE v.sale_item_id = sale_items.id), combo_id,
+                original_unit_price, price_rule_id
+            FROM sale_items WHERE order_id = ?1 ORDER BY id"
+    )?;
+    let mut items = stmt.query_map([order_id], |row| {
+        Ok(OrderItemDetail {
+            id: row.get(0)?,
+            menu_item_id: row.get(1)?,
+            item_name: row.get(2)?,
+            quantity: row.get(3)?,
+            unit_price: row.get(4)?,
+            line_total: row.get(5)?,
+            selected_attributes: parse_selected_attributes(row.get(6)?),
+            notes: row.get(7)?,
+            modifiers: Vec::new(),
+            voided: row.get(8)?,
+            combo_id: row.get(9)?,
+            components: Vec::new(),
+            original_unit_price: row.get(10)?,
+            price_rule_id: row.get(11)?,
+        })
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+    for item in &mut items {
+        item.modifiers = item_modifiers(conn, item.id)?;
+        item.components = item_components(conn, item.id)?;
+    }
+    Ok(items)
+}
+
+/// Take a food order and tell the frontend about any ingredient it ran low.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub fn add_food_order(
+    app: tauri::AppHandle,
+    guest_id: Option<i64>,
+    customer_type: String,
+    customer_name: Option<String>,
+    items: Vec<OrderItemInput>,
+    session_token: Option<String>,
+    notes: Option<String>,
+    table_id: Option<i64>,
+) -> Result<i64, AppError> {
+    let (order_id, ran_low) = insert_food_order(guest_id, customer_type, customer_name, items, session_token, notes, table_id)?;
+    if !ran_low.is_empty() {
+        let _ = app.emit("stock-low", &ran_low);
+    }
+    Ok(order_id)
+}
+
+/// Record the order, its lines and the stock it uses. Also returns the
+/// ingredients this order took to or below their low-stock limit.
+pub fn insert_food_order(guest_id: Option<i64>, customer_type: String, customer_name: Option<String>, mut items: Vec<OrderItemInput>, session_token: Option<String>, notes: Option<String>, table_id: Option<i64>) -> Result<(i64, Vec<LowStockItem>), AppError> {
+    println!("🐛 DEBUG add_food_order - Received parameters:");
+    println!("  guest_id: {:?}", guest_id);
+    println!("  customer_type: {:?}", customer_type);
+    println!("  customer_name: {:?}", customer_name);
+    println!("  items count: {:?}", items.len());
+    
+    let conn = get_db_connection()?;
+    
+    if items.is_empty() {
+        return Err("Order must have at least one item".into());
+    }
+
+    // Happy-hour price rules replace the price of the lines they cover; each
+    // line keeps the price it came in with and the rule that changed it
+    let now = chrono::Local::now().naive_local();
+    let mut line_pricing: Vec<(f64, Option<i64>)> = Vec::with_capacity(items.len());
+    for item in &mut items {
+        let original_unit_price = item.unit_price;
+        let mut price_rule_id = None;
+        if let Some(menu_item_id) = item.menu_item_id {
+            if let Some((rule_id, price)) = crate::menu_pricing::price_rule_for(&conn, menu_item_id, item.unit_price, now)? {
+                item.unit_price = price;
+                price_rule_id = Some(rule_id);
+            }
+        }
+        line_pricing.push((original_unit_price, price_rule_id));
+    }
+    
+    // Check stock availability and attribute selections BEFORE starting transaction
+    let mut line_attributes: Vec<Option<String>> = Vec::with_capacity(items.len());
+    let mut line_components: Vec<Vec<OrderItemComponent>> = Vec::with_capacity(items.len());
+    for item in &items {
+        let components = match item.combo_id {
+            Some(_) if item.menu_item_id.is_some() => {
+                return Err(format!("'{}' can't be both a menu item and a combo", item.item_name).into());
+            }
+            Some(combo_id) => crate::combos::combo_components(&conn, combo_id)?,
+            None => Vec::new(),
+        };
+        for component in &components {
+            let needed = component.quantity * item.quantity as i64;
+            let stock: Option<(i64, i32)> = conn
+                .query_row(
+                    "SELECT stock_quantity, track_stock FROM menu_items WHERE id = ?1",
+                    params![component.menu_item_id],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?;
+            if let Some((current_stock, 1)) = stock {
+                if current_stock < needed {
+                    return Err(format!(
+                        "Insufficient stock for '{}' in '{}'. Available: {}, Requested: {}",
+                        component.item_name, item.item_name, current_stock, needed
+                    ).into());
+                }
+            }
+        }
+        line_components.push(components);
+
+        let mut definitions = Vec::new();
+        if let Some(menu_item_id) = item.menu_item_id {
+            let stock_info: Result<(i32, i32, Option<String>), _> = conn.query_row(
+                "SELECT stock_quantity, track_stock, attributes FROM menu_items WHERE id = ?1",
+                params![menu_item_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            );
+            
+            if let Ok((current_stock, track_stock, attributes)) = stock_info {
+                if track_stock == 1 && current_stock < item.quantity {
+                    return Err(format!(
+                        "Insufficient stock for '{}'. Available: {}, Requested: {}",
+                        item.item_name, current_stock, item.quantity
+                    ).into());
+                }
+                definitions = parse_menu_attributes(attributes);
+            }
+        }
+
+        let selected = validate_selected_attributes(&item.item_name, &definitions, &item.selected_attributes)?;
+        line_attributes.push(if selected.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&selected)?)
+        });
+        validate_modifiers(item)?;
+    }
+    
+    // Calculate total, with the service charge for the current business mode on top
+    let items_total: f64 = items.iter().map(|item| line_unit_price(item) * item.quantity as f64).sum();
+    let service_charge_percent = crate::service_charge::service_charge_percent(&conn)?;
+    let service_charge = crate::service_charge::service_charge_on(items_total, service_charge_percent);
+    let total_amount = items_total + service_charge;
+    println!("🐛 DEBUG add_food_order - Total amount: {:?}", total_amount);
+    
+    let after_hours = check_after_hours(&conn, session_token.as_deref())?;
+    roll_off_past_specials(&conn)?;
+
+    let actor = audit_actor(session_token.as_deref());
+
+    // IMMEDIATE so the specials check-and-decrement below can't interleave with another order
+    let tx = rusqlite::Transaction::new_unchecked(&conn, rusqlite::TransactionBehavior::Immediate)?;
+
+    // Insert order
+    println!("🐛 DEBUG add_food_order - Inserting food order...");
+    let notes = notes.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if let Some(table_id) = table_id {
+        crate::tables::seat_at_table(&tx, table_id)?;
+    }
+    let _rows_affected = tx.execute(
+        "INSERT INTO sales (guest_id, customer_type, customer_name, created_at, paid, total_amount, after_hours, notes, table_id,
+                            service_charge_percent, service_charge) 
+         VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            guest_id, customer_type, customer_name, get_current_timestamp(), total_amount, after_hours, notes, table_id,
+            service_charge_percent, service_charge
+        ],
+    )?;
+    
+    let order_id = tx.last_insert_rowid();
+    // A guest's food is taxed on their checkout bill; anyone else's here and now
+    if guest_id.is_none() {
+        let tax = crate::taxes::record_order_taxes(&tx, order_id, items_total, &business_date_today())?;
+        tx.execute("UPDATE sales SET total_amount = total_amount + ?1 WHERE id = ?2", params![tax, order_id])?;
+    }
+    let total_amount = crate::rounding::round_order_total(&tx, order_id)?;
+    
+    // Insert order items and decrement stock
+    let mut ran_low: Vec<LowStockItem> = Vec::new();
+    for (((item, attributes), components), (original_unit_price, price_rule_id)) in
+        items.into_iter().zip(line_attributes).zip(line_components).zip(line_pricing)
+    {
+        claim_special_portions(&tx, item.special_id, item.menu_item_id, item.quantity)?;
+
+        let item_notes = item.notes.as_deref().map(str::trim).filter(|n| !n.is_empty());
+        tx.execute(
+            "INSERT INTO sale_items (order_id, menu_item_id, item_name, unit_price, quantity, line_total, attributes, notes, combo_id,
+                                     original_unit_price, price_rule_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![order_id, item.menu_item_id, item.item_name, item.unit_price, item.quantity, 
+                   line_unit_price(&item) * item.quantity as f64, attributes, item_notes, item.combo_id,
+                   original_unit_price, price_rule_id],
+        )?;
+        let sale_item_id = tx.last_insert_rowid();
+        for modifier in &item.modifiers {
+            tx.execute(
+                "INSERT INTO sale_item_modifiers (sale_item_id, name, price_delta) VALUES (?1, ?2, ?3)",
+                params![sale_item_id, modifier.name.trim(), modifier.price_delta],
+            )?;
+        }
+        for component in &components {
+            tx.execute(
+                "INSERT INTO sale_item_components (sale_item_id, menu_item_id, item_name, quantity) VALUES (?1, ?2, ?3, ?4)",
+                params![sale_item_id, component.menu_item_id, component.item_name, component.quantity],
+            )?;
+        }
+        
+        // Decrement stock for tracked items, and the ingredients in their recipes;
+        // a combo takes its components' stock instead of its own
+        let portions: Vec<(i64, i32)> = match item.menu_item_id {
+            Some(menu_item_id) => vec![(menu_item_id, item.quantity)],
+            None => components
+                .iter()
+                .filter_map(|c| c.menu_item_id.map(|id| (id, c.quantity as i32 * item.quantity)))
+                .collect(),
+        };
+        for (menu_item_id, quantity) in portions {
+            tx.execute(
+                "UPDATE menu_items 
+                 SET stock_quantity = stock_quantity - ?1 
+                 WHERE id = ?2 AND track_stock = 1",
+                params![quantity, menu_item_id],
+            ).map_err(|e| format!("Failed to decrement stock: {}", e))?;
+            for low in crate::inventory::deduct_recipe_stock(&tx, order_id, menu_item_id, quantity, &actor)? {
+                if !ran_low.iter().any(|l| l.id == low.id) {
+                    ran_low.push(low);
+                }
+            }
+        }
+    }
+    record_change(&tx, &actor, "order", order_id, "create", None, snapshot(&tx, "order", order_id)?)?;
+    
+    tx.commit()?;
+    events::order_created(order_id, guest_id, table_id, total_amount);
+    Ok((order_id, ran_low))
+}
+
+#[tauri::command]
+pub fn get_food_orders_by_guest(guest_id: i64, tag_ids: Option<Vec<i64>>) -> Result<Vec<FoodOrderSummary>, AppError> {
+    let conn = get_db_connection()?;
+    
+    let mut stmt = conn.prepare(&format!(
+        "SELECT fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount,
+                GROUP_CONCAT(oi.item_name || ' x' || oi.quantity) as items,
+                fo.amount_paid
+            FROM sales fo
+            LEFT JOIN sale_items oi ON fo.id = oi.order_id
+         WHERE fo.guest_id = ?1 AND fo.deleted_at IS NULL{}
+         GROUP BY fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount, fo.amount_paid
+         ORDER BY fo.created_at DESC",
+        crate::tags::tag_filter_clause("order", "fo.id", tag_ids.as_deref())
+    ))?;
+    let mut tags_by_order = crate::tags::tags_by_entity(&conn, "order")?;
+    
+    let orders = stmt.query_map([guest_id], |row| {
+        Ok(FoodOrderSummary {
+            id: row.get(0)?,
+            created_at: row.get(1)?,
+            paid: row.get::<_, i32>(2)? == 1,
+            paid_at: row.get(3)?,
+            total_amount: row.get(4)?,
+            items: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+            amount_paid: row.get(6)?,
+            payment_status: payment_status(row.get::<_, i32>(2)? == 1, row.get(6)?),
+            guest_id: Some(guest_id),
+            guest_name: None, // This function doesn't need guest name since it's for a specific guest
+            tags: tags_by_order.remove(&row.get::<_, i64>(0)?).unwrap_or_default(),
+        })
+    })?;
+    
+    orders.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+#[command]
+pub fn get_food_orders(tag_ids: Option<Vec<i64>>) -> Result<Vec<FoodOrderSummary>, AppError> {
+    let conn = get_db_connection()?;
+    
+    let mut stmt = conn.prepare(&format!(
+        "SELECT fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount,
+                GROUP_CONCAT(oi.item_name || ' x' || oi.quantity) as items,
+                fo.guest_id,
+                COALESCE(g.name, 'Walk-in') as guest_name,
+                fo.amount_paid
+            FROM sales fo
+            LEFT JOIN sale_items oi ON fo.id = oi.order_id
+            LEFT JOIN customers g ON fo.guest_id = g.id
+         WHERE fo.deleted_at IS NULL{}
+         GROUP BY fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount, fo.guest_id, g.name, fo.amount_paid
+         ORDER BY fo.created_at DESC",
+        crate::tags::tag_filter_clause("order", "fo.id", tag_ids.as_deref())
+    ))?;
+    let mut tags_by_order = crate::tags::tags_by_entity(&conn, "order")?;
+    
+    let orders = stmt.query_map([], |row| map_order_summary(row, &mut tags_by_order))?;
+    
+    orders.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+const ORDER_LIST: ListSpec = ListSpec {
+    sorts: &[("created_at", "fo.created_at"), ("total_amount", "fo.total_amount"), ("guest_name", "guest_name COLLATE NOCASE")],
+    default_sort: "-created_at",
+    tie_breaker: "fo.id DESC",
+    filters: &[
+        ("guest_id", "fo.guest_id = ?"),
+        ("table_id", "fo.table_id = ?"),
+        ("paid", "fo.paid = ?"),
+        ("search", "COALESCE(g.name, fo.customer_name, 'Walk-in') LIKE '%' || ? || '%'"),
+        ("start_date", "date(fo.created_at) >= ?"),
+        ("end_date", "date(fo.created_at) <= ?"),
+    ],
+};
+
+/// Orders a page at a time. Sorts: created_at, total_amount, guest_name.
+/// Filters: guest_id, table_id, paid ('1' or '0'), search (guest name),
+/// start_date and end_date.
+#[command]
+pub fn get_food_orders_page(page: Option<PageRequest>, tag_ids: Option<Vec<i64>>) -> Result<PageResult<FoodOrderSummary>, AppError> {
+    let query = ORDER_LIST.resolve(page)?;
+    let conn = get_db_connection()?;
+    
+    let conditions = format!("{}{}", query.conditions, crate::tags::tag_filter_clause("order", "fo.id", tag_ids.as_deref()));
+    let total = query.count(
+        &conn,
+        &format!("FROM sales fo LEFT JOIN customers g ON fo.guest_id = g.id WHERE fo.deleted_at IS NULL{}", conditions),
+    )?;
+    
+    let mut stmt = conn.prepare(&format!(
+        "SELECT fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount,
+                GROUP_CONCAT(oi.item_name || ' x' || oi.quantity) as items,
+                fo.guest_id,
+                COALESCE(g.name, 'Walk-in') as guest_name,
+                fo.amount_paid
+            FROM sales fo
+            LEFT JOIN sale_items oi ON fo.id = oi.order_id
+            LEFT JOIN customers g ON fo.guest_id = g.id
+         WHERE fo.deleted_at IS NULL{}
+         GROUP BY fo.id, fo.created_at, fo.paid, fo.paid_at, fo.total_amount, fo.guest_id, g.name, fo.amount_paid{}{}",
+        conditions, query.order_by, query.limit_clause()
+    ))?;
+    let mut tags_by_order = crate::tags::tags_by_entity(&conn, "order")?;
+    let orders = stmt
+        .query_map(rusqlite::params_from_iter(query.values.iter()), |row| map_order_summary(row, &mut tags_by_order))?
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    Ok(query.into_result(orders, total))
+}
+
+/// A row of the get_food_orders query, with its tags taken from `tags_by_order`.
+fn map_order_summary(row: &rusqlite::Row, tags_by_order: &mut HashMap<i64, Vec<Tag>>) -> rusqlite::Result<FoodOrderSummary> {
+    Ok(FoodOrderSummary {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        paid: row.get::<_, i32>(2)? == 1,
+        paid_at: row.get(3)?,
+        total_amount: row.get(4)?,
+        items: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+        amount_paid: row.get(8)?,
+        payment_status: payment_status(row.get::<_, i32>(2)? == 1, row.get(8)?),
+        guest_id: row.get(6)?,
+        guest_name: row.get(7)?,
+        tags: tags_by_order.remove(&row.get::<_, i64>(0)?).unwrap_or_default(),
+    })
+}
+
+/// Settle whatever is still owed on the order with a cash payment.
+#[tauri::command]
+pub fn mark_order_paid(order_id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    
+    let summary = order_payment_summary(&tx, order_id)?;
+    if summary.balance > 0.0 {
+        let before = snapshot(&tx, "order", order_id)?;
+        insert_payment(&tx, order_id, summary.balance, "cash", None)?;
+        sync_order_payment_status(&tx, order_id)?;
+        record_change(&tx, &audit_actor(session_token.as_deref()), "order", order_id, "update", before, snapshot(&tx, "order", order_id)?)?;
+    }
+    
+    tx.commit()?;
+    crate::fiscal::fiscalize_in_background("order", order_id);
+    Ok("Order marked as paid".to_string())
+}
+
+// ===== ORDER PAYMENTS =====
+
+const PAYMENT_METHODS: &[&str] = &["cash", "card", "bank_transfer"];
+
+/// 'paid', 'partially_paid' or 'unpaid' from the order's stored totals.
+pub fn payment_status(paid: bool, amount_paid: f64) -> String {
+    if paid {
+        "paid".to_string()
+    } else if amount_paid > 0.0 {
+        "partially_paid".to_string()
+    } else {
+        "unpaid".to_string()
+    }
+}
+
+pub fn parse_payment_method(method: &str) -> Result<String, String> {
+    let method = method.trim().to_lowercase();
+    if !PAYMENT_METHODS.contains(&method.as_str()) {
+        return Err(format!("Payment method must be one of: {}", PAYMENT_METHODS.join(", ")));
+    }
+    Ok(method)
+}
+
+pub fn order_payment_summary(conn: &rusqlite::Connection, order_id: i64) -> Result<OrderPaymentSummary, String> {
+    let (total_amount, amount_paid, paid): (f64, f64, bool) = conn.query_row(
+        "SELECT total_amount, amount_paid, paid FROM sales WHERE id = ?1 AND deleted_at IS NULL",
+        params![order_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i32>(2)? == 1))
+    ).optional().map_err(|e| e.to_string())?
+        .ok_or(crate::validation::ORDER_NOT_FOUND.to_string())?;
+    
+    Ok(OrderPaymentSummary {
+        order_id,
+        total_amount,
+        amount_paid,
+        balance: (to_cents(total_amount) - to_cents(amount_paid)).max(0) as f64 / 100.0,
+        payment_status: payment_status(paid, amount_paid),
+    })
+}
+
+pub(crate) fn insert_payment(conn: &rusqlite::Connection, order_id: i64, amount: f64, method: &str, note: Option<String>) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO payments (order_id, amount, method, paid_at, note, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?4)",
+        params![order_id, amount, method, get_current_timestamp(), note],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Record what the guest paid at checkout. These rows carry guest_id instead
+/// of order_id; a zero bill records nothing. When deposits came to more than
+/// the bill, the difference is refunded against the deposits instead, latest
+/// first and by the method each was taken with.
+pub(crate) fn insert_checkout_payment(conn: &rusqlite::Connection, guest_id: i64, amount: f64, method: &str) -> Result<(), String> {
+    let amount = to_cents(amount);
+    if amount < 0 {
+        return refund_checkout_overpayment(conn, guest_id, -amount);
+    }
+    if amount == 0 {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO payments (guest_id, amount, method, paid_at, note, created_at) VALUES (?1, ?2, ?3, ?4, 'Checkout', ?4)",
+        params![guest_id, amount as f64 / 100.0, method, get_current_timestamp()],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Give back `owed` cents of the guest's deposits with negative payment rows
+/// pointing at them, as refund_payment does.
+pub(crate) fn refund_checkout_overpayment(conn: &rusqlite::Connection, guest_id: i64, owed: i64) -> Result<(), String> {
+    let deposits = conn
+        .prepare(
+            "SELECT p.id, p.method, p.amount + COALESCE((SELECT SUM(r.amount) FROM payments r WHERE r.refund_of = p.id), 0)
+             FROM payments p
+             WHERE p.guest_id = ?1 AND p.is_deposit = 1 AND p.refund_of IS NULL
+             ORDER BY p.paid_at DESC, p.id DESC",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![guest_id], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)?)))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+
+    let now = get_current_timestamp();
+    let mut owed = owed;
+    for (deposit_id, method, left) in deposits {
+        let refund = to_cents(left).min(owed);
+        if refund <= 0 {
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO payments (guest_id, amount, method, paid_at, note, is_deposit, refund_of, created_at)
+             VALUES (?1, ?2, ?3, ?4, 'Checkout refund', 1, ?5, ?4)",
+            params![guest_id, -(refund as f64 / 100.0), method, now, deposit_id],
+        )
+        .map_err(|e| e.to_string())?;
+        owed -= refund;
+    }
+    if owed > 0 {
+        return Err(format!(
+            "The guest is owed {:.2} more than their deposits hold; check the folio before checking out",
+            owed as f64 / 100.0
+        ));
+    }
+    Ok(())
+}
+
+/// Recompute sales.amount_paid and the paid flag from the payments table. An
+/// order is paid once its payments cover the total; paid_at is the latest payment.
+pub fn sync_order_payment_status(conn: &rusqlite::Connection, order_id: i64) -> Result<OrderPaymentSummary, String> {
+    let (total_amount, amount_paid, last_paid_at): (f64, f64, Option<String>) = conn.query_row(
+        "SELECT s.total_amount, COALESCE(SUM(p.amount), 0), MAX(p.paid_at)
+         FROM sales s LEFT JOIN payments p ON p.order_id = s.id
+         WHERE s.id = ?1
+         GROUP BY s.id",
+        params![order_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    ).optional().map_err(|e| e.to_string())?
+        .ok_or(crate::validation::ORDER_NOT_FOUND.to_string())?;
+    
+    let paid = to_cents(amount_paid) >= to_cents(total_amount);
+    conn.execute(
+        "UPDATE sales SET amount_paid = ?1, paid = ?2, paid_at = ?3 WHERE id = ?4",
+        params![amount_paid, paid as i32, if paid { last_paid_at } else { None }, order_id],
+    ).map_err(|e| e.to_string())?;
+    
+    order_payment_summary(conn, order_id)
+}
+
+/// Record one payment (cash, card or bank_transfer) against an order. An order
+/// can be settled in several payments; paying more than the balance is rejected.
+#[command]
+pub fn add_order_payment(order_id: i64, amount: f64, method: String, note: Option<String>, session_token: Option<String>) -> Result<OrderPaymentSummary, AppError> {
+    let method = parse_payment_method(&method)?;
+    if !amount.is_finite() || to_cents(amount) <= 0 {
+        return Err("Payment amount must be greater than 0".into());
+    }
+    
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    
+    let summary = order_payment_summary(&tx, order_id)?;
+    if summary.payment_status == "paid" {
+        return Err(crate::validation::ORDER_ALREADY_PAID.into());
+    }
+    if to_cents(amount) > to_cents(summary.balance) {
+        let money = crate::money::Money::load(&tx)?;
+        return Err(format!(
+            "Payment of {} exceeds the outstanding balance of {}",
+            money.format(amount, 2),
+            money.format(summary.balance, 2)
+        ).into());
+    }
+    
+    let before = snapshot(&tx, "order", order_id)?;
+    insert_payment(&tx, order_id, to_cents(amount) as f64 / 100.0, &method, note.filter(|n| !n.trim().is_empty()))?;
+    let summary = sync_order_payment_status(&tx, order_id)?;
+    record_change(&tx, &audit_actor(session_token.as_deref()), "order", order_id, "update", before, snapshot(&tx, "order", order_id)?)?;
+    
+    tx.commit()?;
+    if summary.payment_status == "paid" {
+        crate::fiscal::fiscalize_in_background("order", order_id);
+    }
+    Ok(summary)
+}
+
+#[command]
+pub fn get_order_payments(order_id: i64) -> Result<Vec<OrderPayment>, AppError> {
+    let conn = get_db_connection()?;
+    
+    let mut stmt = conn.prepare(
+        "SELECT id, order_id, amount, method, paid_at, note FROM payments WHERE order_id = ?1 ORDER BY paid_at, id"
+    )?;
+    
+    let payments = stmt.query_map(params![order_id], |row| {
+        Ok(OrderPayment {
+            id: row.get(0)?,
+            order_id: row.get(1)?,
+            amount: row.get(2)?,
+            method: row.get(3)?,
+            paid_at: row.get(4)?,
+            note: row.get(5)?,
+        })
+    })?;
+    
+    payments.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+const REFUND_ROLES: &[&str] = &["manager", "admin"];
+
+/// Give back some or all of a payment, by the method it was taken with. The
+/// refund is a negative payment row, so order balances, deposits and the
+/// takings reports all net it off. A payment can be refunded in parts, but
+/// never for more than was paid. Managers and admins only.
+#[command]
+pub fn refund_payment(payment_id: i64, amount: f64, reason: String, session_token: Option<String>) -> Result<PaymentRefund, AppError> {
+    let session = crate::offline_auth::require_session(session_token.as_deref().unwrap_or_default())?;
+    if !REFUND_ROLES.contains(&session.role.as_str()) {
+        return Err(AppError::coded(crate::validation::UNAUTHORIZED, "Only a manager or admin can refund a payment"));
+    }
+    let reason = reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "reason", "Say why the payment is being refunded"));
+    }
+    if !amount.is_finite() || to_cents(amount) <= 0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "amount", "Refund amount must be greater than 0"));
+    }
+    
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    
+    let (order_id, guest_id, paid, method, is_deposit, refund_of): (Option<i64>, Option<i64>, f64, String, bool, Option<i64>) = tx.query_row(
+        "SELECT order_id, guest_id, amount, method, is_deposit, refund_of FROM payments WHERE id = ?1",
+        params![payment_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+    ).optional()?.ok_or_else(|| format!("Payment #{} not found", payment_id))?;
+    if refund_of.is_some() {
+        return Err("A refund can't itself be refunded".into());
+    }
+    
+    let refunded: f64 = tx.query_row(
+        "SELECT COALESCE(-SUM(amount), 0) FROM payments WHERE refund_of = ?1",
+        params![payment_id],
+        |row| row.get(0)
+    )?;
+    let refundable = to_cents(paid) - to_cents(refunded);
+    if to_cents(amount) > refundable {
+        return Err(format!("Only {:.2} of payment #{} is left to refund", refundable as f64 / 100.0, payment_id).into());
+    }
+    
+    let actor = session.username;
+    let before = match order_id {
+        Some(order_id) => snapshot(&tx, "order", order_id)?,
+        None => None,
+    };
+    let now = get_current_timestamp();
+    tx.execute(
+        "INSERT INTO payments (order_id, guest_id, amount, method, paid_at, note, is_deposit, refund_of, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?5)",
+        params![order_id, guest_id, -(to_cents(amount) as f64 / 100.0), method, now, reason, is_deposit, payment_id],
+    )?;
+    let refund_id = tx.last_insert_rowid();
+    
+    if let Some(order_id) = order_id {
+        sync_order_payment_status(&tx, order_id)?;
+        record_change(&tx, &actor, "order", order_id, "update", before, snapshot(&tx, "order", order_id)?)?;
+    }
+    log_audit_event(
+        &tx,
+        &actor,
+        "payment_refunded",
+        &format!("refund #{} of {:.2} against payment #{} ({}): {}", refund_id, amount, payment_id, method, reason),
+    )?;
+    
+    tx.commit()?;
+    
+    Ok(PaymentRefund {
+        id: refund_id,
+        payment_id,
+        order_id,
+        guest_id,
+        amount: to_cents(amount) as f64 / 100.0,
+        method,
+        reason,
+        refunded_at: now,
+        refunded_by: actor,
+    })
+}
+
+/// Money taken per payment method between two dates (inclusive), split into
+/// order payments and checkout settlements, for the end-of-day reconciliation.
+/// Every method is listed, including ones with nothing taken.
+#[command]
+pub async fn payments_by_method_report(start_date: String, end_date: String) -> Result<PaymentsByMethodReport, AppError> {
+    run_blocking(move || {
+        validate_date_format(&start_date)?;
+        validate_date_format(&end_date)?;
+        if end_date < start_date {
+            return Err("End date cannot be before start date".into());
+        }
+        let conn = get_db_connection()?;
+        let methods = payment_method_totals(&conn, &start_date, &end_date)?;
+        let total = methods.iter().map(|m| to_cents(m.total)).sum::<i64>() as f64 / 100.0;
+        Ok(PaymentsByMethodReport { start_date, end_date, methods, total })
+    })
+    .await
+}
+
+/// Money taken per payment method between two dates (inclusive), every method
+/// listed even when nothing was taken with it.
+pub(crate) fn payment_method_totals(conn: &rusqlite::Connection, start_date: &str, end_date: &str) -> Result<Vec<PaymentMethodTotal>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT method,
+                COALESCE(SUM(CASE WHEN order_id IS NOT NULL THEN amount END), 0),
+                COUNT(order_id),
+                COALESCE(SUM(CASE WHEN order_id IS NULL THEN amount END), 0),
+                COUNT(*) - COUNT(order_id)
+         FROM payments
+         WHERE date(paid_at) >= ?1 AND date(paid_at) <= ?2 AND {}
+         GROUP BY method",
+        crate::trash::outside_trash("payments.order_id")
+    ))?;
+
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok(PaymentMethodTotal {
+            method: row.get(0)?,
+            order_amount: row.get(1)?,
+            order_count: row.get(2)?,
+            checkout_amount: row.get(3)?,
+            checkout_count: row.get(4)?,
+            total: row.get::<_, f64>(1)? + row.get::<_, f64>(3)?,
+        })
+    })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut methods: Vec<PaymentMethodTotal> = PAYMENT_METHODS.iter().map(|method| PaymentMethodTotal {
+        method: method.to_string(),
+        order_amount: 0.0,
+        order_count: 0,
+        checkout_amount: 0.0,
+        checkout_count: 0,
+        total: 0.0,
+    }).collect();
+    for row in rows {
+        if let Some(slot) = methods.iter_mut().find(|m| m.method == row.method) {
+            *slot = row;
+        }
+    }
+    Ok(methods)
+}
+
+// ===== EXPENSE COMMANDS =====
+
+/// The tax included in an expense's amount, reclaimable on the tax return.
+fn validate_expense_tax(tax_amount: f64, amount: f64) -> Result<(), AppError> {
+    if !tax_amount.is_finite() || tax_amount < 0.0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "tax_amount", "Tax amount can't be negative"));
+    }
+    if to_cents(tax_amount) > to_cents(amount) {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "tax_amount", "Tax amount can't be more than the expense"));
+    }
+    Ok(())
+}
+
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub fn add_expense(date: String, category: String, description: Option<String>, amount: f64, is_drawing: Option<bool>, acknowledge_unusual_date: Option<bool>, session_token: Option<String>, tax_amount: Option<f64>) -> Result<i64, AppError> {
+    if amount <= 0.0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "amount", "Amount must be positive"));
+    }
+    let tax_amount = tax_amount.unwrap_or(0.0);
+    validate_expense_tax(tax_amount, amount)?;
+    
+    validate_date_format(&date)?;
+    
+    let conn = get_db_connection()?;
+    let category = crate::expense_categories::resolve_category(&conn, &category)?;
+    let unusual_date = check_date_plausibility(&conn, "date", &date, acknowledge_unusual_date.unwrap_or(false))?;
+    
+    conn.execute(
+        "INSERT INTO expenses (date, category, description, amount, is_drawing, tax_amount) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![date, category, description, amount, is_drawing.unwrap_or(false), tax_amount],
+    )?;
+    let expense_id = conn.last_insert_rowid();
+    record_change(&conn, &audit_actor(session_token.as_deref()), "expense", expense_id, "create", None, snapshot(&conn, "expense", expense_id)?)?;
+    
+    log_acknowledged_dates(&conn, &format!("expense {}", expense_id), &[unusual_date])?;
+    
+    Ok(expense_id)
+}
+
+#[command]
+pub fn get_expenses(start_date: Option<String>, end_date: Option<String>) -> Result<Vec<ExpenseRecord>, AppError> {
+    let conn = get_db_connection()?;
+    
+    let (query, params): (String, Vec<String>) = match (start_date, end_date) {
+        (Some(start), Some(end)) => {
             validate_date_format(&start)?;
             validate_date_format(&end)?;
-            ("SELECT id, date, category, description, amount FROM expenses WHERE date BETWEEN ?1 AND ?2 ORDER BY date DESC".to_string(),
+            ("SELECT id, date, category, description, amount, is_drawing, tax_amount FROM expenses WHERE deleted_at IS NULL AND date BETWEEN ?1 AND ?2 ORDER BY date DESC".to_string(),
              vec![start, end])
         }
         (Some(start), None) => {
             validate_date_format(&start)?;
-            ("SELECT id, date, category, description, amount FROM expenses WHERE date >= ?1 ORDER BY date DESC".to_string(),
+            ("SELECT id, date, category, description, amount, is_drawing, tax_amount FROM expenses WHERE deleted_at IS NULL AND date >= ?1 ORDER BY date DESC".to_string(),
              vec![start])
         }
         (None, Some(end)) => {
             validate_date_format(&end)?;
-            ("SELECT id, date, category, description, amount FROM expenses WHERE date <= ?1 ORDER BY date DESC".to_string(),
+            ("SELECT id, date, category, description, amount, is_drawing, tax_amount FROM expenses WHERE deleted_at IS NULL AND date <= ?1 ORDER BY date DESC".to_string(),
              vec![end])
         }
         (None, None) => {
-            ("SELECT id, date, category, description, amount FROM expenses ORDER BY date DESC LIMIT 100".to_string(),
+            ("SELECT id, date, category, description, amount, is_drawing, tax_amount FROM expenses WHERE deleted_at IS NULL ORDER BY date DESC LIMIT 100".to_string(),
              vec![])
         }
     };
     
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&query)?;
     
-    let expense_iter = stmt.query_map(rusqlite::params_from_iter(params), |row| {
-        Ok(ExpenseRecord {
-            id: row.get(0)?,
-            date: row.get(1)?,
-            category: row.get(2)?,
-            description: row.get(3)?,
-            amount: row.get(4)?,
-        })
-    }).map_err(|e| e.to_string())?;
+    let expense_iter = stmt.query_map(rusqlite::params_from_iter(params), map_expense)?;
     
-    expense_iter.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    expense_iter.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+const EXPENSE_LIST: ListSpec = ListSpec {
+    sorts: &[("date", "date"), ("amount", "amount"), ("category", "category COLLATE NOCASE")],
+    default_sort: "-date",
+    tie_breaker: "id DESC",
+    filters: &[
+        ("category", "category = ?"),
+        ("is_drawing", "is_drawing = ?"),
+        ("search", "description LIKE '%' || ? || '%'"),
+        ("start_date", "date >= ?"),
+        ("end_date", "date <= ?"),
+    ],
+};
+
+/// Expenses a page at a time; unlike get_expenses there is no 100-row cap.
+/// Sorts: date, amount, category. Filters: category, is_drawing ('1' or '0'),
+/// search (description), start_date and end_date.
+#[command]
+pub fn get_expenses_page(page: Option<PageRequest>) -> Result<PageResult<ExpenseRecord>, AppError> {
+    let query = EXPENSE_LIST.resolve(page)?;
+    let conn = get_db_connection()?;
+    
+    let from_where = format!("FROM expenses WHERE deleted_at IS NULL{}", query.conditions);
+    let total = query.count(&conn, &from_where)?;
+    
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, date, category, description, amount, is_drawing, tax_amount {}{}{}",
+        from_where, query.order_by, query.limit_clause()
+    ))?;
+    let expenses = stmt
+        .query_map(rusqlite::params_from_iter(query.values.iter()), map_expense)?
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    Ok(query.into_result(expenses, total))
+}
+
+fn map_expense(row: &rusqlite::Row) -> rusqlite::Result<ExpenseRecord> {
+    Ok(ExpenseRecord {
+        id: row.get(0)?,
+        date: row.get(1)?,
+        category: row.get(2)?,
+        description: row.get(3)?,
+        amount: row.get(4)?,
+        is_drawing: row.get(5)?,
+        tax_amount: row.get(6)?,
+    })
 }
 
 #[command]
-pub fn get_expenses_by_date_range(start_date: String, end_date: String) -> Result<Vec<ExpenseRecord>, String> {
+pub fn get_expenses_by_date_range(start_date: String, end_date: String) -> Result<Vec<ExpenseRecord>, AppError> {
     validate_date_format(&start_date)?;
     validate_date_format(&end_date)?;
     
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let conn = get_db_connection()?;
     
     let mut stmt = conn.prepare(
-        "SELECT id, date, category, description, amount 
+        "SELECT id, date, category, description, amount, is_drawing, tax_amount 
          FROM expenses 
-         WHERE date >= ?1 AND date <= ?2 
+         WHERE date >= ?1 AND date <= ?2 AND deleted_at IS NULL
          ORDER BY date DESC"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let expense_iter = stmt.query_map([&start_date, &end_date], |row| {
         Ok(ExpenseRecord {
@@ -1284,32 +3753,34 @@ pub fn get_expenses_by_date_range(start_date: String, end_date: String) -> Resul
             category: row.get(2)?,
             description: row.get(3)?,
             amount: row.get(4)?,
+            is_drawing: row.get(5)?,
+            tax_amount: row.get(6)?,
         })
-    }).map_err(|e| e.to_string())?;
+    })?;
     
-    expense_iter.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    expense_iter.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
 }
 
 #[command]
-pub fn update_expense(expense_id: i64, date: Option<String>, category: Option<String>, description: Option<String>, amount: Option<f64>) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+#[allow(clippy::too_many_arguments)]
+pub fn update_expense(expense_id: i64, date: Option<String>, category: Option<String>, description: Option<String>, amount: Option<f64>, is_drawing: Option<bool>, acknowledge_unusual_date: Option<bool>, session_token: Option<String>, tax_amount: Option<f64>) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
     
     // Build dynamic update query
     let mut update_parts = Vec::new();
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+    let mut unusual_date = None;
     
     if let Some(ref exp_date) = date {
         validate_date_format(exp_date)?;
+        unusual_date = check_date_plausibility(&conn, "date", exp_date, acknowledge_unusual_date.unwrap_or(false))?;
         update_parts.push("date = ?");
         params.push(Box::new(exp_date.clone()));
     }
     
     if let Some(ref cat) = category {
-        if cat.trim().is_empty() {
-            return Err("Category cannot be empty".to_string());
-        }
         update_parts.push("category = ?");
-        params.push(Box::new(cat.trim().to_string()));
+        params.push(Box::new(crate::expense_categories::resolve_category(&conn, cat)?));
     }
     
     if let Some(ref desc) = description {
@@ -1319,123 +3790,163 @@ pub fn update_expense(expense_id: i64, date: Option<String>, category: Option<St
     
     if let Some(exp_amount) = amount {
         if exp_amount <= 0.0 {
-            return Err("Amount must be positive".to_string());
+            return Err(AppError::invalid(NEGATIVE_AMOUNT, "amount", "Amount must be positive"));
         }
         update_parts.push("amount = ?");
         params.push(Box::new(exp_amount));
     }
     
+    if let Some(drawing) = is_drawing {
+        update_parts.push("is_drawing = ?");
+        params.push(Box::new(drawing));
+    }
+    
+    if amount.is_some() || tax_amount.is_some() {
+        let (stored_amount, stored_tax): (f64, f64) = conn.query_row(
+            "SELECT amount, tax_amount FROM expenses WHERE id = ?1 AND deleted_at IS NULL",
+            params![expense_id],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        ).optional()?.ok_or("Expense not found")?;
+        validate_expense_tax(tax_amount.unwrap_or(stored_tax), amount.unwrap_or(stored_amount))?;
+    }
+    
+    if let Some(tax) = tax_amount {
+        update_parts.push("tax_amount = ?");
+        params.push(Box::new(tax));
+    }
+    
     if update_parts.is_empty() {
-        return Err("No fields to update".to_string());
+        return Err("No fields to update".into());
     }
     
-    let query = format!("UPDATE expenses SET {} WHERE id = ?", update_parts.join(", "));
+    let query = format!("UPDATE expenses SET {} WHERE id = ? AND deleted_at IS NULL", update_parts.join(", "));
     params.push(Box::new(expense_id));
     
     let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
     
-    let affected = conn.execute(&query, &*param_refs).map_err(|e| e.to_string())?;
+    let before = snapshot(&conn, "expense", expense_id)?;
+    let affected = conn.execute(&query, &*param_refs)?;
     
     if affected == 0 {
-        return Err("Expense not found".to_string());
+        return Err("Expense not found".into());
     }
+    record_change(&conn, &audit_actor(session_token.as_deref()), "expense", expense_id, "update", before, snapshot(&conn, "expense", expense_id)?)?;
+    
+    log_acknowledged_dates(&conn, &format!("expense {}", expense_id), &[unusual_date])?;
     
     Ok("Expense updated successfully".to_string())
 }
 
+/// Move an expense to the trash, from where `restore_item` can bring it back.
 #[command]
-pub fn delete_expense(expense_id: i64) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn delete_expense(expense_id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let actor = audit_actor(session_token.as_deref());
     
+    let before = snapshot(&conn, "expense", expense_id)?;
     let affected = conn.execute(
-        "DELETE FROM expenses WHERE id = ?1",
-        params![expense_id],
-    ).map_err(|e| e.to_string())?;
+        "UPDATE expenses SET deleted_at = ?1, deleted_by = ?2 WHERE id = ?3 AND deleted_at IS NULL",
+        params![get_current_timestamp(), actor, expense_id],
+    )?;
     
     if affected == 0 {
-        return Err("Expense not found".to_string());
+        return Err("Expense not found".into());
     }
+    record_change(&conn, &actor, "expense", expense_id, "delete", before, None)?;
     
-    Ok("Expense deleted successfully".to_string())
+    Ok("Expense moved to the trash".to_string())
 }
 
-#[tauri::command]
-pub fn toggle_food_order_payment(order_id: i64) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    // Get current payment status
-    let current_paid: i64 = conn.query_row(
-        "SELECT paid FROM sales WHERE id = ?1",
-        params![order_id],
-        |row| row.get(0)
-    ).map_err(|e| {
-        if e.to_string().contains("no rows") {
-            "Food order not found".to_string()
-        } else {
-            e.to_string()
+/// Owner withdrawals in a date range, oldest first, with a running total.
+#[command]
+pub async fn drawings_report(start_date: String, end_date: String) -> Result<DrawingsReport, AppError> {
+    run_blocking(move || {
+        validate_date_format(&start_date)?;
+        validate_date_format(&end_date)?;
+    
+        let conn = get_db_connection()?;
+    
+        let mut stmt = conn.prepare(
+            "SELECT id, date, category, description, amount
+             FROM expenses
+             WHERE is_drawing = 1 AND deleted_at IS NULL AND date >= ?1 AND date <= ?2
+             ORDER BY date, id"
+        )?;
+    
+        let mut running_total = 0.0;
+        let mut rows = Vec::new();
+        let drawing_iter = stmt.query_map(params![start_date, end_date], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, Option<String>>(3)?, row.get::<_, f64>(4)?))
+        })?;
+        for drawing in drawing_iter {
+            let (id, date, category, description, amount) = drawing?;
+            running_total += amount;
+            rows.push(DrawingRow { id, date, category, description, amount, running_total });
         }
-    })?;
     
-    // Toggle the payment status
-    let new_paid = if current_paid == 0 { 1 } else { 0 };
-    let paid_at = if new_paid == 1 { 
-        Some(get_current_timestamp()) 
-    } else { 
-        None 
+        Ok(DrawingsReport {
+            start_date,
+            end_date,
+            rows,
+            total: running_total,
+        })
+    })
+    .await
+}
+
+/// Paid orders lose all their payments; anything else is settled in cash.
+#[tauri::command]
+pub fn toggle_food_order_payment(order_id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    
+    let summary = order_payment_summary(&tx, order_id).map_err(|_| "Food order not found".to_string())?;
+    let before = snapshot(&tx, "order", order_id)?;
+    let status = if summary.payment_status == "paid" {
+        tx.execute("DELETE FROM payments WHERE order_id = ?1", params![order_id])?;
+        "unpaid"
+    } else {
+        if summary.balance > 0.0 {
+            insert_payment(&tx, order_id, summary.balance, "cash", None)?;
+        }
+        "paid"
     };
+    sync_order_payment_status(&tx, order_id)?;
+    record_change(&tx, &audit_actor(session_token.as_deref()), "order", order_id, "update", before, snapshot(&tx, "order", order_id)?)?;
     
-    conn.execute(
-        "UPDATE sales SET paid = ?1, paid_at = ?2 WHERE id = ?3",
-        params![new_paid, paid_at, order_id],
-    ).map_err(|e| e.to_string())?;
-    
-    let status = if new_paid == 1 { "paid" } else { "unpaid" };
+    tx.commit()?;
     Ok(format!("Food order marked as {}", status))
 }
 
+/// Move an order to the trash. Its lines, payments and tags stay with it so
+/// `restore_item` can bring it back; `purge_trash` removes it for good.
 #[tauri::command]
-pub fn delete_food_order(order_id: i64) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    // Start a transaction
-    conn.execute("BEGIN TRANSACTION", []).map_err(|e| e.to_string())?;
-    
-    // Delete order items first (foreign key constraint)
-    conn.execute(
-        "DELETE FROM sale_items WHERE order_id = ?1",
-        params![order_id],
-    ).map_err(|e| {
-        let _ = conn.execute("ROLLBACK", []);
-        e.to_string()
-    })?;
-    
-    // Delete the food order
-    let rows_affected = conn.execute(
-        "DELETE FROM sales WHERE id = ?1",
-        params![order_id],
-    ).map_err(|e| {
-        let _ = conn.execute("ROLLBACK", []);
-        e.to_string()
-    })?;
-    
+pub fn delete_food_order(order_id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    let before = snapshot(&tx, "order", order_id)?;
+    let actor = audit_actor(session_token.as_deref());
+    
+    let rows_affected = tx.execute(
+        "UPDATE sales SET deleted_at = ?1, deleted_by = ?2 WHERE id = ?3 AND deleted_at IS NULL",
+        params![get_current_timestamp(), actor, order_id],
+    )?;
     if rows_affected == 0 {
-        let _ = conn.execute("ROLLBACK", []);
-        return Err("Food order not found".to_string());
+        return Err(AppError::coded(ORDER_NOT_FOUND, "Food order not found"));
     }
+    record_change(&tx, &actor, "order", order_id, "delete", before, None)?;
     
-    // Commit the transaction
-    conn.execute("COMMIT", []).map_err(|e| e.to_string())?;
-    
-    Ok("Food order deleted successfully".to_string())
+    tx.commit()?;
+    Ok("Food order moved to the trash".to_string())
 }
 
 #[tauri::command]
-pub fn get_order_details(order_id: i64) -> Result<FoodOrderDetails, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_order_details(order_id: i64) -> Result<FoodOrderDetails, AppError> {
+    let conn = get_db_connection()?;
     
     // Get order details
     let order = conn.query_row(
-        "SELECT id, guest_id, customer_type, customer_name, created_at, paid, paid_at, total_amount
+        "SELECT id, guest_id, customer_type, customer_name, created_at, paid, paid_at, total_amount, amount_paid, notes, status
          FROM sales WHERE id = ?1",
         params![order_id],
         |row| Ok(FoodOrderInfo {
@@ -1447,31 +3958,21 @@ pub fn get_order_details(order_id: i64) -> Result<FoodOrderDetails, String> {
             paid: row.get::<_, i32>(5)? == 1,
             paid_at: row.get(6)?,
             total_amount: row.get(7)?,
+            amount_paid: row.get(8)?,
+            payment_status: payment_status(row.get::<_, i32>(5)? == 1, row.get(8)?),
+            notes: row.get(9)?,
+            status: row.get(10)?,
         })
-    ).map_err(|e| e.to_string())?;
+    )?;
     
-    // Get order items
-    let mut stmt = conn.prepare(
-        "SELECT id, menu_item_id, item_name, quantity, unit_price, line_total
-            FROM sale_items WHERE order_id = ?1"
-    ).map_err(|e| e.to_string())?;
+    let items = order_item_details(&conn, order_id)?;
     
-    let items = stmt.query_map([order_id], |row| {
-        Ok(OrderItemDetail {
-            id: row.get(0)?,
-            menu_item_id: row.get(1)?,
-            item_name: row.get(2)?,
-            quantity: row.get(3)?,
-            unit_price: row.get(4)?,
-            line_total: row.get(5)?,
-        })
-    }).map_err(|e| e.to_string())?
-    .collect::<Result<Vec<_>, _>>()
-    .map_err(|e| e.to_string())?;
+    let tags = crate::tags::tags_for_entity(&conn, "order", order_id)?;
     
     Ok(FoodOrderDetails {
         order: order,
         items: items,
+        tags,
     })
 }
 
@@ -1479,411 +3980,241 @@ pub fn get_order_details(order_id: i64) -> Result<FoodOrderDetails, String> {
 // Generic naming wrappers for legacy "food order" commands.
 
 #[command]
+#[allow(clippy::too_many_arguments)]
 pub fn add_sale(
+    app: tauri::AppHandle,
     guest_id: Option<i64>,
     customer_type: String,
     customer_name: Option<String>,
     items: Vec<OrderItemInput>,
-) -> Result<i64, String> {
-    add_food_order(guest_id, customer_type, customer_name, items)
+    session_token: Option<String>,
+    notes: Option<String>,
+    table_id: Option<i64>,
+) -> Result<i64, AppError> {
+    add_food_order(app, guest_id, customer_type, customer_name, items, session_token, notes, table_id)
 }
 
 #[command]
-pub fn get_sales() -> Result<Vec<FoodOrderSummary>, String> {
-    get_food_orders()
+pub fn get_sales(tag_ids: Option<Vec<i64>>) -> Result<Vec<FoodOrderSummary>, AppError> {
+    get_food_orders(tag_ids)
 }
 
 #[command]
-pub fn get_sales_by_customer(customer_id: i64) -> Result<Vec<FoodOrderSummary>, String> {
-    get_food_orders_by_guest(customer_id)
+pub fn get_sales_by_customer(customer_id: i64, tag_ids: Option<Vec<i64>>) -> Result<Vec<FoodOrderSummary>, AppError> {
+    get_food_orders_by_guest(customer_id, tag_ids)
 }
 
 #[command]
-pub fn mark_sale_paid(order_id: i64) -> Result<String, String> {
-    mark_order_paid(order_id)
+pub fn mark_sale_paid(order_id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    mark_order_paid(order_id, session_token)
 }
 
 #[command]
-pub fn toggle_sale_payment(order_id: i64) -> Result<String, String> {
-    toggle_food_order_payment(order_id)
+pub fn toggle_sale_payment(order_id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    toggle_food_order_payment(order_id, session_token)
 }
 
 #[command]
-pub fn delete_sale(order_id: i64) -> Result<String, String> {
-    delete_food_order(order_id)
+pub fn delete_sale(order_id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    delete_food_order(order_id, session_token)
 }
 
 #[command]
-pub fn get_sale_details(order_id: i64) -> Result<FoodOrderDetails, String> {
+pub fn get_sale_details(order_id: i64) -> Result<FoodOrderDetails, AppError> {
     get_order_details(order_id)
 }
 
 // Enhanced checkout function with discount support
 #[command]
+#[allow(clippy::too_many_arguments)]
 pub fn checkout_guest_with_discount(
     guest_id: i64, 
     check_out_date: String,
     discount_type: String,
     discount_amount: f64,
-    _discount_description: String
-) -> Result<f64, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    // Get guest details
-    let (check_in, daily_rate, room_id): (String, f64, Option<i64>) = conn.query_row(
-        "SELECT check_in, daily_rate, room_id FROM customers WHERE id = ?1 AND status = 'active'",
-        params![guest_id],
-        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
-    ).map_err(|e| {
-        if e.to_string().contains("no rows") {
-            "Active guest not found".to_string()
-        } else {
-            e.to_string()
-        }
-    })?;
-    
-    // Calculate stay days
-    let check_in_date = NaiveDate::parse_from_str(&check_in, "%Y-%m-%d")
-        .map_err(|_| "Invalid check-in date format")?;
-    let check_out_date_parsed = NaiveDate::parse_from_str(&check_out_date, "%Y-%m-%d")
-        .map_err(|_| "Invalid check-out date format")?;
-    let stay_days = (check_out_date_parsed - check_in_date).num_days().max(1);
-    
-    // Calculate room total
-    let room_total = stay_days as f64 * daily_rate;
-    
-    // Calculate unpaid food total
-    let unpaid_food: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(total_amount), 0) FROM sales WHERE guest_id = ?1 AND paid = 0",
-        params![guest_id],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    // Calculate subtotal before discount
-    let subtotal = room_total + unpaid_food;
-    
-    // Apply discount
-    let discount_value = if discount_amount > 0.0 {
+    _discount_description: String,
+    payment_method: Option<String>,
+    session_token: Option<String>,
+    voucher_code: Option<String>,
+) -> Result<f64, AppError> {
+    let payment_method = parse_payment_method(payment_method.as_deref().unwrap_or("cash"))?;
+    let (discount_pct, discount_flat) = if discount_amount > 0.0 {
         match discount_type.as_str() {
             "percentage" => {
                 if discount_amount > 100.0 {
-                    return Err("Percentage discount cannot exceed 100%".to_string());
+                    return Err("Percentage discount cannot exceed 100%".into());
                 }
-                subtotal * (discount_amount / 100.0)
+                (Some(discount_amount), None)
             },
-            "flat" => discount_amount,
-            _ => return Err("Invalid discount type. Use 'flat' or 'percentage'".to_string())
+            "flat" => (None, Some(discount_amount)),
+            _ => return Err("Invalid discount type. Use 'flat' or 'percentage'".into())
         }
     } else {
-        0.0
+        (None, None)
     };
+    let check_out = NaiveDate::parse_from_str(&check_out_date, "%Y-%m-%d")
+        .map_err(|_| "Invalid check-out date format")?;
     
-    // Calculate final total
-    let grand_total = (subtotal - discount_value).max(0.0);
-    
-    // Update guest status and free up the room
-    let now = get_current_timestamp();
-    
-    // Start a transaction to ensure all operations succeed or fail together
-    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
-    
-    // Update guest checkout status
-    tx.execute(
-        "UPDATE customers SET status = 'checked_out', check_out = ?1, updated_at = ?2 WHERE id = ?3",
-        params![check_out_date, now, guest_id],
-    ).map_err(|e| e.to_string())?;
-    
-    // Free up the room if guest had one
-    if let Some(room_id) = room_id {
-        tx.execute(
-            "UPDATE resources SET is_occupied = 0, guest_id = NULL WHERE id = ?1",
-            params![room_id],
-        ).map_err(|e| e.to_string())?;
-    }
-    
-    // If there was a discount, log it (you could add a discounts table later)
-    if discount_value > 0.0 {
-        // For now, we'll just log it in a comment or you could create a discounts table
-        // tx.execute(
-        //     "INSERT INTO discounts (guest_id, discount_type, discount_amount, description, created_at) 
-        //      VALUES (?1, ?2, ?3, ?4, ?5)",
-        //     params![guest_id, discount_type, discount_value, discount_description, now],
-        // ).map_err(|e| e.to_string())?;
-    }
-    
-    tx.commit().map_err(|e| e.to_string())?;
-    
-    Ok(grand_total)
+    let totals = checkout_active_guest(guest_id, Some(check_out), discount_flat, discount_pct, voucher_code, session_token, |tx, balance_due| {
+        Ok(insert_checkout_payment(tx, guest_id, balance_due, &payment_method)?)
+    })?;
+    Ok(totals.grand_total)
 }
 
 // ===== TAX RATE COMMANDS =====
 
+/// Single-rate setting kept for setup and the catalog screen; named taxes are
+/// managed in taxes.rs.
 #[command]
-pub fn set_tax_rate(rate: f64) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    // Validate tax rate
-    if rate < 0.0 || rate > 100.0 {
-        return Err("Tax rate must be between 0 and 100".to_string());
-    }
-    
-    // Create/migrate settings table
-    ensure_settings_table(&conn)?;
-    
-    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    
-    // Insert or update tax rate
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('tax_rate', ?1, ?2)",
-        params![rate.to_string(), now],
-    ).map_err(|e| e.to_string())?;
-    
+pub fn set_tax_rate(rate: f64) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    crate::taxes::set_single_rate(&conn, rate)?;
     Ok(format!("Tax rate set to {}%", rate))
 }
 
+/// Combined percent of the taxes that apply to the whole bill.
 #[command]
-pub fn get_tax_rate() -> Result<f64, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    // Try to get tax rate from settings
-    let mut stmt = conn.prepare(
-        "SELECT value FROM settings WHERE key = 'tax_rate'"
-    ).map_err(|e| e.to_string())?;
-    
-    let result = stmt.query_row([], |row| {
-        let value_str: String = row.get(0)?;
-        Ok(value_str.parse::<f64>().unwrap_or(5.0))
-    });
-    
-    match result {
-        Ok(rate) => Ok(rate),
-        Err(_) => {
-            // If no tax rate is set, return default 5%
-            Ok(5.0)
-        }
-    }
+pub fn get_tax_rate() -> Result<f64, AppError> {
+    let conn = get_db_connection()?;
+    Ok(crate::taxes::combined_rate(&conn)?)
 }
 
 #[command]
-pub fn set_tax_enabled(enabled: bool) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-
-    // Create/migrate settings table
-    ensure_settings_table(&conn)?;
-    
-    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    
-    // Insert or update tax enabled setting
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('tax_enabled', ?1, ?2)",
-        params![enabled.to_string(), now],
-    ).map_err(|e| e.to_string())?;
-    
+pub fn set_tax_enabled(enabled: bool) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    set_setting(&conn, "tax_enabled", &enabled.to_string())?;
     Ok(format!("Tax {} successfully", if enabled { "enabled" } else { "disabled" }))
 }
 
 #[command]
-pub fn get_tax_enabled() -> Result<bool, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    
-    // Try to get tax enabled setting
-    let mut stmt = conn.prepare(
-        "SELECT value FROM settings WHERE key = 'tax_enabled'"
-    ).map_err(|e| e.to_string())?;
-    
-    let result = stmt.query_row([], |row| {
-        let value_str: String = row.get(0)?;
-        Ok(value_str.parse::<bool>().unwrap_or(true))
-    });
-    
-    match result {
-        Ok(enabled) => Ok(enabled),
-        Err(_) => {
-            // If no setting is found, return default true (enabled)
-            Ok(true)
-        }
-    }
+pub fn get_tax_enabled() -> Result<bool, AppError> {
+    let conn = get_db_connection()?;
+    Ok(crate::taxes::tax_enabled(&conn)?)
 }
 
 // ===== CURRENCY / LOCALE SETTINGS =====
 
-fn ensure_settings_table(conn: &rusqlite::Connection) -> Result<(), String> {
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS settings (
-            key TEXT PRIMARY KEY,
-            value TEXT NOT NULL,
-            updated_at TEXT NOT NULL
-        )",
-        [],
-    )
-    .map_err(|e| e.to_string())?;
-
-    // If the table was created by an older app version without `updated_at`, migrate it.
-    let has_updated_at: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('settings') WHERE name = 'updated_at'",
-            [],
-            |row| row.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-
-    if has_updated_at == 0 {
-        conn.execute(
-            "ALTER TABLE settings ADD COLUMN updated_at TEXT NOT NULL DEFAULT ''",
-            [],
-        )
-        .map_err(|e| e.to_string())?;
-    }
-    Ok(())
-}
-
 #[command]
-pub fn set_currency_code(code: String) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    ensure_settings_table(&conn)?;
+pub fn set_currency_code(code: String) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
 
     let normalized = code.trim().to_uppercase();
     if normalized.len() != 3 || !normalized.chars().all(|c| c.is_ascii_alphabetic()) {
-        return Err("Currency code must be a 3-letter ISO code (e.g., USD, EUR)".to_string());
+        return Err("Currency code must be a 3-letter ISO code (e.g., USD, EUR)".into());
     }
 
-    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('currency_code', ?1, ?2)",
-        rusqlite::params![normalized, now],
-    )
-    .map_err(|e| e.to_string())?;
+    set_setting(&conn, "currency_code", &normalized)?;
 
     Ok("Currency updated".to_string())
 }
 
 #[command]
-pub fn get_currency_code() -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    ensure_settings_table(&conn)?;
-
-    let mut stmt = conn
-        .prepare("SELECT value FROM settings WHERE key = 'currency_code'")
-        .map_err(|e| e.to_string())?;
-
-    let result: Result<String, _> = stmt.query_row([], |row| row.get(0));
-    Ok(result.unwrap_or_else(|_| "USD".to_string()))
+pub fn get_currency_code() -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    Ok(get_setting_or(&conn, "currency_code", "USD")?)
 }
 
 #[command]
-pub fn set_locale(locale: String) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    ensure_settings_table(&conn)?;
+pub fn set_locale(locale: String) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
 
     let normalized = locale.trim();
     if normalized.is_empty() {
-        return Err("Locale cannot be empty (e.g., en-US, fr-FR)".to_string());
+        return Err("Locale cannot be empty (e.g., en-US, fr-FR)".into());
     }
 
-    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('locale', ?1, ?2)",
-        rusqlite::params![normalized, now],
-    )
-    .map_err(|e| e.to_string())?;
+    set_setting(&conn, "locale", normalized)?;
 
     Ok("Locale updated".to_string())
 }
 
 #[command]
-pub fn get_locale() -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    ensure_settings_table(&conn)?;
-
-    let mut stmt = conn
-        .prepare("SELECT value FROM settings WHERE key = 'locale'")
-        .map_err(|e| e.to_string())?;
-
-    let result: Result<String, _> = stmt.query_row([], |row| row.get(0));
-    Ok(result.unwrap_or_else(|_| "en-US".to_string()))
+pub fn get_locale() -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    Ok(get_setting_or(&conn, "locale", "en-US")?)
 }
 
 // ===== BUSINESS PROFILE SETTINGS =====
 
 #[command]
-pub fn set_business_name(name: String) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    ensure_settings_table(&conn)?;
+pub fn set_business_name(name: String) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
 
     let normalized = name.trim();
     if normalized.is_empty() {
-        return Err("Business name is required".to_string());
+        return Err("Business name is required".into());
     }
     if normalized.chars().count() > 80 {
-        return Err("Business name must be 80 characters or fewer".to_string());
+        return Err("Business name must be 80 characters or fewer".into());
     }
 
-    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('business_name', ?1, ?2)",
-        rusqlite::params![normalized, now],
-    )
-    .map_err(|e| e.to_string())?;
+    set_setting(&conn, "business_name", normalized)?;
 
     Ok("Business name updated".to_string())
 }
 
 #[command]
-pub fn get_business_name() -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    ensure_settings_table(&conn)?;
+pub fn get_business_name() -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    Ok(get_setting_or(&conn, "business_name", "Business Manager")?)
+}
 
-    let mut stmt = conn
-        .prepare("SELECT value FROM settings WHERE key = 'business_name'")
-        .map_err(|e| e.to_string())?;
+/// Name and contact details printed at the top of receipts, invoices and
+/// registration cards. Blank address, phone or email lines are left off.
+#[command]
+pub fn set_business_info(name: String, address: Option<String>, phone: Option<String>, email: Option<String>) -> Result<BusinessInfo, AppError> {
+    set_business_name(name)?;
+
+    let conn = get_db_connection()?;
+    for (key, value) in [("business_address", address), ("business_phone", phone), ("business_email", email)] {
+        set_setting(&conn, key, value.as_deref().map(str::trim).unwrap_or_default())?;
+    }
+    get_business_info()
+}
 
-    let result: Result<String, _> = stmt.query_row([], |row| row.get(0));
-    Ok(result.unwrap_or_else(|_| "Business Manager".to_string()))
+#[command]
+pub fn get_business_info() -> Result<BusinessInfo, AppError> {
+    let conn = get_db_connection()?;
+    let setting = |key: &str| get_setting_or(&conn, key, "");
+    let logo_path = setting("business_logo_path")?;
+    Ok(BusinessInfo {
+        name: get_business_name()?,
+        address: setting("business_address")?,
+        phone: setting("business_phone")?,
+        email: setting("business_email")?,
+        logo_path: Some(logo_path).filter(|p| !p.trim().is_empty()),
+    })
 }
 
 // ===== BUSINESS MODE SETTINGS =====
 
+pub const BUSINESS_MODES: &[&str] = &["hotel", "restaurant", "retail"];
+
 #[command]
-pub fn set_business_mode(mode: String) -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    ensure_settings_table(&conn)?;
+pub fn set_business_mode(mode: String) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
 
     let normalized = mode.trim().to_lowercase();
-    match normalized.as_str() {
-        "hotel" | "restaurant" | "retail" => {}
-        _ => {
-            return Err("Business mode must be one of: hotel, restaurant, retail".to_string());
-        }
+    if !BUSINESS_MODES.contains(&normalized.as_str()) {
+        return Err(format!("Business mode must be one of: {}", BUSINESS_MODES.join(", ")).into());
     }
 
-    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('business_mode', ?1, ?2)",
-        rusqlite::params![normalized, now],
-    )
-    .map_err(|e| e.to_string())?;
+    set_setting(&conn, "business_mode", &normalized)?;
 
     Ok("Business mode updated".to_string())
 }
 
-#[command]
-pub fn get_business_mode() -> Result<String, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
-    ensure_settings_table(&conn)?;
-
-    let mut stmt = conn
-        .prepare("SELECT value FROM settings WHERE key = 'business_mode'")
-        .map_err(|e| e.to_string())?;
-
-    let result: Result<String, _> = stmt.query_row([], |row| row.get(0));
-    Ok(result.unwrap_or_else(|_| "hotel".to_string()))
+#[command]
+pub fn get_business_mode() -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    Ok(get_setting_or(&conn, "business_mode", "hotel")?)
 }
 
 // ===== SHIFT MANAGEMENT (Z-REPORT) =====
 
 #[tauri::command]
-pub fn open_shift(admin_id: i64, start_cash: f64) -> Result<i64, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn open_shift(admin_id: i64, start_cash: f64) -> Result<i64, AppError> {
+    let conn = get_db_connection()?;
     
     // Check if there's already an open shift
     let existing_shift: Result<i64, _> = conn.query_row(
@@ -1893,7 +4224,7 @@ pub fn open_shift(admin_id: i64, start_cash: f64) -> Result<i64, String> {
     );
     
     if existing_shift.is_ok() {
-        return Err("There is already an open shift. Please close it first.".to_string());
+        return Err(AppError::coded(crate::validation::SHIFT_ALREADY_OPEN, "There is already an open shift. Please close it first."));
     }
     
     let now = get_current_timestamp();
@@ -1902,24 +4233,227 @@ pub fn open_shift(admin_id: i64, start_cash: f64) -> Result<i64, String> {
         "INSERT INTO shifts (opened_at, opened_by, start_cash, status) 
          VALUES (?1, ?2, ?3, 'open')",
         params![now, admin_id, start_cash],
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let shift_id = conn.last_insert_rowid();
     Ok(shift_id)
 }
 
+fn default_denominations(currency_code: &str) -> Vec<f64> {
+    match currency_code {
+        "PKR" => vec![5000.0, 1000.0, 500.0, 100.0, 75.0, 50.0, 20.0, 10.0, 5.0, 2.0, 1.0],
+        "INR" => vec![500.0, 200.0, 100.0, 50.0, 20.0, 10.0, 5.0, 2.0, 1.0],
+        "EUR" => vec![500.0, 200.0, 100.0, 50.0, 20.0, 10.0, 5.0, 2.0, 1.0, 0.5, 0.2, 0.1, 0.05, 0.02, 0.01],
+        "GBP" => vec![50.0, 20.0, 10.0, 5.0, 2.0, 1.0, 0.5, 0.2, 0.1, 0.05, 0.02, 0.01],
+        _ => vec![100.0, 50.0, 20.0, 10.0, 5.0, 2.0, 1.0, 0.25, 0.1, 0.05, 0.01],
+    }
+}
+
+/// Denominations configured for the active currency, highest first.
+fn load_denominations(conn: &rusqlite::Connection) -> Result<Vec<f64>, String> {
+    let currency_code = get_setting_or(conn, "currency_code", "USD")?.trim().to_uppercase();
+    let configured: Option<Vec<f64>> = get_setting(conn, &format!("denominations_{}", currency_code))?
+        .and_then(|json| serde_json::from_str(&json).ok());
+
+    let mut values = configured.unwrap_or_else(|| default_denominations(&currency_code));
+    values.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(values)
+}
+
+pub fn to_cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+/// Validate a counted breakdown (denomination -> count) against the configured list.
+fn count_denominations(
+    configured: &[f64],
+    breakdown: &HashMap<String, i64>,
+) -> Result<Vec<DenominationCount>, String> {
+    let mut counts: Vec<DenominationCount> = Vec::with_capacity(breakdown.len());
+
+    for (key, count) in breakdown {
+        let value = configured
+            .iter()
+            .copied()
+            .find(|v| key.trim().parse::<f64>().map(|k| to_cents(k) == to_cents(*v)).unwrap_or(false))
+            .ok_or_else(|| format!("Unknown denomination '{}'", key))?;
+
+        if *count < 0 {
+            return Err(format!("Count for denomination {} cannot be negative", key));
+        }
+
+        counts.push(DenominationCount {
+            value,
+            count: *count,
+            subtotal: value * *count as f64,
+        });
+    }
+
+    counts.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(counts)
+}
+
+fn parse_shift_denominations(raw: Option<String>) -> Option<Vec<DenominationCount>> {
+    raw.and_then(|json| serde_json::from_str(&json).ok())
+}
+
+#[tauri::command]
+pub fn get_denominations() -> Result<Vec<f64>, AppError> {
+    let conn = get_db_connection()?;
+    load_denominations(&conn).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn set_denominations(denominations: Vec<f64>) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+
+    if denominations.is_empty() {
+        return Err("At least one denomination is required".into());
+    }
+
+    let mut values: Vec<f64> = Vec::with_capacity(denominations.len());
+    for value in denominations {
+        if !value.is_finite() || value <= 0.0 {
+            return Err(format!("Invalid denomination {}", value).into());
+        }
+        if values.iter().any(|v| to_cents(*v) == to_cents(value)) {
+            return Err(format!("Denomination {} is listed more than once", value).into());
+        }
+        values.push(value);
+    }
+    values.sort_by(|a, b| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+    let currency_code = get_currency_code()?.trim().to_uppercase();
+    let json = serde_json::to_string(&values)?;
+    set_setting(&conn, &format!("denominations_{}", currency_code), &json)?;
+
+    Ok(format!("Denominations updated for {}", currency_code))
+}
+
+/// Every payment taken while the shift was open, refunds included, except on
+/// orders since moved to the trash.
+fn shift_payments(conn: &rusqlite::Connection, shift_id: i64) -> Result<Vec<ShiftPayment>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, order_id, guest_id, amount, method, paid_at, note FROM payments WHERE shift_id = ?1 AND {} ORDER BY paid_at, id",
+        crate::trash::outside_trash("payments.order_id")
+    ))?;
+    let payments = stmt.query_map(params![shift_id], |row| {
+        Ok(ShiftPayment {
+            id: row.get(0)?,
+            order_id: row.get(1)?,
+            guest_id: row.get(2)?,
+            amount: row.get(3)?,
+            method: row.get(4)?,
+            paid_at: row.get(5)?,
+            note: row.get(6)?,
+        })
+    })?;
+    payments.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+/// Takings per method and the cash the drawer should hold at `until`. Counted
+/// cash is left for close_shift to fill in.
+fn build_shift_reconciliation(
+    conn: &rusqlite::Connection,
+    shift_id: i64,
+    opened_at: &str,
+    start_cash: f64,
+    until: &str,
+) -> Result<ShiftReconciliation, AppError> {
+    let payments = shift_payments(conn, shift_id)?;
+    let methods: Vec<ShiftMethodTotal> = PAYMENT_METHODS
+        .iter()
+        .map(|method| {
+            let rows = payments.iter().filter(|p| p.method == *method);
+            let taken = rows.clone().filter(|p| p.amount > 0.0).map(|p| to_cents(p.amount)).sum::<i64>();
+            let refunded = rows.clone().filter(|p| p.amount < 0.0).map(|p| -to_cents(p.amount)).sum::<i64>();
+            ShiftMethodTotal {
+                method: method.to_string(),
+                payment_count: rows.count() as i64,
+                taken: taken as f64 / 100.0,
+                refunded: refunded as f64 / 100.0,
+                net: (taken - refunded) as f64 / 100.0,
+            }
+        })
+        .collect();
+    let cash_sales = methods[0].net;
+    
+    // Calculate total expenses and owner drawings paid out of the drawer during this shift.
+    // Expenses carry a local calendar date; the shift's times are RFC 3339 in UTC.
+    let local_date = |at: &str| {
+        chrono::DateTime::parse_from_rfc3339(at)
+            .map(|at| at.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|_| at.split([' ', 'T']).next().unwrap_or(at).to_string())
+    };
+    let (total_expenses, owner_drawings): (f64, f64) = conn.query_row(
+        "SELECT COALESCE(SUM(CASE WHEN is_drawing = 0 THEN amount END), 0),
+                COALESCE(SUM(CASE WHEN is_drawing = 1 THEN amount END), 0)
+         FROM expenses 
+         WHERE date >= ?1 AND date <= ?2 AND deleted_at IS NULL",
+        params![local_date(opened_at), local_date(until)],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    )?;
+    
+    Ok(ShiftReconciliation {
+        shift_id,
+        status: "open".to_string(),
+        opened_at: opened_at.to_string(),
+        closed_at: None,
+        start_cash,
+        methods,
+        payments,
+        cash_sales,
+        total_expenses,
+        owner_drawings,
+        expected_cash: start_cash + cash_sales - total_expenses - owner_drawings,
+        counted_cash: None,
+        difference: None,
+        denominations: None,
+    })
+}
+
 #[tauri::command]
 pub fn close_shift(
     shift_id: i64,
     admin_id: i64,
-    end_cash_actual: f64,
-    notes: Option<String>
-) -> Result<ShiftSummary, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    end_cash_actual: Option<f64>,
+    notes: Option<String>,
+    denominations: Option<HashMap<String, i64>>,
+) -> Result<ShiftSummary, AppError> {
+    let conn = get_db_connection()?;
+
+    // When a breakdown is supplied the counted total is derived from it, and any
+    // explicitly typed total has to agree with it.
+    let counted = match &denominations {
+        Some(breakdown) => Some(count_denominations(&load_denominations(&conn)?, breakdown)?),
+        None => None,
+    };
+
+    let end_cash_actual = match (&counted, end_cash_actual) {
+        (Some(counts), explicit) => {
+            let computed = counts.iter().map(|c| c.subtotal).sum::<f64>();
+            if let Some(entered) = explicit {
+                if to_cents(entered) != to_cents(computed) {
+                    return Err(format!(
+                        "Counted total from denominations is {:.2} but {:.2} was entered",
+                        computed, entered
+                    ).into());
+                }
+            }
+            computed
+        }
+        (None, Some(entered)) => entered,
+        (None, None) => return Err("Either end_cash_actual or a denominations breakdown is required".into()),
+    };
+
+    let denominations_json = match &counted {
+        Some(counts) => Some(serde_json::to_string(counts)?),
+        None => None,
+    };
     
     // Get shift info
     let shift_info: Result<(String, i64, f64), _> = conn.query_row(
-        "SELECT opened_at, opened_by, start_cash FROM shifts WHERE id = ?1 AND status = 'open'",
+        "SELECT opened_at, CAST(opened_by AS INTEGER), start_cash FROM shifts WHERE id = ?1 AND status = 'open'",
         params![shift_id],
         |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
     );
@@ -1927,36 +4461,38 @@ pub fn close_shift(
     let (opened_at, opened_by, start_cash) = shift_info.map_err(|_| "Shift not found or already closed".to_string())?;
     
     let now = get_current_timestamp();
+    let mut reconciliation = build_shift_reconciliation(&conn, shift_id, &opened_at, start_cash, &now)?;
+    let total_sales = reconciliation.methods.iter().map(|m| m.net).sum::<f64>();
+    let cash_sales = reconciliation.cash_sales;
+    let total_expenses = reconciliation.total_expenses;
+    let owner_drawings = reconciliation.owner_drawings;
     
-    // Calculate total sales during this shift (paid sales only)
-    let total_sales: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(total_amount), 0) FROM sales 
-         WHERE paid = 1 AND paid_at >= ?1 AND paid_at <= ?2",
-        params![opened_at, now],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
-    
-    // Calculate total expenses during this shift
-    let total_expenses: f64 = conn.query_row(
-        "SELECT COALESCE(SUM(amount), 0) FROM expenses 
-         WHERE date >= ?1 AND date <= ?2",
-        params![opened_at.split(' ').next().unwrap_or(&opened_at), now.split(' ').next().unwrap_or(&now)],
-        |row| row.get(0)
-    ).map_err(|e| e.to_string())?;
+    // Orders and check-ins entered outside business hours during this shift
+    let after_hours_count = count_after_hours(&conn, &opened_at, &now)?;
     
-    // Expected end cash = start cash + sales - expenses
-    let end_cash_expected = start_cash + total_sales - total_expenses;
+    // Expected end cash = start cash + cash sales - expenses - drawings
+    let end_cash_expected = reconciliation.expected_cash;
     let difference = end_cash_actual - end_cash_expected;
     
+    reconciliation.status = "closed".to_string();
+    reconciliation.closed_at = Some(now.clone());
+    reconciliation.counted_cash = Some(end_cash_actual);
+    reconciliation.difference = Some(difference);
+    reconciliation.denominations = counted.clone();
+    let reconciliation_json = serde_json::to_string(&reconciliation)?;
+    
     // Update shift
     conn.execute(
         "UPDATE shifts 
          SET closed_at = ?1, closed_by = ?2, end_cash_expected = ?3, end_cash_actual = ?4, 
-             difference = ?5, total_sales = ?6, total_expenses = ?7, status = 'closed', notes = ?8
-         WHERE id = ?9",
+             difference = ?5, total_sales = ?6, total_expenses = ?7, status = 'closed', notes = ?8,
+             denominations = ?9, owner_drawings = ?10, after_hours_count = ?11, cash_sales = ?12,
+             reconciliation = ?13
+         WHERE id = ?14",
         params![now, admin_id, end_cash_expected, end_cash_actual, difference, 
-                total_sales, total_expenses, notes, shift_id],
-    ).map_err(|e| e.to_string())?;
+                total_sales, total_expenses, notes, denominations_json, owner_drawings, after_hours_count, cash_sales,
+                reconciliation_json, shift_id],
+    )?;
     
     Ok(ShiftSummary {
         id: shift_id,
@@ -1970,23 +4506,28 @@ pub fn close_shift(
         difference,
         total_sales,
         total_expenses,
+        owner_drawings,
+        after_hours_count,
+        cash_sales,
         status: "closed".to_string(),
         notes,
+        denominations: counted,
     })
 }
 
 #[tauri::command]
-pub fn get_current_shift() -> Result<Option<ShiftSummary>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_current_shift() -> Result<Option<ShiftSummary>, AppError> {
+    let conn = get_db_connection()?;
     
     let mut stmt = conn.prepare(
-        "SELECT id, opened_at, closed_at, opened_by, closed_by, start_cash, 
+        "SELECT id, opened_at, closed_at, CAST(opened_by AS INTEGER), CAST(closed_by AS INTEGER), start_cash, 
                 end_cash_expected, end_cash_actual, difference, total_sales, 
-                total_expenses, status, notes
+                total_expenses, status, notes, denominations, owner_drawings,
+                COALESCE(after_hours_count, 0), COALESCE(cash_sales, 0)
          FROM shifts 
          WHERE status = 'open'
          LIMIT 1"
-    ).map_err(|e| e.to_string())?;
+    )?;
     
     let shift = stmt.query_row([], |row| {
         Ok(ShiftSummary {
@@ -2003,31 +4544,40 @@ pub fn get_current_shift() -> Result<Option<ShiftSummary>, String> {
             total_expenses: row.get::<_, Option<f64>>(10)?.unwrap_or(0.0),
             status: row.get(11)?,
             notes: row.get(12)?,
+            denominations: parse_shift_denominations(row.get(13)?),
+            owner_drawings: row.get::<_, Option<f64>>(14)?.unwrap_or(0.0),
+            after_hours_count: row.get(15)?,
+            cash_sales: row.get(16)?,
         })
     });
     
+    // The stored count is only written at close; an open shift counts live
     match shift {
-        Ok(s) => Ok(Some(s)),
+        Ok(mut s) => {
+            s.after_hours_count = count_after_hours(&conn, &s.opened_at, &get_current_timestamp())?;
+            Ok(Some(s))
+        }
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e.to_string()),
+        Err(e) => Err(e.into()),
     }
 }
 
 #[tauri::command]
-pub fn get_shift_history(limit: Option<i64>) -> Result<Vec<ShiftSummary>, String> {
-    let conn = get_db_connection().map_err(|e| e.to_string())?;
+pub fn get_shift_history(limit: Option<i64>) -> Result<Vec<ShiftSummary>, AppError> {
+    let conn = get_db_connection()?;
     
     let query = format!(
-        "SELECT id, opened_at, closed_at, opened_by, closed_by, start_cash, 
+        "SELECT id, opened_at, closed_at, CAST(opened_by AS INTEGER), CAST(closed_by AS INTEGER), start_cash, 
                 end_cash_expected, end_cash_actual, difference, total_sales, 
-                total_expenses, status, notes
+                total_expenses, status, notes, denominations, owner_drawings,
+                COALESCE(after_hours_count, 0), COALESCE(cash_sales, 0)
          FROM shifts 
          ORDER BY opened_at DESC
          LIMIT {}",
         limit.unwrap_or(50)
     );
     
-    let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(&query)?;
     
     let shifts = stmt.query_map([], |row| {
         Ok(ShiftSummary {
@@ -2044,8 +4594,850 @@ pub fn get_shift_history(limit: Option<i64>) -> Result<Vec<ShiftSummary>, String
             total_expenses: row.get::<_, Option<f64>>(10)?.unwrap_or(0.0),
             status: row.get(11)?,
             notes: row.get(12)?,
+            denominations: parse_shift_denominations(row.get(13)?),
+            owner_drawings: row.get::<_, Option<f64>>(14)?.unwrap_or(0.0),
+            after_hours_count: row.get(15)?,
+            cash_sales: row.get(16)?,
         })
-    }).map_err(|e| e.to_string())?;
+    })?;
     
-    shifts.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    shifts.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+}
+
+/// The reconciliation stored when the shift was closed, or the running one
+/// for the open shift. Shifts closed before payments were tied to shifts are
+/// rebuilt from their stored totals.
+#[tauri::command]
+pub fn get_shift_reconciliation(shift_id: i64) -> Result<ShiftReconciliation, AppError> {
+    let conn = get_db_connection()?;
+    let (opened_at, closed_at, start_cash, stored) = conn.query_row(
+        "SELECT opened_at, closed_at, start_cash, reconciliation FROM shifts WHERE id = ?1",
+        params![shift_id],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, f64>(2)?, row.get::<_, Option<String>>(3)?))
+    ).optional()?.ok_or_else(|| format!("Shift #{} not found", shift_id))?;
+    
+    if let Some(reconciliation) = stored.and_then(|json| serde_json::from_str(&json).ok()) {
+        return Ok(reconciliation);
+    }
+    let until = closed_at.clone().unwrap_or_else(get_current_timestamp);
+    let mut reconciliation = build_shift_reconciliation(&conn, shift_id, &opened_at, start_cash, &until)?;
+    if closed_at.is_some() {
+        let (counted_cash, difference, denominations) = conn.query_row(
+            "SELECT end_cash_actual, difference, denominations FROM shifts WHERE id = ?1",
+            params![shift_id],
+            |row| Ok((row.get(0)?, row.get(1)?, parse_shift_denominations(row.get(2)?)))
+        )?;
+        reconciliation.status = "closed".to_string();
+        reconciliation.closed_at = closed_at;
+        reconciliation.counted_cash = counted_cash;
+        reconciliation.difference = difference;
+        reconciliation.denominations = denominations;
+    }
+    Ok(reconciliation)
+}
+
+// ===== GUEST FEEDBACK =====
+
+const FEEDBACK_WINDOW_DAYS: i64 = 7;
+const FEEDBACK_COMMENT_MAX_CHARS: usize = 500;
+const FEEDBACK_RECENT_COMMENTS: i64 = 20;
+
+#[tauri::command]
+pub fn record_guest_feedback(guest_id: i64, rating: i32, comment: Option<String>) -> Result<i64, AppError> {
+    if !(1..=5).contains(&rating) {
+        return Err("Rating must be between 1 and 5".into());
+    }
+
+    // Comments are stored as plain text; renderers are responsible for escaping
+    let comment = comment
+        .map(|c| c.trim().chars().take(FEEDBACK_COMMENT_MAX_CHARS).collect::<String>())
+        .filter(|c| !c.is_empty());
+
+    let conn = get_db_connection()?;
+
+    let (status, check_out, room_id): (String, Option<String>, Option<i64>) = conn.query_row(
+        "SELECT status, check_out, room_id FROM customers WHERE id = ?1",
+        params![guest_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    ).map_err(|e| {
+        if e.to_string().contains("no rows") {
+            "Guest not found".to_string()
+        } else {
+            e.to_string()
+        }
+    })?;
+
+    if status != "checked_out" {
+        return Err("Feedback can only be recorded after checkout".into());
+    }
+
+    let check_out = check_out.ok_or("Guest has no check-out date")?;
+    let check_out_date = NaiveDate::parse_from_str(&check_out, "%Y-%m-%d")
+        .map_err(|_| "Invalid check-out date format")?;
+    // check_out is a local business date, so count the window from the business date too
+    let today = NaiveDate::parse_from_str(&business_date_today(), "%Y-%m-%d")
+        .map_err(|_| "Invalid business date")?;
+    let days_since_checkout = (today - check_out_date).num_days();
+    if !(0..=FEEDBACK_WINDOW_DAYS).contains(&days_since_checkout) {
+        return Err(format!(
+            "Feedback can only be recorded within {} days of checkout",
+            FEEDBACK_WINDOW_DAYS
+        ).into());
+    }
+
+    let already_recorded: bool = conn.query_row(
+        "SELECT 1 FROM guest_feedback WHERE guest_id = ?1",
+        params![guest_id],
+        |_| Ok(true)
+    ).unwrap_or(false);
+
+    if already_recorded {
+        return Err("Feedback has already been recorded for this stay".into());
+    }
+
+    // Attribute the stay to whichever shift was running on the check-out day
+    let shift: Option<(i64, i64)> = conn.query_row(
+        "SELECT id, opened_by FROM shifts
+         WHERE date(opened_at) <= ?1 AND (closed_at IS NULL OR date(closed_at) >= ?1)
+         ORDER BY opened_at DESC
+         LIMIT 1",
+        params![check_out],
+        |row| Ok((row.get(0)?, row.get(1)?))
+    ).ok();
+
+    conn.execute(
+        "INSERT INTO guest_feedback (guest_id, room_id, shift_id, staff_admin_id, rating, comment, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            guest_id,
+            room_id,
+            shift.map(|(id, _)| id),
+            shift.map(|(_, admin_id)| admin_id),
+            rating,
+            comment,
+            get_current_timestamp()
+        ],
+    ).map_err(|e| {
+        // The UNIQUE constraint also guards against a concurrent double submit
+        if e.to_string().contains("UNIQUE") {
+            "Feedback has already been recorded for this stay".to_string()
+        } else {
+            e.to_string()
+        }
+    })?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+#[tauri::command]
+pub async fn feedback_report(start_date: String, end_date: String) -> Result<FeedbackReport, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+
+        let (total_responses, average_rating): (i64, Option<f64>) = conn.query_row(
+            "SELECT COUNT(*), AVG(rating) FROM guest_feedback
+             WHERE date(created_at) >= ?1 AND date(created_at) <= ?2",
+            params![start_date, end_date],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT rating, COUNT(*) FROM guest_feedback
+             WHERE date(created_at) >= ?1 AND date(created_at) <= ?2
+             GROUP BY rating"
+        )?;
+
+        let counts = stmt.query_map(params![start_date, end_date], |row| {
+            Ok((row.get::<_, i32>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        // Always return all five buckets so charts don't have gaps
+        let distribution = (1..=5)
+            .map(|rating| RatingBucket {
+                rating,
+                count: counts.iter().find(|(r, _)| *r == rating).map(|(_, c)| *c).unwrap_or(0),
+            })
+            .collect();
+
+        let mut stmt = conn.prepare(
+            "SELECT f.guest_id, c.name, r.number, f.rating, f.comment, f.created_at
+             FROM guest_feedback f
+             JOIN customers c ON c.id = f.guest_id
+             LEFT JOIN resources r ON r.id = f.room_id
+             WHERE date(f.created_at) >= ?1 AND date(f.created_at) <= ?2
+               AND f.comment IS NOT NULL
+             ORDER BY f.created_at DESC
+             LIMIT ?3"
+        )?;
+
+        let recent_comments = stmt.query_map(params![start_date, end_date, FEEDBACK_RECENT_COMMENTS], |row| {
+            Ok(FeedbackComment {
+                guest_id: row.get(0)?,
+                guest_name: row.get(1)?,
+                room_number: row.get(2)?,
+                rating: row.get(3)?,
+                comment: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(FeedbackReport {
+            total_responses,
+            average_rating,
+            distribution,
+            recent_comments,
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn ratings_by_room_report() -> Result<Vec<RoomRatingRow>, AppError> {
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT r.id, r.number, COUNT(f.id), AVG(f.rating)
+             FROM guest_feedback f
+             JOIN resources r ON r.id = f.room_id
+             GROUP BY r.id, r.number
+             ORDER BY AVG(f.rating) DESC, r.number"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(RoomRatingRow {
+                room_id: row.get(0)?,
+                room_number: row.get(1)?,
+                responses: row.get(2)?,
+                average_rating: row.get(3)?,
+            })
+        })?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(AppError::from)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use crate::test_support::{
+        add_test_expense, add_test_room, check_in, count, days_from_today, order_item, room_type, same_amount, today, TestDb, TEST_RATE,
+    };
+    use crate::validation::{OCCUPANCY_CAP_EXCEEDED, OCCUPANCY_CAP_WARNING, ROOM_RESERVED};
+
+    /// Put a biryani with `prepared` portions on today's board.
+    fn biryani_special(prepared: i32) -> i64 {
+        let board = set_daily_specials(today(), vec![DailySpecialInput {
+            menu_item_id: None,
+            name: Some("Biryani".to_string()),
+            price: Some(8.0),
+            prepared_quantity: prepared,
+        }])
+        .unwrap();
+        board.first().expect("special not on today's board").id
+    }
+
+    fn order_special(special_id: i64) -> Result<i64, String> {
+        let item = OrderItemInput { special_id: Some(special_id), ..order_item("Biryani", 8.0, 1) };
+        insert_food_order(None, "walk_in".to_string(), None, vec![item], None, None, None)
+            .map(|(order_id, _)| order_id)
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn ordering_a_special_counts_down_its_portions() {
+        let _db = TestDb::new();
+        let special_id = biryani_special(2);
+        order_special(special_id).unwrap();
+        let remaining = get_daily_specials(None).unwrap().iter().find(|s| s.id == special_id).map(|s| s.remaining);
+        assert_eq!(remaining, Some(1), "expected 1 portion left after the first order");
+    }
+
+    #[test]
+    fn the_last_portion_goes_to_one_till_only() {
+        let _db = TestDb::new();
+        let special_id = biryani_special(1);
+        // Two tills ordering the last portion at the same moment
+        let start = Barrier::new(2);
+        let results: Vec<Result<i64, String>> = std::thread::scope(|threads| {
+            let racers: Vec<_> = (0..2)
+                .map(|_| {
+                    threads.spawn(|| {
+                        start.wait();
+                        order_special(special_id)
+                    })
+                })
+                .collect();
+            racers.into_iter().map(|racer| racer.join().unwrap()).collect()
+        });
+        let refused: Vec<&String> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+        assert_eq!(refused.len(), 1, "expected exactly one of the racing orders to fail, got {:?}", results);
+        assert!(
+            refused[0].contains(crate::validation::SPECIAL_SOLD_OUT) && refused[0].contains("0 portion(s) remaining"),
+            "losing order not refused as sold out: {}", refused[0]
+        );
+        let sold = count("SELECT sold_quantity FROM daily_specials WHERE id = ?1", special_id);
+        assert_eq!(sold, 1, "more portions sold than were prepared");
+    }
+
+    #[test]
+    fn yesterdays_board_rolls_off_and_its_leftovers_count_as_waste() {
+        let _db = TestDb::new();
+        let yesterday = (chrono::Local::now().date_naive() - chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+        let conn = get_db_connection().unwrap();
+        conn.execute(
+            "INSERT INTO daily_specials (special_date, name, price, prepared_quantity, sold_quantity) VALUES (?1, 'Stew', 6.0, 5, 3)",
+            params![yesterday],
+        )
+        .unwrap();
+        let stale_id = conn.last_insert_rowid();
+        assert!(get_daily_specials(None).unwrap().iter().all(|s| s.special_date == today()), "today's board lists an old special");
+        let rolled_off = count("SELECT COUNT(*) FROM daily_specials WHERE id = ?1 AND rolled_off = 1", stale_id);
+        assert_eq!(rolled_off, 1, "yesterday's special not rolled off");
+        assert!(order_special(stale_id).is_err_and(|e| e.contains("not available today")), "yesterday's special could still be ordered");
+        let report = tauri::async_runtime::block_on(specials_performance_report(yesterday.clone(), today())).unwrap();
+        let day = report.iter().find(|row| row.date == yesterday);
+        assert!(day.is_some_and(|row| row.prepared == 5 && row.sold == 3 && row.waste == 2), "yesterday's performance row wrong: {:?}", day);
+    }
+
+    /// A soup at 12.00 and a platter at 48.00 in the Soups category, with
+    /// 50.00 the most a price can reasonably be.
+    fn soup_and_platter() -> (i64, i64) {
+        let soup = add_menu_item("Soup".to_string(), 12.0, "Soups".to_string(), None, None, None, None).unwrap();
+        let platter = add_menu_item("Platter".to_string(), 48.0, "Soups".to_string(), None, None, None, None).unwrap();
+        let conn = get_db_connection().unwrap();
+        set_setting(&conn, "max_reasonable_amount", "50").unwrap();
+        (soup, platter)
+    }
+
+    fn menu_price(id: i64) -> f64 {
+        let conn = get_db_connection().unwrap();
+        conn.query_row("SELECT price FROM menu_items WHERE id = ?1", params![id], |row| row.get(0)).unwrap()
+    }
+
+    fn price_history(id: i64) -> i64 {
+        count("SELECT COUNT(*) FROM menu_price_history WHERE menu_item_id = ?1", id)
+    }
+
+    #[test]
+    fn bulk_price_changes_round_as_chosen() {
+        let _db = TestDb::new();
+        let (soup, _) = soup_and_platter();
+        for (rounding, expected) in [
+            (PriceRounding::None, 13.2),
+            (PriceRounding::Nearest5, 15.0),
+            (PriceRounding::Nearest10, 10.0),
+            (PriceRounding::UpTo10, 20.0),
+        ] {
+            let preview = update_menu_prices(Some("Soups".to_string()), PriceAdjustment::Percent(10.0), Some(rounding), true).unwrap();
+            let new_price = preview.changes.iter().find(|c| c.id == soup).map(|c| c.new_price);
+            assert!(
+                new_price.is_some_and(|p| same_amount(p, expected)),
+                "12.00 + 10% rounded {:?} came to {:?}, expected {:.2}", rounding, new_price, expected
+            );
+        }
+    }
+
+    #[test]
+    fn a_bulk_price_preview_changes_nothing() {
+        let _db = TestDb::new();
+        let (soup, platter) = soup_and_platter();
+        let preview = update_menu_prices(Some("Soups".to_string()), PriceAdjustment::Percent(10.0), None, true).unwrap();
+        assert!(preview.dry_run, "preview not reported as a dry run");
+        assert!(
+            preview.skipped.iter().any(|c| c.id == platter && c.skipped_reason.as_deref().is_some_and(|r| r.contains("50.00"))),
+            "platter going over max_reasonable_amount not skipped: {:?}", preview.skipped
+        );
+        assert!(!preview.changes.iter().any(|c| c.id == platter), "skipped platter also listed as a change");
+        assert!(same_amount(menu_price(soup), 12.0) && price_history(soup) == 0, "dry run changed a price or wrote history");
+    }
+
+    #[test]
+    fn applied_bulk_price_changes_are_kept_in_the_price_history() {
+        let _db = TestDb::new();
+        let (soup, platter) = soup_and_platter();
+        let applied = update_menu_prices(Some("Soups".to_string()), PriceAdjustment::Percent(10.0), None, false).unwrap();
+        assert_eq!(applied.changes.len(), 1, "expected one change, got {:?}", applied.changes);
+        assert!(same_amount(menu_price(soup), 13.2), "soup price not updated to 13.20");
+        assert!(same_amount(menu_price(platter), 48.0), "skipped platter price changed anyway");
+        assert!(price_history(soup) == 1 && price_history(platter) == 0, "expected one history row, for the soup only");
+        let conn = get_db_connection().unwrap();
+        let (old_price, new_price): (f64, f64) = conn
+            .query_row("SELECT old_price, new_price FROM menu_price_history WHERE menu_item_id = ?1", params![soup], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert!(same_amount(old_price, 12.0) && same_amount(new_price, 13.2), "history row records {} -> {}", old_price, new_price);
+    }
+
+    /// A steak that must be ordered with a doneness and may be with a spice.
+    fn steak_with_attributes() -> i64 {
+        let steak = add_menu_item("Steak".to_string(), 12.0, "Grill".to_string(), None, None, None, None).unwrap();
+        set_menu_item_attributes(steak, vec![
+            MenuItemAttribute { name: "Doneness".to_string(), values: vec!["Rare".to_string(), "Well done".to_string()], required: true },
+            MenuItemAttribute { name: "Spice".to_string(), values: vec!["Mild".to_string(), "Hot".to_string()], required: false },
+        ])
+        .unwrap();
+        steak
+    }
+
+    fn order_steak(steak: i64, selected: &[(&str, &str)]) -> Result<i64, String> {
+        let item = OrderItemInput {
+            menu_item_id: Some(steak),
+            selected_attributes: selected.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect(),
+            ..order_item("Steak", 12.0, 1)
+        };
+        insert_food_order(None, "walk_in".to_string(), None, vec![item], None, None, None)
+            .map(|(order_id, _)| order_id)
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn orders_must_pick_valid_attribute_values() {
+        let _db = TestDb::new();
+        let steak = steak_with_attributes();
+        let missing = order_steak(steak, &[("Spice", "Hot")]);
+        assert!(missing.is_err_and(|e| e.contains("'Doneness' is required")), "order without a required attribute accepted");
+        let invalid = order_steak(steak, &[("Doneness", "Medium")]);
+        assert!(
+            invalid.is_err_and(|e| e.contains("Invalid value 'Medium'") && e.contains("Rare, Well done")),
+            "invalid attribute value accepted or not explained"
+        );
+        let unknown = order_steak(steak, &[("Doneness", "Rare"), ("Sauce", "Pepper")]);
+        assert!(unknown.is_err_and(|e| e.contains("Unknown attribute 'Sauce'")), "unknown attribute accepted");
+    }
+
+    #[test]
+    fn attribute_choices_are_stored_as_defined_and_printed_for_the_kitchen() {
+        let _db = TestDb::new();
+        let steak = steak_with_attributes();
+        let order_id = order_steak(steak, &[("doneness", "well DONE")]).unwrap();
+        let details = get_order_details(order_id).unwrap();
+        let selected = details.items.first().map(|line| line.selected_attributes.clone()).unwrap_or_default();
+        assert_eq!(selected, vec![("Doneness".to_string(), "Well done".to_string())], "selection not stored as defined");
+        let ticket = crate::print_templates::build_kitchen_ticket_html(order_id).unwrap();
+        assert!(ticket.contains("Well done"), "kitchen ticket does not show the selected doneness");
+    }
+
+    /// Put rooms ST-801 and ST-802 on floors 1 and 2 of the east wing.
+    fn east_wing_rooms() -> (i64, i64) {
+        crate::room_types::add_room_type(room_type("Standard", None)).unwrap();
+        for (number, floor) in [("ST-801", 1), ("ST-802", 2)] {
+            add_room(number.to_string(), "Standard".to_string(), TEST_RATE, Some(floor), Some(" East wing ".to_string()), None).unwrap();
+        }
+        let rooms = get_rooms(None, None).unwrap();
+        let id = |number: &str| rooms.iter().find(|r| r.number == number).expect("room not listed").id;
+        (id("ST-801"), id("ST-802"))
+    }
+
+    #[test]
+    fn rooms_can_be_listed_by_floor_and_building() {
+        let _db = TestDb::new();
+        let (downstairs, _) = east_wing_rooms();
+        let first_floor = get_rooms(Some(1), Some("east WING".to_string())).unwrap();
+        assert_eq!(first_floor.iter().map(|r| r.id).collect::<Vec<_>>(), vec![downstairs], "wrong rooms on the first floor of the east wing");
+        assert_eq!(first_floor[0].building.as_deref(), Some("East wing"), "building name not trimmed");
+    }
+
+    #[test]
+    fn floor_occupancy_counts_each_floor_of_a_building() {
+        let _db = TestDb::new();
+        let (_, upstairs) = east_wing_rooms();
+        check_in("Upstairs Guest", Some(upstairs));
+        let floors = get_floor_occupancy().unwrap();
+        let east = |floor: i64| {
+            floors.iter()
+                .find(|f| f.building.as_deref() == Some("East wing") && f.floor == Some(floor))
+                .expect("floor missing from the summary")
+        };
+        assert_eq!((east(1).total_rooms, east(1).occupied_rooms), (1, 0), "east wing floor 1 summary is wrong");
+        assert_eq!(east(2).occupied_rooms, 1, "east wing floor 2 has no occupied room");
+        assert!(same_amount(east(2).occupancy_percent, 100.0), "east wing floor 2 is not shown fully occupied");
+    }
+
+    #[test]
+    fn a_blank_building_takes_the_room_out_of_its_wing() {
+        let _db = TestDb::new();
+        let (_, upstairs) = east_wing_rooms();
+        update_room(upstairs, None, None, None, None, Some(String::new()), None).unwrap();
+        let wing = get_rooms(None, Some("East wing".to_string())).unwrap();
+        assert_eq!(wing.len(), 1, "a blank building did not take the room out of the wing");
+        assert!(wing.iter().all(|r| r.id != upstairs), "the room is still listed in the east wing");
+    }
+
+    /// Check `adults` into `room_id` from today until tomorrow.
+    fn check_in_party(name: &str, room_id: i64, adults: i64, override_warning: Option<bool>) -> Result<i64, AppError> {
+        add_guest(
+            name.to_string(), None, Some(room_id), today(), Some(days_from_today(1)), TEST_RATE,
+            Some(adults), Some(0), override_warning, None, None, None,
+        )
+    }
+
+    fn persons_today() -> i64 {
+        persons_in_house(&get_db_connection().unwrap(), &today(), None).unwrap()
+    }
+
+    #[test]
+    fn a_hard_cap_refuses_check_ins_over_it() {
+        let _db = TestDb::new();
+        let room_id = add_test_room("ST-901");
+        set_occupancy_cap(Some(2), "hard".to_string()).unwrap();
+        let over = check_in_party("Cap Trio", room_id, 3, None).unwrap_err().to_string();
+        assert!(over.contains(OCCUPANCY_CAP_EXCEEDED), "check-in over the cap not refused: {}", over);
+        assert!(over.contains("0 expected + 3 arriving exceeds the cap of 2"), "refusal does not give the count and cap: {}", over);
+        check_in_party("Cap Pair", room_id, 2, None).unwrap();
+        assert_eq!(persons_today(), 2, "property not exactly at its cap after the pair checked in");
+    }
+
+    #[test]
+    fn a_soft_cap_warns_until_overridden() {
+        let _db = TestDb::new();
+        let (first, second) = (add_test_room("ST-901"), add_test_room("ST-902"));
+        set_occupancy_cap(Some(2), "soft".to_string()).unwrap();
+        check_in_party("Cap Pair", first, 2, None).unwrap();
+        let warned = check_in_party("Cap Single", second, 1, None).unwrap_err().to_string();
+        assert!(warned.contains(OCCUPANCY_CAP_WARNING), "soft cap did not warn about a check-in over the cap: {}", warned);
+        check_in_party("Cap Single", second, 1, Some(true)).unwrap();
+        assert_eq!(persons_today(), 3, "overridden check-in not counted in house");
+    }
+
+    #[test]
+    fn reservations_for_other_nights_do_not_count_against_today() {
+        let _db = TestDb::new();
+        let (first, second) = (add_test_room("ST-901"), add_test_room("ST-902"));
+        // Booked before the cap is set, and far above it, but for later in the week
+        add_reservation(second, "Cap Coach Party".to_string(), None, days_from_today(3), days_from_today(5), None, Some(7), None, None, None)
+            .unwrap();
+        set_occupancy_cap(Some(2), "hard".to_string()).unwrap();
+        check_in_party("Cap Pair", first, 2, None).unwrap();
+    }
+
+    #[test]
+    fn booked_reservations_count_towards_the_cap() {
+        let _db = TestDb::new();
+        let (first, second) = (add_test_room("ST-901"), add_test_room("ST-902"));
+        set_occupancy_cap(Some(2), "hard".to_string()).unwrap();
+        add_reservation(first, "Cap Pair".to_string(), None, days_from_today(10), days_from_today(12), None, Some(2), None, None, None)
+            .unwrap();
+        let booked = persons_in_house(&get_db_connection().unwrap(), &days_from_today(11), None).unwrap();
+        assert_eq!(booked, 2, "the booked pair is not expected in house");
+        let over = add_reservation(second, "Cap Single".to_string(), None, days_from_today(11), days_from_today(13), None, Some(1), None, None, None);
+        assert!(
+            over.is_err_and(|e| e.to_string().contains(OCCUPANCY_CAP_EXCEEDED)),
+            "a reservation took the property over its cap on a night already fully booked"
+        );
+    }
+
+    /// A guest in ST-401 booked until tomorrow; returns the guest and room ids.
+    fn one_night_stay() -> (i64, i64) {
+        let room_id = add_test_room("ST-401");
+        let guest_id = add_guest(
+            "Extending Guest".to_string(), None, Some(room_id), today(), Some(days_from_today(1)), TEST_RATE,
+            Some(1), Some(0), None, Some(true), None, None,
+        )
+        .unwrap();
+        (guest_id, room_id)
+    }
+
+    #[test]
+    fn extending_a_stay_charges_the_added_nights() {
+        let _db = TestDb::new();
+        let (guest_id, _) = one_night_stay();
+        let extended = extend_stay(guest_id, days_from_today(3), Some(true), None, None).unwrap();
+        assert_eq!((extended.nights, extended.added_nights), (3, 2), "extension covers the wrong nights");
+        assert!(same_amount(extended.added_amount, 2.0 * TEST_RATE), "extension added {:.2} to the room charge", extended.added_amount);
+    }
+
+    #[test]
+    fn a_stay_cannot_be_extended_into_a_reservation() {
+        let _db = TestDb::new();
+        let (guest_id, room_id) = one_night_stay();
+        add_reservation(room_id, "Next Guest".to_string(), None, days_from_today(3), days_from_today(5), None, None, None, None, None)
+            .unwrap();
+        extend_stay(guest_id, days_from_today(3), Some(true), None, None).unwrap();
+        let clash = extend_stay(guest_id, days_from_today(4), Some(true), None, None);
+        assert!(clash.is_err_and(|e| e.to_string().contains(ROOM_RESERVED)), "a stay was extended into a booked reservation");
+    }
+
+    #[test]
+    fn a_stay_cannot_be_extended_past_the_occupancy_cap() {
+        let _db = TestDb::new();
+        let (guest_id, _) = one_night_stay();
+        // Another room is booked for the added night, which fills the property
+        let other_room = add_test_room("ST-402");
+        add_reservation(other_room, "Cap Guest".to_string(), None, days_from_today(3), days_from_today(5), None, None, None, None, None)
+            .unwrap();
+        set_occupancy_cap(Some(1), "hard".to_string()).unwrap();
+        let over_cap = extend_stay(guest_id, days_from_today(4), Some(true), None, None);
+        assert!(
+            over_cap.is_err_and(|e| e.to_string().contains(OCCUPANCY_CAP_EXCEEDED)),
+            "a stay was extended past the occupancy cap"
+        );
+    }
+
+    #[test]
+    fn an_overpaid_checkout_refunds_the_deposit() {
+        let _db = TestDb::new();
+        let stay = check_in("Early payer", None);
+        crate::folio::add_guest_deposit(stay, TEST_RATE * 10.0, "card".to_string(), None).unwrap();
+        let totals = checkout_guest(stay, None, None, Some("cash".to_string()), None, None).unwrap();
+        assert!(totals.balance_due < 0.0, "deposit did not cover the bill: {:?}", totals);
+
+        let conn = get_db_connection().unwrap();
+        let kept: f64 = conn
+            .query_row("SELECT COALESCE(SUM(amount), 0) FROM payments WHERE guest_id = ?1", params![stay], |row| row.get(0))
+            .unwrap();
+        assert!(same_amount(kept, totals.grand_total), "kept {:.2} for a bill of {:.2}", kept, totals.grand_total);
+        let refunds = count(
+            "SELECT COUNT(*) FROM payments WHERE guest_id = ?1 AND amount < 0 AND refund_of IS NOT NULL AND method = 'card'",
+            stay,
+        );
+        assert_eq!(refunds, 1, "overpayment not refunded to the card it was taken on");
+    }
+
+    /// A walk-in who checked out `days` days ago.
+    fn checked_out_days_ago(name: &str, days: i64) -> i64 {
+        let stay = check_in(name, None);
+        checkout_guest(stay, None, None, Some("cash".to_string()), None, None).unwrap();
+        let conn = get_db_connection().unwrap();
+        conn.execute("UPDATE customers SET check_out = ?1 WHERE id = ?2", params![days_from_today(-days), stay]).unwrap();
+        stay
+    }
+
+    #[test]
+    fn feedback_is_taken_once_per_stay() {
+        let _db = TestDb::new();
+        let stay = checked_out_days_ago("Reviewer", 1);
+        record_guest_feedback(stay, 4, Some("Quiet <b>room</b>".to_string())).unwrap();
+        let again = record_guest_feedback(stay, 5, None);
+        assert!(again.is_err_and(|e| e.to_string().contains("already been recorded")), "second feedback for one stay was accepted");
+        let report = tauri::async_runtime::block_on(feedback_report("2000-01-01".to_string(), "2999-12-31".to_string())).unwrap();
+        assert_eq!(report.total_responses, 1, "feedback report counted the refused second response");
+        assert!(report.average_rating.is_some_and(|avg| same_amount(avg, 4.0)), "average rating {:?}, expected 4", report.average_rating);
+    }
+
+    #[test]
+    fn feedback_is_taken_up_to_seven_days_after_checkout() {
+        let _db = TestDb::new();
+        let last_day = checked_out_days_ago("Reviewer", 7);
+        assert!(record_guest_feedback(last_day, 4, None).is_ok(), "feedback refused on the window's last day");
+        let too_late = checked_out_days_ago("Late Reviewer", 8);
+        assert!(record_guest_feedback(too_late, 3, None).is_err(), "feedback accepted 8 days after checkout");
+    }
+
+    fn counted(pairs: &[(&str, i64)]) -> Option<std::collections::HashMap<String, i64>> {
+        Some(pairs.iter().map(|(value, count)| (value.to_string(), *count)).collect())
+    }
+
+    #[test]
+    fn a_drawer_count_must_use_known_denominations() {
+        let _db = TestDb::new();
+        assert!(get_denominations().unwrap().contains(&20.0), "USD denominations do not include 20");
+        let drawer = open_shift(1, 50.0).unwrap();
+        let unknown = close_shift(drawer, 1, None, None, counted(&[("20", 2), ("3", 1)]));
+        assert!(unknown.is_err_and(|e| e.to_string().contains("Unknown denomination '3'")), "unknown denomination accepted");
+        let negative = close_shift(drawer, 1, None, None, counted(&[("20", -1)]));
+        assert!(negative.is_err_and(|e| e.to_string().contains("cannot be negative")), "negative count accepted");
+        let mismatched = close_shift(drawer, 1, Some(55.0), None, counted(&[("20", 2), ("10", 1)]));
+        assert!(
+            mismatched.is_err_and(|e| e.to_string().contains("is 50.00 but 55.00 was entered")),
+            "a total disagreeing with the breakdown was accepted or did not give the counted figure"
+        );
+    }
+
+    #[test]
+    fn a_counted_drawer_keeps_its_breakdown() {
+        let _db = TestDb::new();
+        let drawer = open_shift(1, 50.0).unwrap();
+        let closed = close_shift(drawer, 1, Some(50.0), None, counted(&[("20", 2), ("10", 1)])).unwrap();
+        assert!(
+            same_amount(closed.end_cash_actual, 50.0) && same_amount(closed.difference, 50.0 - closed.end_cash_expected),
+            "drawer counted {:.2}, off by {:.2}", closed.end_cash_actual, closed.difference
+        );
+        let breakdown = get_shift_reconciliation(drawer).unwrap().denominations.unwrap_or_default();
+        assert!(
+            breakdown.iter().any(|d| same_amount(d.value, 20.0) && d.count == 2) && breakdown.iter().any(|d| same_amount(d.value, 10.0) && d.count == 1),
+            "stored reconciliation does not keep the breakdown: {:?}", breakdown
+        );
+    }
+
+    /// Record a 12.00 drawing of the owner's groceries from the drawer.
+    fn owners_groceries() -> i64 {
+        add_test_expense("Supplies", "Cleaning", 15.0);
+        add_expense(today(), "Supplies".to_string(), Some("Owner's groceries".to_string()), 12.0, Some(true), None, None, None).unwrap()
+    }
+
+    #[test]
+    fn a_drawing_leaves_the_drawer_without_counting_as_an_expense() {
+        let _db = TestDb::new();
+        let shift_id = open_shift(1, 50.0).unwrap();
+        let drawer = get_shift_reconciliation(shift_id).unwrap();
+        let stats = tauri::async_runtime::block_on(dashboard_stats()).unwrap();
+        owners_groceries();
+        let after = get_shift_reconciliation(shift_id).unwrap();
+        assert!(
+            same_amount(after.expected_cash, drawer.expected_cash - 27.0) && same_amount(after.owner_drawings, drawer.owner_drawings + 12.0),
+            "drawer expects {:.2} with {:.2} drawn, was {:.2} with {:.2}",
+            after.expected_cash, after.owner_drawings, drawer.expected_cash, drawer.owner_drawings
+        );
+        assert!(same_amount(after.total_expenses, drawer.total_expenses + 15.0), "the drawing counted as a shift expense");
+        let after_stats = tauri::async_runtime::block_on(dashboard_stats()).unwrap();
+        assert!(
+            same_amount(after_stats.total_expenses, stats.total_expenses + 15.0) && same_amount(after_stats.profit_loss, stats.profit_loss - 15.0),
+            "the drawing moved expenses from {:.2} to {:.2}", stats.total_expenses, after_stats.total_expenses
+        );
+        assert!(same_amount(after_stats.owner_drawings, stats.owner_drawings + 12.0), "dashboard drawings are {:.2}", after_stats.owner_drawings);
+    }
+
+    #[test]
+    fn the_drawings_report_lists_each_drawing_with_a_running_total() {
+        let _db = TestDb::new();
+        let drawing = owners_groceries();
+        let report = tauri::async_runtime::block_on(drawings_report(today(), today())).unwrap();
+        assert_eq!(report.rows.len(), 1, "the ordinary expense is listed as a drawing");
+        let row = report.rows.iter().find(|r| r.id == drawing);
+        assert!(row.is_some_and(|r| same_amount(r.amount, 12.0)), "drawing not listed in the drawings report");
+        assert!(
+            report.rows.last().is_some_and(|r| same_amount(r.running_total, report.total)),
+            "running total does not end on the report total {:.2}", report.total
+        );
+    }
+
+    #[test]
+    fn downsampling_keeps_the_endpoints_and_the_total() {
+        let values: Vec<f64> = (0..1000).map(|i| (i % 7) as f64 * 10.0 + i as f64).collect();
+        let series = ChartSeries { labels: (0..1000).map(|i| i.to_string()).collect(), values: values.clone() };
+        let sampled = downsample_series(series, MAX_CHART_POINTS);
+        assert!(
+            sampled.values.len() <= MAX_CHART_POINTS && sampled.labels.len() == sampled.values.len(),
+            "downsampled to {} values and {} labels", sampled.values.len(), sampled.labels.len()
+        );
+        assert_eq!(sampled.labels.first().map(String::as_str), Some("0"), "downsampling moved the first label");
+        assert_eq!(sampled.labels.last().map(String::as_str), Some("999"), "downsampling moved the last label");
+        assert_eq!((sampled.values[0], sampled.values[sampled.values.len() - 1]), (values[0], values[999]), "downsampling moved the endpoints");
+        // Bucket averaging keeps, bucket for bucket, the total
+        let interior: f64 = values[1..999].iter().sum();
+        let buckets = (sampled.values.len() - 2) as f64;
+        let estimate = sampled.values[1..sampled.values.len() - 1].iter().sum::<f64>() * 998.0 / buckets;
+        assert!((estimate - interior).abs() <= interior * 0.01, "downsampled total {:.0} strays from {:.0}", estimate, interior);
+    }
+
+    /// A guest in ST-501 who ordered 4 teas, and a 15.00 supplies expense, all today.
+    fn a_day_of_business() {
+        let guest_id = check_in("Graph Guest", Some(add_test_room("ST-501")));
+        insert_food_order(Some(guest_id), "guest".to_string(), None, vec![order_item("Tea", 2.5, 4)], None, None, None).unwrap();
+        add_test_expense("Supplies", "Cleaning", 15.0);
+    }
+
+    #[test]
+    fn a_long_occupancy_line_is_sampled_down_and_ends_on_today() {
+        let _db = TestDb::new();
+        a_day_of_business();
+        let start = days_from_today(-730);
+        let long = tauri::async_runtime::block_on(dashboard_graphs(start.clone(), today())).unwrap();
+        let short = tauri::async_runtime::block_on(dashboard_graphs(today(), today())).unwrap();
+        assert!(long.occupancy.values.len() <= MAX_CHART_POINTS, "{} occupancy points for two years", long.occupancy.values.len());
+        assert!(
+            long.occupancy.labels.first() == Some(&start) && long.occupancy.labels.last() == Some(&today()),
+            "long occupancy line runs {:?} to {:?}", long.occupancy.labels.first(), long.occupancy.labels.last()
+        );
+        assert!(
+            long.occupancy.values.last() == short.occupancy.values.first() && short.occupancy.values.first().is_some_and(|v| *v > 0.0),
+            "today's occupancy is {:?} in the long range and {:?} alone", long.occupancy.values.last(), short.occupancy.values.first()
+        );
+    }
+
+    #[test]
+    fn the_dashboard_graphs_match_the_individual_reports() {
+        let _db = TestDb::new();
+        a_day_of_business();
+        let graphs = tauri::async_runtime::block_on(dashboard_graphs(today(), today())).unwrap();
+        let revenue = tauri::async_runtime::block_on(crate::timeseries::revenue_timeseries("monthly".to_string(), today(), today())).unwrap();
+        let spending = tauri::async_runtime::block_on(crate::timeseries::expense_timeseries("monthly".to_string(), today(), today())).unwrap();
+        assert_eq!(graphs.monthly_income.values, revenue.total_income.values, "dashboard income differs from revenue_timeseries");
+        assert_eq!(graphs.monthly_expenses.values, spending.expenses.values, "dashboard expenses differ from expense_timeseries");
+        let by_category: f64 = graphs.expenses_by_category.values.iter().sum();
+        assert!(
+            same_amount(by_category, spending.expenses.values.iter().sum()),
+            "expense categories add up to {:.2}, not the {:?} spent", by_category, spending.expenses.values
+        );
+        let tea = graphs.top_items.labels.iter().position(|label| label == "Tea");
+        assert!(tea.is_some_and(|i| same_amount(graphs.top_items.values[i], 4.0)), "top items {:?} do not show the 4 teas", graphs.top_items);
+    }
+
+    #[test]
+    fn a_room_with_an_upcoming_booking_cannot_be_deleted() {
+        let _db = TestDb::new();
+        let room_id = add_test_room("ST-601");
+        let reservation_id = add_reservation(
+            room_id, "Booked Guest".to_string(), None, days_from_today(2), days_from_today(4), None, None, None, None, None,
+        )
+        .unwrap();
+        let refused = delete_room(room_id, None);
+        assert!(refused.is_err_and(|e| e.code() == ROOM_RESERVED), "a room was deleted from under a booking");
+        cancel_reservation(reservation_id).unwrap();
+        delete_room(room_id, None).unwrap();
+        assert_eq!(count("SELECT COUNT(*) FROM reservations WHERE room_id = ?1", room_id), 0, "the cancelled booking outlived its room");
+    }
+
+    #[test]
+    fn a_walk_in_cannot_take_a_room_booked_for_tonight() {
+        let _db = TestDb::new();
+        let room_id = add_test_room("ST-701");
+        add_reservation(room_id, "Booked Guest".to_string(), None, today(), days_from_today(2), None, None, None, None, None).unwrap();
+        let walk_in = add_guest(
+            "Walk-in Guest".to_string(), None, Some(room_id), today(), None, TEST_RATE,
+            Some(1), Some(0), None, None, None, None,
+        );
+        assert!(walk_in.is_err_and(|e| e.code() == ROOM_RESERVED), "a walk-in was checked into a room booked for tonight");
+        let occupied = count("SELECT COUNT(*) FROM resources WHERE id = ?1 AND is_occupied = 1", room_id);
+        assert_eq!(occupied, 0, "the refused walk-in left the room occupied");
+    }
+
+    #[test]
+    fn checking_in_a_booking_uses_its_own_reservation() {
+        let _db = TestDb::new();
+        let room_id = add_test_room("ST-702");
+        let reservation_id =
+            add_reservation(room_id, "Booked Guest".to_string(), None, today(), days_from_today(2), None, None, None, None, None).unwrap();
+        let guest_id = convert_reservation_to_checkin(reservation_id, None, None).unwrap();
+        let conn = get_db_connection().unwrap();
+        let (status, linked): (String, Option<i64>) = conn
+            .query_row("SELECT status, guest_id FROM reservations WHERE id = ?1", params![reservation_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((status.as_str(), linked), ("checked_in", Some(guest_id)), "the reservation was not marked as used by the new stay");
+    }
+
+    #[test]
+    fn a_walk_in_order_adds_tax_when_prices_exclude_it() {
+        let _db = TestDb::new();
+        let conn = get_db_connection().unwrap();
+        crate::taxes::set_single_rate(&conn, 10.0).unwrap();
+        let (order_id, _) = insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Tea", 10.0, 2)], None, None, None).unwrap();
+        let (tax, total): (f64, f64) = conn
+            .query_row("SELECT tax_amount, total_amount FROM sales WHERE id = ?1", params![order_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert!(same_amount(tax, 2.0) && same_amount(total, 22.0), "walk-in order charged {:.2} tax for {:.2}, expected 2.00 for 22.00", tax, total);
+        assert_eq!(count("SELECT COUNT(*) FROM tax_charges WHERE order_id = ?1", order_id), 1, "the order's tax was not recorded");
+        let receipt = crate::print_templates::build_order_receipt_html(order_id, None).unwrap();
+        assert!(receipt.contains("(10.0%)") && receipt.contains("$22.00"), "receipt does not show the tax added to the order");
+    }
+
+    #[test]
+    fn only_a_manager_or_admin_can_refund_a_payment() {
+        let _db = TestDb::new();
+        let (order_id, _) = insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Tea", 10.0, 1)], None, None, None).unwrap();
+        mark_order_paid(order_id, None).unwrap();
+        let payment_id = get_order_payments(order_id).unwrap()[0].id;
+        let refund = |session_token: Option<String>| refund_payment(payment_id, 1.0, "Cold tea".to_string(), session_token);
+        assert!(refund(None).is_err(), "a payment was refunded without signing in");
+        let staff = refund(Some(crate::test_support::session_as("staff")));
+        assert!(staff.is_err_and(|e| e.code() == crate::validation::UNAUTHORIZED), "a staff member refunded a payment");
+        let refunded = refund(Some(crate::test_support::admin_session())).unwrap();
+        assert_eq!(refunded.refunded_by, crate::test_support::ADMIN, "refund not put down to the admin");
+    }
 }
@@ -0,0 +1,256 @@
+use tauri::command;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{GuestCharge, StayFeeRule, StayFeeRules};
+use crate::repository::{get_parsed_setting, get_setting, get_setting_or, set_or_clear_setting, set_setting};
+
+const FEE_TYPES: &[&str] = &["flat", "percent"];
+// Recorded as the creator of fees posted by the cut-off rules rather than by staff
+const STAY_FEE_ACTOR: &str = "stay_fee_rules";
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| format!("Invalid time '{}'. Expected HH:MM", value))
+}
+
+fn load_rule(conn: &Connection, prefix: &str) -> Result<Option<StayFeeRule>, String> {
+    let Some(fee_type) = get_setting(conn, &format!("{}_fee_type", prefix))? else {
+        return Ok(None);
+    };
+    let amount = get_parsed_setting::<f64>(conn, &format!("{}_fee", prefix))?.unwrap_or(0.0);
+    Ok(Some(StayFeeRule { fee_type, amount }).filter(|rule| rule.amount > 0.0))
+}
+
+fn save_rule(conn: &Connection, prefix: &str, rule: Option<&StayFeeRule>) -> Result<(), String> {
+    set_or_clear_setting(conn, &format!("{}_fee_type", prefix), rule.map(|r| r.fee_type.as_str()))?;
+    set_or_clear_setting(conn, &format!("{}_fee", prefix), rule.map(|r| r.amount.to_string()).as_deref())
+}
+
+fn load_rules(conn: &Connection) -> Result<StayFeeRules, String> {
+    Ok(StayFeeRules {
+        check_in_time: get_setting_or(conn, "check_in_time", "14:00")?,
+        check_out_time: get_setting_or(conn, "check_out_time", "12:00")?,
+        early_check_in: load_rule(conn, "early_check_in")?,
+        late_check_out: load_rule(conn, "late_check_out")?,
+    })
+}
+
+/// `rule`'s fee as an unsaved `category` charge on the guest's folio, or None
+/// when the rules already posted one. Percent fees are taken of the stay's
+/// daily rate.
+fn fee_charge(conn: &Connection, guest_id: i64, category: &str, description: String, rule: &StayFeeRule) -> Result<Option<GuestCharge>, String> {
+    let already: bool = conn
+        .query_row(
+            "SELECT 1 FROM guest_charges WHERE guest_id = ?1 AND category = ?2 AND created_by = ?3",
+            params![guest_id, category, STAY_FEE_ACTOR],
+            |_| Ok(true),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .unwrap_or(false);
+    if already {
+        return Ok(None);
+    }
+
+    let amount = if rule.fee_type == "percent" {
+        let daily_rate: f64 = conn
+            .query_row("SELECT daily_rate FROM customers WHERE id = ?1", params![guest_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        (daily_rate * rule.amount / 100.0 * 100.0).round() / 100.0
+    } else {
+        rule.amount
+    };
+    Ok(Some(GuestCharge {
+        id: 0,
+        guest_id,
+        category: category.to_string(),
+        description,
+        quantity: 1,
+        unit_price: amount,
+        amount,
+        charged_on: crate::simple_commands::business_date_today(),
+        created_by: Some(STAY_FEE_ACTOR.to_string()),
+        created_at: get_current_timestamp(),
+    }))
+}
+
+fn insert_fee(conn: &Connection, charge: &GuestCharge) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO guest_charges (guest_id, category, description, quantity, unit_price, amount, charged_on, created_by, created_at)
+         VALUES (?1, ?2, ?3, 1, ?4, ?4, ?5, ?6, ?7)",
+        params![
+            charge.guest_id,
+            charge.category,
+            charge.description,
+            charge.amount,
+            charge.charged_on,
+            charge.created_by,
+            charge.created_at
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Charge the early check-in fee when a room guest is checked in for today
+/// before the check-in time. Back-dated and future check-ins aren't early.
+pub(crate) fn post_early_check_in_fee(conn: &Connection, guest_id: i64, at: NaiveDateTime) -> Result<(), String> {
+    let rules = load_rules(conn)?;
+    let Some(rule) = &rules.early_check_in else {
+        return Ok(());
+    };
+    let (room_id, check_in): (Option<i64>, String) = conn
+        .query_row("SELECT room_id, check_in FROM customers WHERE id = ?1", params![guest_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    if room_id.is_none() || check_in != at.date().format("%Y-%m-%d").to_string() || at.time() >= parse_time(&rules.check_in_time)? {
+        return Ok(());
+    }
+    let description = format!("Early check-in (before {})", rules.check_in_time);
+    match fee_charge(conn, guest_id, "early_checkin", description, rule)? {
+        Some(charge) => insert_fee(conn, &charge),
+        None => Ok(()),
+    }
+}
+
+/// The late check-out fee a room guest leaving at `at` owes and hasn't been
+/// charged yet, unsaved. A guest is late when leaving on the scheduled
+/// check-out date after the check-out time, or any time after that date; a
+/// stay with no date set counts as due out today.
+pub(crate) fn pending_late_check_out_fee(conn: &Connection, guest_id: i64, at: NaiveDateTime) -> Result<Option<GuestCharge>, String> {
+    let rules = load_rules(conn)?;
+    let Some(rule) = &rules.late_check_out else {
+        return Ok(None);
+    };
+    let (room_id, check_out): (Option<i64>, Option<String>) = conn
+        .query_row("SELECT room_id, check_out FROM customers WHERE id = ?1", params![guest_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    if room_id.is_none() {
+        return Ok(None);
+    }
+    let due_out = match check_out.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| format!("Invalid check-out date '{}'", date))?,
+        None => at.date(),
+    };
+    let late = at.date() > due_out || (at.date() == due_out && at.time() > parse_time(&rules.check_out_time)?);
+    if !late {
+        return Ok(None);
+    }
+    fee_charge(conn, guest_id, "late_checkout", format!("Late check-out (after {})", rules.check_out_time), rule)
+}
+
+/// Charge the late check-out fee when a room guest leaves late. Call it inside
+/// the checkout transaction; it is only posted once.
+pub(crate) fn post_late_check_out_fee(conn: &Connection, guest_id: i64, at: NaiveDateTime) -> Result<(), String> {
+    match pending_late_check_out_fee(conn, guest_id, at)? {
+        Some(charge) => insert_fee(conn, &charge),
+        None => Ok(()),
+    }
+}
+
+/// Take back the late check-out fee checkout posted, when the checkout is
+/// reopened; the next checkout decides afresh whether it is due. Returns the
+/// amount removed.
+pub(crate) fn remove_late_check_out_fee(conn: &Connection, guest_id: i64) -> Result<f64, String> {
+    let condition = "guest_id = ?1 AND category = 'late_checkout' AND created_by = ?2";
+    let amount: f64 = conn
+        .query_row(
+            &format!("SELECT COALESCE(SUM(amount), 0) FROM guest_charges WHERE {}", condition),
+            params![guest_id, STAY_FEE_ACTOR],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    conn.execute(&format!("DELETE FROM guest_charges WHERE {}", condition), params![guest_id, STAY_FEE_ACTOR])
+        .map_err(|e| e.to_string())?;
+    Ok(amount)
+}
+
+#[command]
+pub fn get_stay_fee_rules() -> Result<StayFeeRules, AppError> {
+    let conn = get_db_connection()?;
+    Ok(load_rules(&conn)?)
+}
+
+/// Save the check-in/check-out times and the fees charged outside them. A
+/// rule left out (or with a zero amount) charges nothing.
+#[command]
+pub fn set_stay_fee_rules(rules: StayFeeRules) -> Result<StayFeeRules, AppError> {
+    parse_time(&rules.check_in_time)?;
+    parse_time(&rules.check_out_time)?;
+    for rule in rules.early_check_in.iter().chain(rules.late_check_out.iter()) {
+        if !FEE_TYPES.contains(&rule.fee_type.as_str()) {
+            return Err(format!("Fee type must be one of: {}", FEE_TYPES.join(", ")).into());
+        }
+        if !rule.amount.is_finite() || rule.amount < 0.0 || (rule.fee_type == "percent" && rule.amount > 100.0) {
+            return Err("Fee must be a positive amount, and a percentage no more than 100".into());
+        }
+    }
+
+    let conn = get_db_connection()?;
+    set_setting(&conn, "check_in_time", rules.check_in_time.trim())?;
+    set_setting(&conn, "check_out_time", rules.check_out_time.trim())?;
+    save_rule(&conn, "early_check_in", rules.early_check_in.as_ref())?;
+    save_rule(&conn, "late_check_out", rules.late_check_out.as_ref())?;
+    Ok(load_rules(&conn)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{add_guest, checkout_guest};
+    use crate::test_support::{add_test_room, check_in, count, same_amount, today, TestDb, TEST_RATE};
+
+    fn rules(early: Option<StayFeeRule>, late: Option<StayFeeRule>, check_in: &str, check_out: &str) -> StayFeeRules {
+        StayFeeRules {
+            check_in_time: check_in.to_string(),
+            check_out_time: check_out.to_string(),
+            early_check_in: early,
+            late_check_out: late,
+        }
+    }
+
+    fn fee(fee_type: &str, amount: f64) -> Option<StayFeeRule> {
+        Some(StayFeeRule { fee_type: fee_type.to_string(), amount })
+    }
+
+    fn check_out(guest_id: i64) -> f64 {
+        checkout_guest(guest_id, None, None, Some("cash".to_string()), None, None).unwrap().extra_charges
+    }
+
+    #[test]
+    fn early_check_in_and_late_check_out_are_both_charged() {
+        let _db = TestDb::new();
+        // Cut-offs at either end of the day make any check-in early and any check-out late
+        set_stay_fee_rules(rules(fee("flat", 25.0), fee("percent", 50.0), "23:59", "00:00")).unwrap();
+        let guest_id = check_in("Early Guest", Some(add_test_room("ST-501")));
+        let fees = check_out(guest_id);
+        assert!(same_amount(fees, 25.0 + TEST_RATE / 2.0), "early and late fees came to {:.2}", fees);
+    }
+
+    #[test]
+    fn the_bill_shows_a_late_fee_that_only_checkout_posts() {
+        let _db = TestDb::new();
+        set_stay_fee_rules(rules(None, fee("flat", 10.0), "14:00", "00:00")).unwrap();
+        let guest_id = check_in("Late Guest", Some(add_test_room("ST-501")));
+        let invoice = crate::print_templates::build_final_invoice_html(guest_id, None).unwrap();
+        assert!(invoice.contains("Late check-out"), "the bill does not show the late check-out fee");
+        let posted = count("SELECT COUNT(*) FROM guest_charges WHERE guest_id = ?1 AND category = 'late_checkout'", guest_id);
+        assert_eq!(posted, 0, "drawing up the bill posted the late check-out fee");
+        let fees = check_out(guest_id);
+        assert!(same_amount(fees, 10.0), "late check-out fee came to {:.2}, expected 10.00", fees);
+    }
+
+    #[test]
+    fn leaving_before_the_booked_date_is_never_late() {
+        let _db = TestDb::new();
+        set_stay_fee_rules(rules(None, fee("flat", 10.0), "14:00", "00:00")).unwrap();
+        let tomorrow = (chrono::Local::now().date_naive() + chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+        let guest_id = add_guest(
+            "Leaving Early Guest".to_string(), None, Some(add_test_room("ST-501")), today(), Some(tomorrow), TEST_RATE,
+            Some(1), Some(0), None, None, None, None,
+        )
+        .unwrap();
+        let fees = check_out(guest_id);
+        assert!(same_amount(fees, 0.0), "a guest leaving a day early was charged {:.2} in fees", fees);
+    }
+}
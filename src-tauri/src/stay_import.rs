@@ -0,0 +1,494 @@
+use tauri::command;
+use calamine::{open_workbook_auto, Data, Range, Reader};
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::date_checks::{check_historical_date_plausibility, log_acknowledged_dates};
+
+/// Tag stored in customers.imported_from for rows brought in from the legacy register.
+const IMPORT_SOURCE_TAG: &str = "xlsx_register";
+
+/// How many rows from the top are searched for the header row (merged title rows
+/// above the real headers are common in hand-kept registers).
+const HEADER_SEARCH_ROWS: usize = 15;
+
+/// Which sheet column holds each field. Values are header captions (case-insensitive)
+/// or, failing that, column letters such as "C".
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StayColumnMapping {
+    pub name: String,
+    pub phone: Option<String>,
+    pub room_number: String,
+    pub arrival: String,
+    pub departure: String,
+    pub amount: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportRowResult {
+    pub row: usize,
+    pub status: String, // 'created', 'skipped' or 'failed'
+    pub reason: Option<String>,
+    pub guest_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StayImportReport {
+    pub dry_run: bool,
+    pub created: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub rooms_created: Vec<String>,
+    pub rows: Vec<ImportRowResult>,
+}
+
+struct ColumnIndexes {
+    name: usize,
+    phone: Option<usize>,
+    room_number: usize,
+    arrival: usize,
+    departure: usize,
+    amount: Option<usize>,
+}
+
+struct StayRow {
+    name: String,
+    phone: Option<String>,
+    room_number: String,
+    arrival: NaiveDate,
+    departure: NaiveDate,
+    amount: Option<f64>,
+}
+
+pub(crate) fn cell_text(cell: Option<&Data>) -> String {
+    match cell {
+        Some(Data::String(s)) => s.trim().to_string(),
+        Some(Data::Int(i)) => i.to_string(),
+        Some(Data::Float(f)) if f.fract() == 0.0 => format!("{}", *f as i64),
+        Some(Data::Float(f)) => f.to_string(),
+        Some(Data::Bool(b)) => b.to_string(),
+        Some(Data::DateTimeIso(s)) => s.trim().to_string(),
+        Some(Data::DateTime(dt)) => dt.as_datetime().map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+pub(crate) fn column_letter_index(letters: &str) -> Option<usize> {
+    if letters.is_empty() || letters.len() > 2 || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    letters
+        .to_ascii_uppercase()
+        .bytes()
+        .try_fold(0usize, |acc, b| Some(acc * 26 + (b - b'A' + 1) as usize))
+        .map(|n| n - 1)
+}
+
+/// Find the header row and resolve each mapped column. Returns the header row index.
+fn resolve_columns(range: &Range<Data>, mapping: &StayColumnMapping) -> Result<(usize, ColumnIndexes), String> {
+    let header_row = range
+        .rows()
+        .take(HEADER_SEARCH_ROWS)
+        .position(|row| {
+            row.iter()
+                .any(|cell| cell_text(Some(cell)).eq_ignore_ascii_case(mapping.name.trim()))
+        });
+
+    let find = |caption: &str| -> Option<usize> {
+        let caption = caption.trim();
+        header_row
+            .and_then(|h| range.rows().nth(h))
+            .and_then(|row| row.iter().position(|cell| cell_text(Some(cell)).eq_ignore_ascii_case(caption)))
+            .or_else(|| column_letter_index(caption))
+    };
+    let require = |caption: &str| find(caption).ok_or_else(|| format!("Column '{}' not found in the sheet", caption.trim()));
+
+    Ok((
+        header_row.map(|h| h + 1).unwrap_or(0),
+        ColumnIndexes {
+            name: require(&mapping.name)?,
+            phone: mapping.phone.as_deref().map(require).transpose()?,
+            room_number: require(&mapping.room_number)?,
+            arrival: require(&mapping.arrival)?,
+            departure: require(&mapping.departure)?,
+            amount: mapping.amount.as_deref().map(require).transpose()?,
+        },
+    ))
+}
+
+fn parse_date_cell(cell: Option<&Data>) -> Result<NaiveDate, String> {
+    match cell {
+        Some(Data::DateTime(dt)) => dt
+            .as_datetime()
+            .map(|d| d.date())
+            .ok_or_else(|| "unreadable date".to_string()),
+        Some(Data::Float(serial)) => excel_serial_to_date(*serial),
+        Some(Data::Int(serial)) => excel_serial_to_date(*serial as f64),
+        Some(Data::String(_)) | Some(Data::DateTimeIso(_)) => parse_date_text(&cell_text(cell)),
+        _ => Err("missing date".to_string()),
+    }
+}
+
+fn excel_serial_to_date(serial: f64) -> Result<NaiveDate, String> {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30).unwrap();
+    epoch
+        .checked_add_signed(chrono::Duration::days(serial.trunc() as i64))
+        .ok_or_else(|| format!("date serial {} out of range", serial))
+}
+
+pub(crate) fn parse_date_text(text: &str) -> Result<NaiveDate, String> {
+    let text = text.trim();
+    let head = text.get(..10).unwrap_or(text);
+    if let Ok(date) = NaiveDate::parse_from_str(head, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    // Day-first formats, as the register was kept
+    for format in ["%d/%m/%Y", "%d-%m-%Y", "%d.%m.%Y", "%d %b %Y", "%d-%b-%Y", "%d %B %Y", "%d/%m/%y", "%d-%m-%y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(text, format) {
+            return Ok(date);
+        }
+    }
+    Err(format!("unrecognised date '{}'", text))
+}
+
+fn parse_amount_cell(cell: Option<&Data>) -> Result<Option<f64>, String> {
+    match cell {
+        Some(Data::Float(f)) => Ok(Some(*f)),
+        Some(Data::Int(i)) => Ok(Some(*i as f64)),
+        Some(Data::String(s)) => {
+            let cleaned: String = s.chars().filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-').collect();
+            if cleaned.is_empty() {
+                return Ok(None);
+            }
+            cleaned.parse::<f64>().map(Some).map_err(|_| format!("unrecognised amount '{}'", s.trim()))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn parse_stay_row(row: &[Data], cols: &ColumnIndexes) -> Result<StayRow, String> {
+    let name = cell_text(row.get(cols.name));
+    if name.is_empty() {
+        return Err("guest name is empty".to_string());
+    }
+    let room_number = cell_text(row.get(cols.room_number));
+    if room_number.is_empty() {
+        return Err("room number is empty".to_string());
+    }
+    let arrival = parse_date_cell(row.get(cols.arrival)).map_err(|e| format!("arrival: {}", e))?;
+    let departure = parse_date_cell(row.get(cols.departure)).map_err(|e| format!("departure: {}", e))?;
+    if departure < arrival {
+        return Err("departure is before arrival".to_string());
+    }
+    let amount = match cols.amount {
+        Some(idx) => parse_amount_cell(row.get(idx))?,
+        None => None,
+    };
+    if amount.is_some_and(|a| a < 0.0) {
+        return Err("amount is negative".to_string());
+    }
+
+    Ok(StayRow {
+        name,
+        phone: cols.phone.map(|idx| cell_text(row.get(idx))).filter(|p| !p.is_empty()),
+        room_number,
+        arrival,
+        departure,
+        amount,
+    })
+}
+
+fn stay_row_hash(stay: &StayRow) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!(
+        "{}|{}|{}|{}|{}|{}",
+        stay.name.to_lowercase(),
+        stay.phone.as_deref().unwrap_or(""),
+        stay.room_number.to_lowercase(),
+        stay.arrival,
+        stay.departure,
+        stay.amount.map(|a| format!("{:.2}", a)).unwrap_or_default()
+    ));
+    format!("{:x}", hasher.finalize())
+}
+
+fn resolve_room(
+    conn: &Connection,
+    number: &str,
+    create_missing_rooms: bool,
+    rooms_created: &mut Vec<String>,
+) -> Result<i64, String> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM resources WHERE LOWER(number) = LOWER(?1)",
+            params![number],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    if !create_missing_rooms {
+        return Err(format!("room '{}' does not exist", number));
+    }
+
+    // Historical rooms stay inactive so they never show up as bookable
+    conn.execute(
+        "INSERT INTO resources (number, room_type, daily_rate, is_occupied, is_active, resource_type)
+         VALUES (?1, 'Historical', 0, 0, 0, 'ROOM')",
+        params![number],
+    )
+    .map_err(|e| e.to_string())?;
+    rooms_created.push(number.to_string());
+    Ok(conn.last_insert_rowid())
+}
+
+/// Import the pre-app stay register. Each row becomes a checked_out guest carrying its
+/// billed amount; rows already imported (same content hash) are skipped. With dry_run the
+/// whole import runs inside a transaction that is rolled back. Rows with future or
+/// pre-epoch dates fail with UNUSUAL_DATE unless acknowledge_unusual_dates is set.
+#[command]
+pub fn import_stay_history_xlsx(
+    path: String,
+    column_mapping: StayColumnMapping,
+    create_missing_rooms: bool,
+    dry_run: bool,
+    sheet_name: Option<String>,
+    acknowledge_unusual_dates: Option<bool>,
+) -> Result<StayImportReport, String> {
+    let acknowledged = acknowledge_unusual_dates.unwrap_or(false);
+    let mut workbook = open_workbook_auto(path.trim()).map_err(|e| format!("Failed to open workbook: {}", e))?;
+    let sheet = match sheet_name {
+        Some(name) => name,
+        None => workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or("Workbook has no sheets".to_string())?,
+    };
+    let range = workbook
+        .worksheet_range(&sheet)
+        .map_err(|e| format!("Failed to read sheet '{}': {}", sheet, e))?;
+
+    let (first_data_row, cols) = resolve_columns(&range, &column_mapping)?;
+    let sheet_row_offset = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let now = get_current_timestamp();
+
+    let mut report = StayImportReport {
+        dry_run,
+        created: 0,
+        skipped: 0,
+        failed: 0,
+        rooms_created: Vec::new(),
+        rows: Vec::new(),
+    };
+
+    for (idx, row) in range.rows().enumerate().skip(first_data_row) {
+        let row_number = sheet_row_offset + idx + 1;
+        if row.iter().all(|cell| cell_text(Some(cell)).is_empty()) {
+            continue;
+        }
+
+        let result = parse_stay_row(row, &cols).and_then(|stay| {
+            let hash = stay_row_hash(&stay);
+            let already: bool = tx
+                .query_row(
+                    "SELECT 1 FROM customers WHERE import_row_hash = ?1",
+                    params![hash],
+                    |_| Ok(true),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?
+                .unwrap_or(false);
+            if already {
+                return Ok(None);
+            }
+
+            let arrival = stay.arrival.format("%Y-%m-%d").to_string();
+            let departure = stay.departure.format("%Y-%m-%d").to_string();
+            let unusual_dates = [
+                check_historical_date_plausibility(&tx, "arrival", &arrival, acknowledged)?,
+                check_historical_date_plausibility(&tx, "departure", &departure, acknowledged)?,
+            ];
+
+            let room_id = resolve_room(&tx, &stay.room_number, create_missing_rooms, &mut report.rooms_created)?;
+            let nights = (stay.departure - stay.arrival).num_days().max(1);
+            let daily_rate = stay.amount.map(|a| a / nights as f64).unwrap_or(0.0);
+
+            tx.execute(
+                "INSERT INTO customers (name, phone, room_id, check_in, check_out, daily_rate, status,
+                                        billed_amount, imported_from, import_row_hash, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, 'checked_out', ?7, ?8, ?9, ?10, ?10)",
+                params![
+                    stay.name,
+                    stay.phone,
+                    room_id,
+                    arrival,
+                    departure,
+                    daily_rate,
+                    stay.amount,
+                    IMPORT_SOURCE_TAG,
+                    hash,
+                    now
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            let guest_id = tx.last_insert_rowid();
+            crate::guest_profiles::attach_stay(&tx, guest_id, None, &stay.name, stay.phone.as_deref())?;
+            log_acknowledged_dates(&tx, &format!("imported guest {} (row {})", guest_id, row_number), &unusual_dates)?;
+            Ok(Some(guest_id))
+        });
+
+        let row_result = match result {
+            Ok(Some(guest_id)) => {
+                report.created += 1;
+                ImportRowResult { row: row_number, status: "created".to_string(), reason: None, guest_id: Some(guest_id) }
+            }
+            Ok(None) => {
+                report.skipped += 1;
+                ImportRowResult { row: row_number, status: "skipped".to_string(), reason: Some("already imported".to_string()), guest_id: None }
+            }
+            Err(reason) => {
+                report.failed += 1;
+                ImportRowResult { row: row_number, status: "failed".to_string(), reason: Some(reason), guest_id: None }
+            }
+        };
+        report.rows.push(row_result);
+    }
+
+    if dry_run {
+        tx.rollback().map_err(|e| e.to_string())?;
+    } else {
+        tx.commit().map_err(|e| e.to_string())?;
+        if report.created > 0 {
+            crate::events::guest_changed(None, "imported");
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use crate::test_support::{add_test_room, count, same_amount, TestDb};
+
+    /// A hand-kept register: merged title rows above the headers, dates typed
+    /// as text in mixed formats, a row entered twice and one unreadable date.
+    fn write_register(path: &Path) {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let sheet = workbook.add_worksheet();
+        let title = rust_xlsxwriter::Format::new().set_bold();
+        sheet.merge_range(0, 0, 0, 5, "Guest Register 2023", &title).unwrap();
+        sheet.merge_range(1, 0, 1, 5, "Front desk copy", &title).unwrap();
+        let rows: [[&str; 6]; 5] = [
+            ["Guest", "Phone", "Room", "Arrival", "Departure", "Amount"],
+            ["Register Guest", "555 0101", "101", "05/03/2023", "08/03/2023", ""],
+            ["Text Date Guest", "", "H-OLD", "12 Mar 2023", "2023-03-14", "Rs 180"],
+            ["Register Guest", "555 0101", "101", "05/03/2023", "08/03/2023", ""],
+            ["Someday Guest", "", "101", "someday", "2023-03-20", "50"],
+        ];
+        for (r, row) in rows.iter().enumerate() {
+            for (c, text) in row.iter().enumerate() {
+                if !text.is_empty() {
+                    sheet.write_string(r as u32 + 2, c as u16, *text).unwrap();
+                }
+            }
+        }
+        for r in [3, 5] {
+            sheet.write_number(r, 5, 300.0).unwrap();
+        }
+        workbook.save(path).unwrap();
+    }
+
+    /// Import the register written into the test folder, adding room 101
+    /// the first time.
+    fn import_register(db: &TestDb, dry_run: bool) -> StayImportReport {
+        let path = db.dir().join("register.xlsx");
+        if !path.exists() {
+            add_test_room("101");
+            write_register(&path);
+        }
+        let mapping = StayColumnMapping {
+            name: "guest".to_string(),
+            phone: Some("Phone".to_string()),
+            room_number: "Room".to_string(),
+            arrival: "Arrival".to_string(),
+            departure: "Departure".to_string(),
+            amount: Some("F".to_string()),
+        };
+        import_stay_history_xlsx(path.to_string_lossy().to_string(), mapping, true, dry_run, None, None).unwrap()
+    }
+
+    fn statuses(report: &StayImportReport) -> Vec<(usize, &str)> {
+        report.rows.iter().map(|r| (r.row, r.status.as_str())).collect()
+    }
+
+    fn imported() -> i64 {
+        let conn = get_db_connection().unwrap();
+        conn.query_row("SELECT COUNT(*) FROM customers WHERE imported_from = ?1", params![IMPORT_SOURCE_TAG], |row| row.get(0))
+            .unwrap()
+    }
+
+    const EXPECTED: &[(usize, &str)] = &[(4, "created"), (5, "created"), (6, "skipped"), (7, "failed")];
+
+    #[test]
+    fn a_dry_run_reports_every_row_and_saves_nothing() {
+        let db = TestDb::new();
+        let preview = import_register(&db, true);
+        assert!(preview.dry_run && statuses(&preview) == EXPECTED, "dry run reported {:?}", statuses(&preview));
+        assert_eq!(imported(), 0, "a dry run saved imported stays");
+        assert!(
+            preview.rows[3].reason.as_deref().is_some_and(|r| r.contains("unrecognised date 'someday'")),
+            "bad date reported as {:?}", preview.rows[3].reason
+        );
+    }
+
+    #[test]
+    fn stays_are_read_from_text_dates_and_amounts() {
+        let db = TestDb::new();
+        let report = import_register(&db, false);
+        assert_eq!(statuses(&report), EXPECTED, "import reported the wrong row outcomes");
+        assert_eq!(report.rooms_created, vec!["H-OLD".to_string()], "rooms created for the register");
+        let conn = get_db_connection().unwrap();
+        let (check_in, check_out, billed, status, room_active): (String, String, f64, String, bool) = conn
+            .query_row(
+                "SELECT c.check_in, c.check_out, c.billed_amount, c.status, r.is_active
+                 FROM customers c JOIN resources r ON r.id = c.room_id
+                 WHERE c.imported_from = 'xlsx_register' AND c.name = 'Text Date Guest'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .expect("text-dated stay not imported");
+        assert!(
+            check_in == "2023-03-12" && check_out == "2023-03-14" && same_amount(billed, 180.0) && status == "checked_out",
+            "text-dated stay imported as {} to {}, {:.2} billed, {}", check_in, check_out, billed, status
+        );
+        assert!(!room_active, "room created for the register is bookable");
+        let first_stay = count(
+            "SELECT COUNT(*) FROM customers WHERE id = ?1 AND check_in = '2023-03-05' AND check_out = '2023-03-08' AND billed_amount = 300",
+            report.rows[0].guest_id.unwrap_or_default(),
+        );
+        assert_eq!(first_stay, 1, "day-first text dates or the numeric amount misread");
+    }
+
+    #[test]
+    fn importing_the_register_again_skips_stays_already_there() {
+        let db = TestDb::new();
+        import_register(&db, false);
+        let again = import_register(&db, false);
+        assert!(
+            again.created == 0 && again.skipped == 3 && again.failed == 1,
+            "re-importing created {}, skipped {}, failed {}", again.created, again.skipped, again.failed
+        );
+        assert_eq!(imported(), 2, "stays imported twice");
+    }
+}
@@ -0,0 +1,1234 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::db::{get_db_connection, get_current_timestamp, log_audit_event};
+use crate::models::{
+    ComboItemSyncRow, ComboSyncRow, MenuCategorySyncRow, MenuPriceRuleSyncRow, MenuSyncRow, SettingSyncRow, SyncChange,
+    SyncServerStatus, SyncSnapshot, SyncSummary, TaxSyncRow,
+};
+use crate::offline_auth::require_session;
+use crate::validation::UNAUTHORIZED;
+use crate::repository::{get_setting, set_setting};
+
+const SYNC_FORMAT_VERSION: u32 = 1;
+const SYNC_DATASETS: &[&str] = &["menu", "settings", "taxes"];
+const SYNC_TIMEOUT_SECS: u64 = 10;
+// Two desks pull from each other; anything past this is turned away
+const MAX_SYNC_CONNECTIONS: usize = 4;
+// Request line plus headers; a sync request has no body
+const MAX_SYNC_REQUEST_BYTES: u64 = 16 * 1024;
+
+// Business settings both desks should agree on. Anything else (credentials,
+// printers, folders, backup and sync state) stays on the machine it was set on,
+// so a key added later is local until it is listed here.
+const SYNCABLE_SETTING_KEYS: &[&str] = &[
+    "business_name",
+    "business_address",
+    "business_email",
+    "business_phone",
+    "business_mode",
+    "currency_code",
+    "locale",
+    "receipt_locale",
+    "receipt_header",
+    "receipt_footer",
+    "receipt_qr_mode",
+    "receipt_qr_payment_link",
+    "primary_color",
+    "tax_enabled",
+    "prices_include_tax",
+    "total_rounding",
+    "check_in_time",
+    "check_out_time",
+    "early_check_in_fee",
+    "early_check_in_fee_type",
+    "late_check_out_fee",
+    "late_check_out_fee_type",
+    "checkout_reopen_window_hours",
+    "house_rules",
+    "max_property_occupancy",
+    "occupancy_cap_mode",
+    "after_hours_strict",
+    "date_window_past_days",
+    "date_window_future_days",
+    "date_earliest_allowed",
+    "signature_retention_days",
+    "max_reasonable_amount",
+    "idle_timeout_minutes",
+    "absolute_session_hours",
+];
+// Synced above, but offline_auth caches them for the whole process
+const SESSION_POLICY_KEYS: &[&str] = &["idle_timeout_minutes", "absolute_session_hours"];
+const SYNCABLE_SETTING_PREFIXES: &[&str] = &["pricing_", "service_charge_percent_", "denominations_"];
+
+// Port the listener in this process is bound to, if any.
+static SYNC_SERVER: Mutex<Option<u16>> = Mutex::new(None);
+// Requests being served right now
+static SYNC_CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn is_syncable_setting(key: &str) -> bool {
+    SYNCABLE_SETTING_KEYS.contains(&key) || SYNCABLE_SETTING_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+/// Timestamps come both from SQLite defaults ("YYYY-MM-DD HH:MM:SS", UTC) and
+/// from get_current_timestamp (RFC 3339), so compare them parsed, not as text.
+fn parse_sync_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+        .map(|ts| ts.and_utc())
+}
+
+/// True when the peer's copy should replace ours. Unparseable local stamps lose.
+fn peer_is_newer(peer: &str, local: &str) -> bool {
+    match (parse_sync_timestamp(peer), parse_sync_timestamp(local)) {
+        (Some(p), Some(l)) => p > l,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+fn normalize_datasets(datasets: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    let requested = match datasets {
+        Some(list) if !list.is_empty() => list,
+        _ => return Ok(SYNC_DATASETS.iter().map(|d| d.to_string()).collect()),
+    };
+
+    let mut normalized = Vec::new();
+    for dataset in requested {
+        let dataset = dataset.trim().to_lowercase();
+        if !SYNC_DATASETS.contains(&dataset.as_str()) {
+            return Err(format!("Unknown sync dataset '{}'. Expected one of: {}", dataset, SYNC_DATASETS.join(", ")));
+        }
+        if !normalized.contains(&dataset) {
+            normalized.push(dataset);
+        }
+    }
+    Ok(normalized)
+}
+
+fn export_menu(conn: &Connection) -> Result<Vec<MenuSyncRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name, price, category, is_available, attributes, COALESCE(updated_at, created_at, '')
+             FROM menu_items WHERE is_active = 1 ORDER BY name",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(MenuSyncRow {
+                name: row.get(0)?,
+                price: row.get(1)?,
+                category: row.get(2)?,
+                is_available: row.get::<_, i32>(3)? == 1,
+                attributes: row.get(4)?,
+                updated_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn export_settings(conn: &Connection) -> Result<Vec<SettingSyncRow>, String> {
+    let mut stmt = conn
+        .prepare("SELECT key, value, COALESCE(updated_at, '') FROM settings ORDER BY key")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SettingSyncRow {
+                key: row.get(0)?,
+                value: row.get(1)?,
+                updated_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut settings = Vec::new();
+    for row in rows {
+        let row = row.map_err(|e| e.to_string())?;
+        if is_syncable_setting(&row.key) {
+            settings.push(row);
+        }
+    }
+    Ok(settings)
+}
+
+fn export_menu_categories(conn: &Connection) -> Result<Vec<MenuCategorySyncRow>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, sort_order, is_active, COALESCE(updated_at, created_at, '') FROM menu_categories ORDER BY sort_order, name")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(MenuCategorySyncRow {
+                name: row.get(0)?,
+                sort_order: row.get(1)?,
+                is_active: row.get::<_, i32>(2)? == 1,
+                updated_at: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn export_menu_price_rules(conn: &Connection) -> Result<Vec<MenuPriceRuleSyncRow>, String> {
+    // Rules on items taken off the menu go with them
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.name, m.name, r.category, r.weekday_mask, r.start_time, r.end_time, r.price, r.discount_percent,
+                    COALESCE(r.updated_at, r.created_at, '')
+             FROM menu_price_rules r LEFT JOIN menu_items m ON m.id = r.menu_item_id
+             WHERE r.menu_item_id IS NULL OR m.is_active = 1
+             ORDER BY r.name, r.id",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(MenuPriceRuleSyncRow {
+                name: row.get(0)?,
+                menu_item_name: row.get(1)?,
+                category: row.get(2)?,
+                weekday_mask: row.get(3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                price: row.get(6)?,
+                discount_percent: row.get(7)?,
+                updated_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// A combo's items by menu item name, in name order.
+fn combo_sync_items(conn: &Connection, combo_id: i64) -> Result<Vec<ComboItemSyncRow>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.name, ci.quantity FROM combo_items ci JOIN menu_items m ON m.id = ci.menu_item_id
+             WHERE ci.combo_id = ?1 ORDER BY m.name",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![combo_id], |row| Ok(ComboItemSyncRow { menu_item_name: row.get(0)?, quantity: row.get(1)? }))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn export_combos(conn: &Connection) -> Result<Vec<ComboSyncRow>, String> {
+    let combos: Vec<(i64, String, f64, String)> = conn
+        .prepare("SELECT id, name, price, COALESCE(updated_at, created_at, '') FROM combos ORDER BY name")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| e.to_string())?;
+
+    combos
+        .into_iter()
+        .map(|(id, name, price, updated_at)| Ok(ComboSyncRow { name, price, items: combo_sync_items(conn, id)?, updated_at }))
+        .collect()
+}
+
+fn export_taxes(conn: &Connection) -> Result<Vec<TaxSyncRow>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, percent, applies_to, is_active, COALESCE(updated_at, created_at, '') FROM taxes ORDER BY name")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TaxSyncRow {
+                name: row.get(0)?,
+                percent: row.get(1)?,
+                applies_to: row.get(2)?,
+                is_active: row.get::<_, i32>(3)? == 1,
+                updated_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+pub(crate) fn build_snapshot(conn: &Connection, datasets: &[String]) -> Result<SyncSnapshot, String> {
+    let wants = |name: &str| datasets.iter().any(|d| d == name);
+    let menu = wants("menu");
+    Ok(SyncSnapshot {
+        format_version: SYNC_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        generated_at: get_current_timestamp(),
+        menu_items: if menu { Some(export_menu(conn)?) } else { None },
+        settings: if wants("settings") { Some(export_settings(conn)?) } else { None },
+        menu_categories: if menu { Some(export_menu_categories(conn)?) } else { None },
+        menu_price_rules: if menu { Some(export_menu_price_rules(conn)?) } else { None },
+        combos: if menu { Some(export_combos(conn)?) } else { None },
+        taxes: if wants("taxes") { Some(export_taxes(conn)?) } else { None },
+    })
+}
+
+/// Snapshot of this machine's syncable data, as served to LAN peers.
+#[command]
+pub fn get_sync_export(datasets: Option<Vec<String>>) -> Result<SyncSnapshot, String> {
+    let datasets = normalize_datasets(datasets)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    build_snapshot(&conn, &datasets)
+}
+
+fn change(dataset: &str, key: &str, action: &str, detail: Option<String>) -> SyncChange {
+    SyncChange {
+        dataset: dataset.to_string(),
+        key: key.to_string(),
+        action: action.to_string(),
+        detail,
+    }
+}
+
+fn apply_menu(conn: &Connection, rows: &[MenuSyncRow], summary: &mut SyncSummary) -> Result<(), String> {
+    for row in rows {
+        let local: Option<(i64, f64, String, bool, Option<String>, String)> = conn
+            .query_row(
+                "SELECT id, price, category, is_available, attributes, COALESCE(updated_at, created_at, '')
+                 FROM menu_items WHERE name = ?1",
+                params![row.name],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get::<_, i32>(3)? == 1, r.get(4)?, r.get(5)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        match local {
+            None => {
+                crate::menu_categories::ensure_menu_category(conn, &row.category)?;
+                conn.execute(
+                    "INSERT INTO menu_items (name, price, category, is_available, attributes, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![row.name, row.price, row.category, row.is_available as i32, row.attributes, row.updated_at],
+                )
+                .map_err(|e| e.to_string())?;
+                summary.added += 1;
+                summary.changes.push(change("menu", &row.name, "added", Some(format!("price {:.2}", row.price))));
+            }
+            Some((id, price, category, is_available, attributes, updated_at)) => {
+                let same = (price - row.price).abs() < 0.005
+                    && category == row.category
+                    && is_available == row.is_available
+                    && attributes == row.attributes;
+                if same {
+                    summary.unchanged += 1;
+                } else if peer_is_newer(&row.updated_at, &updated_at) {
+                    // Reactivates a soft-deleted local row of the same name as well
+                    crate::menu_categories::ensure_menu_category(conn, &row.category)?;
+                    conn.execute(
+                        "UPDATE menu_items SET price = ?1, category = ?2, is_available = ?3, attributes = ?4,
+                                is_active = 1, updated_at = ?5
+                         WHERE id = ?6",
+                        params![row.price, row.category, row.is_available as i32, row.attributes, row.updated_at, id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    summary.updated += 1;
+                    summary.changes.push(change(
+                        "menu",
+                        &row.name,
+                        "updated",
+                        Some(format!("price {:.2} -> {:.2}", price, row.price)),
+                    ));
+                } else {
+                    summary.skipped.push(change(
+                        "menu",
+                        &row.name,
+                        "skipped",
+                        Some(format!("local copy edited {} is newer than peer's {}", updated_at, row.updated_at)),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_menu_categories(conn: &Connection, rows: &[MenuCategorySyncRow], summary: &mut SyncSummary) -> Result<(), String> {
+    for row in rows {
+        let local: Option<(i64, i64, bool, String)> = conn
+            .query_row(
+                "SELECT id, sort_order, is_active, COALESCE(updated_at, created_at, '')
+                 FROM menu_categories WHERE name = ?1 COLLATE NOCASE",
+                params![row.name],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get::<_, i32>(2)? == 1, r.get(3)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        match local {
+            None => {
+                conn.execute(
+                    "INSERT INTO menu_categories (name, sort_order, is_active, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![row.name, row.sort_order, row.is_active as i32, row.updated_at],
+                )
+                .map_err(|e| e.to_string())?;
+                summary.added += 1;
+                summary.changes.push(change("menu_categories", &row.name, "added", None));
+            }
+            Some((_, sort_order, is_active, _)) if sort_order == row.sort_order && is_active == row.is_active => {
+                summary.unchanged += 1;
+            }
+            Some((id, _, _, updated_at)) if peer_is_newer(&row.updated_at, &updated_at) => {
+                conn.execute(
+                    "UPDATE menu_categories SET sort_order = ?1, is_active = ?2, updated_at = ?3 WHERE id = ?4",
+                    params![row.sort_order, row.is_active as i32, row.updated_at, id],
+                )
+                .map_err(|e| e.to_string())?;
+                summary.updated += 1;
+                summary.changes.push(change("menu_categories", &row.name, "updated", None));
+            }
+            Some((_, _, _, updated_at)) => {
+                summary.skipped.push(change(
+                    "menu_categories",
+                    &row.name,
+                    "skipped",
+                    Some(format!("local copy edited {} is newer than peer's {}", updated_at, row.updated_at)),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The id of the active menu item called `name`, if this install has it.
+fn menu_item_id(conn: &Connection, name: &str) -> Result<Option<i64>, String> {
+    conn.query_row("SELECT id FROM menu_items WHERE name = ?1 AND is_active = 1", params![name], |r| r.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn apply_menu_price_rules(conn: &Connection, rows: &[MenuPriceRuleSyncRow], summary: &mut SyncSummary) -> Result<(), String> {
+    for row in rows {
+        let menu_item_id = match &row.menu_item_name {
+            Some(item) => match menu_item_id(conn, item)? {
+                Some(id) => Some(id),
+                None => {
+                    summary.skipped.push(change(
+                        "menu_price_rules",
+                        &row.name,
+                        "skipped",
+                        Some(format!("menu item '{}' is not on this machine's menu", item)),
+                    ));
+                    continue;
+                }
+            },
+            None => None,
+        };
+        let category = match &row.category {
+            Some(category) => Some(crate::menu_categories::ensure_menu_category(conn, category)?),
+            None => None,
+        };
+
+        // A rule is known by its name and what it applies to; the rest is what peers edit
+        let local: Option<(i64, i64, String, String, Option<f64>, Option<f64>, String)> = conn
+            .query_row(
+                "SELECT id, weekday_mask, start_time, end_time, price, discount_percent, COALESCE(updated_at, created_at, '')
+                 FROM menu_price_rules WHERE name = ?1 COLLATE NOCASE AND menu_item_id IS ?2 AND category IS ?3
+                 ORDER BY id DESC LIMIT 1",
+                params![row.name, menu_item_id, category],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?, r.get(6)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        match local {
+            None => {
+                conn.execute(
+                    "INSERT INTO menu_price_rules
+                         (name, menu_item_id, category, weekday_mask, start_time, end_time, price, discount_percent, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                    params![
+                        row.name, menu_item_id, category, row.weekday_mask,
+                        row.start_time, row.end_time, row.price, row.discount_percent, row.updated_at
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+                summary.added += 1;
+                summary.changes.push(change("menu_price_rules", &row.name, "added", None));
+            }
+            Some((id, weekday_mask, start_time, end_time, price, discount_percent, updated_at)) => {
+                let same_value = |a: Option<f64>, b: Option<f64>| match (a, b) {
+                    (Some(a), Some(b)) => (a - b).abs() < 0.005,
+                    (a, b) => a.is_none() && b.is_none(),
+                };
+                let same = weekday_mask == row.weekday_mask
+                    && start_time == row.start_time
+                    && end_time == row.end_time
+                    && same_value(price, row.price)
+                    && same_value(discount_percent, row.discount_percent);
+                if same {
+                    summary.unchanged += 1;
+                } else if peer_is_newer(&row.updated_at, &updated_at) {
+                    conn.execute(
+                        "UPDATE menu_price_rules
+                         SET weekday_mask = ?1, start_time = ?2, end_time = ?3, price = ?4, discount_percent = ?5, updated_at = ?6
+                         WHERE id = ?7",
+                        params![
+                            row.weekday_mask, row.start_time, row.end_time, row.price, row.discount_percent, row.updated_at, id
+                        ],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    summary.updated += 1;
+                    summary.changes.push(change("menu_price_rules", &row.name, "updated", None));
+                } else {
+                    summary.skipped.push(change(
+                        "menu_price_rules",
+                        &row.name,
+                        "skipped",
+                        Some(format!("local copy edited {} is newer than peer's {}", updated_at, row.updated_at)),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_combos(conn: &Connection, rows: &[ComboSyncRow], summary: &mut SyncSummary) -> Result<(), String> {
+    for row in rows {
+        // Menu items are applied first, so a missing one is off this machine's menu
+        let mut items = Vec::new();
+        for item in &row.items {
+            match menu_item_id(conn, &item.menu_item_name)? {
+                Some(id) => items.push((id, item.quantity)),
+                None => break,
+            }
+        }
+        if items.len() != row.items.len() {
+            let missing = row.items.get(items.len()).map(|i| i.menu_item_name.as_str()).unwrap_or_default();
+            summary.skipped.push(change(
+                "combos",
+                &row.name,
+                "skipped",
+                Some(format!("menu item '{}' is not on this machine's menu", missing)),
+            ));
+            continue;
+        }
+
+        let local: Option<(i64, f64, String)> = conn
+            .query_row(
+                "SELECT id, price, COALESCE(updated_at, created_at, '') FROM combos WHERE name = ?1 COLLATE NOCASE",
+                params![row.name],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        match local {
+            None => {
+                conn.execute(
+                    "INSERT INTO combos (name, price, updated_at) VALUES (?1, ?2, ?3)",
+                    params![row.name, row.price, row.updated_at],
+                )
+                .map_err(|e| e.to_string())?;
+                crate::combos::write_items(conn, conn.last_insert_rowid(), &items)?;
+                summary.added += 1;
+                summary.changes.push(change("combos", &row.name, "added", Some(format!("price {:.2}", row.price))));
+            }
+            Some((id, price, updated_at)) => {
+                let same = (price - row.price).abs() < 0.005 && combo_sync_items(conn, id)? == row.items;
+                if same {
+                    summary.unchanged += 1;
+                } else if peer_is_newer(&row.updated_at, &updated_at) {
+                    conn.execute(
+                        "UPDATE combos SET price = ?1, updated_at = ?2 WHERE id = ?3",
+                        params![row.price, row.updated_at, id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    crate::combos::write_items(conn, id, &items)?;
+                    summary.updated += 1;
+                    summary.changes.push(change(
+                        "combos",
+                        &row.name,
+                        "updated",
+                        Some(format!("price {:.2} -> {:.2}", price, row.price)),
+                    ));
+                } else {
+                    summary.skipped.push(change(
+                        "combos",
+                        &row.name,
+                        "skipped",
+                        Some(format!("local copy edited {} is newer than peer's {}", updated_at, row.updated_at)),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_taxes(conn: &Connection, rows: &[TaxSyncRow], summary: &mut SyncSummary) -> Result<(), String> {
+    for row in rows {
+        let local: Option<(i64, f64, String, bool, String)> = conn
+            .query_row(
+                "SELECT id, percent, applies_to, is_active, COALESCE(updated_at, created_at, '') FROM taxes WHERE name = ?1",
+                params![row.name],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get::<_, i32>(3)? == 1, r.get(4)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        match local {
+            None => {
+                conn.execute(
+                    "INSERT INTO taxes (name, percent, applies_to, is_active, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                    params![row.name, row.percent, row.applies_to, row.is_active as i32, row.updated_at],
+                )
+                .map_err(|e| e.to_string())?;
+                summary.added += 1;
+                summary.changes.push(change("taxes", &row.name, "added", Some(format!("{}%", row.percent))));
+            }
+            Some((id, percent, applies_to, is_active, updated_at)) => {
+                let same = (percent - row.percent).abs() < 0.0001 && applies_to == row.applies_to && is_active == row.is_active;
+                if same {
+                    summary.unchanged += 1;
+                } else if peer_is_newer(&row.updated_at, &updated_at) {
+                    conn.execute(
+                        "UPDATE taxes SET percent = ?1, applies_to = ?2, is_active = ?3, updated_at = ?4 WHERE id = ?5",
+                        params![row.percent, row.applies_to, row.is_active as i32, row.updated_at, id],
+                    )
+                    .map_err(|e| e.to_string())?;
+                    summary.updated += 1;
+                    summary.changes.push(change("taxes", &row.name, "updated", Some(format!("{}% -> {}%", percent, row.percent))));
+                } else {
+                    summary.skipped.push(change(
+                        "taxes",
+                        &row.name,
+                        "skipped",
+                        Some(format!("local copy edited {} is newer than peer's {}", updated_at, row.updated_at)),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_settings(conn: &Connection, rows: &[SettingSyncRow], summary: &mut SyncSummary) -> Result<(), String> {
+    for row in rows {
+        // A peer running an older build may still send these
+        if !is_syncable_setting(&row.key) {
+            continue;
+        }
+
+        let local: Option<(String, String)> = conn
+            .query_row(
+                "SELECT value, COALESCE(updated_at, '') FROM settings WHERE key = ?1",
+                params![row.key],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        let write = |conn: &Connection| {
+            conn.execute(
+                "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)",
+                params![row.key, row.value, row.updated_at],
+            )
+            .map_err(|e| e.to_string())
+        };
+
+        match local {
+            None => {
+                write(conn)?;
+                summary.added += 1;
+                summary.changes.push(change("settings", &row.key, "added", None));
+            }
+            Some((value, _)) if value == row.value => summary.unchanged += 1,
+            Some((_, updated_at)) if peer_is_newer(&row.updated_at, &updated_at) => {
+                write(conn)?;
+                summary.updated += 1;
+                summary.changes.push(change("settings", &row.key, "updated", None));
+            }
+            Some((_, updated_at)) => {
+                summary.skipped.push(change(
+                    "settings",
+                    &row.key,
+                    "skipped",
+                    Some(format!("local copy edited {} is newer than peer's {}", updated_at, row.updated_at)),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Merge a peer's snapshot into `conn`, keeping whichever side of each row was
+/// edited last. The caller owns the transaction.
+pub(crate) fn apply_snapshot(conn: &Connection, peer_url: &str, snapshot: &SyncSnapshot) -> Result<SyncSummary, String> {
+    let mut summary = SyncSummary {
+        peer_url: peer_url.to_string(),
+        peer_generated_at: snapshot.generated_at.clone(),
+        added: 0,
+        updated: 0,
+        unchanged: 0,
+        changes: Vec::new(),
+        skipped: Vec::new(),
+    };
+    // Categories before the items filed under them, items before the rules and combos that use them
+    if let Some(categories) = &snapshot.menu_categories {
+        apply_menu_categories(conn, categories, &mut summary)?;
+    }
+    if let Some(menu) = &snapshot.menu_items {
+        apply_menu(conn, menu, &mut summary)?;
+    }
+    if let Some(rules) = &snapshot.menu_price_rules {
+        apply_menu_price_rules(conn, rules, &mut summary)?;
+    }
+    if let Some(combos) = &snapshot.combos {
+        apply_combos(conn, combos, &mut summary)?;
+    }
+    if let Some(settings) = &snapshot.settings {
+        apply_settings(conn, settings, &mut summary)?;
+    }
+    if let Some(taxes) = &snapshot.taxes {
+        apply_taxes(conn, taxes, &mut summary)?;
+    }
+    Ok(summary)
+}
+
+fn fetch_snapshot(peer_url: &str, token: &str, datasets: &[String]) -> Result<SyncSnapshot, String> {
+    let url = format!("{}/sync/export?datasets={}", peer_url.trim_end_matches('/'), datasets.join(","));
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(SYNC_TIMEOUT_SECS))
+        .build();
+
+    let response = agent
+        .get(&url)
+        .set("Authorization", &format!("Bearer {}", token))
+        .call()
+        .map_err(|e| match e {
+            ureq::Error::Status(401, _) => "Peer rejected the sync token".to_string(),
+            ureq::Error::Status(code, resp) => {
+                format!("Peer returned HTTP {}: {}", code, resp.into_string().unwrap_or_default())
+            }
+            ureq::Error::Transport(t) => format!("Could not reach peer at {}: {}", peer_url, t),
+        })?;
+
+    let snapshot: SyncSnapshot = response
+        .into_json()
+        .map_err(|e| format!("Invalid sync snapshot from peer: {}", e))?;
+    if snapshot.format_version != SYNC_FORMAT_VERSION {
+        return Err(format!(
+            "Peer uses sync format {} (app {}), this install expects {}. Update both machines to the same version.",
+            snapshot.format_version, snapshot.app_version, SYNC_FORMAT_VERSION
+        ));
+    }
+    Ok(snapshot)
+}
+
+/// Pull the menu, settings and taxes from the other front-desk PC and merge them locally.
+/// Each row keeps whichever side was edited last; rows skipped because the local
+/// copy is newer are listed in the summary. The whole pull applies or nothing does.
+#[command]
+pub fn sync_pull(
+    session_token: String,
+    peer_url: String,
+    token: String,
+    datasets: Option<Vec<String>>,
+) -> Result<SyncSummary, String> {
+    let session = require_session(&session_token)?;
+    if session.role != "admin" && session.role != "manager" {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    let peer_url = peer_url.trim().to_string();
+    if !peer_url.starts_with("http://") && !peer_url.starts_with("https://") {
+        return Err("Peer address must start with http:// or https://".to_string());
+    }
+    let datasets = normalize_datasets(datasets)?;
+
+    // Fetch fully before touching the local database
+    let snapshot = fetch_snapshot(&peer_url, token.trim(), &datasets)?;
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let summary = apply_snapshot(&tx, &peer_url, &snapshot)?;
+
+    log_audit_event(
+        &tx,
+        &session.username,
+        "sync_pull",
+        &format!(
+            "from {} ({}): {} added, {} updated, {} skipped",
+            peer_url,
+            datasets.join(","),
+            summary.added,
+            summary.updated,
+            summary.skipped.len()
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if summary.changes.iter().any(|c| c.dataset == "settings" && SESSION_POLICY_KEYS.contains(&c.key.as_str())) {
+        crate::offline_auth::forget_session_policy();
+    }
+
+    Ok(summary)
+}
+
+// ===== LAN endpoint =====
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.flush();
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Compare the peer's token with ours without the time taken giving away how
+/// much of it matched. Hashing first hides the length as well.
+fn token_matches(given: &str, expected: &str) -> bool {
+    let (given, expected) = (Sha256::digest(given.as_bytes()), Sha256::digest(expected.as_bytes()));
+    given.iter().zip(expected.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// Frees a connection slot when the request is done, however it ends
+struct SyncConnectionSlot;
+
+impl Drop for SyncConnectionSlot {
+    fn drop(&mut self) {
+        SYNC_CONNECTIONS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn take_connection_slot() -> Option<SyncConnectionSlot> {
+    let taken = SYNC_CONNECTIONS.fetch_add(1, Ordering::SeqCst);
+    let slot = SyncConnectionSlot;
+    (taken < MAX_SYNC_CONNECTIONS).then_some(slot)
+}
+
+/// Serve one request. Only `GET /sync/export` exists, and it requires the
+/// shared token stored in `sync_token`.
+fn handle_sync_request(mut stream: TcpStream) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(SYNC_TIMEOUT_SECS)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(SYNC_TIMEOUT_SECS)));
+    let mut reader = match stream.try_clone() {
+        Ok(s) => BufReader::new(s.take(MAX_SYNC_REQUEST_BYTES)),
+        Err(_) => return,
+    };
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+
+    let mut bearer = None;
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                bearer = value.trim().strip_prefix("Bearer ").map(|t| t.trim().to_string());
+            }
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if method != "GET" || path != "/sync/export" {
+        write_response(&mut stream, "404 Not Found", &error_body("Not found"));
+        return;
+    }
+
+    let conn = match get_db_connection() {
+        Ok(conn) => conn,
+        Err(e) => {
+            write_response(&mut stream, "500 Internal Server Error", &error_body(&e.to_string()));
+            return;
+        }
+    };
+
+    let expected = get_setting(&conn, "sync_token").ok().flatten().unwrap_or_default();
+    if expected.is_empty() || !bearer.is_some_and(|bearer| token_matches(&bearer, &expected)) {
+        write_response(&mut stream, "401 Unauthorized", &error_body("Invalid sync token"));
+        return;
+    }
+
+    let datasets = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("datasets="))
+        .map(|list| list.split(',').filter(|d| !d.is_empty()).map(|d| d.to_string()).collect());
+
+    let result = normalize_datasets(datasets).and_then(|datasets| build_snapshot(&conn, &datasets));
+    match result.and_then(|snapshot| serde_json::to_string(&snapshot).map_err(|e| e.to_string())) {
+        Ok(body) => write_response(&mut stream, "200 OK", &body),
+        Err(e) => write_response(&mut stream, "400 Bad Request", &error_body(&e)),
+    }
+}
+
+/// Bind the LAN endpoint on `port` in a background thread. The listener lives
+/// for the rest of the process; a second call with the same port is a no-op.
+fn start_sync_server(port: u16) -> Result<(), String> {
+    let mut running = SYNC_SERVER.lock().map_err(|e| e.to_string())?;
+    match *running {
+        Some(current) if current == port => return Ok(()),
+        Some(current) => {
+            return Err(format!(
+                "Sync server is already listening on port {}; restart the app to change ports",
+                current
+            ))
+        }
+        None => {}
+    }
+
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .map_err(|e| format!("Could not listen on port {}: {}", port, e))?;
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let Some(slot) = take_connection_slot() else {
+                let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+                write_response(&mut stream, "503 Service Unavailable", &error_body("Too many sync requests; try again"));
+                continue;
+            };
+            std::thread::spawn(move || {
+                let _slot = slot;
+                handle_sync_request(stream);
+            });
+        }
+    });
+
+    *running = Some(port);
+    Ok(())
+}
+
+/// Start the endpoint at launch if this machine was set up as a sync source.
+pub fn start_configured_sync_server() -> Result<(), String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let port = match get_setting(&conn, "sync_server_port")? {
+        Some(port) => port,
+        None => return Ok(()),
+    };
+    let port: u16 = port.parse().map_err(|_| format!("Invalid sync_server_port '{}'", port))?;
+    start_sync_server(port)
+}
+
+/// Serve this machine's data to the other PC on `port`. A new shared token is
+/// generated when none is given; the peer passes it to sync_pull.
+#[command]
+pub fn enable_sync_server(session_token: String, port: u16, token: Option<String>) -> Result<SyncServerStatus, String> {
+    let session = require_session(&session_token)?;
+    if session.role != "admin" {
+        return Err(UNAUTHORIZED.to_string());
+    }
+    if port < 1024 {
+        return Err("Sync port must be 1024 or higher".to_string());
+    }
+
+    let token = match token.map(|t| t.trim().to_string()) {
+        Some(t) if t.len() < 8 => return Err("Sync token must be at least 8 characters".to_string()),
+        Some(t) => t,
+        None => uuid::Uuid::new_v4().simple().to_string(),
+    };
+
+    start_sync_server(port)?;
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    set_setting(&conn, "sync_server_port", &port.to_string())?;
+    set_setting(&conn, "sync_token", &token)?;
+    log_audit_event(&conn, &session.username, "sync_server_enabled", &format!("port {}", port))
+        .map_err(|e| e.to_string())?;
+
+    Ok(SyncServerStatus { enabled: true, port: Some(port), token: Some(token) })
+}
+
+/// Stop answering peers. Clearing the token makes the running listener refuse
+/// every request straight away; it is not re-bound on the next launch.
+#[command]
+pub fn disable_sync_server(session_token: String) -> Result<SyncServerStatus, String> {
+    let session = require_session(&session_token)?;
+    if session.role != "admin" {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM settings WHERE key IN ('sync_server_port', 'sync_token')", [])
+        .map_err(|e| e.to_string())?;
+    log_audit_event(&conn, &session.username, "sync_server_disabled", "")
+        .map_err(|e| e.to_string())?;
+
+    Ok(SyncServerStatus { enabled: false, port: None, token: None })
+}
+
+#[command]
+pub fn get_sync_server_status(session_token: String) -> Result<SyncServerStatus, String> {
+    let session = require_session(&session_token)?;
+    if session.role != "admin" {
+        return Err(UNAUTHORIZED.to_string());
+    }
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let token = get_setting(&conn, "sync_token")?;
+    let port = SYNC_SERVER.lock().map_err(|e| e.to_string())?.to_owned();
+    Ok(SyncServerStatus { enabled: token.is_some() && port.is_some(), port, token })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::initialize_database;
+    use crate::test_support::{same_amount, TestDb};
+
+    const OLD: &str = "2000-01-01 00:00:00";
+    // Credentials, devices and paths that belong to one machine
+    const LOCAL_KEYS: &[&str] = &[
+        "sync_token",
+        "backup_passphrase",
+        "cloud_backup_webdav_password",
+        "payment_gateway_stripe_secret_key",
+        "twilio_auth_token",
+        "whatsapp_access_token",
+        "fiscal_fbr_token",
+        "fiscal_webhook_token",
+        "receipt_printer",
+        "kitchen_printer",
+        "media_directory",
+        "business_logo_path",
+    ];
+
+    /// A snapshot of the other front-desk PC, kept in a database of its own:
+    /// a new tea, a coffee price edited long ago, a desserts category, a tea
+    /// happy hour and combo, a raised tax and its own settings.
+    fn peer_snapshot(db: &TestDb) -> SyncSnapshot {
+        db.with_database(db.dir().join("peer.db"), || {
+            initialize_database().unwrap();
+            let peer = get_db_connection().unwrap();
+            for (name, price, updated_at) in [("Tea", 3.0, get_current_timestamp()), ("Coffee", 4.0, OLD.to_string())] {
+                peer.execute(
+                    "INSERT INTO menu_items (name, price, category, is_available, updated_at) VALUES (?1, ?2, 'Drinks', 1, ?3)",
+                    params![name, price, updated_at],
+                )
+                .unwrap();
+            }
+            let now = get_current_timestamp();
+            peer.execute(
+                "INSERT INTO menu_categories (name, sort_order, updated_at) VALUES ('Desserts', 9, ?1)",
+                params![now],
+            )
+            .unwrap();
+            let tea: i64 = peer.query_row("SELECT id FROM menu_items WHERE name = 'Tea'", [], |row| row.get(0)).unwrap();
+            peer.execute(
+                "INSERT INTO menu_price_rules (name, menu_item_id, weekday_mask, start_time, end_time, price)
+                 VALUES ('Happy hour', ?1, 127, '16:00', '18:00', 2.0)",
+                params![tea],
+            )
+            .unwrap();
+            peer.execute(
+                "INSERT INTO menu_price_rules (name, category, weekday_mask, start_time, end_time, discount_percent)
+                 VALUES ('Sweet Sunday', 'Desserts', 1, '12:00', '20:00', 10.0)",
+                [],
+            )
+            .unwrap();
+            peer.execute("INSERT INTO combos (name, price, updated_at) VALUES ('Tea for two', 5.0, ?1)", params![now]).unwrap();
+            crate::combos::write_items(&peer, peer.last_insert_rowid(), &[(tea, 2)]).unwrap();
+            peer.execute("UPDATE taxes SET percent = 8.0, updated_at = ?1 WHERE name = 'Tax'", params![now]).unwrap();
+            peer.execute(
+                "INSERT INTO taxes (name, percent, applies_to, updated_at) VALUES ('City tax', 2.0, 'rooms', ?1)",
+                params![now],
+            )
+            .unwrap();
+            set_setting(&peer, "business_name", "Peer Hotel").unwrap();
+            for key in LOCAL_KEYS {
+                set_setting(&peer, key, "peer value").unwrap();
+            }
+            build_snapshot(&peer, &["menu".to_string(), "settings".to_string(), "taxes".to_string()]).unwrap()
+        })
+    }
+
+    #[test]
+    fn snapshots_leave_out_machine_local_settings() {
+        let db = TestDb::new();
+        let snapshot = peer_snapshot(&db);
+        let exported: Vec<&str> = snapshot.settings.iter().flatten().map(|s| s.key.as_str()).collect();
+        assert!(exported.contains(&"business_name"), "business name not exported: {:?}", exported);
+        assert!(exported.iter().all(|key| is_syncable_setting(key)), "machine-local or secret settings exported: {:?}", exported);
+    }
+
+    #[test]
+    fn secrets_sent_by_a_peer_are_not_applied() {
+        let db = TestDb::new();
+        let mut snapshot = peer_snapshot(&db);
+        // A peer on an older build may still send them
+        snapshot.settings.get_or_insert_with(Vec::new).extend(LOCAL_KEYS.iter().map(|key| SettingSyncRow {
+            key: key.to_string(),
+            value: "peer value".to_string(),
+            updated_at: get_current_timestamp(),
+        }));
+        let conn = get_db_connection().unwrap();
+        conn.execute("INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('business_name', 'Local Hotel', ?1)", params![OLD])
+            .unwrap();
+        let before = LOCAL_KEYS.iter().map(|key| get_setting(&conn, key).unwrap()).collect::<Vec<_>>();
+        apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        assert_eq!(get_setting(&conn, "business_name").unwrap().as_deref(), Some("Peer Hotel"), "newer peer business name not applied");
+        for (key, value) in LOCAL_KEYS.iter().zip(before) {
+            assert_eq!(get_setting(&conn, key).unwrap(), value, "peer's {} was copied", key);
+        }
+    }
+
+    #[test]
+    fn newer_local_menu_edits_win() {
+        let db = TestDb::new();
+        let snapshot = peer_snapshot(&db);
+        let conn = get_db_connection().unwrap();
+        conn.execute(
+            "INSERT INTO menu_items (name, price, category, is_available, updated_at) VALUES ('Coffee', 5.0, 'Drinks', 1, ?1)",
+            params![get_current_timestamp()],
+        )
+        .unwrap();
+        let summary = apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        let coffee: f64 = conn.query_row("SELECT price FROM menu_items WHERE name = 'Coffee'", [], |row| row.get(0)).unwrap();
+        assert!(same_amount(coffee, 5.0), "older peer price overwrote a newer local edit: {:.2}", coffee);
+        assert!(
+            summary.changes.iter().any(|c| c.dataset == "menu" && c.key == "Tea" && c.action == "added"),
+            "new peer menu item not reported as added"
+        );
+        assert!(
+            summary.skipped.iter().any(|c| c.dataset == "menu" && c.key == "Coffee"),
+            "newer local menu edit not reported as skipped"
+        );
+    }
+
+    #[test]
+    fn peer_menu_categories_are_synced() {
+        let db = TestDb::new();
+        let snapshot = peer_snapshot(&db);
+        let conn = get_db_connection().unwrap();
+        apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        let sort_order: i64 = conn
+            .query_row("SELECT sort_order FROM menu_categories WHERE name = 'Desserts'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sort_order, 9, "peer category not copied with its position");
+
+        let again = apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        assert!(!again.changes.iter().any(|c| c.dataset == "menu_categories"), "category re-applied: {:?}", again.changes);
+    }
+
+    #[test]
+    fn peer_price_rules_are_synced_once() {
+        let db = TestDb::new();
+        let snapshot = peer_snapshot(&db);
+        let conn = get_db_connection().unwrap();
+        apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        let happy_hour: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM menu_price_rules r JOIN menu_items m ON m.id = r.menu_item_id
+                 WHERE r.name = 'Happy hour' AND m.name = 'Tea' AND r.price = 2.0",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(happy_hour, 1, "item rule not synced exactly once");
+        let sweet_sunday: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM menu_price_rules WHERE name = 'Sweet Sunday' AND category = 'Desserts' AND menu_item_id IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(sweet_sunday, 1, "category rule not synced exactly once");
+    }
+
+    #[test]
+    fn a_peers_edited_price_rule_replaces_the_older_copy() {
+        let db = TestDb::new();
+        let mut snapshot = peer_snapshot(&db);
+        let conn = get_db_connection().unwrap();
+        apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        conn.execute("UPDATE menu_price_rules SET updated_at = ?1", params![OLD]).unwrap();
+        let rule = snapshot.menu_price_rules.as_mut().unwrap().iter_mut().find(|r| r.name == "Happy hour").unwrap();
+        rule.price = Some(1.5);
+        rule.end_time = "19:00".to_string();
+        rule.updated_at = get_current_timestamp();
+        let summary = apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        assert!(summary.changes.iter().any(|c| c.dataset == "menu_price_rules" && c.action == "updated"), "edit not applied: {:?}", summary.changes);
+        let rules: Vec<(f64, String)> = conn
+            .prepare("SELECT price, end_time FROM menu_price_rules WHERE name = 'Happy hour'")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(
+            rules.len() == 1 && same_amount(rules[0].0, 1.5) && rules[0].1 == "19:00",
+            "edited rule should replace the old one, found {:?}", rules
+        );
+    }
+
+    #[test]
+    fn peer_combos_are_synced_with_their_items() {
+        let db = TestDb::new();
+        let snapshot = peer_snapshot(&db);
+        let conn = get_db_connection().unwrap();
+        apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        let (id, price): (i64, f64) = conn
+            .query_row("SELECT id, price FROM combos WHERE name = 'Tea for two'", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert!(same_amount(price, 5.0), "combo price {:.2}", price);
+        assert_eq!(
+            combo_sync_items(&conn, id).unwrap(),
+            vec![ComboItemSyncRow { menu_item_name: "Tea".to_string(), quantity: 2 }],
+            "combo items not carried over"
+        );
+
+        let again = apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        assert!(!again.changes.iter().any(|c| c.dataset == "combos"), "combo re-applied: {:?}", again.changes);
+    }
+
+    #[test]
+    fn peer_taxes_are_synced() {
+        let db = TestDb::new();
+        let snapshot = peer_snapshot(&db);
+        let conn = get_db_connection().unwrap();
+        conn.execute("UPDATE taxes SET updated_at = ?1 WHERE name = 'Tax'", params![OLD]).unwrap();
+        let summary = apply_snapshot(&conn, "http://peer.test", &snapshot).unwrap();
+        let tax: f64 = conn.query_row("SELECT percent FROM taxes WHERE name = 'Tax'", [], |row| row.get(0)).unwrap();
+        assert!(same_amount(tax, 8.0), "newer peer tax rate not applied: {}", tax);
+        let city: (f64, String) = conn
+            .query_row("SELECT percent, applies_to FROM taxes WHERE name = 'City tax'", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!(city, (2.0, "rooms".to_string()), "new peer tax not added");
+        assert!(
+            summary.changes.iter().any(|c| c.dataset == "taxes" && c.key == "Tax" && c.action == "updated"),
+            "tax change not reported"
+        );
+    }
+
+    #[test]
+    fn sync_tokens_must_match_exactly() {
+        assert!(token_matches("s3cret-token", "s3cret-token"));
+        assert!(!token_matches("s3cret-tokem", "s3cret-token"));
+        assert!(!token_matches("s3cret", "s3cret-token"));
+        assert!(!token_matches("", "s3cret-token"));
+    }
+}
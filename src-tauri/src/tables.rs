@@ -0,0 +1,179 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{DiningTable, KitchenOrder};
+use crate::order_status::{advance_order_status, load_order};
+use crate::validation::{EMPTY_FIELD, TABLE_HAS_UNPAID_ORDERS, TABLE_NOT_FOUND, TABLE_NUMBER_EXISTS, TABLE_OPEN};
+
+// Open orders are the ones from the current sitting: not closed yet
+const TABLE_COLUMNS: &str = "t.id, t.number, t.capacity, t.status, t.opened_at,
+                             (SELECT COUNT(*) FROM sales s WHERE s.table_id = t.id AND s.status != 'closed' AND s.deleted_at IS NULL),
+                             (SELECT COALESCE(SUM(MAX(s.total_amount - s.amount_paid, 0)), 0.0) FROM sales s
+                              WHERE s.table_id = t.id AND s.status != 'closed' AND s.paid = 0 AND s.deleted_at IS NULL)";
+
+fn map_table(row: &rusqlite::Row) -> rusqlite::Result<DiningTable> {
+    Ok(DiningTable {
+        id: row.get(0)?,
+        number: row.get(1)?,
+        capacity: row.get(2)?,
+        status: row.get(3)?,
+        opened_at: row.get(4)?,
+        open_orders: row.get(5)?,
+        unpaid_amount: row.get(6)?,
+    })
+}
+
+fn load_table(conn: &Connection, table_id: i64) -> Result<DiningTable, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM dining_tables t WHERE t.id = ?1", TABLE_COLUMNS),
+        params![table_id],
+        map_table,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::coded(TABLE_NOT_FOUND, format!("Table #{} not found", table_id)))
+}
+
+fn list_tables(conn: &Connection, only_open: bool) -> Result<Vec<DiningTable>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM dining_tables t
+         WHERE ?1 = 0 OR t.status = 'open'
+         ORDER BY CAST(t.number AS INTEGER), t.number",
+        TABLE_COLUMNS
+    ))?;
+    let tables = stmt
+        .query_map(params![only_open], map_table)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tables)
+}
+
+/// Open the table if it's free. Orders placed at a table call this, so the
+/// first order of a sitting opens it.
+pub fn seat_at_table(conn: &Connection, table_id: i64) -> Result<(), AppError> {
+    load_table(conn, table_id)?;
+    conn.execute(
+        "UPDATE dining_tables SET status = 'open', opened_at = ?1 WHERE id = ?2 AND status = 'free'",
+        params![get_current_timestamp(), table_id],
+    )?;
+    Ok(())
+}
+
+#[command]
+pub fn add_table(number: String, capacity: Option<i64>) -> Result<DiningTable, AppError> {
+    let number = number.trim();
+    if number.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "number", "Table number cannot be empty"));
+    }
+    let capacity = capacity.unwrap_or(4);
+    if capacity < 1 {
+        return Err("A table must seat at least one person".into());
+    }
+
+    let conn = get_db_connection()?;
+    let exists: bool = conn
+        .query_row("SELECT 1 FROM dining_tables WHERE number = ?1", params![number], |_| Ok(true))
+        .optional()?
+        .unwrap_or(false);
+    if exists {
+        return Err(AppError::coded(TABLE_NUMBER_EXISTS, format!("Table {} already exists", number)));
+    }
+
+    conn.execute(
+        "INSERT INTO dining_tables (number, capacity, created_at) VALUES (?1, ?2, ?3)",
+        params![number, capacity, get_current_timestamp()],
+    )?;
+    load_table(&conn, conn.last_insert_rowid())
+}
+
+/// Every table, in number order.
+#[command]
+pub fn get_tables() -> Result<Vec<DiningTable>, AppError> {
+    let conn = get_db_connection()?;
+    list_tables(&conn, false)
+}
+
+/// Tables with a sitting in progress, with what's still owed on each.
+#[command]
+pub fn get_open_tables() -> Result<Vec<DiningTable>, AppError> {
+    let conn = get_db_connection()?;
+    list_tables(&conn, true)
+}
+
+#[command]
+pub fn open_table(table_id: i64) -> Result<DiningTable, AppError> {
+    let conn = get_db_connection()?;
+    seat_at_table(&conn, table_id)?;
+    load_table(&conn, table_id)
+}
+
+/// End the sitting: the table's orders are closed and the table is free
+/// again. Refused while any of those orders is unpaid.
+#[command]
+pub fn close_table(table_id: i64, session_token: Option<String>) -> Result<DiningTable, AppError> {
+    let conn = get_db_connection()?;
+    let table = load_table(&conn, table_id)?;
+    if table.unpaid_amount > 0.0 {
+        return Err(AppError::coded(
+            TABLE_HAS_UNPAID_ORDERS,
+            format!("Table {} still owes {}", table.number, crate::money::Money::load(&conn)?.format(table.unpaid_amount, 2)),
+        ));
+    }
+
+    let actor = audit_actor(session_token.as_deref());
+    let tx = conn.unchecked_transaction()?;
+    let order_ids: Vec<i64> = tx
+        .prepare("SELECT id FROM sales WHERE table_id = ?1 AND status != 'closed' AND deleted_at IS NULL ORDER BY id")?
+        .query_map(params![table_id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    for order_id in order_ids {
+        advance_order_status(&tx, order_id, "closed", &actor)?;
+    }
+    tx.execute(
+        "UPDATE dining_tables SET status = 'free', opened_at = NULL WHERE id = ?1",
+        params![table_id],
+    )?;
+    tx.commit()?;
+
+    load_table(&conn, table_id)
+}
+
+/// Move an order that isn't closed yet to another table, opening that table
+/// if it's free. The old table stays open until it's closed.
+#[command]
+pub fn move_order_to_table(order_id: i64, table_id: i64, session_token: Option<String>) -> Result<KitchenOrder, AppError> {
+    let conn = get_db_connection()?;
+    let order = load_order(&conn, order_id)?;
+    if order.status == "closed" {
+        return Err(format!("Order #{} is closed and can't be moved", order_id).into());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    seat_at_table(&tx, table_id)?;
+    let before = snapshot(&tx, "order", order_id)?;
+    tx.execute("UPDATE sales SET table_id = ?1 WHERE id = ?2", params![table_id, order_id])?;
+    record_change(
+        &tx,
+        &audit_actor(session_token.as_deref()),
+        "order",
+        order_id,
+        "update",
+        before,
+        snapshot(&tx, "order", order_id)?,
+    )?;
+    tx.commit()?;
+
+    load_order(&conn, order_id)
+}
+
+/// Remove a table. Its past orders keep their history but lose the table.
+#[command]
+pub fn delete_table(table_id: i64) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    let table = load_table(&conn, table_id)?;
+    if table.status == "open" {
+        return Err(AppError::coded(TABLE_OPEN, format!("Close table {} before deleting it", table.number)));
+    }
+    conn.execute("DELETE FROM dining_tables WHERE id = ?1", params![table_id])?;
+    Ok(format!("Table {} deleted", table.number))
+}
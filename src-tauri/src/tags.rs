@@ -0,0 +1,306 @@
+use tauri::command;
+use std::collections::HashMap;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::get_db_connection;
+use crate::models::{Tag, TagUsage};
+
+const TAG_NAME_MAX_CHARS: usize = 40;
+
+/// The table behind each taggable entity type.
+fn entity_table(entity_type: &str) -> Result<&'static str, String> {
+    match entity_type {
+        "guest" => Ok("customers"),
+        "order" => Ok("sales"),
+        other => Err(format!("Unknown entity type '{}'. Use 'guest' or 'order'", other)),
+    }
+}
+
+fn validate_tag_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+    if name.chars().count() > TAG_NAME_MAX_CHARS {
+        return Err(format!("Tag name must be at most {} characters", TAG_NAME_MAX_CHARS));
+    }
+    Ok(name.to_string())
+}
+
+fn map_unique_error(e: rusqlite::Error, name: &str) -> String {
+    if e.to_string().contains("UNIQUE constraint failed") {
+        format!("A tag named '{}' already exists", name)
+    } else {
+        e.to_string()
+    }
+}
+
+/// SQL condition restricting `id_column` to entities carrying every tag in `tag_ids`.
+/// Returns an empty string when no filter is requested. Ids are integers, so they are
+/// inlined rather than bound.
+pub fn tag_filter_clause(entity_type: &str, id_column: &str, tag_ids: Option<&[i64]>) -> String {
+    let ids = match tag_ids {
+        Some(ids) if !ids.is_empty() => ids,
+        _ => return String::new(),
+    };
+    let mut unique: Vec<i64> = ids.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+
+    format!(
+        " AND {} IN (SELECT entity_id FROM entity_tags WHERE entity_type = '{}' AND tag_id IN ({})
+                    GROUP BY entity_id HAVING COUNT(DISTINCT tag_id) = {})",
+        id_column,
+        entity_type,
+        unique.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", "),
+        unique.len()
+    )
+}
+
+/// Tags for many entities of one type in a single query, keyed by entity id.
+pub fn tags_by_entity(conn: &Connection, entity_type: &str) -> Result<HashMap<i64, Vec<Tag>>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT et.entity_id, t.id, t.name, t.color
+             FROM entity_tags et JOIN tags t ON t.id = et.tag_id
+             WHERE et.entity_type = ?1
+             ORDER BY t.name",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![entity_type], |row| {
+            Ok((row.get::<_, i64>(0)?, Tag { id: row.get(1)?, name: row.get(2)?, color: row.get(3)? }))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut map: HashMap<i64, Vec<Tag>> = HashMap::new();
+    for row in rows {
+        let (entity_id, tag) = row.map_err(|e| e.to_string())?;
+        map.entry(entity_id).or_default().push(tag);
+    }
+    Ok(map)
+}
+
+pub fn tags_for_entity(conn: &Connection, entity_type: &str, entity_id: i64) -> Result<Vec<Tag>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, t.name, t.color
+             FROM entity_tags et JOIN tags t ON t.id = et.tag_id
+             WHERE et.entity_type = ?1 AND et.entity_id = ?2
+             ORDER BY t.name",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![entity_type, entity_id], |row| {
+            Ok(Tag { id: row.get(0)?, name: row.get(1)?, color: row.get(2)? })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn add_tag(name: String, color: Option<String>) -> Result<Tag, String> {
+    let name = validate_tag_name(&name)?;
+    let color = color.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    conn.execute("INSERT INTO tags (name, color) VALUES (?1, ?2)", params![name, color])
+        .map_err(|e| map_unique_error(e, &name))?;
+
+    Ok(Tag { id: conn.last_insert_rowid(), name, color })
+}
+
+#[command]
+pub fn rename_tag(tag_id: i64, name: String, color: Option<String>) -> Result<Tag, String> {
+    let name = validate_tag_name(&name)?;
+    let color = color.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let affected = conn
+        .execute(
+            "UPDATE tags SET name = ?1, color = COALESCE(?2, color) WHERE id = ?3",
+            params![name, color, tag_id],
+        )
+        .map_err(|e| map_unique_error(e, &name))?;
+    if affected == 0 {
+        return Err("Tag not found".to_string());
+    }
+
+    conn.query_row("SELECT id, name, color FROM tags WHERE id = ?1", params![tag_id], |row| {
+        Ok(Tag { id: row.get(0)?, name: row.get(1)?, color: row.get(2)? })
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Delete a tag. When it is still attached to anything the call fails with the link
+/// count unless `confirm` is set; the links are then removed with the tag.
+#[command]
+pub fn delete_tag(tag_id: i64, confirm: bool) -> Result<usize, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+
+    let links: i64 = conn
+        .query_row("SELECT COUNT(*) FROM entity_tags WHERE tag_id = ?1", params![tag_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if links > 0 && !confirm {
+        return Err(format!("Tag is attached to {} guest(s)/order(s). Confirm to delete it and remove those links", links));
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM entity_tags WHERE tag_id = ?1", params![tag_id])
+        .map_err(|e| e.to_string())?;
+    let affected = tx
+        .execute("DELETE FROM tags WHERE id = ?1", params![tag_id])
+        .map_err(|e| e.to_string())?;
+    if affected == 0 {
+        return Err("Tag not found".to_string());
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(links as usize)
+}
+
+#[command]
+pub fn list_tags() -> Result<Vec<TagUsage>, String> {
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, t.name, t.color,
+                    COALESCE(SUM(CASE WHEN et.entity_type = 'guest' THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN et.entity_type = 'order' THEN 1 ELSE 0 END), 0)
+             FROM tags t
+             LEFT JOIN entity_tags et ON et.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY t.name",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(TagUsage {
+                tag: Tag { id: row.get(0)?, name: row.get(1)?, color: row.get(2)? },
+                guest_count: row.get(3)?,
+                order_count: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[command]
+pub fn tag_entity(entity_type: String, entity_id: i64, tag_id: i64) -> Result<String, String> {
+    let table = entity_table(&entity_type)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+
+    let entity_exists = conn
+        .query_row(&format!("SELECT 1 FROM {} WHERE id = ?1", table), params![entity_id], |_| Ok(()))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if !entity_exists {
+        return Err(format!("Cannot tag {} {}: it does not exist", entity_type, entity_id));
+    }
+
+    let tag_exists = conn
+        .query_row("SELECT 1 FROM tags WHERE id = ?1", params![tag_id], |_| Ok(()))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if !tag_exists {
+        return Err("Tag not found".to_string());
+    }
+
+    conn.execute(
+        "INSERT OR IGNORE INTO entity_tags (tag_id, entity_type, entity_id) VALUES (?1, ?2, ?3)",
+        params![tag_id, entity_type, entity_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok("Tag added".to_string())
+}
+
+#[command]
+pub fn untag_entity(entity_type: String, entity_id: i64, tag_id: i64) -> Result<String, String> {
+    entity_table(&entity_type)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM entity_tags WHERE tag_id = ?1 AND entity_type = ?2 AND entity_id = ?3",
+        params![tag_id, entity_type, entity_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok("Tag removed".to_string())
+}
+
+#[command]
+pub fn get_entity_tags(entity_type: String, entity_id: i64) -> Result<Vec<Tag>, String> {
+    entity_table(&entity_type)?;
+    let conn = get_db_connection().map_err(|e| e.to_string())?;
+    tags_for_entity(&conn, &entity_type, entity_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{get_all_guests, get_food_orders, insert_food_order};
+    use crate::test_support::{check_in, count, order_item, TestDb};
+
+    /// A VIP and a Corporate tag, and an order for a walk-in guest.
+    fn tagged_world() -> (Tag, Tag, i64, i64) {
+        let vip = add_tag("VIP".to_string(), Some("#d4af37".to_string())).unwrap();
+        let corporate = add_tag("Corporate".to_string(), None).unwrap();
+        let guest_id = check_in("Tagged Guest", None);
+        let (order_id, _) =
+            insert_food_order(Some(guest_id), "guest".to_string(), None, vec![order_item("Tea", 2.5, 1)], None, None, None).unwrap();
+        (vip, corporate, guest_id, order_id)
+    }
+
+    fn lists_order(order_id: i64, tag_ids: Vec<i64>) -> bool {
+        get_food_orders(Some(tag_ids)).unwrap().iter().any(|o| o.id == order_id)
+    }
+
+    #[test]
+    fn filtering_by_tags_needs_every_tag() {
+        let _db = TestDb::new();
+        let (vip, corporate, guest_id, order_id) = tagged_world();
+        tag_entity("order".to_string(), order_id, vip.id).unwrap();
+        assert!(lists_order(order_id, vec![vip.id]), "order tagged VIP missing from the VIP filter");
+        assert!(!lists_order(order_id, vec![vip.id, corporate.id]), "order with only VIP listed under VIP and Corporate");
+        tag_entity("order".to_string(), order_id, corporate.id).unwrap();
+        assert!(lists_order(order_id, vec![vip.id, corporate.id]), "order with both tags missing from the VIP and Corporate filter");
+
+        tag_entity("guest".to_string(), guest_id, vip.id).unwrap();
+        let guests = get_all_guests(Some(vec![vip.id, corporate.id])).unwrap();
+        assert!(guests.iter().all(|g| g.id != guest_id), "guest with only VIP listed under VIP and Corporate");
+        let guests = get_all_guests(Some(vec![vip.id])).unwrap();
+        assert!(
+            guests.iter().any(|g| g.id == guest_id && g.tags.iter().any(|t| t.name == "VIP")),
+            "guest tagged VIP missing from the VIP filter or without its tag"
+        );
+    }
+
+    #[test]
+    fn only_existing_entities_can_be_tagged() {
+        let _db = TestDb::new();
+        let vip = add_tag("VIP".to_string(), None).unwrap();
+        let missing = tag_entity("guest".to_string(), i64::MAX, vip.id);
+        assert!(
+            missing.as_ref().is_err_and(|e| e.contains("Cannot tag guest") && e.contains("does not exist")),
+            "tagging a nonexistent guest gave {:?}", missing
+        );
+    }
+
+    #[test]
+    fn deleting_a_tag_in_use_needs_confirming_and_removes_its_links() {
+        let _db = TestDb::new();
+        let (vip, corporate, guest_id, order_id) = tagged_world();
+        tag_entity("order".to_string(), order_id, vip.id).unwrap();
+        tag_entity("order".to_string(), order_id, corporate.id).unwrap();
+        tag_entity("guest".to_string(), guest_id, vip.id).unwrap();
+        let unconfirmed = delete_tag(vip.id, false);
+        assert!(unconfirmed.as_ref().is_err_and(|e| e.contains("attached to 2")), "deleting a tag in use without confirmation gave {:?}", unconfirmed);
+        assert_eq!(delete_tag(vip.id, true).unwrap(), 2, "deleting the VIP tag removed the wrong number of links");
+        assert_eq!(count("SELECT COUNT(*) FROM entity_tags WHERE tag_id = ?1", vip.id), 0, "links to the deleted tag remain");
+        assert!(lists_order(order_id, vec![corporate.id]), "deleting one tag removed the order's other tag");
+    }
+}
@@ -0,0 +1,250 @@
+use tauri::command;
+use std::fs;
+use rusqlite::{params, Connection};
+use serde_json::json;
+use crate::db::{get_current_timestamp, get_db_connection, run_blocking};
+use crate::errors::AppError;
+use crate::export::escape_csv;
+use crate::models::{ExpenseTaxLine, TaxReport, TaxReportLine};
+use crate::money::Money;
+use crate::print_templates::html_escape;
+use crate::repository::get_setting_or;
+use crate::simple_commands::to_cents;
+use crate::validation::{validate_date_format, INVALID_DATE_FORMAT};
+
+pub(crate) fn validate_period(start_date: &str, end_date: &str) -> Result<(), AppError> {
+    validate_date_format(start_date)?;
+    validate_date_format(end_date)?;
+    if start_date > end_date {
+        return Err(AppError::invalid(INVALID_DATE_FORMAT, "end_date", "End date is before the start date"));
+    }
+    Ok(())
+}
+
+/// Output tax from the taxes recorded on checkout bills and settled walk-in
+/// orders between the two dates (inclusive), less the input tax entered on
+/// business expenses. A walk-in order falls on the day it was taxed.
+pub fn build_tax_report(conn: &Connection, start_date: &str, end_date: &str) -> Result<TaxReport, AppError> {
+    let (checkouts, checkout_sales): (i64, f64) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(checkout_total), 0) FROM customers
+         WHERE status = 'checked_out' AND check_out BETWEEN ?1 AND ?2",
+        params![start_date, end_date],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    // Orders charged to a guest are already in their checkout total
+    let order_sales: f64 = conn.query_row(
+        "SELECT COALESCE(SUM(total_amount), 0) FROM sales s
+         WHERE guest_id IS NULL AND paid = 1 AND deleted_at IS NULL
+           AND COALESCE((SELECT MIN(charged_on) FROM tax_charges WHERE order_id = s.id), date(created_at)) BETWEEN ?1 AND ?2",
+        params![start_date, end_date],
+        |row| row.get(0),
+    )?;
+    let gross_sales = to_cents(checkout_sales) + to_cents(order_sales);
+
+    let mut stmt = conn.prepare(
+        "SELECT tax_id, name, percent, applies_to, SUM(taxable), SUM(amount), COUNT(DISTINCT guest_id) + COUNT(DISTINCT order_id)
+         FROM tax_charges
+         WHERE charged_on BETWEEN ?1 AND ?2
+           AND (order_id IS NULL OR order_id IN (SELECT id FROM sales WHERE paid = 1 AND deleted_at IS NULL))
+         GROUP BY tax_id, name, percent, applies_to
+         ORDER BY name COLLATE NOCASE, percent",
+    )?;
+    let taxes = stmt
+        .query_map(params![start_date, end_date], |row| {
+            Ok(TaxReportLine {
+                tax_id: row.get(0)?,
+                name: row.get(1)?,
+                percent: row.get(2)?,
+                applies_to: row.get(3)?,
+                taxable: to_cents(row.get(4)?) as f64 / 100.0,
+                collected: to_cents(row.get(5)?) as f64 / 100.0,
+                bills: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let total_collected: i64 = taxes.iter().map(|t| to_cents(t.collected)).sum();
+
+    let mut stmt = conn.prepare(
+        "SELECT TRIM(category), SUM(amount), SUM(tax_amount) FROM expenses
+         WHERE is_drawing = 0 AND deleted_at IS NULL AND tax_amount > 0 AND date BETWEEN ?1 AND ?2
+         GROUP BY TRIM(category) ORDER BY TRIM(category) COLLATE NOCASE",
+    )?;
+    let expenses = stmt
+        .query_map(params![start_date, end_date], |row| {
+            Ok(ExpenseTaxLine {
+                category: row.get(0)?,
+                amount: to_cents(row.get(1)?) as f64 / 100.0,
+                tax: to_cents(row.get(2)?) as f64 / 100.0,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let total_expense_tax: i64 = expenses.iter().map(|e| to_cents(e.tax)).sum();
+
+    Ok(TaxReport {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        checkouts,
+        gross_sales: gross_sales as f64 / 100.0,
+        net_sales: (gross_sales - total_collected) as f64 / 100.0,
+        taxes,
+        total_collected: total_collected as f64 / 100.0,
+        expenses,
+        total_expense_tax: total_expense_tax as f64 / 100.0,
+        net_payable: (total_collected - total_expense_tax) as f64 / 100.0,
+        generated_at: get_current_timestamp(),
+    })
+}
+
+/// The VAT/GST return for a period: tax collected per rate and tax paid on expenses.
+#[command]
+pub async fn tax_report(start_date: String, end_date: String) -> Result<TaxReport, AppError> {
+    validate_period(&start_date, &end_date)?;
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        build_tax_report(&conn, &start_date, &end_date)
+    })
+    .await
+}
+
+fn tax_report_csv(report: &TaxReport, currency: &str) -> String {
+    let mut out = format!("Tax return,{} to {}\nCurrency,{}\n\n", report.start_date, report.end_date, currency);
+    out.push_str("Summary\nItem,Value\n");
+    out.push_str(&format!("Checkouts,{}\n", report.checkouts));
+    out.push_str(&format!("Gross sales,{:.2}\nNet sales,{:.2}\n", report.gross_sales, report.net_sales));
+    out.push_str(&format!("Tax collected,{:.2}\nTax on expenses,{:.2}\n", report.total_collected, report.total_expense_tax));
+    out.push_str(&format!("Net tax payable,{:.2}\n\n", report.net_payable));
+
+    out.push_str("Tax collected\nTax,Rate %,Applies to,Bills,Taxable,Collected\n");
+    for tax in &report.taxes {
+        out.push_str(&format!(
+            "{},{},{},{},{:.2},{:.2}\n",
+            escape_csv(&tax.name),
+            tax.percent,
+            tax.applies_to,
+            tax.bills,
+            tax.taxable,
+            tax.collected
+        ));
+    }
+    out.push('\n');
+
+    out.push_str("Tax on expenses\nCategory,Amount,Tax\n");
+    for expense in &report.expenses {
+        out.push_str(&format!("{},{:.2},{:.2}\n", escape_csv(&expense.category), expense.amount, expense.tax));
+    }
+    out.push_str(&format!("\nPrepared at,{}\n", report.generated_at));
+    out
+}
+
+/// The report rendered through the tax_report print template.
+fn tax_report_html(conn: &Connection, report: &TaxReport) -> Result<String, String> {
+    let money = Money::load(conn)?;
+    let tax_rows: String = if report.taxes.is_empty() {
+        r#"<tr><td colspan="5" class="empty">No tax charged</td></tr>"#.to_string()
+    } else {
+        report
+            .taxes
+            .iter()
+            .map(|t| {
+                format!(
+                    "<tr><td>{}</td><td class=\"num\">{}%</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                    html_escape(&t.name),
+                    t.percent,
+                    html_escape(&t.applies_to),
+                    money.format(t.taxable, 2),
+                    money.format(t.collected, 2)
+                )
+            })
+            .collect()
+    };
+    let expense_rows: String = if report.expenses.is_empty() {
+        r#"<tr><td colspan="3" class="empty">No tax on expenses</td></tr>"#.to_string()
+    } else {
+        report
+            .expenses
+            .iter()
+            .map(|e| {
+                format!(
+                    "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                    html_escape(&e.category),
+                    money.format(e.amount, 2),
+                    money.format(e.tax, 2)
+                )
+            })
+            .collect()
+    };
+
+    crate::templates::render(
+        "tax_report",
+        &json!({
+            "business_name": get_setting_or(conn, "business_name", "Business Manager")?,
+            "business_contact": crate::print_templates::business_contact(conn)?,
+            "start_date": report.start_date,
+            "end_date": report.end_date,
+            "checkouts": report.checkouts,
+            "gross_sales": money.format(report.gross_sales, 2),
+            "net_sales": money.format(report.net_sales, 2),
+            "total_collected": money.format(report.total_collected, 2),
+            "total_expense_tax": money.format(report.total_expense_tax, 2),
+            "net_payable": money.format(report.net_payable, 2),
+            "tax_rows": tax_rows,
+            "expense_rows": expense_rows,
+            "generated_at": report.generated_at,
+        }),
+    )
+}
+
+/// Save the tax return for the period as "csv" or "pdf" in the exports folder
+/// and return the file's path. PDFs need Chrome, Edge or Chromium installed.
+#[command]
+pub async fn export_tax_report(start_date: String, end_date: String, format: String) -> Result<String, AppError> {
+    validate_period(&start_date, &end_date)?;
+    run_blocking(move || {
+        let conn = get_db_connection()?;
+        let report = build_tax_report(&conn, &start_date, &end_date)?;
+        let stem = format!("tax_report_{}_{}", start_date, end_date);
+
+        let path = match format.trim().to_lowercase().as_str() {
+            "csv" => {
+                let path = crate::export_jobs::default_export_path(&stem, "csv")?;
+                let csv = tax_report_csv(&report, Money::load(&conn)?.code());
+                fs::write(&path, csv).map_err(|e| format!("Failed to write tax report: {}", e))?;
+                path
+            }
+            "pdf" => {
+                let path = crate::export_jobs::default_export_path(&stem, "pdf")?;
+                let html_path = path.with_extension("html");
+                fs::write(&html_path, tax_report_html(&conn, &report)?).map_err(|e| format!("Failed to write tax report: {}", e))?;
+                let rendered = crate::print_templates::render_html_to_pdf(&html_path, &path);
+                let _ = fs::remove_file(&html_path);
+                rendered?;
+                path
+            }
+            other => return Err(format!("Unknown format '{}'; use csv or pdf", other).into()),
+        };
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{insert_food_order, mark_order_paid};
+    use crate::test_support::{order_item, same_amount, today, TestDb};
+
+    #[test]
+    fn settled_walk_in_sales_and_their_tax_are_reported() {
+        let _db = TestDb::new();
+        let conn = get_db_connection().unwrap();
+        crate::taxes::set_single_rate(&conn, 10.0).unwrap();
+        let walk_in = || insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Tea", 10.0, 2)], None, None, None).unwrap().0;
+        mark_order_paid(walk_in(), None).unwrap();
+        walk_in(); // not paid yet, so not a sale
+        let report = build_tax_report(&conn, &today(), &today()).unwrap();
+        assert!(same_amount(report.gross_sales, 22.0), "gross sales are {:.2}, expected the paid order's 22.00", report.gross_sales);
+        assert!(same_amount(report.total_collected, 2.0), "tax collected is {:.2}, expected 2.00", report.total_collected);
+        let line = report.taxes.first().expect("walk-in tax not on the return");
+        assert!(line.bills == 1 && same_amount(line.taxable, 20.0), "walk-in tax reported on {} bills of {:.2}", line.bills, line.taxable);
+    }
+}
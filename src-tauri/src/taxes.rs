@@ -0,0 +1,315 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{Tax, TaxLine};
+use crate::validation::{EMPTY_FIELD, NEGATIVE_AMOUNT};
+use crate::repository::{get_parsed_setting, set_setting};
+
+const APPLIES_TO: &[&str] = &["rooms", "food", "all"];
+
+const TAX_COLUMNS: &str = "id, name, percent, applies_to, is_active";
+
+fn map_tax(row: &rusqlite::Row) -> rusqlite::Result<Tax> {
+    Ok(Tax {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        percent: row.get(2)?,
+        applies_to: row.get(3)?,
+        is_active: row.get(4)?,
+    })
+}
+
+fn load_tax(conn: &Connection, tax_id: i64) -> Result<Tax, AppError> {
+    conn.query_row(&format!("SELECT {} FROM taxes WHERE id = ?1", TAX_COLUMNS), params![tax_id], map_tax)
+        .optional()?
+        .ok_or_else(|| format!("Tax #{} not found", tax_id).into())
+}
+
+fn validate_percent(percent: f64) -> Result<(), AppError> {
+    if !percent.is_finite() || !(0.0..=100.0).contains(&percent) {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "percent", "Tax percent must be between 0 and 100"));
+    }
+    Ok(())
+}
+
+fn parse_applies_to(applies_to: &str) -> Result<String, AppError> {
+    let applies_to = applies_to.trim().to_lowercase();
+    if !APPLIES_TO.contains(&applies_to.as_str()) {
+        return Err(format!("applies_to must be one of: {}", APPLIES_TO.join(", ")).into());
+    }
+    Ok(applies_to)
+}
+
+/// The master switch from the tax settings; taxes are on until it's turned off.
+pub fn tax_enabled(conn: &Connection) -> Result<bool, String> {
+    Ok(get_parsed_setting::<bool>(conn, "tax_enabled")?.unwrap_or(true))
+}
+
+/// Combined percent of the active taxes that apply to everything.
+pub fn combined_rate(conn: &Connection) -> Result<f64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(percent), 0) FROM taxes WHERE is_active = 1 AND applies_to = 'all'",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Backs the single-rate setting from setup and the catalog screen: with no
+/// taxes yet it adds one called "Tax"; with exactly one active tax that
+/// applies to everything it changes that one. Anything more involved has to
+/// be edited tax by tax.
+pub fn set_single_rate(conn: &Connection, percent: f64) -> Result<(), AppError> {
+    validate_percent(percent)?;
+    let ids: Vec<i64> = {
+        let mut stmt = conn.prepare("SELECT id FROM taxes WHERE is_active = 1 AND applies_to = 'all'")?;
+        let ids = stmt.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        ids
+    };
+    let total: i64 = conn.query_row("SELECT COUNT(*) FROM taxes", [], |row| row.get(0))?;
+    match ids.as_slice() {
+        [] if total == 0 => {
+            conn.execute(
+                "INSERT INTO taxes (name, percent, applies_to, created_at, updated_at) VALUES ('Tax', ?1, 'all', ?2, ?2)",
+                params![percent, get_current_timestamp()],
+            )?;
+        }
+        [tax_id] => {
+            conn.execute(
+                "UPDATE taxes SET percent = ?1, updated_at = ?2 WHERE id = ?3",
+                params![percent, get_current_timestamp(), tax_id],
+            )?;
+        }
+        _ => return Err("Several taxes are set up; change them individually in the tax list".into()),
+    }
+    Ok(())
+}
+
+/// Whether menu prices and room rates already include tax. Off by default,
+/// so tax is added on top of the bill.
+pub fn prices_include_tax(conn: &Connection) -> Result<bool, String> {
+    Ok(get_parsed_setting::<bool>(conn, "prices_include_tax")?.unwrap_or(false))
+}
+
+/// Each active tax on a bill, with rooms taxes charged on `rooms` (stay,
+/// extras and adjustments billed to the room), food taxes on `food`, and the
+/// rest on both. `discounted_total` is the bill after discounts; the discount
+/// is spread over rooms and food in proportion. When prices include tax, the
+/// taxes on each part are taken back out of it rather than added on top.
+/// Empty when tax is switched off.
+pub fn bill_taxes(conn: &Connection, rooms: f64, food: f64, discounted_total: f64) -> Result<Vec<TaxLine>, String> {
+    if !tax_enabled(conn)? {
+        return Ok(Vec::new());
+    }
+    let included = prices_include_tax(conn)?;
+    let before_discount = rooms + food;
+    let factor = if before_discount > 0.0 { discounted_total / before_discount } else { 0.0 };
+
+    let mut stmt = conn
+        .prepare(&format!("SELECT {} FROM taxes WHERE is_active = 1 AND percent > 0 ORDER BY id", TAX_COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let taxes = stmt
+        .query_map([], map_tax)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    // The price before tax of each part
+    let net_of = |amount: f64, part: &str| {
+        if !included {
+            return amount;
+        }
+        let combined: f64 = taxes.iter().filter(|t| t.applies_to == part || t.applies_to == "all").map(|t| t.percent).sum();
+        amount * 100.0 / (100.0 + combined)
+    };
+    let rooms = net_of(rooms * factor, "rooms");
+    let food = net_of(food * factor, "food");
+
+    Ok(taxes
+        .iter()
+        .map(|tax| {
+            let taxable = match tax.applies_to.as_str() {
+                "rooms" => rooms,
+                "food" => food,
+                _ => rooms + food,
+            };
+            TaxLine {
+                tax_id: tax.id,
+                name: tax.name.clone(),
+                percent: tax.percent,
+                applies_to: tax.applies_to.clone(),
+                taxable,
+                amount: taxable * tax.percent / 100.0,
+                included,
+            }
+        })
+        .collect())
+}
+
+/// Keep the taxes charged on a checkout bill, so the tax return reports what
+/// was actually charged even after rates change.
+pub fn record_bill_taxes(conn: &Connection, guest_id: i64, charged_on: &str, lines: &[TaxLine]) -> Result<(), String> {
+    insert_tax_charges(conn, Some(guest_id), None, charged_on, lines)
+}
+
+/// Tax a walk-in or counter order's food when it is placed, since it never
+/// reaches a checkout bill: the taxes are kept against the order (replacing
+/// any from before) and `sales.tax_amount` holds what was added on top, which
+/// is also returned. Nothing is added when prices include tax.
+pub fn record_order_taxes(conn: &Connection, order_id: i64, food: f64, charged_on: &str) -> Result<f64, String> {
+    conn.execute("DELETE FROM tax_charges WHERE order_id = ?1", params![order_id]).map_err(|e| e.to_string())?;
+    let lines = bill_taxes(conn, 0.0, food, food)?;
+    insert_tax_charges(conn, None, Some(order_id), charged_on, &lines)?;
+    let added = crate::simple_commands::to_cents(tax_to_add(&lines)) as f64 / 100.0;
+    conn.execute("UPDATE sales SET tax_amount = ?1 WHERE id = ?2", params![added, order_id]).map_err(|e| e.to_string())?;
+    Ok(added)
+}
+
+/// The taxes kept against an order by `record_order_taxes`, in the order they were charged.
+pub fn order_tax_lines(conn: &Connection, order_id: i64) -> Result<Vec<TaxLine>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(tax_id, 0), name, percent, applies_to, taxable, amount, included
+             FROM tax_charges WHERE order_id = ?1 ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+    let lines = stmt
+        .query_map(params![order_id], |row| {
+            Ok(TaxLine {
+                tax_id: row.get(0)?,
+                name: row.get(1)?,
+                percent: row.get(2)?,
+                applies_to: row.get(3)?,
+                taxable: row.get(4)?,
+                amount: row.get(5)?,
+                included: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    lines.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn insert_tax_charges(
+    conn: &Connection,
+    guest_id: Option<i64>,
+    order_id: Option<i64>,
+    charged_on: &str,
+    lines: &[TaxLine],
+) -> Result<(), String> {
+    for line in lines.iter().filter(|t| t.amount > 0.0) {
+        conn.execute(
+            "INSERT INTO tax_charges (guest_id, order_id, tax_id, name, percent, applies_to, taxable, amount, included, charged_on)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                guest_id,
+                order_id,
+                line.tax_id,
+                line.name,
+                line.percent,
+                line.applies_to,
+                crate::simple_commands::to_cents(line.taxable) as f64 / 100.0,
+                crate::simple_commands::to_cents(line.amount) as f64 / 100.0,
+                line.included,
+                charged_on
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Tax to add on top of a bill; tax already included in the prices isn't.
+pub fn tax_to_add(lines: &[TaxLine]) -> f64 {
+    lines.iter().filter(|t| !t.included).map(|t| t.amount).sum()
+}
+
+#[command]
+pub fn set_prices_include_tax(enabled: bool) -> Result<bool, AppError> {
+    let conn = get_db_connection()?;
+    set_setting(&conn, "prices_include_tax", &enabled.to_string())?;
+    Ok(enabled)
+}
+
+#[command]
+pub fn get_prices_include_tax() -> Result<bool, AppError> {
+    let conn = get_db_connection()?;
+    Ok(prices_include_tax(&conn)?)
+}
+
+/// Add a named tax, e.g. GST on rooms and a sales tax on food.
+#[command]
+pub fn add_tax(name: String, percent: f64, applies_to: String) -> Result<Tax, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "name", "Tax name cannot be empty"));
+    }
+    validate_percent(percent)?;
+    let applies_to = parse_applies_to(&applies_to)?;
+
+    let conn = get_db_connection()?;
+    conn.execute(
+        "INSERT INTO taxes (name, percent, applies_to, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?4)",
+        params![name, percent, applies_to, get_current_timestamp()],
+    )?;
+    load_tax(&conn, conn.last_insert_rowid())
+}
+
+#[command]
+pub fn get_taxes() -> Result<Vec<Tax>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM taxes ORDER BY id", TAX_COLUMNS))?;
+    let taxes = stmt.query_map([], map_tax)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(taxes)
+}
+
+/// Change a tax; fields left out keep their current value. Inactive taxes
+/// stay in the list but aren't charged.
+#[command]
+pub fn update_tax(
+    tax_id: i64,
+    name: Option<String>,
+    percent: Option<f64>,
+    applies_to: Option<String>,
+    is_active: Option<bool>,
+) -> Result<Tax, AppError> {
+    let conn = get_db_connection()?;
+    let current = load_tax(&conn, tax_id)?;
+
+    let name = match name {
+        Some(name) if name.trim().is_empty() => {
+            return Err(AppError::invalid(EMPTY_FIELD, "name", "Tax name cannot be empty"));
+        }
+        Some(name) => name.trim().to_string(),
+        None => current.name,
+    };
+    if let Some(percent) = percent {
+        validate_percent(percent)?;
+    }
+    let applies_to = match applies_to {
+        Some(applies_to) => parse_applies_to(&applies_to)?,
+        None => current.applies_to,
+    };
+
+    conn.execute(
+        "UPDATE taxes SET name = ?1, percent = ?2, applies_to = ?3, is_active = ?4, updated_at = ?5 WHERE id = ?6",
+        params![
+            name,
+            percent.unwrap_or(current.percent),
+            applies_to,
+            is_active.unwrap_or(current.is_active),
+            get_current_timestamp(),
+            tax_id
+        ],
+    )?;
+    load_tax(&conn, tax_id)
+}
+
+#[command]
+pub fn delete_tax(tax_id: i64) -> Result<String, AppError> {
+    let conn = get_db_connection()?;
+    if conn.execute("DELETE FROM taxes WHERE id = ?1", params![tax_id])? == 0 {
+        return Err(format!("Tax #{} not found", tax_id).into());
+    }
+    Ok("Tax deleted".to_string())
+}
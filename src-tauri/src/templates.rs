@@ -0,0 +1,328 @@
+use tauri::command;
+use std::fs;
+use std::path::PathBuf;
+use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext};
+use serde_json::{json, Value};
+use crate::errors::AppError;
+use crate::models::{PrintTemplateInfo, PrintTemplateSource};
+
+struct TemplateDef {
+    name: &'static str,
+    title: &'static str,
+    description: &'static str,
+    source: &'static str,
+}
+
+// The built-in layouts. A copy saved under app data overrides one of these.
+const TEMPLATES: &[TemplateDef] = &[
+    TemplateDef {
+        name: "order_receipt",
+        title: "Order receipt",
+        description: "Receipt for a food order",
+        source: include_str!("../templates/order_receipt.hbs"),
+    },
+    TemplateDef {
+        name: "final_invoice",
+        title: "Final invoice",
+        description: "Guest bill at checkout",
+        source: include_str!("../templates/final_invoice.hbs"),
+    },
+    TemplateDef {
+        name: "kitchen_ticket",
+        title: "Kitchen ticket",
+        description: "Ticket sent to the kitchen printer",
+        source: include_str!("../templates/kitchen_ticket.hbs"),
+    },
+    TemplateDef {
+        name: "registration_card",
+        title: "Registration card",
+        description: "Guest registration card signed at check-in",
+        source: include_str!("../templates/registration_card.hbs"),
+    },
+    TemplateDef {
+        name: "night_audit",
+        title: "Night audit",
+        description: "End-of-day report for the owner's sign-off",
+        source: include_str!("../templates/night_audit.hbs"),
+    },
+    TemplateDef {
+        name: "tax_report",
+        title: "Tax return",
+        description: "VAT/GST collected and paid for a period, for filing",
+        source: include_str!("../templates/tax_report.hbs"),
+    },
+    TemplateDef {
+        name: "guest_registration",
+        title: "Guest register",
+        description: "Guests who stayed in a period, for the police or foreigners' registration office",
+        source: include_str!("../templates/guest_registration.hbs"),
+    },
+    TemplateDef {
+        name: "group_invoice",
+        title: "Group invoice",
+        description: "One bill for every room of a group booking",
+        source: include_str!("../templates/group_invoice.hbs"),
+    },
+];
+
+fn find_template(name: &str) -> Result<&'static TemplateDef, String> {
+    TEMPLATES
+        .iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| format!("Unknown template '{}'", name))
+}
+
+fn override_path(name: &str) -> Result<PathBuf, String> {
+    Ok(crate::media::media_dir(&["templates"])?.join(format!("{}.hbs", name)))
+}
+
+fn override_source(name: &str) -> Option<String> {
+    fs::read_to_string(override_path(name).ok()?).ok()
+}
+
+/// `{{t "Grand Total"}}`: a label in the printout's language, from the
+/// `i18n` map in the context; labels without a translation print as written.
+fn translate_helper(
+    h: &Helper,
+    _: &Handlebars,
+    ctx: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let label = h.param(0).and_then(|p| p.value().as_str()).unwrap_or("");
+    let text = ctx.data().get("i18n").and_then(|m| m.get(label)).and_then(|v| v.as_str()).unwrap_or(label);
+    out.write(&handlebars::html_escape(text))?;
+    Ok(())
+}
+
+fn render_source(source: &str, context: &Value) -> Result<String, String> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_helper("t", Box::new(translate_helper));
+    handlebars
+        .render_template(source, context)
+        .map_err(|e| format!("Template error: {}", e))
+}
+
+/// Render a print template with `context`, using the user's copy when there is
+/// one. A copy that no longer renders falls back to the built-in layout so
+/// printing never breaks over a template edit.
+pub fn render(name: &str, context: &Value) -> Result<String, String> {
+    let def = find_template(name)?;
+    if let Some(source) = override_source(name) {
+        match render_source(&source, context) {
+            Ok(html) => return Ok(html),
+            Err(e) => println!("⚠️ Custom {} template failed, using the built-in one: {}", name, e),
+        }
+    }
+    render_source(def.source, context)
+}
+
+/// Made-up values for every field a template is given, for previews.
+fn sample_context(name: &str) -> Value {
+    let business = json!({
+        "business_name": "Sample Hotel",
+        "business_contact": "1 Main Street | Tel: 555-0100 | desk@example.com",
+        "logo_html": "",
+        "receipt_header_html": "<div class=\"brand-message\">Welcome!</div>",
+        "receipt_footer_html": "<div class=\"brand-message\">See you again soon.</div>",
+    });
+    let specific = match name {
+        "order_receipt" => json!({
+            "order_id": 1024,
+            "payment_color": "#28a745",
+            "order_date": "January 15, 2025 at 07:30 PM",
+            "customer": "Jane Guest",
+            "room": "101",
+            "payment_status": "✓ PAID",
+            "items_html": "<tr><td>Club sandwich</td><td>2</td><td>$8.50</td><td>$17.00</td></tr>",
+            "service_charge_row": "<tr><td colspan=\"3\">Service charge (10.0%)</td><td class=\"text-right\">$1.70</td></tr>",
+            "added_tax_rows": "",
+            "voucher_rows": "",
+            "rounding_row": "",
+            "total": "$18.70",
+            "tax_rows": "",
+            "fiscal_html": "",
+            "qr_code_html": "",
+            "generated_at": "January 15, 2025 at 07:31 PM",
+        }),
+        "final_invoice" => json!({
+            "customer": "Jane Guest (VIP)",
+            "invoice_date": "17-01-2025",
+            "invoice_time": "11:00 AM",
+            "room_number": "101",
+            "check_in": "2025-01-15",
+            "check_out": "2025-01-17",
+            "holds_html": "",
+            "days": 2,
+            "daily_rate": "$100",
+            "room_total": "$200",
+            "room_rows": "<div class=\"table-row\"><div class=\"table-cell\">Room 101 - Accommodation</div><div class=\"table-cell center\">2</div><div class=\"table-cell center\">$100</div><div class=\"table-cell right\">$200</div></div>",
+            "food_rows": "<div class=\"table-row\"><div class=\"table-cell\"><strong>Club sandwich [UNPAID]</strong></div><div class=\"table-cell center\">2</div><div class=\"table-cell center\">$9</div><div class=\"table-cell right\">$17</div></div>",
+            "charges_html": "",
+            "adjustments_html": "",
+            "food_total": "$17",
+            "service_charge_row": "",
+            "charges_row": "",
+            "adjustments_row": "",
+            "subtotal": "$217",
+            "discount_row": "",
+            "voucher_rows": "",
+            "tax_rows": "<div class=\"total-row\"><span>Tax (5.0%):</span><span>$11</span></div>",
+            "rounding_row": "",
+            "grand_total": "$228",
+            "deposit_rows": "",
+            "signature_html": "",
+            "fiscal_html": "",
+            "qr_code_html": "",
+        }),
+        "kitchen_ticket" => json!({
+            "order_id": 1024,
+            "serve_to": "Table 4",
+            "time": "19:30",
+            "items_html": "<div class=\"item\"><span class=\"qty\">2&times;</span> Club sandwich</div><div class=\"attribute\">&rarr; No onions</div>",
+            "notes_html": "",
+        }),
+        "night_audit" => json!({
+            "date": "2025-01-15",
+            "rooms_occupied": 3,
+            "total_rooms": 10,
+            "occupancy": "30.0%",
+            "check_in_count": 1,
+            "check_out_count": 1,
+            "room_revenue": "$300",
+            "food_orders": 4,
+            "food_revenue": "$62",
+            "payments_total": "$280",
+            "outstanding_total": "$45",
+            "check_in_rows": "<tr><td>Jane Guest</td><td>101</td><td>2025-01-15 &ndash; </td><td class=\"num\">$100</td><td class=\"num\">$117</td></tr>",
+            "check_out_rows": "<tr><td>John Smith</td><td>104</td><td>2025-01-12 &ndash; 2025-01-15</td><td class=\"num\">$300</td><td class=\"num\"></td></tr>",
+            "in_house_rows": "<tr><td>Jane Guest</td><td>101</td><td>2025-01-15 &ndash; </td><td class=\"num\">$100</td><td class=\"num\">$117</td></tr>",
+            "payment_rows": "<tr><td>cash</td><td class=\"num\">$62</td><td class=\"num\">$218</td><td class=\"num\">$280</td></tr>",
+            "outstanding_rows": "<tr><td>order</td><td>Jane Guest</td><td>2025-01-15</td><td class=\"num\">$17</td></tr>",
+            "generated_at": "2025-01-15 23:59:00",
+        }),
+        "tax_report" => json!({
+            "start_date": "2025-01-01",
+            "end_date": "2025-03-31",
+            "checkouts": 42,
+            "gross_sales": "$11,500",
+            "net_sales": "$10,000",
+            "total_collected": "$1,500",
+            "total_expense_tax": "$320",
+            "net_payable": "$1,180",
+            "tax_rows": "<tr><td>GST</td><td class=\"num\">15%</td><td>all</td><td class=\"num\">$10,000</td><td class=\"num\">$1,500</td></tr>",
+            "expense_rows": "<tr><td>Groceries</td><td class=\"num\">$2,453</td><td class=\"num\">$320</td></tr>",
+            "generated_at": "2025-04-01 09:00:00",
+        }),
+        "guest_registration" => json!({
+            "start_date": "2025-01-01",
+            "end_date": "2025-01-31",
+            "guest_count": 2,
+            "missing_count": 1,
+            "guest_rows": "<tr><td class=\"num\">1</td><td>Jane Guest</td><td>British</td><td>P1234567</td><td>+44 7700 900123</td><td>101</td><td>2025-01-15</td><td>2025-01-17</td><td class=\"num\">2</td><td class=\"num\">0</td></tr><tr class=\"missing\"><td class=\"num\">2</td><td>John Smith</td><td></td><td></td><td>555-0123</td><td>104</td><td>2025-01-20</td><td></td><td class=\"num\">1</td><td class=\"num\">0</td></tr>",
+            "generated_at": "2025-02-01 09:00:00",
+        }),
+        "group_invoice" => json!({
+            "group_name": "Khan wedding party",
+            "contact_name": "Imran Khan",
+            "contact_phone": "0300 1234567",
+            "room_count": 2,
+            "room_rows": "<tr><td>101</td><td>Imran Khan</td><td>2025-01-15 &ndash; 2025-01-17</td><td class=\"num\">$230</td><td class=\"num\">$100</td><td class=\"num\">$130</td></tr><tr><td>102</td><td>Sara Khan</td><td>2025-01-15 &ndash; 2025-01-17</td><td class=\"num\">$200</td><td class=\"num\">$0</td><td class=\"num\">$200</td></tr>",
+            "total_charges": "$430",
+            "total_paid": "$100",
+            "total_balance": "$330",
+            "settlement": "Amount due from the group: <strong>$330</strong>",
+            "generated_at": "2025-01-17 11:00:00",
+        }),
+        _ => json!({
+            "guest_name": "Jane Guest",
+            "phone": "555-0123",
+            "room": "101",
+            "check_in": "2025-01-15",
+            "check_out": "2025-01-17",
+            "house_rules_html": "<div class=\"rules\"><h3>House Rules</h3>Quiet hours after 10pm.</div>",
+            "signature_html": "<div class=\"signature-line\"></div><div>Guest signature</div>",
+        }),
+    };
+    let mut context = business;
+    if let (Some(context), Value::Object(specific)) = (context.as_object_mut(), specific) {
+        context.extend(specific);
+    }
+    context
+}
+
+fn template_info(def: &TemplateDef) -> Result<PrintTemplateInfo, String> {
+    let path = override_path(def.name)?;
+    let overridden = path.is_file();
+    Ok(PrintTemplateInfo {
+        name: def.name.to_string(),
+        title: def.title.to_string(),
+        description: def.description.to_string(),
+        overridden,
+        override_path: overridden.then(|| path.to_string_lossy().to_string()),
+    })
+}
+
+/// The printable documents whose layout can be customised.
+#[command]
+pub fn list_print_templates() -> Result<Vec<PrintTemplateInfo>, AppError> {
+    Ok(TEMPLATES.iter().map(template_info).collect::<Result<Vec<_>, _>>()?)
+}
+
+/// A template's current source (the user's copy if saved) and the built-in
+/// one, for editing.
+#[command]
+pub fn get_print_template(name: String) -> Result<PrintTemplateSource, AppError> {
+    let def = find_template(&name)?;
+    let custom = override_source(def.name);
+    Ok(PrintTemplateSource {
+        name: def.name.to_string(),
+        overridden: custom.is_some(),
+        source: custom.unwrap_or_else(|| def.source.to_string()),
+        default_source: def.source.to_string(),
+    })
+}
+
+/// Render a template with sample data. `source` previews unsaved edits;
+/// without it the saved template is shown. `locale` previews another
+/// receipt language.
+#[command]
+pub fn preview_print_template(name: String, source: Option<String>, locale: Option<String>) -> Result<String, AppError> {
+    let def = find_template(&name)?;
+    let source = source
+        .or_else(|| override_source(def.name))
+        .unwrap_or_else(|| def.source.to_string());
+    let conn = crate::db::get_db_connection()?;
+    let mut context = sample_context(def.name);
+    crate::receipt_i18n::receipt_labels(&conn, locale.as_deref())?.apply_to(&mut context);
+    Ok(render_source(&source, &context)?)
+}
+
+/// Save a customised layout, used from then on instead of the built-in one.
+/// Templates that don't render are rejected.
+#[command]
+pub fn save_print_template(name: String, source: String) -> Result<PrintTemplateInfo, AppError> {
+    let def = find_template(&name)?;
+    if source.trim().is_empty() {
+        return Err(AppError::invalid(crate::validation::EMPTY_FIELD, "source", "Template cannot be empty"));
+    }
+    render_source(&source, &sample_context(def.name))?;
+
+    let path = override_path(def.name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create templates directory: {}", e))?;
+    }
+    fs::write(&path, source).map_err(|e| format!("Failed to save template: {}", e))?;
+    Ok(template_info(def)?)
+}
+
+/// Go back to the built-in layout.
+#[command]
+pub fn reset_print_template(name: String) -> Result<PrintTemplateInfo, AppError> {
+    let def = find_template(&name)?;
+    let path = override_path(def.name)?;
+    if path.is_file() {
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove custom template: {}", e))?;
+    }
+    Ok(template_info(def)?)
+}
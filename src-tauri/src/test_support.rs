@@ -0,0 +1,204 @@
+//! Setup shared by the unit tests. Each test opens a `TestDb`, a fresh
+//! database in a temp folder of its own that is removed when the test ends.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use rusqlite::params;
+use crate::db::{close_pooled_connections, get_db_connection, get_current_timestamp, initialize_database};
+use crate::models::{OrderItemInput, RoomTypeInput};
+use crate::offline_auth::{AuthManager, LoginRequest};
+use crate::simple_commands::{add_expense, add_guest, add_room, business_date_today, get_rooms};
+
+pub const TEST_RATE: f64 = 100.0;
+pub const ADMIN: &str = "selftest";
+pub const ADMIN_PASSWORD: &str = "self-test-pass";
+
+// The connection pool and the session caches are shared by the whole test
+// binary, so tests that use a database take turns
+static TURN: Mutex<()> = Mutex::new(());
+static DATABASE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// The database of the test running now; get_db_path answers with it.
+pub fn database_path() -> Option<PathBuf> {
+    DATABASE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn use_database(path: Option<PathBuf>) -> Option<PathBuf> {
+    std::mem::replace(&mut *DATABASE.lock().unwrap_or_else(|e| e.into_inner()), path)
+}
+
+fn forget_sessions() {
+    crate::offline_auth::session_cache().clear();
+    crate::offline_auth::forget_session_policy();
+}
+
+pub struct TestDb {
+    dir: PathBuf,
+    _turn: MutexGuard<'static, ()>,
+}
+
+impl TestDb {
+    pub fn new() -> Self {
+        // A test that failed while holding its turn doesn't spoil the others
+        let turn = TURN.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = std::env::temp_dir().join(format!("hotel-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("media")).expect("create the test folder");
+        use_database(Some(dir.join("hotel.db")));
+        forget_sessions();
+        initialize_database().expect("initialize the test database");
+        // Media for this database stays in the test folder too
+        get_db_connection()
+            .and_then(|conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('media_directory', ?1, ?2)",
+                    params![dir.join("media").to_string_lossy().to_string(), get_current_timestamp()],
+                )
+            })
+            .expect("set the test media folder");
+        TestDb { dir, _turn: turn }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Run `f` against another database file, such as a second machine or an
+    /// older install, then switch back to this one.
+    pub fn with_database<T>(&self, path: PathBuf, f: impl FnOnce() -> T) -> T {
+        struct Restore(Option<PathBuf>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                use_database(self.0.take());
+            }
+        }
+
+        let _restore = Restore(use_database(Some(path)));
+        f()
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        use_database(None);
+        // Open connections would keep the files in use and the folder undeletable
+        close_pooled_connections();
+        forget_sessions();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+pub fn same_amount(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() < 0.005
+}
+
+pub fn count(sql: &str, param: i64) -> i64 {
+    let conn = get_db_connection().expect("open the test database");
+    conn.query_row(sql, params![param], |row| row.get(0)).expect("count rows")
+}
+
+pub fn today() -> String {
+    business_date_today()
+}
+
+/// The business date `offset` days from today.
+pub fn days_from_today(offset: i64) -> String {
+    let today = chrono::NaiveDate::parse_from_str(&today(), "%Y-%m-%d").expect("parse today's date");
+    (today + chrono::Duration::days(offset)).format("%Y-%m-%d").to_string()
+}
+
+pub fn room_type(name: &str, max_occupancy: Option<i64>) -> RoomTypeInput {
+    RoomTypeInput { name: name.to_string(), base_rate: TEST_RATE, max_occupancy, amenities: Vec::new() }
+}
+
+/// Add a Standard room at TEST_RATE, creating the room type on first use,
+/// and return the room's id.
+pub fn add_test_room(number: &str) -> i64 {
+    if !crate::room_types::get_room_types().expect("list room types").iter().any(|t| t.name == "Standard") {
+        crate::room_types::add_room_type(room_type("Standard", None)).expect("add the Standard room type");
+    }
+    add_room(number.to_string(), "Standard".to_string(), TEST_RATE, None, None, None).expect("add the room");
+    get_rooms(None, None)
+        .expect("list rooms")
+        .into_iter()
+        .find(|r| r.number == number)
+        .expect("the new room is listed")
+        .id
+}
+
+/// Check a guest in today for one adult at TEST_RATE, to `room_id` or as a
+/// walk-in without a room.
+pub fn check_in(name: &str, room_id: Option<i64>) -> i64 {
+    add_guest(
+        name.to_string(), None, room_id, today(), None, TEST_RATE,
+        Some(1), Some(0), None, None, None, None,
+    )
+    .expect("check the guest in")
+}
+
+/// Record an expense dated today, creating its category on first use.
+pub fn add_test_expense(category: &str, description: &str, amount: f64) -> i64 {
+    let categories = crate::expense_categories::get_expense_categories().expect("list expense categories");
+    if !categories.iter().any(|c| c.name == category) {
+        crate::expense_categories::add_expense_category(category.to_string()).expect("add the expense category");
+    }
+    add_expense(today(), category.to_string(), Some(description.to_string()), amount, None, None, None, None).expect("add the expense")
+}
+
+/// An order line typed in by hand, not from the menu.
+pub fn order_item(name: &str, unit_price: f64, quantity: i32) -> OrderItemInput {
+    OrderItemInput {
+        menu_item_id: None,
+        item_name: name.to_string(),
+        unit_price,
+        quantity,
+        selected_attributes: Vec::new(),
+        special_id: None,
+        notes: None,
+        modifiers: Vec::new(),
+        combo_id: None,
+    }
+}
+
+/// Create the admin account on first use and sign it in, returning the
+/// session token.
+pub fn admin_session() -> String {
+    let auth = AuthManager::new();
+    if !auth.check_password(ADMIN, ADMIN_PASSWORD).unwrap_or(false) {
+        auth.register_initial_admin(ADMIN, ADMIN_PASSWORD, "Self test?", "yes").expect("create the admin");
+    }
+    let request = LoginRequest { username: ADMIN.to_string(), password: ADMIN_PASSWORD.to_string() };
+    auth.login(request).expect("log in").session_token.expect("the admin could not log in")
+}
+
+/// Add a user with `role` (after the admin, who has to come first) and sign
+/// them in, returning the session token.
+pub fn session_as(role: &str) -> String {
+    admin_session();
+    let username = format!("test-{}", role);
+    let auth = AuthManager::new();
+    auth.register_user(&username, ADMIN_PASSWORD, role, "Self test?", "yes").expect("add the user");
+    let request = LoginRequest { username, password: ADMIN_PASSWORD.to_string() };
+    auth.login(request).expect("log in").session_token.expect("the user could not log in")
+}
+
+#[test]
+fn each_test_gets_a_database_of_its_own() {
+    let laundry = || -> i64 {
+        let conn = get_db_connection().expect("open the test database");
+        conn.query_row("SELECT COUNT(*) FROM expense_categories WHERE name = 'Laundry'", [], |row| row.get(0))
+            .expect("count categories")
+    };
+    let first = TestDb::new();
+    crate::expense_categories::add_expense_category("Laundry".to_string()).expect("add a category");
+    let elsewhere = first.with_database(first.dir().join("other.db"), || {
+        initialize_database().expect("initialize the other database");
+        laundry()
+    });
+    assert_eq!((laundry(), elsewhere), (1, 0), "the other database saw this one's category");
+    let folder = first.dir().to_path_buf();
+    drop(first);
+    assert!(!folder.exists(), "the test folder was left behind");
+
+    let _second = TestDb::new();
+    assert_eq!(laundry(), 0, "a category from the previous test is still there");
+}
@@ -0,0 +1,161 @@
+use tauri::command;
+use std::collections::HashMap;
+use chrono::{Datelike, Duration, NaiveDate};
+use rusqlite::params;
+use crate::db::{get_db_connection, run_blocking};
+use crate::errors::AppError;
+use crate::models::{ChartSeries, ExpenseTimeseries, RevenueTimeseries};
+use crate::validation::validate_date_format;
+
+const GRANULARITIES: &[&str] = &["daily", "weekly", "monthly"];
+// About three years of days; longer ranges need a coarser granularity
+const MAX_BUCKETS: usize = 1100;
+
+/// The consecutive buckets a range is split into. Daily buckets are labelled
+/// YYYY-MM-DD, weekly ones by the Monday they start on, monthly ones YYYY-MM.
+struct Buckets {
+    granularity: &'static str,
+    labels: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl Buckets {
+    fn new(granularity: &str, range_start: &str, range_end: &str) -> Result<Self, AppError> {
+        let granularity = GRANULARITIES
+            .iter()
+            .copied()
+            .find(|g| *g == granularity.trim().to_lowercase())
+            .ok_or_else(|| format!("granularity must be one of: {}", GRANULARITIES.join(", ")))?;
+        validate_date_format(range_start)?;
+        validate_date_format(range_end)?;
+        let start = NaiveDate::parse_from_str(range_start, "%Y-%m-%d")?;
+        let end = NaiveDate::parse_from_str(range_end, "%Y-%m-%d")?;
+        if end < start {
+            return Err("Range end must not be before range start".into());
+        }
+
+        let mut buckets = Buckets { granularity, labels: Vec::new(), index: HashMap::new() };
+        let mut day = start;
+        while day <= end {
+            let label = buckets.label(day);
+            if buckets.labels.last() != Some(&label) {
+                if buckets.labels.len() == MAX_BUCKETS {
+                    return Err(format!("That range has more than {} {} buckets; pick a coarser granularity", MAX_BUCKETS, granularity).into());
+                }
+                buckets.index.insert(label.clone(), buckets.labels.len());
+                buckets.labels.push(label);
+            }
+            day += Duration::days(1);
+        }
+        Ok(buckets)
+    }
+
+    fn label(&self, day: NaiveDate) -> String {
+        match self.granularity {
+            "daily" => day.format("%Y-%m-%d").to_string(),
+            "weekly" => (day - Duration::days(day.weekday().num_days_from_monday() as i64)).format("%Y-%m-%d").to_string(),
+            _ => day.format("%Y-%m").to_string(),
+        }
+    }
+
+    fn empty_series(&self) -> ChartSeries {
+        ChartSeries { labels: self.labels.clone(), values: vec![0.0; self.labels.len()] }
+    }
+
+    /// Add `amount` to the bucket `day` (YYYY-MM-DD, time ignored) falls in.
+    fn add(&self, series: &mut ChartSeries, day: &str, amount: f64) {
+        let Ok(day) = NaiveDate::parse_from_str(day.get(..10).unwrap_or(day), "%Y-%m-%d") else {
+            return;
+        };
+        if let Some(&i) = self.index.get(&self.label(day)) {
+            series.values[i] += amount;
+        }
+    }
+}
+
+/// Room and food income per bucket. Room income counts on the check-out day,
+/// as in the dashboard; food income on the day it was paid, less refunds.
+#[command]
+pub async fn revenue_timeseries(granularity: String, range_start: String, range_end: String) -> Result<RevenueTimeseries, AppError> {
+    run_blocking(move || {
+        let buckets = Buckets::new(&granularity, &range_start, &range_end)?;
+        let conn = get_db_connection()?;
+        let mut room_income = buckets.empty_series();
+        let mut food_income = buckets.empty_series();
+
+        let mut stmt = conn.prepare(
+            "SELECT substr(check_out, 1, 10),
+                    SUM(COALESCE(billed_amount, (julianday(check_out) - julianday(check_in) + 1) * daily_rate))
+             FROM customers
+             WHERE status = 'checked_out' AND check_out >= ?1 AND substr(check_out, 1, 10) <= ?2
+             GROUP BY 1",
+        )?;
+        let rows = stmt.query_map(params![range_start, range_end], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+        for row in rows {
+            let (day, amount) = row?;
+            buckets.add(&mut room_income, &day, amount);
+        }
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT date(paid_at), SUM(amount)
+             FROM payments
+             WHERE (order_id IS NOT NULL OR refund_of IS NOT NULL) AND date(paid_at) >= ?1 AND date(paid_at) <= ?2 AND {}
+             GROUP BY 1",
+            crate::trash::outside_trash("payments.order_id")
+        ))?;
+        let rows = stmt.query_map(params![range_start, range_end], |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)))?;
+        for row in rows {
+            let (day, amount) = row?;
+            buckets.add(&mut food_income, &day, amount);
+        }
+
+        let total_income = ChartSeries {
+            labels: buckets.labels.clone(),
+            values: room_income.values.iter().zip(&food_income.values).map(|(room, food)| room + food).collect(),
+        };
+        Ok(RevenueTimeseries {
+            granularity: buckets.granularity.to_string(),
+            range_start,
+            range_end,
+            room_income,
+            food_income,
+            total_income,
+        })
+    })
+    .await
+}
+
+/// Business expenses and owner drawings per bucket, by expense date.
+#[command]
+pub async fn expense_timeseries(granularity: String, range_start: String, range_end: String) -> Result<ExpenseTimeseries, AppError> {
+    run_blocking(move || {
+        let buckets = Buckets::new(&granularity, &range_start, &range_end)?;
+        let conn = get_db_connection()?;
+        let mut expenses = buckets.empty_series();
+        let mut owner_drawings = buckets.empty_series();
+
+        let mut stmt = conn.prepare(
+            "SELECT date, is_drawing, SUM(amount)
+             FROM expenses
+             WHERE date >= ?1 AND date <= ?2 AND deleted_at IS NULL
+             GROUP BY 1, 2",
+        )?;
+        let rows = stmt.query_map(params![range_start, range_end], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?, row.get::<_, f64>(2)?))
+        })?;
+        for row in rows {
+            let (day, is_drawing, amount) = row?;
+            let series = if is_drawing { &mut owner_drawings } else { &mut expenses };
+            buckets.add(series, &day, amount);
+        }
+
+        Ok(ExpenseTimeseries {
+            granularity: buckets.granularity.to_string(),
+            range_start,
+            range_end,
+            expenses,
+            owner_drawings,
+        })
+    })
+    .await
+}
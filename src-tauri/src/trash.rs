@@ -0,0 +1,166 @@
+use tauri::command;
+use chrono::{Duration, Utc};
+use rusqlite::{params, Connection};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, log_audit_event};
+use crate::errors::AppError;
+use crate::models::TrashItem;
+use crate::validation::ORDER_NOT_FOUND;
+
+/// SQL condition that holds unless `order_id_column` points at an order in the
+/// trash, so payments on deleted orders drop out of takings.
+pub(crate) fn outside_trash(order_id_column: &str) -> String {
+    format!(
+        "NOT EXISTS (SELECT 1 FROM sales trashed WHERE trashed.id = {} AND trashed.deleted_at IS NOT NULL)",
+        order_id_column
+    )
+}
+
+/// The table behind each kind of item that can be in the trash.
+fn trash_table(kind: &str) -> Result<&'static str, AppError> {
+    match kind {
+        "order" => Ok("sales"),
+        "expense" => Ok("expenses"),
+        _ => Err(format!("'{}' can't be in the trash; expected 'order' or 'expense'", kind).into()),
+    }
+}
+
+/// Hard-delete trashed orders (with their lines, payments and tags) and
+/// expenses deleted before `cutoff`. Returns how many were removed.
+fn purge_before(conn: &Connection, cutoff: &str) -> Result<usize, AppError> {
+    let orders = "SELECT id FROM sales WHERE deleted_at IS NOT NULL AND deleted_at < ?1";
+    conn.execute(&format!("DELETE FROM sale_items WHERE order_id IN ({})", orders), params![cutoff])?;
+    conn.execute(&format!("DELETE FROM payments WHERE order_id IN ({})", orders), params![cutoff])?;
+    conn.execute(
+        &format!("DELETE FROM entity_tags WHERE entity_type = 'order' AND entity_id IN ({})", orders),
+        params![cutoff],
+    )?;
+    let purged = conn.execute("DELETE FROM sales WHERE deleted_at IS NOT NULL AND deleted_at < ?1", params![cutoff])?;
+    Ok(purged + conn.execute("DELETE FROM expenses WHERE deleted_at IS NOT NULL AND deleted_at < ?1", params![cutoff])?)
+}
+
+/// Deleted orders and expenses, most recently deleted first.
+#[command]
+pub fn get_trash() -> Result<Vec<TrashItem>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT 'order', id, COALESCE(customer_name, customer_type), total_amount, created_at, deleted_at, deleted_by
+         FROM sales WHERE deleted_at IS NOT NULL
+         UNION ALL
+         SELECT 'expense', id, COALESCE(description, category), amount, date, deleted_at, deleted_by
+         FROM expenses WHERE deleted_at IS NOT NULL
+         ORDER BY 6 DESC",
+    )?;
+    let items = stmt
+        .query_map([], |row| {
+            Ok(TrashItem {
+                kind: row.get(0)?,
+                id: row.get(1)?,
+                label: row.get(2)?,
+                amount: row.get(3)?,
+                date: row.get(4)?,
+                deleted_at: row.get(5)?,
+                deleted_by: row.get(6)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(items)
+}
+
+/// Take an order or expense back out of the trash, as it was when deleted.
+#[command]
+pub fn restore_item(kind: String, id: i64, session_token: Option<String>) -> Result<String, AppError> {
+    let table = trash_table(kind.trim())?;
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    let restored = tx.execute(
+        &format!("UPDATE {} SET deleted_at = NULL, deleted_by = NULL WHERE id = ?1 AND deleted_at IS NOT NULL", table),
+        params![id],
+    )?;
+    if restored == 0 {
+        let message = format!("No {} #{} in the trash", kind.trim(), id);
+        return Err(if table == "sales" { AppError::coded(ORDER_NOT_FOUND, message) } else { message.into() });
+    }
+    let after = snapshot(&tx, kind.trim(), id)?;
+    record_change(&tx, &audit_actor(session_token.as_deref()), kind.trim(), id, "restore", None, after)?;
+    tx.commit()?;
+    Ok(format!("{} #{} restored", if table == "sales" { "Order" } else { "Expense" }, id))
+}
+
+/// Permanently remove what has been in the trash for more than
+/// `older_than_days` days, or everything in it when no age is given.
+#[command]
+pub fn purge_trash(older_than_days: Option<i64>, session_token: Option<String>) -> Result<usize, AppError> {
+    let days = older_than_days.unwrap_or(0);
+    if days < 0 {
+        return Err("older_than_days must be >= 0".into());
+    }
+    // Deletion times are RFC 3339 in UTC, so they compare as text
+    let cutoff = (Utc::now() - Duration::days(days)).to_rfc3339();
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    let purged = purge_before(&tx, &cutoff)?;
+    if purged > 0 {
+        log_audit_event(
+            &tx,
+            &audit_actor(session_token.as_deref()),
+            "trash_purged",
+            &format!("{} deleted orders and expenses removed for good", purged),
+        )?;
+    }
+    tx.commit()?;
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::{delete_expense, delete_food_order, get_expenses, get_food_orders, insert_food_order};
+    use crate::test_support::{add_test_expense, order_item, TestDb};
+
+    /// An expense and a walk-in order, both deleted.
+    fn trash_an_expense_and_an_order() -> (i64, i64) {
+        let expense_id = add_test_expense("Supplies", "Binned", 3.0);
+        let (order_id, _) =
+            insert_food_order(None, "walk_in".to_string(), Some("Binned".to_string()), vec![order_item("Toast", 2.0, 1)], None, None, None)
+                .unwrap();
+        delete_expense(expense_id, None).unwrap();
+        delete_food_order(order_id, None).unwrap();
+        (expense_id, order_id)
+    }
+
+    #[test]
+    fn deleted_orders_and_expenses_move_to_the_trash() {
+        let _db = TestDb::new();
+        let (expense_id, order_id) = trash_an_expense_and_an_order();
+        assert!(delete_food_order(order_id, None).is_err(), "an order already in the trash was deleted again");
+        assert!(!get_food_orders(None).unwrap().iter().any(|o| o.id == order_id), "a trashed order is still listed");
+        assert!(!get_expenses(None, None).unwrap().iter().any(|e| e.id == expense_id), "a trashed expense is still listed");
+        let trash = get_trash().unwrap();
+        assert!(
+            trash.iter().any(|t| t.kind == "order" && t.id == order_id) && trash.iter().any(|t| t.kind == "expense" && t.id == expense_id),
+            "deleted order and expense not in the trash"
+        );
+    }
+
+    #[test]
+    fn a_restored_item_is_listed_again() {
+        let _db = TestDb::new();
+        let (expense_id, _) = trash_an_expense_and_an_order();
+        restore_item("expense".to_string(), expense_id, None).unwrap();
+        assert!(get_expenses(None, None).unwrap().iter().any(|e| e.id == expense_id), "restored expense not listed again");
+        assert!(restore_item("expense".to_string(), expense_id, None).is_err(), "restored an expense that isn't in the trash");
+    }
+
+    #[test]
+    fn purging_removes_old_items_for_good() {
+        let _db = TestDb::new();
+        let (_, order_id) = trash_an_expense_and_an_order();
+        assert_eq!(purge_trash(Some(1), None).unwrap(), 0, "items deleted today were purged as over a day old");
+        purge_trash(None, None).unwrap();
+        assert!(get_trash().unwrap().is_empty(), "trash not empty after purging everything");
+        let conn = get_db_connection().unwrap();
+        let left: i64 = conn.query_row("SELECT COUNT(*) FROM sale_items WHERE order_id = ?1", [order_id], |row| row.get(0)).unwrap();
+        assert_eq!(left, 0, "purged order left lines behind");
+    }
+}
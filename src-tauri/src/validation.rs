@@ -6,21 +6,68 @@ use chrono::NaiveDate;
 pub const ROOM_NOT_FOUND: &str = "ROOM_NOT_FOUND";
 pub const ROOM_OCCUPIED: &str = "ROOM_OCCUPIED";
 pub const ROOM_NUMBER_EXISTS: &str = "ROOM_NUMBER_EXISTS";
+pub const ROOM_RESERVED: &str = "ROOM_RESERVED";
+pub const ROOM_OUT_OF_SERVICE: &str = "ROOM_OUT_OF_SERVICE";
+pub const ROOM_TYPE_NOT_FOUND: &str = "ROOM_TYPE_NOT_FOUND";
+pub const ROOM_TYPE_EXISTS: &str = "ROOM_TYPE_EXISTS";
+pub const ROOM_TYPE_IN_USE: &str = "ROOM_TYPE_IN_USE";
+pub const ROOM_OVER_CAPACITY: &str = "ROOM_OVER_CAPACITY";
 pub const GUEST_NOT_FOUND: &str = "GUEST_NOT_FOUND";
 pub const GUEST_NOT_ACTIVE: &str = "GUEST_NOT_ACTIVE";
 pub const GUEST_ALREADY_CHECKED_OUT: &str = "GUEST_ALREADY_CHECKED_OUT";
 pub const MENU_ITEM_NOT_FOUND: &str = "MENU_ITEM_NOT_FOUND";
 pub const MENU_ITEM_UNAVAILABLE: &str = "MENU_ITEM_UNAVAILABLE";
+pub const MENU_CATEGORY_NOT_FOUND: &str = "MENU_CATEGORY_NOT_FOUND";
+pub const MENU_CATEGORY_EXISTS: &str = "MENU_CATEGORY_EXISTS";
+pub const MENU_CATEGORY_IN_USE: &str = "MENU_CATEGORY_IN_USE";
+pub const COMBO_NOT_FOUND: &str = "COMBO_NOT_FOUND";
+pub const COMBO_EXISTS: &str = "COMBO_EXISTS";
+pub const MENU_PRICE_RULE_NOT_FOUND: &str = "MENU_PRICE_RULE_NOT_FOUND";
+pub const SPECIAL_SOLD_OUT: &str = "SPECIAL_SOLD_OUT";
+pub const OCCUPANCY_CAP_EXCEEDED: &str = "OCCUPANCY_CAP_EXCEEDED";
+pub const OCCUPANCY_CAP_WARNING: &str = "OCCUPANCY_CAP_WARNING";
+pub const CHECKOUT_HOLD_ACTIVE: &str = "CHECKOUT_HOLD_ACTIVE";
 pub const ORDER_NOT_FOUND: &str = "ORDER_NOT_FOUND";
 pub const ORDER_ALREADY_PAID: &str = "ORDER_ALREADY_PAID";
+pub const ORDER_ITEM_NOT_FOUND: &str = "ORDER_ITEM_NOT_FOUND";
+pub const ORDER_ITEM_ALREADY_VOIDED: &str = "ORDER_ITEM_ALREADY_VOIDED";
+pub const ORDER_STATUS_BACKWARDS: &str = "ORDER_STATUS_BACKWARDS";
+pub const INVALID_ORDER_STATUS: &str = "INVALID_ORDER_STATUS";
+pub const TABLE_NOT_FOUND: &str = "TABLE_NOT_FOUND";
+pub const TABLE_NUMBER_EXISTS: &str = "TABLE_NUMBER_EXISTS";
+pub const TABLE_OPEN: &str = "TABLE_OPEN";
+pub const TABLE_HAS_UNPAID_ORDERS: &str = "TABLE_HAS_UNPAID_ORDERS";
+pub const SHIFT_ALREADY_OPEN: &str = "SHIFT_ALREADY_OPEN";
+pub const EXPENSE_CATEGORY_NOT_FOUND: &str = "EXPENSE_CATEGORY_NOT_FOUND";
+pub const EXPENSE_CATEGORY_EXISTS: &str = "EXPENSE_CATEGORY_EXISTS";
+pub const EXPENSE_CATEGORY_IN_USE: &str = "EXPENSE_CATEGORY_IN_USE";
+pub const LEDGER_ACCOUNT_NOT_FOUND: &str = "LEDGER_ACCOUNT_NOT_FOUND";
+pub const LEDGER_ACCOUNT_CODE_EXISTS: &str = "LEDGER_ACCOUNT_CODE_EXISTS";
+pub const JOURNAL_UNBALANCED: &str = "JOURNAL_UNBALANCED";
+pub const VOUCHER_NOT_FOUND: &str = "VOUCHER_NOT_FOUND";
+pub const VOUCHER_CODE_EXISTS: &str = "VOUCHER_CODE_EXISTS";
+pub const VOUCHER_INACTIVE: &str = "VOUCHER_INACTIVE";
+pub const VOUCHER_EXPIRED: &str = "VOUCHER_EXPIRED";
+pub const VOUCHER_USED_UP: &str = "VOUCHER_USED_UP";
+pub const GUEST_PROFILE_NOT_FOUND: &str = "GUEST_PROFILE_NOT_FOUND";
+pub const GUEST_ANONYMIZED: &str = "GUEST_ANONYMIZED";
 pub const INVALID_DATE_FORMAT: &str = "INVALID_DATE_FORMAT";
+pub const UNUSUAL_DATE: &str = "UNUSUAL_DATE";
+pub const DATE_BEFORE_EPOCH: &str = "DATE_BEFORE_EPOCH";
+pub const INVALID_PAGE_REQUEST: &str = "INVALID_PAGE_REQUEST";
+pub const AFTER_HOURS_RESTRICTED: &str = "AFTER_HOURS_RESTRICTED";
 pub const NEGATIVE_AMOUNT: &str = "NEGATIVE_AMOUNT";
 pub const EMPTY_FIELD: &str = "EMPTY_FIELD";
+pub const INVALID_VALUE: &str = "INVALID_VALUE";
+pub const WEAK_PASSPHRASE: &str = "WEAK_PASSPHRASE";
+pub const BACKUP_PASSPHRASE_REQUIRED: &str = "BACKUP_PASSPHRASE_REQUIRED";
 pub const INVALID_CREDENTIALS: &str = "INVALID_CREDENTIALS";
 pub const SESSION_EXPIRED: &str = "SESSION_EXPIRED";
+pub const SESSION_IDLE_TIMEOUT: &str = "SESSION_IDLE_TIMEOUT";
 pub const UNAUTHORIZED: &str = "UNAUTHORIZED";
 pub const DATABASE_ERROR: &str = "DATABASE_ERROR";
 pub const CONSTRAINT_VIOLATION: &str = "CONSTRAINT_VIOLATION";
+pub const COMMAND_FAILED: &str = "COMMAND_FAILED";
 
 /// Validation result type
 pub type ValidationResult<T> = Result<T, String>;
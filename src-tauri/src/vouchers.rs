@@ -0,0 +1,319 @@
+use tauri::command;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::audit::{audit_actor, record_change, snapshot};
+use crate::db::{get_db_connection, get_current_timestamp};
+use crate::errors::AppError;
+use crate::models::{NewVoucher, OrderPaymentSummary, Voucher, VoucherCheck, VoucherRedemption};
+use crate::simple_commands::{business_date_today, to_cents};
+use crate::validation::{
+    validate_date_format, EMPTY_FIELD, INVALID_VALUE, NEGATIVE_AMOUNT, ORDER_ALREADY_PAID, VOUCHER_CODE_EXISTS, VOUCHER_EXPIRED,
+    VOUCHER_INACTIVE, VOUCHER_NOT_FOUND, VOUCHER_USED_UP,
+};
+
+const KINDS: &[&str] = &["amount", "percent"];
+const CODE_MAX_CHARS: usize = 32;
+const GENERATED_CODE_CHARS: usize = 8;
+
+const VOUCHER_COLUMNS: &str = "id, code, kind, value, expires_on, max_uses, uses, is_active, note, created_at";
+
+fn map_voucher(row: &rusqlite::Row) -> rusqlite::Result<Voucher> {
+    Ok(Voucher {
+        id: row.get(0)?,
+        code: row.get(1)?,
+        kind: row.get(2)?,
+        value: row.get(3)?,
+        expires_on: row.get(4)?,
+        max_uses: row.get(5)?,
+        uses: row.get(6)?,
+        is_active: row.get::<_, i32>(7)? == 1,
+        note: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+fn load_voucher(conn: &Connection, voucher_id: i64) -> Result<Voucher, AppError> {
+    conn.query_row(&format!("SELECT {} FROM vouchers WHERE id = ?1", VOUCHER_COLUMNS), params![voucher_id], map_voucher)
+        .optional()?
+        .ok_or_else(|| AppError::coded(VOUCHER_NOT_FOUND, format!("Voucher #{} not found", voucher_id)))
+}
+
+/// Look a voucher up by code as typed; codes match whatever the case.
+fn find_voucher(conn: &Connection, code: &str) -> Result<Voucher, AppError> {
+    let code = code.trim().to_uppercase();
+    if code.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "code", "Enter a voucher code"));
+    }
+    conn.query_row(&format!("SELECT {} FROM vouchers WHERE code = ?1 COLLATE NOCASE", VOUCHER_COLUMNS), params![code], map_voucher)
+        .optional()?
+        .ok_or_else(|| AppError::invalid(VOUCHER_NOT_FOUND, "code", format!("No voucher with code '{}'", code)))
+}
+
+/// Codes are kept in upper case: letters, digits and dashes.
+fn normalize_code(code: &str) -> Result<String, AppError> {
+    let code = code.trim().to_uppercase();
+    if code.is_empty() {
+        return Err(AppError::invalid(EMPTY_FIELD, "code", "Voucher code cannot be empty"));
+    }
+    if code.chars().count() > CODE_MAX_CHARS || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(AppError::invalid(
+            INVALID_VALUE,
+            "code",
+            format!("Voucher codes are up to {} letters, digits and dashes", CODE_MAX_CHARS),
+        ));
+    }
+    Ok(code)
+}
+
+fn generate_code() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..GENERATED_CODE_CHARS].to_uppercase()
+}
+
+/// Whether the voucher can be used today, and how many uses it has left.
+fn check_usable(voucher: &Voucher) -> Result<Option<i64>, AppError> {
+    if !voucher.is_active {
+        return Err(AppError::invalid(VOUCHER_INACTIVE, "code", format!("Voucher {} has been withdrawn", voucher.code)));
+    }
+    if let Some(expires_on) = &voucher.expires_on {
+        if *expires_on < business_date_today() {
+            return Err(AppError::invalid(VOUCHER_EXPIRED, "code", format!("Voucher {} expired on {}", voucher.code, expires_on)));
+        }
+    }
+    let uses_left = voucher.max_uses.map(|max| (max - voucher.uses).max(0));
+    if uses_left == Some(0) {
+        return Err(AppError::invalid(VOUCHER_USED_UP, "code", format!("Voucher {} has already been used", voucher.code)));
+    }
+    Ok(uses_left)
+}
+
+/// What the voucher takes off a bill of `amount`: never more than the bill.
+fn discount_on(voucher: &Voucher, amount: f64) -> f64 {
+    let bill = to_cents(amount).max(0);
+    let off = match voucher.kind.as_str() {
+        "percent" => (bill as f64 * voucher.value / 100.0).round() as i64,
+        _ => to_cents(voucher.value),
+    };
+    off.min(bill) as f64 / 100.0
+}
+
+/// Look up `code` for a bill of `amount` (before tax) and return the voucher's
+/// ID and what it takes off. Nothing is recorded until `record_redemption`.
+pub(crate) fn price_voucher(conn: &Connection, code: &str, amount: f64) -> Result<(i64, f64), AppError> {
+    let voucher = find_voucher(conn, code)?;
+    check_usable(&voucher)?;
+    Ok((voucher.id, discount_on(&voucher, amount)))
+}
+
+/// Use up one redemption of the voucher against an order or a checkout bill.
+/// Fails if the voucher ran out in the meantime.
+pub(crate) fn record_redemption(
+    conn: &Connection,
+    voucher_id: i64,
+    amount: f64,
+    order_id: Option<i64>,
+    guest_id: Option<i64>,
+    actor: &str,
+) -> Result<(), AppError> {
+    let used = conn.execute(
+        "UPDATE vouchers SET uses = uses + 1 WHERE id = ?1 AND is_active = 1 AND (max_uses IS NULL OR uses < max_uses)",
+        params![voucher_id],
+    )?;
+    if used == 0 {
+        return Err(AppError::coded(VOUCHER_USED_UP, format!("Voucher #{} can no longer be used", voucher_id)));
+    }
+    conn.execute(
+        "INSERT INTO voucher_redemptions (voucher_id, order_id, guest_id, amount, redeemed_by, redeemed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![voucher_id, order_id, guest_id, to_cents(amount) as f64 / 100.0, actor, get_current_timestamp()],
+    )?;
+    Ok(())
+}
+
+/// Vouchers taken off an order or a guest's checkout bill, as (code, amount).
+pub(crate) fn redemptions_for(conn: &Connection, order_id: Option<i64>, guest_id: Option<i64>) -> Result<Vec<(String, f64)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT v.code, r.amount FROM voucher_redemptions r JOIN vouchers v ON v.id = r.voucher_id
+             WHERE (?1 IS NOT NULL AND r.order_id = ?1) OR (?2 IS NOT NULL AND r.guest_id = ?2)
+             ORDER BY r.id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![order_id, guest_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Issue a gift voucher or promo code. Leave the code out to have one made up.
+#[command]
+pub fn create_voucher(voucher: NewVoucher, session_token: Option<String>) -> Result<Voucher, AppError> {
+    let kind = voucher.kind.trim().to_lowercase();
+    if !KINDS.contains(&kind.as_str()) {
+        return Err(AppError::invalid(INVALID_VALUE, "kind", format!("Voucher kind must be one of: {}", KINDS.join(", "))));
+    }
+    if !voucher.value.is_finite() || to_cents(voucher.value) <= 0 {
+        return Err(AppError::invalid(NEGATIVE_AMOUNT, "value", "Voucher value must be greater than 0"));
+    }
+    if kind == "percent" && voucher.value > 100.0 {
+        return Err(AppError::invalid(INVALID_VALUE, "value", "A voucher can't take off more than 100%"));
+    }
+    let expires_on = voucher.expires_on.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if let Some(date) = &expires_on {
+        validate_date_format(date)?;
+    }
+    if voucher.max_uses.is_some_and(|max| max < 1) {
+        return Err(AppError::invalid(INVALID_VALUE, "max_uses", "A voucher must be usable at least once"));
+    }
+    let code = match voucher.code.filter(|c| !c.trim().is_empty()) {
+        Some(code) => normalize_code(&code)?,
+        None => generate_code(),
+    };
+
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    let exists: bool = tx
+        .query_row("SELECT 1 FROM vouchers WHERE code = ?1 COLLATE NOCASE", params![code], |_| Ok(true))
+        .optional()?
+        .unwrap_or(false);
+    if exists {
+        return Err(AppError::invalid(VOUCHER_CODE_EXISTS, "code", format!("Voucher code {} is already in use", code)));
+    }
+    tx.execute(
+        "INSERT INTO vouchers (code, kind, value, expires_on, max_uses, note) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            code,
+            kind,
+            voucher.value,
+            expires_on,
+            voucher.max_uses,
+            voucher.note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+        ],
+    )?;
+    let voucher_id = tx.last_insert_rowid();
+    crate::db::log_audit_event(&tx, &audit_actor(session_token.as_deref()), "voucher_created", &format!("Voucher {} issued", code))?;
+    tx.commit()?;
+    load_voucher(&conn, voucher_id)
+}
+
+/// All vouchers, newest first; withdrawn ones only when asked for.
+#[command]
+pub fn get_vouchers(include_inactive: Option<bool>) -> Result<Vec<Voucher>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM vouchers WHERE is_active = 1 OR ?1 ORDER BY created_at DESC, id DESC",
+        VOUCHER_COLUMNS
+    ))?;
+    let vouchers = stmt
+        .query_map(params![include_inactive.unwrap_or(false)], map_voucher)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(vouchers)
+}
+
+/// Withdraw a voucher so it can't be used again. Past redemptions stay.
+#[command]
+pub fn deactivate_voucher(voucher_id: i64, session_token: Option<String>) -> Result<Voucher, AppError> {
+    let conn = get_db_connection()?;
+    let voucher = load_voucher(&conn, voucher_id)?;
+    conn.execute("UPDATE vouchers SET is_active = 0 WHERE id = ?1", params![voucher_id])?;
+    crate::db::log_audit_event(
+        &conn,
+        &audit_actor(session_token.as_deref()),
+        "voucher_withdrawn",
+        &format!("Voucher {} withdrawn", voucher.code),
+    )?;
+    load_voucher(&conn, voucher_id)
+}
+
+/// Check a code before it's used: fails with VOUCHER_NOT_FOUND,
+/// VOUCHER_INACTIVE, VOUCHER_EXPIRED or VOUCHER_USED_UP, otherwise returns
+/// the voucher and, given the bill `amount` (before tax), what it takes off.
+#[command]
+pub fn validate_voucher(code: String, amount: Option<f64>) -> Result<VoucherCheck, AppError> {
+    let conn = get_db_connection()?;
+    let voucher = find_voucher(&conn, &code)?;
+    let uses_left = check_usable(&voucher)?;
+    let discount = amount.filter(|a| a.is_finite()).map(|a| discount_on(&voucher, a));
+    Ok(VoucherCheck { voucher, uses_left, discount })
+}
+
+/// Take a voucher off what is left to pay on an order. The order total comes
+/// down by the voucher's value (a percent is of the order total), and an
+/// order with nothing left to pay is marked paid.
+#[command]
+pub fn redeem_voucher_on_order(order_id: i64, code: String, session_token: Option<String>) -> Result<OrderPaymentSummary, AppError> {
+    let conn = get_db_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    let summary = crate::simple_commands::order_payment_summary(&tx, order_id)?;
+    if summary.balance <= 0.0 {
+        return Err(ORDER_ALREADY_PAID.into());
+    }
+    let voucher = find_voucher(&tx, &code)?;
+    check_usable(&voucher)?;
+    let discount = discount_on(&voucher, summary.total_amount).min(summary.balance);
+
+    let actor = audit_actor(session_token.as_deref());
+    let before = snapshot(&tx, "order", order_id)?;
+    record_redemption(&tx, voucher.id, discount, Some(order_id), None, &actor)?;
+    tx.execute(
+        "UPDATE sales SET total_amount = ROUND(total_amount - ?1, 2) WHERE id = ?2",
+        params![discount, order_id],
+    )?;
+    crate::rounding::round_order_total(&tx, order_id)?;
+    let summary = crate::simple_commands::sync_order_payment_status(&tx, order_id)?;
+    record_change(&tx, &actor, "order", order_id, "update", before, snapshot(&tx, "order", order_id)?)?;
+    tx.commit()?;
+
+    if summary.payment_status == "paid" {
+        crate::fiscal::fiscalize_in_background("order", order_id);
+    }
+    Ok(summary)
+}
+
+/// Where vouchers have been used, newest first; for one voucher when given.
+#[command]
+pub fn get_voucher_redemptions(voucher_id: Option<i64>) -> Result<Vec<VoucherRedemption>, AppError> {
+    let conn = get_db_connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.voucher_id, v.code, r.order_id, r.guest_id, r.amount, r.redeemed_by, r.redeemed_at
+         FROM voucher_redemptions r JOIN vouchers v ON v.id = r.voucher_id
+         WHERE ?1 IS NULL OR r.voucher_id = ?1
+         ORDER BY r.redeemed_at DESC, r.id DESC",
+    )?;
+    let redemptions = stmt
+        .query_map(params![voucher_id], |row| {
+            Ok(VoucherRedemption {
+                id: row.get(0)?,
+                voucher_id: row.get(1)?,
+                code: row.get(2)?,
+                order_id: row.get(3)?,
+                guest_id: row.get(4)?,
+                amount: row.get(5)?,
+                redeemed_by: row.get(6)?,
+                redeemed_at: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(redemptions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simple_commands::insert_food_order;
+    use crate::test_support::{order_item, same_amount, TestDb};
+
+    fn voucher(code: &str) -> NewVoucher {
+        NewVoucher { code: Some(code.to_string()), kind: "amount".to_string(), value: 5.0, expires_on: None, max_uses: None, note: None }
+    }
+
+    #[test]
+    fn codes_are_redeemed_whatever_the_case() {
+        let _db = TestDb::new();
+        create_voucher(voucher("Spring-5"), None).unwrap();
+        let (order_id, _) = insert_food_order(None, "walk_in".to_string(), None, vec![order_item("Tea", 10.0, 2)], None, None, None).unwrap();
+        let before = crate::simple_commands::order_payment_summary(&get_db_connection().unwrap(), order_id).unwrap();
+        let summary = redeem_voucher_on_order(order_id, "  spring-5 ".to_string(), None).unwrap();
+        assert!(same_amount(summary.balance, before.balance - 5.0), "lower-case code took {:.2} off", before.balance - summary.balance);
+        let again = create_voucher(voucher("spring-5"), None);
+        assert!(again.is_err_and(|e| e.code() == VOUCHER_CODE_EXISTS), "a code differing only in case was issued twice");
+    }
+}
@@ -0,0 +1,7 @@
+// Same scenario the run_self_test command runs on a customer's machine
+#[test]
+fn end_to_end_scenario_passes() {
+    let report = app_lib::run_self_test_scenario();
+    let failures: Vec<_> = report.steps.iter().filter(|s| s.status != "passed").collect();
+    assert!(report.passed, "self-test failed: {:#?}", failures);
+}
@@ -1,4 +1,6 @@
-import { invoke } from "@tauri-apps/api/core";
+import { invoke, ApiError } from "./invoke";
+
+export { ApiError } from "./invoke";
 
 // Extend Window interface for Tauri
 declare global {
@@ -33,10 +35,35 @@ async function invokeCompat<T>(
 /**
  * Print a receipt for a food order
  * @param orderId - ID of the order to print receipt for
+ * @param locale - Receipt language; defaults to the receipt language setting
  * @returns Success message
  */
-export const printOrderReceipt = (orderId: number): Promise<string> => 
-  invoke("print_order_receipt", { orderId });
+export const printOrderReceipt = (orderId: number, locale?: string): Promise<string> => 
+  invoke("print_order_receipt", { orderId, locale: locale ?? null });
+
+/**
+ * Print a food order receipt straight to the configured receipt printer,
+ * without opening a browser or print dialog
+ * @param orderId - ID of the order to print receipt for
+ * @param locale - Receipt language; defaults to the receipt language setting
+ * @returns Which printer the receipt was sent to
+ */
+export const printOrderReceiptSilent = (orderId: number, locale?: string): Promise<string> =>
+  invoke("print_order_receipt_silent", { orderId, locale: locale ?? null });
+
+/**
+ * Print the kitchen order ticket (items and notes, no prices) to the kitchen printer
+ * @param orderId - ID of the order to send to the kitchen
+ * @returns Which printer the ticket was sent to
+ */
+export const printKitchenTicket = (orderId: number): Promise<string> =>
+  invoke("print_kitchen_ticket", { orderId });
+
+/**
+ * Kitchen printer name, or null when none is configured
+ */
+export const getKitchenPrinter = (): Promise<string | null> =>
+  invoke("get_kitchen_printer");
 
 // ============================================================================
 
@@ -49,6 +76,9 @@ export interface Room {
   is_occupied: boolean;
   guest_id?: number;
   guest_name?: string;
+  out_of_service?: boolean;
+  floor?: number | null; // 0 = ground floor
+  building?: string | null; // Wing or building
 }
 
 // De-hotelified alias
@@ -61,6 +91,18 @@ export interface NewRoom {
   number: string;
   room_type: string;
   daily_rate: number;
+  floor?: number | null;
+  building?: string | null; // Blank on update takes the room out of its building
+}
+
+// Rooms on one floor of one building, from getFloorOccupancy
+export interface FloorOccupancy {
+  building: string | null;
+  floor: number | null;
+  total_rooms: number;
+  occupied_rooms: number;
+  out_of_service_rooms: number;
+  occupancy_percent: number; // Of the rooms in service
 }
 
 // De-hotelified alias
@@ -104,10 +146,221 @@ export interface NewGuest {
   check_in: string;
   check_out?: string;
   daily_rate: number;
+  profile_id?: number; // A returning guest's profile; matched by phone when left out
 }
 
 export type NewCustomer = NewGuest;
 
+export type GuestChargeCategory = "minibar" | "laundry" | "early_checkin" | "late_checkout" | "telephone" | "other";
+
+export interface GuestDeposit {
+  id: number;
+  guest_id: number;
+  amount: number;
+  method: "cash" | "card" | "bank_transfer";
+  paid_at: string;
+  note?: string;
+}
+
+// Running balance of a stay so far, before any checkout discount or tax
+export interface GuestFolio {
+  guest_id: number;
+  stay_days: number;
+  room_total: number;
+  unpaid_food: number;
+  adjustments: number;
+  extra_charges: number;
+  total_charges: number;
+  deposits: GuestDeposit[];
+  deposits_total: number;
+  balance: number;
+}
+
+export interface GuestProfile {
+  id: number;
+  name: string;
+  phone: string | null;
+  email: string | null;
+  id_number: string | null;
+  nationality: string | null;
+  preferences: string | null;
+  notes: string | null;
+  stay_count: number;
+  last_check_in: string | null;
+  anonymized_at: string | null;
+  created_at: string;
+}
+
+export interface GuestProfileInput {
+  name: string;
+  phone?: string;
+  email?: string;
+  id_number?: string;
+  nationality?: string;
+  preferences?: string;
+  notes?: string;
+}
+
+export interface ProfileStay {
+  guest_id: number;
+  room_number: string | null;
+  check_in: string;
+  check_out: string | null;
+  status: string;
+  adults: number;
+  children: number;
+  spent: number;
+  outstanding: number;
+}
+
+export interface GuestProfileHistory {
+  profile: GuestProfile;
+  stays: ProfileStay[];
+  total_stays: number;
+  total_spent: number;
+  outstanding_balance: number;
+  favourite_items: string[];
+}
+
+export interface DuplicateGuestGroup {
+  matched_on: "phone" | "name";
+  profiles: GuestProfile[];
+}
+
+export interface GuestMergeResult {
+  profile: GuestProfile;
+  profiles_merged: number;
+  stays_moved: number;
+}
+
+export interface GuestAnonymization {
+  profile_id: number | null;
+  stays_anonymized: number;
+  signatures_removed: number;
+  anonymized_at: string;
+}
+
+export interface GroupRoomInput {
+  room_id: number;
+  guest_name?: string; // Defaults to the group's contact
+  phone?: string;
+  daily_rate?: number; // Defaults to the room's rate
+  adults?: number;
+  children?: number;
+}
+
+export interface NewGroupBooking {
+  name: string;
+  contact_name?: string;
+  contact_phone?: string;
+  shared_folio: boolean; // The group pays every room's bill together
+  check_in: string;
+  check_out: string;
+  notes?: string;
+  rooms: GroupRoomInput[];
+  override_occupancy_warning?: boolean; // Book past a soft occupancy cap
+}
+
+export interface GroupMember {
+  reservation_id: number | null;
+  guest_id: number | null;
+  room_id: number | null;
+  room_number: string | null;
+  guest_name: string;
+  check_in: string;
+  check_out: string | null;
+  status: "booked" | "cancelled" | "active" | "checked_out";
+  charges: number;
+  paid: number;
+  balance: number;
+}
+
+export interface GroupBooking {
+  id: number;
+  name: string;
+  contact_name: string | null;
+  contact_phone: string | null;
+  shared_folio: boolean;
+  notes: string | null;
+  created_at: string;
+  members: GroupMember[];
+  balance: number;
+}
+
+export interface GroupMemberResult {
+  room_number: string | null;
+  guest_id: number | null;
+  error: string | null;
+}
+
+// Miscellaneous item billed to a room; included in the checkout total
+export interface GuestCharge {
+  id: number;
+  guest_id: number;
+  category: GuestChargeCategory;
+  description: string;
+  quantity: number;
+  unit_price: number;
+  amount: number;
+  charged_on: string;
+  created_by?: string;
+  created_at: string;
+}
+
+export interface StayFeeRule {
+  fee_type: "flat" | "percent"; // percent is of the stay's daily rate
+  amount: number;
+}
+
+// Check-in/check-out cut-offs; fees outside them are posted to the folio as extra charges
+export interface StayFeeRules {
+  check_in_time: string; // HH:MM
+  check_out_time: string; // HH:MM
+  early_check_in: StayFeeRule | null;
+  late_check_out: StayFeeRule | null;
+}
+
+// The part of a stay spent in one room; a room transfer starts a new segment
+export interface StaySegment {
+  room_id: number | null;
+  room_number: string | null;
+  start_date: string;
+  end_date: string | null; // null for the room the guest is in now
+  nights: number;
+  daily_rate: number;
+  total: number;
+}
+
+// A stay after extendStay, with the room charge projected to the new check-out
+export interface StayExtension {
+  guest_id: number;
+  check_in: string;
+  previous_check_out: string | null;
+  check_out: string;
+  nights: number;
+  added_nights: number;
+  room_total: number;
+  added_amount: number;
+}
+
+// A managed room type; every room's room_type names one of these
+export interface RoomType {
+  id: number;
+  name: string;
+  base_rate: number;
+  max_occupancy: number | null; // null for no limit
+  amenities: string[];
+  room_count: number;
+  occupied_rooms: number;
+}
+
+export interface RoomTypeInput {
+  name: string;
+  base_rate: number;
+  max_occupancy?: number | null;
+  amenities?: string[];
+}
+
 // Menu & Food Orders
 export interface MenuItem {
   id: number;
@@ -120,6 +373,62 @@ export interface MenuItem {
   track_stock?: number;      // 0 = service, 1 = physical product
   stock_quantity?: number;   // only meaningful when track_stock = 1
   low_stock_limit?: number;  // default: 5
+
+  rule_price?: number | null;  // What a happy-hour price rule makes it cost right now
+}
+
+export interface MenuCategory {
+  id: number;
+  name: string;
+  sort_order: number;
+  is_active: boolean;  // Inactive categories' items are left off the menu
+  item_count: number;
+}
+
+export interface ComboItem {
+  menu_item_id: number;
+  item_name: string;
+  quantity: number;
+  unit_price: number;  // The item's own menu price
+}
+
+export interface Combo {
+  id: number;
+  name: string;
+  price: number;
+  items: ComboItem[];
+  items_price: number;  // What the items cost bought separately
+  available: boolean;   // Every item is on the menu and available
+}
+
+export interface ComboInput {
+  name: string;
+  price: number;
+  items: { menu_item_id: number; quantity: number }[];
+}
+
+export interface MenuPriceRule {
+  id: number;
+  name: string;
+  menu_item_id?: number | null;  // Either one item...
+  menu_item_name?: string | null;
+  category?: string | null;      // ...or a whole category
+  weekday_mask: number;          // bit 0 = Monday .. bit 6 = Sunday
+  start_time: string;            // HH:MM
+  end_time: string;              // HH:MM; at or before start_time runs past midnight
+  price?: number | null;         // A fixed price...
+  discount_percent?: number | null;  // ...or a percentage off the menu price
+}
+
+export interface MenuPriceRuleInput {
+  name: string;
+  menu_item_id?: number;
+  category?: string;
+  weekday_mask: number;
+  start_time: string;
+  end_time: string;
+  price?: number;
+  discount_percent?: number;
 }
 
 export interface NewMenuItem {
@@ -134,11 +443,507 @@ export interface NewMenuItem {
   low_stock_limit?: number;
 }
 
-export interface OrderItem {
+export interface MenuImportOptions {
+  update_existing?: boolean; // Rows naming an item already on the menu update it instead of being skipped
+  dry_run?: boolean;
+  default_category?: string | null; // For new items whose row has no category
+  sheet_name?: string | null; // Spreadsheets only; the first sheet by default
+}
+
+export interface MenuImportRow {
+  row: number;
+  name: string | null;
+  status: "created" | "updated" | "skipped" | "failed";
+  reason: string | null;
+  item_id: number | null;
+}
+
+export interface MenuImportReport {
+  dry_run: boolean;
+  created: number;
+  updated: number;
+  skipped: number;
+  failed: number;
+  rows: MenuImportRow[];
+}
+
+// Ingredient stock, in whatever unit the kitchen counts it in
+export interface InventoryItem {
+  id: number;
+  name: string;
+  unit: string;
+  quantity: number;
+  low_stock_limit: number;
+  low_stock: boolean;
+  unit_cost: number;  // Per unit; updated when a purchase order is received
+  updated_at: string;
+}
+
+export interface RecipeIngredientInput {
+  inventory_item_id: number;
+  quantity: number;  // Per portion
+}
+
+export interface RecipeIngredient extends RecipeIngredientInput {
+  name: string;
+  unit: string;
+}
+
+export type StockAdjustmentReason = "restock" | "waste" | "correction";
+
+export interface StockMovement {
+  id: number;
+  inventory_item_id: number;
+  item_name: string;
+  change: number;
+  reason: StockAdjustmentReason | "order";
+  order_id?: number;
+  note?: string;
+  created_by?: string;
+  created_at: string;
+}
+
+// Menu item or ingredient at or below its limit. Orders that take an
+// ingredient below its limit also emit a "stock-low" event with these.
+export interface LowStockItem {
+  kind: "menu_item" | "ingredient";
+  id: number;
+  name: string;
+  stock_quantity: number;
+  low_stock_limit: number;
+  unit?: string;  // Ingredients only
+}
+
+export interface LowStockIngredient {
+  id: number;
+  name: string;
+  unit: string;
+  quantity: number;
+  low_stock_limit: number;
+  shortfall: number;
+  used_in: string[];  // Menu items that need it
+}
+
+export interface MenuItemProfitability {
   menu_item_id: number;
+  name: string;
+  quantity_sold: number;
+  revenue: number;
+  portion_cost: number;  // Current recipe at current ingredient costs
+  cost_of_goods: number;
+  gross_profit: number;
+  margin_percent?: number;
+  costed: boolean;  // False when the item has no recipe
+}
+
+// A supplier and what's owed to them for goods already received
+export interface Supplier {
+  id: number;
+  name: string;
+  contact_name?: string;
+  phone?: string;
+  email?: string;
+  notes?: string;
+  received_value: number;
+  paid: number;
+  balance: number;  // Negative when paid ahead
+}
+
+export interface PurchaseOrderLineInput {
+  inventory_item_id?: number;  // Lines without one are paid for but not stocked
+  description?: string;        // Defaults to the inventory item's name
+  quantity: number;
+  unit_cost: number;
+}
+
+export interface PurchaseOrderLine {
+  id: number;
+  inventory_item_id?: number;
+  description: string;
+  quantity: number;
+  unit_cost: number;
+  received_quantity: number;
+}
+
+export type PurchaseOrderStatus = "ordered" | "partially_received" | "received" | "cancelled";
+
+export interface PurchaseOrder {
+  id: number;
+  supplier_id: number;
+  supplier_name: string;
+  status: PurchaseOrderStatus;
+  order_date: string;
+  expected_date?: string;
+  notes?: string;
+  total_amount: number;
+  amount_paid: number;
+  created_by?: string;
+  received_at?: string;
+  lines: PurchaseOrderLine[];
+}
+
+export interface ReceiveLineInput {
+  line_id: number;
+  quantity: number;
+}
+
+export interface SupplierPayment {
+  id: number;
+  supplier_id: number;
+  purchase_order_id?: number;
+  amount: number;
+  method: "cash" | "card" | "bank_transfer";
+  paid_on: string;
+  note?: string;
+  expense_id?: number;  // The matching row in expenses
+  created_by?: string;
+}
+
+// A company guests can be billed to on credit
+export interface Company {
+  id: number;
+  name: string;
+  contact_name?: string;
+  phone?: string;
+  email?: string;
+  billing_address?: string;
+  credit_limit?: number;  // Absent for no limit
+  notes?: string;
+  charged: number;
+  paid: number;
+  balance: number;  // Still owed by the company
+}
+
+export interface CompanyPayment {
+  id: number;
+  company_id: number;
+  amount: number;
+  method: "cash" | "card" | "bank_transfer";
+  paid_on: string;
+  note?: string;
+  created_by?: string;
+}
+
+export interface CompanyStatementEntry {
+  date: string;
+  kind: "charge" | "payment";
+  reference: string;
+  description: string;
+  amount: number;   // Charges positive, payments negative
+  balance: number;  // Running balance after this entry
+}
+
+export interface CompanyStatement {
+  company: Company;
+  start_date: string;
+  end_date: string;
+  opening_balance: number;
+  entries: CompanyStatementEntry[];
+  total_charges: number;
+  total_payments: number;
+  closing_balance: number;
+}
+
+export interface ReceivableItem {
+  source: "order" | "checkout" | "company";
+  reference_id: number;  // Order, guest or company charge id
+  name: string;
+  date: string;          // When the money became owed
+  age_days: number;
+  bucket: "0-30" | "31-60" | "61+";
+  amount: number;
+}
+
+export interface AgingTotals {
+  days_0_30: number;
+  days_31_60: number;
+  days_61_plus: number;
+  total: number;
+}
+
+export interface ReceivablesAgingReport {
+  as_of: string;
+  items: ReceivableItem[];  // Oldest first
+  orders: AgingTotals;
+  checkouts: AgingTotals;
+  companies: AgingTotals;
+  total: AgingTotals;
+}
+
+export interface Tax {
+  id: number;
+  name: string;
+  percent: number;
+  applies_to: "rooms" | "food" | "all";
+  is_active: boolean;
+}
+
+// One tax charged on a bill
+export interface TaxLine {
+  tax_id: number;
+  name: string;
+  percent: number;
+  applies_to: Tax["applies_to"];
+  taxable: number;  // Part of the discounted bill, before tax, the tax is charged on
+  amount: number;
+  included: boolean;  // Already in the prices, so not added to the total
+}
+
+// Shown at the top of receipts, invoices and registration cards
+export interface BusinessInfo {
+  name: string;
+  address: string;
+  phone: string;
+  email: string;
+  logo_path: string | null;
+}
+
+export type PrintTemplateName = "order_receipt" | "final_invoice" | "kitchen_ticket" | "registration_card";
+
+export interface PrintTemplateInfo {
+  name: PrintTemplateName;
+  title: string;
+  description: string;
+  overridden: boolean;  // A customised copy is saved and printed instead of the built-in layout
+  override_path: string | null;
+}
+
+export interface PrintTemplateSource {
+  name: PrintTemplateName;
+  source: string;  // Handlebars HTML that prints now
+  default_source: string;
+  overridden: boolean;
+}
+
+export interface BackupSchedule {
+  frequency: "off" | "daily" | "weekly";
+  directory: string | null;
+  retention: number;  // Scheduled backups kept; older ones are deleted
+  last_run: string | null;
+  last_backup_path: string | null;
+  last_error: string | null;  // Cleared by the next successful backup
+  next_run: string | null;
+}
+
+export interface BackupFileInfo {
+  file_name: string;
+  path: string;
+  kind: "manual" | "scheduled" | "before_restore";
+  size_bytes: number;  // The .db file alone
+  has_media: boolean;
+  has_json_export: boolean;
+  encrypted: boolean;  // Needs the backup passphrase to restore
+  created_at: string | null;
+}
+
+export interface TableRestoreResult {
+  table: string;
+  inserted: number;  // Rows only in the backup
+  updated: number;  // Rows matched by their natural key and overwritten
+  skipped: number;  // Rows left alone, e.g. settings that belong to this PC
+}
+
+export type CloudBackupProvider = "none" | "s3" | "google_drive" | "webdav";
+
+export interface CloudBackupSettingsInput {
+  provider: CloudBackupProvider;
+  s3_endpoint?: string;  // Defaults to AWS; set for R2, B2, MinIO, ...
+  s3_region?: string;
+  s3_bucket?: string;
+  s3_prefix?: string;  // Folder inside the bucket
+  s3_access_key_id?: string;
+  s3_secret_access_key?: string;
+  gdrive_client_id?: string;
+  gdrive_client_secret?: string;
+  gdrive_refresh_token?: string;
+  gdrive_folder_id?: string;
+  webdav_url?: string;  // Folder the backups are put in
+  webdav_username?: string;
+  webdav_password?: string;
+}
+
+export interface CloudBackupSettings {
+  provider: CloudBackupProvider;
+  s3_endpoint: string | null;
+  s3_region: string | null;
+  s3_bucket: string | null;
+  s3_prefix: string | null;
+  s3_access_key_id: string | null;
+  gdrive_client_id: string | null;
+  gdrive_folder_id: string | null;
+  webdav_url: string | null;
+  webdav_username: string | null;
+  has_secret: boolean;  // Secrets are saved; they are never sent back
+}
+
+export interface CloudBackupStatus {
+  provider: CloudBackupProvider;
+  status: "uploading" | "uploaded" | "failed" | null;
+  last_attempt: string | null;
+  last_sync: string | null;  // Last successful upload
+  last_error: string | null;
+  last_files: string[];
+}
+
+export interface DatabaseEncryptionStatus {
+  encrypted: boolean;
+  mode: "keyring" | "password" | null;
+  username: string | null;  // Admin whose password unlocks it, in password mode
+  locked: boolean;  // Waiting for unlockDatabase; nothing else works until then
+}
+
+export type SearchHitKind = "guest" | "order" | "menu_item" | "expense";
+
+export interface SearchHit {
+  kind: SearchHitKind;
+  id: number;        // Id in that kind's table
+  title: string;
+  detail?: string;   // Phone for guests, category for expenses
+}
+
+// One page of a list. Each *_page command documents the sort keys and
+// filters it accepts; unknown ones fail with INVALID_PAGE_REQUEST.
+export interface PageRequest {
+  limit?: number;   // Default 50, at most 500
+  offset?: number;
+  sort?: string;    // Sort key, "-" prefix for descending (e.g. "-created_at")
+  filters?: Record<string, string>;
+}
+
+export interface PageResult<T> {
+  items: T[];
+  total: number;    // Rows matching the filters, across all pages
+  limit: number;
+  offset: number;
+  next_offset: number | null;  // null on the last page
+}
+
+// Sent to every window after a change is committed; listen with
+// listen<Payload>(EVENT, ...) from @tauri-apps/api/event instead of polling.
+export const GUEST_CHANGED_EVENT = "guest-changed";
+export const ORDER_CREATED_EVENT = "order-created";
+export const ROOM_STATUS_CHANGED_EVENT = "room-status-changed";
+
+export interface GuestChangedEvent {
+  guest_id: number | null;  // null when many guests changed (import)
+  change: "checked_in" | "updated" | "checked_out" | "imported";
+}
+
+export interface RoomStatusChangedEvent {
+  room_id: number | null;  // null when many rooms changed
+  change: "added" | "updated" | "deleted" | "occupied" | "vacated" | "out_of_service" | "in_service";
+}
+
+export interface OrderCreatedEvent {
+  order_id: number;
+  guest_id: number | null;
+  table_id: number | null;
+  total_amount: number;
+}
+
+export interface ReceiptQrSetting {
+  mode: "off" | "details" | "payment_link";
+  payment_link: string | null;  // May use {reference}, {amount} and {currency}
+}
+
+export interface ReceiptLanguageInfo {
+  code: string;
+  name: string;
+  rtl: boolean;  // Printed right-to-left
+}
+
+export type NotificationEventType = "booking_confirmation" | "checkout_invoice" | "payment_reminder";
+export type NotificationChannel = "sms" | "whatsapp";
+
+export interface NotificationProviderSettings {
+  provider: "none" | "twilio" | "whatsapp_business";
+  twilio_account_sid: string | null;
+  twilio_from: string | null;           // SMS sender number
+  twilio_whatsapp_from: string | null;  // WhatsApp-enabled sender number
+  whatsapp_phone_number_id: string | null;
+  has_secret: boolean;  // Auth token / access token saved; never sent back
+}
+
+export interface NotificationEventSetting {
+  event_type: NotificationEventType;
+  enabled: boolean;
+  channel: NotificationChannel;
+  template: string;  // Message text with {placeholders}
+  placeholders: string[];
+}
+
+export interface NotificationLogEntry {
+  id: number;
+  event_type: NotificationEventType | "test";
+  channel: NotificationChannel;
+  recipient: string;
+  message: string;
+  status: "sent" | "failed";
+  error: string | null;
+  provider_message_id: string | null;
+  guest_id: number | null;
+  reservation_id: number | null;
+  created_at: string;
+}
+
+export interface ServiceChargeSetting {
+  business_mode: "hotel" | "restaurant" | "retail";
+  percent: number;
+}
+
+export interface ServiceChargeDay {
+  date: string;
+  orders: number;
+  charged: number;
+  collected: number;  // Paid so far; orders of checked-out guests count in full
+}
+
+export interface ServiceChargeReport {
+  start_date: string;
+  end_date: string;
+  days: ServiceChargeDay[];
+  total_charged: number;
+  total_collected: number;
+}
+
+export interface CheckoutTotals {
+  room_total: number;
+  unpaid_food: number;
+  adjustments: number;
+  extra_charges: number;
+  tax: number;  // All taxes on the bill after discounts, added or included; 0 when tax is off
+  taxes: TaxLine[];
+  rounding: number;  // Adjustment from the rounding setting, added to the total
+  grand_total: number;
+  deposits: number;
+  balance_due: number;  // grand_total less deposits
+  stay_days: number;
+}
+
+// What reopenCheckout undid
+export interface ReopenedStay {
+  guest_id: number;
+  check_out: string | null;
+  room_restored: boolean;  // false when someone else has the room now
+  payments_reversed: number;
+  company_charges_reversed: number;
+  taxes_reversed: number;
+}
+
+// A change to one order line; price_delta is per unit and may be negative
+export interface OrderItemModifier {
+  name: string;
+  price_delta?: number;
+}
+
+export interface OrderItem {
+  menu_item_id: number | null;  // null for a combo line
   item_name: string;
   quantity: number;
-  unit_price: number;
+  unit_price: number;  // Before modifiers
+  notes?: string;
+  modifiers?: OrderItemModifier[];
+  combo_id?: number;  // The combo this line sells; its items are cooked and taken from stock
 }
 
 export interface FoodOrder {
@@ -172,17 +977,78 @@ export interface FoodOrderInfo {
   paid: boolean;
   paid_at?: string;
   total_amount: number;
+  status?: OrderStatus;
 }
 
 export type SaleRecord = FoodOrderInfo;
 
+export type OrderStatus = "pending" | "preparing" | "served" | "closed";
+
+// Restaurant table; open from its first order until it's closed
+export interface DiningTable {
+  id: number;
+  number: string;
+  capacity: number;
+  status: "free" | "open";
+  opened_at?: string;
+  open_orders: number;
+  unpaid_amount: number;
+}
+
+// An order as the kitchen sees it, with when it reached each status
+export interface KitchenOrder {
+  id: number;
+  guest_id?: number;
+  customer_name?: string;
+  room_number?: string;
+  table_id?: number;
+  table_number?: string;
+  status: OrderStatus;
+  notes?: string;
+  created_at: string;
+  preparing_at?: string;
+  served_at?: string;
+  closed_at?: string;
+  total_amount: number;
+  paid: boolean;
+  items: OrderItemDetail[];
+}
+
 export interface OrderItemDetail {
   id: number;
   menu_item_id?: number;
   item_name: string;
   quantity: number;
   unit_price: number;
-  line_total: number;
+  line_total: number;  // Includes modifiers; zero once voided
+  notes?: string;
+  modifiers?: OrderItemModifier[];
+  voided?: boolean;
+  combo_id?: number | null;
+  components?: OrderItemComponent[];  // What a combo line is made of
+  original_unit_price?: number | null;  // Price before a happy-hour rule
+  price_rule_id?: number | null;
+}
+
+export interface OrderItemComponent {
+  menu_item_id: number | null;
+  item_name: string;
+  quantity: number;  // Per combo
+}
+
+export type VoidReason = "comp" | "wrong_item" | "quality_issue" | "kitchen_error" | "customer_changed_mind" | "other";
+
+export interface VoidedItem {
+  id: number;
+  sale_item_id: number;
+  order_id: number;
+  item_name: string;
+  quantity: number;
+  amount: number;  // Line total taken off the order
+  reason: VoidReason;
+  note?: string;
+  voided_by?: string;
+  voided_at: string;
 }
 
 export interface FoodOrderDetails {
@@ -190,37 +1056,355 @@ export interface FoodOrderDetails {
   items: OrderItemDetail[];
 }
 
-export type SaleDetails = FoodOrderDetails;
+export type SaleDetails = FoodOrderDetails;
+
+export interface NewFoodOrder {
+  guest_id: number | null;  // Allow null for walk-in customers
+  items: OrderItem[];
+  notes?: string;  // Printed on the kitchen ticket
+  table_id?: number;  // Restaurant mode: opens the table if it's free
+}
+
+export type NewSale = NewFoodOrder;
+
+// Guest with orders for display purposes
+export interface GuestWithOrders extends Guest {
+  food_orders?: FoodOrder[];
+  room_number?: number;
+  check_in_date?: string;
+  check_out_date?: string;
+}
+
+// Expenses
+export interface ExpenseRecord {
+  id: number;
+  date: string;
+  category: string;
+  description: string;
+  amount: number;
+  tax_amount: number;     // Tax included in the amount, reclaimable on the tax return
+}
+
+export interface TrashItem {
+  kind: "order" | "expense";
+  id: number;
+  label: string;       // Customer for an order, description for an expense
+  amount: number;
+  date: string;        // When the order was taken / the expense's date
+  deleted_at: string;
+  deleted_by?: string | null;
+}
+
+export interface NewExpense {
+  date: string;
+  category: string;
+  description: string;
+  amount: number;
+  taxAmount?: number;     // Tax included in the amount
+}
+
+export interface ExpenseCategory {
+  id: number;
+  name: string;
+  expense_count: number;  // Expenses and drawings booked under it
+}
+
+// Which column holds each field: a header caption or a column letter such as "C"
+export interface ExpenseImportMapping {
+  date: string;
+  amount: string;
+  description?: string | null;
+  category?: string | null;
+  default_category?: string | null;  // For rows with no category
+  bank_statement?: boolean;  // Signed amounts: money out is imported, money in skipped
+  dry_run?: boolean;
+  sheet_name?: string | null;
+  acknowledge_unusual_dates?: boolean;
+}
+
+export interface ImportCounts {
+  created: number;
+  skipped: number;
+  failed: number;
+}
+
+// A record that was skipped or couldn't be imported; source is e.g. "guests.csv row 4"
+export interface ImportIssue {
+  entity: "room" | "guest" | "order" | "expense";
+  source: string;
+  status: "skipped" | "failed";
+  reason: string;
+}
+
+export interface LegacyImportReport {
+  adapter: string;
+  dry_run: boolean;
+  rooms: ImportCounts;
+  guests: ImportCounts;
+  orders: ImportCounts;
+  expenses: ImportCounts;
+  issues: ImportIssue[];
+}
+
+export interface ImportAdapterInfo {
+  name: string;  // Passed to importLegacyData, e.g. "csv_bundle" or "legacy_database"
+  description: string;
+}
+
+export interface ExpenseImportRow {
+  row: number;
+  status: "created" | "skipped" | "failed";
+  reason: string | null;
+  expense_id: number | null;
+}
+
+export interface ExpenseImportReport {
+  dry_run: boolean;
+  created: number;
+  skipped: number;
+  failed: number;
+  total_amount: number;  // Of the expenses created
+  rows: ExpenseImportRow[];
+}
+
+export interface ExpenseBudget {
+  category: string;
+  monthly_amount: number;
+  updated_at: string;
+}
+
+export interface BudgetLine {
+  category: string;
+  budget?: number;        // Missing when the category has no budget
+  actual: number;
+  variance?: number;      // actual - budget; positive means over plan
+  percent_used?: number;
+  over_budget: boolean;
+}
+
+export interface BudgetVsActualReport {
+  year: number;
+  month: number;          // 1-12
+  lines: BudgetLine[];    // Furthest over budget first; unbudgeted spend last
+  total_budget: number;
+  total_actual: number;   // All business expenses, budgeted or not
+  total_variance: number;
+}
+
+// Ledger
+export interface LedgerAccount {
+  id: number;
+  code: string;
+  name: string;
+  account_type: 'asset' | 'liability' | 'equity' | 'revenue' | 'expense';
+  is_system: boolean;     // Posted to automatically
+}
+
+export interface JournalLine {
+  account_id: number;
+  account_code: string;
+  account_name: string;
+  debit: number;
+  credit: number;
+}
+
+export interface JournalEntry {
+  id: number;
+  entry_date: string;
+  memo: string;
+  source_type: 'payment' | 'deposit_applied' | 'expense' | null;  // null for manual entries
+  source_id: number | null;
+  reversal_of: number | null;
+  reversed_by: number | null;
+  lines: JournalLine[];
+}
+
+export interface NewJournalLine {
+  account_id: number;
+  debit?: number;
+  credit?: number;
+}
+
+export interface TrialBalanceLine {
+  account_id: number;
+  code: string;
+  name: string;
+  account_type: LedgerAccount['account_type'];
+  debit: number;          // Net debit balance, or 0
+  credit: number;         // Net credit balance, or 0
+}
+
+export interface TrialBalance {
+  as_of: string;
+  lines: TrialBalanceLine[];
+  total_debit: number;
+  total_credit: number;
+}
+
+export interface ProfitAndLossLine {
+  code: string;
+  name: string;
+  amount: number;
+}
+
+export interface ProfitAndLoss {
+  start_date: string;
+  end_date: string;
+  revenue: ProfitAndLossLine[];
+  expenses: ProfitAndLossLine[];
+  total_revenue: number;
+  total_expenses: number;
+  net_income: number;
+}
+
+// Tax return
+export interface TaxReportLine {
+  tax_id: number | null;
+  name: string;
+  percent: number;
+  applies_to: 'rooms' | 'food' | 'all';
+  taxable: number;        // Sales, net of tax, the tax was charged on
+  collected: number;
+  bills: number;
+}
+
+export interface ExpenseTaxLine {
+  category: string;
+  amount: number;         // Expenses in the category, tax included
+  tax: number;
+}
+
+export interface TaxReport {
+  start_date: string;
+  end_date: string;
+  checkouts: number;
+  gross_sales: number;    // Checkout bills, tax included
+  net_sales: number;      // Checkout bills less the tax on them
+  taxes: TaxReportLine[];
+  total_collected: number;
+  expenses: ExpenseTaxLine[];
+  total_expense_tax: number;
+  net_payable: number;    // Negative when a refund is due
+  generated_at: string;
+}
+
+// Vouchers and promo codes
+export interface OrderPaymentSummary {
+  order_id: number;
+  total_amount: number;
+  amount_paid: number;
+  balance: number;
+  payment_status: 'paid' | 'partially_paid' | 'unpaid';
+}
+
+export interface Voucher {
+  id: number;
+  code: string;
+  kind: 'amount' | 'percent';
+  value: number;                   // Amount off, or percent off the bill
+  expires_on: string | null;       // Last day it can be used
+  max_uses: number | null;         // 1 for single use; null for no limit
+  uses: number;
+  is_active: boolean;
+  note: string | null;
+  created_at: string;
+}
+
+export interface NewVoucher {
+  code?: string;                   // Generated when left out
+  kind: 'amount' | 'percent';
+  value: number;
+  expires_on?: string;
+  max_uses?: number;
+  note?: string;
+}
+
+export interface VoucherCheck {
+  voucher: Voucher;
+  uses_left: number | null;
+  discount: number | null;         // What it takes off the amount given
+}
+
+export interface VoucherRedemption {
+  id: number;
+  voucher_id: number;
+  code: string;
+  order_id: number | null;
+  guest_id: number | null;         // Set when it came off a checkout bill
+  amount: number;
+  redeemed_by: string | null;
+  redeemed_at: string;
+}
+
+// Online card payments
+export type PaymentGatewayProvider = "none" | "stripe";
 
-export interface NewFoodOrder {
-  guest_id: number | null;  // Allow null for walk-in customers
-  items: OrderItem[];
+export interface PaymentGatewaySettingsInput {
+  provider: PaymentGatewayProvider;
+  stripe_secret_key?: string;      // Leave out to keep the saved key
+  stripe_publishable_key?: string;
 }
 
-export type NewSale = NewFoodOrder;
-
-// Guest with orders for display purposes
-export interface GuestWithOrders extends Guest {
-  food_orders?: FoodOrder[];
-  room_number?: number;
-  check_in_date?: string;
-  check_out_date?: string;
+export interface PaymentGatewaySettings {
+  provider: PaymentGatewayProvider;
+  stripe_publishable_key: string | null;
+  test_mode: boolean;
+  has_secret: boolean;
 }
 
-// Expenses
-export interface ExpenseRecord {
+export interface OnlinePayment {
   id: number;
-  date: string;
-  category: string;
-  description: string;
+  source_type: 'order' | 'folio';
+  source_id: number;               // Order ID, or the guest ID for a folio
+  provider: string;
+  intent_id: string;
+  client_secret: string | null;    // For the card form to confirm the payment
   amount: number;
+  currency: string;
+  status: string;                  // The gateway's status; "succeeded" once paid
+  payment_id: number | null;
+  note: string | null;
+  paid_at: string | null;
+  created_at: string;
 }
 
-export interface NewExpense {
-  date: string;
-  category: string;
-  description: string;
-  amount: number;
+// Fiscal e-invoicing
+export type FiscalProvider = "none" | "fbr" | "webhook";
+
+export interface FiscalSettingsInput {
+  provider: FiscalProvider;
+  fbr_environment?: 'sandbox' | 'live';
+  fbr_pos_id?: string;
+  fbr_token?: string;        // Leave out to keep the saved token
+  fbr_pct_code?: string;
+  webhook_url?: string;
+  webhook_token?: string;    // Leave out to keep the saved token
+  print_on_receipt: boolean;
+}
+
+export interface FiscalSettings {
+  provider: FiscalProvider;
+  fbr_environment: 'sandbox' | 'live';
+  fbr_pos_id: string | null;
+  fbr_pct_code: string | null;
+  webhook_url: string | null;
+  print_on_receipt: boolean;
+  has_secret: boolean;
+}
+
+export interface FiscalRecord {
+  id: number;
+  source_type: 'order' | 'checkout';
+  source_id: number;         // Order ID, or the guest ID for a checkout bill
+  usin: string;
+  provider: string;
+  status: 'pending' | 'submitted' | 'failed';
+  fiscal_number: string | null;
+  qr_payload: string | null;
+  error: string | null;
+  attempts: number;
+  submitted_at: string | null;
+  created_at: string;
 }
 
 // Dashboard & Analytics
@@ -236,6 +1420,109 @@ export interface DashboardStats {
   occupancy_rate: number;
 }
 
+// Parallel label/value arrays, ready to hand to a chart
+export interface ChartSeries {
+  labels: string[];
+  values: number[];
+}
+
+// Daily buckets are labelled YYYY-MM-DD, weekly ones by their Monday, monthly ones YYYY-MM
+export type TimeseriesGranularity = "daily" | "weekly" | "monthly";
+
+export interface RevenueTimeseries {
+  granularity: TimeseriesGranularity;
+  range_start: string;
+  range_end: string;
+  room_income: ChartSeries;
+  food_income: ChartSeries;
+  total_income: ChartSeries;
+}
+
+export interface ExpenseTimeseries {
+  granularity: TimeseriesGranularity;
+  range_start: string;
+  range_end: string;
+  expenses: ChartSeries;  // Business expenses only
+  owner_drawings: ChartSeries;
+}
+
+export interface PaymentMethodTotal {
+  method: string;
+  order_amount: number;
+  order_count: number;
+  checkout_amount: number;
+  checkout_count: number;
+  total: number;
+}
+
+export interface NightAuditStay {
+  guest_id: number;
+  name: string;
+  room_number: string;
+  check_in: string;
+  check_out?: string;
+  amount: number;    // The night's rate, or the billed total for check-outs
+  balance?: number;  // Folio balance, for guests still checked in
+}
+
+export interface NightAuditReport {
+  date: string;
+  check_ins: NightAuditStay[];
+  check_outs: NightAuditStay[];
+  in_house: NightAuditStay[];
+  total_rooms: number;
+  occupancy_percent: number;
+  room_revenue: number;
+  food_orders: number;
+  food_revenue: number;
+  payments: PaymentMethodTotal[];
+  payments_total: number;
+  outstanding: ReceivableItem[];  // Owed from the date or earlier, as it stands now
+  outstanding_total: number;
+  generated_at: string;
+}
+
+export interface ShiftPayment {
+  id: number;
+  order_id?: number;
+  guest_id?: number;  // Checkout payments and deposits
+  amount: number;     // Negative for refunds
+  method: string;
+  paid_at: string;
+  note?: string;
+}
+
+export interface ShiftMethodTotal {
+  method: "cash" | "card" | "bank_transfer";
+  payment_count: number;
+  taken: number;
+  refunded: number;
+  net: number;
+}
+
+export interface ShiftDenominationCount {
+  value: number;
+  count: number;
+  subtotal: number;
+}
+
+export interface ShiftReconciliation {
+  shift_id: number;
+  status: "open" | "closed";
+  opened_at: string;
+  closed_at?: string;
+  start_cash: number;
+  methods: ShiftMethodTotal[];  // cash, card, bank_transfer
+  payments: ShiftPayment[];
+  cash_sales: number;           // Net cash taken
+  total_expenses: number;
+  owner_drawings: number;
+  expected_cash: number;
+  counted_cash?: number;        // Set at close
+  difference?: number;          // Counted minus expected; negative when short
+  denominations?: ShiftDenominationCount[];
+}
+
 // Authentication
 export interface LoginCredentials {
   username: string;
@@ -289,12 +1576,16 @@ export const addRoom = async (room: NewRoom): Promise<number> => {
     number: room.number,
     resourceType: room.room_type,
     dailyRate: room.daily_rate,
+    floor: room.floor ?? null,
+    building: room.building ?? null,
   };
 
   const fallbackParams = {
     number: room.number,
     roomType: room.room_type,
     dailyRate: room.daily_rate,
+    floor: room.floor ?? null,
+    building: room.building ?? null,
   };
   
   return invokeCompat<number>("add_resource", primaryParams, "add_room", fallbackParams);
@@ -308,20 +1599,29 @@ export const addUnit = (unit: NewUnit): Promise<number> => addRoom(unit);
 
 /**
  * Get all rooms in the hotel
+ * @param filter - Optional floor and/or building to list only those rooms
  * @returns Array of all rooms with their current status
  * @example
  * ```ts
  * const rooms = await getRooms();
- * console.log(`Found ${rooms.length} rooms`);
+ * const eastFirstFloor = await getRooms({ floor: 1, building: "East" });
  * ```
  */
-export const getRooms = (): Promise<Room[]> => 
-  invokeCompat<Room[]>("get_resources", undefined, "get_rooms");
+export const getRooms = (filter?: { floor?: number; building?: string }): Promise<Room[]> => {
+  const params = { floor: filter?.floor ?? null, building: filter?.building ?? null };
+  return invokeCompat<Room[]>("get_resources", params, "get_rooms", params);
+};
 
-export const getResources = (): Promise<Resource[]> => getRooms();
+export const getResources = (filter?: { floor?: number; building?: string }): Promise<Resource[]> => getRooms(filter);
 
 // UI-facing generic wrapper (preferred)
-export const getUnits = (): Promise<Unit[]> => getRooms();
+export const getUnits = (filter?: { floor?: number; building?: string }): Promise<Unit[]> => getRooms(filter);
+
+/**
+ * Room counts and occupancy per floor of each building
+ */
+export const getFloorOccupancy = (): Promise<FloorOccupancy[]> =>
+  invoke("get_floor_occupancy");
 
 /**
  * Get available rooms for guest assignment/editing
@@ -357,9 +1657,23 @@ export const getAvailableUnitsForCustomer = (customerId?: number): Promise<Unit[
 export const updateRoom = (roomId: number, updates: Partial<NewRoom>): Promise<boolean> => 
   invokeCompat<boolean>(
     "update_resource",
-    { resource_id: roomId, number: updates.number, daily_rate: updates.daily_rate },
+    {
+      resource_id: roomId,
+      number: updates.number,
+      daily_rate: updates.daily_rate,
+      resource_type: updates.room_type,
+      floor: updates.floor,
+      building: updates.building,
+    },
     "update_room",
-    { room_id: roomId, number: updates.number, daily_rate: updates.daily_rate }
+    {
+      room_id: roomId,
+      number: updates.number,
+      daily_rate: updates.daily_rate,
+      room_type: updates.room_type,
+      floor: updates.floor,
+      building: updates.building,
+    }
   );
 
 // UI-facing generic wrapper (preferred)
@@ -412,7 +1726,8 @@ export const addGuest = async (guest: NewGuest): Promise<number> => {
     roomId: guest.room_id || null,  // Use camelCase to match expected parameter
     checkIn: guest.check_in,        // Use camelCase to match expected parameter
     checkOut: guest.check_out,      // Use camelCase to match expected parameter
-    dailyRate: guest.daily_rate     // Use camelCase to match expected parameter
+    dailyRate: guest.daily_rate,    // Use camelCase to match expected parameter
+    profileId: guest.profile_id ?? null
   };
   
   return invokeCompat<number>("add_customer", params, "add_guest", params);
@@ -438,6 +1753,14 @@ export const getActiveCustomers = (): Promise<ActiveCustomerRow[]> => getActiveG
 export const getAllGuests = (): Promise<Guest[]> => 
   invokeCompat<Guest[]>("get_all_customers", undefined, "get_all_guests");
 
+/**
+ * Get guests a page at a time
+ * Sorts: created_at, name, check_in, check_out, status.
+ * Filters: status, room_id, search (name or phone), start_date, end_date (check-in).
+ */
+export const getGuestsPage = (page?: PageRequest, tagIds?: number[]): Promise<PageResult<Guest>> =>
+  invoke("get_guests_page", { page, tagIds });
+
 // UI-facing generic wrapper (preferred)
 export const getCustomers = (): Promise<Customer[]> => getAllGuests();
 
@@ -499,10 +1822,191 @@ export const updateGuest = (guestId: number, updates: Partial<NewGuest>): Promis
 export const updateCustomer = (customerId: number, updates: Partial<NewCustomer>): Promise<boolean> =>
   updateGuest(customerId, updates);
 
+/**
+ * Push an in-house guest's check-out to a later date, if no reservation holds the room for the added nights
+ * and the property stays under its occupancy cap
+ */
+export const extendStay = (
+  guestId: number,
+  newCheckOut: string,
+  acknowledgeUnusualDate?: boolean,
+  overrideOccupancyWarning?: boolean,
+  sessionToken?: string
+): Promise<StayExtension> =>
+  invoke("extend_stay", {
+    guestId,
+    newCheckOut,
+    acknowledgeUnusualDate: acknowledgeUnusualDate ?? null,
+    overrideOccupancyWarning: overrideOccupancyWarning ?? null,
+    sessionToken: sessionToken ?? null,
+  });
+
+/**
+ * Bill a minibar, laundry, late checkout or other item to an in-house guest
+ * @param description - Defaults to the category name
+ * @param chargedOn - Defaults to today (YYYY-MM-DD)
+ */
+export const addGuestCharge = (
+  guestId: number,
+  category: GuestChargeCategory,
+  unitPrice: number,
+  quantity?: number,
+  description?: string,
+  chargedOn?: string
+): Promise<GuestCharge> =>
+  invoke("add_guest_charge", {
+    guestId,
+    category,
+    unitPrice,
+    quantity: quantity ?? null,
+    description: description ?? null,
+    chargedOn: chargedOn ?? null
+  });
+
+export const getGuestCharges = (guestId: number): Promise<GuestCharge[]> =>
+  invoke("get_guest_charges", { guestId });
+
+/**
+ * Take a deposit from an in-house guest; checkout only settles what's left
+ * @returns The guest's folio with the new balance
+ */
+export const addGuestDeposit = (
+  guestId: number,
+  amount: number,
+  method: GuestDeposit["method"],
+  note?: string
+): Promise<GuestFolio> =>
+  invoke("add_guest_deposit", { guestId, amount, method, note: note ?? null });
+
+/**
+ * Charges so far, deposits paid and the balance for an in-house guest
+ */
+export const getGuestFolio = (guestId: number): Promise<GuestFolio> =>
+  invoke("get_guest_folio", { guestId });
+
+/**
+ * Guest profiles, most recently seen first, searched by name, phone, email or ID number
+ */
+export const getGuestProfiles = (search?: string): Promise<GuestProfile[]> =>
+  invoke("get_guest_profiles", { search: search ?? null });
+
+export const getGuestProfile = (profileId: number): Promise<GuestProfile> =>
+  invoke("get_guest_profile", { profileId });
+
+export const updateGuestProfile = (
+  profileId: number,
+  profile: GuestProfileInput,
+  sessionToken?: string
+): Promise<GuestProfile> =>
+  invoke("update_guest_profile", { profileId, profile, sessionToken: sessionToken ?? null });
+
+/**
+ * A returning guest's stays, total spend, outstanding balance and usual orders
+ */
+export const getGuestProfileHistory = (profileId: number): Promise<GuestProfileHistory> =>
+  invoke("get_guest_profile_history", { profileId });
+
+/**
+ * Profiles that look like the same guest entered twice, grouped by shared phone or name
+ */
+export const findDuplicateGuests = (): Promise<DuplicateGuestGroup[]> =>
+  invoke("find_duplicate_guests");
+
+/**
+ * Fold duplicate profiles into one; their stays (with orders and payments) move to the primary
+ */
+export const mergeGuests = (
+  primaryId: number,
+  duplicateIds: number[],
+  sessionToken?: string
+): Promise<GuestMergeResult> =>
+  invoke("merge_guests", { primaryId, duplicateIds, sessionToken: sessionToken ?? null });
+
+/**
+ * Everything held about a guest (profile, stays, orders, payments, messages) as JSON,
+ * for a data access request. Any one of the guest's stays identifies them.
+ */
+export const exportGuestData = (guestId: number): Promise<Record<string, unknown>> =>
+  invoke("export_guest_data", { guestId });
+
+/**
+ * Erase a checked-out guest's personal details across all their stays, keeping amounts
+ * and payments. Managers and admins only; cannot be undone.
+ */
+export const anonymizeGuest = (guestId: number, sessionToken: string): Promise<GuestAnonymization> =>
+  invoke("anonymize_guest", { guestId, sessionToken });
+
+/**
+ * Book several rooms for one party; either every room is booked or none
+ */
+export const createGroupBooking = (group: NewGroupBooking): Promise<GroupBooking> =>
+  invoke("create_group_booking", { group });
+
+export const getGroupBookings = (): Promise<GroupBooking[]> =>
+  invoke("get_group_bookings");
+
+export const getGroupBooking = (groupId: number): Promise<GroupBooking> =>
+  invoke("get_group_booking", { groupId });
+
+/**
+ * Check in every booked room of the group; rooms that fail come back with their error
+ */
+export const checkInGroup = (
+  groupId: number,
+  overrideOccupancyWarning?: boolean,
+  sessionToken?: string
+): Promise<GroupMemberResult[]> =>
+  invoke("check_in_group", { groupId, overrideOccupancyWarning: overrideOccupancyWarning ?? null, sessionToken: sessionToken ?? null });
+
+/**
+ * Check out every in-house room of the group, settling each balance with one payment method
+ */
+export const checkOutGroup = (
+  groupId: number,
+  paymentMethod?: "cash" | "card" | "bank_transfer",
+  sessionToken?: string
+): Promise<GroupMemberResult[]> =>
+  invoke("check_out_group", { groupId, paymentMethod: paymentMethod ?? null, sessionToken: sessionToken ?? null });
+
+export const buildGroupInvoiceHtml = (groupId: number): Promise<string> =>
+  invoke("build_group_invoice_html", { groupId });
+
+/**
+ * Save the register of guests who stayed in the period (nationality, document number,
+ * arrival and departure) as CSV or printable HTML, for the police or foreigners' registration office
+ * @returns Path of the saved file
+ */
+export const exportGuestRegistrationReport = (startDate: string, endDate: string, format: 'csv' | 'html'): Promise<string> =>
+  invoke("export_guest_registration_report", { startDate, endDate, format });
+
+/**
+ * Remove a charge posted by mistake, while the guest is still in house
+ */
+export const deleteGuestCharge = (chargeId: number): Promise<string> =>
+  invoke("delete_guest_charge", { chargeId });
+
+export const getStayFeeRules = (): Promise<StayFeeRules> =>
+  invoke("get_stay_fee_rules");
+
+export const setStayFeeRules = (rules: StayFeeRules): Promise<StayFeeRules> =>
+  invoke("set_stay_fee_rules", { rules });
+
+/**
+ * Move an in-house guest to another room from `effectiveDate` (YYYY-MM-DD, today or earlier).
+ * Nights before it stay billed at the old room's rate; the final invoice lists both rooms.
+ */
+export const transferGuestRoom = (
+  guestId: number,
+  newRoomId: number,
+  effectiveDate: string,
+  sessionToken?: string
+): Promise<StaySegment[]> =>
+  invoke("transfer_guest_room", { guestId, newRoomId, effectiveDate, sessionToken: sessionToken ?? null });
+
 // Menu Management APIs
 /**
- * Get all menu items
- * @returns Array of all menu items
+ * Get the menu items on offer, grouped by category in the categories' order
+ * @returns Array of menu items
  */
 export const getMenuItems = (): Promise<MenuItem[]> => 
   invoke("get_menu_items");
@@ -561,6 +2065,309 @@ export const deleteMenuItem = async (itemId: number): Promise<boolean> => {
   return invoke<boolean>("delete_menu_item", { itemId: itemId });
 };
 
+/**
+ * Load menu items from a CSV file or spreadsheet with name, price and optional
+ * category and availability columns, matched to the menu by name
+ * @returns How each row went, with the reason for any that failed
+ */
+export const importMenuCsv = (path: string, options?: MenuImportOptions): Promise<MenuImportReport> =>
+  invoke("import_menu_csv", { path, options: options ?? null });
+
+/**
+ * Menu categories, in the order the menu shows them (inactive ones included).
+ * Items filed under a category that doesn't exist yet add it at the end.
+ */
+export const getMenuCategories = (): Promise<MenuCategory[]> =>
+  invoke("get_menu_categories");
+
+export const addMenuCategory = (name: string): Promise<MenuCategory> =>
+  invoke("add_menu_category", { name });
+
+/**
+ * Rename a category (its items follow) or switch it off to take its items off the menu
+ */
+export const updateMenuCategory = (categoryId: number, name: string, isActive: boolean, sessionToken?: string): Promise<MenuCategory> =>
+  invoke("update_menu_category", { categoryId, name, isActive, sessionToken: sessionToken ?? null });
+
+/**
+ * Delete a category no item uses; fails with MENU_CATEGORY_IN_USE otherwise
+ */
+export const deleteMenuCategory = (categoryId: number): Promise<void> =>
+  invoke("delete_menu_category", { categoryId });
+
+/**
+ * Put categories in the order given; any left out follow in their current order
+ */
+export const reorderMenuCategories = (categoryIds: number[]): Promise<MenuCategory[]> =>
+  invoke("reorder_menu_categories", { categoryIds });
+
+/**
+ * Combos: menu items sold together at one price. Order one as a line with
+ * combo_id set (and no menu_item_id); its items go on the kitchen ticket and
+ * come out of stock.
+ */
+export const getCombos = (): Promise<Combo[]> =>
+  invoke("get_combos");
+
+export const addCombo = (combo: ComboInput): Promise<Combo> =>
+  invoke("add_combo", { combo });
+
+export const updateCombo = (comboId: number, combo: ComboInput): Promise<Combo> =>
+  invoke("update_combo", { comboId, combo });
+
+export const deleteCombo = (comboId: number): Promise<void> =>
+  invoke("delete_combo", { comboId });
+
+/**
+ * Happy-hour price rules for a menu item or category. New orders taken inside
+ * a rule's window are charged its price when lower than the menu price.
+ */
+export const getMenuPriceRules = (): Promise<MenuPriceRule[]> =>
+  invoke("get_menu_price_rules");
+
+export const addMenuPriceRule = (rule: MenuPriceRuleInput): Promise<MenuPriceRule> =>
+  invoke("add_menu_price_rule", { rule });
+
+export const deleteMenuPriceRule = (ruleId: number): Promise<void> =>
+  invoke("delete_menu_price_rule", { ruleId });
+
+// Inventory APIs
+/**
+ * Add an ingredient to track
+ * @param name - Ingredient name
+ * @param unit - Unit it's counted in, e.g. "kg"; defaults to "pcs"
+ * @param quantity - Opening stock
+ * @param lowStockLimit - Appears on the low-stock report at or below this
+ * @param unitCost - Cost per unit, used for recipe costing
+ */
+export const addInventoryItem = (
+  name: string,
+  unit?: string,
+  quantity?: number,
+  lowStockLimit?: number,
+  unitCost?: number
+): Promise<InventoryItem> =>
+  invoke("add_inventory_item", {
+    name,
+    unit: unit ?? null,
+    quantity: quantity ?? null,
+    lowStockLimit: lowStockLimit ?? null,
+    unitCost: unitCost ?? null
+  });
+
+/**
+ * Rename an ingredient or change its unit, low-stock limit or unit cost
+ */
+export const updateInventoryItem = (
+  itemId: number,
+  updates: { name?: string; unit?: string; low_stock_limit?: number; unit_cost?: number }
+): Promise<InventoryItem> =>
+  invoke("update_inventory_item", {
+    itemId,
+    name: updates.name ?? null,
+    unit: updates.unit ?? null,
+    lowStockLimit: updates.low_stock_limit ?? null,
+    unitCost: updates.unit_cost ?? null
+  });
+
+export const deleteInventoryItem = (itemId: number): Promise<string> =>
+  invoke("delete_inventory_item", { itemId });
+
+/**
+ * All ingredients with their stock on hand
+ */
+export const getStockLevels = (): Promise<InventoryItem[]> =>
+  invoke("get_stock_levels");
+
+/**
+ * Record a delivery (positive change), waste (negative) or a recount correction
+ */
+export const adjustStock = (
+  inventoryItemId: number,
+  change: number,
+  reason: StockAdjustmentReason,
+  note?: string
+): Promise<InventoryItem> =>
+  invoke("adjust_stock", { inventoryItemId, change, reason, note: note ?? null });
+
+/**
+ * Stock changes, newest first, for one ingredient or all of them
+ */
+export const getStockMovements = (inventoryItemId?: number, limit?: number): Promise<StockMovement[]> =>
+  invoke("get_stock_movements", { inventoryItemId: inventoryItemId ?? null, limit: limit ?? null });
+
+/**
+ * Replace the ingredients one portion of a menu item uses; orders deduct them from stock
+ */
+export const setMenuItemRecipe = (menuItemId: number, ingredients: RecipeIngredientInput[]): Promise<RecipeIngredient[]> =>
+  invoke("set_menu_item_recipe", { menuItemId, ingredients });
+
+export const getMenuItemRecipe = (menuItemId: number): Promise<RecipeIngredient[]> =>
+  invoke("get_menu_item_recipe", { menuItemId });
+
+/**
+ * Ingredients at or below their limit, most short first
+ */
+export const lowStockReport = (): Promise<LowStockIngredient[]> =>
+  invoke("low_stock_report");
+
+/**
+ * Revenue vs ingredient cost per menu item sold, most profitable first
+ * @param startDate - First day (YYYY-MM-DD)
+ * @param endDate - Last day, inclusive (YYYY-MM-DD)
+ */
+export const menuItemProfitability = (startDate: string, endDate: string): Promise<MenuItemProfitability[]> =>
+  invoke("menu_item_profitability", { startDate, endDate });
+
+// Purchasing APIs
+/**
+ * Add a supplier to buy stock from
+ */
+export const addSupplier = (
+  name: string,
+  contact?: { contact_name?: string; phone?: string; email?: string; notes?: string }
+): Promise<Supplier> =>
+  invoke("add_supplier", {
+    name,
+    contactName: contact?.contact_name ?? null,
+    phone: contact?.phone ?? null,
+    email: contact?.email ?? null,
+    notes: contact?.notes ?? null
+  });
+
+/**
+ * Suppliers by name, with their outstanding balances
+ */
+export const getSuppliers = (): Promise<Supplier[]> =>
+  invoke("get_suppliers");
+
+/**
+ * Order stock from a supplier
+ * @param expectedDate - Expected delivery date (YYYY-MM-DD)
+ */
+export const createPurchaseOrder = (
+  supplierId: number,
+  lines: PurchaseOrderLineInput[],
+  expectedDate?: string,
+  notes?: string
+): Promise<PurchaseOrder> =>
+  invoke("create_purchase_order", { supplierId, lines, expectedDate: expectedDate ?? null, notes: notes ?? null });
+
+/**
+ * Purchase orders, newest first, optionally for one supplier and/or in one status
+ */
+export const getPurchaseOrders = (supplierId?: number, status?: PurchaseOrderStatus): Promise<PurchaseOrder[]> =>
+  invoke("get_purchase_orders", { supplierId: supplierId ?? null, status: status ?? null });
+
+/**
+ * Book goods in and add them to stock. Without lines, everything outstanding is received.
+ */
+export const receivePurchaseOrder = (purchaseOrderId: number, lines?: ReceiveLineInput[]): Promise<PurchaseOrder> =>
+  invoke("receive_purchase_order", { purchaseOrderId, lines: lines ?? null });
+
+/**
+ * Cancel a purchase order nothing has been received against
+ */
+export const cancelPurchaseOrder = (purchaseOrderId: number): Promise<PurchaseOrder> =>
+  invoke("cancel_purchase_order", { purchaseOrderId });
+
+/**
+ * Pay a supplier; the payment is also recorded as an expense on paidOn
+ * @param method - "cash", "card" or "bank_transfer"
+ * @param paidOn - Payment date (YYYY-MM-DD)
+ */
+export const recordSupplierPayment = (
+  supplierId: number,
+  amount: number,
+  method: SupplierPayment["method"],
+  paidOn: string,
+  purchaseOrderId?: number,
+  note?: string
+): Promise<SupplierPayment> =>
+  invoke("record_supplier_payment", {
+    supplierId,
+    amount,
+    method,
+    paidOn,
+    purchaseOrderId: purchaseOrderId ?? null,
+    note: note ?? null
+  });
+
+export const getSupplierPayments = (supplierId: number): Promise<SupplierPayment[]> =>
+  invoke("get_supplier_payments", { supplierId });
+
+// Company Account APIs
+/**
+ * Add a company that guests can be billed to on credit
+ * @param creditLimit - Most the company may owe; omit for no limit
+ */
+export const addCompany = (
+  name: string,
+  contactName?: string,
+  phone?: string,
+  email?: string,
+  billingAddress?: string,
+  creditLimit?: number,
+  notes?: string
+): Promise<Company> =>
+  invoke("add_company", {
+    name,
+    contactName: contactName ?? null,
+    phone: phone ?? null,
+    email: email ?? null,
+    billingAddress: billingAddress ?? null,
+    creditLimit: creditLimit ?? null,
+    notes: notes ?? null
+  });
+
+export const getCompanies = (): Promise<Company[]> =>
+  invoke("get_companies");
+
+/**
+ * Check a guest out and charge the bill (less deposits) to a company account.
+ * Fails, leaving the guest checked in, if the company would go over its credit limit.
+ */
+export const postToCompanyAccount = (
+  guestId: number,
+  companyId: number,
+  discountFlat?: number,
+  discountPct?: number
+): Promise<CheckoutTotals> =>
+  invoke("post_to_company_account", {
+    guestId,
+    companyId,
+    discountFlat: discountFlat ?? null,
+    discountPct: discountPct ?? null
+  });
+
+/**
+ * Record money received from a company; can't exceed what it owes
+ * @param paidOn - Payment date (YYYY-MM-DD)
+ */
+export const recordCompanyPayment = (
+  companyId: number,
+  amount: number,
+  method: CompanyPayment["method"],
+  paidOn: string,
+  note?: string
+): Promise<CompanyPayment> =>
+  invoke("record_company_payment", { companyId, amount, method, paidOn, note: note ?? null });
+
+/**
+ * Charges and payments on a company account, with the balance brought forward
+ * @param startDate - YYYY-MM-DD
+ * @param endDate - YYYY-MM-DD (inclusive)
+ */
+export const getCompanyStatement = (companyId: number, startDate: string, endDate: string): Promise<CompanyStatement> =>
+  invoke("company_statement", { companyId, startDate, endDate });
+
+/**
+ * Unpaid orders, unsettled checkouts and company balances, bucketed by age
+ * (0-30, 31-60 and 61+ days) for chasing overdue money
+ */
+export const getReceivablesAgingReport = (): Promise<ReceivablesAgingReport> =>
+  invoke("receivables_aging_report");
+
 // Food Order APIs
 /**
  * Create a new food order for a guest
@@ -582,7 +2389,9 @@ export const addFoodOrder = async (order: NewFoodOrder): Promise<number> => {
     guestId: order.guest_id,
     customerType: order.guest_id ? 'active' : 'walkin',
     customerName: order.guest_id ? undefined : 'Walk-in Customer',
-    items: order.items
+    items: order.items,
+    notes: order.notes,
+    tableId: order.table_id ?? null
   };
   
   return invokeCompat<number>("add_sale", params, "add_food_order", params);
@@ -598,6 +2407,14 @@ export const addSale = (sale: NewSale): Promise<number> => addFoodOrder(sale);
 export const getFoodOrders = (): Promise<FoodOrderSummary[]> => 
   invokeCompat<FoodOrderSummary[]>("get_sales", undefined, "get_food_orders");
 
+/**
+ * Get food orders a page at a time
+ * Sorts: created_at, total_amount, guest_name.
+ * Filters: guest_id, table_id, paid ("1"/"0"), search (guest name), start_date, end_date.
+ */
+export const getFoodOrdersPage = (page?: PageRequest, tagIds?: number[]): Promise<PageResult<FoodOrderSummary>> =>
+  invoke("get_food_orders_page", { page, tagIds });
+
 // UI-facing generic wrapper (preferred)
 export const getSales = (): Promise<SaleSummary[]> => getFoodOrders();
 
@@ -641,7 +2458,7 @@ export const toggleFoodOrderPayment = (orderId: number): Promise<string> =>
 export const toggleSalePayment = (saleId: number): Promise<string> => toggleFoodOrderPayment(saleId);
 
 /**
- * Delete a food order and all its items
+ * Move a food order and its items to the trash (see restoreItem)
  * @param orderId - ID of the order to delete
  * @returns Success message
  */
@@ -662,6 +2479,83 @@ export const getOrderDetails = (orderId: number): Promise<FoodOrderDetails> =>
 // UI-facing generic wrapper (preferred)
 export const getSaleDetails = (saleId: number): Promise<SaleDetails> => getOrderDetails(saleId);
 
+/**
+ * Move an order forward through pending → preparing → served → closed
+ * @param orderId - ID of the order
+ * @param status - New status; stages may be skipped but not reversed
+ * @returns The order with its status timestamps
+ */
+export const setOrderStatus = (orderId: number, status: OrderStatus): Promise<KitchenOrder> =>
+  invoke("set_order_status", { orderId, status });
+
+/**
+ * Orders in one status, oldest first
+ * @param status - Status to list; omit for every order that isn't closed
+ * @returns Orders with their items
+ */
+export const getOrdersByStatus = (status?: OrderStatus): Promise<KitchenOrder[]> =>
+  invoke("get_orders_by_status", { status: status ?? null });
+
+/**
+ * Void or comp one order line: its total becomes zero and the order total is recalculated
+ * @param orderItemId - ID of the order line (OrderItemDetail.id)
+ * @param reason - Reason code; "other" needs a note
+ * @param note - Optional explanation for management
+ */
+export const voidOrderItem = (orderItemId: number, reason: VoidReason, note?: string): Promise<VoidedItem> =>
+  invoke("void_order_item", { orderItemId, reason, note: note ?? null });
+
+/**
+ * Voided lines, newest first, optionally between two dates (YYYY-MM-DD, inclusive)
+ */
+export const getVoidedItems = (startDate?: string, endDate?: string): Promise<VoidedItem[]> =>
+  invoke("get_voided_items", { startDate: startDate ?? null, endDate: endDate ?? null });
+
+// Restaurant Table APIs
+/**
+ * Add a restaurant table
+ * @param number - Table number or name shown to staff
+ * @param capacity - Seats, defaults to 4
+ */
+export const addTable = (number: string, capacity?: number): Promise<DiningTable> =>
+  invoke("add_table", { number, capacity: capacity ?? null });
+
+/**
+ * All tables in number order
+ */
+export const getTables = (): Promise<DiningTable[]> =>
+  invoke("get_tables");
+
+/**
+ * Tables with a sitting in progress, with what's still owed on each
+ */
+export const getOpenTables = (): Promise<DiningTable[]> =>
+  invoke("get_open_tables");
+
+/**
+ * Seat diners at a free table
+ */
+export const openTable = (tableId: number): Promise<DiningTable> =>
+  invoke("open_table", { tableId });
+
+/**
+ * Close the table's orders and free it; fails while any order is unpaid
+ */
+export const closeTable = (tableId: number): Promise<DiningTable> =>
+  invoke("close_table", { tableId });
+
+/**
+ * Move an order that isn't closed to another table
+ */
+export const moveOrderToTable = (orderId: number, tableId: number): Promise<KitchenOrder> =>
+  invoke("move_order_to_table", { orderId, tableId });
+
+/**
+ * Delete a free table
+ */
+export const deleteTable = (tableId: number): Promise<string> =>
+  invoke("delete_table", { tableId });
+
 // Expense Management APIs
 /**
  * Add a new business expense
@@ -687,6 +2581,14 @@ export const addExpense = (expense: NewExpense): Promise<number> =>
 export const getExpenses = (): Promise<ExpenseRecord[]> => 
   invoke("get_expenses");
 
+/**
+ * Get expenses a page at a time (no 100-row cap)
+ * Sorts: date, amount, category.
+ * Filters: category, is_drawing ("1"/"0"), search (description), start_date, end_date.
+ */
+export const getExpensesPage = (page?: PageRequest): Promise<PageResult<ExpenseRecord>> =>
+  invoke("get_expenses_page", { page });
+
 /**
  * Get expenses within a date range
  * @param startDate - Start date (YYYY-MM-DD)
@@ -706,13 +2608,220 @@ export const updateExpense = (expenseId: number, updates: Partial<NewExpense>):
   invoke("update_expense", { expenseId, ...updates });
 
 /**
- * Delete an expense record
+ * Move an expense record to the trash (see restoreItem)
  * @param expenseId - ID of the expense to delete
  * @returns Success status
  */
 export const deleteExpense = (expenseId: number): Promise<boolean> => 
   invoke("delete_expense", { expenseId });
 
+/**
+ * Deleted orders and expenses, most recently deleted first
+ */
+export const getTrash = (): Promise<TrashItem[]> =>
+  invoke("get_trash");
+
+/**
+ * Bring a deleted order or expense back
+ */
+export const restoreItem = (kind: TrashItem["kind"], id: number, sessionToken?: string): Promise<string> =>
+  invoke("restore_item", { kind, id, sessionToken });
+
+/**
+ * Permanently remove what has been in the trash longer than the given number
+ * of days, or everything when no age is given; returns how many were removed
+ */
+export const purgeTrash = (olderThanDays?: number, sessionToken?: string): Promise<number> =>
+  invoke("purge_trash", { olderThanDays, sessionToken });
+
+/**
+ * The managed expense categories; expenses can only be booked under one of these
+ */
+export const getExpenseCategories = (): Promise<ExpenseCategory[]> =>
+  invoke("get_expense_categories");
+
+export const addExpenseCategory = (name: string): Promise<ExpenseCategory> =>
+  invoke("add_expense_category", { name });
+
+/**
+ * Rename a category and every expense booked under it
+ */
+export const renameExpenseCategory = (categoryId: number, name: string, sessionToken?: string): Promise<ExpenseCategory> =>
+  invoke("rename_expense_category", { categoryId, name, sessionToken });
+
+/**
+ * Move a category's expenses and budget into another and remove it, e.g. to fix a typo
+ * @returns The target category
+ */
+export const mergeExpenseCategories = (sourceId: number, targetId: number, sessionToken?: string): Promise<ExpenseCategory> =>
+  invoke("merge_expense_categories", { sourceId, targetId, sessionToken });
+
+/**
+ * Delete a category no expense uses; fails with EXPENSE_CATEGORY_IN_USE otherwise
+ */
+export const deleteExpenseCategory = (categoryId: number): Promise<void> =>
+  invoke("delete_expense_category", { categoryId });
+
+/**
+ * Import historical expenses from a CSV, spreadsheet or bank statement export.
+ * Rows matching a recorded expense's date, amount and description are skipped.
+ */
+export const importExpensesCsv = (path: string, mapping: ExpenseImportMapping, sessionToken?: string): Promise<ExpenseImportReport> =>
+  invoke("import_expenses_csv", { path, mapping, sessionToken: sessionToken ?? null });
+
+/**
+ * Full data import from other software: rooms, guests, orders and expenses.
+ * Records imported before are skipped; with dryRun nothing is saved.
+ */
+export const getImportAdapters = (): Promise<ImportAdapterInfo[]> =>
+  invoke("get_import_adapters");
+
+export const importLegacyData = (adapter: string, path: string, dryRun: boolean, sessionToken?: string): Promise<LegacyImportReport> =>
+  invoke("import_legacy_data", { adapter, path, dryRun, sessionToken: sessionToken ?? null });
+
+/**
+ * Monthly budgets per expense category
+ */
+export const getExpenseBudgets = (): Promise<ExpenseBudget[]> =>
+  invoke("get_expense_budgets");
+
+/**
+ * Set a category's monthly budget; returns all budgets
+ */
+export const setExpenseBudget = (category: string, monthlyAmount: number): Promise<ExpenseBudget[]> =>
+  invoke("set_expense_budget", { category, monthlyAmount });
+
+/**
+ * Remove a category's budget; returns the remaining budgets
+ */
+export const deleteExpenseBudget = (category: string): Promise<ExpenseBudget[]> =>
+  invoke("delete_expense_budget", { category });
+
+/**
+ * Business expenses for a month against each category's budget
+ * @param month - 1-12
+ */
+export const budgetVsActual = (year: number, month: number): Promise<BudgetVsActualReport> =>
+  invoke("budget_vs_actual", { year, month });
+
+/**
+ * Ledger: payments, checkouts and expenses are posted automatically each time
+ * the ledger is read
+ */
+export const getLedgerAccounts = (): Promise<LedgerAccount[]> =>
+  invoke("get_ledger_accounts");
+
+export const addLedgerAccount = (code: string, name: string, accountType: LedgerAccount['account_type']): Promise<LedgerAccount> =>
+  invoke("add_ledger_account", { code, name, accountType });
+
+export const getJournalEntries = (startDate: string, endDate: string): Promise<JournalEntry[]> =>
+  invoke("get_journal_entries", { startDate, endDate });
+
+/**
+ * Manual entry, e.g. opening balances; fails with JOURNAL_UNBALANCED unless debits equal credits
+ */
+export const addJournalEntry = (entryDate: string, memo: string, lines: NewJournalLine[], sessionToken?: string): Promise<JournalEntry> =>
+  invoke("add_journal_entry", { entryDate, memo, lines, sessionToken });
+
+/**
+ * Reverse a manual entry; returns the reversing entry
+ */
+export const reverseJournalEntry = (entryId: number, sessionToken?: string): Promise<JournalEntry> =>
+  invoke("reverse_journal_entry", { entryId, sessionToken });
+
+/**
+ * Account balances as of a date (default today)
+ */
+export const getTrialBalance = (asOf?: string): Promise<TrialBalance> =>
+  invoke("trial_balance", { asOf });
+
+export const getProfitAndLoss = (startDate: string, endDate: string): Promise<ProfitAndLoss> =>
+  invoke("profit_and_loss", { startDate, endDate });
+
+/**
+ * Save the journal as an import-ready CSV for an accounting package
+ * @returns Path of the saved file
+ */
+export const exportAccountingCsv = (format: 'quickbooks' | 'xero' | 'tally', startDate: string, endDate: string): Promise<string> =>
+  invoke("export_accounting_csv", { format, startDate, endDate });
+
+/**
+ * VAT/GST return: tax charged on checkout bills per rate, less tax paid on expenses
+ */
+export const getTaxReport = (startDate: string, endDate: string): Promise<TaxReport> =>
+  invoke("tax_report", { startDate, endDate });
+
+/**
+ * Save the tax return as CSV or PDF
+ * @returns Path of the saved file
+ */
+export const exportTaxReport = (startDate: string, endDate: string, format: 'csv' | 'pdf'): Promise<string> =>
+  invoke("export_tax_report", { startDate, endDate, format });
+
+export const createVoucher = (voucher: NewVoucher, sessionToken?: string): Promise<Voucher> =>
+  invoke("create_voucher", { voucher, sessionToken });
+
+export const getVouchers = (includeInactive?: boolean): Promise<Voucher[]> =>
+  invoke("get_vouchers", { includeInactive });
+
+export const deactivateVoucher = (voucherId: number, sessionToken?: string): Promise<Voucher> =>
+  invoke("deactivate_voucher", { voucherId, sessionToken });
+
+/**
+ * Check a voucher code; with the bill amount (before tax) also returns what it takes off.
+ * Fails with VOUCHER_NOT_FOUND, VOUCHER_INACTIVE, VOUCHER_EXPIRED or VOUCHER_USED_UP
+ */
+export const validateVoucher = (code: string, amount?: number): Promise<VoucherCheck> =>
+  invoke("validate_voucher", { code, amount });
+
+/**
+ * Take a voucher off what's left to pay on an order
+ */
+export const redeemVoucherOnOrder = (orderId: number, code: string, sessionToken?: string): Promise<OrderPaymentSummary> =>
+  invoke("redeem_voucher_on_order", { orderId, code, sessionToken });
+
+export const getVoucherRedemptions = (voucherId?: number): Promise<VoucherRedemption[]> =>
+  invoke("get_voucher_redemptions", { voucherId });
+
+export const setPaymentGatewaySettings = (settings: PaymentGatewaySettingsInput): Promise<PaymentGatewaySettings> =>
+  invoke("set_payment_gateway_settings", { settings });
+
+export const getPaymentGatewaySettings = (): Promise<PaymentGatewaySettings> =>
+  invoke("get_payment_gateway_settings");
+
+/**
+ * Start an online card payment for what's left to pay on an order or a guest's folio
+ */
+export const createOnlinePayment = (sourceType: 'order' | 'folio', sourceId: number): Promise<OnlinePayment> =>
+  invoke("create_online_payment", { sourceType, sourceId });
+
+/**
+ * Check an online payment with the gateway; records it once it has succeeded. Safe to poll.
+ */
+export const checkOnlinePayment = (id: number): Promise<OnlinePayment> =>
+  invoke("check_online_payment", { id });
+
+export const cancelOnlinePayment = (id: number): Promise<OnlinePayment> =>
+  invoke("cancel_online_payment", { id });
+
+export const getOnlinePayments = (sourceType?: 'order' | 'folio', sourceId?: number): Promise<OnlinePayment[]> =>
+  invoke("get_online_payments", { sourceType, sourceId });
+
+export const setFiscalSettings = (settings: FiscalSettingsInput): Promise<FiscalSettings> =>
+  invoke("set_fiscal_settings", { settings });
+
+export const getFiscalSettings = (): Promise<FiscalSettings> =>
+  invoke("get_fiscal_settings");
+
+/**
+ * Submit, or retry, a paid order or a checkout bill (by guest ID) to the fiscal provider
+ */
+export const fiscalizeInvoice = (sourceType: 'order' | 'checkout', sourceId: number): Promise<FiscalRecord> =>
+  invoke("fiscalize_invoice", { sourceType, sourceId });
+
+export const getFiscalInvoices = (status?: 'pending' | 'submitted' | 'failed'): Promise<FiscalRecord[]> =>
+  invoke("get_fiscal_invoices", { status });
+
 // Dashboard & Analytics APIs
 /**
  * Get dashboard statistics and metrics
@@ -727,6 +2836,40 @@ export const deleteExpense = (expenseId: number): Promise<boolean> =>
 export const getDashboardStats = (): Promise<DashboardStats> => 
   invoke("dashboard_stats");
 
+/**
+ * Room, food and total income per bucket, for trend charts
+ * @param granularity - "daily", "weekly" or "monthly"
+ * @param rangeStart - First day (YYYY-MM-DD)
+ * @param rangeEnd - Last day (YYYY-MM-DD)
+ */
+export const getRevenueTimeseries = (granularity: TimeseriesGranularity, rangeStart: string, rangeEnd: string): Promise<RevenueTimeseries> =>
+  invoke("revenue_timeseries", { granularity, rangeStart, rangeEnd });
+
+/**
+ * Business expenses and owner drawings per bucket, for trend charts
+ */
+export const getExpenseTimeseries = (granularity: TimeseriesGranularity, rangeStart: string, rangeEnd: string): Promise<ExpenseTimeseries> =>
+  invoke("expense_timeseries", { granularity, rangeStart, rangeEnd });
+
+/**
+ * End-of-day summary of a date (YYYY-MM-DD) for the owner's sign-off
+ */
+export const getNightAuditReport = (date: string): Promise<NightAuditReport> =>
+  invoke("night_audit_report", { date });
+
+/**
+ * Save the night audit as "csv" or "pdf" in the exports folder; returns the file path
+ */
+export const exportNightAudit = (date: string, format: "csv" | "pdf"): Promise<string> =>
+  invoke("export_night_audit", { date, format });
+
+/**
+ * Expected vs counted cash and every payment taken during a shift; running
+ * totals for the open shift, the stored report once it is closed
+ */
+export const getShiftReconciliation = (shiftId: number): Promise<ShiftReconciliation> =>
+  invoke("get_shift_reconciliation", { shiftId });
+
 // Authentication APIs
 /**
  * Admin login
@@ -815,6 +2958,7 @@ export const exportHistoryCsvWithDialog = (tab: string, filters: ExportFilters =
 /**
  * Generate HTML receipt for a food order
  * @param orderId - ID of the food order
+ * @param locale - Receipt language; defaults to the receipt language setting
  * @returns HTML string ready for printing
  * @example
  * ```ts
@@ -824,12 +2968,13 @@ export const exportHistoryCsvWithDialog = (tab: string, filters: ExportFilters =
  * newWindow?.print();
  * ```
  */
-export const buildOrderReceiptHtml = (orderId: number): Promise<string> => 
-  invoke("build_order_receipt_html", { orderId });
+export const buildOrderReceiptHtml = (orderId: number, locale?: string): Promise<string> => 
+  invoke("build_order_receipt_html", { orderId, locale: locale ?? null });
 
 /**
  * Generate HTML invoice for a guest's final bill
  * @param guestId - ID of the guest
+ * @param locale - Invoice language; defaults to the receipt language setting
  * @returns HTML string ready for printing
  * @example
  * ```ts
@@ -839,8 +2984,8 @@ export const buildOrderReceiptHtml = (orderId: number): Promise<string> =>
  * newWindow?.print();
  * ```
  */
-export const buildFinalInvoiceHtml = (guestId: number): Promise<string> => 
-  invoke("build_final_invoice_html", { guestId });
+export const buildFinalInvoiceHtml = (guestId: number, locale?: string): Promise<string> => 
+  invoke("build_final_invoice_html", { guestId, locale: locale ?? null });
 
 /**
  * Generate HTML for final invoice with discount information
@@ -848,6 +2993,7 @@ export const buildFinalInvoiceHtml = (guestId: number): Promise<string> =>
  * @param discountType - Type of discount ('flat' or 'percentage')
  * @param discountAmount - Amount or percentage of discount
  * @param discountDescription - Description/reason for discount
+ * @param locale - Invoice language; defaults to the receipt language setting
  * @returns HTML string ready for printing with discount included
  * @example
  * ```ts
@@ -861,13 +3007,15 @@ export const buildFinalInvoiceHtmlWithDiscount = (
   guestId: number, 
   discountType: string, 
   discountAmount: number, 
-  discountDescription: string
+  discountDescription: string,
+  locale?: string
 ): Promise<string> => 
   invoke("build_final_invoice_html_with_discount", { 
     guestId, 
     discountType, 
     discountAmount, 
-    discountDescription 
+    discountDescription,
+    locale: locale ?? null
   });
 
 /**
@@ -877,6 +3025,7 @@ export const buildFinalInvoiceHtmlWithDiscount = (
  * @param discountType - Type of discount ('flat' or 'percentage')
  * @param discountAmount - Amount or percentage of discount
  * @param discountDescription - Description/reason for discount
+ * @param voucherCode - Voucher or promo code to take off the bill before tax
  * @returns Final bill amount after discount
  * @example
  * ```typescript
@@ -889,18 +3038,54 @@ export const checkoutGuestWithDiscount = (
   checkOutDate: string,
   discountType: 'flat' | 'percentage' = 'flat',
   discountAmount: number = 0,
-  discountDescription: string = ''
+  discountDescription: string = '',
+  voucherCode?: string
 ): Promise<number> => {
   void discountType;
   void discountDescription;
   return invokeCompat<number>(
     "checkout_customer_with_discount",
-    { customerId: guestId, checkOutDate, discountAmount },
+    { customerId: guestId, checkOutDate, discountAmount, voucherCode },
     "checkout_guest_with_discount",
-    { guestId, checkOutDate, discountAmount }
+    { guestId, checkOutDate, discountAmount, voucherCode }
   );
 };
 
+/**
+ * Undo a mistaken checkout within the reopen window: the stay is active again and
+ * the checkout's payment, taxes and voucher are reversed
+ */
+export const reopenCheckout = (guestId: number, sessionToken?: string): Promise<ReopenedStay> =>
+  invoke("reopen_checkout", { guestId, sessionToken: sessionToken ?? null });
+
+/**
+ * Hours after a checkout during which it can be reopened; 0 turns reopening off
+ */
+export const getCheckoutReopenWindow = (): Promise<number> =>
+  invoke("get_checkout_reopen_window");
+
+export const setCheckoutReopenWindow = (hours: number): Promise<number> =>
+  invoke("set_checkout_reopen_window", { hours });
+
+// Room types
+export const getRoomTypes = (): Promise<RoomType[]> =>
+  invoke("get_room_types");
+
+export const addRoomType = (roomType: RoomTypeInput): Promise<RoomType> =>
+  invoke("add_room_type", { roomType });
+
+/**
+ * Update a room type; a new name carries over to its rooms and rate plans
+ */
+export const updateRoomType = (roomTypeId: number, roomType: RoomTypeInput, sessionToken?: string): Promise<RoomType> =>
+  invoke("update_room_type", { roomTypeId, roomType, sessionToken: sessionToken ?? null });
+
+/**
+ * Delete a room type that no room uses
+ */
+export const deleteRoomType = (roomTypeId: number): Promise<void> =>
+  invoke("delete_room_type", { roomTypeId });
+
 // Database Management APIs
 /**
  * Create a database backup
@@ -909,6 +3094,14 @@ export const checkoutGuestWithDiscount = (
 export const createDatabaseBackup = (): Promise<string> => 
   invoke("create_database_backup");
 
+/**
+ * Write the whole database as a plain-text SQL dump (like `sqlite3 .dump`).
+ * The dump is not encrypted, even when the database is.
+ * @returns File path of the dump
+ */
+export const exportSqlDump = (path: string, sessionToken?: string): Promise<string> =>
+  invoke("export_sql_dump", { path, sessionToken: sessionToken ?? null });
+
 /**
  * Reset database with fresh seed data
  * @returns Success status
@@ -930,6 +3123,136 @@ export const getDatabasePath = (): Promise<string> =>
 export const getDatabaseStats = (): Promise<DatabaseStats> => 
   invoke("get_database_stats");
 
+/**
+ * Back up automatically every day or week into a folder, keeping the newest
+ * `retention` scheduled backups
+ * @param frequency - "off", "daily" or "weekly"
+ * @param directory - Existing folder for the backups; required unless off
+ * @param retention - Scheduled backups to keep (default 7)
+ */
+export const setBackupSchedule = (
+  frequency: BackupSchedule["frequency"],
+  directory?: string,
+  retention?: number
+): Promise<BackupSchedule> =>
+  invoke("set_backup_schedule", { frequency, directory: directory ?? null, retention: retention ?? null });
+
+export const getBackupSchedule = (): Promise<BackupSchedule> =>
+  invoke("get_backup_schedule");
+
+/**
+ * Keep only the newest `keep` manual backups in each backup folder; older
+ * ones are deleted after every backup
+ * @param keep - Backups to keep, or null to keep them all
+ */
+export const setBackupRetention = (keep: number | null): Promise<number | null> =>
+  invoke("set_backup_retention", { keep });
+
+export const getBackupRetention = (): Promise<number | null> =>
+  invoke("get_backup_retention");
+
+/**
+ * The backups in a folder, newest first
+ * @param backupPath - Folder the backups were written to
+ */
+export const listBackups = (backupPath: string): Promise<BackupFileInfo[]> =>
+  invoke("list_backups", { backupPath });
+
+/**
+ * Encrypt backups (AES-256-GCM) with a passphrase from now on
+ * @param passphrase - At least 8 characters, or null to stop encrypting
+ * @returns Whether backups are now encrypted
+ */
+export const setBackupEncryption = (passphrase: string | null): Promise<boolean> =>
+  invoke("set_backup_encryption", { passphrase });
+
+export const getBackupEncryption = (): Promise<boolean> =>
+  invoke("get_backup_encryption");
+
+/**
+ * Replace the database with a backup. Encrypted backups use the saved
+ * passphrase unless one is given.
+ */
+export const restoreDatabaseFromBackup = (backupFilePath: string, passphrase?: string): Promise<string> =>
+  invoke("restore_database_from_backup", { backupFilePath, passphrase: passphrase ?? null });
+
+/**
+ * Tables that can be restored on their own with restoreTablesFromBackup
+ */
+export const getRestorableTables = (): Promise<string[]> =>
+  invoke("get_restorable_tables");
+
+/**
+ * Restore only some tables (e.g. menu_items and resources) from a backup,
+ * merged into the live database by natural key; guests, sales and the rest
+ * are left untouched
+ */
+export const restoreTablesFromBackup = (
+  backupFilePath: string,
+  tables: string[],
+  passphrase?: string
+): Promise<TableRestoreResult[]> =>
+  invoke("restore_tables_from_backup", { backupFilePath, tables, passphrase: passphrase ?? null });
+
+// Cloud Backup APIs
+
+/**
+ * Choose where backups are uploaded after they're taken. Secrets left out
+ * keep the saved ones.
+ */
+export const setCloudBackupProvider = (settings: CloudBackupSettingsInput): Promise<CloudBackupSettings> =>
+  invoke("set_cloud_backup_provider", { settings });
+
+export const getCloudBackupProvider = (): Promise<CloudBackupSettings> =>
+  invoke("get_cloud_backup_provider");
+
+/**
+ * How the last upload went and when backups last reached the cloud
+ */
+export const getCloudBackupStatus = (): Promise<CloudBackupStatus> =>
+  invoke("get_cloud_backup_status");
+
+// Database Encryption APIs
+
+/**
+ * Encrypt the database file. "keyring" unlocks automatically from the OS
+ * keyring; "password" asks for the admin's password at every start, and the
+ * data can't be recovered if that password is forgotten
+ */
+export const enableDatabaseEncryption = (
+  mode: "keyring" | "password",
+  sessionToken: string,
+  username?: string,
+  password?: string
+): Promise<DatabaseEncryptionStatus> =>
+  invoke("enable_database_encryption", { mode, username: username ?? null, password: password ?? null, sessionToken });
+
+/**
+ * Decrypt the database file; the signed-in admin must enter their password again
+ */
+export const disableDatabaseEncryption = (
+  password: string,
+  sessionToken: string
+): Promise<DatabaseEncryptionStatus> =>
+  invoke("disable_database_encryption", { password, sessionToken });
+
+/**
+ * Check at startup: when locked, ask for the admin password before anything else
+ */
+export const getDatabaseEncryption = (): Promise<DatabaseEncryptionStatus> =>
+  invoke("get_database_encryption");
+
+export const unlockDatabase = (password: string): Promise<DatabaseEncryptionStatus> =>
+  invoke("unlock_database", { password });
+
+/**
+ * Search guests, named orders, menu items and expenses at once
+ * @param term - Words to match; each may be the start of a word
+ * @param limit - Most hits to return (default 20, at most 100)
+ */
+export const globalSearch = (term: string, limit?: number): Promise<SearchHit[]> =>
+  invoke("global_search", { term, limit });
+
 // ============================================================================
 // MOCK DATA FOR DEVELOPMENT
 // ============================================================================
@@ -982,6 +3305,12 @@ export const ErrorCodes = {
   ROOM_NOT_FOUND: "ROOM_NOT_FOUND",
   ROOM_OCCUPIED: "ROOM_OCCUPIED",
   ROOM_NUMBER_EXISTS: "ROOM_NUMBER_EXISTS",
+  ROOM_RESERVED: "ROOM_RESERVED",
+  ROOM_OUT_OF_SERVICE: "ROOM_OUT_OF_SERVICE",
+  ROOM_OVER_CAPACITY: "ROOM_OVER_CAPACITY",
+  ROOM_TYPE_NOT_FOUND: "ROOM_TYPE_NOT_FOUND",
+  ROOM_TYPE_EXISTS: "ROOM_TYPE_EXISTS",
+  ROOM_TYPE_IN_USE: "ROOM_TYPE_IN_USE",
   
   // Guest errors
   GUEST_NOT_FOUND: "GUEST_NOT_FOUND",
@@ -991,15 +3320,69 @@ export const ErrorCodes = {
   // Menu errors
   MENU_ITEM_NOT_FOUND: "MENU_ITEM_NOT_FOUND",
   MENU_ITEM_UNAVAILABLE: "MENU_ITEM_UNAVAILABLE",
+  MENU_CATEGORY_NOT_FOUND: "MENU_CATEGORY_NOT_FOUND",
+  MENU_CATEGORY_EXISTS: "MENU_CATEGORY_EXISTS",
+  MENU_CATEGORY_IN_USE: "MENU_CATEGORY_IN_USE",
+  COMBO_NOT_FOUND: "COMBO_NOT_FOUND",
+  COMBO_EXISTS: "COMBO_EXISTS",
+  MENU_PRICE_RULE_NOT_FOUND: "MENU_PRICE_RULE_NOT_FOUND",
   
   // Order errors
   ORDER_NOT_FOUND: "ORDER_NOT_FOUND",
   ORDER_ALREADY_PAID: "ORDER_ALREADY_PAID",
+  ORDER_STATUS_BACKWARDS: "ORDER_STATUS_BACKWARDS",
+  ORDER_ITEM_NOT_FOUND: "ORDER_ITEM_NOT_FOUND",
+  ORDER_ITEM_ALREADY_VOIDED: "ORDER_ITEM_ALREADY_VOIDED",
+  INVALID_ORDER_STATUS: "INVALID_ORDER_STATUS",
+
+  // Restaurant table errors
+  TABLE_NOT_FOUND: "TABLE_NOT_FOUND",
+  TABLE_NUMBER_EXISTS: "TABLE_NUMBER_EXISTS",
+  TABLE_OPEN: "TABLE_OPEN",
+  TABLE_HAS_UNPAID_ORDERS: "TABLE_HAS_UNPAID_ORDERS",
+
+  // Cash drawer shift errors
+  SHIFT_ALREADY_OPEN: "SHIFT_ALREADY_OPEN",
+
+  // Expense category errors
+  EXPENSE_CATEGORY_NOT_FOUND: "EXPENSE_CATEGORY_NOT_FOUND",
+  EXPENSE_CATEGORY_EXISTS: "EXPENSE_CATEGORY_EXISTS",
+  EXPENSE_CATEGORY_IN_USE: "EXPENSE_CATEGORY_IN_USE",
+
+  // Ledger errors
+  LEDGER_ACCOUNT_NOT_FOUND: "LEDGER_ACCOUNT_NOT_FOUND",
+  LEDGER_ACCOUNT_CODE_EXISTS: "LEDGER_ACCOUNT_CODE_EXISTS",
+  JOURNAL_UNBALANCED: "JOURNAL_UNBALANCED",
+
+  // Voucher errors
+  VOUCHER_NOT_FOUND: "VOUCHER_NOT_FOUND",
+  VOUCHER_CODE_EXISTS: "VOUCHER_CODE_EXISTS",
+  VOUCHER_INACTIVE: "VOUCHER_INACTIVE",
+  VOUCHER_EXPIRED: "VOUCHER_EXPIRED",
+  VOUCHER_USED_UP: "VOUCHER_USED_UP",
+
+  // Guest profile errors
+  GUEST_PROFILE_NOT_FOUND: "GUEST_PROFILE_NOT_FOUND",
+  GUEST_ANONYMIZED: "GUEST_ANONYMIZED",
+  
+  // Business rules
+  OCCUPANCY_CAP_EXCEEDED: "OCCUPANCY_CAP_EXCEEDED",
+  OCCUPANCY_CAP_WARNING: "OCCUPANCY_CAP_WARNING",
+  CHECKOUT_HOLD_ACTIVE: "CHECKOUT_HOLD_ACTIVE",
+  AFTER_HOURS_RESTRICTED: "AFTER_HOURS_RESTRICTED",
   
   // Validation errors
   INVALID_DATE_FORMAT: "INVALID_DATE_FORMAT",
   NEGATIVE_AMOUNT: "NEGATIVE_AMOUNT",
   EMPTY_FIELD: "EMPTY_FIELD",
+  INVALID_VALUE: "INVALID_VALUE",
+  UNUSUAL_DATE: "UNUSUAL_DATE",
+  DATE_BEFORE_EPOCH: "DATE_BEFORE_EPOCH",
+  INVALID_PAGE_REQUEST: "INVALID_PAGE_REQUEST",
+
+  // Backup errors
+  WEAK_PASSPHRASE: "WEAK_PASSPHRASE",
+  BACKUP_PASSPHRASE_REQUIRED: "BACKUP_PASSPHRASE_REQUIRED",
   
   // Auth errors
   INVALID_CREDENTIALS: "INVALID_CREDENTIALS",
@@ -1008,7 +3391,10 @@ export const ErrorCodes = {
   
   // Database errors
   DATABASE_ERROR: "DATABASE_ERROR",
-  CONSTRAINT_VIOLATION: "CONSTRAINT_VIOLATION"
+  CONSTRAINT_VIOLATION: "CONSTRAINT_VIOLATION",
+  
+  // Anything without a specific code
+  COMMAND_FAILED: "COMMAND_FAILED"
 } as const;
 
 /**
@@ -1017,6 +3403,11 @@ export const ErrorCodes = {
  * @returns User-friendly error message
  */
 export const handleApiError = (error: unknown): string => {
+  if (error instanceof ApiError) {
+    // Codes without a friendlier text fall back to the backend's message
+    const friendly = handleApiError(error.code);
+    return friendly === error.code ? error.message : friendly;
+  }
   if (typeof error === 'string') {
     switch (error) {
       case ErrorCodes.ROOM_NOT_FOUND:
@@ -1046,7 +3437,8 @@ export const handleApiError = (error: unknown): string => {
 // ============================================================================
 
 /**
- * Set the tax rate for invoices
+ * Set the tax rate for invoices. Changes the one tax on the whole bill;
+ * fails once several taxes are set up (use updateTax instead)
  * @param rate - Tax rate percentage (0-100)
  * @returns Success message
  */
@@ -1055,7 +3447,7 @@ export const setTaxRate = (rate: number): Promise<string> =>
 
 /**
  * Get the current tax rate
- * @returns Tax rate percentage
+ * @returns Combined percentage of the taxes on the whole bill
  */
 export const getTaxRate = (): Promise<number> => 
   invoke("get_tax_rate");
@@ -1074,3 +3466,220 @@ export const setTaxEnabled = (enabled: boolean): Promise<string> =>
  */
 export const getTaxEnabled = (): Promise<boolean> => 
   invoke("get_tax_enabled");
+
+/**
+ * Add a named tax, e.g. GST on rooms and sales tax on food
+ * @param appliesTo - "rooms" (stay, extras and adjustments), "food" or "all"
+ */
+export const addTax = (name: string, percent: number, appliesTo: Tax["applies_to"]): Promise<Tax> =>
+  invoke("add_tax", { name, percent, appliesTo });
+
+export const getTaxes = (): Promise<Tax[]> =>
+  invoke("get_taxes");
+
+/**
+ * Change a tax; omitted fields are left as they are. Inactive taxes aren't charged
+ */
+export const updateTax = (
+  taxId: number,
+  changes: { name?: string; percent?: number; appliesTo?: Tax["applies_to"]; isActive?: boolean }
+): Promise<Tax> =>
+  invoke("update_tax", {
+    taxId,
+    name: changes.name ?? null,
+    percent: changes.percent ?? null,
+    appliesTo: changes.appliesTo ?? null,
+    isActive: changes.isActive ?? null
+  });
+
+export const deleteTax = (taxId: number): Promise<string> =>
+  invoke("delete_tax", { taxId });
+
+/**
+ * Set whether menu prices and room rates already include tax. When they do,
+ * tax is itemized out of the bill instead of added on top
+ */
+export const setPricesIncludeTax = (enabled: boolean): Promise<boolean> =>
+  invoke("set_prices_include_tax", { enabled });
+
+export const getPricesIncludeTax = (): Promise<boolean> =>
+  invoke("get_prices_include_tax");
+
+// Business Info APIs
+
+/**
+ * Set the business name and contact details printed on receipts and invoices.
+ * Blank address, phone or email lines are left off
+ */
+export const setBusinessInfo = (
+  name: string,
+  details: { address?: string; phone?: string; email?: string }
+): Promise<BusinessInfo> =>
+  invoke("set_business_info", {
+    name,
+    address: details.address ?? null,
+    phone: details.phone ?? null,
+    email: details.email ?? null
+  });
+
+export const getBusinessInfo = (): Promise<BusinessInfo> =>
+  invoke("get_business_info");
+
+/**
+ * Set the logo printed on receipts and invoices from a file path or the image
+ * bytes (PNG, JPEG, WebP or SVG, up to 5MB). The image is copied into app data
+ * @returns The stored logo path
+ */
+export const setReceiptLogo = (logo: string | Uint8Array): Promise<string> =>
+  invoke("set_receipt_logo", { logo: typeof logo === "string" ? logo : Array.from(logo) });
+
+/**
+ * The logo templates print, as a data URL; the bundled logo until one is set
+ */
+export const getReceiptLogo = (): Promise<string | null> =>
+  invoke("get_receipt_logo");
+
+// Print Template APIs
+
+export const listPrintTemplates = (): Promise<PrintTemplateInfo[]> =>
+  invoke("list_print_templates");
+
+export const getPrintTemplate = (name: PrintTemplateName): Promise<PrintTemplateSource> =>
+  invoke("get_print_template", { name });
+
+/**
+ * Render a template with sample data; pass `source` to preview unsaved edits
+ * and `locale` to preview another receipt language
+ */
+export const previewPrintTemplate = (name: PrintTemplateName, source?: string, locale?: string): Promise<string> =>
+  invoke("preview_print_template", { name, source: source ?? null, locale: locale ?? null });
+
+/**
+ * Save a customised Handlebars layout, printed from then on. Rejected if it doesn't render
+ */
+export const savePrintTemplate = (name: PrintTemplateName, source: string): Promise<PrintTemplateInfo> =>
+  invoke("save_print_template", { name, source });
+
+/**
+ * Drop the customised copy and print the built-in layout again
+ */
+export const resetPrintTemplate = (name: PrintTemplateName): Promise<PrintTemplateInfo> =>
+  invoke("reset_print_template", { name });
+
+// Receipt Language APIs
+
+export const getReceiptLanguages = (): Promise<ReceiptLanguageInfo[]> =>
+  invoke("get_receipt_languages");
+
+/**
+ * Print receipts and invoices in this language (e.g. "ur", "ar") whatever the
+ * app locale; null goes back to following the app locale
+ */
+export const setReceiptLocale = (locale: string | null): Promise<string | null> =>
+  invoke("set_receipt_locale", { locale });
+
+export const getReceiptLocale = (): Promise<string | null> =>
+  invoke("get_receipt_locale");
+
+// Receipt QR Code APIs
+
+/**
+ * Choose what the QR code on receipts and invoices holds: nothing, a digital
+ * copy of the totals, or a payment link such as
+ * "https://pay.example.com/?ref={reference}&amount={amount}"
+ */
+export const setReceiptQr = (mode: ReceiptQrSetting["mode"], paymentLink?: string): Promise<ReceiptQrSetting> =>
+  invoke("set_receipt_qr", { mode, paymentLink: paymentLink ?? null });
+
+export const getReceiptQr = (): Promise<ReceiptQrSetting> =>
+  invoke("get_receipt_qr");
+
+// Notification APIs
+
+/**
+ * Set up Twilio (SMS and WhatsApp) or the WhatsApp Business API. Leave a
+ * token out to keep the saved one
+ */
+export const setNotificationProvider = (settings: {
+  provider: NotificationProviderSettings["provider"];
+  twilioAccountSid?: string;
+  twilioAuthToken?: string;
+  twilioFrom?: string;
+  twilioWhatsappFrom?: string;
+  whatsappAccessToken?: string;
+  whatsappPhoneNumberId?: string;
+}): Promise<NotificationProviderSettings> =>
+  invoke("set_notification_provider", {
+    provider: settings.provider,
+    twilioAccountSid: settings.twilioAccountSid ?? null,
+    twilioAuthToken: settings.twilioAuthToken ?? null,
+    twilioFrom: settings.twilioFrom ?? null,
+    twilioWhatsappFrom: settings.twilioWhatsappFrom ?? null,
+    whatsappAccessToken: settings.whatsappAccessToken ?? null,
+    whatsappPhoneNumberId: settings.whatsappPhoneNumberId ?? null,
+  });
+
+export const getNotificationProvider = (): Promise<NotificationProviderSettings> =>
+  invoke("get_notification_provider");
+
+/**
+ * Switch an event's message on or off, choose SMS or WhatsApp and optionally
+ * reword it; a blank template restores the default wording
+ */
+export const setNotificationEvent = (
+  eventType: NotificationEventType,
+  enabled: boolean,
+  channel: NotificationChannel,
+  template?: string
+): Promise<NotificationEventSetting> =>
+  invoke("set_notification_event", { eventType, enabled, channel, template: template ?? null });
+
+export const getNotificationEvents = (): Promise<NotificationEventSetting[]> =>
+  invoke("get_notification_events");
+
+/**
+ * Text an in-house guest the balance on their stay
+ */
+export const sendPaymentReminder = (guestId: number): Promise<NotificationLogEntry> =>
+  invoke("send_payment_reminder", { guestId });
+
+export const sendTestNotification = (phone: string, channel: NotificationChannel): Promise<NotificationLogEntry> =>
+  invoke("send_test_notification", { phone, channel });
+
+export const getNotificationLog = (limit?: number): Promise<NotificationLogEntry[]> =>
+  invoke("get_notification_log", { limit: limit ?? null });
+
+// Rounding APIs
+
+export type TotalRounding = "none" | "0.05" | "1" | "5" | "10";
+
+/**
+ * Set how grand totals are rounded at checkout and on invoices: to the
+ * nearest 0.05, 1, 5 or 10, or not at all
+ */
+export const setTotalRounding = (rule: TotalRounding): Promise<TotalRounding> =>
+  invoke("set_total_rounding", { rule });
+
+export const getTotalRounding = (): Promise<TotalRounding> =>
+  invoke("get_total_rounding");
+
+// Service Charge APIs
+
+/**
+ * Set the service charge added to food orders in a business mode. Orders
+ * already placed keep their rate
+ */
+export const setServiceChargePercent = (
+  businessMode: ServiceChargeSetting["business_mode"],
+  percent: number
+): Promise<ServiceChargeSetting> =>
+  invoke("set_service_charge_percent", { businessMode, percent });
+
+export const getServiceChargeSettings = (): Promise<ServiceChargeSetting[]> =>
+  invoke("get_service_charge_settings");
+
+/**
+ * Service charge on orders placed between two dates (YYYY-MM-DD, inclusive), per day
+ */
+export const getServiceChargeReport = (startDate: string, endDate: string): Promise<ServiceChargeReport> =>
+  invoke("service_charge_report", { startDate, endDate });
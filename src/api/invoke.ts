@@ -0,0 +1,62 @@
+import { invoke as tauriInvoke, type InvokeArgs, type InvokeOptions } from "@tauri-apps/api/core";
+
+/**
+ * Structured error returned by backend commands
+ */
+export interface AppErrorPayload {
+  code: string;
+  message: string;
+  field: string | null;
+}
+
+/**
+ * Error thrown for a failed command. `code` is one of the backend error codes
+ * (see ErrorCodes); `field` names the argument at fault for validation errors.
+ */
+export class ApiError extends Error {
+  code: string;
+  field: string | null;
+
+  constructor(payload: AppErrorPayload) {
+    super(payload.message);
+    this.name = "ApiError";
+    this.code = payload.code;
+    this.field = payload.field;
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
+export const isAppErrorPayload = (error: unknown): error is AppErrorPayload =>
+  typeof error === "object" &&
+  error !== null &&
+  typeof (error as AppErrorPayload).code === "string" &&
+  typeof (error as AppErrorPayload).message === "string";
+
+/**
+ * Adds the signed-in user's session token to plain argument objects that
+ * don't carry one, so the backend can record who made each change. Commands
+ * without a `sessionToken` argument ignore it.
+ */
+const withSessionToken = (args?: InvokeArgs): InvokeArgs | undefined => {
+  const token = localStorage.getItem("bm_session_token");
+  if (!token || (args !== undefined && (Array.isArray(args) || args instanceof ArrayBuffer || args instanceof Uint8Array))) {
+    return args;
+  }
+  const record = (args ?? {}) as Record<string, unknown>;
+  return "sessionToken" in record ? record : { ...record, sessionToken: token };
+};
+
+/**
+ * Drop-in replacement for Tauri's invoke that rethrows structured backend
+ * errors as ApiError, so `err.message` and `String(err)` stay readable.
+ */
+export async function invoke<T>(cmd: string, args?: InvokeArgs, options?: InvokeOptions): Promise<T> {
+  try {
+    return await tauriInvoke<T>(cmd, withSessionToken(args), options);
+  } catch (error) {
+    throw isAppErrorPayload(error) ? new ApiError(error) : error;
+  }
+}
@@ -1,5 +1,5 @@
-import React, { useState } from 'react';
-import { addExpense, type NewExpense } from '../api/client';
+import React, { useEffect, useState } from 'react';
+import { addExpense, addExpenseCategory, getExpenseCategories, type NewExpense } from '../api/client';
 import { useCurrency } from '../context/CurrencyContext';
 import { useNotification } from '../context/NotificationContext';
 import { useTheme } from '../context/ThemeContext';
@@ -25,14 +25,21 @@ const AddExpense: React.FC<AddExpenseProps> = ({ onBack, onExpenseAdded }) => {
   const [loading, setLoading] = useState(false);
   const [showCustomCategory, setShowCustomCategory] = useState(false);
 
-  // Predefined categories as per requirements
-  const categories = [
-    'Groceries',
-    'Maintenance',
-    'Salaries',
-    'Utility Bills',
-    'Others'
-  ];
+  // Managed categories from the backend; "Others" adds a new one
+  const [categories, setCategories] = useState<string[]>(['Groceries', 'Others']);
+
+  const loadCategories = async () => {
+    try {
+      const managed = await getExpenseCategories();
+      setCategories([...managed.map(c => c.name), 'Others']);
+    } catch (err) {
+      console.error('Failed to load expense categories:', err);
+    }
+  };
+
+  useEffect(() => {
+    loadCategories();
+  }, []);
 
   const handleInputChange = (field: string, value: string) => {
     setFormData(prev => ({ ...prev, [field]: value }));
@@ -83,9 +90,12 @@ const AddExpense: React.FC<AddExpenseProps> = ({ onBack, onExpenseAdded }) => {
     setLoading(true);
     
     try {
-      const finalCategory = formData.category === 'Others' 
-        ? formData.customCategory 
-        : formData.category;
+      let finalCategory = formData.category;
+      if (formData.category === 'Others') {
+        const custom = formData.customCategory.trim();
+        const existing = categories.find(c => c !== 'Others' && c.toLowerCase() === custom.toLowerCase());
+        finalCategory = existing ?? (await addExpenseCategory(custom)).name;
+      }
       
       const expenseData: NewExpense = {
         date: formData.date,
@@ -110,6 +120,7 @@ const AddExpense: React.FC<AddExpenseProps> = ({ onBack, onExpenseAdded }) => {
         amount: ''
       });
       setShowCustomCategory(false);
+      loadCategories();
       
       onExpenseAdded();
       
@@ -2,7 +2,9 @@ import React, { useEffect, useState } from 'react';
 import {
     addSale,
     getActiveCustomers,
+    getKitchenPrinter,
     getMenuItems,
+    printKitchenTicket,
     printOrderReceipt,
     toggleSalePayment,
     type ActiveCustomerRow,
@@ -36,6 +38,7 @@ const AddSale: React.FC<AddSaleProps> = ({ onBack, onSaleAdded }) => {
   const [activeGuests, setActiveGuests] = useState<ActiveCustomerRow[]>([]);
   const [menuItems, setMenuItems] = useState<MenuItem[]>([]);
   const [orderItems, setOrderItems] = useState<OrderItemWithDetails[]>([]);
+  const [orderNotes, setOrderNotes] = useState('');
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [showSuccessModal, setShowSuccessModal] = useState(false);
@@ -162,11 +165,17 @@ const AddSale: React.FC<AddSaleProps> = ({ onBack, onSaleAdded }) => {
           item_name: item.menu_item.name,
           quantity: item.quantity,
           unit_price: item.unit_price
-        }))
+        })),
+        notes: orderNotes.trim() || undefined
       };
 
       const orderId = await addSale(newOrder);
       console.log('✅ Sale added successfully:', orderId);
+
+      // With a kitchen printer set up, the ticket goes to the kitchen as soon as the order is placed
+      getKitchenPrinter()
+        .then((printer) => (printer ? printKitchenTicket(orderId) : undefined))
+        .catch((err) => showWarning('Kitchen Ticket Not Printed', `${err}`));
       
       const customerInfo = customerType === 'walkin' 
         ? walkinCustomerName 
@@ -182,6 +191,7 @@ const AddSale: React.FC<AddSaleProps> = ({ onBack, onSaleAdded }) => {
       
       // Reset form
       setOrderItems([]);
+      setOrderNotes('');
       setSelectedGuestId(0);
       setWalkinCustomerName('Walk-in');
       setCustomerType('active');
@@ -442,6 +452,20 @@ const AddSale: React.FC<AddSaleProps> = ({ onBack, onSaleAdded }) => {
               )}
             </div>
 
+            {/* Kitchen notes */}
+            <div style={{ marginTop: '14px' }}>
+              <div style={{ fontSize: '12px', fontWeight: 700, color: colors.textSecondary, marginBottom: '8px' }}>
+                Kitchen notes
+              </div>
+              <textarea
+                value={orderNotes}
+                onChange={(e) => setOrderNotes(e.target.value)}
+                placeholder="e.g. no onions, serve together (optional)"
+                className="bc-input"
+                style={{ minHeight: 60, resize: 'vertical' }}
+              />
+            </div>
+
             {/* Total */}
             <div style={{ marginTop: '14px', borderTop: `1px solid ${colors.border}`, paddingTop: '12px' }}>
               <div style={{ display: 'flex', justifyContent: 'space-between', gap: '12px' }}>
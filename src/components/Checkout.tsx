@@ -7,12 +7,15 @@ import {
     getMenuItems,
     getSaleDetails,
     getSalesByCustomer,
+    getPricesIncludeTax,
+    getTotalRounding,
     getTaxEnabled,
-    getTaxRate,
+    getTaxes,
     toggleSalePayment,
     type ActiveCustomerRow,
     type MenuItem,
-    type NewSale
+    type NewSale,
+    type Tax
 } from '../api/client';
 import { useCurrency } from '../context/CurrencyContext';
 import { useLabels } from '../context/LabelContext';
@@ -66,7 +69,10 @@ const Checkout: React.FC<CheckoutProps> = ({ guest, onBack, onCheckoutComplete }
     
     // Tax states
     const [taxEnabled, setTaxEnabled] = useState(false);
-    const [taxRate, setTaxRate] = useState(0);
+    const [taxes, setTaxes] = useState<Tax[]>([]);
+    const [pricesIncludeTax, setPricesIncludeTax] = useState(false);
+    const [roundingStep, setRoundingStep] = useState(0);
+    const [rounding, setRounding] = useState(0);
     
     // UI states
     const [showAddFood, setShowAddFood] = useState(false);
@@ -84,16 +90,21 @@ const Checkout: React.FC<CheckoutProps> = ({ guest, onBack, onCheckoutComplete }
             
                         // Load tax settings
             try {
-                const [taxEnabledResult, taxRateResult] = await Promise.all([
+                const [taxEnabledResult, taxesResult, includedResult, roundingResult] = await Promise.all([
                     getTaxEnabled(),
-                    getTaxRate()
+                    getTaxes(),
+                    getPricesIncludeTax(),
+                    getTotalRounding()
                 ]);
                 setTaxEnabled(taxEnabledResult);
-                setTaxRate(taxRateResult);
+                setTaxes(taxesResult.filter(tax => tax.is_active && tax.percent > 0));
+                setPricesIncludeTax(includedResult);
+                setRoundingStep(roundingResult === 'none' ? 0 : Number(roundingResult));
             } catch (err) {
                 console.error('Failed to load tax settings:', err);
                 setTaxEnabled(false);
-                setTaxRate(0);
+                setTaxes([]);
+                setPricesIncludeTax(false);
             }
             
             // Load detailed order information for each order
@@ -151,6 +162,29 @@ const Checkout: React.FC<CheckoutProps> = ({ guest, onBack, onCheckoutComplete }
         }
     }, [guest.check_in, guest.check_out, guest.daily_rate, guest.guest_id, showError]);
 
+    // Each tax on its share of the discounted bill, as the checkout charges it.
+    // With tax-inclusive prices the tax is taken back out of each part instead.
+    const taxLines = useCallback((afterDiscount: number, foodTotal: number) => {
+        if (!taxEnabled) return [];
+        const beforeDiscount = roomCharges + foodTotal;
+        const factor = beforeDiscount > 0 ? afterDiscount / beforeDiscount : 0;
+        const netOf = (amount: number, part: 'rooms' | 'food') => {
+            if (!pricesIncludeTax) return amount;
+            const combined = taxes
+                .filter(tax => tax.applies_to === part || tax.applies_to === 'all')
+                .reduce((sum, tax) => sum + tax.percent, 0);
+            return (amount * 100) / (100 + combined);
+        };
+        const rooms = netOf(roomCharges * factor, 'rooms');
+        const food = netOf(foodTotal * factor, 'food');
+        return taxes.map(tax => {
+            const base = tax.applies_to === 'rooms' ? rooms
+                : tax.applies_to === 'food' ? food
+                : rooms + food;
+            return { tax, amount: (base * tax.percent) / 100 };
+        });
+    }, [pricesIncludeTax, roomCharges, taxEnabled, taxes]);
+
     const calculateTotals = useCallback(() => {
         // Calculate unpaid food orders total
         const unpaidTotal = foodOrders
@@ -176,13 +210,18 @@ const Checkout: React.FC<CheckoutProps> = ({ guest, onBack, onCheckoutComplete }
         const afterDiscount = Math.max(0, subtotal - discountAmount);
         
         // Calculate tax amount and final total
-        let finalTotal = afterDiscount;
-        if (taxEnabled && taxRate > 0) {
-            finalTotal = afterDiscount * (1 + taxRate / 100);
-        }
+        const taxTotal = pricesIncludeTax
+            ? 0
+            : taxLines(afterDiscount, unpaidTotal).reduce((sum, line) => sum + line.amount, 0);
         
-        setGrandTotal(finalTotal);
-    }, [discount.amount, discount.type, foodOrders, roomCharges, taxEnabled, taxRate]);
+        // Rounded to the nearest step of the rounding setting, as the checkout does
+        const total = afterDiscount + taxTotal;
+        const cents = Math.round(total * 100);
+        const stepCents = Math.round(roundingStep * 100);
+        const rounded = stepCents > 0 ? (Math.round(cents / stepCents) * stepCents) / 100 : total;
+        setRounding(stepCents > 0 ? rounded - cents / 100 : 0);
+        setGrandTotal(rounded);
+    }, [discount.amount, discount.type, foodOrders, pricesIncludeTax, roomCharges, roundingStep, taxLines]);
 
     // Load data on mount / when guest changes
     useEffect(() => {
@@ -742,8 +781,15 @@ const Checkout: React.FC<CheckoutProps> = ({ guest, onBack, onCheckoutComplete }
                             </div>
                         )}
                         
-                        {taxEnabled && taxRate > 0 && (
-                            <div style={{ 
+                        {taxLines(
+                            Math.max(0, roomCharges + unpaidFoodTotal - (discount.amount > 0
+                                ? (discount.type === 'percentage'
+                                    ? ((roomCharges + unpaidFoodTotal) * discount.amount) / 100
+                                    : discount.amount)
+                                : 0)),
+                            unpaidFoodTotal
+                        ).map(({ tax, amount }) => (
+                            <div key={tax.id} style={{ 
                                 display: 'flex', 
                                 justifyContent: 'space-between', 
                                 marginBottom: '0.5rem',
@@ -751,14 +797,21 @@ const Checkout: React.FC<CheckoutProps> = ({ guest, onBack, onCheckoutComplete }
                                 borderBottom: `1px solid ${colors.border}`,
                                 color: colors.accent
                             }}>
-                                <span>Tax ({taxRate}%):</span>
-                                <span>
-                                    {formatMoney(((roomCharges + unpaidFoodTotal - (discount.amount > 0 
-                                        ? (discount.type === 'percentage' 
-                                            ? ((roomCharges + unpaidFoodTotal) * discount.amount) / 100
-                                            : discount.amount)
-                                        : 0)) * taxRate) / 100)}
-                                </span>
+                                <span>{pricesIncludeTax ? 'Includes ' : ''}{tax.name} ({tax.percent}%):</span>
+                                <span>{formatMoney(amount)}</span>
+                            </div>
+                        ))}
+                        
+                        {rounding !== 0 && (
+                            <div style={{ 
+                                display: 'flex', 
+                                justifyContent: 'space-between', 
+                                marginBottom: '0.5rem',
+                                paddingBottom: '0.5rem',
+                                borderBottom: `1px solid ${colors.border}`
+                            }}>
+                                <span>Rounding:</span>
+                                <span>{formatMoney(rounding)}</span>
                             </div>
                         )}
                         
@@ -822,8 +875,8 @@ const Checkout: React.FC<CheckoutProps> = ({ guest, onBack, onCheckoutComplete }
                     }}>
                         <strong>Note:</strong> All orders will appear on the receipt. 
                         Only unpaid orders are included in the total amount.
-                        {taxEnabled && (
-                            <><br/><strong>Tax:</strong> {taxRate}% tax is applied to the final total.</>
+                        {taxEnabled && taxes.length > 0 && (
+                            <><br/><strong>Tax:</strong> {taxes.map(tax => `${tax.name} ${tax.percent}% (${tax.applies_to})`).join(', ')} {pricesIncludeTax ? 'included in the prices' : 'applied after any discount'}.</>
                         )}
                         <br/><strong>Tip:</strong> Configure tax settings in Manage Catalog Resources → Settings tab.
                     </div>
@@ -1,6 +1,7 @@
 import React, { useEffect, useState } from 'react';
 import * as XLSX from 'xlsx';
 import {
+  budgetVsActual,
   getCustomers,
   getExpensesByDateRange,
   getSales,
@@ -242,8 +243,19 @@ const FinancialReport: React.FC<FinancialReportProps> = ({ onBack }) => {
         formatMoney(week.profit)
       ]);
 
+      // Budget vs actual per expense category
+      const budget = await budgetVsActual(parseInt(selectedYear), parseInt(selectedMonth));
+      const budgetHeader = [[''], ['Budget vs Actual'], ['Category', 'Budget', 'Actual', 'Variance']];
+      const budgetRows = budget.lines.map(line => [
+        line.category,
+        typeof line.budget === 'number' ? formatMoney(line.budget) : 'No budget',
+        formatMoney(line.actual),
+        typeof line.variance === 'number' ? formatMoney(line.variance) : ''
+      ]);
+      const budgetTotal = [['Total', formatMoney(budget.total_budget), formatMoney(budget.total_actual), formatMoney(budget.total_variance)]];
+
       // Combine all data
-      const allData = [...summaryData, ...weeklyHeader, ...weeklyRows];
+      const allData = [...summaryData, ...weeklyHeader, ...weeklyRows, ...budgetHeader, ...budgetRows, ...budgetTotal];
 
       // Create worksheet
       const ws = XLSX.utils.aoa_to_sheet(allData);
@@ -1,14 +1,9 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../api/invoke';
+import type { LowStockItem } from '../api/client';
+import { listen } from '@tauri-apps/api/event';
 import React, { useEffect, useState } from 'react';
 import { useTheme } from '../context/ThemeContext';
 
-interface LowStockItem {
-  id: number;
-  name: string;
-  stock_quantity: number;
-  low_stock_limit: number;
-}
-
 const LowStockAlert: React.FC = () => {
   const { theme, colors } = useTheme();
   const [lowStockItems, setLowStockItems] = useState<LowStockItem[]>([]);
@@ -27,9 +22,13 @@ const LowStockAlert: React.FC = () => {
 
   useEffect(() => {
     fetchLowStockItems();
-    // Refresh every 5 minutes
+    // Refresh every 5 minutes, and straight away when an order runs an ingredient low
     const interval = setInterval(fetchLowStockItems, 300000);
-    return () => clearInterval(interval);
+    const unlisten = listen<LowStockItem[]>('stock-low', fetchLowStockItems);
+    return () => {
+      clearInterval(interval);
+      unlisten.then((stop) => stop());
+    };
   }, []);
 
   if (loading) {
@@ -89,7 +88,7 @@ const LowStockAlert: React.FC = () => {
       <div style={{ marginTop: '0.75rem' }}>
         {lowStockItems.map((item) => (
           <div
-            key={item.id}
+            key={`${item.kind}-${item.id}`}
             style={{
               background: isDark ? 'rgba(255, 193, 7, 0.1)' : '#fffaed',
               border: `1px solid ${isDark ? 'rgba(255, 193, 7, 0.3)' : '#ffe8a1'}`,
@@ -117,13 +116,13 @@ const LowStockAlert: React.FC = () => {
                   color: isDark ? 'rgba(255, 255, 255, 0.7)' : '#666',
                 }}
               >
-                Minimum required: {item.low_stock_limit}
+                Minimum required: {item.low_stock_limit}{item.unit ? ` ${item.unit}` : ''}
               </div>
             </div>
             <div
               style={{
                 background:
-                  item.stock_quantity === 0
+                  item.stock_quantity <= 0
                     ? '#dc3545'
                     : item.stock_quantity <= item.low_stock_limit / 2
                     ? '#fd7e14'
@@ -136,7 +135,7 @@ const LowStockAlert: React.FC = () => {
                 textAlign: 'center',
               }}
             >
-              {item.stock_quantity === 0 ? 'OUT' : `${item.stock_quantity} left`}
+              {item.stock_quantity <= 0 ? 'OUT' : `${item.stock_quantity}${item.unit ? ` ${item.unit}` : ''} left`}
             </div>
           </div>
         ))}
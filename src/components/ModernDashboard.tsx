@@ -1,4 +1,6 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../api/invoke';
+import { ORDER_CREATED_EVENT, type LowStockItem, type OrderCreatedEvent } from '../api/client';
+import { listen } from '@tauri-apps/api/event';
 import React, { useEffect, useState } from 'react';
 import { useAuth } from '../context/AuthContext';
 import { useCurrency } from '../context/CurrencyContext';
@@ -20,13 +22,6 @@ interface SaleSummary {
   guest_name: string | null;
 }
 
-interface LowStockItem {
-  id: number;
-  name: string;
-  stock_quantity: number;
-  low_stock_limit: number;
-}
-
 const ModernDashboard: React.FC = () => {
   const { logout, userRole, adminId } = useAuth();
   const { colors, theme } = useTheme();
@@ -41,6 +36,25 @@ const ModernDashboard: React.FC = () => {
     loadBusinessName();
   }, []);
 
+  useEffect(() => {
+    const unlisten = listen<LowStockItem[]>('stock-low', () => {
+      invoke<LowStockItem[]>('get_low_stock_items').then(setLowStockItems).catch(console.error);
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  // Orders placed from another window show up without a reload
+  useEffect(() => {
+    const unlisten = listen<OrderCreatedEvent>(ORDER_CREATED_EVENT, () => {
+      loadDashboardData();
+    });
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
   const loadDashboardData = async () => {
     try {
       // Used for "Recent Activity" and today's revenue/order counts.
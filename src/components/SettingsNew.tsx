@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../api/invoke';
 import { open } from '@tauri-apps/plugin-dialog';
 import React, { useEffect, useState } from 'react';
 import { useCurrency } from '../context/CurrencyContext';
@@ -39,6 +39,10 @@ const Settings: React.FC = () => {
   const [receiptFooter, setReceiptFooter] = useState<string>('');
   const [isSavingReceiptHeader, setIsSavingReceiptHeader] = useState(false);
   const [isSavingReceiptFooter, setIsSavingReceiptFooter] = useState(false);
+  const [receiptPrinter, setReceiptPrinter] = useState<string>('');
+  const [isSavingReceiptPrinter, setIsSavingReceiptPrinter] = useState(false);
+  const [kitchenPrinter, setKitchenPrinter] = useState<string>('');
+  const [isSavingKitchenPrinter, setIsSavingKitchenPrinter] = useState(false);
 
   useEffect(() => {
     setPendingLocale(locale);
@@ -47,12 +51,14 @@ const Settings: React.FC = () => {
   useEffect(() => {
     const loadBranding = async () => {
       try {
-        const [logoPath, logoDataUrl, savedPrimary, savedHeader, savedFooter] = await Promise.all([
+        const [logoPath, logoDataUrl, savedPrimary, savedHeader, savedFooter, savedPrinter, savedKitchenPrinter] = await Promise.all([
           invoke<string | null>('get_business_logo_path'),
           invoke<string | null>('get_business_logo_data_url'),
           invoke<string | null>('get_primary_color'),
           invoke<string | null>('get_receipt_header'),
-          invoke<string | null>('get_receipt_footer')
+          invoke<string | null>('get_receipt_footer'),
+          invoke<string | null>('get_receipt_printer'),
+          invoke<string | null>('get_kitchen_printer')
         ]);
 
         if (logoPath) setBusinessLogoPath(logoPath);
@@ -63,6 +69,8 @@ const Settings: React.FC = () => {
         }
         setReceiptHeader(savedHeader ?? '');
         setReceiptFooter(savedFooter ?? '');
+        setReceiptPrinter(savedPrinter ?? '');
+        setKitchenPrinter(savedKitchenPrinter ?? '');
       } catch (error) {
         // Branding is optional; don't block Settings if unavailable.
         console.warn('Branding settings not available:', error);
@@ -129,6 +137,32 @@ const Settings: React.FC = () => {
     }
   };
 
+  const saveReceiptPrinter = async () => {
+    setIsSavingReceiptPrinter(true);
+    try {
+      await invoke('set_receipt_printer', { name: receiptPrinter });
+      showSuccess('Saved', receiptPrinter.trim() ? 'Receipt printer saved successfully' : 'Receipts will print to the default printer');
+    } catch (error) {
+      console.error('Failed to save receipt printer:', error);
+      showError('Save Failed', `${error}`);
+    } finally {
+      setIsSavingReceiptPrinter(false);
+    }
+  };
+
+  const saveKitchenPrinter = async () => {
+    setIsSavingKitchenPrinter(true);
+    try {
+      await invoke('set_kitchen_printer', { name: kitchenPrinter });
+      showSuccess('Saved', kitchenPrinter.trim() ? 'Kitchen printer saved successfully' : 'Kitchen tickets will no longer print automatically');
+    } catch (error) {
+      console.error('Failed to save kitchen printer:', error);
+      showError('Save Failed', `${error}`);
+    } finally {
+      setIsSavingKitchenPrinter(false);
+    }
+  };
+
   const handlePrimaryColorChange = async (hex: string) => {
     setPrimaryColorState(hex);
     try {
@@ -524,6 +558,36 @@ const Settings: React.FC = () => {
                 </button>
               </div>
             </div>
+
+            <div>
+              <div style={{ fontSize: 13, fontWeight: 700, marginBottom: 8, color: 'var(--app-text-secondary)' }}>Receipt Printer</div>
+              <input
+                value={receiptPrinter}
+                onChange={(e) => setReceiptPrinter(e.target.value)}
+                placeholder="Printer name for silent printing (blank = system default)"
+                className="bc-input"
+              />
+              <div style={{ marginTop: 10 }}>
+                <button className="bc-btn bc-btn-primary" onClick={saveReceiptPrinter} type="button" disabled={isSavingReceiptPrinter}>
+                  {isSavingReceiptPrinter ? 'Saving…' : 'Save Printer'}
+                </button>
+              </div>
+            </div>
+
+            <div>
+              <div style={{ fontSize: 13, fontWeight: 700, marginBottom: 8, color: 'var(--app-text-secondary)' }}>Kitchen Printer</div>
+              <input
+                value={kitchenPrinter}
+                onChange={(e) => setKitchenPrinter(e.target.value)}
+                placeholder="Printer for kitchen tickets (blank = don't print them automatically)"
+                className="bc-input"
+              />
+              <div style={{ marginTop: 10 }}>
+                <button className="bc-btn bc-btn-primary" onClick={saveKitchenPrinter} type="button" disabled={isSavingKitchenPrinter}>
+                  {isSavingKitchenPrinter ? 'Saving…' : 'Save Printer'}
+                </button>
+              </div>
+            </div>
           </div>
         </div>
       )}
@@ -1,5 +1,5 @@
 /* eslint-disable react-refresh/only-export-components */
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../api/invoke';
 import React, { createContext, useContext, useEffect, useMemo, useState } from 'react';
 
 type CurrencyCode = string;
@@ -1,4 +1,4 @@
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../api/invoke';
 
 const authDebugEnabled = (): boolean => {
   return Boolean(import.meta.env.DEV) && String(import.meta.env.VITE_AUTH_DEBUG) === '1';